@@ -0,0 +1,60 @@
+extern crate amp;
+#[macro_use]
+extern crate criterion;
+
+use amp::{OpenModeIndex, OpenModeIndexChange};
+use criterion::Criterion;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const DIRECTORY_COUNT: usize = 1_000;
+const FILES_PER_DIRECTORY: usize = 100;
+
+// Builds (or reuses) a directory holding 100k files.
+fn build_tree() -> PathBuf {
+    let root = env::temp_dir().join("amp_open_mode_index_bench");
+    let last_file = root
+        .join(format!("directory_{}", DIRECTORY_COUNT - 1))
+        .join(format!("file_{}.rs", FILES_PER_DIRECTORY - 1));
+    if last_file.exists() {
+        return root;
+    }
+
+    for directory in 0..DIRECTORY_COUNT {
+        let directory_path = root.join(format!("directory_{}", directory));
+        fs::create_dir_all(&directory_path).unwrap();
+        for file in 0..FILES_PER_DIRECTORY {
+            fs::write(directory_path.join(format!("file_{}.rs", file)), "").unwrap();
+        }
+    }
+
+    root
+}
+
+fn full_indexing(c: &mut Criterion) {
+    let root = build_tree();
+
+    c.bench_function("full indexing", move |b| b.iter(|| {
+        let mut index = OpenModeIndex::new(root.clone(), None);
+        index.populate();
+        index
+    }));
+}
+
+fn incremental_indexing(c: &mut Criterion) {
+    let root = build_tree();
+    let mut index = OpenModeIndex::new(root.clone(), None);
+    index.populate();
+
+    let path = root.join("directory_500").join("added.rs");
+    fs::write(&path, "").unwrap();
+
+    c.bench_function("incremental indexing", move |b| b.iter(|| {
+        index.apply(&OpenModeIndexChange::Added(path.clone()));
+        index.apply(&OpenModeIndexChange::Removed(path.clone()));
+    }));
+}
+
+criterion_group!(benches, full_indexing, incremental_indexing);
+criterion_main!(benches);
@@ -1,11 +1,20 @@
 use crate::errors::*;
-use crate::commands::{self, Result};
-use crate::input::KeyMap;
+use crate::commands::{self, Command, Result};
+use crate::input::{Key, KeyMap};
 use scribe::Buffer;
+use scribe::buffer::{Position, Range};
+use std::iter;
 use std::mem;
-use crate::models::application::{Application, Mode};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use crate::models::application::{Application, ClipboardContent, Mode, Preferences, Session};
 use crate::models::application::modes::*;
 use crate::util;
+use crate::util::spell::Dictionary;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Keeps mistyped counts (e.g. "99999j") from tying up the editor.
+const MAX_COUNT: usize = 10_000;
 
 pub fn handle_input(app: &mut Application) -> Result {
     // Listen for and respond to user input.
@@ -15,27 +24,223 @@ pub fn handle_input(app: &mut Application) -> Result {
         })
     });
 
-    if let Some(coms) = commands {
-        // Run all commands, stopping at the first error encountered, if any.
-        for com in coms {
-            com(app)?;
+    // Keys are recorded even if their commands fail, but the
+    // key that stops a recording isn't part of the macro.
+    let recording = app.macros.is_recording();
+    let key = app.view.last_key().clone();
+
+    // Track the keys making up the last change (see LastChange for
+    // which sequences are captured), unless we're replaying them.
+    // Keys following a count continue the sequence that it started.
+    let tracking = !app.last_change.repeating;
+    if let Some(ref key) = key {
+        if tracking {
+            let normal_mode = is_normal_mode(app) && app.pending_count.is_none();
+            let change_count = current_change_count(app);
+            app.last_change.start_key(key.clone(), normal_mode, change_count);
+        }
+    }
+
+    let counting = match key {
+        Some(ref key) => accumulate_count(app, key, commands.is_some()),
+        None => false,
+    };
+
+    record_insert_start(app);
+
+    // A selected register only applies to the command following it.
+    let register_selected = app.clipboard.register_selected();
+
+    let mut result = Ok(());
+    if !counting {
+        // Escape cancels a pending count, rather than being repeated.
+        let count = app.pending_count.take();
+        let cancelled = count.is_some() && key == Some(Key::Esc);
+        if let Some(coms) = commands.filter(|_| !cancelled) {
+            // Run all commands, stopping at the first error encountered, if any.
+            result = run_counted_commands(app, &coms, count.unwrap_or(1));
+        }
+
+        // Copies and pastes use (and clear) the register themselves. Other
+        // commands drop it, unless they're still waiting on input to finish.
+        if register_selected && !awaiting_input(app) {
+            app.clipboard.clear_register();
+        }
+    }
+
+    if let Some(key) = key {
+        if recording && app.macros.is_recording() {
+            app.macros.record(key);
+        }
+    }
+
+    if tracking && !counting {
+        let normal_mode = is_normal_mode(app);
+        let change_count = current_change_count(app);
+        app.last_change.finish_key(normal_mode, change_count);
+    }
+
+    result
+}
+
+/// Adds a digit typed in normal mode to the pending count, returning whether
+/// it was one. Bound digits aren't counted, unless they continue a count
+/// (e.g. the zero in "10j", since "0" on its own switches to command mode).
+fn accumulate_count(app: &mut Application, key: &Key, bound: bool) -> bool {
+    let digit = match *key {
+        Key::Char(c) if is_normal_mode(app) => c.to_digit(10),
+        _ => None,
+    };
+
+    match (digit, app.pending_count) {
+        (Some(digit), Some(count)) => {
+            app.pending_count = Some((count * 10 + digit as usize).min(MAX_COUNT));
+            true
+        }
+        (Some(digit), None) if digit > 0 && !bound => {
+            app.pending_count = Some(digit as usize);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Runs the commands bound to a key the specified number of times, stopping
+/// at the first error. Commands that leave normal mode (e.g. switching to
+/// insert mode) are only run once; operators keep the count, applying it
+/// to their motion. Keys pressed once in operator mode leave its count alone.
+/// Repeated changes are grouped, so that they're undone in a single step.
+fn run_counted_commands(app: &mut Application, commands: &[Command], count: usize) -> Result {
+    let grouped = count > 1 && match app.workspace.current_buffer() {
+        Some(buffer) => app.view.hold_undo_group(buffer).is_ok(),
+        None => false,
+    };
+
+    let result = repeat_commands(app, commands, count);
+
+    if grouped {
+        // Commands that leave normal mode end the group when returning to it.
+        let normal_mode = is_normal_mode(app);
+        if let Some(buffer) = app.workspace.current_buffer() {
+            app.view.release_undo_group(buffer)?;
+            if normal_mode {
+                app.view.end_undo_group(buffer)?;
+            }
+        }
+    }
+
+    result
+}
+
+fn repeat_commands(app: &mut Application, commands: &[Command], count: usize) -> Result {
+    let started_in_normal_mode = is_normal_mode(app);
+    for _ in 0..count {
+        for command in commands {
+            command(app)?;
+        }
+
+        if !is_normal_mode(app) {
+            if let Mode::Operator(ref mut mode) = app.mode {
+                if started_in_normal_mode {
+                    mode.count = count;
+                }
+            }
+            break;
         }
     }
 
     Ok(())
 }
 
+/// Repeats the last change made to a buffer by replaying the
+/// keys that made it, starting from the current cursor position.
+pub fn repeat_last_change(app: &mut Application) -> Result {
+    let keys = app.last_change.keys().to_vec();
+    if keys.is_empty() {
+        bail!("No change to repeat");
+    }
+
+    app.last_change.repeating = true;
+    let result = keys.into_iter().try_for_each(|key| {
+        app.view.last_key = Some(key);
+        handle_input(app)
+    });
+    app.last_change.repeating = false;
+
+    // The repeat key itself isn't a change that can be repeated.
+    app.last_change.discard_pending();
+
+    result
+}
+
+fn is_normal_mode(app: &Application) -> bool {
+    if let Mode::Normal = app.mode { true } else { false }
+}
+
+/// Whether the app is waiting on input to complete the last command, such as
+/// the motion of an operator or the confirmation of a prompt.
+fn awaiting_input(app: &Application) -> bool {
+    match app.mode {
+        Mode::Operator(_) | Mode::Confirm(_) => true,
+        _ => false,
+    }
+}
+
+/// Records where insert mode started if it was entered without doing so
+/// (e.g. when a new file is opened in insert mode), using the cursor
+/// position as of the first key typed in it.
+fn record_insert_start(app: &mut Application) {
+    let insert_mode = match app.mode {
+        Mode::Insert(ref mut mode) => mode,
+        Mode::Completion(ref mut mode) => &mut mode.insert_mode,
+        Mode::Snippet(ref mut mode) => &mut mode.insert_mode,
+        _ => return,
+    };
+    if insert_mode.start.is_none() {
+        if let Some(buffer) = app.workspace.current_buffer() {
+            insert_mode.start = Some(*buffer.cursor.clone());
+        }
+    }
+}
+
+fn current_change_count(app: &mut Application) -> usize {
+    match app.workspace.current_buffer() {
+        Some(buffer) => app.view.change_count(buffer),
+        None => 0,
+    }
+}
+
 pub fn switch_to_normal_mode(app: &mut Application) -> Result {
     let _ = commands::buffer::end_command_group(app);
+
+    // Discard any theme being previewed.
+    if let Mode::Theme(ref mode) = app.mode {
+        app.view.set_theme(mode.original_theme());
+    }
+
+    // Return to the selection that a search was confined to.
+    if let Mode::Search(ref mut mode) = app.mode {
+        if let Some(selection) = mode.selection.take() {
+            if let Some(buffer) = app.workspace.current_buffer() {
+                buffer.cursor.move_to(selection.cursor);
+            }
+            app.mode = *selection.mode;
+
+            return Ok(());
+        }
+    }
     app.mode = Mode::Normal;
 
     Ok(())
 }
 
 pub fn switch_to_insert_mode(app: &mut Application) -> Result {
-    if app.workspace.current_buffer().is_some() {
+    util::ensure_writable(app)?;
+    if let Some(buffer) = app.workspace.current_buffer() {
+        let mut insert_mode = InsertMode::new();
+        insert_mode.start = Some(*buffer.cursor.clone());
         commands::buffer::start_command_group(app)?;
-        app.mode = Mode::Insert;
+        app.mode = Mode::Insert(insert_mode);
         commands::view::scroll_to_cursor(app)?;
     } else {
         bail!(BUFFER_MISSING);
@@ -44,12 +249,25 @@ pub fn switch_to_insert_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_completion_mode(app: &mut Application) -> Result {
+    if let Mode::Insert(_) = app.mode {
+        if !commands::completion::show_completions(app)? {
+            bail!("No completions found for the word before the cursor");
+        }
+    } else {
+        bail!("Completions can only be listed from insert mode");
+    }
+
+    Ok(())
+}
+
 pub fn switch_to_jump_mode(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     // Initialize a new jump mode and swap
     // it with the current application mode.
-    let jump_mode = Mode::Jump(JumpMode::new(buffer.cursor.line));
+    let labels = app.preferences.borrow().jump_labels().to_string();
+    let jump_mode = Mode::Jump(JumpMode::new(buffer.cursor.line, &labels));
     let old_mode = mem::replace(&mut app.mode, jump_mode);
 
     // If we were previously in a select mode, store it
@@ -84,6 +302,19 @@ pub fn switch_to_second_stage_jump_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Switches to a jump mode that only targets the start of each visible line.
+pub fn switch_to_line_start_jump_mode(app: &mut Application) -> Result {
+    switch_to_jump_mode(app)?;
+    if let Mode::Jump(ref mut mode) = app.mode {
+        mode.first_phase = false;
+        mode.line_starts = true;
+    } else {
+        bail!("Failed to switch to jump mode.");
+    };
+
+    Ok(())
+}
+
 pub fn switch_to_line_jump_mode(app: &mut Application) -> Result {
     if app.workspace.current_buffer().is_some() {
         app.mode = Mode::LineJump(LineJumpMode::new());
@@ -94,6 +325,70 @@ pub fn switch_to_line_jump_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_delete_operator_mode(app: &mut Application) -> Result {
+    switch_to_operator_mode(app, Operator::Delete)
+}
+
+pub fn switch_to_change_operator_mode(app: &mut Application) -> Result {
+    switch_to_operator_mode(app, Operator::Change)
+}
+
+fn switch_to_operator_mode(app: &mut Application, operator: Operator) -> Result {
+    util::ensure_writable(app)?;
+    if app.workspace.current_buffer().is_some() {
+        app.mode = Mode::Operator(OperatorMode::new(operator));
+    } else {
+        bail!(BUFFER_MISSING);
+    }
+
+    Ok(())
+}
+
+pub fn switch_to_shell_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Shell(ShellMode::new());
+
+    Ok(())
+}
+
+/// Prompts for a shell command through which to filter the selection.
+pub fn switch_to_filter_mode(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let selection = match app.mode {
+        Mode::Select(ref mode) => Range::new(*buffer.cursor.clone(), mode.anchor),
+        Mode::SelectLine(ref mode) => mode.to_range(&*buffer.cursor),
+        _ => bail!("Can't filter a selection outside of select modes"),
+    };
+    app.mode = Mode::Shell(ShellMode::filter(selection));
+
+    Ok(())
+}
+
+/// Prompts for a shell command whose output is inserted at the cursor.
+pub fn switch_to_read_command_mode(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    if app.workspace.current_buffer().is_none() {
+        bail!(BUFFER_MISSING);
+    }
+    app.mode = Mode::Shell(ShellMode::read());
+
+    Ok(())
+}
+
+pub fn switch_to_recent_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Recent(RecentMode::new());
+
+    Ok(())
+}
+
+pub fn switch_to_recent_files_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::RecentFiles(RecentFilesMode::new(app.recent_files.paths(), config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
 pub fn switch_to_open_mode(app: &mut Application) -> Result {
     let exclusions = app.preferences.borrow().open_mode_exclusions()?;
     let config = app.preferences.borrow().search_select_config();
@@ -103,9 +398,30 @@ pub fn switch_to_open_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Searches the workspace's files for the most recent search query or,
+/// failing that, the identifier under the cursor, listing matching lines.
+pub fn switch_to_workspace_search_mode(app: &mut Application) -> Result {
+    let query = match app.search_query {
+        Some(ref query) => query.clone(),
+        None => app.workspace
+            .current_buffer()
+            .and_then(|buffer| util::token::identifier_at_cursor(buffer))
+            .ok_or("No search query or identifier under the cursor to search for")?,
+    };
+    let exclusions = app.preferences.borrow().open_mode_exclusions()?;
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::WorkspaceSearch(
+        WorkspaceSearchMode::new(app.workspace.path.clone(), query, exclusions, app.event_channel.clone(), config)
+    );
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
 pub fn switch_to_command_mode(app: &mut Application) -> Result {
     let config = app.preferences.borrow().search_select_config();
-    app.mode = Mode::Command(CommandMode::new(config));
+    app.mode = Mode::Command(CommandMode::new(config, app.command_history.recent()));
+    app.command_history.reset();
     commands::search_select::search(app)?;
 
     Ok(())
@@ -117,7 +433,9 @@ pub fn switch_to_symbol_jump_mode(app: &mut Application) -> Result {
             .chain_err(|| "No tokens available for the current buffer")?;
         let config = app.preferences.borrow().search_select_config();
 
-        app.mode = Mode::SymbolJump(SymbolJumpMode::new(&token_set, config));
+        let revision = app.view.revision(buf);
+
+        app.mode = Mode::SymbolJump(SymbolJumpMode::new(&token_set, revision, config));
     } else {
         bail!(BUFFER_MISSING);
     }
@@ -128,12 +446,10 @@ pub fn switch_to_symbol_jump_mode(app: &mut Application) -> Result {
 
 pub fn switch_to_theme_mode(app: &mut Application) -> Result {
     let config = app.preferences.borrow().search_select_config();
-    app.mode = Mode::Theme(
-        ThemeMode::new(
-            app.view.theme_set.themes.keys().map(|k| k.to_string()).collect(),
-            config
-        ),
-    );
+    let original_theme = app.preferences.borrow().theme().to_string();
+    let mut themes: Vec<String> = app.view.theme_set.themes.keys().map(|k| k.to_string()).collect();
+    themes.sort();
+    app.mode = Mode::Theme(ThemeMode::new(themes, original_theme, config));
     commands::search_select::search(app)?;
 
     Ok(())
@@ -159,11 +475,22 @@ pub fn switch_to_select_line_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_select_block_mode(app: &mut Application) -> Result {
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.mode = Mode::SelectBlock(SelectBlockMode::new(*buffer.cursor.clone()));
+    } else {
+        bail!(BUFFER_MISSING);
+    }
+
+    Ok(())
+}
+
 pub fn switch_to_search_mode(app: &mut Application) -> Result {
     if app.workspace.current_buffer().is_some() {
         app.mode = Mode::Search(
-            SearchMode::new(app.search_query.clone())
+            SearchMode::new(app.search_query.clone(), app.preferences.borrow().search_config())
         );
+        app.search_history.reset();
     } else {
         bail!(BUFFER_MISSING);
     }
@@ -171,6 +498,165 @@ pub fn switch_to_search_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Like switch_to_search_mode, but steps through results
+/// towards the start of the buffer.
+pub fn switch_to_reverse_search_mode(app: &mut Application) -> Result {
+    switch_to_search_mode(app)?;
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.direction = SearchDirection::Backward;
+    }
+
+    Ok(())
+}
+
+/// Starts a search confined to the selection, which
+/// is restored once the search is left.
+pub fn switch_to_search_in_selection_mode(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let range = match app.mode {
+        Mode::Select(ref mode) => Range::new(*buffer.cursor.clone(), mode.anchor),
+        Mode::SelectLine(ref mode) => mode.to_range(&*buffer.cursor),
+        _ => bail!("Can't search a selection outside of select modes"),
+    };
+    let cursor = *buffer.cursor.clone();
+
+    let mut search_mode = SearchMode::new(app.search_query.clone(), app.preferences.borrow().search_config());
+    search_mode.selection = Some(SearchSelection {
+        range,
+        mode: Box::new(mem::replace(&mut app.mode, Mode::Normal)),
+        cursor,
+    });
+    app.mode = Mode::Search(search_mode);
+    app.search_history.reset();
+
+    Ok(())
+}
+
+pub fn switch_to_replace_mode(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    if app.workspace.current_buffer().is_some() {
+        app.mode = Mode::Replace(
+            ReplaceMode::new(app.search_query.clone(), app.preferences.borrow().search_config())
+        );
+    } else {
+        bail!(BUFFER_MISSING);
+    }
+
+    Ok(())
+}
+
+/// Waits for a register name, which is used by the next copy or paste.
+pub fn switch_to_register_mode(app: &mut Application) -> Result {
+    let previous = mem::replace(&mut app.mode, Mode::Normal);
+    app.mode = Mode::Register(RegisterMode::new(previous));
+
+    Ok(())
+}
+
+/// Selects the typed register and returns to the mode register mode was
+/// entered from. Any other key (e.g. escape) returns without selecting one.
+pub fn select_register(app: &mut Application) -> Result {
+    match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Register(mode) => app.mode = *mode.previous,
+        mode => {
+            app.mode = mode;
+            bail!("Can't select a register outside of register mode");
+        }
+    }
+
+    if let Some(Key::Char(name)) = *app.view.last_key() {
+        app.clipboard.select_register(name)?;
+    }
+
+    Ok(())
+}
+
+/// Waits for a pane command (e.g. splitting or closing the focused pane).
+pub fn switch_to_pane_mode(app: &mut Application) -> Result {
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.mode = Mode::Pane;
+
+    Ok(())
+}
+
+pub fn switch_to_set_mark_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Mark(MarkMode::new(MarkAction::Set));
+
+    Ok(())
+}
+
+pub fn switch_to_jump_to_mark_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Mark(MarkMode::new(MarkAction::Jump));
+
+    Ok(())
+}
+
+/// Sets or jumps to the mark with the typed name, returning to normal
+/// mode first. Keys other than letters (e.g. escape) cancel.
+pub fn select_mark(app: &mut Application) -> Result {
+    let action = match app.mode {
+        Mode::Mark(ref mode) => mode.action,
+        _ => bail!("Can't select a mark outside of mark mode"),
+    };
+    app.mode = Mode::Normal;
+
+    let name = match *app.view.last_key() {
+        Some(Key::Char(name)) if name.is_ascii_alphabetic() => name,
+        _ => return Ok(()),
+    };
+
+    match action {
+        MarkAction::Set => {
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            app.marks.set(name, buffer)?;
+            app.message = Some(format!("Set mark \"{}\"", name));
+
+            Ok(())
+        }
+        MarkAction::Jump => jump_to_mark(app, name),
+    }
+}
+
+/// Returns to the location most recently jumped away from using a mark.
+pub fn jump_back(app: &mut Application) -> Result {
+    let (id, position) = app.marks.pop_jump().ok_or("No previous location to jump back to")?;
+    if !util::select_buffer_by_id(id, app) {
+        bail!("The previous location's buffer has been closed");
+    }
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    util::move_cursor_within_bounds(buffer, position);
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Moves the cursor to the named mark, switching buffers for global marks
+/// and recording the current location so that it can be jumped back to.
+fn jump_to_mark(app: &mut Application, name: char) -> Result {
+    let (original_id, original_position) = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        (buffer.id.ok_or("Buffer ID doesn't exist")?, *buffer.cursor)
+    };
+
+    if name.is_ascii_uppercase() {
+        let id = app.marks
+            .global_buffer_id(name)
+            .ok_or_else(|| format!("Mark \"{}\" hasn't been set", name))?;
+        if !util::select_buffer_by_id(id, app) {
+            bail!("Mark \"{}\" was set in a buffer that has been closed", name);
+        }
+    }
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = match app.marks.position(name, buffer) {
+        Some(position) => position,
+        None => bail!("Mark \"{}\" hasn't been set", name),
+    };
+    util::move_cursor_within_bounds(buffer, position);
+    app.marks.push_jump(original_id, original_position);
+
+    commands::view::scroll_to_cursor(app)
+}
+
 pub fn switch_to_path_mode(app: &mut Application) -> Result {
     let path = app.workspace
         .current_buffer()
@@ -208,6 +694,163 @@ pub fn switch_to_syntax_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Lists suggested corrections for the misspelled word at the cursor.
+pub fn switch_to_spelling_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    let (range, word) = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let data = buffer.data();
+        let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+        let (offset, word) = util::spell::word_at(line, buffer.cursor.offset)
+            .ok_or("No word at the cursor")?;
+        let start = Position{ line: buffer.cursor.line, offset };
+        let end = Position{ line: buffer.cursor.line, offset: offset + word.graphemes(true).count() };
+
+        (Range::new(start, end), word)
+    };
+
+    // Suggestions don't require spell checking to be enabled.
+    let loaded_dictionary;
+    let dictionary = match app.view.dictionary() {
+        Some(dictionary) => dictionary,
+        None => {
+            let path = app.preferences.borrow().spell_dictionary();
+            loaded_dictionary = Dictionary::load(&path)?;
+            &loaded_dictionary
+        }
+    };
+    if dictionary.contains(&word) {
+        bail!("\"{}\" is spelled correctly", word);
+    }
+    let suggestions = dictionary.suggestions(&word, config.max_results);
+    if suggestions.is_empty() {
+        bail!("No suggestions found for \"{}\"", word);
+    }
+
+    app.mode = Mode::Spelling(SpellingMode::new(range, suggestions, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Lists encodings that the current buffer's file
+/// can be reloaded (and subsequently saved) with.
+pub fn switch_to_encoding_mode(app: &mut Application) -> Result {
+    // We'll need a file to reload, so check before entering encoding mode.
+    let _ = app.workspace
+        .current_buffer()
+        .ok_or("Switching encodings requires an open buffer")?
+        .path
+        .as_ref()
+        .ok_or(BUFFER_PATH_MISSING)?;
+
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Encoding(
+        EncodingMode::new(
+            util::encoding::ENCODINGS.iter().map(|name| name.to_string()).collect(),
+            config
+        ),
+    );
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Opens the path under the cursor (e.g. in an include statement or compiler
+/// output), resolving it relative to the current buffer's directory and then
+/// the workspace. A trailing ":line" or ":line:column" suffix is honoured.
+pub fn open_path_under_cursor(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let argument = util::token::path_at_cursor(buffer).ok_or("No path found under the cursor")?;
+    let bases: Vec<PathBuf> = buffer.path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(|directory| directory.to_path_buf())
+        .into_iter()
+        .chain(iter::once(app.workspace.path.clone()))
+        .collect();
+    let (path, position) = util::resolve_path_argument(&argument, &bases)
+        .ok_or_else(|| format!("Couldn't find a file at \"{}\"", argument))?;
+
+    if !util::select_buffer(&path, app) {
+        util::open_buffer(&path, app)?;
+    }
+    if let Mode::LargeFile(ref mut mode) = app.mode {
+        if let Some(position) = position {
+            mode.move_to_line(position.line);
+        }
+        return Ok(());
+    }
+    if let Some(position) = position {
+        util::move_cursor_within_bounds(app.workspace.current_buffer().ok_or(BUFFER_MISSING)?, position);
+    }
+    app.track_recent_path();
+
+    commands::view::scroll_to_cursor(app)
+}
+
+pub fn switch_to_buffer_list_mode(app: &mut Application) -> Result {
+    let original_id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    let workspace_path = app.workspace.path.clone();
+    let mut buffers = Vec::new();
+
+    // Collect all open buffers, starting with the one after the current
+    // buffer, so that the most likely destination is selected by default.
+    loop {
+        app.workspace.next_buffer();
+        if let Some(buffer) = app.workspace.current_buffer() {
+            if let Some(id) = buffer.id {
+                let path = buffer.path.as_ref().map(|path| util::display_path(path, &workspace_path));
+                buffers.push(BufferEntry::new(id, path.as_ref().map(|p| p.as_str()), buffer.modified()));
+            }
+        }
+
+        if app.workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+            break;
+        }
+    }
+
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::BufferList(BufferListMode::new(buffers, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_bookmark_mode(app: &mut Application) -> Result {
+    let original_id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    let workspace_path = app.workspace.path.clone();
+    let mut bookmarks = Vec::new();
+
+    // Collect bookmarks from all open buffers, starting with the current one.
+    loop {
+        if let Some(buffer) = app.workspace.current_buffer() {
+            let path = buffer.path
+                .as_ref()
+                .map(|path| util::display_path(path, &workspace_path))
+                .unwrap_or_else(|| String::from("untitled"));
+            if let (Some(id), Ok(lines)) = (buffer.id, app.view.bookmarks(buffer)) {
+                let data = buffer.data();
+                for line in lines {
+                    let content = data.lines().nth(line).unwrap_or("");
+                    bookmarks.push(Bookmark::new(id, &path, line, content));
+                }
+            }
+        }
+
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+            break;
+        }
+    }
+
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Bookmark(BookmarkMode::new(bookmarks, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
 pub fn display_default_keymap(app: &mut Application) -> Result {
     commands::workspace::new_buffer(app)?;
 
@@ -244,6 +887,33 @@ pub fn display_available_commands(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn display_registers(app: &mut Application) -> Result {
+    let listing = app.clipboard
+        .registers()
+        .iter()
+        .map(|(name, content)| {
+            let (style, data) = match content {
+                ClipboardContent::Inline(data) => ("inline", data),
+                ClipboardContent::Block(data) => ("block", data),
+                ClipboardContent::Rectangle(data) => ("rectangle", data),
+                ClipboardContent::None => return String::new(),
+            };
+
+            format!("\"{} ({}): {}\n", name, style, data.replace('\n', "\\n"))
+        })
+        .collect::<String>();
+    if listing.is_empty() {
+        bail!("No registers have been used");
+    }
+
+    commands::workspace::new_buffer(app)?;
+    if let Some(buffer) = app.workspace.current_buffer() {
+        buffer.insert(listing);
+    }
+
+    Ok(())
+}
+
 pub fn display_last_error(app: &mut Application) -> Result {
     let error = app.error.take().ok_or("No error to display")?;
     let scope_display_buffer = {
@@ -271,18 +941,368 @@ pub fn suspend(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Opens the files recorded in the workspace's last session, restoring their
+/// cursor positions and bookmarks. Files that have since been deleted are skipped.
+pub fn restore_session(app: &mut Application) -> Result {
+    let session_path = Preferences::session_path(&app.workspace.path)?;
+    let session = Session::load(&session_path)?;
+    let mut skipped = 0;
+
+    for entry in session.entries {
+        if !entry.path.exists() {
+            skipped += 1;
+            continue;
+        }
+
+        util::open_buffer(&entry.path, app)?;
+
+        // Files that have since grown too large to load aren't restored.
+        if let Mode::LargeFile(_) = app.mode {
+            continue;
+        }
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        buffer.cursor.move_to(entry.cursor);
+        app.view.restore_bookmarks(buffer, &entry.bookmarks)?;
+    }
+    switch_to_normal_mode(app)?;
+
+    if skipped > 0 {
+        bail!("Skipped {} file(s) from the last session that no longer exist", skipped);
+    }
+
+    Ok(())
+}
+
+/// Exits the application. If any buffers have unsaved changes, a confirmation
+/// prompt is displayed first. Otherwise, when quit confirmation is enabled,
+/// this has to be run twice in quick succession for it to take effect.
 pub fn exit(app: &mut Application) -> Result {
+    let modified_count = modified_buffer_count(app);
+    if modified_count > 0 {
+        let prompt = if modified_count == 1 {
+            String::from("1 buffer has unsaved changes; quit anyway?")
+        } else {
+            format!("{} buffers have unsaved changes; quit anyway?", modified_count)
+        };
+        app.mode = Mode::Confirm(ConfirmMode::with_choices(prompt, vec![
+            ConfirmChoice::new('y', "Quit, discarding changes", force_exit),
+            ConfirmChoice::new('s', "Save all and quit", save_all_and_exit),
+            ConfirmChoice::new('n', "Cancel", switch_to_normal_mode),
+        ], Some(2)));
+
+        return Ok(());
+    }
+
+    let (confirm_quit, timeout) = {
+        let preferences = app.preferences.borrow();
+        (preferences.confirm_quit(), Duration::from_millis(preferences.confirm_quit_timeout()))
+    };
+
+    if confirm_quit {
+        let confirmed = app.quit_requested_at
+            .map(|time| time.elapsed() <= timeout)
+            .unwrap_or(false);
+        if !confirmed {
+            app.quit_requested_at = Some(Instant::now());
+            bail!("Press again to quit");
+        }
+    }
+
+    app.mode = Mode::Exit;
+
+    Ok(())
+}
+
+/// Saves all buffers with unsaved changes and exits. Buffers without a
+/// path prevent exiting, as they'd need one to be specified first.
+pub fn save_all_and_exit(app: &mut Application) -> Result {
+    let original_id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+
+    loop {
+        let modified = app.workspace.current_buffer()
+            .map(|buffer| buffer.modified() && !buffer.data().is_empty())
+            .unwrap_or(false);
+        if modified {
+            if app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.path.is_none() {
+                bail!("A buffer without a path has unsaved changes");
+            }
+            commands::buffer::save(app)?;
+        }
+
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+            break;
+        }
+    }
+    app.mode = Mode::Exit;
+
+    Ok(())
+}
+
+/// Exits the application immediately, discarding any unsaved changes.
+pub fn force_exit(app: &mut Application) -> Result {
     app.mode = Mode::Exit;
 
     Ok(())
 }
 
+// Counts buffers with unsaved changes, cycling through the workspace and back
+// to the original buffer. New, empty buffers don't have anything to lose.
+fn modified_buffer_count(app: &mut Application) -> usize {
+    let original_id = match app.workspace.current_buffer() {
+        Some(buffer) => buffer.id,
+        None => return 0,
+    };
+    let mut count = 0;
+
+    loop {
+        if let Some(buffer) = app.workspace.current_buffer() {
+            if buffer.modified() && !buffer.data().is_empty() {
+                count += 1;
+            }
+        }
+
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+            break;
+        }
+    }
+
+    count
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::commands;
+    use crate::input::Key;
     use crate::models::Application;
-    use crate::models::application::Mode;
+    use crate::models::application::{ClipboardContent, Mode, Preferences};
+    use crate::models::application::modes::{InsertMode, SearchSelectMode};
+    use crate::util;
     use scribe::Buffer;
+    use scribe::buffer::{Position, Range};
     use std::path::PathBuf;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn exit_requires_a_second_press_when_confirm_quit_is_enabled() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("confirm_quit: true").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        assert!(super::exit(&mut app).is_err());
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+
+        super::exit(&mut app).unwrap();
+        assert!(match app.mode { Mode::Exit => true, _ => false });
+    }
+
+    #[test]
+    fn symbol_jump_mode_rebuilds_its_symbols_once_the_buffer_changes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("symbols.rs"));
+        buffer.insert("fn amp() {}");
+        util::add_buffer(buffer, &mut app).unwrap();
+        super::switch_to_symbol_jump_mode(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        buffer.insert("\n");
+        let revision = app.view.revision(buffer);
+        match app.mode {
+            Mode::SymbolJump(ref mut mode) => {
+                assert_eq!(mode.selection().map(|symbol| symbol.position.line), Some(0));
+                mode.refresh(buffer, revision).unwrap();
+                assert_eq!(mode.selection().map(|symbol| symbol.position.line), Some(1));
+            }
+            _ => panic!("Application isn't in symbol jump mode."),
+        }
+    }
+
+    #[test]
+    fn handle_input_records_the_insert_start_for_insert_modes_entered_without_one() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("backspace: [indent, eol]").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        // Opening an empty file in insert mode skips switch_to_insert_mode.
+        app.mode = Mode::Insert(InsertMode::default());
+        app.view.last_key = Some(Key::Backspace);
+        super::handle_input(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp");
+    }
+
+    #[test]
+    fn repeat_last_change_replays_the_last_changing_key_sequence() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        // Delete a character, then move without changing anything.
+        for key in vec![Key::Char('x'), Key::Char('l')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        super::repeat_last_change(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "m editor");
+
+        // Insert mode sessions are repeated as a whole.
+        for key in vec![Key::Char('i'), Key::Char('a'), Key::Char('b'), Key::Esc] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        app.view.last_key = Some(Key::Char('.'));
+        super::handle_input(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "mabab editor");
+        assert_eq!(app.last_change.keys(), &[Key::Char('i'), Key::Char('a'), Key::Char('b'), Key::Esc]);
+    }
+
+    #[test]
+    fn handle_input_refuses_select_mode_deletions_in_read_only_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        util::add_buffer(buffer, &mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        app.view.set_read_only(buffer, true).unwrap();
+
+        for key in vec![Key::Char('v'), Key::Char('l'), Key::Char('l')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        app.view.last_key = Some(Key::Char('d'));
+        assert!(super::handle_input(&mut app).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp editor");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 2 });
+        assert!(match app.mode { Mode::Select(_) => true, _ => false });
+
+        // Nothing was changed, so there's nothing to redo once it's writable.
+        commands::buffer::toggle_read_only(&mut app).unwrap();
+        commands::buffer::redo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp editor");
+    }
+
+    #[test]
+    fn handle_input_repeats_commands_by_a_count_prefix() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor amp editor");
+        util::add_buffer(buffer, &mut app).unwrap();
+        let press = |app: &mut Application, keys: &str| for c in keys.chars() {
+            app.view.last_key = Some(Key::Char(c));
+            super::handle_input(app).unwrap();
+        };
+
+        press(&mut app, "3x");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), " editor amp editor");
+        assert_eq!(app.pending_count, None);
+
+        // Counts can be repeated along with the change they were applied to.
+        press(&mut app, ".");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "itor amp editor");
+
+        // Zeroes continue a count, and escape cancels one.
+        press(&mut app, "10");
+        assert_eq!(app.pending_count, Some(10));
+        app.view.last_key = Some(Key::Esc);
+        super::handle_input(&mut app).unwrap();
+        assert_eq!(app.pending_count, None);
+
+        // Commands that leave normal mode are only run once.
+        press(&mut app, "2ia");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "aitor amp editor");
+    }
+
+    #[test]
+    fn handle_input_undoes_counted_commands_in_a_single_step() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        util::add_buffer(buffer, &mut app).unwrap();
+        let press = |app: &mut Application, keys: &str| for c in keys.chars() {
+            app.view.last_key = Some(Key::Char(c));
+            super::handle_input(app).unwrap();
+        };
+
+        press(&mut app, "3x");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), " editor");
+
+        commands::buffer::undo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp editor");
+
+        // Changes made after the count are undone separately.
+        press(&mut app, "2xx");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), " editor");
+        commands::buffer::undo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "p editor");
+    }
+
+    #[test]
+    fn repeat_last_change_fails_without_a_change() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::add_buffer(Buffer::new(), &mut app).unwrap();
+
+        assert!(super::repeat_last_change(&mut app).is_err());
+    }
+
+    #[test]
+    fn exit_prompts_for_confirmation_when_a_buffer_has_unsaved_changes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+        app.workspace.add_buffer(Buffer::new());
+
+        super::exit(&mut app).unwrap();
+        assert!(match app.mode {
+            Mode::Confirm(ref mode) => mode.prompt == "1 buffer has unsaved changes; quit anyway?",
+            _ => false
+        });
+
+        commands::confirm::confirm_command(&mut app).unwrap();
+        assert!(match app.mode { Mode::Exit => true, _ => false });
+    }
+
+    #[test]
+    fn save_all_and_exit_saves_modified_buffers() {
+        let path = concat!(env!("OUT_DIR"), "/save_all_and_exit.txt");
+        std::fs::write(path, "editor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(std::path::Path::new(path), &mut app).unwrap();
+        app.workspace.current_buffer().unwrap().insert("amp ");
+        app.workspace.add_buffer(Buffer::new());
+
+        super::save_all_and_exit(&mut app).unwrap();
+
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "amp editor\n");
+        assert!(match app.mode { Mode::Exit => true, _ => false });
+    }
+
+    #[test]
+    fn exit_skips_confirmation_for_new_empty_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        super::exit(&mut app).unwrap();
+        assert!(match app.mode { Mode::Exit => true, _ => false });
+    }
+
+    #[test]
+    fn force_exit_skips_confirmation_for_unsaved_changes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+
+        super::force_exit(&mut app).unwrap();
+        assert!(match app.mode { Mode::Exit => true, _ => false });
+    }
 
     #[test]
     fn display_available_commands_creates_a_new_buffer() {
@@ -324,6 +1344,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_in_selection_mode_confines_results_and_restores_the_selection() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp amp amp editor");
+        app.workspace.add_buffer(buffer);
+
+        super::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 7 });
+        super::switch_to_search_in_selection_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input = Some(String::from("amp"));
+        }
+        commands::search::run(&mut app).unwrap();
+
+        let result_count = match app.mode {
+            Mode::Search(ref mode) => mode.results.as_ref().map(|r| r.len()),
+            _ => None,
+        };
+        assert_eq!(result_count, Some(2));
+
+        // Leaving the search returns to the original selection.
+        super::switch_to_normal_mode(&mut app).unwrap();
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position{ line: 0, offset: 0 }),
+            _ => panic!("Expected select mode"),
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 7 });
+    }
+
     #[test]
     fn switch_to_path_mode_inserts_workspace_directory_as_default() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -372,4 +1422,208 @@ mod tests {
 
         assert!(super::switch_to_path_mode(&mut app).is_err());
     }
+
+    #[test]
+    fn switch_to_bookmark_mode_lists_bookmarks_from_all_open_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        for content in &["amp\neditor", "bookmarks\n"] {
+            let mut buffer = Buffer::new();
+            buffer.insert(*content);
+            util::add_buffer(buffer, &mut app).unwrap();
+            let buffer = app.workspace.current_buffer().unwrap();
+            buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+            commands::buffer::toggle_bookmark(&mut app).unwrap();
+        }
+
+        super::switch_to_bookmark_mode(&mut app).unwrap();
+        let labels = match app.mode {
+            Mode::Bookmark(ref mode) => mode.results().map(|r| r.to_string()).collect(),
+            _ => Vec::new(),
+        };
+        assert_eq!(labels, vec!["untitled:2", "untitled:2  editor"]);
+    }
+
+    #[test]
+    fn select_register_restores_the_previous_mode_and_uses_the_register() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::application::switch_to_register_mode(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('a'));
+        commands::application::select_register(&mut app).unwrap();
+        assert!(match app.mode { Mode::SelectLine(_) => true, _ => false });
+
+        commands::selection::copy(&mut app).unwrap();
+        assert_eq!(
+            app.clipboard.registers(),
+            vec![('a', &ClipboardContent::Block(String::from("amp\n")))]
+        );
+    }
+
+    #[test]
+    fn handle_input_clears_a_selected_register_unless_the_next_command_uses_it() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        // Moving after selecting the register leaves the copy unnamed.
+        for key in vec![Key::Char('"'), Key::Char('a'), Key::Char('j'), Key::Char('V'), Key::Char('y')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        assert!(app.clipboard.registers().is_empty());
+
+        // Copying right after selecting it uses the register.
+        for key in vec![Key::Char('V'), Key::Char('"'), Key::Char('a'), Key::Char('y')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        assert_eq!(
+            app.clipboard.registers(),
+            vec![('a', &ClipboardContent::Block(String::from("editor")))]
+        );
+    }
+
+    #[test]
+    fn switch_to_buffer_list_mode_lists_open_buffers_starting_with_the_next_one() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(&PathBuf::from("Cargo.toml")).unwrap();
+        util::add_buffer(Buffer::new(), &mut app).unwrap();
+        app.workspace.current_buffer().unwrap().insert("amp");
+
+        super::switch_to_buffer_list_mode(&mut app).unwrap();
+        let labels = match app.mode {
+            Mode::BufferList(ref mode) => mode.results().map(|r| r.to_string()).collect(),
+            _ => Vec::new(),
+        };
+        assert_eq!(labels, vec!["Cargo.toml", "[No Name] [+]"]);
+
+        commands::search_select::accept(&mut app).unwrap();
+        assert!(app.workspace.current_buffer().unwrap().path.as_ref().unwrap().ends_with("Cargo.toml"));
+    }
+
+    #[test]
+    fn switching_buffers_keeps_their_cursor_and_scroll_positions() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(&PathBuf::from("Cargo.toml"), &mut app).unwrap();
+        {
+            let buffer = app.workspace.current_buffer().unwrap();
+            buffer.cursor.move_to(Position{ line: 42, offset: 2 });
+            app.view.scroll_to_cursor(buffer).unwrap();
+        }
+        let visible_lines = {
+            let buffer = app.workspace.current_buffer().unwrap();
+            app.view.visible_lines(buffer).unwrap()
+        };
+        util::add_buffer(Buffer::new(), &mut app).unwrap();
+
+        super::switch_to_buffer_list_mode(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(*buffer.cursor, Position{ line: 42, offset: 2 });
+        assert_eq!(app.view.visible_lines(buffer).unwrap(), visible_lines);
+
+        // Reopening the file (e.g. in open mode) selects the same buffer.
+        util::add_buffer(Buffer::new(), &mut app).unwrap();
+        util::open_buffer(&PathBuf::from("Cargo.toml"), &mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 42, offset: 2 });
+    }
+
+    #[test]
+    fn open_path_under_cursor_opens_the_file_at_the_referenced_position() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("error: see Cargo.toml:1:3");
+        buffer.cursor.move_to(Position{ line: 0, offset: 14 });
+        app.workspace.add_buffer(buffer);
+
+        super::open_path_under_cursor(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert!(buffer.path.as_ref().unwrap().ends_with("Cargo.toml"));
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 2 });
+    }
+
+    #[test]
+    fn open_path_under_cursor_fails_when_the_file_does_not_exist() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("missing.rs");
+        app.workspace.add_buffer(buffer);
+
+        assert!(super::open_path_under_cursor(&mut app).is_err());
+    }
+
+    #[test]
+    fn marks_can_be_set_jumped_to_and_jumped_back_from_across_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut first = Buffer::new();
+        first.insert("amp\neditor");
+        util::add_buffer(first, &mut app).unwrap();
+        let mut second = Buffer::new();
+        second.insert("marks");
+        util::add_buffer(second, &mut app).unwrap();
+
+        // Set a global mark in the second buffer.
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 2 });
+        for key in vec![Key::Char('*'), Key::Char('A')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+
+        // Jump to it from the first buffer.
+        app.workspace.next_buffer();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 1 });
+        for key in vec![Key::Char('`'), Key::Char('A')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "marks");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 2 });
+
+        super::jump_back(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 1 });
+        assert!(super::jump_back(&mut app).is_err());
+    }
+
+    #[test]
+    fn jumping_to_a_mark_past_the_end_of_the_buffer_clamps_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nmarks");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 4 });
+        for key in vec![Key::Char('*'), Key::Char('a')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+
+        // Remove the marked line, along with the line break before it.
+        app.workspace.current_buffer().unwrap().delete_range(
+            Range::new(Position{ line: 1, offset: 6 }, Position{ line: 2, offset: 5 })
+        );
+        for key in vec![Key::Char('`'), Key::Char('a')] {
+            app.view.last_key = Some(key);
+            super::handle_input(&mut app).unwrap();
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+    }
+
+    #[test]
+    fn jumping_to_an_unset_mark_fails() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::add_buffer(Buffer::new(), &mut app).unwrap();
+
+        app.view.last_key = Some(Key::Char('`'));
+        super::handle_input(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('a'));
+        assert!(super::handle_input(&mut app).is_err());
+    }
 }
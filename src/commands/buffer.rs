@@ -1,16 +1,48 @@
 use crate::errors::*;
+use crate::errors;
 use crate::commands::{self, Result};
+use std::cmp;
+use std::fs;
+use std::iter;
 use std::mem;
+use std::path::Path;
+use std::time::Duration;
 use crate::input::Key;
 use crate::util;
-use crate::util::token::{Direction, adjacent_token_position};
-use crate::models::application::{Application, ClipboardContent, Mode};
+use crate::util::grapheme_index::{content_length, index_position, position_index};
+use crate::util::editor_config::EditorConfig;
+use crate::util::encoding::SourceEncoding;
+use crate::util::line_ending::{self, LineEnding, LineEndings};
+use encoding_rs::{Encoding, UTF_8};
+use crate::util::token::{self, Direction, adjacent_token_position};
+use crate::models::application::{Application, ClipboardContent, Mode, Preferences, SecondaryCursors};
 use crate::models::application::modes::ConfirmMode;
 use scribe::buffer::{Buffer, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn save(app: &mut Application) -> Result {
-    remove_trailing_whitespace(app)?;
-    ensure_trailing_newline(app)?;
+    util::ensure_writable(app)?;
+
+    // Re-read EditorConfig settings, in case they (or the buffer's path) changed.
+    let path = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.path.clone();
+    let editor_config = match path {
+        Some(path) => {
+            app.preferences.borrow_mut().load_editor_config(&path);
+            app.preferences.borrow().editor_config(Some(&path)).cloned().unwrap_or_default()
+        }
+        None => EditorConfig::default(),
+    };
+
+    if editor_config.trim_trailing_whitespace != Some(false) {
+        remove_trailing_whitespace(app)?;
+    }
+    if editor_config.insert_final_newline != Some(false) {
+        ensure_trailing_newline(app)?;
+    }
+    if let Some(ending) = editor_config.end_of_line {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        app.view.set_line_endings(buffer, LineEndings { ending, mixed: false })?;
+    }
 
     // Slight duplication here, but we need to check for a buffer path without
     // borrowing the buffer for the full scope of this save command. That will
@@ -23,35 +55,161 @@ pub fn save(app: &mut Application) -> Result {
         .path.is_some();
 
     if path_set {
-        app.workspace
-            .current_buffer()
-            .ok_or(BUFFER_MISSING)?
-            .save()
-            .chain_err(|| "Unable to save buffer")
-    } else {
-        commands::application::switch_to_path_mode(app)?;
-        if let Mode::Path(ref mut mode) = app.mode {
-            mode.save_on_accept = true;
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        if let Some(path) = buffer.path.as_ref().filter(|path| path.is_dir()) {
+            bail!("Can't save to {}, as it's a directory", path.display());
         }
 
-        Ok(())
+        // Encode the content up front, so that the file is left
+        // untouched if it contains characters we can't represent.
+        let data = buffer.data();
+        let converted_data = saved_line_endings(app.view.line_endings(buffer), &data);
+        let encoded_data = match app.view.encoding(buffer) {
+            Some(source_encoding) => Some(util::encoding::encode(
+                converted_data.as_ref().unwrap_or(&data),
+                source_encoding
+            )?),
+            None => converted_data.map(String::into_bytes),
+        };
+        match (encoded_data, buffer.path.clone()) {
+            (Some(encoded_data), Some(path)) => {
+                fs::write(path, encoded_data).chain_err(|| "Unable to save buffer")?;
+                util::encoding::mark_saved(buffer).chain_err(|| "Unable to save buffer")?;
+            }
+            _ => buffer.save().chain_err(|| "Unable to save buffer")?,
+        }
+
+        if let Some(path) = buffer.path.as_ref() {
+            app.file_watcher.record_write(path);
+            util::swap::remove(path);
+        }
+
+        // Git change signs and status are based on the file on disk; recompute them.
+        app.view.clear_git_dirty();
+        app.view.clear_line_changes(buffer)
+    } else {
+        commands::path::save_as(app)
     }
 }
 
 pub fn reload(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.reload().chain_err(|| {
-        "Unable to reload buffer."
-    })
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if let Some(source_encoding) = app.view.encoding(buffer) {
+        return reload_with_encoding(app, source_encoding.encoding);
+    }
+
+    buffer.reload().chain_err(|| "Unable to reload buffer.")?;
+    app.view.set_line_endings(buffer, line_ending::detect(&buffer.data()))?;
+    app.view.clear_line_changes(buffer)
+}
+
+/// Reloads the buffer for the file that a confirmation prompt concerns
+/// (e.g. one changed on disk), leaving the current buffer selected.
+pub fn reload_confirmed(app: &mut Application) -> Result {
+    let path = match app.mode {
+        Mode::Confirm(ref mode) => mode
+            .selection()
+            .and_then(|choice| choice.path.clone())
+            .ok_or("No file to reload")?,
+        _ => bail!("Can't reload a confirmed file outside of confirm mode"),
+    };
+    let original_id = app.workspace.current_buffer().and_then(|buffer| buffer.id);
+    if !util::select_buffer(&path, app) {
+        bail!("{} is no longer open", path.display());
+    }
+
+    let result = reload(app);
+    if let Some(id) = original_id {
+        util::select_buffer_by_id(id, app);
+    }
+
+    result
+}
+
+/// Reloads the buffer, decoding its file using the specified encoding,
+/// which is also used when saving it. Invalid sequences are replaced
+/// with U+FFFD (�), so that they're visible.
+pub fn reload_with_encoding(app: &mut Application, encoding: &'static Encoding) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let bytes = fs::read(&path).chain_err(|| "Unable to reload buffer.")?;
+    let source_encoding = SourceEncoding::forced(encoding, &bytes);
+    let had_errors = util::encoding::reload_buffer(buffer, source_encoding, &bytes)
+        .chain_err(|| "Unable to reload buffer.")?;
+
+    // UTF-8 files without a byte order mark are saved as-is.
+    let tracked_encoding = if encoding == UTF_8 && !source_encoding.bom {
+        None
+    } else {
+        Some(source_encoding)
+    };
+    app.view.set_encoding(buffer, tracked_encoding)?;
+    app.view.set_line_endings(buffer, line_ending::detect(&buffer.data()))?;
+    app.view.clear_line_changes(buffer)?;
+
+    if had_errors {
+        app.message = Some(format!(
+            "Reloaded as {}; invalid sequences were replaced with �",
+            encoding.name()
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn convert_line_endings_to_lf(app: &mut Application) -> Result {
+    convert_line_endings(app, LineEnding::Lf)
+}
+
+pub fn convert_line_endings_to_crlf(app: &mut Application) -> Result {
+    convert_line_endings(app, LineEnding::Crlf)
+}
+
+pub fn convert_line_endings_to_cr(app: &mut Application) -> Result {
+    convert_line_endings(app, LineEnding::Cr)
+}
+
+/// Normalizes all of the buffer's line breaks to the specified ending,
+/// which is then used for any line breaks added before it's saved.
+fn convert_line_endings(app: &mut Application, ending: LineEnding) -> Result {
+    util::ensure_writable(app)?;
+    let data = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.data();
+    let converted_data = line_ending::convert(&data, ending);
+    if converted_data != data {
+        replace_content(app, converted_data)?;
+    }
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.set_line_endings(buffer, LineEndings { ending, mixed: false })
+}
+
+/// The buffer's content with line breaks added using a different ending
+/// (e.g. newlines inserted into a CRLF file) converted to the buffer's
+/// line ending, or None if there's nothing to convert. Only the saved
+/// file is converted; the buffer (and its history) is left alone.
+/// Buffers with mixed line endings are saved as-is.
+fn saved_line_endings(line_endings: Option<LineEndings>, data: &str) -> Option<String> {
+    let ending = match line_endings {
+        Some(LineEndings { ending, mixed: false }) => ending,
+        _ => return None,
+    };
+
+    Some(line_ending::convert(data, ending)).filter(|converted_data| converted_data != data)
 }
 
 pub fn delete(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.delete();
+    util::ensure_writable(app)?;
+    edit_at_each_cursor(app, |buffer| {
+        buffer.delete();
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app)?;
 
     Ok(())
 }
 
 pub fn delete_token(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let mut subsequent_token_on_line = false;
 
     if let Some(buffer) = app.workspace.current_buffer() {
@@ -78,6 +236,7 @@ pub fn delete_token(app: &mut Application) -> Result {
 }
 
 pub fn delete_current_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     commands::application::switch_to_select_line_mode(app)?;
     commands::selection::copy_and_delete(app)?;
     commands::application::switch_to_normal_mode(app)?;
@@ -95,59 +254,124 @@ pub fn copy_current_line(app: &mut Application) -> Result {
     Ok(())
 }
 
-pub fn merge_next_line(app: &mut Application) -> Result {
+/// Adds the current line to the end of the clipboard's content.
+pub fn append_current_line(app: &mut Application) -> Result {
+    commands::application::switch_to_select_line_mode(app)?;
+    commands::selection::append_copy(app)?;
+    commands::application::switch_to_normal_mode(app)?;
+    commands::view::scroll_to_cursor(app)?;
+
+    Ok(())
+}
+
+/// Copies the entire buffer to the clipboard without moving the cursor,
+/// asking for confirmation first if it exceeds the large file threshold.
+pub fn copy_all(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let current_line = buffer.cursor.line;
     let data = buffer.data();
+    let line_count = buffer.line_count();
+
+    let confirmed = if let Mode::Confirm(_) = app.mode { true } else { false };
+    if !confirmed && data.len() as u64 > app.preferences.borrow().large_file_threshold() {
+        app.mode = Mode::Confirm(ConfirmMode::with_prompt(
+            copy_all,
+            format!("Copy {} bytes to the clipboard?", data.len())
+        ));
+        return Ok(());
+    }
+
+    let byte_count = data.len();
+    app.clipboard.set_content(ClipboardContent::Block(data))?;
+    app.message = Some(format!("Copied {} bytes ({} lines)", byte_count, line_count));
+
+    Ok(())
+}
 
-    // Don't bother if there isn't a line below.
-    data.lines().nth(current_line + 1).ok_or("No line below current line")?;
-
-    // Join the two lines.
-    let mut merged_lines: String = buffer.data()
-                                         .lines()
-                                         .enumerate()
-                                         .skip(current_line)
-                                         .take(2)
-                                         .map(|(index, line)| {
-                                             if index == current_line {
-                                                 format!("{} ", line)
-                                             } else {
-                                                 line.trim_start().to_string()
-                                             }
-                                         })
-                                         .collect();
-
-    // Append a newline if there is a line below the next.
-    if buffer.data().lines().nth(current_line + 2).is_some() {
-        merged_lines.push('\n');
-    }
-
-    // Remove the two lines, move to the start of the line,
-    // insert the merged lines, and position the cursor,
-    // batched as a single operation.
-    buffer.start_operation_group();
-    let target_position = Position {
-        line: current_line,
-        offset: data.lines().nth(current_line).unwrap().len(),
+/// Joins the current line with the next one, or all of the lines spanned
+/// by the selection in select modes. Each line break and the following
+/// line's leading whitespace are replaced with a single space, which isn't
+/// doubled up if the preceding line already ends with whitespace.
+pub fn merge_next_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (first_line, last_line) = match app.mode {
+        Mode::Select(ref mode) => {
+            let selection = Range::new(*buffer.cursor.clone(), mode.anchor);
+            (selection.start().line, selection.end().line)
+        }
+        Mode::SelectLine(ref mode) => {
+            (cmp::min(mode.anchor, buffer.cursor.line), cmp::max(mode.anchor, buffer.cursor.line))
+        }
+        _ => (buffer.cursor.line, buffer.cursor.line),
     };
-    buffer.delete_range(Range::new(Position {
-                                       line: current_line,
-                                       offset: 0,
-                                   },
-                                   Position {
-                                       line: current_line + 2,
-                                       offset: 0,
-                                   }));
+
+    // A single line is joined with the one below it, if there is one.
+    let data = buffer.data();
+    let last_line = if first_line == last_line { last_line + 1 } else { last_line };
+    let last_line_length = match data.lines().nth(last_line) {
+        Some(line) => line.graphemes(true).count(),
+        None if first_line + 1 == last_line => return Ok(()),
+        None => bail!("Couldn't find the selection's last line"),
+    };
+
+    let mut joined = String::new();
+    let mut join_offset = 0;
+    for (index, line) in data.lines().enumerate().skip(first_line).take(last_line - first_line + 1) {
+        if index == first_line {
+            joined.push_str(line);
+            continue;
+        }
+
+        join_offset = joined.trim_end().graphemes(true).count();
+        if !joined.is_empty() && !joined.ends_with(char::is_whitespace) {
+            joined.push(' ');
+        }
+        joined.push_str(line.trim_start());
+    }
+
+    // Replace the lines and move to the last join, as a single operation.
+    app.view.start_undo_group(buffer)?;
+    let start = Position{ line: first_line, offset: 0 };
+    buffer.delete_range(Range::new(start, Position{ line: last_line, offset: last_line_length }));
+    buffer.cursor.move_to(start);
+    buffer.insert(joined);
+    buffer.cursor.move_to(Position{ line: first_line, offset: join_offset });
+    app.view.end_undo_group(buffer)?;
+
+    Ok(())
+}
+
+/// Breaks the current line in two at the cursor, without entering insert mode;
+/// the inverse of merge_next_line. Like insert_newline, the new line inherits
+/// the current line's indentation, replacing any whitespace at the cursor.
+pub fn split_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = *buffer.cursor.clone();
+    let data = buffer.data();
+    let current_line = data.lines().nth(position.line).ok_or(CURRENT_LINE_MISSING)?;
+    let indent_content: String = current_line.chars().take_while(|&c| c.is_whitespace()).collect();
+    let trailing_whitespace = current_line
+        .chars()
+        .skip(position.offset)
+        .take_while(|&c| c.is_whitespace())
+        .count();
+
+    // Swap any whitespace at the cursor for a newline
+    // and indentation, batched as a single operation.
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(Range::new(
+        position,
+        Position{ line: position.line, offset: position.offset + trailing_whitespace }
+    ));
+    buffer.insert(format!("\n{}", indent_content));
     buffer.cursor.move_to(Position {
-        line: current_line,
-        offset: 0,
+        line: position.line + 1,
+        offset: indent_content.chars().count(),
     });
-    buffer.insert(merged_lines);
-    buffer.cursor.move_to(target_position);
-    buffer.end_operation_group();
+    app.view.end_undo_group(buffer)?;
 
-    Ok(())
+    commands::view::scroll_to_cursor(app)
 }
 
 pub fn close(app: &mut Application) -> Result {
@@ -166,11 +390,19 @@ pub fn close(app: &mut Application) -> Result {
         };
 
     if unmodified || empty || confirm_mode {
-        // Clean up view-related data for the buffer.
-        app.view.forget_buffer(
-            app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
-        )?;
+        // Clean up view-related data and file watches for the buffer.
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        app.view.forget_buffer(buffer)?;
+        if let Some(ref path) = buffer.path {
+            app.file_watcher.unwatch(path);
+            app.language_servers.close(path);
+            util::swap::remove(path);
+        }
         app.workspace.close_current_buffer();
+
+        if app.workspace.current_buffer().is_none() {
+            handle_last_buffer_close(app)?;
+        }
     } else {
         // Display a confirmation prompt before closing a modified buffer.
         let confirm_mode = ConfirmMode::new(close);
@@ -180,6 +412,22 @@ pub fn close(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Applies the configured behaviour for when the workspace becomes empty.
+fn handle_last_buffer_close(app: &mut Application) -> Result {
+    let action = app.preferences.borrow().on_last_buffer_close().to_string();
+
+    match action.as_str() {
+        // Closing the last buffer is deliberate; skip any quit confirmation.
+        "quit" => {
+            app.mode = Mode::Exit;
+            Ok(())
+        },
+        "scratch" => commands::workspace::new_buffer(app),
+        "greeter" => Ok(()),
+        _ => bail!("Unknown on_last_buffer_close option: \"{}\"", action),
+    }
+}
+
 pub fn close_others(app: &mut Application) -> Result {
     // Get the current buffer's ID so we know what *not* to close.
     let id = app.workspace.current_buffer().map(|b| b.id).ok_or(BUFFER_MISSING)?;
@@ -208,6 +456,11 @@ pub fn close_others(app: &mut Application) -> Result {
                 modified_buffer = true;
             } else {
                 app.view.forget_buffer(buf)?;
+                if let Some(ref path) = buf.path {
+                    app.file_watcher.unwatch(path);
+                    app.language_servers.close(path);
+                    util::swap::remove(path);
+                }
             }
         }
 
@@ -229,6 +482,11 @@ pub fn close_others(app: &mut Application) -> Result {
 pub fn close_others_confirm(app: &mut Application) -> Result {
     if let Some(buf) = app.workspace.current_buffer() {
         app.view.forget_buffer(buf)?;
+        if let Some(ref path) = buf.path {
+            app.file_watcher.unwatch(path);
+            app.language_servers.close(path);
+            util::swap::remove(path);
+        }
     }
     app.workspace.close_current_buffer();
     commands::application::switch_to_normal_mode(app)?;
@@ -236,31 +494,58 @@ pub fn close_others_confirm(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Deletes the character before the cursor. The backspace preference
+/// determines whether this can join the current line with the previous
+/// one ("eol"), outdent whitespace-only lines ("indent"), and delete
+/// text preceding the point where insert mode was entered ("start").
 pub fn backspace(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+
+    // Insert start and indent handling don't carry over to secondary cursors.
+    if !app.secondary_cursors.is_empty() {
+        let join_lines = app.preferences.borrow().backspace("eol");
+        edit_at_each_cursor(app, |buffer| {
+            delete_previous_character(buffer, join_lines);
+            Ok(())
+        })?;
+        return commands::view::scroll_to_cursor(app);
+    }
+
     let mut outdent = false;
+    let preferences = app.preferences.borrow();
+    let insert_start = match app.mode {
+        Mode::Insert(ref mode) => mode.start,
+        _ => None,
+    };
 
     if let Some(buffer) = app.workspace.current_buffer() {
-        if buffer.cursor.offset == 0 {
-            buffer.cursor.move_up();
-            buffer.cursor.move_to_end_of_line();
-            buffer.delete();
+        let position = *buffer.cursor.clone();
+        let before_insert_start = insert_start.map(|start| position <= start).unwrap_or(false);
+        if before_insert_start && !preferences.backspace("start") {
+            return Ok(());
+        }
+
+        if position.offset == 0 {
+            delete_previous_character(buffer, preferences.backspace("eol"));
         } else {
             let data = buffer.data();
             let current_line = data
                 .lines()
                 .nth(buffer.cursor.line)
                 .ok_or(CURRENT_LINE_MISSING)?;
-            if current_line.chars().all(|c| c.is_whitespace()) {
+            if current_line.chars().all(|c| c.is_whitespace()) && preferences.backspace("indent") {
                 outdent = true
             } else {
-                buffer.cursor.move_left();
-                buffer.delete();
+                delete_empty_pair_closer(buffer, current_line, &preferences.auto_pairs());
+                delete_previous_character(buffer, false);
             }
         }
     } else {
         bail!(BUFFER_MISSING);
     }
 
+    drop(preferences);
+
     if outdent {
         commands::buffer::outdent_line(app)?;
     }
@@ -268,22 +553,212 @@ pub fn backspace(app: &mut Application) -> Result {
 }
 
 pub fn insert_char(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let character = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let character = match *app.view.last_key() {
+            Some(Key::Char(character)) => character,
+            _ => bail!("No character to insert"),
+        };
+        if completes_escape_sequence(&mut app.mode, buffer, character, &app.preferences.borrow()) {
+            return commands::application::switch_to_normal_mode(app);
+        }
+
+        character
+    };
+
+    let pairs = app.preferences.borrow().auto_pairs();
+    edit_at_each_cursor(app, |buffer| {
+        insert_paired_character(buffer, character, &pairs);
+        Ok(())
+    })?;
+
     if let Some(buffer) = app.workspace.current_buffer() {
-        if let Some(Key::Char(character)) = *app.view.last_key() {
-            // TODO: Drop explicit call to to_string().
-            buffer.insert(character.to_string());
-            buffer.cursor.move_right();
-        } else {
-            bail!("No character to insert");
+        if let Mode::Insert(ref mut mode) = app.mode {
+            mode.record_insert(*buffer.cursor);
         }
-    } else {
-        bail!(BUFFER_MISSING);
     }
     commands::view::scroll_to_cursor(app)?;
 
     Ok(())
 }
 
+/// Runs an edit at the buffer's cursor and then at each secondary cursor,
+/// shifting the cursors that follow each edit by the content it added or
+/// removed. Overlapping cursors are merged once all edits have been made.
+pub fn edit_at_each_cursor<F>(app: &mut Application, mut edit: F) -> Result
+    where F: FnMut(&mut Buffer) -> Result
+{
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if app.secondary_cursors.is_empty() {
+        return edit(buffer);
+    }
+
+    // Insert mode already groups its changes for undo purposes.
+    let grouped = !matches!(app.mode, Mode::Insert(_));
+    if grouped {
+        app.view.start_undo_group(buffer)?;
+    }
+
+    let result = edit_at_cursors(buffer, &mut app.secondary_cursors, edit);
+
+    // Failed edits still end the group, leaving the buffer editable.
+    if grouped {
+        app.view.end_undo_group(buffer)?;
+    }
+
+    result
+}
+
+/// Runs the edit at the buffer's cursor and each secondary cursor (see
+/// edit_at_each_cursor), reading the buffer's content once per edit.
+fn edit_at_cursors<F>(buffer: &mut Buffer, secondary_cursors: &mut SecondaryCursors, mut edit: F) -> Result
+    where F: FnMut(&mut Buffer) -> Result
+{
+    let mut data = buffer.data();
+    let mut length = content_length(&data) as isize;
+    let mut indices: Vec<usize> = iter::once(&*buffer.cursor)
+        .chain(secondary_cursors.positions())
+        .map(|position| position_index(&data, position))
+        .collect();
+
+    for cursor in 0..indices.len() {
+        let index = indices[cursor];
+        buffer.cursor.move_to(index_position(&data, index));
+        edit(buffer)?;
+
+        // The edited content is where the next cursor's edit starts from.
+        data = buffer.data();
+        let edited_length = content_length(&data) as isize;
+        let delta = edited_length - length;
+        length = edited_length;
+        indices[cursor] = position_index(&data, &buffer.cursor);
+        for (other, other_index) in indices.iter_mut().enumerate() {
+            if other != cursor && *other_index > index {
+                *other_index = (*other_index as isize + delta).max(index as isize) as usize;
+            }
+        }
+    }
+
+    let primary = index_position(&data, indices[0]);
+    buffer.cursor.move_to(primary);
+    secondary_cursors.replace(
+        indices[1..].iter().map(|&index| index_position(&data, index)).collect(),
+        &primary
+    );
+
+    Ok(())
+}
+
+/// Deletes the character before the cursor, joining
+/// it with the previous line if it's at the start of one.
+fn delete_previous_character(buffer: &mut Buffer, join_lines: bool) {
+    if buffer.cursor.offset == 0 {
+        // There's nothing to join at the start of the buffer.
+        if buffer.cursor.line == 0 || !join_lines {
+            return;
+        }
+
+        buffer.cursor.move_up();
+        buffer.cursor.move_to_end_of_line();
+    } else {
+        buffer.cursor.move_left();
+    }
+    buffer.delete();
+}
+
+/// Inserts the character at the cursor, moving past it. Typing the closing
+/// character of an auto pair that's already at the cursor steps over it,
+/// and typing an opening character adds its closer, unless the cursor
+/// precedes a word. Quotes aren't paired after a word (e.g. in prose
+/// contractions), or within strings and comments.
+fn insert_paired_character(buffer: &mut Buffer, character: char, pairs: &[(char, char)]) {
+    let data = buffer.data();
+    let line = data.split('\n').nth(buffer.cursor.line).unwrap_or("");
+    let (previous, next) = adjacent_characters(line, buffer.cursor.offset);
+    let is_closer = |c: char| pairs.iter().any(|&(_, closer)| closer == c);
+
+    if next == Some(character) && is_closer(character) {
+        buffer.cursor.move_right();
+        return;
+    }
+
+    let closer = pairs
+        .iter()
+        .find(|&&(opener, _)| opener == character)
+        .map(|&(_, closer)| closer)
+        .filter(|_| next.map(|c| c.is_whitespace() || is_closer(c)).unwrap_or(true))
+        .filter(|&closer| {
+            closer != character ||
+                !(previous.map(|c| c.is_alphanumeric()).unwrap_or(false) || in_string_or_comment(buffer))
+        });
+    match closer {
+        Some(closer) => buffer.insert(format!("{}{}", character, closer)),
+        None => buffer.insert(character.to_string()),
+    }
+    buffer.cursor.move_right();
+}
+
+/// Removes the character following the cursor if it closes an empty
+/// auto pair with the preceding one, so that backspace removes both.
+fn delete_empty_pair_closer(buffer: &mut Buffer, line: &str, pairs: &[(char, char)]) {
+    if let (Some(previous), Some(next)) = adjacent_characters(line, buffer.cursor.offset) {
+        if pairs.contains(&(previous, next)) {
+            buffer.delete();
+        }
+    }
+}
+
+/// The characters before and after the offset on the line.
+fn adjacent_characters(line: &str, offset: usize) -> (Option<char>, Option<char>) {
+    let graphemes: Vec<&str> = line.graphemes(true).collect();
+    let character = |index: usize| graphemes.get(index).and_then(|grapheme| grapheme.chars().next());
+
+    (offset.checked_sub(1).and_then(|index| character(index)), character(offset))
+}
+
+/// Whether the cursor is within a string or comment, according to
+/// the buffer's syntax (and never, if its content can't be lexed).
+fn in_string_or_comment(buffer: &Buffer) -> bool {
+    let position = *buffer.cursor;
+    buffer.tokens()
+        .map(|tokens| {
+            token::string_and_comment_ranges(tokens.iter())
+                .take_while(|range| range.start() < position)
+                .any(|range| position < range.end())
+        })
+        .unwrap_or(false)
+}
+
+/// Checks the character against the configured insert mode escape sequence.
+/// When it completes the sequence, the sequence's previously inserted
+/// characters are removed from the buffer and true is returned.
+fn completes_escape_sequence(mode: &mut Mode, buffer: &mut Buffer, character: char, preferences: &Preferences) -> bool {
+    let sequence = match preferences.insert_escape_sequence() {
+        Some(sequence) => sequence,
+        None => return false,
+    };
+    let timeout = Duration::from_millis(preferences.insert_escape_timeout());
+
+    if let Mode::Insert(ref mut insert_mode) = *mode {
+        if insert_mode.track_escape_sequence(character, &sequence, timeout, *buffer.cursor) {
+            let end = *buffer.cursor;
+            let inserted_count = sequence.chars().count() - 1;
+            let start = Position {
+                line: end.line,
+                offset: end.offset.saturating_sub(inserted_count),
+            };
+            buffer.delete_range(Range::new(start, end));
+            buffer.cursor.move_to(start);
+
+            return true;
+        }
+    }
+
+    false
+}
+
 pub fn display_current_scope(app: &mut Application) -> Result {
     let scope_display_buffer = {
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -300,57 +775,97 @@ pub fn display_current_scope(app: &mut Application) -> Result {
     util::add_buffer(scope_display_buffer, app)
 }
 
-/// Inserts a newline character at the current cursor position.
-/// Also performs automatic indentation, basing the indent off
-/// of the previous line's leading whitespace.
+/// Inserts a newline character at the current cursor position. Unless
+/// disabled, also performs automatic indentation, basing the indent off
+/// of the previous line's leading whitespace (plus a level, if the line
+/// opens a brace-delimited block).
 pub fn insert_newline(app: &mut Application) -> Result {
-    if let Some(buffer) = app.workspace.current_buffer() {
-        // Insert the newline character.
-        buffer.insert("\n");
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let autoindent = app.preferences.borrow().autoindent();
+    let uses_braces = buffer.syntax_definition
+        .as_ref()
+        .map(|syntax| util::indent::uses_braces(&syntax.name))
+        .unwrap_or(false);
+    let block_indent = if uses_braces {
+        Some(app.preferences.borrow().tab_content(buffer.path.as_ref()))
+    } else {
+        None
+    };
 
-        // Get the cursor position before moving it to the start of the new line.
-        let position = buffer.cursor.clone();
-        buffer.cursor.move_down();
-        buffer.cursor.move_to_start_of_line();
+    edit_at_each_cursor(app, |buffer| {
+        if autoindent {
+            insert_indented_newline_at_cursor(buffer, block_indent.as_ref().map(|indent| indent.as_str()));
+        } else {
+            buffer.insert("\n");
+            buffer.cursor.move_down();
+            buffer.cursor.move_to_start_of_line();
+        }
+        Ok(())
+    })?;
+    commands::view::scroll_to_cursor(app)?;
 
-        // Get a slice of the buffer up to and including the current line.
-        let data = buffer.data();
-        let end_of_current_line = data
-            .lines()
-            .nth(position.line)
-            .map(|l| (l.as_ptr() as usize) + l.len())
-            .unwrap();
-        let offset = end_of_current_line - (data.as_str().as_ptr() as usize);
-        let (previous_content, _) = data.split_at(offset);
-
-        // Searching backwards, copy the nearest non-blank line's indent content.
-        let nearest_non_blank_line = previous_content.lines().rev().find(|line| !line.is_empty());
-        let indent_content = match nearest_non_blank_line {
-            Some(line) => line.chars().take_while(|&c| c.is_whitespace()).collect(),
-            None => String::new(),
-        };
+    Ok(())
+}
 
-        // Insert and move to the end of the indent content.
-        let indent_length = indent_content.chars().count();
-        buffer.insert(indent_content);
-        buffer.cursor.move_to(Position {
-            line: position.line + 1,
-            offset: indent_length,
-        });
-    } else {
-        bail!(BUFFER_MISSING);
+fn insert_indented_newline_at_cursor(buffer: &mut Buffer, block_indent: Option<&str>) {
+    // Insert the newline character.
+    buffer.insert("\n");
+
+    // Get the cursor position before moving it to the start of the new line.
+    let position = buffer.cursor.clone();
+    buffer.cursor.move_down();
+    buffer.cursor.move_to_start_of_line();
+
+    // Get the lines of the buffer up to and including the current line.
+    let data = buffer.data();
+    let previous_lines: Vec<&str> = data.lines().take(position.line + 1).collect();
+    let current_line = previous_lines.get(position.line).cloned().unwrap_or("");
+
+    // Searching backwards, copy the nearest non-blank line's indent content.
+    let nearest_non_blank_line = previous_lines.iter().rev().find(|line| !line.is_empty());
+    let mut indent_content: String = match nearest_non_blank_line {
+        Some(line) => line.chars().take_while(|&c| c.is_whitespace()).collect(),
+        None => String::new(),
+    };
+
+    // Indent the contents of a block one level deeper than its opening line.
+    if let Some(block_indent) = block_indent {
+        if current_line.trim_end().ends_with('{') {
+            indent_content.push_str(block_indent);
+        }
     }
-    commands::view::scroll_to_cursor(app)?;
 
-    Ok(())
+    // Don't leave behind a line containing nothing but (likely
+    // auto-inserted) whitespace, which would otherwise linger.
+    if !current_line.is_empty() && current_line.trim().is_empty() {
+        buffer.delete_range(Range::new(
+            Position{ line: position.line, offset: 0 },
+            Position{ line: position.line, offset: current_line.graphemes(true).count() }
+        ));
+        buffer.cursor.move_to(Position{ line: position.line + 1, offset: 0 });
+    }
+
+    // Insert and move to the end of the indent content.
+    let indent_length = indent_content.chars().count();
+    buffer.insert(indent_content);
+    buffer.cursor.move_to(Position {
+        line: position.line + 1,
+        offset: indent_length,
+    });
 }
 
+/// Indents the current line (or selected lines) by one level. Blank lines
+/// within a selection are left alone, and the selection remains active,
+/// so that it can be indented again.
 pub fn indent_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
+    let data = buffer.data();
 
     let target_position = match app.mode {
-        Mode::Insert => {
+        Mode::Insert(_) => {
             Position {
                 line: buffer.cursor.line,
                 offset: buffer.cursor.offset + tab_content.chars().count(),
@@ -374,15 +889,20 @@ pub fn indent_line(app: &mut Application) -> Result {
 
     // Move to the start of the current line and
     // insert the content, as a single operation.
-    buffer.start_operation_group();
+    let selecting = lines.len() > 1;
+    app.view.start_undo_group(buffer)?;
     for line in lines {
+        if selecting && is_blank_line(&data, line) {
+            continue;
+        }
+
         buffer.cursor.move_to(Position {
             line,
             offset: 0,
         });
         buffer.insert(tab_content.clone());
     }
-    buffer.end_operation_group();
+    app.view.end_undo_group(buffer)?;
 
     // Move to the original position, shifted to compensate for the indent.
     buffer.cursor.move_to(target_position);
@@ -390,11 +910,13 @@ pub fn indent_line(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Removes one level of indentation from the current line (or selected
+/// lines): a leading tab, or up to a tab width's worth of leading spaces.
+/// Blank lines within a selection are left alone.
 pub fn outdent_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
-
-    // FIXME: Determine this based on file type and/or user config.
+    let tab_width = app.preferences.borrow().tab_width(buffer.path.as_ref());
     let data = buffer.data();
 
     // Get the range of lines we'll outdent based on
@@ -411,29 +933,23 @@ pub fn outdent_line(app: &mut Application) -> Result {
     };
 
     // Group the individual outdent operations as one.
-    buffer.start_operation_group();
+    let selecting = lines.len() > 1;
+    app.view.start_undo_group(buffer)?;
 
     for line in lines {
-        if let Some(content) = data.lines().nth(line) {
-            let mut space_char_count = 0;
+        if selecting && is_blank_line(&data, line) {
+            continue;
+        }
 
-            // Check for leading whitespace.
-            if tab_content.chars().next() == Some('\t') {
-                // We're looking for a tab character.
-                if content.chars().next() == Some('\t') {
-                    space_char_count = 1;
-                }
+        if let Some(content) = data.lines().nth(line) {
+            // Check for leading whitespace, stopping short of anything
+            // else, so that lines with less than a full level are cleared
+            // of their indentation, rather than being cut into.
+            let space_char_count = if content.starts_with('\t') {
+                1
             } else {
-                // We're looking for spaces.
-                for character in content.chars().take(tab_content.chars().count()) {
-                    if character == ' ' {
-                        space_char_count += 1;
-                    } else {
-                        // We've run into a non-whitespace character; stop here.
-                        break;
-                    }
-                }
-            }
+                content.chars().take(tab_width).take_while(|&character| character == ' ').count()
+            };
 
             // Remove leading whitespace, up to indent size,
             // if we found any, and adjust cursor accordingly.
@@ -448,27 +964,171 @@ pub fn outdent_line(app: &mut Application) -> Result {
                                                }));
 
                 // Figure out where the cursor should sit, guarding against underflow.
-                let target_offset = buffer.cursor
-                                          .offset
-                                          .checked_sub(space_char_count)
-                                          .unwrap_or(0);
-                let target_line = buffer.cursor.line;
+                if buffer.cursor.line == line {
+                    let target_offset = buffer.cursor
+                                              .offset
+                                              .checked_sub(space_char_count)
+                                              .unwrap_or(0);
 
-                buffer.cursor.move_to(Position {
-                    line: target_line,
-                    offset: target_offset,
-                });
+                    buffer.cursor.move_to(Position {
+                        line,
+                        offset: target_offset,
+                    });
+                }
             }
         }
     }
 
     // Finish grouping the individual outdent operations as one.
-    buffer.end_operation_group();
+    app.view.end_undo_group(buffer)?;
+
+    Ok(())
+}
+
+/// Whether the line is empty or consists only of whitespace.
+fn is_blank_line(data: &str, line: usize) -> bool {
+    data.split('\n').nth(line).map(|content| content.trim().is_empty()).unwrap_or(true)
+}
+
+/// Comments out the current line (or selected lines), using the comment
+/// tokens for the buffer's syntax. If every non-blank line is already
+/// commented, the comments are removed instead.
+pub fn toggle_comment(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let syntax_name = buffer.syntax_definition
+        .as_ref()
+        .map(|syntax| syntax.name.clone())
+        .unwrap_or_default();
+    let tokens = match util::comment::tokens(&syntax_name) {
+        Some(tokens) => tokens,
+        None => {
+            app.message = Some(format!("No comment syntax defined for {}", syntax_name));
+            return Ok(());
+        }
+    };
+
+    let (first_line, last_line) = match app.mode {
+        Mode::Select(ref mode) => (
+            cmp::min(mode.anchor.line, buffer.cursor.line),
+            cmp::max(mode.anchor.line, buffer.cursor.line)
+        ),
+        Mode::SelectLine(ref mode) => (
+            cmp::min(mode.anchor, buffer.cursor.line),
+            cmp::max(mode.anchor, buffer.cursor.line)
+        ),
+        _ => (buffer.cursor.line, buffer.cursor.line),
+    };
+
+    let data = buffer.data();
+    let lines: Vec<&str> = data.split('\n').skip(first_line).take(last_line - first_line + 1).collect();
+    let toggled_lines = util::comment::toggle(&lines, tokens);
+
+    // Keep the cursor on the same content by shifting it
+    // along with any change to its line's length.
+    let cursor = *buffer.cursor;
+    let shift = toggled_lines[cursor.line - first_line].graphemes(true).count() as isize -
+        lines[cursor.line - first_line].graphemes(true).count() as isize;
+
+    app.view.start_undo_group(buffer)?;
+    for (index, (line, toggled_line)) in lines.iter().zip(toggled_lines.iter()).enumerate() {
+        if *line == toggled_line.as_str() {
+            continue;
+        }
+
+        let line_number = first_line + index;
+        buffer.delete_range(Range::new(
+            Position{ line: line_number, offset: 0 },
+            Position{ line: line_number, offset: line.graphemes(true).count() }
+        ));
+        buffer.cursor.move_to(Position{ line: line_number, offset: 0 });
+        buffer.insert(toggled_line.clone());
+    }
+    app.view.end_undo_group(buffer)?;
+
+    buffer.cursor.move_to(Position{
+        line: cursor.line,
+        offset: cmp::max(cursor.offset as isize + shift, 0) as usize
+    });
+
+    Ok(())
+}
+
+/// Converts the leading tabs of the buffer (or selected lines) to spaces.
+pub fn convert_indentation_to_spaces(app: &mut Application) -> Result {
+    convert_lines(app, |line, tab_width| util::indent::retab(line, tab_width, true))
+}
+
+/// Converts the leading spaces of the buffer (or selected lines) to tabs.
+pub fn convert_indentation_to_tabs(app: &mut Application) -> Result {
+    convert_lines(app, |line, tab_width| util::indent::retab(line, tab_width, false))
+}
+
+/// Converts every tab in the buffer (or selected lines) to spaces.
+pub fn convert_tabs_to_spaces(app: &mut Application) -> Result {
+    convert_lines(app, util::indent::expand_tabs)
+}
+
+/// Converts runs of spaces ending at tab stops throughout
+/// the buffer (or selected lines) to tabs.
+pub fn convert_spaces_to_tabs(app: &mut Application) -> Result {
+    convert_lines(app, util::indent::unexpand_spaces)
+}
+
+/// Replaces each of the selected lines (or all of them, outside of select
+/// modes) with the result of the conversion, as a single undoable edit.
+fn convert_lines<F>(app: &mut Application, convert: F) -> Result
+    where F: Fn(&str, usize) -> String
+{
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let tab_width = app.preferences.borrow().tab_width(buffer.path.as_ref());
+    let (first_line, last_line) = match app.mode {
+        Mode::Select(ref mode) => (
+            cmp::min(mode.anchor.line, buffer.cursor.line),
+            cmp::max(mode.anchor.line, buffer.cursor.line)
+        ),
+        Mode::SelectLine(ref mode) => (
+            cmp::min(mode.anchor, buffer.cursor.line),
+            cmp::max(mode.anchor, buffer.cursor.line)
+        ),
+        _ => (0, buffer.line_count()),
+    };
+
+    let data = buffer.data();
+    let cursor = *buffer.cursor;
+    app.view.start_undo_group(buffer)?;
+    for (line_number, line) in data.split('\n').enumerate().skip(first_line).take(last_line - first_line + 1) {
+        let converted_line = convert(line, tab_width);
+        if converted_line == line {
+            continue;
+        }
+
+        buffer.delete_range(Range::new(
+            Position{ line: line_number, offset: 0 },
+            Position{ line: line_number, offset: line.graphemes(true).count() }
+        ));
+        buffer.cursor.move_to(Position{ line: line_number, offset: 0 });
+        buffer.insert(converted_line);
+    }
+    app.view.end_undo_group(buffer)?;
+
+    // Keep the cursor on its line, even if it's been shortened.
+    let line_length = buffer.data()
+        .split('\n')
+        .nth(cursor.line)
+        .map(|line| line.graphemes(true).count())
+        .unwrap_or(0);
+    buffer.cursor.move_to(Position{
+        line: cursor.line,
+        offset: cmp::min(cursor.offset, line_length)
+    });
 
     Ok(())
 }
 
 pub fn change_token(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     commands::buffer::delete_token(app)?;
     commands::application::switch_to_insert_mode(app)?;
 
@@ -476,13 +1136,14 @@ pub fn change_token(app: &mut Application) -> Result {
 }
 
 pub fn delete_rest_of_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     // Create a range extending from the
     // cursor's current position to the next line.
     let starting_position = *buffer.cursor;
     let target_line = buffer.cursor.line + 1;
-    buffer.start_operation_group();
+    app.view.start_undo_group(buffer)?;
     buffer.delete_range(Range::new(starting_position,
                                    Position {
                                        line: target_line,
@@ -496,6 +1157,7 @@ pub fn delete_rest_of_line(app: &mut Application) -> Result {
 }
 
 pub fn change_rest_of_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     commands::buffer::delete_rest_of_line(app)?;
     commands::application::switch_to_insert_mode(app)?;
 
@@ -503,40 +1165,103 @@ pub fn change_rest_of_line(app: &mut Application) -> Result {
 }
 
 pub fn start_command_group(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer()
-        .ok_or(BUFFER_MISSING)?
-        .start_operation_group();
-
-    Ok(())
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.start_undo_group(buffer)
 }
 
 pub fn end_command_group(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer()
-        .ok_or(BUFFER_MISSING)?
-        .end_operation_group();
-
-    Ok(())
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.end_undo_group(buffer)
 }
 
 pub fn undo(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.undo();
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.undo(buffer)?;
     commands::view::scroll_to_cursor(app).chain_err(|| {
         "Couldn't scroll to cursor after undoing."
     })
 }
 
 pub fn redo(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.redo();
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.redo(buffer)?;
     commands::view::scroll_to_cursor(app).chain_err(|| {
         "Couldn't scroll to cursor after redoing."
     })
 }
 
+/// Prompts for a file (using open mode's fuzzy search) whose
+/// contents will be inserted at the cursor once selected.
+pub fn read_file(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    commands::application::switch_to_open_mode(app)?;
+    if let Mode::Open(ref mut mode) = app.mode {
+        mode.read_on_accept = true;
+    }
+
+    Ok(())
+}
+
+/// Inserts the specified file's contents at the cursor as a single edit,
+/// refusing files larger than the configured large file threshold.
+pub fn insert_file(app: &mut Application, path: &Path) -> Result {
+    util::ensure_writable(app)?;
+    let size = fs::metadata(path)
+        .chain_err(|| format!("Couldn't find {}", path.to_string_lossy()))?
+        .len();
+    let threshold = app.preferences.borrow().large_file_threshold();
+    if size > threshold {
+        bail!(
+            "{} is too large to insert ({} bytes, limit is {})",
+            path.to_string_lossy(),
+            size,
+            threshold
+        );
+    }
+    let content = fs::read_to_string(path)
+        .chain_err(|| format!("Couldn't read {}", path.to_string_lossy()))?;
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    buffer.insert(content);
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Inserts the template configured for the current buffer's file type,
+/// filling in its placeholders and moving to its cursor placeholder.
+pub fn apply_template(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or("The current buffer has no path")?;
+    let template_path = app.preferences
+        .borrow()
+        .template_path(Some(&path))
+        .ok_or("No template is configured for the current buffer's file type")?;
+    let template = fs::read_to_string(&template_path)
+        .chain_err(|| format!("Couldn't read {}", template_path.to_string_lossy()))?;
+
+    let file_name = path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (content, cursor) = util::template::render(
+        &template,
+        &file_name,
+        &util::template::current_date()
+    );
+    buffer.insert(content);
+    if let Some(position) = cursor {
+        buffer.cursor.move_to(position);
+    }
+
+    commands::view::scroll_to_cursor(app)
+}
+
 pub fn paste(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let insert_below = match app.mode {
-        Mode::Select(_) | Mode::SelectLine(_) | Mode::Search(_) => {
+        Mode::Select(_) | Mode::SelectLine(_) | Mode::SelectBlock(_) | Mode::Search(_) => {
             commands::selection::delete(app).chain_err(|| {
                 "Couldn't delete selection prior to pasting."
             })?;
@@ -550,6 +1275,8 @@ pub fn paste(app: &mut Application) -> Result {
         match *app.clipboard.get_content() {
             ClipboardContent::Inline(ref content) => buffer.insert(content.clone()),
             ClipboardContent::Block(ref content) => {
+                // Whole lines are pasted as such, even if the last one wasn't terminated.
+                let content = line_wise(content);
                 let original_cursor_position = *buffer.cursor.clone();
                 let line = original_cursor_position.line;
 
@@ -572,45 +1299,104 @@ pub fn paste(app: &mut Application) -> Result {
                         } else {
                             // We're on a trailing newline, which doesn't
                             // have any data; just insert the content here.
-                            buffer.insert(content.clone());
+                            buffer.insert(content);
                         }
                     } else {
-                        buffer.insert(content.clone());
+                        buffer.insert(content);
                     }
                 } else {
-                    buffer.insert(content.clone());
+                    buffer.insert(content);
                 }
             }
+            ClipboardContent::Rectangle(ref content) => paste_rectangle(buffer, content),
             ClipboardContent::None => (),
         }
     } else {
         bail!(BUFFER_MISSING);
     }
-    commands::view::scroll_to_cursor(app)?;
+    commands::view::scroll_to_cursor(app)?;
+
+    Ok(())
+}
+
+/// Inserts each row of rectangular content at the cursor's column on
+/// successive lines, padding short lines with spaces and adding lines
+/// past the end of the buffer as needed. The cursor is left in place.
+fn paste_rectangle(buffer: &mut Buffer, content: &str) {
+    let origin = *buffer.cursor.clone();
+
+    for (index, row) in content.split('\n').enumerate() {
+        let line = origin.line + index;
+        let data = buffer.data();
+        let lines: Vec<&str> = data.split('\n').collect();
+        let line_length = match lines.get(line) {
+            Some(line_content) => line_content.graphemes(true).count(),
+            None => {
+                // Add a line to the end of the buffer.
+                let last_line = lines.len() - 1;
+                buffer.cursor.move_to(Position {
+                    line: last_line,
+                    offset: lines[last_line].graphemes(true).count(),
+                });
+                buffer.insert("\n");
+                0
+            }
+        };
+
+        if row.is_empty() {
+            continue;
+        }
+        if line_length < origin.offset {
+            buffer.cursor.move_to(Position { line, offset: line_length });
+            buffer.insert(" ".repeat(origin.offset - line_length));
+        }
+        buffer.cursor.move_to(Position { line, offset: origin.offset });
+        buffer.insert(row);
+    }
 
-    Ok(())
+    buffer.cursor.move_to(origin);
 }
 
+/// Pastes whole lines above the current one. Other content
+/// is inserted at the cursor, as it is when pasting below.
 pub fn paste_above(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
-    if let ClipboardContent::Block(ref content) = *app.clipboard.get_content() {
-        let mut start_of_line = Position {
-            line: buffer.cursor.line,
-            offset: 0,
-        };
+    match *app.clipboard.get_content() {
+        ClipboardContent::Inline(ref content) => buffer.insert(content.clone()),
+        ClipboardContent::Block(ref content) => {
+            let mut start_of_line = Position {
+                line: buffer.cursor.line,
+                offset: 0,
+            };
 
-        // Temporarily move the cursor to the start of the line
-        // to insert the clipboard content (without allocating).
-        mem::swap(&mut *buffer.cursor, &mut start_of_line);
-        buffer.insert(content.clone());
-        mem::swap(&mut *buffer.cursor, &mut start_of_line);
+            // Temporarily move the cursor to the start of the line
+            // to insert the clipboard content (without allocating).
+            mem::swap(&mut *buffer.cursor, &mut start_of_line);
+            buffer.insert(line_wise(content));
+            mem::swap(&mut *buffer.cursor, &mut start_of_line);
+        }
+        ClipboardContent::Rectangle(ref content) => paste_rectangle(buffer, content),
+        ClipboardContent::None => (),
     }
 
     Ok(())
 }
 
+/// Terminates the content's last line, if need be, so that
+/// pasting it doesn't join it with an existing line.
+fn line_wise(content: &str) -> String {
+    let mut content = content.to_string();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+
+    content
+}
+
 pub fn remove_trailing_whitespace(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let mut line = 0;
     let mut offset = 0;
@@ -674,6 +1460,7 @@ pub fn remove_trailing_whitespace(app: &mut Application) -> Result {
 }
 
 pub fn ensure_trailing_newline(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     // Find end of buffer position.
@@ -706,6 +1493,7 @@ pub fn ensure_trailing_newline(app: &mut Application) -> Result {
 }
 
 pub fn insert_tab(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
     let tab_content_width = tab_content.chars().count();
@@ -719,14 +1507,416 @@ pub fn insert_tab(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn increment_number(app: &mut Application) -> Result {
+    adjust_number(app, 1)
+}
+
+pub fn decrement_number(app: &mut Application) -> Result {
+    adjust_number(app, -1)
+}
+
+/// Adds the amount to the number under or after the cursor on the current
+/// line, leaving the cursor at the end of the number. Lines without a
+/// number are left untouched.
+pub fn adjust_number(app: &mut Application, amount: i64) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = *buffer.cursor.clone();
+    let data = buffer.data();
+    let current_line = data.lines().nth(position.line).unwrap_or("");
+    let (start, end, number) = match util::number::adjust(current_line, position.offset, amount) {
+        Some(adjustment) => adjustment,
+        None => return Ok(()),
+    };
+    let start = Position{ line: position.line, offset: start };
+    let number_length = number.chars().count();
+
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(Range::new(start, Position{ line: position.line, offset: end }));
+    buffer.cursor.move_to(start);
+    buffer.insert(number);
+    buffer.cursor.move_to(Position {
+        line: position.line,
+        offset: start.offset + number_length - 1,
+    });
+    app.view.end_undo_group(buffer)?;
+
+    commands::view::scroll_to_cursor(app)
+}
+
+pub fn toggle_bookmark(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.toggle_bookmark(buffer)?;
+
+    Ok(())
+}
+
+/// Allows or refuses edits to the current buffer.
+pub fn toggle_read_only(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let read_only = !app.view.read_only(buffer);
+    app.view.set_read_only(buffer, read_only)?;
+    app.message = Some(String::from(if read_only {
+        "Buffer is now read-only"
+    } else {
+        "Buffer is now writable"
+    }));
+
+    Ok(())
+}
+
+pub fn format_json(app: &mut Application) -> Result {
+    format_content(app, util::format::json)
+}
+
+pub fn format_xml(app: &mut Application) -> Result {
+    format_content(app, util::format::xml)
+}
+
+/// Replaces the current buffer's content with a formatted version of it,
+/// as a single operation. The buffer is left untouched if formatting fails.
+fn format_content(app: &mut Application, formatter: fn(&str, &str) -> errors::Result<String>) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
+    let data = buffer.data();
+    let formatted_data = formatter(&data, &tab_content)?;
+
+    replace_content(app, formatted_data)
+}
+
+/// Strips invisible control characters and byte order marks from the
+/// current buffer, replacing non-breaking spaces with regular ones.
+pub fn remove_control_characters(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let data = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.data();
+    let mut cleaned_data = String::with_capacity(data.len());
+
+    for character in data.chars() {
+        match character {
+            '\u{a0}' => cleaned_data.push(' '),
+            c if util::control_character_symbol(c).is_some() => (),
+            c => cleaned_data.push(c),
+        }
+    }
+
+    if cleaned_data != data {
+        replace_content(app, cleaned_data)?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the buffer's content with that of its swap file, leaving
+/// it modified, so that the recovered changes can be reviewed.
+pub fn recover_swap(app: &mut Application) -> Result {
+    let path = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
+        .path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let swap = util::swap::read(&path).ok_or("Couldn't read the buffer's swap file")?;
+
+    replace_content(app, swap.data)
+}
+
+/// Removes the buffer's swap file, discarding the changes it recorded.
+pub fn discard_swap(app: &mut Application) -> Result {
+    let path = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
+        .path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    util::swap::remove(&path);
+
+    Ok(())
+}
+
+/// Replaces the current buffer's content as a single operation,
+/// keeping the cursor at its original position, if possible.
+fn replace_content(app: &mut Application, content: String) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let original_position = *buffer.cursor;
+    let end_position = data.lines().enumerate().last().map(|(line, content)| {
+        if data.ends_with('\n') {
+            Position { line: line + 1, offset: 0 }
+        } else {
+            Position { line, offset: content.chars().count() }
+        }
+    }).unwrap_or(Position { line: 0, offset: 0 });
+
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(Range::new(Position { line: 0, offset: 0 }, end_position));
+    buffer.cursor.move_to(Position { line: 0, offset: 0 });
+    buffer.insert(content);
+    app.view.end_undo_group(buffer)?;
+
+    // Try to restore the original cursor position.
+    if !buffer.cursor.move_to(original_position) {
+        buffer.cursor.move_to_first_line();
+    }
+    commands::view::scroll_to_cursor(app)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::commands;
+    use crate::input::Key;
     use crate::models::Application;
-    use crate::models::application::{ClipboardContent, Mode};
+    use crate::models::application::{ClipboardContent, Mode, Preferences};
+    use crate::util;
+    use crate::util::line_ending::{LineEnding, LineEndings};
     use scribe::Buffer;
     use scribe::buffer::Position;
     use std::path::Path;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn increment_number_adjusts_the_next_number_and_moves_to_its_end() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\nversion = 0.9");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        super::increment_number(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nversion = 1.9");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 10 });
+
+        super::adjust_number(&mut app, -5).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nversion = -4.9");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 11 });
+
+        super::undo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nversion = 1.9");
+    }
+
+    #[test]
+    fn decrement_number_does_nothing_without_a_number() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp 1\neditor");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        super::decrement_number(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp 1\neditor");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+    }
+
+    #[test]
+    fn format_json_reindents_buffer_content_as_a_single_operation() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("{\"amp\":[1,2]}");
+        app.workspace.add_buffer(buffer);
+
+        super::format_json(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(),
+                   "{\n  \"amp\": [\n    1,\n    2\n  ]\n}\n");
+
+        super::undo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "{\"amp\":[1,2]}");
+    }
+
+    #[test]
+    fn format_json_leaves_invalid_content_unchanged() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("{\"amp\":}");
+        app.workspace.add_buffer(buffer);
+
+        assert!(super::format_json(&mut app).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "{\"amp\":}");
+    }
+
+    #[test]
+    fn insert_char_inserts_at_each_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\namp\n");
+        app.workspace.add_buffer(buffer);
+        app.secondary_cursors.add(Position{ line: 1, offset: 0 }, &Position{ line: 0, offset: 0 });
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+
+        for character in "an ".chars() {
+            app.view.last_key = Some(Key::Char(character));
+            super::insert_char(&mut app).unwrap();
+        }
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "an amp\nan amp\n");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 3 });
+        assert_eq!(app.secondary_cursors.positions(), &[Position{ line: 1, offset: 3 }]);
+    }
+
+    #[test]
+    fn edit_at_each_cursor_ends_its_undo_group_when_an_edit_fails() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\namp\n");
+        util::add_buffer(buffer, &mut app).unwrap();
+        app.secondary_cursors.add(Position{ line: 1, offset: 0 }, &Position{ line: 0, offset: 0 });
+
+        let mut edits = 0;
+        assert!(super::edit_at_each_cursor(&mut app, |buffer| {
+            edits += 1;
+            if edits > 1 {
+                bail!("Edit failed");
+            }
+            buffer.insert("a");
+            Ok(())
+        }).is_err());
+
+        // Later changes are undone on their own, rather than joining the failed edit's group.
+        app.workspace.current_buffer().unwrap().insert("n");
+        super::undo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "aamp\namp\n");
+    }
+
+    #[test]
+    fn toggle_read_only_refuses_edits_until_toggled_back() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        util::add_buffer(buffer, &mut app).unwrap();
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('a'));
+
+        super::toggle_read_only(&mut app).unwrap();
+        assert!(super::insert_char(&mut app).is_err());
+        assert!(super::delete(&mut app).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp");
+
+        super::toggle_read_only(&mut app).unwrap();
+        super::insert_char(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "aamp");
+    }
+
+    #[test]
+    fn backspace_merges_cursors_that_meet() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("ab\ncd");
+        buffer.cursor.move_to(Position{ line: 0, offset: 1 });
+        app.workspace.add_buffer(buffer);
+        app.secondary_cursors.add(Position{ line: 0, offset: 2 }, &Position{ line: 0, offset: 1 });
+
+        super::backspace(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "\ncd");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+        assert!(app.secondary_cursors.is_empty());
+    }
+
+    #[test]
+    fn insert_char_leaves_insert_mode_when_escape_sequence_is_typed() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("insert_escape_sequence: jk").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+
+        for character in "jjk".chars() {
+            app.view.last_key = Some(Key::Char(character));
+            super::insert_char(&mut app).unwrap();
+        }
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "jamp");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 1 });
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn insert_char_pairs_brackets_and_quotes_when_auto_pairs_are_enabled() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("auto_pairs:\n  enabled: true").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("\n");
+        app.workspace.add_buffer(buffer);
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+
+        // Closers are stepped over, and quotes aren't paired after words.
+        for character in "amp(\"x\") don't".chars() {
+            app.view.last_key = Some(Key::Char(character));
+            super::insert_char(&mut app).unwrap();
+        }
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp(\"x\") don't\n");
+
+        // Backspacing between an empty pair removes both characters.
+        app.view.last_key = Some(Key::Char('['));
+        super::insert_char(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp(\"x\") don't[]\n");
+        super::backspace(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp(\"x\") don't\n");
+    }
+
+    #[test]
+    fn insert_char_does_not_pair_quotes_within_strings() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("auto_pairs:\n  enabled: true").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("x = \"amp  editor\"\n");
+        app.workspace.add_buffer(buffer);
+        let syntax = app.workspace.syntax_set.find_syntax_by_name("Python").cloned();
+        app.workspace.current_buffer().unwrap().syntax_definition = syntax;
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 9 });
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+
+        app.view.last_key = Some(Key::Char('\''));
+        super::insert_char(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "x = \"amp \' editor\"\n");
+    }
+
+    #[test]
+    fn close_quits_when_last_buffer_is_closed_if_configured() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("on_last_buffer_close: quit").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        commands::workspace::new_buffer(&mut app).unwrap();
+
+        commands::buffer::close(&mut app).unwrap();
+        assert!(match app.mode { Mode::Exit => true, _ => false });
+    }
+
+    #[test]
+    fn close_opens_scratch_buffer_when_last_modified_buffer_is_confirmed_if_configured() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("on_last_buffer_close: scratch").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        commands::workspace::new_buffer(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().insert("amp");
+
+        // Modified buffers still prompt before being closed.
+        commands::buffer::close(&mut app).unwrap();
+        assert!(match app.mode { Mode::Confirm(_) => true, _ => false });
+
+        commands::confirm::confirm_command(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn remove_control_characters_strips_invisible_characters() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\u{feff}amp\u{1b}\u{a0}editor\r\n");
+        app.workspace.add_buffer(buffer);
+
+        super::remove_control_characters(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp editor\r\n");
+    }
+
+    #[test]
+    fn remove_control_characters_keeps_cr_line_endings() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\u{1b}\reditor\r");
+        app.workspace.add_buffer(buffer);
+
+        super::remove_control_characters(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\reditor\r");
+    }
 
     #[test]
     fn insert_newline_uses_current_line_indentation() {
@@ -818,7 +2008,7 @@ mod tests {
 
         // Ensure that we're in insert mode.
         assert!(match app.mode {
-            crate::models::application::Mode::Insert => true,
+            crate::models::application::Mode::Insert(_) => true,
             _ => false,
         });
 
@@ -1115,6 +2305,40 @@ mod tests {
                    "  amp\n  editor");
     }
 
+    #[test]
+    fn indent_and_outdent_leave_blank_selected_lines_alone_and_keep_the_selection() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n\n  \n editor");
+        app.workspace.add_buffer(buffer);
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::cursor::move_to_last_line(&mut app).unwrap();
+
+        super::indent_line(&mut app).unwrap();
+        super::indent_line(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "    amp\n\n  \n     editor");
+        assert!(match app.mode { Mode::SelectLine(_) => true, _ => false });
+
+        super::outdent_line(&mut app).unwrap();
+        super::outdent_line(&mut app).unwrap();
+        super::outdent_line(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n\n  \neditor");
+    }
+
+    #[test]
+    fn outdent_line_removes_tabs_and_spaces_regardless_of_the_soft_tabs_preference() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\tamp\n  editor");
+        app.workspace.add_buffer(buffer);
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+
+        super::outdent_line(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor");
+    }
+
     #[test]
     fn outdent_line_works_with_reversed_selections() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1319,14 +2543,62 @@ mod tests {
             offset: 8,
         });
 
-        // Now that we've set up the buffer, add it
-        // to the application and run the command.
+        // Now that we've set up the buffer, add it
+        // to the application and run the command.
+        app.workspace.add_buffer(buffer);
+        commands::buffer::backspace(&mut app).unwrap();
+
+        // Ensure that the clipboard contents are pasted to the line below.
+        assert_eq!(app.workspace.current_buffer().unwrap().data(),
+                   "amp\neditor\n      ");
+    }
+
+    #[test]
+    fn backspace_joins_lines_at_the_start_of_a_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::backspace(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "ampeditor");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 3 });
+    }
+
+    #[test]
+    fn backspace_does_nothing_at_the_start_of_the_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::backspace(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor");
+    }
+
+    #[test]
+    fn backspace_respects_configured_boundaries() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("backspace: [indent]").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
         app.workspace.add_buffer(buffer);
+
+        // Text preceding the insertion point is off-limits.
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
         commands::buffer::backspace(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor");
 
-        // Ensure that the clipboard contents are pasted to the line below.
-        assert_eq!(app.workspace.current_buffer().unwrap().data(),
-                   "amp\neditor\n      ");
+        // As is joining lines.
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 0 });
+        commands::buffer::backspace(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor");
     }
 
     #[test]
@@ -1351,6 +2623,120 @@ mod tests {
                    });
     }
 
+    #[test]
+    fn split_line_breaks_the_line_at_the_cursor_and_keeps_indentation() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("  amp editor\nline");
+        buffer.cursor.move_to(Position{ line: 0, offset: 5 });
+
+        app.workspace.add_buffer(buffer);
+        commands::buffer::split_line(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "  amp\n  editor\nline");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor,
+                   Position{ line: 1, offset: 2 });
+
+        // The split is undone as a single operation.
+        app.workspace.current_buffer().unwrap().undo();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "  amp editor\nline");
+    }
+
+    #[test]
+    fn copy_all_copies_the_buffer_without_moving_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 2 });
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::copy_all(&mut app).unwrap();
+
+        assert_eq!(
+            *app.clipboard.get_content(),
+            ClipboardContent::Block(String::from("amp\neditor\n"))
+        );
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 2 });
+        assert_eq!(app.message, Some(String::from("Copied 11 bytes (3 lines)")));
+    }
+
+    #[test]
+    fn copy_all_asks_for_confirmation_for_large_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("large_file_threshold: 2").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::copy_all(&mut app).unwrap();
+        assert!(match app.mode { Mode::Confirm(_) => true, _ => false });
+        assert!(app.message.is_none());
+
+        commands::confirm::confirm_command(&mut app).unwrap();
+        assert_eq!(app.message, Some(String::from("Copied 3 bytes (1 lines)")));
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn insert_file_inserts_the_file_contents_at_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::insert_file(&mut app, Path::new("LICENSE")).unwrap();
+
+        let license = std::fs::read_to_string("LICENSE").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), format!("{}amp\n", license));
+
+        // The insertion is undone as a single operation.
+        app.workspace.current_buffer().unwrap().undo();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n");
+    }
+
+    #[test]
+    fn insert_file_refuses_missing_and_large_files() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("large_file_threshold: 10").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        app.workspace.add_buffer(Buffer::new());
+
+        assert!(commands::buffer::insert_file(&mut app, Path::new("non_existent_file")).is_err());
+        assert!(commands::buffer::insert_file(&mut app, Path::new("LICENSE")).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "");
+    }
+
+    #[test]
+    fn apply_template_inserts_the_rendered_template() {
+        let template_path = concat!(env!("OUT_DIR"), "/template.rs");
+        std::fs::write(template_path, "// {{filename}}\nfn {{cursor}}() {}\n").unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str(&format!("templates:\n  rs: {}", template_path)).unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.path = Some(std::path::PathBuf::from("amp.rs"));
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::apply_template(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "// amp.rs\nfn () {}\n");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 3 });
+    }
+
+    #[test]
+    fn read_file_switches_to_open_mode_to_pick_a_file_to_insert() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        commands::buffer::read_file(&mut app).unwrap();
+
+        match app.mode {
+            Mode::Open(ref mode) => assert!(mode.read_on_accept),
+            _ => panic!("Application isn't in open mode"),
+        }
+    }
+
     #[test]
     fn merge_next_line_does_nothing_if_there_is_no_next_line() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1389,6 +2775,20 @@ mod tests {
                    "amp editor\ntest");
     }
 
+    #[test]
+    fn merge_next_line_joins_the_selected_lines_without_doubling_spaces() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n\n  editor \n  text\nbuffer\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 3, offset: 0 });
+        commands::buffer::merge_next_line(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp editor text\nbuffer\n");
+    }
+
     #[test]
     fn merge_next_line_works_when_the_first_line_has_leading_whitespace() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1523,6 +2923,50 @@ mod tests {
                    original_position);
     }
 
+    #[test]
+    fn paste_above_inserts_inline_content_at_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        app.clipboard.set_content(ClipboardContent::Inline("text ".to_string())).unwrap();
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::paste_above(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ntext editor");
+    }
+
+    #[test]
+    fn append_current_line_accumulates_lines_that_are_pasted_line_wise() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\ntext\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::copy_current_line(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::buffer::append_current_line(&mut app).unwrap();
+        assert_eq!(*app.clipboard.get_content(), ClipboardContent::Block("amp\neditor\n".to_string()));
+
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::buffer::paste(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor\ntext\namp\neditor\n");
+    }
+
+    #[test]
+    fn paste_terminates_block_content_so_that_it_stays_on_its_own_lines() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        app.clipboard.set_content(ClipboardContent::Block("text".to_string())).unwrap();
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::paste(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ntext\neditor\n");
+    }
+
     #[test]
     fn close_displays_confirmation_when_buffer_is_modified() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1670,4 +3114,276 @@ mod tests {
         app.workspace.next_buffer();
         assert_eq!(app.workspace.current_buffer().unwrap().data(), "two");
     }
+
+    #[test]
+    fn toggle_comment_comments_and_uncomments_the_current_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn amp() {\n    editor();\n}\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 4 });
+        app.workspace.add_buffer(buffer);
+        let syntax = app.workspace.syntax_set.find_syntax_by_name("Rust").cloned();
+        app.workspace.current_buffer().unwrap().syntax_definition = syntax;
+
+        super::toggle_comment(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "fn amp() {\n    // editor();\n}\n"
+        );
+        assert_eq!(
+            *app.workspace.current_buffer().unwrap().cursor,
+            Position{ line: 1, offset: 7 }
+        );
+
+        super::toggle_comment(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "fn amp() {\n    editor();\n}\n"
+        );
+    }
+
+    #[test]
+    fn toggle_comment_comments_selected_lines() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp = 1\n\neditor = 2\n");
+        app.workspace.add_buffer(buffer);
+        let syntax = app.workspace.syntax_set.find_syntax_by_name("Python").cloned();
+        app.workspace.current_buffer().unwrap().syntax_definition = syntax;
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 0 });
+        super::toggle_comment(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "# amp = 1\n\n# editor = 2\n"
+        );
+    }
+
+    #[test]
+    fn toggle_comment_leaves_buffers_without_comment_syntax_alone() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+        let syntax = app.workspace.syntax_set.find_syntax_by_name("Plain Text").cloned();
+        app.workspace.current_buffer().unwrap().syntax_definition = syntax;
+
+        super::toggle_comment(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp");
+        assert_eq!(app.message, Some(String::from("No comment syntax defined for Plain Text")));
+    }
+
+    #[test]
+    fn insert_newline_indents_a_level_after_an_opening_brace() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("  fn amp() {");
+        buffer.cursor.move_to(Position{ line: 0, offset: 12 });
+        app.workspace.add_buffer(buffer);
+        let syntax = app.workspace.syntax_set.find_syntax_by_name("Rust").cloned();
+        app.workspace.current_buffer().unwrap().syntax_definition = syntax;
+
+        super::insert_newline(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "  fn amp() {\n    ");
+        assert_eq!(
+            *app.workspace.current_buffer().unwrap().cursor,
+            Position{ line: 1, offset: 4 }
+        );
+    }
+
+    #[test]
+    fn insert_newline_removes_whitespace_from_the_line_it_leaves() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("    amp\n    ");
+        buffer.cursor.move_to(Position{ line: 1, offset: 4 });
+        app.workspace.add_buffer(buffer);
+
+        super::insert_newline(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "    amp\n\n    ");
+        assert_eq!(
+            *app.workspace.current_buffer().unwrap().cursor,
+            Position{ line: 2, offset: 4 }
+        );
+    }
+
+    #[test]
+    fn insert_newline_does_not_indent_when_autoindent_is_disabled() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("    amp");
+        buffer.cursor.move_to(Position{ line: 0, offset: 7 });
+        app.workspace.add_buffer(buffer);
+        let data = YamlLoader::load_from_str("autoindent: false").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        super::insert_newline(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "    amp\n");
+        assert_eq!(
+            *app.workspace.current_buffer().unwrap().cursor,
+            Position{ line: 1, offset: 0 }
+        );
+    }
+
+    #[test]
+    fn convert_indentation_to_spaces_converts_the_whole_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n\t  editor\tbuffer\n\t\tcursor\n");
+        app.workspace.add_buffer(buffer);
+        let data = YamlLoader::load_from_str("tab_width: 4").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        super::convert_indentation_to_spaces(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "amp\n      editor\tbuffer\n        cursor\n"
+        );
+    }
+
+    #[test]
+    fn convert_spaces_to_tabs_converts_only_the_selected_lines() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("    amp\n    am  editor\n    buffer\n");
+        app.workspace.add_buffer(buffer);
+        let data = YamlLoader::load_from_str("tab_width: 4").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 0 });
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        super::convert_spaces_to_tabs(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "    amp\n\tam\teditor\n    buffer\n"
+        );
+    }
+
+    #[test]
+    fn convert_line_endings_to_crlf_converts_the_buffer_and_its_line_ending() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\r\n");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        super::convert_line_endings_to_crlf(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amp\r\neditor\r\n");
+        assert_eq!(
+            app.view.line_endings(buffer),
+            Some(LineEndings { ending: LineEnding::Crlf, mixed: false })
+        );
+    }
+
+    #[test]
+    fn save_preserves_crlf_line_endings_in_the_saved_file() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/crlf_save.txt"));
+        std::fs::write(path, "amp\r\neditor\r\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(path, &mut app).unwrap();
+
+        // Add a line using a plain newline, as insert mode does.
+        app.workspace.current_buffer().unwrap().insert("\n");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 0 });
+        super::save(&mut app).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "\r\namp\r\neditor\r\n");
+
+        // The buffer, its cursor, and its history are left alone.
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "\namp\r\neditor\r\n");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 0 });
+        assert!(!buffer.modified());
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "amp\r\neditor\r\n");
+    }
+
+    #[test]
+    fn save_leaves_mixed_line_endings_alone() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/mixed_save.txt"));
+        std::fs::write(path, "amp\r\neditor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(path, &mut app).unwrap();
+
+        app.workspace.current_buffer().unwrap().insert("\n");
+        super::save(&mut app).unwrap();
+
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "\namp\r\neditor\n");
+    }
+
+    #[test]
+    fn save_applies_editor_config_settings() {
+        let directory = Path::new(concat!(env!("OUT_DIR"), "/save_editor_config"));
+        std::fs::create_dir_all(directory).unwrap();
+        std::fs::write(
+            directory.join(".editorconfig"),
+            "root = true\n[*.txt]\nend_of_line = crlf\ninsert_final_newline = false\ntrim_trailing_whitespace = false\n"
+        ).unwrap();
+        let path = directory.join("notes.txt");
+        std::fs::write(&path, "amp  \neditor").unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(&path, &mut app).unwrap();
+        super::save(&mut app).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "amp  \r\neditor");
+    }
+
+    #[test]
+    fn save_re_encodes_buffers_using_their_file_encoding() {
+        let path = concat!(env!("OUT_DIR"), "/latin1.txt");
+        std::fs::write(path, b"caf\xE9\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(Path::new(path), &mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "caf\u{e9}\n");
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        buffer.cursor.move_to(Position{ line: 0, offset: 4 });
+        buffer.insert("s");
+        super::save(&mut app).unwrap();
+
+        assert_eq!(std::fs::read(path).unwrap(), b"caf\xE9s\n".to_vec());
+    }
+
+    #[test]
+    fn reload_with_encoding_replaces_invalid_sequences_visibly() {
+        let path = concat!(env!("OUT_DIR"), "/forced_encoding.txt");
+        std::fs::write(path, b"caf\xE9\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(Path::new(path), &mut app).unwrap();
+
+        super::reload_with_encoding(&mut app, encoding_rs::UTF_8).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "caf\u{fffd}\n");
+        assert!(app.view.encoding(buffer).is_none());
+        assert!(app.message.is_some());
+    }
+
+    #[test]
+    fn recover_swap_restores_unsaved_changes_until_the_buffer_is_saved() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/swapped.txt"));
+        std::fs::write(path, "amp\n").unwrap();
+        util::swap::write(path, "amp\neditor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::open_buffer(path, &mut app).unwrap();
+
+        super::recover_swap(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amp\neditor\n");
+        assert!(buffer.modified());
+
+        super::save(&mut app).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "amp\neditor\n");
+        assert!(util::swap::read(path).is_none());
+    }
 }
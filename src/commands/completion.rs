@@ -0,0 +1,230 @@
+use crate::errors::*;
+use crate::errors;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::CompletionMode;
+use crate::util::completion;
+use std::mem;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Inserts the rest of the selected candidate and returns to insert mode.
+pub fn accept(app: &mut Application) -> Result {
+    let remainder = match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Completion(mode) => {
+            let remainder = mode.remainder().map(|r| r.to_string());
+            app.mode = Mode::Insert(mode.insert_mode);
+
+            remainder
+        }
+        mode => {
+            app.mode = mode;
+            bail!("Can't accept a completion outside of completion mode");
+        }
+    };
+
+    if let Some(remainder) = remainder {
+        let width = remainder.graphemes(true).count();
+        commands::buffer::edit_at_each_cursor(app, |buffer| {
+            buffer.insert(remainder.as_str());
+            for _ in 0..width {
+                buffer.cursor.move_right();
+            }
+            Ok(())
+        })?;
+
+        if let Some(buffer) = app.workspace.current_buffer() {
+            if let Mode::Insert(ref mut mode) = app.mode {
+                mode.record_insert(*buffer.cursor);
+            }
+        }
+    }
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Returns to insert mode without inserting anything.
+pub fn dismiss(app: &mut Application) -> Result {
+    restore_insert_mode(app);
+
+    Ok(())
+}
+
+pub fn select_next(app: &mut Application) -> Result {
+    if let Mode::Completion(ref mut mode) = app.mode {
+        mode.select_next();
+    }
+
+    Ok(())
+}
+
+pub fn select_previous(app: &mut Application) -> Result {
+    if let Mode::Completion(ref mut mode) = app.mode {
+        mode.select_previous();
+    }
+
+    Ok(())
+}
+
+/// Types the character as insert mode would, narrowing the candidates
+/// down to those that complete the updated word, if there are any left.
+pub fn insert_char(app: &mut Application) -> Result {
+    restore_insert_mode(app);
+    commands::buffer::insert_char(app)?;
+    show_completions(app)?;
+
+    Ok(())
+}
+
+/// Deletes the character before the cursor as insert mode would,
+/// listing the candidates for the shortened word, if there are any.
+pub fn backspace(app: &mut Application) -> Result {
+    restore_insert_mode(app);
+    commands::buffer::backspace(app)?;
+    show_completions(app)?;
+
+    Ok(())
+}
+
+/// Switches from insert mode to completion mode, listing the words in open
+/// buffers that complete the one preceding the cursor. Stays in insert mode
+/// and returns false if there aren't any (e.g. there's no word to complete).
+pub fn show_completions(app: &mut Application) -> errors::Result<bool> {
+    let (prefix, candidates) = completions(app)?;
+    if candidates.is_empty() {
+        return Ok(false);
+    }
+
+    match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Insert(insert_mode) => {
+            app.mode = Mode::Completion(CompletionMode::new(insert_mode, prefix, candidates));
+
+            Ok(true)
+        }
+        mode => {
+            app.mode = mode;
+
+            Ok(false)
+        }
+    }
+}
+
+/// The word preceding the cursor, and the (best) candidates that complete it.
+fn completions(app: &mut Application) -> errors::Result<(String, Vec<String>)> {
+    let limit = app.preferences.borrow().search_select_config().max_results;
+    let (original_id, data, cursor) = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+        (buffer.id, buffer.data(), *buffer.cursor)
+    };
+    let prefix = data
+        .lines()
+        .nth(cursor.line)
+        .map(|line| completion::prefix(line, cursor.offset))
+        .unwrap_or_default();
+
+    // Gather the content of the other open buffers, returning to the current one.
+    let mut others = Vec::new();
+    loop {
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+            break;
+        }
+        if let Some(buffer) = app.workspace.current_buffer() {
+            others.push(buffer.data());
+        }
+    }
+
+    let mut candidates = completion::candidates(&prefix, &data, &cursor, &others);
+    candidates.truncate(limit);
+
+    Ok((prefix, candidates))
+}
+
+fn restore_insert_mode(app: &mut Application) {
+    match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Completion(mode) => app.mode = Mode::Insert(mode.insert_mode),
+        mode => app.mode = mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use crate::util;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn completion_application(content: &str, cursor: Position) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer.cursor.move_to(cursor);
+        util::add_buffer(buffer, &mut app).unwrap();
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+
+        app
+    }
+
+    #[test]
+    fn switch_to_completion_mode_lists_words_completing_the_one_being_typed() {
+        let mut app = completion_application("amplify ampere\nam", Position{ line: 1, offset: 2 });
+        commands::application::switch_to_completion_mode(&mut app).unwrap();
+
+        match app.mode {
+            Mode::Completion(ref mode) => {
+                assert_eq!(mode.prefix, "am");
+                assert_eq!(mode.candidates().collect::<Vec<&String>>(), vec!["ampere", "amplify"]);
+            }
+            _ => panic!("Not in completion mode"),
+        }
+    }
+
+    #[test]
+    fn switch_to_completion_mode_requires_a_word_to_complete() {
+        let mut app = completion_application("amplify\n", Position{ line: 1, offset: 0 });
+
+        assert!(commands::application::switch_to_completion_mode(&mut app).is_err());
+        assert!(matches!(app.mode, Mode::Insert(_)));
+    }
+
+    #[test]
+    fn accept_inserts_the_rest_of_the_selection_and_returns_to_insert_mode() {
+        let mut app = completion_application("amplify ampere\nam", Position{ line: 1, offset: 2 });
+        commands::application::switch_to_completion_mode(&mut app).unwrap();
+        super::select_next(&mut app).unwrap();
+        super::accept(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amplify ampere\namplify");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 7 });
+        assert!(matches!(app.mode, Mode::Insert(_)));
+    }
+
+    #[test]
+    fn insert_char_narrows_the_candidates() {
+        let mut app = completion_application("amplify ampere\nam", Position{ line: 1, offset: 2 });
+        commands::application::switch_to_completion_mode(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('p'));
+        super::insert_char(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('l'));
+        super::insert_char(&mut app).unwrap();
+
+        match app.mode {
+            Mode::Completion(ref mode) => {
+                assert_eq!(mode.candidates().collect::<Vec<&String>>(), vec!["amplify"]);
+            }
+            _ => panic!("Not in completion mode"),
+        }
+    }
+
+    #[test]
+    fn dismiss_returns_to_insert_mode() {
+        let mut app = completion_application("amplify\nam", Position{ line: 1, offset: 2 });
+        commands::application::switch_to_completion_mode(&mut app).unwrap();
+        super::dismiss(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Insert(_)));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amplify\nam");
+    }
+}
@@ -1,14 +1,79 @@
 use crate::commands::{self, Result};
+use crate::input::Key;
 use crate::models::application::{Application, Mode};
 
+/// Runs the first choice (i.e. "yes", for yes/no prompts).
 pub fn confirm_command(app: &mut Application) -> Result {
-    let command =
+    let key =
       if let Mode::Confirm(ref mode) = app.mode {
-          mode.command
+          mode.choices.first().map(|choice| choice.key).ok_or("No choices available")?
       } else {
           bail!("Can't confirm command outside of confirm mode");
       };
 
+    run_choice(app, key)
+}
+
+/// Runs the choice for the last key pressed.
+pub fn select_choice(app: &mut Application) -> Result {
+    let key = match *app.view.last_key() {
+        Some(Key::Char(c)) => c,
+        _ => bail!("No choice for that key"),
+    };
+
+    run_choice(app, key)
+}
+
+pub fn select_default(app: &mut Application) -> Result {
+    let key =
+      if let Mode::Confirm(ref mode) = app.mode {
+          mode.default_choice().map(|choice| choice.key).ok_or("There's no default choice")?
+      } else {
+          bail!("Can't select choice outside of confirm mode");
+      };
+
+    run_choice(app, key)
+}
+
+fn run_choice(app: &mut Application, key: char) -> Result {
+    // The selection is kept on the mode, so that
+    // the command can refer to its choice's data.
+    let command =
+      if let Mode::Confirm(ref mut mode) = app.mode {
+          mode.select(key).ok_or_else(|| format!("No choice for \"{}\"", key))?
+      } else {
+          bail!("Can't select choice outside of confirm mode");
+      };
     command(app)?;
-    commands::application::switch_to_normal_mode(app)
+
+    // Leave the mode alone if the command changed it (e.g. to exit).
+    if let Mode::Confirm(_) = app.mode {
+        commands::application::switch_to_normal_mode(app)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use crate::models::application::modes::{ConfirmChoice, ConfirmMode};
+
+    #[test]
+    fn select_choice_runs_the_command_for_the_last_key() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.mode = Mode::Confirm(ConfirmMode::with_choices("Which mode?", vec![
+            ConfirmChoice::new('i', "Insert", commands::application::switch_to_insert_mode),
+            ConfirmChoice::new('q', "Quit", commands::application::force_exit),
+        ], Some(0)));
+
+        app.view.last_key = Some(Key::Char('x'));
+        assert!(super::select_choice(&mut app).is_err());
+
+        app.view.last_key = Some(Key::Char('q'));
+        super::select_choice(&mut app).unwrap();
+        assert!(match app.mode { Mode::Exit => true, _ => false });
+    }
 }
@@ -1,30 +1,226 @@
 use crate::errors::*;
 use crate::commands::{self, Result};
-use crate::util::token::{Direction, adjacent_token_position};
-use crate::models::application::Application;
+use crate::input::Key;
+use crate::util::{self, delimiter, directive};
+use crate::util::ctags::{self, Tag};
+use crate::util::token::{self, Direction, adjacent_token_position};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{SearchSelectMode, SymbolJumpMode, TagJumpMode};
+use crate::view::View;
+use scribe::Buffer;
 use scribe::buffer::Position;
+use std::iter;
 use super::{application, buffer};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn move_up(app: &mut Application) -> Result {
+    if moves_by_visual_line(app) {
+        return move_up_visual_line(app);
+    }
+
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_up();
+    step_over_folds(app, Direction::Backward)?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_down(app: &mut Application) -> Result {
+    if moves_by_visual_line(app) {
+        return move_down_visual_line(app);
+    }
+
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_down();
+    step_over_folds(app, Direction::Forward)?;
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+/// Moves the cursor up a single row on-screen, which
+/// may lie within the same (wrapped) logical line.
+pub fn move_up_visual_line(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = *buffer.cursor;
+    let data = buffer.data();
+    let lines: Vec<&str> = data.lines().collect();
+    let line = |line: usize| lines.get(line).cloned().unwrap_or("");
+    let current = WrappedLine::new(line(position.line), &app.view, buffer);
+    let (row, column) = current.position(position.offset);
+
+    let target = if row > 0 {
+        Position{ line: position.line, offset: current.offset(row - 1, column) }
+    } else if position.line > 0 {
+        // Move onto the last row of the previous line.
+        let previous = WrappedLine::new(line(position.line - 1), &app.view, buffer);
+        let (last_row, _) = previous.position(previous.length);
+        Position{
+            line: position.line - 1,
+            offset: previous.offset(last_row, column).min(previous.length),
+        }
+    } else {
+        return Ok(());
+    };
+    buffer.cursor.move_to(target);
+    step_over_folds(app, Direction::Backward)?;
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+/// Moves the cursor down a single row on-screen, which
+/// may lie within the same (wrapped) logical line.
+pub fn move_down_visual_line(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = *buffer.cursor;
+    let data = buffer.data();
+    let lines: Vec<&str> = data.lines().collect();
+    let line = |line: usize| lines.get(line).cloned().unwrap_or("");
+    let current = WrappedLine::new(line(position.line), &app.view, buffer);
+    let (row, column) = current.position(position.offset);
+    let (last_row, _) = current.position(current.length);
+
+    let target = if row < last_row {
+        Position{
+            line: position.line,
+            offset: current.offset(row + 1, column).min(current.length),
+        }
+    } else if position.line + 1 < buffer.line_count() {
+        // Move onto the first row of the next line.
+        let next = WrappedLine::new(line(position.line + 1), &app.view, buffer);
+        Position{
+            line: position.line + 1,
+            offset: next.offset(0, column).min(next.length),
+        }
+    } else {
+        return Ok(());
+    };
+    buffer.cursor.move_to(target);
+    step_over_folds(app, Direction::Forward)?;
+
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+/// Continues moving the cursor in the specified direction until it's no
+/// longer on a folded line. Folds running to the end of the buffer are
+/// stepped back over, onto their first line.
+pub fn step_over_folds(app: &mut Application, mut direction: Direction) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let folds = app.view.folds(buffer).unwrap_or_default();
+    let folded = |line: usize| folds.iter().any(|&(start, end)| start < line && line <= end);
+
+    while folded(buffer.cursor.line) {
+        let line = buffer.cursor.line;
+        match direction {
+            Direction::Forward => buffer.cursor.move_down(),
+            Direction::Backward => buffer.cursor.move_up(),
+        };
+        if buffer.cursor.line == line {
+            direction = Direction::Backward;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether regular vertical movement should follow visual lines.
+fn moves_by_visual_line(app: &mut Application) -> bool {
+    let path = app.workspace.current_buffer().and_then(|buffer| buffer.path.clone());
+    let preferences = app.preferences.borrow();
+
+    preferences.line_wrapping(path.as_ref()) && preferences.wrap_movement()
+}
+
+/// The on-screen layout of a wrapped line. Its first row spans the full
+/// width available to buffer content, while continuation rows are narrowed
+/// by their indentation (see the wrap_indent preference).
+struct WrappedLine {
+    length: usize,
+    width: usize,
+    indent: usize,
+}
+
+impl WrappedLine {
+    fn new(line: &str, view: &View, buffer: &Buffer) -> WrappedLine {
+        WrappedLine {
+            length: line.graphemes(true).count(),
+            width: view.wrapped_line_width(buffer),
+            indent: view.wrap_indent_width(buffer, line),
+        }
+    }
+
+    /// Maps an offset within the line to the on-screen row and column it's
+    /// rendered at. An offset at the very end of the line stays on its last row.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let row_width = self.width - self.indent;
+        let row_of = |offset: usize| if offset < self.width {
+            0
+        } else {
+            1 + (offset - self.width) / row_width
+        };
+        let row = row_of(offset).min(row_of(self.length.checked_sub(1).unwrap_or(0)));
+
+        if row == 0 {
+            (0, offset)
+        } else {
+            (row, self.indent + offset - self.width - (row - 1) * row_width)
+        }
+    }
+
+    /// Maps an on-screen row and column to the offset rendered there;
+    /// the inverse of position. Columns within a continuation row's
+    /// indentation map to its first grapheme.
+    fn offset(&self, row: usize, column: usize) -> usize {
+        if row == 0 {
+            column
+        } else {
+            self.width + (row - 1) * (self.width - self.indent) + column.saturating_sub(self.indent)
+        }
+    }
+}
+
 pub fn move_left(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_left();
+    let wrap = wraps_at_line_boundaries(app);
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = *buffer.cursor;
+    buffer.cursor.move_left();
+
+    // Move to the end of the previous line if we're stuck at the start of this one.
+    if wrap && *buffer.cursor == position && position.line > 0 {
+        buffer.cursor.move_up();
+        buffer.cursor.move_to_end_of_line();
+    }
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_right(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_right();
+    let wrap = wraps_at_line_boundaries(app);
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = *buffer.cursor;
+    buffer.cursor.move_right();
+
+    // Move to the start of the next line if we're stuck at the end of this one.
+    if wrap && *buffer.cursor == position {
+        buffer.cursor.move_to(Position{ line: position.line + 1, offset: 0 });
+    }
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+/// Whether the key that triggered the current horizontal
+/// motion is configured to wrap across line boundaries.
+fn wraps_at_line_boundaries(app: &Application) -> bool {
+    let key = match *app.view.last_key() {
+        Some(Key::Char(c)) => c.to_string(),
+        Some(Key::Left) => String::from("left"),
+        Some(Key::Right) => String::from("right"),
+        _ => return false,
+    };
+
+    app.preferences.borrow().whichwrap(&key)
+}
+
+/// Removes any secondary cursors, leaving only the buffer's own.
+pub fn collapse_cursors(app: &mut Application) -> Result {
+    app.secondary_cursors.clear();
+
+    Ok(())
+}
+
 pub fn move_to_start_of_line(app: &mut Application) -> Result {
     app.workspace
         .current_buffer()
@@ -52,12 +248,55 @@ pub fn move_to_first_line(app: &mut Application) -> Result {
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+/// Moves the cursor to the last line, or the first line of the fold that
+/// hides it, if the buffer ends with a folded block. The column is kept,
+/// unless the last_line_first_word preference moves it to the first word.
 pub fn move_to_last_line(app: &mut Application) -> Result {
     app.workspace
         .current_buffer()
         .ok_or(BUFFER_MISSING)?
         .cursor
         .move_to_last_line();
+    step_over_folds(app, Direction::Backward)?;
+
+    if app.preferences.borrow().last_line_first_word() {
+        let line = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.line;
+        move_to_first_word_of_visible_line(app, line)
+    } else {
+        commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+    }
+}
+
+/// Moves the cursor to the first word of the topmost visible line.
+pub fn move_to_top_of_view(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (first_line, _) = app.view.visible_lines(buffer)?;
+    move_to_first_word_of_visible_line(app, first_line)
+}
+
+/// Moves the cursor to the first word of the middle visible line.
+pub fn move_to_middle_of_view(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (first_line, last_line) = app.view.visible_lines(buffer)?;
+    move_to_first_word_of_visible_line(app, first_line + (last_line - first_line) / 2)
+}
+
+/// Moves the cursor to the first word of the bottommost visible line.
+pub fn move_to_bottom_of_view(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (_, last_line) = app.view.visible_lines(buffer)?;
+    move_to_first_word_of_visible_line(app, last_line)
+}
+
+fn move_to_first_word_of_visible_line(app: &mut Application, line: usize) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let offset = buffer.data()
+        .lines()
+        .nth(line)
+        .and_then(|content| content.chars().position(|character| !character.is_whitespace()))
+        .unwrap_or(0);
+    buffer.cursor.move_to(Position{ line, offset });
+
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
@@ -96,6 +335,7 @@ pub fn move_to_first_word_of_line(app: &mut Application) -> Result {
 }
 
 pub fn insert_at_end_of_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     move_to_end_of_line(app)?;
     application::switch_to_insert_mode(app)?;
     commands::view::scroll_to_cursor(app)?;
@@ -104,6 +344,7 @@ pub fn insert_at_end_of_line(app: &mut Application) -> Result {
 }
 
 pub fn insert_at_first_word_of_line(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     move_to_first_word_of_line(app)?;
     application::switch_to_insert_mode(app)?;
     commands::view::scroll_to_cursor(app)?;
@@ -112,6 +353,7 @@ pub fn insert_at_first_word_of_line(app: &mut Application) -> Result {
 }
 
 pub fn insert_with_newline(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     move_to_end_of_line(app)?;
     buffer::start_command_group(app)?;
     buffer::insert_newline(app)?;
@@ -122,6 +364,7 @@ pub fn insert_with_newline(app: &mut Application) -> Result {
 }
 
 pub fn insert_with_newline_above(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     let current_line_number = app
         .workspace
         .current_buffer()
@@ -190,15 +433,166 @@ pub fn move_to_end_of_current_token(app: &mut Application) -> Result {
 }
 
 pub fn append_to_current_token(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
     move_to_end_of_current_token(app)?;
     application::switch_to_insert_mode(app)
 }
 
+/// Moves the cursor to the definition of the identifier under the cursor,
+/// using the current buffer's symbols. If there are several candidates,
+/// symbol jump mode is opened, pre-filtered to the identifier.
+pub fn move_to_definition(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let identifier = token::identifier_at_cursor(buffer)
+        .ok_or("No identifier found under the cursor")?;
+    let token_set = buffer.tokens()
+        .chain_err(|| "No tokens available for the current buffer")?;
+    let config = app.preferences.borrow().search_select_config();
+    let mut mode = SymbolJumpMode::new(&token_set, app.view.revision(buffer), config);
+    let definitions: Vec<Position> = mode
+        .definitions_of(&identifier)
+        .iter()
+        .map(|symbol| symbol.position)
+        .collect();
+
+    match definitions.len() {
+        0 => bail!("Couldn't find a definition for \"{}\"", identifier),
+        1 => {
+            if !buffer.cursor.move_to(definitions[0]) {
+                bail!("Couldn't move to the definition's position");
+            }
+        },
+        _ => {
+            // Hand the ambiguity over to the user.
+            mode.query().push_str(&identifier);
+            mode.search();
+            mode.set_insert_mode(false);
+            app.mode = Mode::SymbolJump(mode);
+
+            return Ok(());
+        }
+    }
+
+    commands::view::scroll_to_jump_position(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+/// Moves the cursor to the definition of the identifier under the cursor,
+/// as listed in the workspace's ctags index. If there are several
+/// candidates, they're presented for selection in tag jump mode.
+pub fn move_to_tag_definition(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let identifier = token::identifier_at_cursor(buffer)
+        .ok_or("No identifier found under the cursor")?;
+    let mut tags = ctags::definitions(&app.workspace.path, &identifier)?;
+
+    match tags.len() {
+        0 => bail!("Couldn't find a tag for \"{}\"", identifier),
+        1 => jump_to_tag(app, &tags.remove(0))?,
+        _ => {
+            let config = app.preferences.borrow().search_select_config();
+            app.mode = Mode::TagJump(TagJumpMode::new(tags, config));
+
+            return commands::search_select::search(app);
+        }
+    }
+
+    commands::view::scroll_to_jump_position(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+/// Opens the tag's file (or switches to it, if it's already open) and moves
+/// the cursor to the tag's name on the line containing its definition.
+/// Tags pointing at missing files or content are reported as stale.
+pub fn jump_to_tag(app: &mut Application, tag: &Tag) -> Result {
+    let path = app.workspace.path.join(&tag.path);
+    if !path.exists() {
+        bail!("{} doesn't exist; the tags file may be stale", tag.path.display());
+    }
+    if !util::select_buffer(&path, app) {
+        util::open_buffer(&path, app)
+            .chain_err(|| "Couldn't open a buffer for the tag's path.")?;
+    }
+
+    // Large files aren't searched for the tag; they're viewed from the top.
+    if let Mode::LargeFile(_) = app.mode {
+        return Ok(());
+    }
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let line = tag.line(&data).ok_or_else(|| {
+        format!("Couldn't find \"{}\" in {}; the tags file may be stale", tag.name, tag.path.display())
+    })?;
+    let offset = data
+        .lines()
+        .nth(line)
+        .and_then(|content| {
+            content.find(&tag.name).map(|index| content[..index].graphemes(true).count())
+        })
+        .unwrap_or(0);
+
+    if !buffer.cursor.move_to(Position{ line, offset }) {
+        bail!("Couldn't move to the tag's position");
+    }
+
+    Ok(())
+}
+
+/// Moves the cursor to the preprocessor or region directive paired
+/// with the one on the current line (e.g. from #if to #endif).
+pub fn move_to_matching_directive(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let pairs = app.preferences.borrow().directive_pairs(buffer.path.as_ref());
+    let line = directive::matching_directive(&buffer.data(), buffer.cursor.line, &pairs)?;
+    buffer.cursor.move_to(Position{ line, offset: 0 });
+    move_to_first_word_of_line(app)
+}
+
+/// Moves to the bracket matching the one at (or, failing that, following)
+/// the cursor on the current line, ignoring brackets in strings and comments.
+pub fn move_to_matching_bracket(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if let Some(position) = matching_bracket(buffer) {
+        buffer.cursor.move_to(position);
+        commands::view::scroll_to_cursor(app)?;
+    }
+
+    Ok(())
+}
+
+/// Moves to the matching bracket (see move_to_matching_bracket) or, if there
+/// isn't a bracket to match on the current line, to the matching directive.
+pub fn move_to_matching_pair(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    match matching_bracket(buffer) {
+        Some(position) => {
+            buffer.cursor.move_to(position);
+            commands::view::scroll_to_cursor(app)
+        }
+        None => move_to_matching_directive(app),
+    }
+}
+
+fn matching_bracket(buffer: &mut Buffer) -> Option<Position> {
+    let data = buffer.data();
+
+    // Strings and comments are only lexed as far as the search reaches.
+    match buffer.tokens() {
+        Ok(tokens) => delimiter::matching_bracket(&data, &*buffer.cursor, token::string_and_comment_ranges(tokens.iter())),
+        Err(_) => delimiter::matching_bracket(&data, &*buffer.cursor, iter::empty()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use scribe::Buffer;
     use scribe::buffer::Position;
-    use crate::models::application::Application;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode, Preferences};
+    use crate::models::application::modes::SearchSelectMode;
+    use crate::util;
+    use yaml_rust::YamlLoader;
 
     #[test]
     fn move_to_first_word_of_line_works() {
@@ -223,6 +617,36 @@ mod tests {
                    });
     }
 
+    #[test]
+    fn move_to_last_line_keeps_the_column_by_default() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n    editor");
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        super::move_to_last_line(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 2 });
+    }
+
+    #[test]
+    fn move_to_last_line_moves_to_the_first_word_of_the_last_visible_line_when_configured_to() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("last_line_first_word: true").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n  editor\nfn amp() {\n    editor();\n}");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        super::move_to_last_line(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 4, offset: 0 });
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        app.view.fold(buffer, 1, 4).unwrap();
+        super::move_to_last_line(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 2 });
+    }
+
     #[test]
     fn move_to_start_of_previous_token_works() {
         // Set up the application.
@@ -334,7 +758,7 @@ mod tests {
 
         // Ensure that we're in insert mode.
         assert!(match app.mode {
-            crate::models::application::Mode::Insert => true,
+            crate::models::application::Mode::Insert(_) => true,
             _ => false,
         });
     }
@@ -368,11 +792,144 @@ mod tests {
 
         // Ensure that we're in insert mode.
         assert!(match app.mode {
-            crate::models::application::Mode::Insert => true,
+            crate::models::application::Mode::Insert(_) => true,
             _ => false,
         });
     }
 
+    #[test]
+    fn move_to_definition_moves_cursor_to_symbol_definition() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("definition.rs"));
+        buffer.insert("fn amp() {}\namp();");
+        buffer.cursor.move_to(Position { line: 1, offset: 1 });
+        app.workspace.add_buffer(buffer);
+
+        super::move_to_definition(&mut app).unwrap();
+
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor,
+                   Position {
+                       line: 0,
+                       offset: 3,
+                   });
+    }
+
+    #[test]
+    fn move_to_definition_switches_to_symbol_jump_mode_with_multiple_candidates() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("definition.rs"));
+        buffer.insert("fn amp() {}\nfn amp() {}\namp();");
+        buffer.cursor.move_to(Position { line: 2, offset: 1 });
+        app.workspace.add_buffer(buffer);
+
+        super::move_to_definition(&mut app).unwrap();
+
+        match app.mode {
+            Mode::SymbolJump(ref mut mode) => assert_eq!(mode.query(), "amp"),
+            _ => panic!("Application isn't in symbol jump mode."),
+        }
+    }
+
+    #[test]
+    fn move_to_tag_definition_opens_the_tagged_file_at_the_definition() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/ctags"));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("tags"), "amp\tsrc/amp.rs\t/^pub fn amp() {$/;\"\tf\n").unwrap();
+        fs::write(root.join("src/amp.rs"), "// Amp\npub fn amp() {\n}\n").unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.path = root;
+        let mut buffer = Buffer::new();
+        buffer.insert("amp();");
+        util::add_buffer(buffer, &mut app).unwrap();
+        super::move_to_tag_definition(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "// Amp\npub fn amp() {\n}\n");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 7 });
+    }
+
+    #[test]
+    fn move_to_tag_definition_reports_a_missing_tags_file() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.path = PathBuf::from(concat!(env!("OUT_DIR"), "/ctags_missing"));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+
+        let error = super::move_to_tag_definition(&mut app).unwrap_err();
+        assert!(error.to_string().starts_with("No tags file found"));
+    }
+
+    #[test]
+    fn move_left_stops_at_start_of_line_by_default() {
+        let mut app = set_up_application("amp\neditor");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 0 });
+        app.view.last_key = Some(Key::Char('h'));
+        super::move_left(&mut app).unwrap();
+
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+    }
+
+    #[test]
+    fn move_left_and_right_wrap_across_lines_for_configured_keys() {
+        let mut app = set_up_application("amp\neditor");
+        let data = YamlLoader::load_from_str("whichwrap: [h, right]").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 0 });
+        app.view.last_key = Some(Key::Char('h'));
+        super::move_left(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 3 });
+
+        app.view.last_key = Some(Key::Right);
+        super::move_right(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+
+        // Keys left out of the list don't wrap.
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 3 });
+        app.view.last_key = Some(Key::Char('l'));
+        super::move_right(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 3 });
+    }
+
+    #[test]
+    fn move_to_matching_directive_moves_between_nested_directives() {
+        let mut app = set_up_application("#if AMP\n  #ifdef EDITOR\n  #endif\n#endif\n");
+
+        super::move_to_matching_directive(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor,
+                   Position{ line: 3, offset: 0 });
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 0 });
+        super::move_to_matching_directive(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor,
+                   Position{ line: 1, offset: 2 });
+    }
+
+    #[test]
+    fn move_down_and_up_follow_visual_lines_when_configured() {
+        // The test terminal leaves six columns for buffer content.
+        let mut app = set_up_application("abcdefghijkl\nxy");
+        let data = YamlLoader::load_from_str("wrap_movement: true").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 1 });
+
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 7 });
+
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 1 });
+
+        super::move_up(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 7 });
+
+        super::move_up(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 1 });
+    }
+
     fn set_up_application(content: &str) -> Application {
         let mut app = Application::new(&Vec::new()).unwrap();
         let mut buffer = Buffer::new();
@@ -385,4 +942,53 @@ mod tests {
 
         app
     }
+
+    #[test]
+    fn move_to_matching_bracket_moves_to_the_matching_bracket() {
+        let mut app = set_up_application("amp(editor)");
+
+        super::move_to_matching_bracket(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 10 });
+
+        super::move_to_matching_bracket(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 3 });
+    }
+
+    #[test]
+    fn move_to_matching_pair_prefers_brackets_and_falls_back_to_directives() {
+        let mut app = set_up_application("#if (AMP)\n#endif");
+
+        super::move_to_matching_pair(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 8 });
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 0 });
+        super::move_to_matching_pair(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn move_to_matching_bracket_does_nothing_without_a_bracket() {
+        let mut app = set_up_application("amp\n(editor)");
+
+        super::move_to_matching_bracket(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn move_to_top_middle_and_bottom_of_view_use_the_visible_lines() {
+        let content: String = (0..20).map(|line| format!("  {}\n", line)).collect();
+        let mut app = set_up_application(&content);
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 20, offset: 0 });
+        crate::commands::view::scroll_to_cursor(&mut app).unwrap();
+
+        // The 10 line test terminal shows lines 12 through 20.
+        super::move_to_top_of_view(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 12, offset: 2 });
+
+        super::move_to_middle_of_view(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 16, offset: 2 });
+
+        super::move_to_bottom_of_view(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 20, offset: 0 });
+    }
 }
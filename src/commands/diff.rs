@@ -0,0 +1,137 @@
+use crate::errors::*;
+use crate::errors;
+use crate::commands::Result;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{DiffMode, DiffSide};
+use crate::util::{self, git};
+
+/// Compares the buffer in another pane (on the left) against the current one.
+pub fn diff_panes(app: &mut Application) -> Result {
+    let (_, other_pane) = app.view.unfocused_panes().into_iter().next()
+        .ok_or("The view isn't split")?;
+    let current_id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id
+        .ok_or("Buffer doesn't have an id")?;
+    let right = current_side(app)?;
+
+    // Read the other buffer, restoring the current one afterwards.
+    if !util::select_buffer_by_id(other_pane.buffer_id, app) {
+        bail!("Couldn't find the other pane's buffer");
+    }
+    let left = current_side(app);
+    util::select_buffer_by_id(current_id, app);
+
+    app.mode = Mode::Diff(DiffMode::new(left?, right));
+
+    Ok(())
+}
+
+/// Compares the current buffer against its version in the HEAD commit.
+pub fn diff_against_head(app: &mut Application) -> Result {
+    let repo = app.repository.as_ref().ok_or("No repository available")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.as_ref().ok_or(BUFFER_PATH_MISSING)?;
+    let left = DiffSide::new(String::from("HEAD"), &git::head_content(repo, path)?);
+    let right = current_side(app)?;
+
+    app.mode = Mode::Diff(DiffMode::new(left, right));
+
+    Ok(())
+}
+
+pub fn move_down(app: &mut Application) -> Result {
+    diff_mode(app)?.move_rows(1);
+
+    Ok(())
+}
+
+pub fn move_up(app: &mut Application) -> Result {
+    diff_mode(app)?.move_rows(-1);
+
+    Ok(())
+}
+
+pub fn page_down(app: &mut Application) -> Result {
+    let amount = app.view.half_page_height() as isize * 2;
+    diff_mode(app)?.move_rows(amount);
+
+    Ok(())
+}
+
+pub fn page_up(app: &mut Application) -> Result {
+    let amount = app.view.half_page_height() as isize * 2;
+    diff_mode(app)?.move_rows(-amount);
+
+    Ok(())
+}
+
+pub fn next_hunk(app: &mut Application) -> Result {
+    diff_mode(app)?.next_hunk().ok_or("No more differences")?;
+
+    Ok(())
+}
+
+pub fn previous_hunk(app: &mut Application) -> Result {
+    diff_mode(app)?.previous_hunk().ok_or("No earlier differences")?;
+
+    Ok(())
+}
+
+/// Stops comparing buffers, returning to the current one.
+pub fn close(app: &mut Application) -> Result {
+    diff_mode(app)?;
+    app.mode = Mode::Normal;
+
+    Ok(())
+}
+
+/// The current buffer's content, titled after its path.
+fn current_side(app: &mut Application) -> errors::Result<DiffSide> {
+    let title = app.workspace
+        .current_buffer_path()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("untitled"));
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+    Ok(DiffSide::new(title, &buffer.data()))
+}
+
+fn diff_mode(app: &mut Application) -> errors::Result<&mut DiffMode> {
+    match app.mode {
+        Mode::Diff(ref mut mode) => Ok(mode),
+        _ => bail!("Not viewing a diff"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Mode};
+    use crate::util;
+    use scribe::Buffer;
+
+    #[test]
+    fn diff_panes_compares_the_other_pane_against_the_current_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        util::add_buffer(buffer, &mut app).unwrap();
+        assert!(super::diff_panes(&mut app).is_err());
+        commands::view::split_vertically(&mut app).unwrap();
+
+        // The focused pane shows a second buffer.
+        let mut other_buffer = Buffer::new();
+        other_buffer.insert("amp\ntext editor\nin rust\n");
+        util::add_buffer(other_buffer, &mut app).unwrap();
+
+        super::diff_panes(&mut app).unwrap();
+        match app.mode {
+            Mode::Diff(ref mode) => {
+                assert_eq!(mode.left.lines, vec!["amp", "editor"]);
+                assert_eq!(mode.right.lines, vec!["amp", "text editor", "in rust"]);
+                assert_eq!(mode.hunks(), vec![1]);
+            }
+            _ => panic!("Not in diff mode"),
+        }
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ntext editor\nin rust\n");
+    }
+}
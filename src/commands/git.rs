@@ -2,8 +2,11 @@ use crate::errors::*;
 use crate::errors;
 use crate::commands::{self, Result};
 use crate::models::application::{Application, ClipboardContent, Mode};
+use crate::util::{self, git};
+use crate::util::conflict::{self, Conflict};
 use git2;
 use regex::Regex;
+use scribe::buffer::{Position, Range};
 
 pub fn add(app: &mut Application) -> Result {
     let repo = app.repository.as_ref().ok_or("No repository available")?;
@@ -94,6 +97,127 @@ pub fn copy_remote_url(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn blame(app: &mut Application) -> Result {
+    // Blame is only meaningful for files inside the repository.
+    let repo = match app.repository {
+        Some(ref repo) => repo,
+        None => return Ok(()),
+    };
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let buffer_path = buffer.path.as_ref().ok_or(BUFFER_PATH_MISSING)?;
+    let relative_path = match git::relative_path(repo, buffer_path) {
+        Ok(path) => path,
+        Err(_) => return Ok(()),
+    };
+
+    let blame = repo.blame_file(&relative_path, None).chain_err(|| {
+        "Couldn't blame the current file"
+    })?;
+    let mut content = match blame.get_line(buffer.cursor.line + 1) {
+        Some(ref hunk) if hunk.final_commit_id() != git2::Oid::zero() => {
+            let commit = repo.find_commit(hunk.final_commit_id()).chain_err(|| {
+                "Couldn't find the commit for the current line"
+            })?;
+            let author = commit.author();
+
+            format!(
+                "{}: {}",
+                author.name().unwrap_or("Unknown author"),
+                commit.summary().unwrap_or("")
+            )
+        }
+        _ => String::from("Not committed yet"),
+    };
+
+    // Blame is based on the committed file; it may not match unsaved changes.
+    if buffer.modified() {
+        content.push_str(" (modified)");
+    }
+    app.message = Some(content);
+
+    Ok(())
+}
+
+pub fn move_to_next_conflict(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let conflict = conflict::conflicts(&buffer.data())
+        .into_iter()
+        .find(|conflict| conflict.start > buffer.cursor.line)
+        .ok_or("No merge conflicts below the cursor")?;
+    buffer.cursor.move_to(Position{ line: conflict.start, offset: 0 });
+
+    commands::view::scroll_to_jump_position(app)
+}
+
+pub fn move_to_previous_conflict(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let conflict = conflict::conflicts(&buffer.data())
+        .into_iter()
+        .rev()
+        .find(|conflict| conflict.start < buffer.cursor.line)
+        .ok_or("No merge conflicts above the cursor")?;
+    buffer.cursor.move_to(Position{ line: conflict.start, offset: 0 });
+
+    commands::view::scroll_to_jump_position(app)
+}
+
+pub fn accept_ours(app: &mut Application) -> Result {
+    resolve_conflict(app, |conflict, lines| conflict.ours(lines))
+}
+
+pub fn accept_theirs(app: &mut Application) -> Result {
+    resolve_conflict(app, |conflict, lines| conflict.theirs(lines))
+}
+
+pub fn accept_both(app: &mut Application) -> Result {
+    resolve_conflict(app, |conflict, lines| {
+        let mut content = conflict.ours(lines);
+        content.extend(conflict.theirs(lines));
+
+        content
+    })
+}
+
+/// Replaces the conflict under the cursor (markers included)
+/// with the lines selected from it, as a single operation.
+fn resolve_conflict<F>(app: &mut Application, select_lines: F) -> Result
+    where F: for<'a> Fn(&Conflict, &[&'a str]) -> Vec<&'a str>
+{
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let lines: Vec<&str> = data.split('\n').collect();
+    let conflict = conflict::conflicts(&data)
+        .into_iter()
+        .find(|conflict| conflict.includes(buffer.cursor.line))
+        .ok_or("The cursor isn't inside of a merge conflict")?;
+
+    // Extend the range to the start of the line after the conflict,
+    // falling back to the end of its last line if there isn't one.
+    let followed_by_line = conflict.end + 1 < lines.len();
+    let end = if followed_by_line {
+        Position{ line: conflict.end + 1, offset: 0 }
+    } else {
+        Position{ line: conflict.end, offset: lines[conflict.end].chars().count() }
+    };
+    let mut content: String = select_lines(&conflict, &lines)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect();
+    if !followed_by_line {
+        content.pop();
+    }
+
+    let start = Position{ line: conflict.start, offset: 0 };
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(Range::new(start, end));
+    buffer.cursor.move_to(start);
+    buffer.insert(content);
+    app.view.end_undo_group(buffer)?;
+
+    commands::view::scroll_to_cursor(app)
+}
+
 fn get_gh_path(url: &str) -> errors::Result<&str> {
     lazy_static! {
         static ref REGEX: Regex =
@@ -104,15 +228,93 @@ fn get_gh_path(url: &str) -> errors::Result<&str> {
     })
 }
 
-#[test]
-fn test_get_gh_path() {
-    let cases = [
-        ("git@github.com:jmacdonald/amp.git", "jmacdonald/amp"),
-        ("https://github.com/jmacdonald/amp.git", "jmacdonald/amp"),
-        ("https://github.com/jmacdonald/amp", "jmacdonald/amp"),
-    ];
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::Application;
+    use git2::{Repository, Signature};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::fs;
+    use std::path::{Path, PathBuf};
 
-    cases.iter().for_each(|(url, expected_gh_path)| {
-        assert_eq!(&get_gh_path(url).unwrap(), expected_gh_path)
-    })
+    #[test]
+    fn test_get_gh_path() {
+        let cases = [
+            ("git@github.com:jmacdonald/amp.git", "jmacdonald/amp"),
+            ("https://github.com/jmacdonald/amp.git", "jmacdonald/amp"),
+            ("https://github.com/jmacdonald/amp", "jmacdonald/amp"),
+        ];
+
+        cases.iter().for_each(|(url, expected_gh_path)| {
+            assert_eq!(&super::get_gh_path(url).unwrap(), expected_gh_path)
+        })
+    }
+
+    #[test]
+    fn accept_theirs_replaces_conflict_with_incoming_lines() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\neditor\n");
+        buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        super::accept_theirs(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ntheirs\neditor\n");
+
+        // The resolution should be undone as a single operation.
+        app.workspace.current_buffer().unwrap().undo();
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "amp\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\neditor\n"
+        );
+    }
+
+    #[test]
+    fn accept_both_keeps_both_sides_of_conflict() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch");
+        app.workspace.add_buffer(buffer);
+
+        super::accept_both(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "ours\ntheirs");
+    }
+
+    #[test]
+    fn move_to_next_conflict_moves_cursor_to_conflict_start() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n");
+        app.workspace.add_buffer(buffer);
+
+        super::move_to_next_conflict(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+        assert!(super::move_to_next_conflict(&mut app).is_err());
+    }
+
+    #[test]
+    fn blame_reports_the_author_and_summary_for_paths_with_relative_components() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/git_blame"));
+        let _ = fs::remove_dir_all(&path);
+        let repo = Repository::init(&path).unwrap();
+        let signature = Signature::now("amp", "amp@example.com").unwrap();
+        fs::create_dir_all(path.join("src")).unwrap();
+        fs::write(path.join("amp.txt"), "amp\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("amp.txt")).unwrap();
+        index.write().unwrap();
+        let tree_id = index.write_tree().unwrap();
+        {
+            let tree = repo.find_tree(tree_id).unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[]).unwrap();
+        }
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.repository = Some(repo);
+        app.workspace.add_buffer(Buffer::from_file(&path.join("src/../amp.txt")).unwrap());
+        commands::git::blame(&mut app).unwrap();
+
+        assert_eq!(app.message, Some(String::from("amp: initial")));
+    }
 }
@@ -10,17 +10,11 @@ use scribe::Workspace;
 pub fn match_tag(app: &mut Application) -> Result {
     let result =
         if let Mode::Jump(ref mut jump_mode) = app.mode {
-            match jump_mode.input.len() {
-                0 => return Ok(()), // Not enough data to match to a position.
-                1 => {
-                    if jump_mode.first_phase {
-                        jump_to_tag(jump_mode, &mut app.workspace)
-                    } else {
-                        return Ok(()) // Not enough data to match to a position.
-                    }
-                },
-                _ => jump_to_tag(jump_mode, &mut app.workspace),
+            if jump_mode.input.is_empty() || jump_mode.is_tag_prefix(&jump_mode.input) {
+                return Ok(()) // Not enough data to match to a position.
             }
+
+            jump_to_tag(jump_mode, &mut app.workspace)
         } else {
             bail!("Can't match jump tags outside of jump mode.");
         };
@@ -0,0 +1,161 @@
+use crate::errors;
+use crate::commands::Result;
+use crate::input::Key;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::LargeFileMode;
+
+pub fn move_down(app: &mut Application) -> Result {
+    large_file_mode(app)?.move_lines(1);
+
+    Ok(())
+}
+
+pub fn move_up(app: &mut Application) -> Result {
+    large_file_mode(app)?.move_lines(-1);
+
+    Ok(())
+}
+
+pub fn page_down(app: &mut Application) -> Result {
+    let amount = app.view.half_page_height() as isize * 2;
+    large_file_mode(app)?.move_lines(amount);
+
+    Ok(())
+}
+
+pub fn page_up(app: &mut Application) -> Result {
+    let amount = app.view.half_page_height() as isize * 2;
+    large_file_mode(app)?.move_lines(-amount);
+
+    Ok(())
+}
+
+pub fn move_to_first_line(app: &mut Application) -> Result {
+    large_file_mode(app)?.move_to_line(0);
+
+    Ok(())
+}
+
+pub fn move_to_last_line(app: &mut Application) -> Result {
+    let mode = large_file_mode(app)?;
+    let last_line = mode.file.line_count().saturating_sub(1);
+    mode.move_to_line(last_line);
+
+    Ok(())
+}
+
+/// Starts typing a search query, shown in the status line.
+pub fn start_search(app: &mut Application) -> Result {
+    large_file_mode(app)?.input = Some(String::new());
+
+    Ok(())
+}
+
+pub fn push_search_char(app: &mut Application) -> Result {
+    let character = match *app.view.last_key() {
+        Some(Key::Char(c)) => c,
+        _ => bail!("Last key press wasn't a character"),
+    };
+    if let Some(ref mut input) = large_file_mode(app)?.input {
+        input.push(character);
+    }
+
+    Ok(())
+}
+
+pub fn pop_search_char(app: &mut Application) -> Result {
+    if let Some(ref mut input) = large_file_mode(app)?.input {
+        input.pop();
+    }
+
+    Ok(())
+}
+
+pub fn cancel_search(app: &mut Application) -> Result {
+    large_file_mode(app)?.input = None;
+
+    Ok(())
+}
+
+/// Searches for the typed query, scanning the file from the cursor.
+pub fn accept_search(app: &mut Application) -> Result {
+    let mode = large_file_mode(app)?;
+    let query = mode.input.take().ok_or("No search query to accept")?;
+    if query.is_empty() {
+        return Ok(());
+    }
+    mode.query = Some(query);
+
+    next_match(app)
+}
+
+pub fn next_match(app: &mut Application) -> Result {
+    let mode = large_file_mode(app)?;
+    let query = mode.query.clone().ok_or("No search query")?;
+    let position = mode.file
+        .find_next(&query, &mode.cursor)?
+        .ok_or_else(|| format!("No matches found for \"{}\"", query))?;
+    mode.cursor = position;
+
+    Ok(())
+}
+
+pub fn previous_match(app: &mut Application) -> Result {
+    let mode = large_file_mode(app)?;
+    let query = mode.query.clone().ok_or("No search query")?;
+    let position = mode.file
+        .find_previous(&query, &mode.cursor)?
+        .ok_or_else(|| format!("No matches found for \"{}\"", query))?;
+    mode.cursor = position;
+
+    Ok(())
+}
+
+/// Stops viewing the file, returning to the open buffers.
+pub fn close(app: &mut Application) -> Result {
+    large_file_mode(app)?;
+    app.mode = Mode::Normal;
+
+    Ok(())
+}
+
+fn large_file_mode(app: &mut Application) -> errors::Result<&mut LargeFileMode> {
+    match app.mode {
+        Mode::LargeFile(ref mut mode) => Ok(mode),
+        _ => bail!("Not viewing a large file"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use crate::models::application::modes::LargeFileMode;
+    use crate::util::large_file::LargeFile;
+    use scribe::buffer::Position;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn accept_search_moves_to_the_next_match() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/large_file_search"));
+        fs::write(path, "amp\neditor\nthe amp editor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.mode = Mode::LargeFile(LargeFileMode::new(LargeFile::open(path).unwrap()));
+
+        super::start_search(&mut app).unwrap();
+        for character in "amp".chars() {
+            app.view.last_key = Some(Key::Char(character));
+            super::push_search_char(&mut app).unwrap();
+        }
+        super::accept_search(&mut app).unwrap();
+
+        match app.mode {
+            Mode::LargeFile(ref mode) => {
+                assert_eq!(mode.cursor, Position{ line: 2, offset: 4 });
+                assert!(mode.input.is_none());
+            }
+            _ => panic!("Not in large file mode"),
+        }
+    }
+}
@@ -1,52 +1,49 @@
 use crate::errors::*;
 use crate::input::Key;
 use crate::commands::{self, Result};
+use crate::util::token::Direction;
 use crate::models::application::{Application, Mode};
 use scribe::buffer::Position;
 
 pub fn accept_input(app: &mut Application) -> Result {
     if let Mode::LineJump(ref mode) = app.mode {
-        // Try parsing an integer from the input.
-        let line_number = mode
-            .input
-            .parse::<usize>()
-            .chain_err(|| "Couldn't parse a line number from the provided input.")?;
-
-        // Ignore zero-value line numbers.
-        if line_number > 0 {
-            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-
-            // Input values won't be zero-indexed; map the value so
-            // that we can use it for a zero-indexed buffer position.
-            let target_line = line_number - 1;
-
-            // Build an ideal target position to which we'll try moving.
-            let mut target_position = Position {
-                line: target_line,
-                offset: buffer.cursor.offset,
-            };
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+        // Resolve the input (a line number, relative offset, or percentage)
+        // to a zero-indexed line, clamped to the buffer's lines.
+        let target_line = mode
+            .target_line(buffer.cursor.line, buffer.line_count())
+            .ok_or("Couldn't parse a line number from the provided input.")?;
+
+        // Build an ideal target position to which we'll try moving.
+        let mut target_position = Position {
+            line: target_line,
+            offset: buffer.cursor.offset,
+        };
 
-            if !buffer.cursor.move_to(target_position) {
-                // Moving to that position failed. It may be because the
-                // current offset doesn't exist there. Try falling back
-                // to the end of the target line.
-                let line_content = buffer
-                    .data()
-                    .lines()
-                    .nth(target_line)
-                    .map(|line| line.to_string())
-                    .ok_or("Couldn't find the specified line")?;
-
-                target_position.offset = line_content.len();
-                buffer.cursor.move_to(target_position);
-            }
+        if !buffer.cursor.move_to(target_position) {
+            // Moving to that position failed. It may be because the
+            // current offset doesn't exist there. Try falling back
+            // to the end of the target line.
+            let line_content = buffer
+                .data()
+                .lines()
+                .nth(target_line)
+                .map(|line| line.to_string())
+                .ok_or("Couldn't find the specified line")?;
+
+            target_position.offset = line_content.len();
+            buffer.cursor.move_to(target_position);
         }
     } else {
         bail!("Can't accept line jump input outside of line jump mode.");
     }
 
+    // Land on the first line of any fold hiding the target line.
+    commands::cursor::step_over_folds(app, Direction::Backward)?;
+
     commands::application::switch_to_normal_mode(app)?;
-    commands::view::scroll_cursor_to_center(app)?;
+    commands::view::scroll_to_jump_position(app)?;
 
     Ok(())
 }
@@ -152,6 +149,26 @@ mod tests {
         });
     }
 
+    #[test]
+    fn accept_input_moves_by_relative_offsets_and_percentages() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("1\n2\n3\n4\n5\n6\n7\n8\n9\n10");
+        buffer.cursor.move_to(Position { line: 4, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        for &(input, line) in &[("+2", 6), ("-10", 0), ("50%", 4), ("+100", 9)] {
+            commands::application::switch_to_line_jump_mode(&mut app).unwrap();
+            match app.mode {
+                Mode::LineJump(ref mut mode) => mode.input = input.to_string(),
+                _ => (),
+            };
+            commands::line_jump::accept_input(&mut app).unwrap();
+
+            assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, line);
+        }
+    }
+
     #[test]
     fn accept_input_ignores_zero_input() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -0,0 +1,118 @@
+use crate::commands::{self, Result};
+use crate::input::Key;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{MacroAction, MacroMode};
+
+// Bounds macros that (directly or indirectly) play themselves.
+const PLAYBACK_DEPTH_LIMIT: usize = 20;
+
+/// Stops recording the current macro or, if
+/// we're not recording, prompts for a register to record into.
+pub fn toggle_recording(app: &mut Application) -> Result {
+    if let Some((register, count)) = app.macros.stop_recording() {
+        app.message = Some(format!("Recorded {} keys into macro \"{}\"", count, register));
+    } else {
+        app.mode = Mode::Macro(MacroMode::new(MacroAction::Record));
+    }
+
+    Ok(())
+}
+
+/// Prompts for the register of a macro to play.
+pub fn switch_to_playback_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Macro(MacroMode::new(MacroAction::Play));
+
+    Ok(())
+}
+
+/// Records or plays the macro in the typed register, returning to normal
+/// mode first. Keys other than letters and digits (e.g. escape) cancel.
+pub fn select_register(app: &mut Application) -> Result {
+    let action = match app.mode {
+        Mode::Macro(ref mode) => mode.action,
+        _ => bail!("Can't select a macro register outside of macro mode"),
+    };
+    app.mode = Mode::Normal;
+
+    let register = match *app.view.last_key() {
+        Some(Key::Char(name)) if name.is_ascii_alphanumeric() => name,
+        _ => return Ok(()),
+    };
+
+    match action {
+        MacroAction::Record => {
+            app.macros.start_recording(register);
+            app.message = Some(format!("Recording macro \"{}\"", register));
+
+            Ok(())
+        }
+        MacroAction::Play => play(app, register),
+    }
+}
+
+/// Feeds the macro's keys through regular input handling,
+/// stopping at the first command that fails.
+fn play(app: &mut Application, register: char) -> Result {
+    let keys = app.macros
+        .keys(register)
+        .ok_or_else(|| format!("Macro \"{}\" hasn't been recorded", register))?
+        .to_vec();
+    if app.macros.playback_depth >= PLAYBACK_DEPTH_LIMIT {
+        bail!("Macro playback is nested too deeply");
+    }
+
+    app.macros.playback_depth += 1;
+    let result = keys.into_iter().try_for_each(|key| {
+        app.view.last_key = Some(key);
+        commands::application::handle_input(app)
+    });
+    app.macros.playback_depth -= 1;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use scribe::Buffer;
+
+    fn press(app: &mut Application, key: Key) -> commands::Result {
+        app.view.last_key = Some(key);
+        commands::application::handle_input(app)
+    }
+
+    #[test]
+    fn recorded_macros_replay_their_keys() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        for key in vec![Key::Char('Z'), Key::Char('a'), Key::Char('i'), Key::Char('x'), Key::Esc, Key::Char('Z')] {
+            press(&mut app, key).unwrap();
+        }
+        assert_eq!(app.message, Some(String::from("Recorded 3 keys into macro \"a\"")));
+
+        press(&mut app, Key::Char('@')).unwrap();
+        press(&mut app, Key::Char('a')).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "xx");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn self_referencing_macros_stop_at_the_playback_limit() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        // Playing the macro while recording it fails, but is still recorded.
+        press(&mut app, Key::Char('Z')).unwrap();
+        press(&mut app, Key::Char('a')).unwrap();
+        press(&mut app, Key::Char('@')).unwrap();
+        assert!(press(&mut app, Key::Char('a')).is_err());
+        press(&mut app, Key::Char('Z')).unwrap();
+
+        press(&mut app, Key::Char('@')).unwrap();
+        assert!(press(&mut app, Key::Char('a')).is_err());
+        assert_eq!(app.macros.playback_depth, 0);
+    }
+}
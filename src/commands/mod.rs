@@ -4,15 +4,24 @@ use std::collections::HashMap;
 
 pub mod application;
 pub mod buffer;
+pub mod completion;
 pub mod confirm;
 pub mod cursor;
+pub mod diff;
 pub mod git;
 pub mod jump;
+pub mod large_file;
 pub mod line_jump;
+pub mod macros;
+pub mod operator;
 pub mod path;
 pub mod preferences;
+pub mod recent;
+pub mod replace;
 pub mod search;
 pub mod selection;
+pub mod shell;
+pub mod snippet;
 pub mod search_select;
 pub mod view;
 pub mod workspace;
@@ -0,0 +1,135 @@
+use crate::errors::*;
+use crate::input::Key;
+use crate::commands::{self, Result};
+use std::mem;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{FindMotion, Operator};
+use crate::util;
+
+pub fn find_next_char(app: &mut Application) -> Result {
+    set_motion(app, FindMotion::Find)
+}
+
+pub fn till_next_char(app: &mut Application) -> Result {
+    set_motion(app, FindMotion::Till)
+}
+
+pub fn find_previous_char(app: &mut Application) -> Result {
+    set_motion(app, FindMotion::FindBackward)
+}
+
+pub fn till_previous_char(app: &mut Application) -> Result {
+    set_motion(app, FindMotion::TillBackward)
+}
+
+/// Applies the pending operator over the range between the cursor
+/// and the last key's character, as determined by the mode's motion
+/// and count (e.g. "3Xf," deletes through the third comma).
+pub fn apply(app: &mut Application) -> Result {
+    let (operator, motion, count) = match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Operator(mode) => (
+            mode.operator,
+            mode.motion.ok_or("Can't apply an operator without a motion")?,
+            mode.count
+        ),
+        _ => bail!("Can't apply an operator outside of operator mode"),
+    };
+    util::ensure_writable(app)?;
+    let target = match *app.view.last_key() {
+        Some(Key::Char(c)) => c.to_string(),
+        _ => bail!("Operator motions require a character target"),
+    };
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+    let position = motion
+        .target(line, *buffer.cursor, &target, count)
+        .ok_or_else(|| format!("Couldn't find \"{}\" on the current line", target))?;
+
+    commands::application::switch_to_select_mode(app)?;
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_to(position);
+    commands::selection::copy_and_delete(app)?;
+    commands::application::switch_to_normal_mode(app)?;
+
+    if operator == Operator::Change {
+        commands::application::switch_to_insert_mode(app)?;
+    }
+    commands::view::scroll_to_cursor(app)
+}
+
+fn set_motion(app: &mut Application, motion: FindMotion) -> Result {
+    if let Mode::Operator(ref mut mode) = app.mode {
+        mode.motion = Some(motion);
+    } else {
+        bail!("Can't set a motion outside of operator mode");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn delete_till_removes_content_up_to_the_target() {
+        let mut app = set_up_application("f(amp, editor)");
+        commands::application::switch_to_delete_operator_mode(&mut app).unwrap();
+        super::till_next_char(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char(')'));
+        super::apply(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "f()");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn change_find_removes_the_target_and_enters_insert_mode() {
+        let mut app = set_up_application("f(amp, editor)");
+        commands::application::switch_to_change_operator_mode(&mut app).unwrap();
+        super::find_next_char(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char(','));
+        super::apply(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "f( editor)");
+        assert!(match app.mode { Mode::Insert(_) => true, _ => false });
+    }
+
+    #[test]
+    fn apply_leaves_the_buffer_untouched_when_the_target_is_missing() {
+        let mut app = set_up_application("f(amp, editor)");
+        commands::application::switch_to_delete_operator_mode(&mut app).unwrap();
+        super::find_next_char(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char(';'));
+
+        assert!(super::apply(&mut app).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "f(amp, editor)");
+    }
+
+    #[test]
+    fn counted_delete_find_removes_through_the_counted_target() {
+        let mut app = set_up_application("f(amp, editor, buffer, view)");
+        for key in vec![Key::Char('3'), Key::Char('X'), Key::Char('f'), Key::Char(',')] {
+            app.view.last_key = Some(key);
+            commands::application::handle_input(&mut app).unwrap();
+        }
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "f( view)");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    fn set_up_application(content: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+}
@@ -1,8 +1,11 @@
 use crate::errors::*;
+use crate::errors;
 use crate::commands::{self, Result};
 use crate::input::Key;
 use crate::models::application::{Application, Mode};
-use std::path::PathBuf;
+use crate::models::application::modes::{ConfirmChoice, ConfirmMode, PathMode};
+use crate::util;
+use std::path::{Path, PathBuf};
 
 pub fn push_char(app: &mut Application) -> Result {
     let last_key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
@@ -27,38 +30,120 @@ pub fn pop_char(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn complete(app: &mut Application) -> Result {
+    if let Mode::Path(ref mut mode) = app.mode {
+        mode.complete();
+    } else {
+        bail!("Cannot complete path outside of path mode");
+    }
+    Ok(())
+}
+
+/// Points the buffer to the path, saving it if requested. Saving over another
+/// existing file needs to be confirmed first, and directories are rejected.
 pub fn accept_path(app: &mut Application) -> Result {
-    let save_on_accept =
-        if let Mode::Path(ref mut mode) = app.mode {
-            let current_buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-            let path_name = mode.input.clone();
-            if path_name.is_empty() {
+    let (path, save_on_accept) =
+        if let Mode::Path(ref mode) = app.mode {
+            if mode.input.is_empty() {
                 bail!("Please provide a non-empty path")
             }
-            current_buffer.path = Some(PathBuf::from(path_name));
-            mode.save_on_accept
+            (mode.path(), mode.save_on_accept)
         } else {
             bail!("Cannot accept path outside of path mode");
         };
+    if path.is_dir() {
+        bail!("{} is a directory", path.display());
+    }
+
+    if save_on_accept && overwrites_another_file(app, &path)? {
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        app.mode = Mode::Confirm(ConfirmMode::with_choices(
+            format!("{} already exists; overwrite it?", name),
+            vec![
+                ConfirmChoice::new('y', "Overwrite", overwrite).with_path(path.clone()),
+                ConfirmChoice::new('n', "Choose another path", edit_path).with_path(path),
+            ],
+            Some(1)
+        ));
+
+        return Ok(());
+    }
+
+    set_path(app, path, save_on_accept)
+}
+
+/// Saves the buffer over the existing file it's being confirmed for.
+pub fn overwrite(app: &mut Application) -> Result {
+    let path = confirmed_path(app)?;
+
+    set_path(app, path, true)
+}
+
+/// Returns to path mode from an overwrite confirmation, to choose another path.
+pub fn edit_path(app: &mut Application) -> Result {
+    let path = confirmed_path(app)?;
+    let mut mode = PathMode::new(path.to_string_lossy().into_owned());
+    mode.save_on_accept = true;
+    app.mode = Mode::Path(mode);
+
+    Ok(())
+}
+
+/// Prompts for a new path for the current buffer, saving it there.
+pub fn save_as(app: &mut Application) -> Result {
+    commands::application::switch_to_path_mode(app)?;
+    if let Mode::Path(ref mut mode) = app.mode {
+        mode.save_on_accept = true;
+    }
 
+    Ok(())
+}
+
+fn set_path(app: &mut Application, path: PathBuf, save: bool) -> Result {
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.path = Some(path);
     app.workspace.update_current_syntax().chain_err(||
         "Failed to update buffer's syntax definition"
     )?;
     app.mode = Mode::Normal;
 
-    if save_on_accept {
+    if save {
         commands::buffer::save(app)
     } else {
         Ok(())
     }
 }
 
+/// Whether the path refers to an existing file other than the current buffer's.
+fn overwrites_another_file(app: &mut Application, path: &Path) -> errors::Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+    Ok(buffer.path.as_ref().map(|current_path| {
+        util::canonical_path(current_path) != util::canonical_path(path)
+    }).unwrap_or(true))
+}
+
+fn confirmed_path(app: &Application) -> errors::Result<PathBuf> {
+    match app.mode {
+        Mode::Confirm(ref mode) => mode
+            .selection()
+            .and_then(|choice| choice.path.clone())
+            .ok_or_else(|| "No path to confirm".into()),
+        _ => bail!("Can't confirm path outside of confirm mode"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::commands;
+    use crate::input::Key;
     use crate::models::Application;
     use crate::models::application::Mode;
     use scribe::Buffer;
+    use std::env;
+    use std::fs;
     use std::path::{PathBuf, Path};
 
     #[test]
@@ -88,6 +173,8 @@ mod tests {
 
     #[test]
     fn accept_path_respects_save_on_accept_flag() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "new_path"));
+        let _ = std::fs::remove_file(path);
         let mut app = Application::new(&Vec::new()).unwrap();
 
         let buffer = Buffer::new();
@@ -96,7 +183,7 @@ mod tests {
         // Switch to the mode, add a name, set the flag, and accept it.
         commands::application::switch_to_path_mode(&mut app).unwrap();
         if let Mode::Path(ref mut mode) = app.mode {
-            mode.input = Path::new(concat!(env!("OUT_DIR"), "new_path")).to_string_lossy().into();
+            mode.input = path.to_string_lossy().into();
             mode.save_on_accept = true;
         }
         super::accept_path(&mut app).unwrap();
@@ -127,6 +214,68 @@ mod tests {
 
     }
 
+    #[test]
+    fn accept_path_confirms_before_saving_over_another_file() {
+        let path = concat!(env!("OUT_DIR"), "/existing_save_target.txt");
+        fs::write(path, "editor").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+
+        commands::path::save_as(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = String::from(path);
+        }
+        super::accept_path(&mut app).unwrap();
+        assert!(match app.mode { Mode::Confirm(_) => true, _ => false });
+        assert!(app.workspace.current_buffer().unwrap().path.is_none());
+
+        // Choosing another path returns to path mode with the input intact.
+        app.view.last_key = Some(Key::Char('n'));
+        commands::confirm::select_choice(&mut app).unwrap();
+        assert!(match app.mode { Mode::Path(ref mode) => mode.input == path, _ => false });
+
+        super::accept_path(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('y'));
+        commands::confirm::select_choice(&mut app).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "amp\n");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn accept_path_rejects_directories() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        commands::path::save_as(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = String::from(env!("OUT_DIR"));
+        }
+
+        assert!(super::accept_path(&mut app).is_err());
+        assert!(app.workspace.current_buffer().unwrap().path.is_none());
+    }
+
+    #[test]
+    fn accept_path_expands_a_leading_tilde() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        let buffer = Buffer::new();
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_path_mode(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = String::from("~/new_path");
+        }
+        super::accept_path(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().path,
+            Some(PathBuf::from(env::var_os("HOME").unwrap()).join("new_path"))
+        );
+    }
+
     #[test]
     fn accept_path_updates_syntax() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1,5 +1,6 @@
 use crate::commands::Result;
 use crate::models::application::{Application, Preferences};
+use crate::presenters;
 use crate::util;
 
 pub fn edit(app: &mut Application) -> Result {
@@ -7,6 +8,156 @@ pub fn edit(app: &mut Application) -> Result {
     util::add_buffer(preference_buffer, app)
 }
 
+/// Re-reads the preferences files, applying their changes to the open
+/// buffers. If they can't be read or parsed, the current ones are kept.
 pub fn reload(app: &mut Application) -> Result {
-    app.preferences.borrow_mut().reload()
+    app.preferences.borrow_mut().reload()?;
+    app.view.update_mouse_reporting();
+
+    // Highlighting is cached per-theme, and the theme may have changed.
+    app.view.clear_render_caches();
+
+    app.message = Some(
+        presenters::status_line::unknown_segments_warning(&app.preferences.borrow())
+            .unwrap_or_else(|| String::from("Reloaded preferences"))
+    );
+
+    Ok(())
+}
+
+/// Switches between the light and dark variants of the current theme,
+/// based on their names (e.g. solarized_dark and solarized_light). If the
+/// counterpart isn't available, the configured light_theme or dark_theme
+/// preference is used instead.
+pub fn toggle_theme_variant(app: &mut Application) -> Result {
+    let current_theme = app.preferences.borrow().theme().to_string();
+    let (variant, counterpart) = theme_counterpart(&current_theme).ok_or_else(||
+        format!("Couldn't tell if the \"{}\" theme is light or dark", current_theme)
+    )?;
+
+    let theme = if app.view.theme_set.themes.contains_key(&counterpart) {
+        counterpart
+    } else {
+        app.preferences
+            .borrow()
+            .theme_variant(variant)
+            .filter(|theme| app.view.theme_set.themes.contains_key(theme))
+            .ok_or_else(|| format!("Couldn't find a {} variant of the \"{}\" theme", variant, current_theme))?
+    };
+    app.view.set_theme(&theme);
+
+    Ok(())
+}
+
+/// Switches line wrapping on or off for every buffer, for the rest of the session.
+pub fn toggle_line_wrapping(app: &mut Application) -> Result {
+    let path = app.workspace.current_buffer().and_then(|buffer| buffer.path.clone());
+    let wrapping = app.preferences.borrow().line_wrapping(path.as_ref());
+    app.preferences.borrow_mut().set_line_wrapping(!wrapping);
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.view.scroll_to_cursor(buffer)?;
+    }
+
+    Ok(())
+}
+
+/// Shows or hides tabs, trailing spaces, and line endings, for the rest of the session.
+pub fn toggle_whitespace(app: &mut Application) -> Result {
+    let show = app.preferences.borrow().show_whitespace();
+    app.preferences.borrow_mut().set_show_whitespace(!show);
+
+    Ok(())
+}
+
+/// Determines the opposite variant ("light" or "dark") of the named theme,
+/// and the name its counterpart would have, swapping the last occurrence.
+fn theme_counterpart(theme: &str) -> Option<(&'static str, String)> {
+    let light = theme.rfind("light");
+    let dark = theme.rfind("dark");
+    let (index, current, opposite) = match (light, dark) {
+        (Some(l), Some(d)) if l > d => (l, "light", "dark"),
+        (_, Some(d)) => (d, "dark", "light"),
+        (Some(l), None) => (l, "light", "dark"),
+        (None, None) => return None,
+    };
+
+    Some((opposite, format!("{}{}{}", &theme[..index], opposite, &theme[index + current.len()..])))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::{Application, Preferences};
+    use super::theme_counterpart;
+    use std::fs;
+    use std::path::Path;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn theme_counterpart_swaps_the_variant_suffix() {
+        assert_eq!(theme_counterpart("solarized_dark"), Some(("light", String::from("solarized_light"))));
+        assert_eq!(theme_counterpart("solarized-light"), Some(("dark", String::from("solarized-dark"))));
+        assert_eq!(theme_counterpart("monokai"), None);
+    }
+
+    #[test]
+    fn toggle_line_wrapping_and_whitespace_flip_the_in_memory_preferences() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let wrapping = app.preferences.borrow().line_wrapping(None);
+        let whitespace = app.preferences.borrow().show_whitespace();
+
+        super::toggle_line_wrapping(&mut app).unwrap();
+        super::toggle_whitespace(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().line_wrapping(None), !wrapping);
+        assert_eq!(app.preferences.borrow().show_whitespace(), !whitespace);
+
+        super::toggle_line_wrapping(&mut app).unwrap();
+        super::toggle_whitespace(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().line_wrapping(None), wrapping);
+        assert_eq!(app.preferences.borrow().show_whitespace(), whitespace);
+    }
+
+    #[test]
+    fn toggle_theme_variant_switches_to_the_counterpart_theme() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.preferences.borrow_mut().set_theme("solarized_dark");
+
+        super::toggle_theme_variant(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), "solarized_light");
+
+        super::toggle_theme_variant(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), "solarized_dark");
+    }
+
+    #[test]
+    fn toggle_theme_variant_falls_back_to_the_configured_variant() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("theme: amp_dark\nlight_theme: solarized_light").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        super::toggle_theme_variant(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), "solarized_light");
+    }
+
+    #[test]
+    fn reload_keeps_the_current_preferences_when_parsing_fails() {
+        // Create an on-disk preferences file first, if one doesn't already exist.
+        if Preferences::load().is_err() {
+            Preferences::edit().unwrap().save().unwrap();
+        }
+
+        // Use a project file, rather than overwriting the user's preferences.
+        let directory = Path::new(concat!(env!("OUT_DIR"), "/reload_preferences"));
+        fs::create_dir_all(directory).unwrap();
+        fs::write(directory.join(".amp.yml"), "tab_width: 7").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.preferences.borrow_mut().load_project(directory).unwrap();
+        assert_eq!(app.preferences.borrow().tab_width(None), 7);
+
+        fs::write(directory.join(".amp.yml"), "tab_width: [7").unwrap();
+        let error = super::reload(&mut app).unwrap_err();
+
+        assert_eq!(error.to_string(), "Couldn't parse project preferences file");
+        assert_eq!(app.preferences.borrow().tab_width(None), 7);
+        assert_ne!(app.message, Some(String::from("Reloaded preferences")));
+    }
 }
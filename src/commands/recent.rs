@@ -0,0 +1,129 @@
+use crate::errors::*;
+use crate::input::Key;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use std::path::{Path, PathBuf};
+use crate::util;
+
+pub fn push_char(app: &mut Application) -> Result {
+    let key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
+
+    if let Key::Char(c) = *key {
+        if let Mode::Recent(ref mut mode) = app.mode {
+            mode.input.push(c)
+        } else {
+            bail!("Can't push pattern character outside of recent mode")
+        }
+    } else {
+        bail!("Last key press wasn't a character")
+    }
+
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::Recent(ref mut mode) = app.mode {
+        mode.input.pop()
+    } else {
+        bail!("Can't pop pattern character outside of recent mode")
+    };
+
+    Ok(())
+}
+
+/// Opens the most recently used file whose path contains the entered
+/// pattern, skipping the current buffer so that repeated use toggles
+/// between files. Matches on the file name take precedence over those
+/// found elsewhere in the path.
+pub fn open_match(app: &mut Application) -> Result {
+    let pattern = match app.mode {
+        Mode::Recent(ref mode) => mode.input.to_lowercase(),
+        _ => bail!("Can't open a recent file outside of recent mode"),
+    };
+    if pattern.is_empty() {
+        bail!("Please provide part of a recent file's path");
+    }
+
+    let current_path = app.workspace
+        .current_buffer()
+        .and_then(|buffer| buffer.path.as_ref())
+        .map(|path| util::canonical_path(path));
+    let workspace_path = util::canonical_path(&app.workspace.path);
+    let candidates: Vec<&PathBuf> = app.recent_paths
+        .iter()
+        .filter(|path| Some(*path) != current_path.as_ref())
+        .collect();
+
+    let file_name_match = candidates.iter().find(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_lowercase().contains(&pattern))
+            .unwrap_or(false)
+    });
+    let path = file_name_match
+        .or_else(|| candidates.iter().find(|path| {
+            relative_path(path, &workspace_path).to_lowercase().contains(&pattern)
+        }))
+        .map(|path| (*path).clone())
+        .ok_or_else(|| format!("No recent file matching \"{}\"", pattern))?;
+
+    commands::application::switch_to_normal_mode(app)?;
+    if !util::select_buffer(&path, app) {
+        util::open_buffer(&path, app)
+            .chain_err(|| "Couldn't open a buffer for the matching path.")?;
+    }
+    app.track_recent_path();
+
+    Ok(())
+}
+
+/// Paths inside the workspace are matched relative to it, so that
+/// its own location doesn't produce spurious matches.
+fn relative_path(path: &Path, workspace_path: &Path) -> String {
+    path.strip_prefix(workspace_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Mode};
+    use std::path::Path;
+
+    #[test]
+    fn open_match_opens_the_most_recent_file_matching_the_pattern() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(Path::new("Cargo.toml")).unwrap();
+        app.track_recent_path();
+        app.workspace.open_buffer(Path::new("Cargo.lock")).unwrap();
+        app.track_recent_path();
+
+        commands::application::switch_to_recent_mode(&mut app).unwrap();
+        if let Mode::Recent(ref mut mode) = app.mode {
+            mode.input = String::from("cargo");
+        }
+        super::open_match(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().path.as_ref().unwrap().canonicalize().unwrap(),
+            Path::new("Cargo.toml").canonicalize().unwrap()
+        );
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn open_match_reports_when_no_recent_file_matches() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(Path::new("Cargo.toml")).unwrap();
+        app.track_recent_path();
+
+        commands::application::switch_to_recent_mode(&mut app).unwrap();
+        if let Mode::Recent(ref mut mode) = app.mode {
+            mode.input = String::from("missing");
+        }
+
+        assert!(super::open_match(&mut app).is_err());
+        assert!(match app.mode { Mode::Recent(_) => true, _ => false });
+    }
+}
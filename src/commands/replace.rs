@@ -0,0 +1,221 @@
+use crate::errors::*;
+use crate::input::Key;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::util;
+use scribe::buffer::Distance;
+
+pub fn push_char(app: &mut Application) -> Result {
+    let key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
+
+    if let Key::Char(c) = *key {
+        if let Mode::Replace(ref mut mode) = app.mode {
+            mode.input_mut().push(c);
+        } else {
+            bail!("Can't push replace character outside of replace mode");
+        }
+    } else {
+        bail!("Last key press wasn't a character")
+    }
+
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.input_mut().pop();
+    } else {
+        bail!("Can't pop replace character outside of replace mode");
+    }
+
+    Ok(())
+}
+
+/// Moves from entering the query to entering its replacement,
+/// and from there, to confirming the first match.
+pub fn accept_input(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        if mode.search.input.as_ref().map(|q| q.is_empty()).unwrap_or(true) {
+            bail!(SEARCH_QUERY_MISSING);
+        }
+
+        if mode.replacement.is_none() {
+            mode.replacement = Some(String::new());
+            return Ok(());
+        }
+        mode.search.validate_query()?;
+        mode.search.insert = false;
+    } else {
+        bail!("Can't accept replace input outside of replace mode");
+    }
+
+    // Group all of the replacements into a single undo step,
+    // closed when switching back to normal mode.
+    commands::buffer::start_command_group(app)?;
+    move_to_next_match(app)
+}
+
+/// Replaces the current match and moves to the next one.
+pub fn replace_current(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    if let Mode::Replace(ref mut mode) = app.mode {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+        // Re-run the search before replacing anything, in case the buffer changed.
+        let range = mode.find_next(buffer)?.ok_or(NO_SEARCH_RESULTS)?;
+        let matched = buffer.read(&range).unwrap_or_default();
+        let replacement = mode.search.expand_replacement(
+            &matched,
+            mode.replacement.as_ref().map(|r| r.as_str()).unwrap_or("")
+        )?;
+        buffer.delete_range(range.clone());
+        buffer.cursor.move_to(range.start());
+        if !replacement.is_empty() {
+            buffer.insert(replacement.as_str());
+        }
+
+        // Continue after the replacement, so that it isn't matched itself.
+        buffer.cursor.move_to(range.start() + Distance::of_str(&replacement));
+        mode.replaced += 1;
+    } else {
+        bail!("Can't replace match outside of replace mode");
+    }
+
+    move_to_next_match(app)
+}
+
+/// Leaves the current match as-is and moves to the next one.
+pub fn skip_current(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let range = mode.find_next(buffer)?.ok_or(NO_SEARCH_RESULTS)?;
+        buffer.cursor.move_to(range.end());
+    } else {
+        bail!("Can't skip match outside of replace mode");
+    }
+
+    move_to_next_match(app)
+}
+
+/// Replaces the current match and all of those following it.
+pub fn replace_all(app: &mut Application) -> Result {
+    while let Mode::Replace(_) = app.mode {
+        replace_current(app)?;
+    }
+
+    Ok(())
+}
+
+/// Moves the cursor to the next match, finishing once there are none left.
+fn move_to_next_match(app: &mut Application) -> Result {
+    let (next_match, replaced) = if let Mode::Replace(ref mut mode) = app.mode {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let next_match = mode.find_next(buffer)?;
+        if let Some(ref range) = next_match {
+            buffer.cursor.move_to(range.start());
+        }
+
+        (next_match, mode.replaced)
+    } else {
+        bail!("Can't move to match outside of replace mode");
+    };
+
+    if next_match.is_some() {
+        commands::view::scroll_to_jump_position(app)
+            .chain_err(|| SCROLL_TO_CURSOR_FAILED)
+    } else {
+        commands::application::switch_to_normal_mode(app)?;
+        app.message = Some(match replaced {
+            1 => String::from("Replaced 1 match"),
+            count => format!("Replaced {} matches", count),
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Mode};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn replace_mode_app(data: &str, query: &str, replacement: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(data);
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_replace_mode(&mut app).unwrap();
+        if let Mode::Replace(ref mut mode) = app.mode {
+            mode.search.input = Some(String::from(query));
+            mode.replacement = Some(String::from(replacement));
+        }
+        super::accept_input(&mut app).unwrap();
+
+        app
+    }
+
+    #[test]
+    fn accept_input_moves_from_the_query_to_the_replacement() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        commands::application::switch_to_replace_mode(&mut app).unwrap();
+
+        assert!(super::accept_input(&mut app).is_err());
+        if let Mode::Replace(ref mut mode) = app.mode {
+            mode.search.input = Some(String::from("amp"));
+        }
+        super::accept_input(&mut app).unwrap();
+
+        if let Mode::Replace(ref mode) = app.mode {
+            assert_eq!(mode.replacement, Some(String::new()));
+            assert!(mode.insert_mode());
+        } else {
+            panic!("Not in replace mode");
+        }
+    }
+
+    #[test]
+    fn replace_current_and_skip_current_step_through_matches() {
+        let mut app = replace_mode_app("amp\namp\namp", "amp", "editor");
+
+        super::replace_current(&mut app).unwrap();
+        super::skip_current(&mut app).unwrap();
+        super::replace_current(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\namp\neditor");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+        assert_eq!(app.message, Some(String::from("Replaced 2 matches")));
+    }
+
+    #[test]
+    fn replace_current_uses_the_latest_buffer_content() {
+        let mut app = replace_mode_app("amp amp", "amp", "a");
+
+        // Edit the buffer mid-replace, shifting the remaining match.
+        app.workspace.current_buffer().unwrap().insert("xx");
+        super::replace_current(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "xxa amp");
+    }
+
+    #[test]
+    fn replace_all_deletes_matches_with_an_empty_replacement() {
+        let mut app = replace_mode_app("amp editor amp", "amp", "");
+        super::replace_all(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), " editor ");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 8 });
+    }
+
+    #[test]
+    fn replace_all_does_not_match_replacements() {
+        let mut app = replace_mode_app("amp amp", "amp", "amp amp");
+        super::replace_all(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp amp amp amp");
+    }
+}
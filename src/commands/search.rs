@@ -2,31 +2,76 @@ use crate::errors::*;
 use crate::input::Key;
 use crate::commands::{self, Result};
 use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{SearchDirection, SearchMode};
 
+const SEARCH_HIT_BOTTOM: &str = "Search hit BOTTOM";
+const SEARCH_HIT_TOP: &str = "Search hit TOP";
+
+/// Moves to the previous result in the search direction
+/// (i.e. the following result when searching backward).
 pub fn move_to_previous_result(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
-        mode.results.as_mut().ok_or(NO_SEARCH_RESULTS)?.select_previous();
+        let direction = mode.direction.reverse();
+        step(mode, direction)?;
     } else {
         bail!("Can't move to search result outside of search mode");
     }
 
-    commands::view::scroll_cursor_to_center(app)
+    commands::view::scroll_to_jump_position(app)
         .chain_err(|| SCROLL_TO_CURSOR_FAILED)?;
     move_to_current_result(app)
 }
 
+/// Moves to the next result in the search direction
+/// (i.e. the preceding result when searching backward).
 pub fn move_to_next_result(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
-        mode.results.as_mut().ok_or(NO_SEARCH_RESULTS)?.select_next();
+        let direction = mode.direction;
+        step(mode, direction)?;
     } else {
         bail!("Can't move to search result outside of search mode");
     }
 
-    commands::view::scroll_cursor_to_center(app)
+    commands::view::scroll_to_jump_position(app)
         .chain_err(|| SCROLL_TO_CURSOR_FAILED)?;
     move_to_current_result(app)
 }
 
+/// Selects the adjacent result in the specified direction, wrapping
+/// around the ends of the buffer only if configured to do so.
+fn step(mode: &mut SearchMode, direction: SearchDirection) -> Result {
+    let wrap = mode.config.wrap;
+    let results = mode.results.as_mut().ok_or(NO_SEARCH_RESULTS)?;
+
+    match direction {
+        SearchDirection::Forward => {
+            if !wrap && results.selected_index() + 1 >= results.len() {
+                bail!(SEARCH_HIT_BOTTOM);
+            }
+            results.select_next();
+        }
+        SearchDirection::Backward => {
+            if !wrap && results.selected_index() == 0 {
+                bail!(SEARCH_HIT_TOP);
+            }
+            results.select_previous();
+        }
+    }
+
+    Ok(())
+}
+
+/// Flips the direction in which results are stepped through.
+pub fn toggle_direction(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.direction = mode.direction.reverse();
+    } else {
+        bail!("Can't change search direction outside of search mode");
+    }
+
+    Ok(())
+}
+
 pub fn move_to_current_result(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -41,7 +86,7 @@ pub fn move_to_current_result(app: &mut Application) -> Result {
         bail!("Can't move to search result outside of search mode");
     }
 
-    commands::view::scroll_cursor_to_center(app)
+    commands::view::scroll_to_jump_position(app)
         .chain_err(|| SCROLL_TO_CURSOR_FAILED)?;
 
     Ok(())
@@ -49,8 +94,16 @@ pub fn move_to_current_result(app: &mut Application) -> Result {
 
 pub fn accept_query(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
+        // Stay in insert sub-mode if the query can't be used
+        // (e.g. an invalid regex), so that it can be corrected.
+        mode.validate_query()?;
+
         // Disable insert sub-mode.
         mode.insert = false;
+
+        if let Some(ref query) = mode.input {
+            app.search_history.add(query);
+        }
     } else {
         bail!("Can't accept search query outside of search mode");
     }
@@ -59,6 +112,22 @@ pub fn accept_query(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Like accept_query, but moves to the closest result before the cursor.
+pub fn accept_query_reverse(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.validate_query()?;
+        mode.insert = false;
+
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        mode.search(&buffer)?;
+    } else {
+        bail!("Can't accept search query outside of search mode");
+    }
+
+    select_closest_previous_result(app)?;
+    move_to_current_result(app)
+}
+
 pub fn clear_query(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         mode.input = None;
@@ -70,6 +139,31 @@ pub fn clear_query(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn previous_query(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        if let Some(query) = app.search_history.previous() {
+            mode.input = Some(query.to_string());
+            app.search_query = mode.input.clone();
+        }
+    } else {
+        bail!("Can't recall search query outside of search mode");
+    }
+
+    Ok(())
+}
+
+pub fn next_query(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        // Stepping past the newest entry leaves an empty query.
+        mode.input = Some(app.search_history.next().unwrap_or("").to_string());
+        app.search_query = mode.input.clone();
+    } else {
+        bail!("Can't recall search query outside of search mode");
+    }
+
+    Ok(())
+}
+
 pub fn push_search_char(app: &mut Application) -> Result {
     let key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
 
@@ -102,15 +196,20 @@ pub fn pop_search_char(app: &mut Application) -> Result {
 }
 
 pub fn run(app: &mut Application) -> Result {
-    if let Mode::Search(ref mut mode) = app.mode {
+    let direction = if let Mode::Search(ref mut mode) = app.mode {
         // Search the buffer.
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
         mode.search(&buffer)?;
+
+        mode.direction
     } else {
         bail!("Can't run search outside of search mode");
-    }
+    };
 
-    select_closest_result(app)?;
+    match direction {
+        SearchDirection::Forward => select_closest_result(app)?,
+        SearchDirection::Backward => select_closest_previous_result(app)?,
+    }
     move_to_current_result(app)
 }
 
@@ -124,6 +223,9 @@ fn select_closest_result(app: &mut Application) -> Result {
             .iter()
             .filter(|r| r.start() <= *buffer.cursor)
             .count();
+        if !mode.config.wrap && !results.is_empty() && skip_count == results.len() {
+            bail!(SEARCH_HIT_BOTTOM);
+        }
         for _ in 0..skip_count {
             results.select_next();
         }
@@ -132,6 +234,34 @@ fn select_closest_result(app: &mut Application) -> Result {
     Ok(())
 }
 
+fn select_closest_previous_result(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let results = mode.results.as_mut().ok_or(NO_SEARCH_RESULTS)?;
+
+        let previous_count = results
+            .iter()
+            .filter(|r| r.start() < *buffer.cursor)
+            .count();
+        if results.is_empty() {
+            return Ok(());
+        } else if previous_count == 0 {
+            if !mode.config.wrap {
+                bail!(SEARCH_HIT_TOP);
+            }
+
+            // Wrap around to the last result.
+            results.select_previous();
+        } else {
+            for _ in 1..previous_count {
+                results.select_next();
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use scribe::Buffer;
@@ -285,4 +415,148 @@ mod tests {
                        offset: 0,
                    });
     }
+
+    #[test]
+    fn move_to_next_result_stops_at_the_last_result_without_wrapping() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nedit\nedit");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input = Some(String::from("ed"));
+            mode.config.wrap = false;
+        }
+        commands::search::accept_query(&mut app).unwrap();
+
+        let error = commands::search::move_to_next_result(&mut app).unwrap_err();
+        assert_eq!(error.to_string(), "Search hit BOTTOM");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 2, offset: 0 });
+    }
+
+    #[test]
+    fn move_to_previous_result_stops_at_the_first_result_without_wrapping() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nedit\nedit");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input = Some(String::from("ed"));
+            mode.config.wrap = false;
+        }
+        commands::search::accept_query(&mut app).unwrap();
+
+        let error = commands::search::move_to_previous_result(&mut app).unwrap_err();
+        assert_eq!(error.to_string(), "Search hit TOP");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 4 });
+    }
+
+    #[test]
+    fn accept_query_reverse_moves_to_the_closest_previous_result() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nedit\nedit");
+        buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        app.search_query = Some(String::from("ed"));
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::accept_query_reverse(&mut app).unwrap();
+
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+    }
+
+    #[test]
+    fn previous_query_and_next_query_cycle_through_accepted_queries() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+
+        for query in &["amp", "editor"] {
+            commands::application::switch_to_search_mode(&mut app).unwrap();
+            if let Mode::Search(ref mut mode) = app.mode {
+                mode.input = Some(query.to_string());
+            }
+            commands::search::accept_query(&mut app).unwrap();
+        }
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::previous_query(&mut app).unwrap();
+        commands::search::previous_query(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("amp")));
+
+        commands::search::next_query(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("editor")));
+
+        commands::search::next_query(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::new()));
+    }
+
+    #[test]
+    fn reverse_search_moves_to_the_closest_previous_result_and_steps_backward() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nedit\nedit");
+        buffer.cursor.move_to(Position{ line: 2, offset: 2 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_reverse_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input = Some(String::from("ed"));
+        }
+        commands::search::accept_query(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 2, offset: 0 });
+
+        commands::search::move_to_next_result(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+
+        commands::search::move_to_previous_result(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 2, offset: 0 });
+    }
+
+    #[test]
+    fn reverse_search_stops_at_the_first_result_without_wrapping() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nedit\nedit");
+        buffer.cursor.move_to(Position{ line: 1, offset: 2 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_reverse_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input = Some(String::from("ed"));
+            mode.config.wrap = false;
+        }
+        commands::search::accept_query(&mut app).unwrap();
+        commands::search::move_to_next_result(&mut app).unwrap();
+
+        let error = commands::search::move_to_next_result(&mut app).unwrap_err();
+        assert_eq!(error.to_string(), "Search hit TOP");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 4 });
+    }
+
+    #[test]
+    fn toggle_direction_reverses_next_and_previous() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nedit\nedit");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input = Some(String::from("ed"));
+        }
+        commands::search::accept_query(&mut app).unwrap();
+        commands::search::move_to_next_result(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+
+        commands::search::toggle_direction(&mut app).unwrap();
+        commands::search::move_to_next_result(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 4 });
+    }
 }
@@ -4,7 +4,13 @@ use crate::input::Key;
 use std::mem;
 use crate::models::application::modes::open::DisplayablePath;
 use crate::models::application::{Application, Mode};
-use crate::models::application::modes::SearchSelectMode;
+use crate::models::application::modes::{SearchSelectMode, command_name, history_entry};
+use crate::util;
+use encoding_rs::Encoding;
+use git2::Repository;
+use scribe::buffer::Position;
+use std::env;
+use std::path::{Path, PathBuf};
 
 pub fn accept(app: &mut Application) -> Result {
     // Consume the application mode. This is necessary because the selection in
@@ -13,26 +19,60 @@ pub fn accept(app: &mut Application) -> Result {
     let mut app_mode = mem::replace(&mut app.mode, Mode::Normal);
 
     match app_mode {
+        Mode::Bookmark(ref mut mode) => {
+            let bookmark = mode.selection().ok_or("No bookmark selected")?;
+            if !util::select_buffer_by_id(bookmark.buffer_id, app) {
+                bail!("The bookmarked buffer is no longer open");
+            }
+
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            if !buffer.cursor.move_to(Position{ line: bookmark.line, offset: 0 }) {
+                bail!("Couldn't move to the selected bookmark's position");
+            }
+        },
+        Mode::BufferList(ref mut mode) => {
+            let entry = mode.selection().ok_or("No buffer selected")?;
+            if !util::select_buffer_by_id(entry.buffer_id, app) {
+                bail!("The selected buffer is no longer open");
+            }
+        },
         Mode::Command(ref mode) => {
             let selection = mode.selection().ok_or("No command selected")?;
 
-            // Run the selected command.
-            (selection.command)(app)?;
+            // Run the selected command, recording whether it failed.
+            let result = (selection.command)(app);
+            app.command_history.add(&history_entry(selection.description, result.is_err()));
+            result?;
         },
         Mode::Open(ref mut mode) => {
             let &DisplayablePath(ref path) = mode
                 .selection()
                 .ok_or("Couldn't find a selected path to open")?;
 
-            app.workspace
-                .open_buffer(&path)
-                .chain_err(|| "Couldn't open a buffer for the specified path.")?;
-            app.view.initialize_buffer(app.workspace.current_buffer().unwrap())?;
+            let path = app.workspace.path.join(path);
+            if mode.read_on_accept {
+                return commands::buffer::insert_file(app, &path);
+            }
+
+            open_path(app, &path)?;
+        },
+        Mode::RecentFiles(ref mut mode) => {
+            let path = PathBuf::from(mode.selection().ok_or("No recent file selected")?);
+            if !path.exists() {
+                bail!("The selected file no longer exists");
+            }
+
+            // Files outside of the workspace can optionally move it along with them.
+            let switch_directory = app.preferences.borrow().recent_files_switch_directory();
+            if switch_directory && !path.starts_with(&app.workspace.path) {
+                switch_workspace(app, &path)?;
+            }
 
+            open_path(app, &path)?;
         },
         Mode::Theme(ref mut mode) => {
             let theme_key = mode.selection().ok_or("No theme selected")?;
-            app.preferences.borrow_mut().set_theme(theme_key.as_str());
+            app.view.set_theme(theme_key);
         },
         Mode::SymbolJump(ref mut mode) => {
             let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -47,67 +87,210 @@ pub fn accept(app: &mut Application) -> Result {
         },
         Mode::Syntax(ref mut mode) => {
             let name = mode.selection().ok_or("No syntax selected")?;
-            let syntax =
-                app.workspace.syntax_set.find_syntax_by_name(name).and_then(|s|
-                    Some(s.clone())
-                );
-            let mut buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-            buffer.syntax_definition = syntax;
+            let syntax = app.workspace.syntax_set
+                .find_syntax_by_name(name)
+                .cloned()
+                .ok_or_else(|| format!("Couldn't find the \"{}\" syntax", name))?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            buffer.syntax_definition = Some(syntax);
+
+            // Apply the new filetype's preferences, and re-highlight
+            // the buffer, since its cached render state is out of date.
+            app.preferences.borrow_mut().set_syntax(buffer);
+            app.view.clear_render_cache(buffer)?;
+        },
+        Mode::Spelling(ref mut mode) => {
+            util::ensure_writable(app)?;
+            let correction = mode.selection().ok_or("No correction selected")?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            app.view.start_undo_group(buffer)?;
+            buffer.delete_range(mode.range.clone());
+            buffer.cursor.move_to(mode.range.start());
+            buffer.insert(correction.as_str());
+            app.view.end_undo_group(buffer)?;
+        },
+        Mode::TagJump(ref mut mode) => {
+            let entry = mode.selection().ok_or("No definition selected")?;
+            commands::cursor::jump_to_tag(app, &entry.tag)?;
+        },
+        Mode::WorkspaceSearch(ref mut mode) => {
+            let entry = mode.selection().ok_or("No match selected")?;
+            let path = app.workspace.path.join(&entry.path);
+            if !util::select_buffer(&path, app) {
+                util::open_buffer(&path, app)
+                    .chain_err(|| "Couldn't open a buffer for the selected match.")?;
+            }
+
+            if let Mode::LargeFile(ref mut mode) = app.mode {
+                mode.move_to_line(entry.line);
+            } else {
+                let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                if !buffer.cursor.move_to(Position{ line: entry.line, offset: 0 }) {
+                    bail!("Couldn't move to the selected match; the file may have changed");
+                }
+            }
+        },
+        Mode::Encoding(ref mut mode) => {
+            let name = mode.selection().ok_or("No encoding selected")?;
+            let encoding = Encoding::for_label(name.as_bytes())
+                .ok_or_else(|| format!("Unknown encoding: {}", name))?;
+            commands::buffer::reload_with_encoding(app, encoding)?;
         },
         _ => bail!("Can't accept selection outside of search select mode."),
     }
 
-    commands::view::scroll_cursor_to_center(app).ok();
+    commands::view::scroll_to_jump_position(app).ok();
+
+    Ok(())
+}
+
+/// Opens the path in a new buffer, or selects it if it's already open
+/// and buffers are being reused. Large files are opened in their own mode.
+fn open_path(app: &mut Application, path: &Path) -> Result {
+    util::open_buffer(path, app)
+        .chain_err(|| "Couldn't open a buffer for the specified path.")?;
+
+    Ok(())
+}
+
+/// Moves the workspace to the repository containing the file,
+/// or to its directory if it isn't part of one.
+fn switch_workspace(app: &mut Application, file: &Path) -> Result {
+    let directory = file.parent().ok_or("Couldn't find the file's directory")?;
+    let repository = Repository::discover(directory).ok();
+    let workspace_path = repository.as_ref()
+        .and_then(|repo| repo.workdir())
+        .unwrap_or(directory)
+        .to_path_buf();
+
+    env::set_current_dir(&workspace_path)
+        .chain_err(|| "Couldn't change to the file's directory")?;
+    app.preferences.borrow_mut().change_project(&workspace_path)?;
+    app.view.update_mouse_reporting();
+    app.workspace.path = workspace_path;
+    app.repository = repository;
 
     Ok(())
 }
 
 pub fn search(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Bookmark(ref mut mode) => mode.search(),
+        Mode::BufferList(ref mut mode) => mode.search(),
         Mode::Command(ref mut mode) => mode.search(),
         Mode::Open(ref mut mode) => mode.search(),
+        Mode::RecentFiles(ref mut mode) => mode.search(),
         Mode::Theme(ref mut mode) => mode.search(),
         Mode::SymbolJump(ref mut mode) => mode.search(),
         Mode::Syntax(ref mut mode) => mode.search(),
+        Mode::Spelling(ref mut mode) => mode.search(),
+        Mode::TagJump(ref mut mode) => mode.search(),
+        Mode::WorkspaceSearch(ref mut mode) => mode.search(),
+        Mode::Encoding(ref mut mode) => mode.search(),
         _ => bail!("Can't search outside of search select mode."),
     };
 
+    // Themes are only previewed once a query has narrowed them down.
+    let narrowed_themes = match app.mode {
+        Mode::Theme(ref mut mode) => !mode.query().is_empty(),
+        _ => false,
+    };
+    if narrowed_themes {
+        preview_theme(app);
+    }
+
     Ok(())
 }
 
+/// Recalls the previously run command in command mode.
+pub fn previous_command(app: &mut Application) -> Result {
+    if let Mode::Command(ref mut mode) = app.mode {
+        if let Some(entry) = app.command_history.previous() {
+            *mode.query() = command_name(entry).to_string();
+        }
+    } else {
+        bail!("Can't recall command outside of command mode");
+    }
+
+    search(app)
+}
+
+/// Recalls the next (more recently) run command in command mode.
+pub fn next_command(app: &mut Application) -> Result {
+    if let Mode::Command(ref mut mode) = app.mode {
+        *mode.query() = command_name(app.command_history.next().unwrap_or("")).to_string();
+    } else {
+        bail!("Can't recall command outside of command mode");
+    }
+
+    search(app)
+}
+
 pub fn select_next(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Bookmark(ref mut mode) => mode.select_next(),
+        Mode::BufferList(ref mut mode) => mode.select_next(),
         Mode::Command(ref mut mode) => mode.select_next(),
         Mode::Open(ref mut mode) => mode.select_next(),
+        Mode::RecentFiles(ref mut mode) => mode.select_next(),
         Mode::Theme(ref mut mode) => mode.select_next(),
         Mode::SymbolJump(ref mut mode) => mode.select_next(),
         Mode::Syntax(ref mut mode) => mode.select_next(),
+        Mode::Spelling(ref mut mode) => mode.select_next(),
+        Mode::TagJump(ref mut mode) => mode.select_next(),
+        Mode::WorkspaceSearch(ref mut mode) => mode.select_next(),
+        Mode::Encoding(ref mut mode) => mode.select_next(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
+    preview_theme(app);
 
     Ok(())
 }
 
 pub fn select_previous(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Bookmark(ref mut mode) => mode.select_previous(),
+        Mode::BufferList(ref mut mode) => mode.select_previous(),
         Mode::Command(ref mut mode) => mode.select_previous(),
         Mode::Open(ref mut mode) => mode.select_previous(),
+        Mode::RecentFiles(ref mut mode) => mode.select_previous(),
         Mode::Theme(ref mut mode) => mode.select_previous(),
         Mode::SymbolJump(ref mut mode) => mode.select_previous(),
         Mode::Syntax(ref mut mode) => mode.select_previous(),
+        Mode::Spelling(ref mut mode) => mode.select_previous(),
+        Mode::TagJump(ref mut mode) => mode.select_previous(),
+        Mode::WorkspaceSearch(ref mut mode) => mode.select_previous(),
+        Mode::Encoding(ref mut mode) => mode.select_previous(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
+    preview_theme(app);
 
     Ok(())
 }
 
+/// Applies the selected theme while browsing themes, so that it can be previewed.
+fn preview_theme(app: &mut Application) {
+    if let Mode::Theme(ref mode) = app.mode {
+        if let Some(theme) = mode.selection() {
+            app.view.set_theme(theme);
+        }
+    }
+}
+
 pub fn enable_insert(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Bookmark(ref mut mode) => mode.set_insert_mode(true),
+        Mode::BufferList(ref mut mode) => mode.set_insert_mode(true),
         Mode::Command(ref mut mode) => mode.set_insert_mode(true),
         Mode::Open(ref mut mode) => mode.set_insert_mode(true),
+        Mode::RecentFiles(ref mut mode) => mode.set_insert_mode(true),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(true),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(true),
         Mode::Syntax(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Spelling(ref mut mode) => mode.set_insert_mode(true),
+        Mode::TagJump(ref mut mode) => mode.set_insert_mode(true),
+        Mode::WorkspaceSearch(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Encoding(ref mut mode) => mode.set_insert_mode(true),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
 
@@ -116,11 +299,18 @@ pub fn enable_insert(app: &mut Application) -> Result {
 
 pub fn disable_insert(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Bookmark(ref mut mode) => mode.set_insert_mode(false),
+        Mode::BufferList(ref mut mode) => mode.set_insert_mode(false),
         Mode::Command(ref mut mode) => mode.set_insert_mode(false),
         Mode::Open(ref mut mode) => mode.set_insert_mode(false),
+        Mode::RecentFiles(ref mut mode) => mode.set_insert_mode(false),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(false),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(false),
         Mode::Syntax(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Spelling(ref mut mode) => mode.set_insert_mode(false),
+        Mode::TagJump(ref mut mode) => mode.set_insert_mode(false),
+        Mode::WorkspaceSearch(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Encoding(ref mut mode) => mode.set_insert_mode(false),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
 
@@ -130,11 +320,18 @@ pub fn disable_insert(app: &mut Application) -> Result {
 pub fn push_search_char(app: &mut Application) -> Result {
     if let Some(Key::Char(c)) = *app.view.last_key() {
         match app.mode {
+            Mode::Bookmark(ref mut mode) => mode.push_search_char(c),
+            Mode::BufferList(ref mut mode) => mode.push_search_char(c),
             Mode::Command(ref mut mode) => mode.push_search_char(c),
             Mode::Open(ref mut mode) => mode.push_search_char(c),
+            Mode::RecentFiles(ref mut mode) => mode.push_search_char(c),
             Mode::Theme(ref mut mode) => mode.push_search_char(c),
             Mode::SymbolJump(ref mut mode) => mode.push_search_char(c),
             Mode::Syntax(ref mut mode) => mode.push_search_char(c),
+            Mode::Spelling(ref mut mode) => mode.push_search_char(c),
+            Mode::TagJump(ref mut mode) => mode.push_search_char(c),
+            Mode::WorkspaceSearch(ref mut mode) => mode.push_search_char(c),
+            Mode::Encoding(ref mut mode) => mode.push_search_char(c),
             _ => bail!("Can't push search character outside of search select mode"),
         }
     }
@@ -145,11 +342,18 @@ pub fn push_search_char(app: &mut Application) -> Result {
 
 pub fn pop_search_token(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Bookmark(ref mut mode) => mode.pop_search_token(),
+        Mode::BufferList(ref mut mode) => mode.pop_search_token(),
         Mode::Command(ref mut mode) => mode.pop_search_token(),
         Mode::Open(ref mut mode) => mode.pop_search_token(),
+        Mode::RecentFiles(ref mut mode) => mode.pop_search_token(),
         Mode::Theme(ref mut mode) => mode.pop_search_token(),
         Mode::SymbolJump(ref mut mode) => mode.pop_search_token(),
         Mode::Syntax(ref mut mode) => mode.pop_search_token(),
+        Mode::Spelling(ref mut mode) => mode.pop_search_token(),
+        Mode::TagJump(ref mut mode) => mode.pop_search_token(),
+        Mode::WorkspaceSearch(ref mut mode) => mode.pop_search_token(),
+        Mode::Encoding(ref mut mode) => mode.pop_search_token(),
         _ => bail!("Can't pop search token outside of search select mode"),
     }
 
@@ -159,11 +363,18 @@ pub fn pop_search_token(app: &mut Application) -> Result {
 
 pub fn step_back(app: &mut Application) -> Result {
     let result_count = match app.mode {
+        Mode::Bookmark(ref mut mode) => mode.results().count(),
+        Mode::BufferList(ref mut mode) => mode.results().count(),
         Mode::Command(ref mut mode) => mode.results().count(),
         Mode::Open(ref mut mode) => mode.results().count(),
+        Mode::RecentFiles(ref mut mode) => mode.results().count(),
         Mode::Theme(ref mut mode) => mode.results().count(),
         Mode::SymbolJump(ref mut mode) => mode.results().count(),
         Mode::Syntax(ref mut mode) => mode.results().count(),
+        Mode::Spelling(ref mut mode) => mode.results().count(),
+        Mode::TagJump(ref mut mode) => mode.results().count(),
+        Mode::WorkspaceSearch(ref mut mode) => mode.results().count(),
+        Mode::Encoding(ref mut mode) => mode.results().count(),
         _ => bail!("Can't pop search token outside of search select mode"),
     };
 
@@ -173,3 +384,116 @@ pub fn step_back(app: &mut Application) -> Result {
         disable_insert(app)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode, SyntaxMode};
+    use crate::util;
+    use crate::util::spell::Dictionary;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::path::Path;
+
+    fn selected_theme(app: &Application) -> String {
+        match app.mode {
+            Mode::Theme(ref mode) => mode.selection().unwrap().clone(),
+            _ => panic!("Not in theme mode"),
+        }
+    }
+
+    #[test]
+    fn select_next_previews_themes_until_theme_mode_is_cancelled() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let original_theme = app.preferences.borrow().theme().to_string();
+
+        commands::application::switch_to_theme_mode(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), original_theme);
+
+        super::select_next(&mut app).unwrap();
+        let previewed_theme = selected_theme(&app);
+        assert_eq!(app.preferences.borrow().theme(), previewed_theme);
+
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), original_theme);
+    }
+
+    #[test]
+    fn accept_keeps_the_previewed_theme() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        commands::application::switch_to_theme_mode(&mut app).unwrap();
+        super::select_next(&mut app).unwrap();
+        let previewed_theme = selected_theme(&app);
+        super::accept(&mut app).unwrap();
+
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+        assert_eq!(app.preferences.borrow().theme(), previewed_theme);
+    }
+
+    #[test]
+    fn accept_opens_the_selected_recent_file() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = util::canonical_path(Path::new("Cargo.toml"));
+        app.recent_files.add(&path);
+
+        commands::application::switch_to_recent_files_mode(&mut app).unwrap();
+        super::accept(&mut app).unwrap();
+
+        let buffer_path = app.workspace.current_buffer().unwrap().path.clone().unwrap();
+        assert_eq!(util::canonical_path(&buffer_path), path);
+    }
+
+    #[test]
+    fn accept_replaces_the_misspelled_word_with_the_selected_correction() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("the quikc fox");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        util::add_buffer(buffer, &mut app).unwrap();
+        app.view.set_dictionary(Some(Dictionary::parse("the\nquick\nquack\nfox")));
+
+        commands::application::switch_to_spelling_mode(&mut app).unwrap();
+        match app.mode {
+            Mode::Spelling(ref mode) => assert_eq!(mode.selection(), Some(&String::from("quick"))),
+            _ => panic!("Not in spelling mode"),
+        }
+        super::accept(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "the quick fox");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 4 });
+    }
+
+    #[test]
+    fn accept_applies_the_selected_syntax_to_the_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp = 1\n");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        commands::application::switch_to_syntax_mode(&mut app).unwrap();
+        if let Mode::Syntax(ref mut mode) = app.mode {
+            mode.query().push_str("Plain Text");
+            mode.search();
+        }
+        super::accept(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.syntax_definition.as_ref().map(|syntax| syntax.name.as_str()), Some("Plain Text"));
+    }
+
+    #[test]
+    fn accept_reports_syntaxes_that_dont_exist() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::add_buffer(Buffer::new(), &mut app).unwrap();
+
+        let mut mode = SyntaxMode::new(vec![String::from("Nonexistent")], SearchSelectConfig::default());
+        mode.search();
+        app.mode = Mode::Syntax(mode);
+
+        assert!(super::accept(&mut app).is_err());
+    }
+}
@@ -1,11 +1,22 @@
 use crate::models::application::{Application, ClipboardContent, Mode};
-use scribe::buffer::{LineRange, Range};
+use crate::models::application::modes::{SelectLineMode, SelectMode};
+use scribe::buffer::{LineRange, Position, Range};
 use super::application;
 use crate::errors::*;
+use crate::errors;
 use crate::commands::{self, Result};
 use crate::util;
+use crate::util::grapheme_index::{content_length, index_position, position_index};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn delete(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    if !app.secondary_cursors.is_empty() {
+        if let Mode::Select(_) = app.mode {
+            return delete_at_each_cursor(app);
+        }
+    }
+
     if let Some(buffer) = app.workspace.current_buffer() {
         match app.mode {
             Mode::Select(ref select_mode) => {
@@ -19,6 +30,20 @@ pub fn delete(app: &mut Application) -> Result {
                 buffer.delete_range(delete_range.clone());
                 buffer.cursor.move_to(delete_range.start());
             }
+            Mode::SelectBlock(ref mode) => {
+                let ranges = mode.to_ranges(&*buffer.cursor, &buffer.data(), app.preferences.borrow().tab_width(buffer.path.as_ref()));
+
+                // Delete from the bottom up, leaving the remaining ranges intact.
+                app.view.start_undo_group(buffer)?;
+                for range in ranges.iter().rev() {
+                    buffer.delete_range(range.clone());
+                }
+                app.view.end_undo_group(buffer)?;
+
+                if let Some(range) = ranges.first() {
+                    buffer.cursor.move_to(range.start());
+                }
+            }
             Mode::Search(ref mode) => {
                 let selection = mode.results
                     .as_ref()
@@ -35,21 +60,429 @@ pub fn delete(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Deletes the selection, along with the same amount of content
+/// following each secondary cursor.
+fn delete_at_each_cursor(app: &mut Application) -> Result {
+    let length = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let selection = match app.mode {
+            Mode::Select(ref select_mode) => Range::new(*buffer.cursor.clone(), select_mode.anchor),
+            _ => bail!("Can't delete selections outside of select mode"),
+        };
+        let data = buffer.data();
+        buffer.cursor.move_to(selection.start());
+
+        position_index(&data, &selection.end()) - position_index(&data, &selection.start())
+    };
+
+    commands::buffer::edit_at_each_cursor(app, |buffer| {
+        let data = buffer.data();
+        let start = *buffer.cursor.clone();
+        let end = index_position(&data, position_index(&data, &start) + length);
+        buffer.delete_range(Range::new(start, end));
+        Ok(())
+    })
+}
+
+/// Adds a secondary cursor at the start of the next occurrence
+/// of the selected text, following the selection and any existing
+/// secondary cursors.
+pub fn add_cursor_at_next_occurrence(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let selection = match app.mode {
+        Mode::Select(ref select_mode) => Range::new(*buffer.cursor.clone(), select_mode.anchor),
+        _ => bail!("Can't add cursors outside of select mode"),
+    };
+    let content = buffer.read(&selection).ok_or("Couldn't read selected data from buffer")?;
+    if content.is_empty() {
+        bail!("Can't add cursors without a selection");
+    }
+
+    // Occurrences can't overlap the selection or previously added cursors.
+    let data = buffer.data();
+    let length = position_index(&data, &selection.end()) - position_index(&data, &selection.start());
+    let search_start = app.secondary_cursors
+        .positions()
+        .iter()
+        .map(|position| position_index(&data, position) + length)
+        .chain(Some(position_index(&data, &selection.end())))
+        .max()
+        .unwrap_or(0);
+    let position = data
+        .match_indices(content.as_str())
+        .map(|(byte_offset, _)| content_length(&data[..byte_offset]))
+        .find(|&index| index >= search_start)
+        .map(|index| index_position(&data, index))
+        .ok_or("No further occurrences of the selection")?;
+
+    app.secondary_cursors.add(position, &selection.start());
+    app.message = Some(format!("{} cursors", app.secondary_cursors.len() + 1));
+
+    Ok(())
+}
+
 pub fn copy_and_delete(app: &mut Application) -> Result {
-    let _ = copy_to_clipboard(app);
+    util::ensure_writable(app)?;
+    let _ = copy_to_clipboard(app, false);
     delete(app)
 }
 
 pub fn change(app: &mut Application) -> Result {
-    let _ = copy_to_clipboard(app);
-    delete(app)?;
+    util::ensure_writable(app)?;
+    let _ = copy_to_clipboard(app, false);
+    if let Mode::SelectBlock(_) = app.mode {
+        // Leave a cursor on each of the block's lines.
+        let starts = block_starts(app)?;
+        delete(app)?;
+        place_cursors(app, &starts)?;
+    } else {
+        delete(app)?;
+    }
     application::switch_to_insert_mode(app)?;
     commands::view::scroll_to_cursor(app)
 }
 
+/// Sorts the selected lines lexicographically.
+pub fn sort_lines(app: &mut Application) -> Result {
+    transform_selected_lines(app, |lines| lines.sort())
+}
+
+pub fn sort_lines_case_insensitively(app: &mut Application) -> Result {
+    transform_selected_lines(app, |lines| lines.sort_by_key(|line| line.to_lowercase()))
+}
+
+pub fn sort_lines_in_reverse(app: &mut Application) -> Result {
+    transform_selected_lines(app, |lines| {
+        lines.sort();
+        lines.reverse();
+    })
+}
+
+/// Sorts the selected lines, comparing any numbers they contain by value.
+pub fn sort_lines_numerically(app: &mut Application) -> Result {
+    transform_selected_lines(app, |lines| lines.sort_by(|a, b| util::sort::natural_cmp(a, b)))
+}
+
+/// Removes adjacent duplicate lines from the selection.
+pub fn remove_duplicate_lines(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    transform_selected_lines(app, |lines| lines.dedup())
+}
+
+/// Replaces the lines selected in select line mode with the result of the
+/// transformation, leaving the newline following the last line as-is.
+fn transform_selected_lines<F>(app: &mut Application, transform: F) -> Result
+    where F: FnOnce(&mut Vec<&str>)
+{
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (first_line, mut last_line) = match app.mode {
+        Mode::SelectLine(ref mode) => (mode.anchor.min(buffer.cursor.line), mode.anchor.max(buffer.cursor.line)),
+        _ => bail!("Can't transform lines outside of select line mode"),
+    };
+
+    // Splitting on newlines (rather than using lines()) keeps any carriage
+    // returns in place, but yields an empty line following a final newline,
+    // which isn't content to be transformed.
+    let data = buffer.data();
+    if last_line > first_line && data.ends_with('\n') && last_line == data.matches('\n').count() {
+        last_line -= 1;
+    }
+    let mut lines: Vec<&str> = data.split('\n').skip(first_line).take(last_line - first_line + 1).collect();
+    let last_line_length = lines.last().map(|line| line.graphemes(true).count()).unwrap_or(0);
+    transform(&mut lines);
+    let content = lines.join("\n");
+
+    let selected_range = Range::new(
+        Position{ line: first_line, offset: 0 },
+        Position{ line: last_line, offset: last_line_length }
+    );
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(selected_range.clone());
+    buffer.cursor.move_to(selected_range.start());
+    buffer.insert(content);
+    app.view.end_undo_group(buffer)?;
+
+    application::switch_to_normal_mode(app)?;
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Switches to insert mode with a cursor at the left edge
+/// of each line reaching the block selection.
+pub fn insert_before_block(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let starts = block_starts(app)?;
+    place_cursors(app, &starts)?;
+    application::switch_to_insert_mode(app)
+}
+
+fn block_starts(app: &mut Application) -> errors::Result<Vec<Position>> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    match app.mode {
+        Mode::SelectBlock(ref mode) => Ok(
+            mode.to_ranges(&*buffer.cursor, &buffer.data(), app.preferences.borrow().tab_width(buffer.path.as_ref()))
+                .iter()
+                .map(|range| range.start())
+                .collect()
+        ),
+        _ => bail!("Can't use block selections outside of select block mode"),
+    }
+}
+
+/// Moves the buffer's cursor to the first position, adding
+/// secondary cursors at the rest.
+fn place_cursors(app: &mut Application, positions: &[Position]) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (primary, secondary) = positions.split_first().ok_or("No cursor positions to use")?;
+    buffer.cursor.move_to(*primary);
+    app.secondary_cursors.replace(secondary.to_vec(), primary);
+
+    Ok(())
+}
+
+/// Evaluates the selected arithmetic expression, replacing it with the
+/// result. The buffer is left untouched if the expression is invalid.
+pub fn evaluate(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let selected_range = match app.mode {
+        Mode::Select(ref select_mode) => Range::new(*buffer.cursor.clone(), select_mode.anchor),
+        Mode::SelectLine(ref mode) => util::inclusive_range(
+            &LineRange::new(mode.anchor, buffer.cursor.line),
+            buffer
+        ),
+        _ => bail!("Can't evaluate selections outside of select modes"),
+    };
+    let expression = buffer.read(&selected_range)
+        .ok_or("Couldn't read selected data from buffer")?;
+    let result = util::expression::evaluate(expression.trim())?;
+
+    // Preserve any whitespace surrounding the expression (e.g. a trailing
+    // newline from a line selection) so that the result slots into place.
+    let trimmed_start = &expression[..expression.len() - expression.trim_start().len()];
+    let trimmed_end = &expression[expression.trim_end().len()..];
+
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(selected_range.clone());
+    buffer.cursor.move_to(selected_range.start());
+    buffer.insert(format!("{}{}{}", trimmed_start, result, trimmed_end));
+    app.view.end_undo_group(buffer)?;
+
+    application::switch_to_normal_mode(app)?;
+    commands::view::scroll_to_cursor(app)
+}
+
 pub fn copy(app: &mut Application) -> Result {
-    copy_to_clipboard(app)?;
-    application::switch_to_normal_mode(app)
+    copy_to_clipboard(app, false)?;
+
+    if app.preferences.borrow().keep_selection_after_yank() {
+        Ok(())
+    } else {
+        application::switch_to_normal_mode(app)
+    }
+}
+
+/// Adds the selection to the end of the clipboard's content, rather
+/// than replacing it. Selected lines keep the clipboard line-wise.
+pub fn append_copy(app: &mut Application) -> Result {
+    copy_to_clipboard(app, true)?;
+
+    if app.preferences.borrow().keep_selection_after_yank() {
+        Ok(())
+    } else {
+        application::switch_to_normal_mode(app)
+    }
+}
+
+/// Copies the selection to the clipboard as a fenced markdown code block,
+/// labelled with the buffer's syntax. Selections starting within a line's
+/// indentation are extended to include all of it, preserving its structure.
+pub fn copy_as_markdown(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let selected_range = match app.mode {
+        Mode::Select(ref select_mode) => {
+            let range = Range::new(*buffer.cursor.clone(), select_mode.anchor);
+            let start = range.start();
+            let indentation = buffer.data()
+                .lines()
+                .nth(start.line)
+                .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
+                .unwrap_or(0);
+
+            if start.offset <= indentation {
+                Range::new(Position{ line: start.line, offset: 0 }, range.end())
+            } else {
+                range
+            }
+        }
+        Mode::SelectLine(ref mode) => util::inclusive_range(
+            &LineRange::new(mode.anchor, buffer.cursor.line),
+            buffer
+        ),
+        _ => bail!("Can't copy data to clipboard outside of select modes"),
+    };
+    let data = buffer.read(&selected_range)
+        .ok_or("Couldn't read selected data from buffer")?;
+    let language = buffer.syntax_definition
+        .as_ref()
+        .map(|syntax| markdown_language(&syntax.name))
+        .unwrap_or_default();
+
+    let mut content = format!("```{}\n{}", language, data);
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("```\n");
+    app.clipboard.set_content(ClipboardContent::Block(content))?;
+
+    if app.preferences.borrow().keep_selection_after_yank() {
+        Ok(())
+    } else {
+        application::switch_to_normal_mode(app)
+    }
+}
+
+/// Re-indents the selected lines so that the shallowest of them matches the
+/// indentation of the preceding line (plus a level, if it opens a block),
+/// preserving their relative indentation. Leading whitespace is normalized
+/// to the buffer's tab settings, as a single undoable edit.
+pub fn reindent(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let anchor_line = match app.mode {
+        Mode::Select(ref select_mode) => select_mode.anchor.line,
+        Mode::SelectLine(ref mode) => mode.anchor,
+        _ => bail!("Can't reindent outside of select modes"),
+    };
+    let first_line = anchor_line.min(buffer.cursor.line);
+    let last_line = anchor_line.max(buffer.cursor.line);
+    let tab_width = app.preferences.borrow().tab_width(buffer.path.as_ref());
+    let soft_tabs = app.preferences.borrow().soft_tabs(buffer.path.as_ref());
+
+    // Use the closest non-blank line above the selection as context.
+    let data = buffer.data();
+    let context_line = data.lines()
+        .take(first_line)
+        .filter(|line| !line.trim().is_empty())
+        .last();
+    let mut target_width = context_line
+        .map(|line| util::indent::width(line, tab_width))
+        .unwrap_or(0);
+    if context_line.map(|line| line.trim_end().ends_with(|c: char| "{([:".contains(c))).unwrap_or(false) {
+        target_width += tab_width;
+    }
+
+    let selected_range = util::inclusive_range(&LineRange::new(first_line, last_line), buffer);
+    let selected_data = buffer.read(&selected_range)
+        .ok_or("Couldn't read selected data from buffer")?;
+    let reindented_data = util::indent::reindent(&selected_data, target_width, tab_width, soft_tabs);
+
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(selected_range.clone());
+    buffer.cursor.move_to(selected_range.start());
+    buffer.insert(reindented_data);
+    app.view.end_undo_group(buffer)?;
+
+    application::switch_to_normal_mode(app)?;
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Selects the syntactic token (e.g. a string or numeric literal) at the
+/// cursor using the buffer's syntax scopes, falling back to the identifier
+/// at the cursor when there's no scope more specific than the syntax itself.
+pub fn select_token(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let scoped_range = buffer.tokens().ok().and_then(|tokens|
+        util::token::scoped_token_range(tokens.iter(), &*buffer.cursor)
+    );
+    let range = scoped_range
+        .or_else(|| util::token::identifier_range_at_cursor(buffer))
+        .ok_or("Couldn't find a token at the cursor")?;
+
+    app.mode = Mode::Select(SelectMode::new(range.start()));
+    buffer.cursor.move_to(range.end());
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Selects the content between the nearest enclosing brackets or quotes.
+/// Repeating it with a selection expands it to the next enclosing pair.
+pub fn select_inside_pair(app: &mut Application) -> Result {
+    select_pair(app, false)
+}
+
+/// Selects the nearest enclosing brackets or quotes, along with their
+/// content. Repeating it with a selection expands to the next enclosing pair.
+pub fn select_around_pair(app: &mut Application) -> Result {
+    select_pair(app, true)
+}
+
+fn select_pair(app: &mut Application, include_delimiters: bool) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let cursor_position = *buffer.cursor.clone();
+    let current_selection = match app.mode {
+        Mode::Select(ref select_mode) => Some(Range::new(cursor_position, select_mode.anchor)),
+        _ => None,
+    };
+
+    // Search from the start of any existing selection, skipping
+    // pairs that wouldn't grow it so that the selection expands.
+    let search_position = current_selection
+        .as_ref()
+        .map(|selection| selection.start())
+        .unwrap_or(cursor_position);
+    let (start, end) = util::delimiter::enclosing_pairs(&buffer.data(), &search_position)
+        .into_iter()
+        .map(|(opening, closing)| if include_delimiters {
+            (opening, Position{ line: closing.line, offset: closing.offset + 1 })
+        } else {
+            (Position{ line: opening.line, offset: opening.offset + 1 }, closing)
+        })
+        .find(|&(start, end)| match current_selection {
+            Some(ref selection) => {
+                start <= selection.start() && end >= selection.end() &&
+                    (start, end) != (selection.start(), selection.end())
+            },
+            None => true,
+        })
+        .ok_or("Couldn't find enclosing delimiters")?;
+
+    app.mode = Mode::Select(SelectMode::new(start));
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_to(end);
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Selects the lines around the cursor that are indented at least as far as
+/// its line. From a blank line, the surrounding blank lines are selected.
+pub fn select_indentation_block(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let tab_width = app.preferences.borrow().tab_width(buffer.path.as_ref());
+    let range = util::text_object::indentation_block(&buffer.data(), buffer.cursor.line, tab_width);
+
+    select_lines(app, range)
+}
+
+/// Selects the paragraph (i.e. the lines between blank lines) around the
+/// cursor. From a blank line, the surrounding blank lines are selected.
+pub fn select_paragraph(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let range = util::text_object::paragraph(&buffer.data(), buffer.cursor.line);
+
+    select_lines(app, range)
+}
+
+fn select_lines(app: &mut Application, range: Option<(usize, usize)>) -> Result {
+    let (start, end) = range.ok_or("Couldn't find any lines to select")?;
+
+    app.mode = Mode::SelectLine(SelectLineMode::new(start));
+    app.workspace
+        .current_buffer()
+        .ok_or(BUFFER_MISSING)?
+        .cursor
+        .move_to(Position{ line: end, offset: 0 });
+
+    commands::view::scroll_to_cursor(app)
 }
 
 pub fn select_all(app: &mut Application) -> Result {
@@ -68,17 +501,17 @@ pub fn select_all(app: &mut Application) -> Result {
     Ok(())
 }
 
-fn copy_to_clipboard(app: &mut Application) -> Result {
+fn copy_to_clipboard(app: &mut Application, append: bool) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
-    match app.mode {
+    let content = match app.mode {
         Mode::Select(ref select_mode) => {
             let cursor_position = *buffer.cursor.clone();
             let selected_range = Range::new(cursor_position, select_mode.anchor);
 
             let data = buffer.read(&selected_range.clone())
                 .ok_or("Couldn't read selected data from buffer")?;
-            app.clipboard.set_content(ClipboardContent::Inline(data))?;
+            ClipboardContent::Inline(data)
         }
         Mode::SelectLine(ref mode) => {
             let selected_range = util::inclusive_range(
@@ -92,20 +525,74 @@ fn copy_to_clipboard(app: &mut Application) -> Result {
 
             let data = buffer.read(&selected_range.clone())
                 .ok_or("Couldn't read selected data from buffer")?;
-            app.clipboard.set_content(ClipboardContent::Block(data))?;
+            ClipboardContent::Block(data)
+        }
+        Mode::SelectBlock(ref mode) => {
+            // Lines short of the block are copied as empty rows, preserving its shape.
+            let ranges = mode.to_ranges(&*buffer.cursor, &buffer.data(), app.preferences.borrow().tab_width(buffer.path.as_ref()));
+            let (first_line, last_line) = mode.lines(&*buffer.cursor);
+            let rows: Vec<String> = (first_line..=last_line)
+                .map(|line| {
+                    ranges
+                        .iter()
+                        .find(|range| range.start().line == line)
+                        .and_then(|range| buffer.read(range))
+                        .unwrap_or_default()
+                })
+                .collect();
+            ClipboardContent::Rectangle(rows.join("\n"))
         }
         _ => bail!("Can't copy data to clipboard outside of select modes"),
     };
 
-    Ok(())
+    if append {
+        app.clipboard.append_content(content)
+    } else {
+        app.clipboard.set_content(content)
+    }
+}
+
+/// Converts a syntax definition name (e.g. "Rust") into a
+/// code block info string, omitting it for plain text.
+fn markdown_language(syntax_name: &str) -> String {
+    if syntax_name == "Plain Text" {
+        return String::new();
+    }
+
+    syntax_name.to_lowercase().replace(' ', "-")
 }
 
 #[cfg(test)]
 mod tests {
     use crate::commands;
-    use crate::models::application::{Application, Mode};
+    use crate::input::Key;
+    use crate::models::application::{Application, ClipboardContent, Mode, Preferences};
     use scribe::Buffer;
     use scribe::buffer::Position;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn select_indentation_block_selects_lines_in_select_line_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("def amp():\n    x = 1\n\n    y = 2\nprint(1)\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 4 });
+        app.workspace.add_buffer(buffer);
+
+        super::select_indentation_block(&mut app).unwrap();
+        match app.mode {
+            Mode::SelectLine(ref mode) => assert_eq!(mode.anchor, 1),
+            _ => panic!("Not in select line mode"),
+        }
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 3);
+
+        super::select_paragraph(&mut app).unwrap();
+        match app.mode {
+            Mode::SelectLine(ref mode) => assert_eq!(mode.anchor, 3),
+            _ => panic!("Not in select line mode"),
+        }
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 4);
+    }
 
     #[test]
     fn select_all_selects_the_entire_buffer() {
@@ -219,4 +706,295 @@ mod tests {
             String::from("amp\nitor\nbuffer")
         )
     }
+
+    #[test]
+    fn copy_keeps_selection_when_configured() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("keep_selection_after_yank: true").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 3 });
+        commands::selection::copy(&mut app).unwrap();
+
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position{ line: 0, offset: 0 }),
+            _ => panic!("Selection wasn't preserved"),
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 3 });
+    }
+
+    #[test]
+    fn copy_as_markdown_wraps_selection_in_a_fenced_code_block() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn amp() {\n    editor();\n}\n");
+        app.workspace.add_buffer(buffer);
+        app.workspace.current_buffer().unwrap().path = Some(std::path::PathBuf::from("amp.rs"));
+        app.workspace.update_current_syntax().unwrap();
+
+        // Start the selection after the second line's indentation.
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 4 });
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 13 });
+        commands::selection::copy_as_markdown(&mut app).unwrap();
+
+        assert_eq!(
+            *app.clipboard.get_content(),
+            ClipboardContent::Block(String::from("```rust\n    editor();\n```\n"))
+        );
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn select_inside_pair_selects_and_expands_to_enclosing_pairs() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp(editor[0])");
+        buffer.cursor.move_to(Position{ line: 0, offset: 11 });
+        app.workspace.add_buffer(buffer);
+
+        commands::selection::select_inside_pair(&mut app).unwrap();
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position{ line: 0, offset: 11 }),
+            _ => panic!("Application isn't in select mode"),
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 12 });
+
+        commands::selection::select_inside_pair(&mut app).unwrap();
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position{ line: 0, offset: 4 }),
+            _ => panic!("Application isn't in select mode"),
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 13 });
+    }
+
+    #[test]
+    fn select_around_pair_includes_the_delimiters() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp(\"editor\")");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        app.workspace.add_buffer(buffer);
+
+        commands::selection::select_around_pair(&mut app).unwrap();
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position{ line: 0, offset: 4 }),
+            _ => panic!("Application isn't in select mode"),
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 12 });
+    }
+
+    #[test]
+    fn reindent_aligns_selected_lines_with_the_enclosing_block() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn amp() {\n\t\tif editor {\n\t\t\tbuffer();\n\t\t}\n}\n");
+        app.workspace.add_buffer(buffer);
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 0 });
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 3, offset: 0 });
+        commands::selection::reindent(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "fn amp() {\n  if editor {\n    buffer();\n  }\n}\n"
+        );
+
+        // The re-indentation is undone as a single operation.
+        app.workspace.current_buffer().unwrap().undo();
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "fn amp() {\n\t\tif editor {\n\t\t\tbuffer();\n\t\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn select_token_falls_back_to_the_identifier_at_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor_name");
+        buffer.cursor.move_to(Position{ line: 0, offset: 8 });
+        app.workspace.add_buffer(buffer);
+
+        commands::selection::select_token(&mut app).unwrap();
+
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position{ line: 0, offset: 4 }),
+            _ => panic!("Application isn't in select mode"),
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 15 });
+    }
+
+    #[test]
+    fn evaluate_replaces_selection_with_result() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("width: 2 * (40 + 2)\n");
+        app.workspace.add_buffer(buffer);
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 7 });
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 19 });
+        commands::selection::evaluate(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "width: 84\n");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn evaluate_leaves_buffer_untouched_for_invalid_expressions() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("2 * (40 +\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        assert!(commands::selection::evaluate(&mut app).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "2 * (40 +\n");
+    }
+
+    #[test]
+    fn change_replaces_each_occurrence_added_as_a_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("let amp = amp + ampersand;");
+        buffer.cursor.move_to(Position{ line: 0, offset: 4 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 7 });
+        commands::selection::add_cursor_at_next_occurrence(&mut app).unwrap();
+        commands::selection::add_cursor_at_next_occurrence(&mut app).unwrap();
+        commands::selection::change(&mut app).unwrap();
+        for character in "ed".chars() {
+            app.view.last_key = Some(Key::Char(character));
+            commands::buffer::insert_char(&mut app).unwrap();
+        }
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "let ed = ed + edersand;");
+    }
+
+    #[test]
+    fn add_cursor_at_next_occurrence_fails_without_further_occurrences() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 3 });
+
+        assert!(commands::selection::add_cursor_at_next_occurrence(&mut app).is_err());
+        assert!(app.secondary_cursors.is_empty());
+    }
+
+    #[test]
+    fn copy_and_paste_preserve_block_selections() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nab\nbuffer\n");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_block_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 5 });
+        commands::selection::copy(&mut app).unwrap();
+
+        assert_eq!(
+            *app.clipboard.get_content(),
+            ClipboardContent::Rectangle(String::from(" e\n\nfe"))
+        );
+
+        app.mode = Mode::Normal;
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 1, offset: 1 });
+        commands::buffer::paste(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "amp editor\na eb\nbuffer\n fe"
+        );
+    }
+
+    #[test]
+    fn change_inserts_on_each_line_of_a_block_selection() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("let a = 1;\nx\nlet b = 2;\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_block_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 3 });
+        commands::selection::change(&mut app).unwrap();
+        for character in "const".chars() {
+            app.view.last_key = Some(Key::Char(character));
+            commands::buffer::insert_char(&mut app).unwrap();
+        }
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "const a = 1;\nconst\nconst b = 2;\n");
+    }
+
+    #[test]
+    fn sort_lines_sorts_the_selected_lines() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("use c;\nuse b;\nuse B;\nuse a;\n\nfn amp() {}\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 3, offset: 0 });
+        commands::selection::sort_lines(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "use B;\nuse a;\nuse b;\nuse c;\n\nfn amp() {}\n"
+        );
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn sort_lines_leaves_the_empty_line_after_a_final_newline_alone() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("b\na\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 0 });
+        commands::selection::sort_lines(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "a\nb\n");
+    }
+
+    #[test]
+    fn sort_lines_case_insensitively_ignores_case() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("c\nB\na");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 0 });
+        commands::selection::sort_lines_case_insensitively(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "a\nB\nc");
+    }
+
+    #[test]
+    fn remove_duplicate_lines_removes_adjacent_duplicates() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\namp\neditor\namp\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 3, offset: 0 });
+        commands::selection::remove_duplicate_lines(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor\namp\n");
+    }
 }
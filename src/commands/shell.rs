@@ -0,0 +1,336 @@
+use crate::errors::*;
+use crate::errors;
+use crate::input::Key;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{ShellMode, ShellOutput};
+use crate::util;
+use crate::util::grapheme_index::{content_length, index_position, position_index};
+use scribe::Buffer;
+use scribe::buffer::Range;
+use std::env;
+use std::io::{Read, Write};
+use std::mem;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+pub fn push_char(app: &mut Application) -> Result {
+    let key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
+
+    if let Key::Char(c) = *key {
+        if let Mode::Shell(ref mut mode) = app.mode {
+            mode.input.push(c)
+        } else {
+            bail!("Can't push command character outside of shell mode")
+        }
+    } else {
+        bail!("Last key press wasn't a character")
+    }
+
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::Shell(ref mut mode) = app.mode {
+        mode.input.pop()
+    } else {
+        bail!("Can't pop command character outside of shell mode")
+    };
+
+    Ok(())
+}
+
+/// Leaves shell mode without running the command, returning
+/// to insert mode if its output was to be inserted there.
+pub fn cancel(app: &mut Application) -> Result {
+    let reading = match app.mode {
+        Mode::Shell(ShellMode { output: ShellOutput::Cursor, .. }) => true,
+        _ => false,
+    };
+    commands::application::switch_to_normal_mode(app)?;
+    if reading {
+        commands::application::switch_to_insert_mode(app)?;
+    }
+
+    Ok(())
+}
+
+/// Runs the entered command using the user's shell, from the workspace
+/// directory. Depending on how shell mode was entered, its output is opened
+/// in a new buffer, replaces the selection, or is inserted at the cursor.
+/// The command doesn't have access to the terminal, so interactive programs
+/// aren't supported.
+pub fn run_command(app: &mut Application) -> Result {
+    let (command, output) = match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Shell(mode) => (mode.input, mode.output),
+        mode => {
+            app.mode = mode;
+            bail!("Can't run a command outside of shell mode");
+        }
+    };
+    if command.trim().is_empty() {
+        app.mode = Mode::Shell(ShellMode { input: command, output });
+        bail!("Please provide a command to run");
+    }
+    commands::application::switch_to_normal_mode(app)?;
+
+    match output {
+        ShellOutput::NewBuffer => open_output(app, &command),
+        ShellOutput::Selection(selection) => filter_selection(app, &command, selection),
+        ShellOutput::Cursor => insert_output(app, &command),
+    }
+}
+
+/// Opens the command's output in a new buffer, along with its exit status.
+fn open_output(app: &mut Application, command: &str) -> Result {
+    let timeout = app.preferences.borrow().shell_timeout();
+    let output = run_shell_command(command, &app.workspace.path, None, timeout)?;
+
+    let mut content = format!("$ {}\n", command);
+    content.push_str(&String::from_utf8_lossy(&output.stdout));
+    content.push_str(&String::from_utf8_lossy(&output.stderr));
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if !output.status.success() {
+        match output.status.code() {
+            Some(code) => content.push_str(&format!("[exited with status {}]\n", code)),
+            None => content.push_str("[terminated by signal]\n"),
+        }
+    }
+
+    let mut buffer = Buffer::new();
+    buffer.insert(content);
+    util::add_buffer(buffer, app)
+}
+
+/// Pipes the selected text through the command, replacing it with the
+/// command's output. The buffer is left untouched if the command fails,
+/// in which case its error output is reported instead.
+fn filter_selection(app: &mut Application, command: &str, selection: Range) -> Result {
+    util::ensure_writable(app)?;
+    let timeout = app.preferences.borrow().shell_timeout();
+    let workspace_path = app.workspace.path.clone();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let input = buffer.read(&selection).ok_or("Couldn't read selected data from buffer")?;
+    let output = run_shell_command(command, &workspace_path, Some(input), timeout)?;
+    check_status(&output)?;
+
+    app.view.start_undo_group(buffer)?;
+    buffer.delete_range(selection.clone());
+    buffer.cursor.move_to(selection.start());
+    buffer.insert(String::from_utf8_lossy(&output.stdout).into_owned());
+    app.view.end_undo_group(buffer)?;
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Inserts the command's output at the cursor, returning to insert mode
+/// with the cursor placed after it. Nothing is inserted if the command
+/// fails, in which case its error output is reported instead.
+fn insert_output(app: &mut Application, command: &str) -> Result {
+    commands::application::switch_to_insert_mode(app)?;
+
+    let timeout = app.preferences.borrow().shell_timeout();
+    let output = run_shell_command(command, &app.workspace.path, None, timeout)?;
+    check_status(&output)?;
+
+    let content = String::from_utf8_lossy(&output.stdout).into_owned();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let end = position_index(&buffer.data(), &*buffer.cursor) + content_length(&content);
+    buffer.insert(content);
+    buffer.cursor.move_to(index_position(&buffer.data(), end));
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Runs the command using the user's shell, from the specified directory,
+/// passing it the input (if any). Commands that haven't finished within
+/// the timeout (in seconds) are killed, so that they can't freeze the editor.
+fn run_shell_command(command: &str, directory: &Path, input: Option<String>, timeout: u64) -> errors::Result<Output> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| String::from("sh"));
+    let mut child = Command::new(&shell)
+        .arg("-c")
+        .arg(command)
+        .current_dir(directory)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .chain_err(|| format!("Failed to run command using {}", shell))?;
+
+    // Feed input and collect output from other threads, so that large
+    // amounts of data can't fill the pipes and deadlock while we wait.
+    // Commands that exit without reading their input close the pipe
+    // early, which isn't an error in and of itself.
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    let status = loop {
+        if let Some(status) = child.try_wait().chain_err(|| "Failed to wait for the command")? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("Command timed out after {} seconds", timeout);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_in_background<R: Read + Send + 'static>(reader: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut data = Vec::new();
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut data);
+        }
+
+        data
+    })
+}
+
+/// Reports the command's error output (or exit status) if it failed.
+fn check_status(output: &Output) -> Result {
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        match (error.trim(), output.status.code()) {
+            ("", Some(code)) => bail!("Command exited with status {}", code),
+            ("", None) => bail!("Command was terminated by a signal"),
+            (error, _) => bail!("{}", error),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Mode, Preferences};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::time::{Duration, Instant};
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn run_command_opens_output_in_a_new_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        commands::application::switch_to_shell_mode(&mut app).unwrap();
+        if let Mode::Shell(ref mut mode) = app.mode {
+            mode.input = String::from("echo amp; exit 3");
+        }
+        super::run_command(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "$ echo amp; exit 3\namp\n[exited with status 3]\n"
+        );
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn run_command_filters_the_selection_through_the_command() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\nc\nb\na\neditor\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 3, offset: 0 });
+        commands::application::switch_to_filter_mode(&mut app).unwrap();
+        if let Mode::Shell(ref mut mode) = app.mode {
+            mode.input = String::from("sort");
+        }
+        super::run_command(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\na\nb\nc\neditor\n");
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn run_command_leaves_the_selection_when_the_filter_fails() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::application::switch_to_filter_mode(&mut app).unwrap();
+        if let Mode::Shell(ref mut mode) = app.mode {
+            mode.input = String::from("echo broken >&2; exit 1");
+        }
+        let error = super::run_command(&mut app).unwrap_err();
+
+        assert_eq!(error.to_string(), "broken");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n");
+    }
+
+    #[test]
+    fn run_command_inserts_output_at_the_cursor_in_read_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+        commands::application::switch_to_read_command_mode(&mut app).unwrap();
+        if let Mode::Shell(ref mut mode) = app.mode {
+            mode.input = String::from("printf 'editor\\n'");
+        }
+        super::run_command(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\namp\n");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+        assert!(match app.mode { Mode::Insert(_) => true, _ => false });
+    }
+
+    #[test]
+    fn cancel_returns_to_insert_mode_from_read_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+        commands::application::switch_to_read_command_mode(&mut app).unwrap();
+        super::cancel(&mut app).unwrap();
+        assert!(match app.mode { Mode::Insert(_) => true, _ => false });
+
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+        commands::application::switch_to_shell_mode(&mut app).unwrap();
+        super::cancel(&mut app).unwrap();
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn run_command_kills_commands_that_exceed_the_timeout() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("shell_timeout: 1").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let started_at = Instant::now();
+
+        commands::application::switch_to_shell_mode(&mut app).unwrap();
+        if let Mode::Shell(ref mut mode) = app.mode {
+            mode.input = String::from("sleep 5");
+        }
+
+        assert!(super::run_command(&mut app).is_err());
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+    }
+}
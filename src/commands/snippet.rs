@@ -0,0 +1,266 @@
+use crate::errors::*;
+use crate::errors;
+use crate::commands::{self, Command, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{InsertMode, SnippetMode};
+use crate::util::{self, completion, snippet};
+use crate::util::grapheme_index::{content_length, index_position, position_index};
+use scribe::buffer::{Position, Range};
+use std::mem;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Replaces the snippet trigger preceding the cursor with its snippet, moving
+/// to its first tab-stop (tracked in snippet mode) or final position. Inserts
+/// a tab instead if there's no snippet for the word before the cursor.
+pub fn expand(app: &mut Application) -> Result {
+    util::ensure_writable(app)?;
+    let (start, snippet) = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let data = buffer.data();
+        let cursor = *buffer.cursor;
+        let line = data.lines().nth(cursor.line).unwrap_or("");
+        let trigger = completion::prefix(line, cursor.offset);
+        let body = if trigger.is_empty() {
+            None
+        } else {
+            app.preferences.borrow().snippet(buffer.path.as_ref(), &trigger)
+        };
+        let body = match body {
+            Some(body) => body,
+            None => return commands::buffer::insert_tab(app),
+        };
+
+        // Indent the snippet's lines to match the line it's expanded on.
+        let indentation: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+        let start = Position {
+            line: cursor.line,
+            offset: cursor.offset - trigger.graphemes(true).count(),
+        };
+
+        (start, snippet::parse(&snippet::indent(&body, &indentation)))
+    };
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    buffer.delete_range(Range::new(start, *buffer.cursor));
+    buffer.cursor.move_to(start);
+    buffer.insert(snippet.content.as_str());
+
+    let data = buffer.data();
+    let start_index = position_index(&data, &start);
+    let stops: Vec<Vec<usize>> = snippet.stops
+        .iter()
+        .map(|stop| stop.iter().map(|offset| start_index + offset).collect())
+        .collect();
+    let end = start_index + snippet.end;
+    let cursor_index = stops.first().and_then(|stop| stop.first()).cloned().unwrap_or(end);
+    buffer.cursor.move_to(index_position(&data, cursor_index));
+
+    if !stops.is_empty() {
+        if let Mode::Insert(_) = app.mode {
+            if let Mode::Insert(insert_mode) = mem::replace(&mut app.mode, Mode::Normal) {
+                app.mode = Mode::Snippet(SnippetMode::new(insert_mode, stops, end));
+            }
+        }
+    }
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Moves to the snippet's next tab-stop or, once they've
+/// all been visited, its final position in insert mode.
+pub fn next_tab_stop(app: &mut Application) -> Result {
+    let index = match app.mode {
+        Mode::Snippet(ref mut mode) => mode.next_stop().ok_or_else(|| mode.end()),
+        _ => bail!("Can't move to a tab-stop outside of snippet mode"),
+    };
+    if index.is_err() {
+        restore_insert_mode(app);
+    }
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let index = index.unwrap_or_else(|end| end);
+    buffer.cursor.move_to(index_position(&data, index));
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Leaves the snippet's remaining tab-stops behind, returning to insert mode.
+pub fn dismiss(app: &mut Application) -> Result {
+    restore_insert_mode(app);
+
+    Ok(())
+}
+
+pub fn insert_char(app: &mut Application) -> Result {
+    edit(app, commands::buffer::insert_char)
+}
+
+pub fn insert_newline(app: &mut Application) -> Result {
+    edit(app, commands::buffer::insert_newline)
+}
+
+pub fn backspace(app: &mut Application) -> Result {
+    edit(app, commands::buffer::backspace)
+}
+
+pub fn delete(app: &mut Application) -> Result {
+    edit(app, commands::buffer::delete)
+}
+
+/// Runs the command in insert mode, tracking the content it adds or removes
+/// in the current tab-stop and copying the stop's content to its mirrors.
+/// Snippet mode is left if the cursor ends up outside of the current stop.
+fn edit(app: &mut Application, command: Command) -> Result {
+    let mut mode = match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Snippet(mode) => mode,
+        mode => {
+            app.mode = mode;
+            bail!("Can't edit a tab-stop outside of snippet mode");
+        }
+    };
+
+    let (length, cursor_index) = cursor_state(app)?;
+    app.mode = Mode::Insert(mem::replace(&mut mode.insert_mode, InsertMode::new()));
+    let result = command(app);
+    match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Insert(insert_mode) => mode.insert_mode = insert_mode,
+        other => {
+            // The command left insert mode (e.g. an escape sequence was typed).
+            app.mode = other;
+            return result;
+        }
+    }
+    let (updated_length, updated_cursor_index) = cursor_state(app)?;
+
+    // Describe the change as a removal or insertion at the earlier cursor position.
+    let index = cursor_index.min(updated_cursor_index);
+    if updated_length >= length {
+        mode.track_edit(0, index, 0, updated_length - length);
+    } else {
+        mode.track_edit(0, index, length - updated_length, 0);
+    }
+
+    if mode.within_current_stop(updated_cursor_index) {
+        update_mirrors(app, &mut mode)?;
+        app.mode = Mode::Snippet(mode);
+    } else {
+        app.mode = Mode::Insert(mode.insert_mode);
+    }
+
+    result
+}
+
+/// Replaces the content of the current stop's mirrors with
+/// that of its first range, keeping the cursor in place.
+fn update_mirrors(app: &mut Application, mode: &mut SnippetMode) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let (start, end) = match mode.current_stop().first() {
+        Some(&range) => range,
+        None => return Ok(()),
+    };
+    let cursor_offset = position_index(&data, &*buffer.cursor).saturating_sub(start);
+    let content: String = data.graphemes(true).skip(start).take(end - start).collect();
+
+    for mirror in 1..mode.current_stop().len() {
+        let data = buffer.data();
+        let (mirror_start, mirror_end) = mode.current_stop()[mirror];
+        let mirror_position = index_position(&data, mirror_start);
+        buffer.delete_range(Range::new(mirror_position, index_position(&data, mirror_end)));
+        buffer.cursor.move_to(mirror_position);
+        buffer.insert(content.as_str());
+        mode.track_edit(mirror, mirror_start, mirror_end - mirror_start, end - start);
+    }
+
+    // Mirrors preceding the cursor will have shifted it.
+    let data = buffer.data();
+    let start = mode.current_stop().first().map(|&(start, _)| start).unwrap_or(start);
+    buffer.cursor.move_to(index_position(&data, start + cursor_offset));
+
+    Ok(())
+}
+
+/// The length of the current buffer's content and the cursor's index within it.
+fn cursor_state(app: &mut Application) -> errors::Result<(usize, usize)> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+
+    Ok((content_length(&data), position_index(&data, &*buffer.cursor)))
+}
+
+fn restore_insert_mode(app: &mut Application) {
+    match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Snippet(mode) => app.mode = Mode::Insert(mode.insert_mode),
+        mode => app.mode = mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode, Preferences};
+    use crate::util;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use yaml_rust::YamlLoader;
+
+    fn snippet_application(content: &str, snippets: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str(snippets).unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer.cursor.move_to(Position{ line: 0, offset: content.len() });
+        util::add_buffer(buffer, &mut app).unwrap();
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+
+        app
+    }
+
+    fn type_text(app: &mut Application, text: &str) {
+        for c in text.chars() {
+            app.view.last_key = Some(Key::Char(c));
+            super::insert_char(app).unwrap();
+        }
+    }
+
+    #[test]
+    fn expand_visits_tab_stops_and_finishes_at_the_final_position() {
+        let mut app = snippet_application("  fn", "snippets:\n  fn: \"fn $1($2) {\\n    $0\\n}\"");
+        super::expand(&mut app).unwrap();
+        type_text(&mut app, "amp");
+        super::next_tab_stop(&mut app).unwrap();
+        type_text(&mut app, "x");
+        super::next_tab_stop(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "  fn amp(x) {\n      \n  }");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 6 });
+        assert!(matches!(app.mode, Mode::Insert(_)));
+    }
+
+    #[test]
+    fn expand_updates_mirrored_tab_stops() {
+        let mut app = snippet_application("tag", "snippets:\n  tag: \"<$1>$0</$1>\"");
+        super::expand(&mut app).unwrap();
+        type_text(&mut app, "div");
+        app.view.last_key = Some(Key::Backspace);
+        super::backspace(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "<di></di>");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 3 });
+        assert!(matches!(app.mode, Mode::Snippet(_)));
+    }
+
+    #[test]
+    fn expand_inserts_a_tab_without_a_matching_trigger() {
+        let mut app = snippet_application("amp", "snippets:\n  fn: \"fn $1\"\ntab_width: 2");
+        super::expand(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp  ");
+        assert!(matches!(app.mode, Mode::Insert(_)));
+    }
+}
@@ -1,6 +1,11 @@
 use crate::errors::*;
 use crate::commands::Result;
 use crate::models::application::Application;
+use crate::util::{self, fold, token};
+use crate::util::spell::Dictionary;
+use crate::view::{Direction, Orientation, Pane};
+use scribe::Buffer;
+use scribe::buffer::{Position, Range};
 
 pub fn scroll_up(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -14,6 +19,31 @@ pub fn scroll_down(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Scrolls up by half of the screen, moving the cursor up by the same number of lines.
+pub fn scroll_half_page_up(app: &mut Application) -> Result {
+    let amount = app.view.half_page_height();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_up(buffer, amount)?;
+    for _ in 0..amount {
+        buffer.cursor.move_up();
+    }
+    app.view.scroll_to_cursor(buffer)?;
+    Ok(())
+}
+
+/// Scrolls down by half of the screen, moving the cursor down by the same
+/// number of lines. Scrolling stops once the last line is on-screen.
+pub fn scroll_half_page_down(app: &mut Application) -> Result {
+    let amount = app.view.half_page_height();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_down_within_buffer(buffer, amount)?;
+    for _ in 0..amount {
+        buffer.cursor.move_down();
+    }
+    app.view.scroll_to_cursor(buffer)?;
+    Ok(())
+}
+
 pub fn scroll_to_cursor(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     app.view.scroll_to_cursor(buffer)?;
@@ -25,3 +55,341 @@ pub fn scroll_cursor_to_center(app: &mut Application) -> Result {
     app.view.scroll_to_center(buffer)?;
     Ok(())
 }
+
+/// Reveals the cursor after jumping to it, placing it
+/// in the viewport according to the jump_position preference.
+pub fn scroll_to_jump_position(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let fraction = app.preferences.borrow().jump_position();
+    app.view.scroll_to_fraction(buffer, fraction)?;
+    Ok(())
+}
+
+/// Folds the block at the cursor, or unfolds the fold containing it. Blocks
+/// are delimited by brackets in languages that use them to group statements,
+/// and by indentation otherwise (or if there's no bracketed block).
+pub fn toggle_fold(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let line = buffer.cursor.line;
+    if app.view.unfold(buffer, line)? {
+        return Ok(());
+    }
+
+    let data = buffer.data();
+    let bracket_range = bracket_exclusions(buffer)
+        .and_then(|ignored| fold::bracket_range(&data, line, &ignored));
+    let tab_width = app.preferences.borrow().tab_width(buffer.path.as_ref());
+    let (start, end) = bracket_range
+        .or_else(|| fold::indentation_range(&data, line, tab_width))
+        .ok_or("No block to fold at the cursor")?;
+    app.view.fold(buffer, start, end)?;
+
+    // Keep the cursor out of the folded lines.
+    if line > start {
+        buffer.cursor.move_to(Position{ line: start, offset: 0 });
+    }
+    app.view.scroll_to_cursor(buffer)?;
+
+    Ok(())
+}
+
+pub fn unfold_all(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.unfold_all(buffer)?;
+
+    Ok(())
+}
+
+/// Folds every block in the buffer, collapsing it onto its outermost blocks.
+pub fn fold_all(app: &mut Application) -> Result {
+    fold_to_depth(app, |_| 1)
+}
+
+/// Folds the blocks enclosing the shallowest folds (or,
+/// if nothing is folded, the most deeply nested blocks).
+pub fn fold_more(app: &mut Application) -> Result {
+    fold_to_depth(app, |depth| depth.saturating_sub(1).max(1))
+}
+
+/// Unfolds the shallowest folds, folding the blocks nested within them instead.
+pub fn fold_less(app: &mut Application) -> Result {
+    fold_to_depth(app, |depth| depth + 1)
+}
+
+// Replaces the buffer's folds with the blocks at the depth derived from
+// that of its shallowest fold, moving the cursor out of any new folds.
+fn fold_to_depth<F: Fn(usize) -> usize>(app: &mut Application, target_depth: F) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let tab_width = app.preferences.borrow().tab_width(buffer.path.as_ref());
+    let ignored = bracket_exclusions(buffer);
+    let blocks = fold::blocks(&buffer.data(), tab_width, ignored.as_deref());
+    if blocks.is_empty() {
+        bail!("No blocks to fold");
+    }
+
+    let depth = target_depth(app.view.folded_depth(buffer, &blocks)?);
+    app.view.fold_to_depth(buffer, &blocks, depth)?;
+
+    let line = buffer.cursor.line;
+    let enclosing_fold = app.view
+        .folds(buffer)?
+        .into_iter()
+        .find(|&(start, end)| start < line && line <= end);
+    if let Some((start, _)) = enclosing_fold {
+        buffer.cursor.move_to(Position{ line: start, offset: 0 });
+    }
+    app.view.scroll_to_cursor(buffer)?;
+
+    Ok(())
+}
+
+// Brackets delimit blocks in languages that group statements with braces,
+// other than those in strings and comments, which are returned as ranges
+// to be ignored. Other languages' blocks are delimited by indentation.
+fn bracket_exclusions(buffer: &Buffer) -> Option<Vec<Range>> {
+    let uses_braces = buffer.syntax_definition
+        .as_ref()
+        .map(|syntax| util::indent::uses_braces(&syntax.name))
+        .unwrap_or(false);
+    if !uses_braces {
+        return None;
+    }
+
+    Some(
+        buffer.tokens()
+            .map(|tokens| token::string_and_comment_ranges(tokens.iter()).collect())
+            .unwrap_or_else(|_| Vec::new())
+    )
+}
+
+/// Underlines misspelled words in prose (comments, strings, and text
+/// documents) using the configured dictionary, or stops doing so.
+pub fn toggle_spell_check(app: &mut Application) -> Result {
+    if app.view.dictionary().is_some() {
+        app.view.set_dictionary(None);
+    } else {
+        let path = app.preferences.borrow().spell_dictionary();
+        app.view.set_dictionary(Some(Dictionary::load(&path)?));
+    }
+
+    Ok(())
+}
+
+/// Highlights occurrences of the word under the cursor, or stops doing so.
+pub fn toggle_word_highlights(app: &mut Application) -> Result {
+    app.view.toggle_word_highlights();
+
+    Ok(())
+}
+
+/// Splits the focused pane into two side-by-side panes, both showing the current buffer.
+pub fn split_vertically(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.split_pane(buffer, Orientation::Vertical)?;
+
+    Ok(())
+}
+
+/// Splits the focused pane into two stacked panes, both showing the current buffer.
+pub fn split_horizontally(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.split_pane(buffer, Orientation::Horizontal)?;
+
+    Ok(())
+}
+
+pub fn focus_pane_up(app: &mut Application) -> Result {
+    focus_pane(app, Direction::Up)
+}
+
+pub fn focus_pane_down(app: &mut Application) -> Result {
+    focus_pane(app, Direction::Down)
+}
+
+pub fn focus_pane_left(app: &mut Application) -> Result {
+    focus_pane(app, Direction::Left)
+}
+
+pub fn focus_pane_right(app: &mut Application) -> Result {
+    focus_pane(app, Direction::Right)
+}
+
+/// Moves focus to the next pane, cycling through all of them.
+pub fn focus_next_pane(app: &mut Application) -> Result {
+    let pane = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        app.view.focus_next_pane(buffer)?
+    };
+
+    restore_pane(app, pane)
+}
+
+/// Closes the focused pane, moving focus to the pane taking its place.
+/// Closing the second-to-last pane returns to a single, full-screen view.
+pub fn close_pane(app: &mut Application) -> Result {
+    let pane = app.view.close_pane()?;
+
+    restore_pane(app, pane)
+}
+
+/// Equally sizes panes that share a row (or column).
+pub fn balance_panes(app: &mut Application) -> Result {
+    app.view.balance_panes()
+}
+
+fn focus_pane(app: &mut Application, direction: Direction) -> Result {
+    let pane = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        app.view.focus_pane(buffer, direction)?
+    };
+
+    restore_pane(app, pane)
+}
+
+/// Selects a newly focused pane's buffer, restoring its cursor and scroll offset.
+fn restore_pane(app: &mut Application, pane: Pane) -> Result {
+    util::select_buffer_by_id(pane.buffer_id, app);
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    buffer.cursor.move_to(pane.cursor);
+    app.view.restore_pane(buffer, &pane)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::Application;
+    use crate::util;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn half_page_scrolling_moves_the_cursor_and_stops_at_the_end_of_the_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        for _ in 0..20 {
+            buffer.insert("amp\n");
+        }
+        app.workspace.add_buffer(buffer);
+
+        // The 10 line test terminal has 9 lines for the buffer, so half a page is 4.
+        super::scroll_half_page_down(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 4);
+
+        for _ in 0..3 {
+            super::scroll_half_page_down(&mut app).unwrap();
+        }
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.cursor.line, 16);
+        assert_eq!(app.view.visible_lines(buffer).unwrap(), (12, 20));
+
+        super::scroll_half_page_up(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.cursor.line, 12);
+        assert_eq!(app.view.visible_lines(buffer).unwrap(), (8, 16));
+    }
+
+    #[test]
+    fn toggle_fold_folds_the_enclosing_block_and_unfolds_it() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp:\n    one\n    two\nend\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 2 });
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        super::toggle_fold(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(app.view.folds(buffer).unwrap(), vec![(0, 2)]);
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+
+        // Vertical movement steps over the folded lines.
+        commands::cursor::move_down(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 3);
+        commands::cursor::move_up(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 0);
+
+        super::toggle_fold(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert!(app.view.folds(buffer).unwrap().is_empty());
+    }
+
+    #[test]
+    fn fold_all_and_fold_less_fold_blocks_by_depth() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("class Amp:\n    def edit(self):\n        pass\n    x = 1\nend\n");
+        buffer.cursor.move_to(Position{ line: 2, offset: 4 });
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        // The cursor moves to the first line of the fold hiding it.
+        super::fold_all(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(app.view.folds(buffer).unwrap(), vec![(0, 3)]);
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+
+        super::fold_less(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(app.view.folds(buffer).unwrap(), vec![(1, 2)]);
+
+        super::fold_less(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert!(app.view.folds(buffer).unwrap().is_empty());
+
+        // With nothing folded, the most deeply nested blocks are folded first.
+        super::fold_more(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(app.view.folds(buffer).unwrap(), vec![(1, 2)]);
+
+        super::fold_more(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(app.view.folds(buffer).unwrap(), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn focus_next_pane_restores_each_panes_buffer_and_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        util::add_buffer(buffer, &mut app).unwrap();
+        super::split_vertically(&mut app).unwrap();
+        assert_eq!(app.view.pane_position(), Some((1, 2)));
+
+        // The left pane shows a second buffer.
+        let mut other_buffer = Buffer::new();
+        other_buffer.insert("second");
+        util::add_buffer(other_buffer, &mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 3 });
+
+        super::focus_next_pane(&mut app).unwrap();
+        assert_eq!(app.view.pane_position(), Some((2, 2)));
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amp\neditor\n");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+
+        super::focus_pane_left(&mut app).unwrap();
+        assert_eq!(app.view.pane_position(), Some((1, 2)));
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "second");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 3 });
+        assert!(super::focus_pane_up(&mut app).is_err());
+    }
+
+    #[test]
+    fn closing_the_second_to_last_pane_returns_to_a_full_screen_view() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        util::add_buffer(buffer, &mut app).unwrap();
+        super::split_horizontally(&mut app).unwrap();
+        super::split_vertically(&mut app).unwrap();
+        assert_eq!(app.view.pane_position(), Some((1, 3)));
+
+        super::close_pane(&mut app).unwrap();
+        assert_eq!(app.view.pane_position(), Some((1, 2)));
+        super::close_pane(&mut app).unwrap();
+        assert_eq!(app.view.pane_position(), None);
+        assert!(super::close_pane(&mut app).is_err());
+    }
+}
@@ -172,7 +172,7 @@ fn parse_mode_key_bindings(mode: &Yaml, commands: &HashMap<&str, Command>) -> Re
 ///
 ///   ctrl-r becomes Key::Ctrl('r')
 ///
-fn parse_key(data: &str) -> Result<Key> {
+pub fn parse_key(data: &str) -> Result<Key> {
     let mut key_components = data.split('-');
     let component = key_components.next().ok_or(
         "A keymap key is an empty string",
@@ -208,6 +208,7 @@ fn parse_key(data: &str) -> Result<Key> {
             "escape"    => Key::Esc,
             "tab"       => Key::Tab,
             "enter"     => Key::Enter,
+            "minus"     => Key::Char('-'),
             "_"         => Key::AnyChar,
             _           => Key::Char(
                 // It's not a keyword; take its first character, if available.
@@ -337,7 +338,8 @@ mod tests {
             ("normal:\n  insert: cursor::move_up",    Key::Insert,    commands::cursor::move_up),
             ("normal:\n  escape: cursor::move_up",    Key::Esc,       commands::cursor::move_up),
             ("normal:\n  tab: cursor::move_up",       Key::Tab,       commands::cursor::move_up),
-            ("normal:\n  enter: cursor::move_up",     Key::Enter,     commands::cursor::move_up)
+            ("normal:\n  enter: cursor::move_up",     Key::Enter,     commands::cursor::move_up),
+            ("normal:\n  minus: cursor::move_up",     Key::Char('-'), commands::cursor::move_up)
         ];
 
         for (binding, key, command) in mappings {
@@ -362,6 +364,14 @@ mod tests {
             (command[0] as *const usize),
             (commands::cursor::move_up as *const usize)
         );
+
+        let command = keymap.commands_for("normal", &Key::Char('-')).expect(
+            "Keymap doesn't contain command",
+        );
+        assert_eq!(
+            (command[0] as *const usize),
+            (commands::application::jump_back as *const usize)
+        );
     }
 
     #[test]
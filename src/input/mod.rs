@@ -1,6 +1,10 @@
 pub use self::key_map::KeyMap;
+pub use self::replay::Replay;
 
 mod key_map;
+mod replay;
+
+use scribe::buffer::Position;
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Key {
@@ -22,3 +26,13 @@ pub enum Key {
     Char(char),
     Ctrl(char),
 }
+
+/// Mouse input, located using zero-based screen coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseEvent {
+    Press(Position),
+    Drag(Position),
+    Release(Position),
+    ScrollUp(Position),
+    ScrollDown(Position),
+}
@@ -0,0 +1,147 @@
+use crate::errors::*;
+use crate::input::Key;
+use crate::models::application::Event;
+use std::fs;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+use super::key_map::parse_key;
+
+const REPLAY_ARG: &str = "--replay";
+const REPLAY_DELAY_ARG: &str = "--replay-delay";
+
+/// A sequence of key presses to be fed to the application
+/// as though they were typed, used for scripting and demos.
+pub struct Replay {
+    keys: Vec<Key>,
+    delay: Duration,
+}
+
+impl Replay {
+    /// Builds a replay from the --replay FILE and --replay-delay MILLISECONDS
+    /// arguments, removing them from the argument list so that they aren't
+    /// treated as paths. Keys aren't read from stdin, which the terminal is
+    /// still listening to for input.
+    pub fn from_args(args: &mut Vec<String>) -> Result<Option<Replay>> {
+        let path = take_arg_value(args, REPLAY_ARG)?;
+        let delay = match take_arg_value(args, REPLAY_DELAY_ARG)? {
+            Some(delay) => delay.parse().chain_err(|| {
+                format!("Invalid {} value \"{}\"", REPLAY_DELAY_ARG, delay)
+            })?,
+            None => 0,
+        };
+
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let data = fs::read_to_string(&path)
+            .chain_err(|| format!("Couldn't read replay file \"{}\"", path))?;
+
+        Ok(Some(Replay{ keys: parse(&data)?, delay: Duration::from_millis(delay) }))
+    }
+
+    /// Spins up a thread that sends the replay's key presses
+    /// to the application event channel, pausing between each.
+    pub fn start(self, events: Sender<Event>) {
+        thread::spawn(move || {
+            for key in self.keys {
+                thread::sleep(self.delay);
+
+                // Stop once the application has gone away.
+                if events.send(Event::Key(key)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Parses whitespace-separated keys, using the same notation as keymaps
+/// (e.g. "i h i escape ctrl-s"). Lines starting with "//" are comments.
+fn parse(data: &str) -> Result<Vec<Key>> {
+    let mut keys = Vec::new();
+    for line in data.lines().filter(|line| !line.trim_start().starts_with("//")) {
+        for token in line.split_whitespace() {
+            let key = if token.chars().count() == 1 {
+                // Keymap notation reserves characters like "-" and "_".
+                Key::Char(token.chars().nth(0).unwrap())
+            } else {
+                parse_key(token).chain_err(|| format!("Invalid replay key \"{}\"", token))?
+            };
+            keys.push(key);
+        }
+    }
+
+    Ok(keys)
+}
+
+fn take_arg_value(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    match args.iter().position(|arg| arg == name) {
+        Some(index) => {
+            if index + 1 >= args.len() {
+                bail!("The {} option requires a value", name);
+            }
+            let value = args.remove(index + 1);
+            args.remove(index);
+
+            Ok(Some(value))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::Key;
+    use crate::models::application::Event;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use super::{parse, Replay};
+
+    #[test]
+    fn parse_uses_keymap_notation_and_skips_comments() {
+        let keys = parse("// Insert a dash.\ni - space\n  escape ctrl-s\n").unwrap();
+
+        assert_eq!(
+            keys,
+            vec![Key::Char('i'), Key::Char('-'), Key::Char(' '), Key::Esc, Key::Ctrl('s')]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_keys() {
+        assert!(parse("alt-x").is_err());
+    }
+
+    #[test]
+    fn from_args_removes_replay_arguments() {
+        let mut args = vec![
+            String::from("amp"),
+            String::from("--replay-delay"),
+            String::from("10"),
+            String::from("Cargo.toml"),
+        ];
+
+        // Without a replay file, there's nothing to replay.
+        assert!(Replay::from_args(&mut args).unwrap().is_none());
+        assert_eq!(args, vec![String::from("amp"), String::from("Cargo.toml")]);
+    }
+
+    #[test]
+    fn from_args_requires_option_values() {
+        let mut args = vec![String::from("amp"), String::from("--replay")];
+
+        assert!(Replay::from_args(&mut args).is_err());
+    }
+
+    #[test]
+    fn start_sends_keys_to_the_event_channel() {
+        let (event_tx, event_rx) = mpsc::channel();
+        let replay = Replay{ keys: vec![Key::Char('i'), Key::Esc], delay: Duration::from_millis(0) };
+        replay.start(event_tx);
+
+        assert_eq!(event_rx.recv().unwrap(), Event::Key(Key::Char('i')));
+        assert_eq!(event_rx.recv().unwrap(), Event::Key(Key::Esc));
+    }
+}
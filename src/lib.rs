@@ -5,9 +5,12 @@
 extern crate app_dirs;
 extern crate bloodhound;
 extern crate fragment;
+extern crate fuzzy_matcher;
 extern crate git2;
+extern crate ignore;
 extern crate luthor;
 extern crate mio;
+extern crate notify;
 extern crate pad;
 extern crate regex;
 extern crate scribe;
@@ -15,6 +18,7 @@ extern crate signal_hook;
 extern crate syntect;
 extern crate unicode_segmentation;
 extern crate clipboard;
+extern crate encoding_rs;
 extern crate yaml_rust as yaml;
 extern crate smallvec;
 
@@ -24,6 +28,9 @@ extern crate error_chain;
 #[macro_use]
 extern crate lazy_static;
 
+#[macro_use]
+extern crate serde_json;
+
 // Private modules
 mod commands;
 mod errors;
@@ -35,4 +42,5 @@ mod view;
 
 // External application API
 pub use crate::models::Application;
+pub use crate::models::application::modes::open::{Index as OpenModeIndex, IndexChange as OpenModeIndexChange};
 pub use crate::errors::Error;
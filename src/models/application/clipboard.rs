@@ -1,24 +1,31 @@
 use crate::errors::*;
 use clipboard::{ClipboardContext, ClipboardProvider};
+use std::collections::HashMap;
+use std::mem;
 
-/// In-app content can be captured in both regular and full-line selection
-/// modes. This type describes the structure of said content, based on the
-/// context in which it was captured. When OS-level clipboard contents are
-/// used, they are always represented as inline, as we cannot infer block
-/// style without the copy context.
+/// In-app content can be captured in regular, full-line, and rectangular
+/// (block) selection modes. This type describes the structure of said
+/// content, based on the context in which it was captured. When OS-level
+/// clipboard contents are used, they are always represented as inline, as
+/// we cannot infer block style without the copy context. Rectangular content
+/// holds one row per selected line, separated by newlines.
 #[derive(Debug, PartialEq)]
 pub enum ClipboardContent {
     Inline(String),
     Block(String),
+    Rectangle(String),
     None,
 }
 
 /// Qualifies in-app copy/paste content with structural information, and
 /// synchronizes said content with the OS-level clipboard (preferring it
-/// in scenarios where it differs from the in-app equivalent).
+/// in scenarios where it differs from the in-app equivalent). Content can
+/// also be stored in named registers (a-z), which are kept in-app only.
 pub struct Clipboard {
     content: ClipboardContent,
     system_clipboard: Option<ClipboardContext>,
+    registers: HashMap<char, ClipboardContent>,
+    register: Option<char>,
 }
 
 impl Default for Clipboard {
@@ -38,13 +45,66 @@ impl Clipboard {
         Clipboard {
             content: ClipboardContent::None,
             system_clipboard,
+            registers: HashMap::new(),
+            register: None,
         }
     }
 
+    /// Builds a clipboard that doesn't synchronize with the system clipboard.
+    pub fn internal() -> Clipboard {
+        Clipboard {
+            content: ClipboardContent::None,
+            system_clipboard: None,
+            registers: HashMap::new(),
+            register: None,
+        }
+    }
+
+    /// Uses the named register (a-z) for the next get or set operation,
+    /// after which the unnamed (regular) clipboard is used again. Using
+    /// an uppercase name appends to the register, rather than replacing it.
+    pub fn select_register(&mut self, name: char) -> Result<()> {
+        if !name.is_ascii_alphabetic() {
+            bail!("Invalid register \"{}\"; use a-z", name);
+        }
+        self.register = Some(name);
+
+        Ok(())
+    }
+
+    /// Whether a named register has been selected for the next operation.
+    pub fn register_selected(&self) -> bool {
+        self.register.is_some()
+    }
+
+    /// Goes back to using the unnamed clipboard, without using
+    /// the selected register (e.g. if it was selected by mistake).
+    pub fn clear_register(&mut self) {
+        self.register = None;
+    }
+
+    /// The named registers with content, sorted by name.
+    pub fn registers(&self) -> Vec<(char, &ClipboardContent)> {
+        let mut registers: Vec<(char, &ClipboardContent)> = self.registers
+            .iter()
+            .filter(|(_, content)| **content != ClipboardContent::None)
+            .map(|(name, content)| (*name, content))
+            .collect();
+        registers.sort_by_key(|(name, _)| *name);
+
+        registers
+    }
+
     /// Returns the in-app clipboard content. However, if in-app content
     /// differs from the system clipboard, the system clipboard content will
     /// be saved to the in-app clipboard as inline data and returned instead.
     pub fn get_content(&mut self) -> &ClipboardContent {
+        if let Some(name) = self.register.take() {
+            return self.registers
+                .entry(name.to_ascii_lowercase())
+                .or_insert(ClipboardContent::None);
+        }
+
         // Check the system clipboard for newer content.
         let new_content = match self.system_clipboard {
             Some(ref mut clipboard) => {
@@ -56,7 +116,8 @@ impl Clipboard {
                             // There is system clipboard content we can use.
                             match self.content {
                                 ClipboardContent::Inline(ref app_content) |
-                                ClipboardContent::Block(ref app_content) => {
+                                ClipboardContent::Block(ref app_content) |
+                                ClipboardContent::Rectangle(ref app_content) => {
                                     // We have in-app clipboard content, too. Prefer
                                     // the system clipboard content if they differ.
                                     if content != *app_content {
@@ -84,24 +145,154 @@ impl Clipboard {
         &self.content
     }
 
+    /// Adds the content to the end of the existing content (that of the
+    /// selected register, if any), rather than replacing it. Whole-line
+    /// content keeps the combination line-wise, so it's pasted as such.
+    pub fn append_content(&mut self, content: ClipboardContent) -> Result<()> {
+        if let Some(name) = self.register.take() {
+            self.register = Some(name.to_ascii_uppercase());
+            return self.set_content(content);
+        }
+
+        // Pick up newer system clipboard content before appending to it.
+        self.get_content();
+        let existing = mem::replace(&mut self.content, ClipboardContent::None);
+
+        self.set_content(append(existing, content))
+    }
+
     // Updates the in-app and system clipboards with the specified content.
     pub fn set_content(&mut self, content: ClipboardContent) -> Result<()> {
+        if let Some(name) = self.register.take() {
+            let register = self.registers
+                .entry(name.to_ascii_lowercase())
+                .or_insert(ClipboardContent::None);
+            *register = if name.is_ascii_uppercase() {
+                append(mem::replace(register, ClipboardContent::None), content)
+            } else {
+                content
+            };
+
+            return Ok(());
+        }
+
         // Update the in-app clipboard.
         self.content = content;
 
         // Update the system clipboard.
-        match self.content {
+        let updated = match self.content {
             ClipboardContent::Inline(ref app_content) |
-            ClipboardContent::Block(ref app_content) => {
-                if let Some(ref mut clipboard) = self.system_clipboard {
-                    return clipboard
-                        .set_contents(app_content.clone())
-                        .map_err(|_| Error::from("Failed to update system clipboard"));
+            ClipboardContent::Block(ref app_content) |
+            ClipboardContent::Rectangle(ref app_content) => {
+                match self.system_clipboard {
+                    Some(ref mut clipboard) => clipboard.set_contents(app_content.clone()).is_ok(),
+                    None => true,
                 }
             }
-            _ => (),
+            _ => true,
+        };
+
+        // The system clipboard has become unavailable (e.g. the display server
+        // went away); fall back to the in-app clipboard, which is already set.
+        if !updated {
+            self.system_clipboard = None;
         }
 
         Ok(())
     }
 }
+
+/// Combines clipboard content, which becomes a block if either part is one.
+/// Rectangles gain rows when appended to one another, and are otherwise
+/// treated as blocks.
+fn append(existing: ClipboardContent, content: ClipboardContent) -> ClipboardContent {
+    match (existing, content) {
+        (ClipboardContent::None, content) => content,
+        (existing, ClipboardContent::None) => existing,
+        (ClipboardContent::Inline(existing), ClipboardContent::Inline(content)) => {
+            ClipboardContent::Inline(existing + &content)
+        }
+        (ClipboardContent::Rectangle(existing), ClipboardContent::Rectangle(content)) => {
+            ClipboardContent::Rectangle(with_trailing_newline(existing) + &content)
+        }
+        (ClipboardContent::Inline(existing), ClipboardContent::Block(content)) |
+        (ClipboardContent::Inline(existing), ClipboardContent::Rectangle(content)) |
+        (ClipboardContent::Block(existing), ClipboardContent::Inline(content)) |
+        (ClipboardContent::Block(existing), ClipboardContent::Block(content)) |
+        (ClipboardContent::Block(existing), ClipboardContent::Rectangle(content)) |
+        (ClipboardContent::Rectangle(existing), ClipboardContent::Inline(content)) |
+        (ClipboardContent::Rectangle(existing), ClipboardContent::Block(content)) => {
+            ClipboardContent::Block(with_trailing_newline(existing) + &with_trailing_newline(content))
+        }
+    }
+}
+
+fn with_trailing_newline(mut content: String) -> String {
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clipboard, ClipboardContent};
+
+    #[test]
+    fn internal_clipboard_preserves_content_structure() {
+        let mut clipboard = Clipboard::internal();
+        clipboard.set_content(ClipboardContent::Block(String::from("amp\n"))).unwrap();
+
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Block(String::from("amp\n")));
+    }
+
+    #[test]
+    fn named_registers_are_separate_from_the_unnamed_clipboard() {
+        let mut clipboard = Clipboard::internal();
+        clipboard.set_content(ClipboardContent::Inline(String::from("amp"))).unwrap();
+        clipboard.select_register('a').unwrap();
+        clipboard.set_content(ClipboardContent::Block(String::from("editor\n"))).unwrap();
+
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Inline(String::from("amp")));
+        clipboard.select_register('a').unwrap();
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Block(String::from("editor\n")));
+    }
+
+    #[test]
+    fn uppercase_register_names_append_content() {
+        let mut clipboard = Clipboard::internal();
+        clipboard.select_register('a').unwrap();
+        clipboard.set_content(ClipboardContent::Inline(String::from("amp"))).unwrap();
+        clipboard.select_register('A').unwrap();
+        clipboard.set_content(ClipboardContent::Inline(String::from(" editor"))).unwrap();
+        clipboard.select_register('A').unwrap();
+        clipboard.set_content(ClipboardContent::Block(String::from("text\n"))).unwrap();
+
+        assert_eq!(
+            clipboard.registers(),
+            vec![('a', &ClipboardContent::Block(String::from("amp editor\ntext\n")))]
+        );
+    }
+
+    #[test]
+    fn append_content_combines_content_in_the_unnamed_clipboard() {
+        let mut clipboard = Clipboard::internal();
+        clipboard.set_content(ClipboardContent::Block(String::from("amp\n"))).unwrap();
+        clipboard.append_content(ClipboardContent::Block(String::from("editor\n"))).unwrap();
+
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Block(String::from("amp\neditor\n")));
+
+        clipboard.select_register('a').unwrap();
+        clipboard.append_content(ClipboardContent::Inline(String::from("text"))).unwrap();
+        clipboard.select_register('a').unwrap();
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Inline(String::from("text")));
+    }
+
+    #[test]
+    fn select_register_rejects_invalid_names() {
+        let mut clipboard = Clipboard::internal();
+
+        assert!(clipboard.select_register('1').is_err());
+    }
+}
@@ -1,9 +1,18 @@
-use crate::input::Key;
-use crate::models::application::modes::open::Index;
+use crate::input::{Key, MouseEvent};
+use crate::models::application::modes::WorkspaceSearchResults;
+use crate::models::application::modes::open::{Index, IndexChange};
+use crate::util::lsp::Diagnostic;
+use std::path::PathBuf;
 
 #[derive(Debug, PartialEq)]
 pub enum Event {
     Key(Key),
+    Mouse(MouseEvent),
     Resize,
-    OpenModeIndexComplete(Index)
+    OpenModeIndexComplete(Index),
+    OpenModeIndexChanged(IndexChange),
+    WorkspaceSearchComplete(WorkspaceSearchResults),
+    FileChanged(PathBuf),
+    AutoSaveTick,
+    Diagnostics(PathBuf, Vec<Diagnostic>)
 }
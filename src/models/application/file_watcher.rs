@@ -0,0 +1,150 @@
+use crate::models::application::Event;
+use crate::util;
+use notify::{self, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const DEBOUNCE_DELAY: u64 = 100;
+
+/// Watches the directories of open buffers' files, forwarding changes to the
+/// application's event channel. Directories (rather than files) are watched
+/// so that changes made by replacing a file (e.g. atomic saves) are caught.
+pub struct FileWatcher {
+    watcher: Option<RecommendedWatcher>,
+    directories: HashMap<PathBuf, usize>,
+    writes: HashMap<PathBuf, SystemTime>,
+}
+
+impl FileWatcher {
+    /// Creates a watcher that sends file changes to the specified channel.
+    /// Watching is best-effort: if the platform watcher can't be set up, or
+    /// a directory can't be watched, changes simply won't be reported.
+    pub fn new(event_channel: Sender<Event>) -> FileWatcher {
+        let (tx, rx) = mpsc::channel();
+        let watcher = notify::watcher(tx, Duration::from_millis(DEBOUNCE_DELAY)).ok();
+
+        thread::spawn(move || {
+            for event in rx {
+                let path = match event {
+                    DebouncedEvent::Create(path) |
+                    DebouncedEvent::Write(path) |
+                    DebouncedEvent::Rename(_, path) => path,
+                    _ => continue,
+                };
+
+                if event_channel.send(Event::FileChanged(path)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        FileWatcher { watcher, directories: HashMap::new(), writes: HashMap::new() }
+    }
+
+    pub fn watch(&mut self, path: &Path) {
+        let directory = match watched_directory(path) {
+            Some(directory) => directory,
+            None => return,
+        };
+        let count = self.directories.entry(directory.clone()).or_insert(0);
+        *count += 1;
+
+        if *count == 1 {
+            if let Some(ref mut watcher) = self.watcher {
+                let _ = watcher.watch(&directory, RecursiveMode::NonRecursive);
+            }
+        }
+    }
+
+    /// Stops watching the path's directory, once no other watched paths share it.
+    pub fn unwatch(&mut self, path: &Path) {
+        let directory = match watched_directory(path) {
+            Some(directory) => directory,
+            None => return,
+        };
+        let remaining = match self.directories.get_mut(&directory) {
+            Some(count) => {
+                *count -= 1;
+                *count
+            }
+            None => return,
+        };
+
+        if remaining == 0 {
+            self.directories.remove(&directory);
+            if let Some(ref mut watcher) = self.watcher {
+                let _ = watcher.unwatch(&directory);
+            }
+        }
+    }
+
+    /// Remembers that we've just written the file (e.g. by saving a buffer),
+    /// so that the resulting change isn't mistaken for an external one.
+    pub fn record_write(&mut self, path: &Path) {
+        if let Ok(modified) = path.metadata().and_then(|metadata| metadata.modified()) {
+            self.writes.insert(util::canonical_path(path), modified);
+        }
+    }
+
+    /// Whether the file is as we last wrote it, judging by its modification time.
+    pub fn written_by_us(&self, path: &Path) -> bool {
+        let modified = match path.metadata().and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(_) => return false,
+        };
+
+        self.writes.get(&util::canonical_path(path)) == Some(&modified)
+    }
+}
+
+fn watched_directory(path: &Path) -> Option<PathBuf> {
+    util::canonical_path(path).parent().map(|directory| directory.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileWatcher;
+    use std::env;
+    use std::fs;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn unwatch_keeps_directories_watched_by_other_paths() {
+        let (tx, _rx) = mpsc::channel();
+        let mut watcher = FileWatcher::new(tx);
+        let directory = env::current_dir().unwrap();
+
+        watcher.watch(&directory.join("Cargo.toml"));
+        watcher.watch(&directory.join("Cargo.lock"));
+        watcher.unwatch(&directory.join("Cargo.toml"));
+        assert_eq!(watcher.directories.get(&directory.canonicalize().unwrap()), Some(&1));
+
+        watcher.unwatch(&directory.join("Cargo.lock"));
+        assert!(watcher.directories.is_empty());
+    }
+
+    #[test]
+    fn written_by_us_detects_later_writes() {
+        let (tx, _rx) = mpsc::channel();
+        let mut watcher = FileWatcher::new(tx);
+        let path = env::temp_dir().join("amp_file_watcher_writes.txt");
+        fs::write(&path, "amp").unwrap();
+        assert!(!watcher.written_by_us(&path));
+
+        watcher.record_write(&path);
+        assert!(watcher.written_by_us(&path));
+
+        // Make sure the modification time advances, however coarse it is.
+        let modified = path.metadata().unwrap().modified().unwrap();
+        while path.metadata().unwrap().modified().unwrap() == modified {
+            thread::sleep(Duration::from_millis(10));
+            fs::write(&path, "editor").unwrap();
+        }
+        assert!(!watcher.written_by_us(&path));
+    }
+}
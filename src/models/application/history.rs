@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::PathBuf;
+
+const HISTORY_LIMIT: usize = 50;
+
+/// A bounded list of previously entered inputs (e.g. search queries), with a
+/// position used to step through them. Entries are persisted to disk when the
+/// history has a path.
+#[derive(Default)]
+pub struct History {
+    entries: Vec<String>,
+    position: Option<usize>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Loads the history stored at the specified path, starting
+    /// with an empty history if it doesn't exist or can't be read.
+    pub fn load(path: PathBuf) -> History {
+        let entries = fs::read_to_string(&path)
+            .map(|data| data.lines().map(String::from).collect())
+            .unwrap_or_else(|_| Vec::new());
+
+        History { entries, position: None, path: Some(path) }
+    }
+
+    /// Adds an entry to the end of the history, collapsing consecutive
+    /// duplicates and discarding the oldest entries past the limit.
+    pub fn add(&mut self, entry: &str) {
+        self.reset();
+        if entry.is_empty() || entry.contains('\n') || self.entries.last().map(|e| e == entry).unwrap_or(false) {
+            return;
+        }
+
+        self.entries.push(entry.to_string());
+        if self.entries.len() > HISTORY_LIMIT {
+            let excess = self.entries.len() - HISTORY_LIMIT;
+            self.entries.drain(..excess);
+        }
+
+        // History is a convenience; failing to persist it shouldn't get in the way.
+        if let Some(ref path) = self.path {
+            let _ = fs::write(path, self.entries.join("\n"));
+        }
+    }
+
+    /// The entries, from newest to oldest.
+    pub fn recent(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().rev().map(|e| e.as_str())
+    }
+
+    /// Steps back to the next-oldest entry, stopping at the oldest.
+    pub fn previous(&mut self) -> Option<&str> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => self.entries.len() - 1,
+            Some(position) => position.saturating_sub(1),
+        };
+        self.position = Some(position);
+
+        self.entries.get(position).map(|e| e.as_str())
+    }
+
+    /// Steps forward to the next-newest entry, returning
+    /// nothing once we've moved past the newest.
+    pub fn next(&mut self) -> Option<&str> {
+        let position = match self.position {
+            Some(position) if position + 1 < self.entries.len() => position + 1,
+            _ => {
+                self.reset();
+                return None;
+            }
+        };
+        self.position = Some(position);
+
+        self.entries.get(position).map(|e| e.as_str())
+    }
+
+    /// Moves back to the end of the history.
+    pub fn reset(&mut self) {
+        self.position = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, HISTORY_LIMIT};
+
+    #[test]
+    fn add_collapses_consecutive_duplicates() {
+        let mut history = History::default();
+        history.add("amp");
+        history.add("amp");
+        history.add("editor");
+        history.add("amp");
+
+        assert_eq!(history.entries, vec!["amp", "editor", "amp"]);
+    }
+
+    #[test]
+    fn add_discards_the_oldest_entries_past_the_limit() {
+        let mut history = History::default();
+        for i in 0..HISTORY_LIMIT + 2 {
+            history.add(&i.to_string());
+        }
+
+        assert_eq!(history.entries.len(), HISTORY_LIMIT);
+        assert_eq!(history.entries[0], "2");
+    }
+
+    #[test]
+    fn previous_and_next_step_through_entries() {
+        let mut history = History::default();
+        history.add("amp");
+        history.add("editor");
+
+        assert_eq!(history.previous(), Some("editor"));
+        assert_eq!(history.previous(), Some("amp"));
+        assert_eq!(history.previous(), Some("amp"));
+        assert_eq!(history.next(), Some("editor"));
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous(), Some("editor"));
+    }
+
+    #[test]
+    fn recent_lists_entries_from_newest_to_oldest() {
+        let mut history = History::default();
+        history.add("amp");
+        history.add("editor");
+
+        assert_eq!(history.recent().collect::<Vec<&str>>(), vec!["editor", "amp"]);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::errors::*;
+use crate::models::application::Event;
+use crate::util::lsp;
+use scribe::Buffer;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufReader, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const INITIALIZE_REQUEST_ID: u64 = 0;
+const SHUTDOWN_REQUEST_ID: u64 = 1;
+
+// How long servers are given to exit once asked to, in milliseconds.
+const EXIT_TIMEOUT: u64 = 200;
+
+/// Runs the language servers configured for open buffers' filetypes, keeping
+/// them in sync with the buffers' content and forwarding their diagnostics to
+/// the application's event channel. Servers are started on demand (one per
+/// configured command), and are shut down when dropped.
+pub struct LanguageServers {
+    servers: HashMap<Vec<String>, Server>,
+    documents: HashMap<PathBuf, Document>,
+    event_channel: Sender<Event>,
+}
+
+/// A buffer opened with a language server, and the revision last sent to it.
+struct Document {
+    command: Vec<String>,
+    uri: String,
+    revision: usize,
+}
+
+struct Server {
+    process: Child,
+    input: Sender<Outgoing>,
+}
+
+/// A message for a server's writer thread, which owns its input, so that
+/// writing to a server that isn't reading never blocks the caller.
+enum Outgoing {
+    /// Written right away (e.g. responses to the server).
+    Immediate(Value),
+
+    /// Queued until the server has responded to the initialize
+    /// request, since the protocol requires it, and written then.
+    Deferred(Value),
+
+    /// The server has responded to the initialize request.
+    Initialized,
+}
+
+/// A server's input, written to by its writer thread.
+struct Connection {
+    input: ChildStdin,
+    initialized: bool,
+    pending: Vec<Value>,
+}
+
+impl LanguageServers {
+    pub fn new(event_channel: Sender<Event>) -> LanguageServers {
+        LanguageServers {
+            servers: HashMap::new(),
+            documents: HashMap::new(),
+            event_channel,
+        }
+    }
+
+    /// Opens the buffer with the language server started using the command
+    /// (starting it if need be) or, if it's already been opened, sends its
+    /// content if it has changed since (i.e. its revision has advanced).
+    /// Buffers without a path or configured command are ignored.
+    pub fn sync(&mut self, buffer: &Buffer, revision: usize, command: Option<Vec<String>>, root: &Path) -> Result<()> {
+        let path = match buffer.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        if let Some(document) = self.documents.get_mut(path) {
+            if document.revision == revision {
+                return Ok(());
+            }
+            document.revision = revision;
+
+            return match self.servers.get(&document.command) {
+                Some(server) => server.send(lsp::notification("textDocument/didChange", json!({
+                    "textDocument": { "uri": document.uri, "version": revision },
+                    "contentChanges": [{ "text": buffer.data() }]
+                }))),
+                None => Ok(()),
+            };
+        }
+
+        let command = match command {
+            Some(command) => command,
+            None => return Ok(()),
+        };
+
+        // Track the document before starting its server, so
+        // that failing to do so isn't retried on every change.
+        let uri = lsp::path_uri(&root.join(path));
+        self.documents.insert(path.clone(), Document {
+            command: command.clone(),
+            uri: uri.clone(),
+            revision,
+        });
+        if !self.servers.contains_key(&command) {
+            let server = Server::start(&command, root, self.event_channel.clone())?;
+            self.servers.insert(command.clone(), server);
+        }
+
+        let language_id = buffer
+            .syntax_definition
+            .as_ref()
+            .map(|syntax| syntax.name.to_lowercase())
+            .unwrap_or_default();
+        self.servers[&command].send(lsp::notification("textDocument/didOpen", json!({
+            "textDocument": {
+                "uri": uri,
+                "languageId": language_id,
+                "version": revision,
+                "text": buffer.data()
+            }
+        })))
+    }
+
+    /// Lets the path's language server know that it's been closed.
+    pub fn close(&mut self, path: &Path) {
+        if let Some(document) = self.documents.remove(path) {
+            if let Some(server) = self.servers.get(&document.command) {
+                let _ = server.send(lsp::notification("textDocument/didClose", json!({
+                    "textDocument": { "uri": document.uri }
+                })));
+            }
+        }
+    }
+}
+
+impl Server {
+    /// Spawns the server, starting the initialization handshake, a thread that
+    /// writes messages sent to it, and a thread that reads its messages,
+    /// forwarding any diagnostics it publishes.
+    fn start(command: &[String], root: &Path, event_channel: Sender<Event>) -> Result<Server> {
+        let (program, args) = command.split_first().ok_or("The language server command is empty")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .chain_err(|| format!("Couldn't start the \"{}\" language server", program))?;
+        let input = child.stdin.take().ok_or("Couldn't write to the language server")?;
+        let output = child.stdout.take().ok_or("Couldn't read from the language server")?;
+
+        let mut connection = Connection {
+            input,
+            initialized: false,
+            pending: Vec::new(),
+        };
+        connection.write(&lsp::request(INITIALIZE_REQUEST_ID, "initialize", json!({
+            "processId": process::id(),
+            "rootUri": lsp::path_uri(root),
+            "capabilities": {
+                "textDocument": {
+                    "synchronization": { "didSave": false },
+                    "publishDiagnostics": {}
+                }
+            }
+        })))?;
+        let (input_sender, input_receiver) = mpsc::channel();
+        thread::spawn(move || connection.write_all(input_receiver));
+
+        let reader_input = input_sender.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(output);
+            while let Ok(Some(message)) = lsp::read_message(&mut reader) {
+                let id = message.get("id").cloned();
+                let outgoing = match (message.get("method").and_then(Value::as_str), id) {
+                    (Some("textDocument/publishDiagnostics"), _) => {
+                        if let Some((path, diagnostics)) = lsp::parse_diagnostics(&message["params"]) {
+                            if event_channel.send(Event::Diagnostics(path, diagnostics)).is_err() {
+                                break;
+                            }
+                        }
+                        continue;
+                    }
+                    // We don't support any server requests, but still respond to them.
+                    (Some(_), Some(id)) => Outgoing::Immediate(lsp::response(id, Value::Null)),
+                    (None, Some(ref id)) if id.as_u64() == Some(INITIALIZE_REQUEST_ID) => {
+                        Outgoing::Initialized
+                    }
+                    _ => continue,
+                };
+                if reader_input.send(outgoing).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Server { process: child, input: input_sender })
+    }
+
+    fn send(&self, message: Value) -> Result<()> {
+        self.input
+            .send(Outgoing::Deferred(message))
+            .map_err(|_| "The language server connection is unavailable".into())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        // Ask the server to exit, stopping it outright if it can't be reached.
+        let exited = self.input
+            .send(Outgoing::Immediate(lsp::request(SHUTDOWN_REQUEST_ID, "shutdown", Value::Null)))
+            .and_then(|_| self.input.send(Outgoing::Immediate(lsp::notification("exit", Value::Null))))
+            .is_ok();
+
+        // Give the server a moment to exit on its own, then stop it outright
+        // if need be, waiting on it either way so it isn't left as a zombie.
+        if exited {
+            let deadline = Instant::now() + Duration::from_millis(EXIT_TIMEOUT);
+            while Instant::now() < deadline {
+                match self.process.try_wait() {
+                    Ok(Some(_)) => return,
+                    Ok(None) => thread::sleep(Duration::from_millis(10)),
+                    Err(_) => break,
+                }
+            }
+        }
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+impl Connection {
+    /// Writes messages as they're received, until the server
+    /// can't be written to or every sender has been dropped.
+    fn write_all(mut self, messages: Receiver<Outgoing>) {
+        for message in messages {
+            let written = match message {
+                Outgoing::Immediate(message) => self.write(&message),
+                Outgoing::Deferred(message) => self.send(message),
+                Outgoing::Initialized => self.initialize(),
+            };
+            if written.is_err() {
+                break;
+            }
+        }
+    }
+
+    fn send(&mut self, message: Value) -> Result<()> {
+        if self.initialized {
+            self.write(&message)
+        } else {
+            self.pending.push(message);
+
+            Ok(())
+        }
+    }
+
+    fn write(&mut self, message: &Value) -> Result<()> {
+        self.input.write_all(lsp::encode(message).as_bytes())?;
+        self.input.flush()?;
+
+        Ok(())
+    }
+
+    /// Completes the initialization handshake, sending any queued messages.
+    fn initialize(&mut self) -> Result<()> {
+        self.initialized = true;
+        self.write(&lsp::notification("initialized", json!({})))?;
+        for message in mem::replace(&mut self.pending, Vec::new()) {
+            self.write(&message)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::Buffer;
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use super::LanguageServers;
+
+    #[test]
+    fn sync_doesnt_block_on_servers_that_stop_reading() {
+        let (tx, _rx) = mpsc::channel();
+        let mut servers = LanguageServers::new(tx);
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("amp.rs"));
+        buffer.insert(&"amp editor\n".repeat(100_000));
+        let root = Path::new(env!("OUT_DIR"));
+
+        // A server that responds to the initialize request, and then stops reading.
+        let response = r#"{"jsonrpc":"2.0","id":0,"result":{}}"#;
+        let script = format!(
+            "printf 'Content-Length: {}\\r\\n\\r\\n%s' '{}'; sleep 5",
+            response.len(),
+            response
+        );
+        let command = Some(vec![String::from("sh"), String::from("-c"), script]);
+
+        // Each sync sends the full content, which fills the server's input.
+        servers.sync(&buffer, 0, command.clone(), root).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        for revision in 1..5 {
+            servers.sync(&buffer, revision, command.clone(), root).unwrap();
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::input::Key;
+use std::mem;
+
+/// Tracks the keys that made up the most recent change to a buffer, so that
+/// the change can be repeated by replaying them.
+///
+/// A change is captured as the full sequence of keys starting with one
+/// pressed in normal mode and ending with the next return to normal mode,
+/// provided the buffer was modified along the way. That covers single-key
+/// edits (e.g. `x`), insert mode sessions (from `i` through `escape`, along
+/// with everything typed), operators and their motions (e.g. `X w`), and
+/// selections along with the command applied to them (e.g. `v l l d`; the
+/// motions are replayed from the cursor, rather than reusing the original
+/// range). Sequences that don't modify the buffer, like plain motions, undo,
+/// and redo, leave the previous change in place.
+#[derive(Default)]
+pub struct LastChange {
+    keys: Vec<Key>,
+    pending: Vec<Key>,
+    pending_change_count: usize,
+    pub repeating: bool,
+}
+
+impl LastChange {
+    /// The keys of the most recently completed change.
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Tracks a key before it's handled. Keys pressed in
+    /// normal mode start a new, potentially changing, sequence.
+    pub fn start_key(&mut self, key: Key, normal_mode: bool, change_count: usize) {
+        if normal_mode {
+            self.pending.clear();
+            self.pending_change_count = change_count;
+        }
+
+        self.pending.push(key);
+    }
+
+    /// Completes the pending sequence once a key has been handled and we're
+    /// back in normal mode, keeping it if the buffer was changed.
+    pub fn finish_key(&mut self, normal_mode: bool, change_count: usize) {
+        if !normal_mode {
+            return;
+        }
+
+        if change_count > self.pending_change_count && !self.pending.is_empty() {
+            self.keys = mem::replace(&mut self.pending, Vec::new());
+        } else {
+            self.pending.clear();
+        }
+    }
+
+    /// Drops the pending sequence, so that it won't be captured as a change.
+    pub fn discard_pending(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LastChange;
+    use crate::input::Key;
+
+    #[test]
+    fn finish_key_captures_sequences_that_change_the_buffer() {
+        let mut last_change = LastChange::default();
+        last_change.start_key(Key::Char('i'), true, 0);
+        last_change.finish_key(false, 0);
+        last_change.start_key(Key::Char('a'), false, 0);
+        last_change.finish_key(false, 0);
+        last_change.start_key(Key::Esc, false, 0);
+        last_change.finish_key(true, 1);
+
+        assert_eq!(last_change.keys(), &[Key::Char('i'), Key::Char('a'), Key::Esc]);
+    }
+
+    #[test]
+    fn finish_key_ignores_sequences_that_dont_change_the_buffer() {
+        let mut last_change = LastChange::default();
+        last_change.start_key(Key::Char('x'), true, 0);
+        last_change.finish_key(true, 1);
+        last_change.start_key(Key::Char('l'), true, 1);
+        last_change.finish_key(true, 1);
+
+        assert_eq!(last_change.keys(), &[Key::Char('x')]);
+    }
+}
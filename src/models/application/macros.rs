@@ -0,0 +1,77 @@
+use crate::input::Key;
+use std::collections::HashMap;
+
+/// Sequences of keys recorded into named registers, so that they can be
+/// replayed. Keys processed while a macro is being played aren't recorded,
+/// since the playback itself (e.g. "@a") already is.
+#[derive(Default)]
+pub struct Macros {
+    recording: Option<(char, Vec<Key>)>,
+    registers: HashMap<char, Vec<Key>>,
+    pub playback_depth: usize,
+}
+
+impl Macros {
+    /// Starts recording into the named register, discarding its current contents.
+    pub fn start_recording(&mut self, register: char) {
+        self.recording = Some((register, Vec::new()));
+    }
+
+    /// Stops recording, storing the recorded keys and returning
+    /// the register name and key count, if we were recording.
+    pub fn stop_recording(&mut self) -> Option<(char, usize)> {
+        let (register, keys) = self.recording.take()?;
+        let count = keys.len();
+        self.registers.insert(register, keys);
+
+        Some((register, count))
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn record(&mut self, key: Key) {
+        if self.playback_depth > 0 {
+            return;
+        }
+
+        if let Some((_, ref mut keys)) = self.recording {
+            keys.push(key);
+        }
+    }
+
+    pub fn keys(&self, register: char) -> Option<&[Key]> {
+        self.registers.get(&register).map(|keys| keys.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Macros;
+    use crate::input::Key;
+
+    #[test]
+    fn stop_recording_stores_recorded_keys_in_the_register() {
+        let mut macros = Macros::default();
+        macros.record(Key::Char('x'));
+        macros.start_recording('a');
+        macros.record(Key::Char('i'));
+        macros.record(Key::Esc);
+
+        assert_eq!(macros.stop_recording(), Some(('a', 2)));
+        assert_eq!(macros.keys('a'), Some(&[Key::Char('i'), Key::Esc][..]));
+        assert_eq!(macros.stop_recording(), None);
+    }
+
+    #[test]
+    fn record_ignores_keys_during_playback() {
+        let mut macros = Macros::default();
+        macros.start_recording('a');
+        macros.playback_depth = 1;
+        macros.record(Key::Char('i'));
+        macros.stop_recording();
+
+        assert_eq!(macros.keys('a'), Some(&[][..]));
+    }
+}
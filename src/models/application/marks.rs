@@ -0,0 +1,227 @@
+use crate::errors::*;
+use scribe::Buffer;
+use scribe::buffer::Position;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Bounds the number of locations that can be jumped back to.
+const JUMP_LIMIT: usize = 100;
+
+/// Named cursor positions. Lowercase marks are local to the buffer they're
+/// set in, while uppercase (global) marks can be jumped to from any buffer.
+/// Marks follow their lines as buffers change, and the locations jumped away
+/// from are recorded, so that they can be returned to.
+#[derive(Default)]
+pub struct Marks {
+    buffers: HashMap<usize, Rc<RefCell<BufferMarks>>>,
+    jumps: Vec<(usize, Position)>,
+}
+
+/// The marks set in a buffer. Buffer changes are recorded as they happen,
+/// and marks are shifted to follow their lines the next time they're read.
+struct BufferMarks {
+    positions: HashMap<char, Position>,
+    line_count: usize,
+    change_position: Option<Position>,
+}
+
+impl Marks {
+    /// Sets the named mark at the buffer's cursor, replacing any previous
+    /// position (or, for global marks, a position in another buffer).
+    pub fn set(&mut self, name: char, buffer: &mut Buffer) -> Result<()> {
+        let id = buffer.id.ok_or("Buffer ID doesn't exist")?;
+        if is_global(name) {
+            for marks in self.buffers.values() {
+                marks.borrow_mut().positions.remove(&name);
+            }
+        }
+
+        let marks = match self.buffers.get(&id) {
+            Some(marks) => marks.clone(),
+            None => {
+                let marks = track(buffer);
+                self.buffers.insert(id, marks.clone());
+                marks
+            }
+        };
+        let mut marks = marks.borrow_mut();
+        marks.update(buffer.line_count());
+        marks.positions.insert(name, *buffer.cursor);
+
+        Ok(())
+    }
+
+    /// The ID of the buffer the global mark was set in.
+    pub fn global_buffer_id(&self, name: char) -> Option<usize> {
+        self.buffers
+            .iter()
+            .find(|(_, marks)| marks.borrow().positions.contains_key(&name))
+            .map(|(id, _)| *id)
+    }
+
+    /// The named mark's position in the buffer, accounting for changes
+    /// made since it was set. Marks on removed lines are moved to the
+    /// start of the change, and may lie beyond the end of the buffer.
+    pub fn position(&self, name: char, buffer: &Buffer) -> Option<Position> {
+        let marks = self.buffers.get(&buffer.id?)?;
+        let mut marks = marks.borrow_mut();
+        marks.update(buffer.line_count());
+
+        marks.positions.get(&name).cloned()
+    }
+
+    /// Records a location jumped away from, discarding the oldest beyond the limit.
+    pub fn push_jump(&mut self, buffer_id: usize, position: Position) {
+        self.jumps.push((buffer_id, position));
+        if self.jumps.len() > JUMP_LIMIT {
+            self.jumps.remove(0);
+        }
+    }
+
+    /// Removes and returns the most recent location jumped away from.
+    pub fn pop_jump(&mut self) -> Option<(usize, Position)> {
+        self.jumps.pop()
+    }
+}
+
+impl BufferMarks {
+    fn new(line_count: usize) -> BufferMarks {
+        BufferMarks {
+            positions: HashMap::new(),
+            line_count,
+            change_position: None,
+        }
+    }
+
+    /// Records the earliest position changed since the last update.
+    fn record_change(&mut self, position: Position) {
+        if self.change_position.map(|p| position < p).unwrap_or(true) {
+            self.change_position = Some(position);
+        }
+    }
+
+    /// Shifts marks following a recorded change by the difference
+    /// between the specified and previous line counts.
+    fn update(&mut self, line_count: usize) {
+        if let Some(position) = self.change_position.take() {
+            // A change at the start of a line moves the line itself.
+            let first_line = if position.offset == 0 {
+                position.line
+            } else {
+                position.line + 1
+            };
+
+            for mark in self.positions.values_mut().filter(|mark| mark.line >= first_line) {
+                if line_count > self.line_count {
+                    mark.line += line_count - self.line_count;
+                } else if line_count < self.line_count {
+                    let removed = self.line_count - line_count;
+                    if mark.line < first_line + removed {
+                        *mark = Position { line: first_line, offset: 0 };
+                    } else {
+                        mark.line -= removed;
+                    }
+                }
+            }
+        }
+
+        self.line_count = line_count;
+    }
+}
+
+fn is_global(name: char) -> bool {
+    name.is_ascii_uppercase()
+}
+
+/// Builds a mark set for the buffer, extending its change
+/// callback to record changes against the set's marks.
+fn track(buffer: &mut Buffer) -> Rc<RefCell<BufferMarks>> {
+    let marks = Rc::new(RefCell::new(BufferMarks::new(buffer.line_count())));
+    let tracked_marks = marks.clone();
+    let callback = buffer.change_callback.take();
+
+    buffer.change_callback = Some(
+        Box::new(move |change_position| {
+            tracked_marks.borrow_mut().record_change(change_position);
+            if let Some(ref callback) = callback {
+                callback(change_position);
+            }
+        })
+    );
+
+    marks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Marks;
+    use scribe::Buffer;
+    use scribe::buffer::{Position, Range};
+
+    fn buffer(id: usize, content: &str) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.id = Some(id);
+        buffer.insert(content);
+
+        buffer
+    }
+
+    #[test]
+    fn marks_follow_their_lines_as_the_buffer_changes() {
+        let mut marks = Marks::default();
+        let mut buffer = buffer(1, "amp\neditor\nmarks");
+        buffer.cursor.move_to(Position{ line: 2, offset: 1 });
+        marks.set('a', &mut buffer).unwrap();
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+        buffer.insert("new\nlines\n");
+        assert_eq!(marks.position('a', &buffer), Some(Position{ line: 4, offset: 1 }));
+
+        buffer.delete_range(Range::new(
+            Position{ line: 0, offset: 0 },
+            Position{ line: 3, offset: 0 }
+        ));
+        assert_eq!(marks.position('a', &buffer), Some(Position{ line: 1, offset: 1 }));
+    }
+
+    #[test]
+    fn marks_on_removed_lines_move_to_the_change() {
+        let mut marks = Marks::default();
+        let mut buffer = buffer(1, "amp\neditor\nmarks");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        marks.set('a', &mut buffer).unwrap();
+
+        buffer.delete_range(Range::new(
+            Position{ line: 1, offset: 0 },
+            Position{ line: 2, offset: 0 }
+        ));
+        assert_eq!(marks.position('a', &buffer), Some(Position{ line: 1, offset: 0 }));
+    }
+
+    #[test]
+    fn local_marks_are_per_buffer_and_global_marks_are_unique() {
+        let mut marks = Marks::default();
+        let mut first = buffer(1, "amp");
+        let mut second = buffer(2, "editor");
+        marks.set('a', &mut first).unwrap();
+        marks.set('A', &mut first).unwrap();
+        marks.set('A', &mut second).unwrap();
+
+        assert!(marks.position('a', &first).is_some());
+        assert!(marks.position('a', &second).is_none());
+        assert_eq!(marks.global_buffer_id('A'), Some(2));
+        assert!(marks.position('A', &first).is_none());
+    }
+
+    #[test]
+    fn pop_jump_returns_the_most_recent_location() {
+        let mut marks = Marks::default();
+        marks.push_jump(1, Position{ line: 1, offset: 0 });
+        marks.push_jump(2, Position{ line: 2, offset: 0 });
+
+        assert_eq!(marks.pop_jump(), Some((2, Position{ line: 2, offset: 0 })));
+        assert_eq!(marks.pop_jump(), Some((1, Position{ line: 1, offset: 0 })));
+        assert_eq!(marks.pop_jump(), None);
+    }
+}
@@ -1,43 +1,99 @@
 mod clipboard;
 mod event;
+mod file_watcher;
+mod history;
+mod language_servers;
+mod last_change;
+mod macros;
+mod marks;
 pub mod modes;
 mod preferences;
+mod recent_files;
+mod secondary_cursors;
+mod session;
 
 // Published API
 pub use self::clipboard::ClipboardContent;
 pub use self::event::Event;
+pub use self::file_watcher::FileWatcher;
+pub use self::history::History;
+pub use self::language_servers::LanguageServers;
+pub use self::last_change::LastChange;
+pub use self::macros::Macros;
+pub use self::marks::Marks;
 pub use self::preferences::Preferences;
 pub use self::preferences::THEME_DEFAULT;
+pub use self::recent_files::RecentFiles;
+pub use self::secondary_cursors::SecondaryCursors;
+pub use self::session::Session;
 
 use self::clipboard::Clipboard;
 use self::modes::*;
 use crate::commands;
 use crate::errors::*;
+use crate::input::{MouseEvent, Replay};
 use git2::Repository;
 use crate::presenters;
 use scribe::{Buffer, Workspace};
+use scribe::buffer::Position;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
 use std::sync::mpsc::{self, Receiver, Sender};
-use crate::view::View;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::util;
+use crate::util::lsp::Diagnostic;
+use crate::util::swap::SwapFile;
+use crate::view::{Terminal, View};
+
+const AUTOSAVE_TICK: u64 = 1;
+const COMMAND_HISTORY: &str = "command";
+const MOUSE_SCROLL_LINES: usize = 3;
+const READ_ONLY_ARG: &str = "--read-only";
+const RECENT_FILES: &str = "recent_files";
+const RECENT_PATH_LIMIT: usize = 100;
+const SEARCH_HISTORY: &str = "search";
 
 pub enum Mode {
+    Bookmark(BookmarkMode),
+    BufferList(BufferListMode),
     Confirm(ConfirmMode),
     Command(CommandMode),
+    Completion(CompletionMode),
+    Diff(DiffMode),
+    Encoding(EncodingMode),
     Exit,
-    Insert,
+    Insert(InsertMode),
     Jump(JumpMode),
+    LargeFile(LargeFileMode),
     LineJump(LineJumpMode),
+    Macro(MacroMode),
+    Mark(MarkMode),
     Path(PathMode),
     Normal,
     Open(OpenMode),
+    Operator(OperatorMode),
+    Pane,
+    Recent(RecentMode),
+    RecentFiles(RecentFilesMode),
+    Register(RegisterMode),
+    Replace(ReplaceMode),
     Select(SelectMode),
+    SelectBlock(SelectBlockMode),
     SelectLine(SelectLineMode),
+    Shell(ShellMode),
+    Snippet(SnippetMode),
     Search(SearchMode),
+    Spelling(SpellingMode),
     SymbolJump(SymbolJumpMode),
     Syntax(SyntaxMode),
+    TagJump(TagJumpMode),
+    WorkspaceSearch(WorkspaceSearchMode),
     Theme(ThemeMode),
 }
 
@@ -45,42 +101,112 @@ pub struct Application {
     pub mode: Mode,
     pub workspace: Workspace,
     pub search_query: Option<String>,
+    pub search_history: History,
+    pub command_history: History,
+    pub macros: Macros,
+    pub marks: Marks,
+    pub last_change: LastChange,
+    pub secondary_cursors: SecondaryCursors,
+    pub recent_paths: Vec<PathBuf>,
+    pub recent_files: RecentFiles,
+    pub quit_requested_at: Option<Instant>,
+    pub pending_count: Option<usize>,
     pub view: View,
     pub clipboard: Clipboard,
+    pub file_watcher: FileWatcher,
+    pub language_servers: LanguageServers,
     pub repository: Option<Repository>,
     pub error: Option<Error>,
+    pub message: Option<String>,
     pub preferences: Rc<RefCell<Preferences>>,
     pub event_channel: Sender<Event>,
     events: Receiver<Event>,
+    last_input_at: Instant,
+    autosave_pending: bool,
+    swap_revisions: HashMap<PathBuf, usize>,
 }
 
 impl Application {
     pub fn new(args: &Vec<String>) -> Result<Application> {
-        let preferences = initialize_preferences();
+        Application::build(args, None)
+    }
+
+    /// Sets up the application to use the specified terminal, rather than
+    /// the one for the current environment (e.g. a headless test terminal).
+    pub fn with_terminal(args: &Vec<String>, terminal: Arc<Box<Terminal + Sync + Send + 'static>>) -> Result<Application> {
+        Application::build(args, Some(terminal))
+    }
+
+    fn build(args: &Vec<String>, terminal: Option<Arc<Box<Terminal + Sync + Send + 'static>>>) -> Result<Application> {
+        let mut args = args.clone();
+        let replay = Replay::from_args(&mut args)?;
+        let (preferences, keymap_error) = initialize_preferences();
 
         let (event_channel, events) = mpsc::channel();
-        let mut view = View::new(preferences.clone(), event_channel.clone())?;
-        let clipboard = Clipboard::new();
+        let mut view = match terminal {
+            Some(terminal) => View::with_terminal(preferences.clone(), event_channel.clone(), terminal)?,
+            None => View::new(preferences.clone(), event_channel.clone())?,
+        };
+        let clipboard = if preferences.borrow().system_clipboard() {
+            Clipboard::new()
+        } else {
+            Clipboard::internal()
+        };
 
         // Set up a workspace in the current directory.
-        let workspace = create_workspace(&mut view, args)?;
+        let mut file_watcher = FileWatcher::new(event_channel.clone());
+        let (workspace, mode, large_files) = create_workspace(&mut view, &mut file_watcher, &preferences, &args)?;
+        let message = presenters::status_line::unknown_segments_warning(&preferences.borrow());
 
-        Ok(Application {
-            mode: Mode::Normal,
+        let mut app = Application {
+            mode,
             workspace,
             search_query: None,
+            search_history: load_history(SEARCH_HISTORY),
+            command_history: load_history(COMMAND_HISTORY),
+            macros: Macros::default(),
+            marks: Marks::default(),
+            last_change: LastChange::default(),
+            secondary_cursors: SecondaryCursors::default(),
+            recent_paths: Vec::new(),
+            recent_files: load_recent_files(RECENT_FILES),
+            quit_requested_at: None,
+            pending_count: None,
             view,
             clipboard,
+            file_watcher,
+            language_servers: LanguageServers::new(event_channel.clone()),
             repository: Repository::discover(&env::current_dir()?).ok(),
-            error: None,
+            error: keymap_error,
+            message,
             preferences,
             event_channel,
             events,
-        })
+            last_input_at: Instant::now(),
+            autosave_pending: false,
+            swap_revisions: HashMap::new(),
+        };
+        app.view_large_files(large_files)?;
+        app.track_recent_path();
+        let timer_needed = {
+            let preferences = app.preferences.borrow();
+            preferences.autosave_interval() > 0 || preferences.swap_files()
+        };
+        if timer_needed {
+            spawn_autosave_timer(app.event_channel.clone());
+        }
+
+        // Feed scripted key presses through the regular input pipeline.
+        if let Some(replay) = replay {
+            replay.start(app.event_channel.clone());
+        }
+
+        Ok(app)
     }
 
     pub fn run(&mut self) -> Result<()> {
         loop {
+            self.sync_language_server();
             self.render();
             self.wait_for_event()?;
 
@@ -89,32 +215,152 @@ impl Application {
             }
         }
 
+        self.save_session();
+
+        // Exiting cleanly, even without saving, leaves nothing to recover.
+        for path in self.swap_revisions.keys() {
+            util::swap::remove(path);
+        }
+
         Ok(())
     }
 
+    /// Views the first of the large files specified as arguments, since only
+    /// one can be viewed at a time; any others are reported as skipped.
+    fn view_large_files(&mut self, paths: Vec<(PathBuf, Option<Position>)>) -> Result<()> {
+        let mut paths = paths.into_iter();
+        if let Some((path, position)) = paths.next() {
+            util::open_buffer(&path, self)?;
+            if let (Mode::LargeFile(ref mut mode), Some(position)) = (&mut self.mode, position) {
+                mode.move_to_line(position.line);
+            }
+        }
+
+        let skipped: Vec<String> = paths.map(|(path, _)| path.display().to_string()).collect();
+        if !skipped.is_empty() {
+            self.message = Some(format!(
+                "Only one large file can be viewed at a time; skipped {}",
+                skipped.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Opens the current buffer with its language server, if one is configured,
+    /// or sends it any changes. Failures are reported, rather than fatal.
+    fn sync_language_server(&mut self) {
+        let root = self.workspace.path.clone();
+        let buffer = match self.workspace.current_buffer() {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        let command = self.preferences.borrow().language_server(buffer.path.as_ref());
+        let revision = self.view.revision(buffer);
+
+        if let Err(error) = self.language_servers.sync(buffer, revision, command, &root) {
+            self.error = Some(error);
+        }
+    }
+
+    /// Records the open buffers for the workspace, when session
+    /// restoration is enabled. Failing to do so shouldn't prevent exiting.
+    fn save_session(&mut self) {
+        if !self.preferences.borrow().restore_session() {
+            return;
+        }
+
+        let session = Session::from_workspace(&mut self.workspace, &self.view);
+        if let Ok(path) = Preferences::session_path(&self.workspace.path) {
+            let _ = session.save(&path);
+        }
+    }
+
+    /// Copies the buffers displayed in the unfocused panes of a split view,
+    /// so that they can be rendered alongside the current buffer. Panes
+    /// whose buffers have since been closed are closed, too.
+    fn snapshot_unfocused_panes(&mut self) {
+        let panes = self.view.unfocused_panes();
+        if panes.is_empty() {
+            return;
+        }
+        let current_id = match self.workspace.current_buffer() {
+            Some(buffer) => buffer.id,
+            None => return,
+        };
+
+        for (id, pane) in panes {
+            let buffer = if util::select_buffer_by_id(pane.buffer_id, self) {
+                self.workspace.current_buffer()
+            } else {
+                None
+            };
+            self.view.update_pane_snapshot(id, buffer.map(|buffer| &*buffer), pane.cursor);
+        }
+
+        if let Some(id) = current_id {
+            util::select_buffer_by_id(id, self);
+        }
+    }
+
     fn render(&mut self) {
         if let Err(error) = self.present() {
             presenters::error::display(&mut self.workspace, &mut self.view, &error);
         } else if let Some(ref error) = self.error {
             // Display an error from previous command invocation, if one exists.
             presenters::error::display(&mut self.workspace, &mut self.view, error);
+        } else if let Some(ref message) = self.message {
+            // Display a message from the previous command invocation, if one exists.
+            if let Err(error) = presenters::message::display(&mut self.workspace, &mut self.view, message) {
+                presenters::error::display(&mut self.workspace, &mut self.view, &error);
+            }
         }
     }
 
     fn present(&mut self) -> Result<()> {
+        // Let the view know which mode it's presenting, for status line colors.
+        self.view.mode_str = self.mode_str();
+        self.snapshot_unfocused_panes();
+
         match self.mode {
-            Mode::Confirm(_) => {
-                presenters::modes::confirm::display(&mut self.workspace, &mut self.view)
+            Mode::Bookmark(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::BufferList(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Confirm(ref mode) => {
+                presenters::modes::confirm::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Command(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
-            Mode::Insert => presenters::modes::insert::display(&mut self.workspace, &mut self.view),
+            Mode::Completion(ref mode) => presenters::modes::completion::display(
+                &mut self.workspace,
+                mode,
+                &mut self.view,
+                &self.repository,
+                &self.preferences.borrow(),
+                self.secondary_cursors.positions(),
+            ),
+            Mode::Insert(_) | Mode::Snippet(_) => presenters::modes::insert::display(
+                &mut self.workspace,
+                &mut self.view,
+                &self.repository,
+                &self.preferences.borrow(),
+                self.secondary_cursors.positions(),
+            ),
             Mode::Open(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Search(ref mode) => {
-                presenters::modes::search::display(&mut self.workspace, mode, &mut self.view)
+                presenters::modes::search::display(
+                    &mut self.workspace,
+                    mode,
+                    &mut self.view,
+                    &self.repository,
+                    &self.preferences.borrow(),
+                )
             }
             Mode::Jump(ref mut mode) => {
                 presenters::modes::jump::display(&mut self.workspace, mode, &mut self.view)
@@ -122,25 +368,99 @@ impl Application {
             Mode::LineJump(ref mode) => {
                 presenters::modes::line_jump::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Operator(ref mode) => {
+                presenters::modes::operator::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::Path(ref mode) => {
                 presenters::modes::path::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Recent(ref mode) => {
+                presenters::modes::recent::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::RecentFiles(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Macro(ref mode) => {
+                presenters::modes::macros::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Mark(ref mode) => {
+                presenters::modes::mark::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Pane => {
+                presenters::modes::pane::display(&mut self.workspace, &mut self.view)
+            }
+            Mode::Register(_) => {
+                presenters::modes::register::display(&mut self.workspace, &mut self.view)
+            }
+            Mode::Replace(ref mode) => {
+                presenters::modes::replace::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Shell(ref mode) => {
+                presenters::modes::shell::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::SymbolJump(ref mut mode) => {
+                // Rebuild the symbols if the buffer's been modified (e.g. reloaded) in the meantime.
+                if let Some(buffer) = self.workspace.current_buffer() {
+                    mode.refresh(buffer, self.view.revision(buffer))?;
+                }
+
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Syntax(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Spelling(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::TagJump(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::WorkspaceSearch(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Encoding(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::Select(ref mode) => {
-                presenters::modes::select::display(&mut self.workspace, mode, &mut self.view)
+                presenters::modes::select::display(
+                    &mut self.workspace,
+                    mode,
+                    &mut self.view,
+                    &self.repository,
+                    &self.preferences.borrow(),
+                    self.secondary_cursors.positions(),
+                )
+            }
+            Mode::SelectBlock(ref mode) => {
+                let path = self.workspace.current_buffer_path().map(|path| path.to_path_buf());
+                let tab_width = self.preferences.borrow().tab_width(path.as_ref());
+                presenters::modes::select_block::display(&mut self.workspace, mode, &mut self.view, tab_width)
             }
             Mode::SelectLine(ref mode) => {
-                presenters::modes::select_line::display(&mut self.workspace, mode, &mut self.view)
+                presenters::modes::select_line::display(
+                    &mut self.workspace,
+                    mode,
+                    &mut self.view,
+                    &self.repository,
+                    &self.preferences.borrow(),
+                )
+            }
+            Mode::LargeFile(ref mut mode) => {
+                let path = mode.file.path().to_path_buf();
+                let tab_width = self.preferences.borrow().tab_width(Some(&path));
+                presenters::modes::large_file::display(mode, &mut self.view, tab_width)
+            }
+            Mode::Diff(ref mut mode) => {
+                let tab_width = self.preferences.borrow().tab_width(None);
+                presenters::modes::diff::display(mode, &mut self.view, tab_width)
             }
             Mode::Normal => presenters::modes::normal::display(
                 &mut self.workspace,
                 &mut self.view,
                 &self.repository,
+                &self.preferences.borrow(),
+                self.secondary_cursors.positions(),
+                self.pending_count,
             ),
             Mode::Theme(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
@@ -150,16 +470,52 @@ impl Application {
     }
 
     fn wait_for_event(&mut self) -> Result<()> {
-        let event = self
+        let mut event = self
             .events
             .recv()
             .chain_err(|| "Error receiving application event")?;
+
+        // Ticks that don't autosave anything have nothing to render, so keep waiting.
+        while let Event::AutoSaveTick = event {
+            let autosaved = self.autosave();
+            self.update_swap_files();
+            match autosaved {
+                Ok(false) => (),
+                Ok(true) => return Ok(()),
+                Err(error) => {
+                    self.error = Some(error);
+                    return Ok(());
+                }
+            }
+            event = self
+                .events
+                .recv()
+                .chain_err(|| "Error receiving application event")?;
+        }
+
+        let buffer_id = self.workspace.current_buffer().and_then(|buffer| buffer.id);
         match event {
             Event::Key(key) => {
+                self.last_input_at = Instant::now();
+                self.autosave_pending = true;
                 self.view.last_key = Some(key);
+                self.message = None;
                 self.error = commands::application::handle_input(self).err();
+                self.track_recent_path();
             }
+            Event::Mouse(event) => self.error = self.handle_mouse_event(event).err(),
             Event::Resize => {}
+            Event::FileChanged(path) => {
+                if let Err(error) = self.handle_file_change(&path) {
+                    self.error = Some(error);
+                }
+            }
+            Event::AutoSaveTick => (), // Handled above.
+            Event::Diagnostics(path, diagnostics) => {
+                if let Err(error) = self.handle_diagnostics(&path, diagnostics) {
+                    self.error = Some(error);
+                }
+            }
             Event::OpenModeIndexComplete(index) => {
                 if let Mode::Open(ref mut open_mode) = self.mode {
                     open_mode.set_index(index);
@@ -169,13 +525,288 @@ impl Application {
                     open_mode.search();
                 }
             }
+            Event::OpenModeIndexChanged(change) => {
+                if let Mode::Open(ref mut open_mode) = self.mode {
+                    open_mode.update_index(&change);
+                    open_mode.search();
+                }
+            }
+            Event::WorkspaceSearchComplete(results) => {
+                // Ignore results for searches that have since been replaced.
+                if let Mode::WorkspaceSearch(ref mut mode) = self.mode {
+                    if mode.query == results.query {
+                        mode.set_results(results);
+                        mode.search();
+                    }
+                }
+            }
+        }
+
+        // Secondary cursors can't be carried over to another buffer.
+        if self.workspace.current_buffer().and_then(|buffer| buffer.id) != buffer_id {
+            self.secondary_cursors.clear();
+        }
+
+        Ok(())
+    }
+
+    /// Places the cursor (pressing), selects text (dragging), and scrolls the
+    /// current buffer using the mouse. Input is ignored unless the mouse
+    /// preference is enabled, and in modes that aren't editing the buffer.
+    fn handle_mouse_event(&mut self, event: MouseEvent) -> Result<()> {
+        if !self.preferences.borrow().mouse() {
+            return Ok(());
+        }
+        match self.mode {
+            Mode::Normal | Mode::Insert(_) | Mode::Select(_) | Mode::SelectLine(_) => (),
+            _ => return Ok(()),
+        }
+
+        match event {
+            MouseEvent::Press(screen_position) => {
+                let position = match self.mouse_position(&screen_position)? {
+                    Some(position) => position,
+                    None => return Ok(()),
+                };
+                if let Mode::Select(_) | Mode::SelectLine(_) = self.mode {
+                    commands::application::switch_to_normal_mode(self)?;
+                }
+                let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                buffer.cursor.move_to(position);
+            }
+            MouseEvent::Drag(screen_position) => {
+                let position = match self.mouse_position(&screen_position)? {
+                    Some(position) => position,
+                    None => return Ok(()),
+                };
+                match self.mode {
+                    Mode::Select(_) | Mode::SelectLine(_) => (),
+                    _ => {
+                        // The selection is anchored where the drag started.
+                        commands::application::switch_to_normal_mode(self)?;
+                        commands::application::switch_to_select_mode(self)?;
+                    }
+                }
+                let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                buffer.cursor.move_to(position);
+            }
+            MouseEvent::Release(_) => (),
+            MouseEvent::ScrollUp(_) => {
+                let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                self.view.scroll_up(buffer, MOUSE_SCROLL_LINES)?;
+            }
+            MouseEvent::ScrollDown(_) => {
+                let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                self.view.scroll_down(buffer, MOUSE_SCROLL_LINES)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The current buffer position displayed at the screen position, if any.
+    fn mouse_position(&mut self, screen_position: &Position) -> Result<Option<Position>> {
+        let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+        self.view.buffer_position(buffer, screen_position)
+    }
+
+    /// Saves modified buffers once there's been no input for the configured
+    /// interval, returning whether it was time to do so. Buffers without a
+    /// path are skipped, unless they're to be copied to the autosave directory.
+    /// Saving normalizes content (e.g. trimming trailing whitespace), so it
+    /// only happens in normal mode.
+    fn autosave(&mut self) -> Result<bool> {
+        let (interval, save_unnamed) = {
+            let preferences = self.preferences.borrow();
+            (preferences.autosave_interval(), preferences.autosave_unnamed_buffers())
+        };
+        if interval == 0 || !self.autosave_pending || self.last_input_at.elapsed() < Duration::from_secs(interval) {
+            return Ok(false);
+        }
+        match self.mode {
+            Mode::Normal => (),
+            _ => return Ok(false),
+        }
+        self.autosave_pending = false;
+
+        let original_id = match self.workspace.current_buffer() {
+            Some(buffer) => buffer.id,
+            None => return Ok(false),
+        };
+        let mut result = Ok(());
+        loop {
+            let (id, modified, has_path) = {
+                let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                (buffer.id, buffer.modified() && !buffer.data().is_empty(), buffer.path.is_some())
+            };
+            if modified && has_path {
+                // Report the first failure, without giving up on the other buffers.
+                if let Err(error) = commands::buffer::save(self) {
+                    if result.is_ok() {
+                        result = Err(error);
+                    }
+                }
+            } else if modified && save_unnamed {
+                let data = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?.data();
+                let path = Preferences::autosave_path(&format!("untitled-{}", id.unwrap_or(0)))?;
+                fs::write(path, data).chain_err(|| "Couldn't autosave an unnamed buffer")?;
+            }
+
+            self.workspace.next_buffer();
+            if self.workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+                break;
+            }
+        }
+
+        result.map(|_| true)
+    }
+
+    /// Writes swap files for modified buffers whose content has changed since
+    /// their swap files were last written, and removes those of buffers that
+    /// are no longer modified. Swap files are a convenience; failing to write
+    /// them shouldn't get in the way.
+    fn update_swap_files(&mut self) {
+        if !self.preferences.borrow().swap_files() {
+            return;
+        }
+        let original_id = match self.workspace.current_buffer() {
+            Some(buffer) => buffer.id,
+            None => return,
+        };
+
+        loop {
+            if let Some(buffer) = self.workspace.current_buffer() {
+                if let Some(path) = buffer.path.clone() {
+                    let revision = self.view.revision(buffer);
+                    if !buffer.modified() {
+                        if self.swap_revisions.remove(&path).is_some() {
+                            util::swap::remove(&path);
+                        }
+                    } else if self.swap_revisions.get(&path) != Some(&revision) &&
+                        util::swap::write(&path, &buffer.data()).is_ok() {
+                        self.swap_revisions.insert(path, revision);
+                    }
+                }
+            }
+
+            self.workspace.next_buffer();
+            if self.workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+                break;
+            }
+        }
+    }
+
+    /// Reloads the open buffer for a file that's changed on disk. Buffers with
+    /// unsaved changes prompt for confirmation instead, unless another mode is
+    /// active, in which case the change is ignored. Either way, the current
+    /// buffer remains selected.
+    fn handle_file_change(&mut self, path: &Path) -> Result<()> {
+        // Our own saves aren't external changes, even if we've since kept editing.
+        if self.file_watcher.written_by_us(path) {
+            return Ok(());
+        }
+        let original_id = self.workspace.current_buffer().and_then(|b| b.id);
+        if !util::select_buffer(path, self) {
+            return Ok(());
+        }
+
+        // The changed buffer is only selected while it's
+        // being checked, even if reloading it fails.
+        let result = self.reload_changed_buffer(path);
+        if let Some(id) = original_id {
+            util::select_buffer_by_id(id, self);
+        }
+
+        result
+    }
+
+    /// Reloads the current buffer if its file's content no longer matches,
+    /// prompting first if doing so would discard unsaved changes.
+    fn reload_changed_buffer(&mut self, path: &Path) -> Result<()> {
+        // Changes we've written ourselves (i.e. saves) match the buffer.
+        let (changed, modified) = {
+            let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            let changed = fs::read_to_string(path)
+                .map(|data| data != buffer.data())
+                .unwrap_or(false);
+
+            (changed, buffer.modified())
+        };
+
+        if changed && !modified {
+            commands::buffer::reload(self)?;
+        } else if changed {
+            if let Mode::Normal = self.mode {
+                self.mode = Mode::Confirm(ConfirmMode::with_choices(
+                    format!(
+                        "{} changed on disk; reload and discard changes?",
+                        path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default()
+                    ),
+                    vec![
+                        ConfirmChoice::new('y', "Yes", commands::buffer::reload_confirmed)
+                            .with_path(path.to_path_buf()),
+                        ConfirmChoice::new('n', "No", commands::application::switch_to_normal_mode),
+                    ],
+                    None
+                ));
+            }
         }
 
         Ok(())
     }
 
+    /// Shows diagnostics published by a language server for the open buffer with
+    /// the specified path, replacing any it previously published for the buffer.
+    fn handle_diagnostics(&mut self, path: &Path, diagnostics: Vec<Diagnostic>) -> Result<()> {
+        let original_id = self.workspace.current_buffer().and_then(|b| b.id);
+        if !util::select_buffer(path, self) {
+            return Ok(());
+        }
+
+        let result = match self.workspace.current_buffer() {
+            Some(buffer) => self.view.set_diagnostics(buffer, diagnostics),
+            None => Err(BUFFER_MISSING.into()),
+        };
+        if let Some(id) = original_id {
+            util::select_buffer_by_id(id, self);
+        }
+
+        result
+    }
+
+    /// Moves the current buffer's path to the front of the recently used
+    /// list, which is trimmed to a fixed length to keep lookups cheap,
+    /// and of the recent files list persisted across sessions.
+    pub fn track_recent_path(&mut self) {
+        let path = match self.workspace.current_buffer().and_then(|b| b.path.as_ref()) {
+            Some(path) => util::canonical_path(path),
+            None => return,
+        };
+        if self.recent_paths.first() == Some(&path) {
+            return;
+        }
+
+        self.recent_files.add(&path);
+        self.recent_paths.retain(|p| *p != path);
+        self.recent_paths.insert(0, path);
+        self.recent_paths.truncate(RECENT_PATH_LIMIT);
+    }
+
+    /// Secondary cursors belong to the buffer they were added in, and
+    /// are dropped once the current buffer changes, however it's changed.
     pub fn mode_str(&self) -> Option<&'static str> {
         match self.mode {
+            Mode::Bookmark(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::BufferList(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
             Mode::Command(ref mode) => if mode.insert_mode() {
                 Some("search_select_insert")
             } else {
@@ -191,6 +822,11 @@ impl Application {
             } else {
                 Some("search_select")
             },
+            Mode::RecentFiles(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
             Mode::Theme(ref mode) => if mode.insert_mode() {
                 Some("search_select_insert")
             } else {
@@ -201,14 +837,59 @@ impl Application {
             } else {
                 Some("search_select")
             },
+            Mode::Spelling(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::TagJump(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::WorkspaceSearch(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Encoding(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::LargeFile(ref mode) => if mode.input.is_some() {
+                Some("large_file_search")
+            } else {
+                Some("large_file")
+            },
             Mode::Normal => Some("normal"),
+            Mode::Operator(ref mode) => if mode.motion.is_some() {
+                Some("operator_target")
+            } else {
+                Some("operator")
+            },
             Mode::Path(_) => Some("path"),
             Mode::Confirm(_) => Some("confirm"),
-            Mode::Insert => Some("insert"),
+            Mode::Completion(_) => Some("completion"),
+            Mode::Diff(_) => Some("diff"),
+            Mode::Insert(_) => Some("insert"),
             Mode::Jump(_) => Some("jump"),
             Mode::LineJump(_) => Some("line_jump"),
             Mode::Select(_) => Some("select"),
+            Mode::SelectBlock(_) => Some("select_block"),
             Mode::SelectLine(_) => Some("select_line"),
+            Mode::Recent(_) => Some("recent"),
+            Mode::Macro(_) => Some("macro"),
+            Mode::Mark(_) => Some("mark"),
+            Mode::Pane => Some("pane"),
+            Mode::Register(_) => Some("register"),
+            Mode::Replace(ref mode) => if mode.insert_mode() {
+                Some("replace_insert")
+            } else {
+                Some("replace")
+            },
+            Mode::Shell(_) => Some("shell"),
+            Mode::Snippet(_) => Some("snippet"),
             Mode::Search(ref mode) => if mode.insert_mode() {
                 Some("search_insert")
             } else {
@@ -219,15 +900,83 @@ impl Application {
     }
 }
 
-fn initialize_preferences() -> Rc<RefCell<Preferences>> {
-    Rc::new(RefCell::new(
-        Preferences::load().unwrap_or_else(|_| Preferences::new(None)),
-    ))
+/// Loads the user's preferences, falling back to defaults if they can't be
+/// read. Problems with their keymap are returned, so they can be reported.
+fn initialize_preferences() -> (Rc<RefCell<Preferences>>, Option<Error>) {
+    let preferences = Preferences::load().unwrap_or_else(|_| Preferences::new(None));
+    let keymap_error = preferences.check_keymap().err();
+
+    (Rc::new(RefCell::new(preferences)), keymap_error)
+}
+
+/// Offers to recover unsaved changes from a crashed instance's swap file,
+/// or warns that the file's being edited by another running instance.
+fn recovery_prompt(path: &Path, swap: &SwapFile) -> ConfirmMode {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+
+    if swap.owned_by_other_instance() {
+        ConfirmMode::with_choices(
+            format!("{} is being edited by another instance of Amp (process {})", name, swap.pid),
+            vec![
+                ConfirmChoice::new('c', "Continue editing", commands::application::switch_to_normal_mode),
+                ConfirmChoice::new('q', "Quit", commands::application::force_exit),
+            ],
+            Some(0)
+        )
+    } else {
+        ConfirmMode::with_choices(
+            format!("{} has unsaved changes from a session that didn't exit cleanly", name),
+            vec![
+                ConfirmChoice::new('r', "Recover the changes", commands::buffer::recover_swap),
+                ConfirmChoice::new('d', "Discard the changes", commands::buffer::discard_swap),
+                ConfirmChoice::new('k', "Keep them for later", commands::application::switch_to_normal_mode),
+            ],
+            None
+        )
+    }
+}
+
+/// Periodically prompts the application to check whether it's time
+/// to autosave, and to bring swap files up to date.
+fn spawn_autosave_timer(event_channel: Sender<Event>) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(AUTOSAVE_TICK));
+        if event_channel.send(Event::AutoSaveTick).is_err() {
+            break;
+        }
+    });
+}
+
+#[cfg(not(test))]
+fn load_history(name: &str) -> History {
+    Preferences::history_path(name)
+        .map(History::load)
+        .unwrap_or_default()
+}
+
+// Keep tests from reading or writing the user's history.
+#[cfg(test)]
+fn load_history(_: &str) -> History {
+    History::default()
+}
+
+#[cfg(not(test))]
+fn load_recent_files(name: &str) -> RecentFiles {
+    Preferences::history_path(name)
+        .map(RecentFiles::load)
+        .unwrap_or_default()
 }
 
-fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
-    // Discard the executable portion of the argument list.
-    let mut path_args = args.iter().skip(1).peekable();
+#[cfg(test)]
+fn load_recent_files(_: &str) -> RecentFiles {
+    RecentFiles::default()
+}
+
+fn create_workspace(view: &mut View, file_watcher: &mut FileWatcher, preferences: &RefCell<Preferences>, args: &Vec<String>) -> Result<(Workspace, Mode, Vec<(PathBuf, Option<Position>)>)> {
+    // Discard the executable portion of the argument list,
+    // along with the flag opening its files read-only.
+    let read_only = args.iter().skip(1).any(|arg| arg == READ_ONLY_ARG);
+    let mut path_args = args.iter().skip(1).filter(|arg| *arg != READ_ONLY_ARG).peekable();
 
     // Move into an argument-specified directory, if present.
     let initial_dir = env::current_dir()?;
@@ -242,6 +991,10 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
     let workspace_dir = env::current_dir()?;
     let mut workspace = Workspace::new(&workspace_dir)?;
 
+    // Layer the workspace's own preferences over the user's.
+    preferences.borrow_mut().load_project(&workspace_dir)?;
+    view.update_mouse_reporting();
+
     // Load user syntax definitions.
     //
     // It's important to do this before opening buffers, as that's when syntax
@@ -257,16 +1010,51 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
     // it again as a non-existent file and create a buffer for it.
     if workspace_dir != initial_dir { path_args.next(); }
 
-    // Try to open specified files.
+    // Offer to restore the workspace's last session when no files are specified.
+    let restorable_session = if path_args.peek().is_none() && preferences.borrow().restore_session() {
+        Preferences::session_path(&workspace.path)
+            .and_then(|path| Session::load(&path))
+            .map(|session| session.entries.len())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    // Try to open specified files, tracking whether the
+    // last of them (the current buffer) already existed.
+    let mut existing_file = true;
+    let mut large_files = Vec::new();
+    let mut recoverable_file = None;
     for path_arg in path_args {
-        let path = Path::new(&path_arg);
+        let (path, position) = util::parse_path_argument(path_arg);
+        let path = path.as_path();
 
         if path.is_dir() { continue; }
 
+        // Files too large to load don't get a buffer; they're
+        // viewed once the application is set up (see open_buffer).
+        if util::large_file::exceeds(path, preferences.borrow().large_file_threshold()) {
+            large_files.push((path.to_path_buf(), position));
+            continue;
+        }
+
+        // Files specified more than once (e.g. via a symlink) share a buffer.
+        existing_file = path.exists();
+        let absolute_path = workspace.path.join(path);
+        if preferences.borrow().reuse_open_buffers() && util::select_workspace_buffer(&absolute_path, &mut workspace) {
+            if read_only {
+                view.set_read_only(workspace.current_buffer().unwrap(), true)?;
+            }
+            if let Some(position) = position {
+                util::move_cursor_within_bounds(workspace.current_buffer().unwrap(), position);
+            }
+            continue;
+        }
+
         // Open the specified path if it exists, or
         // create a new buffer pointing to it if it doesn't.
-        let argument_buffer = if path.exists() {
-            Buffer::from_file(path)?
+        let (argument_buffer, source_encoding) = if existing_file {
+            util::encoding::read_buffer(path)?
         } else {
             let mut buffer = Buffer::new();
 
@@ -277,21 +1065,176 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
                 buffer.path = Some(workspace.path.join(path));
             }
 
-            buffer
+            (buffer, None)
         };
+        if let Some(ref path) = argument_buffer.path {
+            preferences.borrow_mut().load_editor_config(path);
+        }
         workspace.add_buffer(argument_buffer);
+        preferences.borrow_mut().set_syntax(workspace.current_buffer().unwrap());
         view.initialize_buffer(workspace.current_buffer().unwrap())?;
+        view.set_encoding(workspace.current_buffer().unwrap(), source_encoding)?;
+        if read_only {
+            view.set_read_only(workspace.current_buffer().unwrap(), true)?;
+        }
+        if let Some(path) = workspace.current_buffer().and_then(|b| b.path.as_ref()) {
+            file_watcher.watch(path);
+        }
+
+        // Look for unsaved changes left behind by another instance.
+        if existing_file && recoverable_file.is_none() && preferences.borrow().swap_files() {
+            let buffer = workspace.current_buffer().unwrap();
+            if let Some(swap) = buffer.path.as_ref().and_then(|path| util::swap::read(path)) {
+                if swap.owned_by_other_instance() || swap.data != buffer.data() {
+                    recoverable_file = Some((buffer.path.clone().unwrap(), swap));
+                }
+            }
+        }
+
+        if let Some(position) = position {
+            util::move_cursor_within_bounds(workspace.current_buffer().unwrap(), position);
+        }
+    }
+
+    // Start in insert mode if configured to do so for empty files.
+    let mut mode = Mode::Normal;
+    if restorable_session > 0 {
+        mode = Mode::Confirm(ConfirmMode::with_prompt(
+            commands::application::restore_session,
+            format!("Restore {} buffer(s) from the last session?", restorable_session)
+        ));
+    }
+    if let Some(buffer) = workspace.current_buffer() {
+        if buffer.path.is_some() && buffer.data().is_empty() &&
+            preferences.borrow().insert_on_empty_file(existing_file) {
+            mode = Mode::Insert(InsertMode::default());
+        }
+
+        // Offer to apply a configured template to new files, instead.
+        if !existing_file && buffer.data().is_empty() {
+            let template = preferences.borrow().template_path(buffer.path.as_ref());
+            if let Some(template_name) = template.as_ref().filter(|t| t.exists()).and_then(|t| t.file_name()) {
+                mode = Mode::Confirm(ConfirmMode::with_prompt(
+                    commands::buffer::apply_template,
+                    format!("Apply the {} template?", template_name.to_string_lossy())
+                ));
+            }
+        }
+    }
+
+    if let Some((path, swap)) = recoverable_file {
+        util::select_workspace_buffer(&path, &mut workspace);
+        mode = Mode::Confirm(recovery_prompt(&path, &swap));
     }
 
-    Ok(workspace)
+    Ok((workspace, mode, large_files))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Application;
+    use super::{Application, Event, FileWatcher, Mode, Preferences};
+    use crate::input::{Key, MouseEvent};
+    use crate::view::{TestTerminal, View};
     use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::cell::RefCell;
     use std::env;
     use std::path::Path;
+    use std::rc::Rc;
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::Duration;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn run_replays_keys_from_a_file_until_exiting() {
+        let path = concat!(env!("OUT_DIR"), "/replayed.txt");
+        let keys_path = concat!(env!("OUT_DIR"), "/replay_keys.txt");
+        std::fs::write(path, "editor\n").unwrap();
+        std::fs::write(keys_path, "// Prefix the file, save it, and quit.\ni a m p space escape s Q\n").unwrap();
+
+        // The application isn't Send, so it's set up on the thread that runs it.
+        let (result_tx, result_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let args = vec![
+                String::new(),
+                String::from("--replay"),
+                String::from(keys_path),
+                String::from(path),
+            ];
+            let result = Application::with_terminal(&args, Arc::new(Box::new(TestTerminal::without_input())))
+                .and_then(|mut app| app.run());
+            result_tx.send(result.is_ok()).unwrap();
+        });
+
+        assert!(result_rx.recv_timeout(Duration::from_secs(10)).unwrap());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "amp editor\n");
+    }
+
+    #[test]
+    fn autosave_saves_modified_buffers_once_input_has_stopped() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/autosave.txt"));
+        std::fs::write(path, "editor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("autosave_interval: 1").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        crate::util::open_buffer(path, &mut app).unwrap();
+        app.workspace.current_buffer().unwrap().insert("amp ");
+        app.autosave_pending = true;
+
+        // Input was too recent.
+        app.autosave().unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "editor\n");
+
+        app.last_input_at -= Duration::from_secs(1);
+        app.autosave().unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "amp editor\n");
+        assert!(app.file_watcher.written_by_us(path));
+    }
+
+    #[test]
+    fn switching_buffers_clears_secondary_cursors() {
+        let mut app = Application::with_terminal(
+            &Vec::new(),
+            Arc::new(Box::new(TestTerminal::without_input()))
+        ).unwrap();
+        crate::util::add_buffer(Buffer::new(), &mut app).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        crate::util::add_buffer(buffer, &mut app).unwrap();
+        app.mode = Mode::Normal;
+        app.secondary_cursors.add(Position{ line: 1, offset: 0 }, &Position{ line: 0, offset: 0 });
+
+        // Moving within the buffer keeps them.
+        app.event_channel.send(Event::Key(Key::Char('l'))).unwrap();
+        app.wait_for_event().unwrap();
+        assert_eq!(app.secondary_cursors.len(), 1);
+
+        app.event_channel.send(Event::Key(Key::Tab)).unwrap();
+        app.wait_for_event().unwrap();
+        assert!(app.secondary_cursors.is_empty());
+    }
+
+    #[test]
+    fn file_changes_to_modified_buffers_prompt_without_switching_buffers() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/externally_changed.txt"));
+        std::fs::write(path, "editor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        crate::util::open_buffer(path, &mut app).unwrap();
+        app.workspace.current_buffer().unwrap().insert("amp ");
+        crate::util::add_buffer(Buffer::new(), &mut app).unwrap();
+        let original_id = app.workspace.current_buffer().unwrap().id;
+
+        std::fs::write(path, "changed\n").unwrap();
+        app.handle_file_change(path).unwrap();
+        assert!(match app.mode { Mode::Confirm(_) => true, _ => false });
+        assert_eq!(app.workspace.current_buffer().unwrap().id, original_id);
+
+        crate::commands::confirm::confirm_command(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, original_id);
+        assert!(crate::util::select_buffer(path, &mut app));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "changed\n");
+    }
 
     #[test]
     fn application_uses_file_arguments_to_load_contents_into_buffers_when_files_exist() {
@@ -320,4 +1263,158 @@ mod tests {
         );
         assert_eq!(application.workspace.current_buffer().unwrap().data(), "");
     }
+
+    #[test]
+    fn application_positions_cursor_using_line_and_column_suffixes() {
+        let path = concat!(env!("OUT_DIR"), "/line_and_column_suffixes.txt");
+        std::fs::write(path, "amp\neditor\n").unwrap();
+        let mut application = Application::new(&vec![String::new(), format!("{}:2:3", path)]).unwrap();
+        assert_eq!(
+            *application.workspace.current_buffer().unwrap().cursor,
+            Position{ line: 1, offset: 2 }
+        );
+    }
+
+    #[test]
+    fn application_clamps_out_of_range_line_suffixes_to_the_last_line() {
+        let path = concat!(env!("OUT_DIR"), "/out_of_range_line_suffix.txt");
+        std::fs::write(path, "amp\neditor").unwrap();
+        let mut application = Application::new(&vec![String::new(), format!("{}:100000", path)]).unwrap();
+        let buffer = application.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.cursor.line, 1);
+    }
+
+    #[test]
+    fn view_large_files_views_the_first_and_reports_the_rest() {
+        let first = Path::new(concat!(env!("OUT_DIR"), "/first_large_file.txt"));
+        let second = Path::new(concat!(env!("OUT_DIR"), "/second_large_file.txt"));
+        std::fs::write(first, "amp\neditor\n").unwrap();
+        std::fs::write(second, "amp\neditor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("large_file_threshold: 4").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        app.view_large_files(vec![
+            (first.to_path_buf(), Some(Position{ line: 1, offset: 0 })),
+            (second.to_path_buf(), None)
+        ]).unwrap();
+        match app.mode {
+            Mode::LargeFile(ref mode) => {
+                assert_eq!(mode.file.path(), first);
+                assert_eq!(mode.cursor.line, 1);
+            }
+            _ => panic!("Not in large file mode"),
+        }
+        assert_eq!(
+            app.message,
+            Some(format!("Only one large file can be viewed at a time; skipped {}", second.display()))
+        );
+    }
+
+    #[test]
+    fn application_opens_files_read_only_when_requested() {
+        let mut application = Application::new(
+            &vec![String::new(), String::from("--read-only"), String::from("Cargo.toml")]
+        ).unwrap();
+        let buffer = application.workspace.current_buffer().unwrap();
+
+        assert!(buffer.path.as_ref().unwrap().ends_with("Cargo.toml"));
+        assert!(application.view.read_only(buffer));
+    }
+
+    #[test]
+    fn application_opens_repeated_files_read_only_when_requested() {
+        let mut application = Application::new(&vec![
+            String::new(),
+            String::from("Cargo.toml"),
+            String::from("--read-only"),
+            String::from("./Cargo.toml:2")
+        ]).unwrap();
+        let buffer = application.workspace.current_buffer().unwrap();
+
+        assert_eq!(buffer.cursor.line, 1);
+        assert!(application.view.read_only(buffer));
+    }
+
+    #[test]
+    fn create_workspace_starts_in_insert_mode_for_new_empty_files_when_configured() {
+        let data = YamlLoader::load_from_str("insert_on_empty_file: true").unwrap();
+        let preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().nth(0))));
+        let (event_channel, _) = mpsc::channel();
+        let mut file_watcher = FileWatcher::new(event_channel.clone());
+        let mut view = View::new(preferences.clone(), event_channel).unwrap();
+
+        let (_, mode, _) = super::create_workspace(
+            &mut view,
+            &mut file_watcher,
+            &preferences,
+            &vec![String::new(), String::from("non_existent_file")]
+        ).unwrap();
+        match mode {
+            Mode::Insert(_) => (),
+            _ => panic!("Expected insert mode for a new empty file"),
+        }
+
+        let (_, mode, _) = super::create_workspace(
+            &mut view,
+            &mut file_watcher,
+            &preferences,
+            &vec![String::new(), String::from("Cargo.lock")]
+        ).unwrap();
+        match mode {
+            Mode::Normal => (),
+            _ => panic!("Expected normal mode for an existing file"),
+        }
+    }
+
+    fn mouse_application() -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("mouse: true").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    #[test]
+    fn mouse_presses_move_the_cursor_and_drags_select() {
+        let mut app = mouse_application();
+
+        // The test terminal's gutter is 4 columns wide.
+        app.handle_mouse_event(MouseEvent::Press(Position{ line: 1, offset: 5 })).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 1 });
+
+        app.handle_mouse_event(MouseEvent::Drag(Position{ line: 0, offset: 6 })).unwrap();
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position{ line: 1, offset: 1 }),
+            _ => panic!("Expected select mode after dragging"),
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 2 });
+
+        app.handle_mouse_event(MouseEvent::Press(Position{ line: 0, offset: 4 })).unwrap();
+        if let Mode::Select(_) = app.mode {
+            panic!("Expected pressing to end the selection");
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn mouse_presses_in_the_gutter_or_status_line_are_ignored() {
+        let mut app = mouse_application();
+
+        app.handle_mouse_event(MouseEvent::Press(Position{ line: 1, offset: 1 })).unwrap();
+        app.handle_mouse_event(MouseEvent::Press(Position{ line: 9, offset: 5 })).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn mouse_events_are_ignored_unless_enabled() {
+        let mut app = mouse_application();
+        *app.preferences.borrow_mut() = Preferences::new(None);
+
+        app.handle_mouse_event(MouseEvent::Press(Position{ line: 1, offset: 5 })).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+    }
 }
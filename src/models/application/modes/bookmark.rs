@@ -0,0 +1,166 @@
+use fragment::matching::AsStr;
+use crate::util::{self, SelectableVec};
+use crate::util::fuzzy::FuzzyMatch;
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+pub struct BookmarkMode {
+    insert: bool,
+    input: String,
+    bookmarks: Vec<Bookmark>,
+    results: SelectableVec<Bookmark>,
+    matched_indices: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bookmark {
+    pub buffer_id: usize,
+    pub line: usize,
+    label: String,
+}
+
+impl Bookmark {
+    /// Builds a bookmark labelled with its buffer's path, its
+    /// (one-based) line number, and the line's trimmed content.
+    pub fn new(buffer_id: usize, path: &str, line: usize, content: &str) -> Bookmark {
+        Bookmark {
+            buffer_id,
+            line,
+            label: format!("{}:{}  {}", path, line + 1, content.trim())
+                .trim_end()
+                .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Bookmark {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", &self.label)
+    }
+}
+
+impl AsStr for Bookmark {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+impl BookmarkMode {
+    pub fn new(bookmarks: Vec<Bookmark>, config: SearchSelectConfig) -> BookmarkMode {
+        BookmarkMode {
+            insert: true,
+            input: String::new(),
+            bookmarks,
+            results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for BookmarkMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BOOKMARKS")
+    }
+}
+
+impl SearchSelectMode<Bookmark> for BookmarkMode {
+    fn search(&mut self) {
+        // List all bookmarks until a query is entered.
+        let results = if self.input.is_empty() {
+            self.bookmarks
+                .iter()
+                .take(self.config.max_results)
+                .map(FuzzyMatch::unmatched)
+                .collect()
+        } else {
+            util::fuzzy::find(&self.input, &self.bookmarks, self.config.max_results)
+        };
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<Bookmark> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&Bookmark> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.bookmarks.is_empty() {
+            Some(String::from("No bookmarks set."))
+        } else if self.results().count() == 0 {
+            Some(String::from("No matching bookmarks found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use super::{Bookmark, BookmarkMode};
+
+    #[test]
+    fn search_lists_all_bookmarks_without_a_query() {
+        let bookmarks = vec![
+            Bookmark::new(0, "src/main.rs", 0, "fn main() {"),
+            Bookmark::new(1, "README.md", 4, "  # Amp  "),
+        ];
+        let mut mode = BookmarkMode::new(bookmarks.clone(), SearchSelectConfig::default());
+        mode.search();
+
+        assert_eq!(mode.results().cloned().collect::<Vec<Bookmark>>(), bookmarks);
+        assert_eq!(bookmarks[1].to_string(), "README.md:5  # Amp");
+    }
+
+    #[test]
+    fn search_filters_bookmarks_using_query() {
+        let bookmarks = vec![
+            Bookmark::new(0, "src/main.rs", 0, "fn main() {"),
+            Bookmark::new(1, "README.md", 4, "# Amp"),
+        ];
+        let mut mode = BookmarkMode::new(bookmarks.clone(), SearchSelectConfig::default());
+        mode.query().push_str("main");
+        mode.search();
+
+        assert_eq!(mode.results().cloned().collect::<Vec<Bookmark>>(), vec![bookmarks[0].clone()]);
+    }
+}
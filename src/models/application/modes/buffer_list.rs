@@ -0,0 +1,158 @@
+use fragment::matching::AsStr;
+use crate::util::{self, SelectableVec};
+use crate::util::fuzzy::FuzzyMatch;
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+pub struct BufferListMode {
+    insert: bool,
+    input: String,
+    buffers: Vec<BufferEntry>,
+    results: SelectableVec<BufferEntry>,
+    matched_indices: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BufferEntry {
+    pub buffer_id: usize,
+    label: String,
+}
+
+impl BufferEntry {
+    /// Builds an entry labelled with its buffer's path (or a placeholder,
+    /// for buffers without one), marking those with unsaved changes.
+    pub fn new(buffer_id: usize, path: Option<&str>, modified: bool) -> BufferEntry {
+        let mut label = path.unwrap_or("[No Name]").to_string();
+        if modified {
+            label.push_str(" [+]");
+        }
+
+        BufferEntry { buffer_id, label }
+    }
+}
+
+impl fmt::Display for BufferEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", &self.label)
+    }
+}
+
+impl AsStr for BufferEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+impl BufferListMode {
+    pub fn new(buffers: Vec<BufferEntry>, config: SearchSelectConfig) -> BufferListMode {
+        BufferListMode {
+            insert: true,
+            input: String::new(),
+            buffers,
+            results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for BufferListMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BUFFERS")
+    }
+}
+
+impl SearchSelectMode<BufferEntry> for BufferListMode {
+    fn search(&mut self) {
+        // List all buffers until a query is entered.
+        let results = if self.input.is_empty() {
+            self.buffers
+                .iter()
+                .take(self.config.max_results)
+                .map(FuzzyMatch::unmatched)
+                .collect()
+        } else {
+            util::fuzzy::find(&self.input, &self.buffers, self.config.max_results)
+        };
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<BufferEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&BufferEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.results().count() == 0 {
+            Some(String::from("No matching buffers found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use super::{BufferEntry, BufferListMode};
+
+    #[test]
+    fn buffer_entries_are_labelled_with_placeholders_and_modified_markers() {
+        assert_eq!(BufferEntry::new(0, Some("src/main.rs"), false).to_string(), "src/main.rs");
+        assert_eq!(BufferEntry::new(1, Some("src/main.rs"), true).to_string(), "src/main.rs [+]");
+        assert_eq!(BufferEntry::new(2, None, false).to_string(), "[No Name]");
+    }
+
+    #[test]
+    fn search_filters_buffers_using_query() {
+        let buffers = vec![
+            BufferEntry::new(0, Some("src/main.rs"), false),
+            BufferEntry::new(1, Some("README.md"), true),
+        ];
+        let mut mode = BufferListMode::new(buffers.clone(), SearchSelectConfig::default());
+        mode.search();
+        assert_eq!(mode.results().cloned().collect::<Vec<BufferEntry>>(), buffers);
+
+        mode.query().push_str("main");
+        mode.search();
+        assert_eq!(mode.results().cloned().collect::<Vec<BufferEntry>>(), vec![buffers[0].clone()]);
+    }
+}
@@ -3,13 +3,19 @@ use crate::commands::Command;
 
 // Utility type to make an Amp command function presentable (via the
 // Display trait), which is required for any type used in search/select mode.
+// Recently run commands that errored are flagged as such.
 pub struct DisplayableCommand {
     pub description: &'static str,
     pub command: Command,
+    pub failed: bool,
 }
 
 impl fmt::Display for DisplayableCommand {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.description)
+        if self.failed {
+            write!(f, "{} (failed last time)", self.description)
+        } else {
+            write!(f, "{}", self.description)
+        }
     }
 }
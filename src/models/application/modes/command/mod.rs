@@ -1,7 +1,6 @@
 mod displayable_command;
 
-use fragment;
-use crate::util::SelectableVec;
+use crate::util::{self, SelectableVec};
 use std::collections::HashMap;
 use std::fmt;
 use std::slice::Iter;
@@ -9,26 +8,66 @@ use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
 use crate::commands::{self, Command};
 pub use self::displayable_command::DisplayableCommand;
 
+/// Prefixes command history entries for commands that returned an error.
+const FAILED_COMMAND_MARKER: char = '!';
+
 pub struct CommandMode {
     insert: bool,
     input: String,
     commands: HashMap<&'static str, Command>,
+    recent: Vec<(&'static str, bool)>,
     results: SelectableVec<DisplayableCommand>,
+    matched_indices: Vec<Vec<usize>>,
     config: SearchSelectConfig,
 }
 
 impl CommandMode {
-    pub fn new(config: SearchSelectConfig) -> CommandMode {
+    /// Builds a command mode listing the recently run commands (history
+    /// entries, newest first) when no query has been entered.
+    pub fn new<'a, I>(config: SearchSelectConfig, history: I) -> CommandMode
+        where I: Iterator<Item = &'a str>
+    {
+        let commands = commands::hash_map();
+
+        // Only the most recent run of each command is listed.
+        let mut recent: Vec<(&'static str, bool)> = Vec::new();
+        for entry in history {
+            let failed = entry.starts_with(FAILED_COMMAND_MARKER);
+            let name = match commands.get_key_value(command_name(entry)) {
+                Some((name, _)) => *name,
+                None => continue,
+            };
+            if !recent.iter().any(|&(recent_name, _)| recent_name == name) {
+                recent.push((name, failed));
+            }
+        }
+
         CommandMode {
             insert: true,
             input: String::new(),
-            commands: commands::hash_map(),
+            commands,
+            recent,
             results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
             config,
         }
     }
 }
 
+/// Builds a command history entry, flagging commands that returned an error.
+pub fn history_entry(name: &str, failed: bool) -> String {
+    if failed {
+        format!("{}{}", FAILED_COMMAND_MARKER, name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Extracts the command name from a command history entry.
+pub fn command_name(entry: &str) -> &str {
+    entry.trim_start_matches(FAILED_COMMAND_MARKER)
+}
+
 impl fmt::Display for CommandMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "COMMAND")
@@ -37,14 +76,38 @@ impl fmt::Display for CommandMode {
 
 impl SearchSelectMode<DisplayableCommand> for CommandMode {
     fn search(&mut self) {
-        let commands: Vec<&'static str> = self.commands.keys().map(|k| *k).collect();
+        // Without a query, offer recently run commands for re-selection.
+        if self.input.is_empty() && !self.recent.is_empty() {
+            let commands = &self.commands;
+            self.matched_indices = Vec::new();
+            self.results = SelectableVec::new(
+                self.recent
+                .iter()
+                .take(self.config.max_results)
+                .map(|&(description, failed)| {
+                    DisplayableCommand{
+                      description,
+                      command: commands[description],
+                      failed
+                    }
+                })
+                .collect()
+            );
+
+            return;
+        }
+
+        // Sort the commands so that equally ranked results have a stable order.
+        let mut commands: Vec<&'static str> = self.commands.keys().map(|k| *k).collect();
+        commands.sort();
 
         // Find the commands we're looking for using the query.
-        let results = fragment::matching::find(
+        let results = util::fuzzy::find(
             &self.input,
             &commands,
             self.config.max_results
         );
+        self.matched_indices = results.iter().map(|result| result.indices.clone()).collect();
 
         // We don't care about the result objects; we just want
         // the underlying commands. Map the collection to get these.
@@ -52,10 +115,11 @@ impl SearchSelectMode<DisplayableCommand> for CommandMode {
             results
             .into_iter()
             .filter_map(|result| {
-                self.commands.get(*result).map(|command| {
+                self.commands.get(*result.item).map(|command| {
                     DisplayableCommand{
-                      description: *result,
-                      command: *command
+                      description: *result.item,
+                      command: *command,
+                      failed: false
                     }
                 })
             })
@@ -98,4 +162,36 @@ impl SearchSelectMode<DisplayableCommand> for CommandMode {
     fn config(&self) -> &SearchSelectConfig {
         &self.config
     }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandMode, command_name, history_entry};
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+    #[test]
+    fn search_lists_recent_commands_without_a_query() {
+        let history = vec![
+            "!buffer::save",
+            "cursor::move_up",
+            "buffer::save",
+            "nonexistent::command",
+        ];
+        let mut mode = CommandMode::new(SearchSelectConfig::default(), history.into_iter());
+        mode.search();
+
+        let results: Vec<String> = mode.results().map(|result| result.to_string()).collect();
+        assert_eq!(results, vec!["buffer::save (failed last time)", "cursor::move_up"]);
+    }
+
+    #[test]
+    fn history_entries_flag_failed_commands() {
+        assert_eq!(history_entry("buffer::save", true), "!buffer::save");
+        assert_eq!(command_name("!buffer::save"), "buffer::save");
+        assert_eq!(command_name(&history_entry("buffer::save", false)), "buffer::save");
+    }
 }
@@ -0,0 +1,66 @@
+use crate::models::application::modes::InsertMode;
+use crate::util::SelectableVec;
+use std::slice::Iter;
+
+/// Lists words that complete the one being typed, on top of insert mode,
+/// which is held onto so that it can be restored once a completion has been
+/// accepted or dismissed.
+pub struct CompletionMode {
+    pub insert_mode: InsertMode,
+    pub prefix: String,
+    candidates: SelectableVec<String>,
+}
+
+impl CompletionMode {
+    pub fn new(insert_mode: InsertMode, prefix: String, candidates: Vec<String>) -> CompletionMode {
+        CompletionMode {
+            insert_mode,
+            prefix,
+            candidates: SelectableVec::new(candidates),
+        }
+    }
+
+    pub fn candidates(&self) -> Iter<String> {
+        self.candidates.iter()
+    }
+
+    pub fn selection(&self) -> Option<&String> {
+        self.candidates.selection()
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.candidates.selected_index()
+    }
+
+    pub fn select_next(&mut self) {
+        self.candidates.select_next();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.candidates.select_previous();
+    }
+
+    /// The part of the selected candidate that has yet to be typed.
+    pub fn remainder(&self) -> Option<&str> {
+        self.selection().map(|candidate| &candidate[self.prefix.len()..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::InsertMode;
+    use super::CompletionMode;
+
+    #[test]
+    fn remainder_returns_the_untyped_part_of_the_selection() {
+        let mut mode = CompletionMode::new(
+            InsertMode::new(),
+            String::from("amp"),
+            vec![String::from("amplify"), String::from("ampere")]
+        );
+        assert_eq!(mode.remainder(), Some("lify"));
+
+        mode.select_next();
+        assert_eq!(mode.remainder(), Some("ere"));
+    }
+}
@@ -1,11 +1,112 @@
-use crate::commands::Command;
+use crate::commands::{self, Command};
+use std::path::PathBuf;
 
-pub struct ConfirmMode {
+const DEFAULT_PROMPT: &str = "Are you sure?";
+
+/// One of the responses to a confirmation prompt, identified
+/// by its key, which is used to select it and run its command.
+pub struct ConfirmChoice {
+    pub key: char,
+    pub label: String,
     pub command: Command,
+
+    /// The file the choice concerns (e.g. one about to be
+    /// overwritten), for use by its command once selected.
+    pub path: Option<PathBuf>,
+}
+
+impl ConfirmChoice {
+    pub fn new<T: Into<String>>(key: char, label: T, command: Command) -> ConfirmChoice {
+        ConfirmChoice { key, label: label.into(), command, path: None }
+    }
+
+    pub fn with_path(mut self, path: PathBuf) -> ConfirmChoice {
+        self.path = Some(path);
+        self
+    }
+}
+
+pub struct ConfirmMode {
+    pub prompt: String,
+    pub choices: Vec<ConfirmChoice>,
+    pub default: Option<usize>,
+    selected: Option<char>,
 }
 
 impl ConfirmMode {
     pub fn new(command: Command) -> ConfirmMode {
-        ConfirmMode { command }
+        ConfirmMode::with_prompt(command, DEFAULT_PROMPT)
+    }
+
+    /// A yes/no prompt, running the command if confirmed.
+    pub fn with_prompt<T: Into<String>>(command: Command, prompt: T) -> ConfirmMode {
+        ConfirmMode::with_choices(prompt, vec![
+            ConfirmChoice::new('y', "Yes", command),
+            ConfirmChoice::new('n', "No", commands::application::switch_to_normal_mode),
+        ], None)
+    }
+
+    /// A prompt offering the choices, the default (if any) of which is
+    /// specified by its index and can be selected without using its key.
+    pub fn with_choices<T: Into<String>>(prompt: T, choices: Vec<ConfirmChoice>, default: Option<usize>) -> ConfirmMode {
+        ConfirmMode { prompt: prompt.into(), choices, default, selected: None }
+    }
+
+    pub fn choice(&self, key: char) -> Option<&ConfirmChoice> {
+        self.choices.iter().find(|choice| choice.key == key)
+    }
+
+    pub fn default_choice(&self) -> Option<&ConfirmChoice> {
+        self.default.and_then(|index| self.choices.get(index))
+    }
+
+    /// Marks the choice with the key as the one being run, returning its command.
+    pub fn select(&mut self, key: char) -> Option<Command> {
+        let command = self.choice(key)?.command;
+        self.selected = Some(key);
+
+        Some(command)
+    }
+
+    /// The choice whose command is being run, if any.
+    pub fn selection(&self) -> Option<&ConfirmChoice> {
+        self.selected.and_then(|key| self.choice(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfirmChoice, ConfirmMode};
+    use crate::commands;
+    use std::path::PathBuf;
+
+    #[test]
+    fn choices_are_found_by_key_and_default() {
+        let mode = ConfirmMode::with_choices("Quit?", vec![
+            ConfirmChoice::new('y', "Quit", commands::application::exit),
+            ConfirmChoice::new('n', "Cancel", commands::application::switch_to_normal_mode),
+        ], Some(1));
+
+        assert_eq!(mode.choice('y').map(|choice| choice.label.as_str()), Some("Quit"));
+        assert!(mode.choice('x').is_none());
+        assert_eq!(mode.default_choice().map(|choice| choice.key), Some('n'));
+        assert!(ConfirmMode::new(commands::application::exit).default_choice().is_none());
+    }
+
+    #[test]
+    fn selecting_a_choice_exposes_its_path() {
+        let mut mode = ConfirmMode::with_choices("Overwrite?", vec![
+            ConfirmChoice::new('y', "Overwrite", commands::application::switch_to_normal_mode)
+                .with_path(PathBuf::from("amp.rs")),
+            ConfirmChoice::new('n', "Cancel", commands::application::switch_to_normal_mode),
+        ], None);
+        assert!(mode.selection().is_none());
+        assert!(mode.select('x').is_none());
+
+        assert!(mode.select('y').is_some());
+        assert_eq!(
+            mode.selection().and_then(|choice| choice.path.clone()),
+            Some(PathBuf::from("amp.rs"))
+        );
     }
 }
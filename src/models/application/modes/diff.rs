@@ -0,0 +1,106 @@
+use crate::util::diff::{self, Row, RowKind};
+use std::fmt;
+
+/// One of the two versions being compared.
+pub struct DiffSide {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+impl DiffSide {
+    pub fn new(title: String, content: &str) -> DiffSide {
+        DiffSide {
+            title,
+            lines: content.lines().map(|line| line.to_string()).collect(),
+        }
+    }
+}
+
+/// Compares two versions of a file side by side, with their lines
+/// aligned into rows. Editing isn't supported.
+pub struct DiffMode {
+    pub left: DiffSide,
+    pub right: DiffSide,
+    pub rows: Vec<Row>,
+    pub cursor: usize,
+    pub scroll_offset: usize,
+}
+
+impl DiffMode {
+    pub fn new(left: DiffSide, right: DiffSide) -> DiffMode {
+        let rows = diff::rows(&left.lines, &right.lines);
+
+        DiffMode { left, right, rows, cursor: 0, scroll_offset: 0 }
+    }
+
+    /// Moves the cursor by the number of rows, staying within the diff.
+    pub fn move_rows(&mut self, amount: isize) {
+        let last_row = self.rows.len().saturating_sub(1) as isize;
+        self.cursor = (self.cursor as isize + amount).max(0).min(last_row) as usize;
+    }
+
+    /// Adjusts the scroll offset so that the cursor
+    /// is within the specified number of visible rows.
+    pub fn scroll_to_cursor(&mut self, height: usize) {
+        if self.cursor < self.scroll_offset {
+            self.scroll_offset = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll_offset + height {
+            self.scroll_offset = self.cursor + 1 - height;
+        }
+    }
+
+    /// The rows on which each hunk of differing lines starts, in order.
+    pub fn hunks(&self) -> Vec<usize> {
+        self.rows.iter().enumerate().filter(|&(index, row)| {
+            row.kind != RowKind::Unchanged &&
+                (index == 0 || self.rows[index - 1].kind == RowKind::Unchanged)
+        }).map(|(index, _)| index).collect()
+    }
+
+    /// Moves the cursor to the start of the next hunk, if there is one.
+    pub fn next_hunk(&mut self) -> Option<usize> {
+        let hunk = self.hunks().into_iter().find(|&row| row > self.cursor)?;
+        self.cursor = hunk;
+
+        Some(hunk)
+    }
+
+    /// Moves the cursor to the start of the previous hunk, if there is one.
+    pub fn previous_hunk(&mut self) -> Option<usize> {
+        let hunk = self.hunks().into_iter().rev().find(|&row| row < self.cursor)?;
+        self.cursor = hunk;
+
+        Some(hunk)
+    }
+}
+
+impl fmt::Display for DiffMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DIFF")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiffMode, DiffSide};
+
+    #[test]
+    fn hunk_navigation_moves_between_runs_of_differing_rows() {
+        let mut mode = DiffMode::new(
+            DiffSide::new(String::from("left"), "a\nb\nc\nd\ne\n"),
+            DiffSide::new(String::from("right"), "x\nb\nc\ny\nz\ne\n"),
+        );
+
+        assert_eq!(mode.hunks(), vec![0, 3]);
+        assert_eq!(mode.next_hunk(), Some(3));
+        assert_eq!(mode.next_hunk(), None);
+        assert_eq!(mode.cursor, 3);
+        assert_eq!(mode.previous_hunk(), Some(0));
+        assert_eq!(mode.previous_hunk(), None);
+
+        mode.move_rows(100);
+        mode.scroll_to_cursor(2);
+        assert_eq!(mode.cursor, 5);
+        assert_eq!(mode.scroll_offset, 4);
+    }
+}
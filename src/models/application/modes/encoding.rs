@@ -0,0 +1,81 @@
+use crate::util::{self, SelectableVec};
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+pub struct EncodingMode {
+    insert: bool,
+    input: String,
+    encodings: Vec<String>,
+    results: SelectableVec<String>,
+    matched_indices: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+impl EncodingMode {
+    pub fn new(encodings: Vec<String>, config: SearchSelectConfig) -> EncodingMode {
+        EncodingMode {
+            insert: true,
+            input: String::new(),
+            encodings,
+            results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for EncodingMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ENCODING")
+    }
+}
+
+impl SearchSelectMode<String> for EncodingMode {
+    fn search(&mut self) {
+        // Find the encodings we're looking for using the query.
+        let results = util::fuzzy::find(&self.input, &self.encodings, self.config.max_results);
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<String> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&String> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+}
@@ -1,9 +1,103 @@
+use scribe::buffer::Position;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
 pub struct InsertMode {
     pub input: Option<char>,
+    pub start: Option<Position>,
+    escape_input: String,
+    last_insert: Option<(Instant, Position)>,
 }
 
 impl InsertMode {
     pub fn new() -> InsertMode {
-        InsertMode { input: None }
+        InsertMode::default()
+    }
+
+    /// Tracks a typed character against the escape sequence, returning true if
+    /// it completes the sequence. Characters only continue a partially-typed
+    /// sequence if they follow the last insert within the timeout, without
+    /// the cursor having moved in the meantime.
+    pub fn track_escape_sequence(&mut self, character: char, sequence: &str, timeout: Duration, cursor: Position) -> bool {
+        let continuing = self.last_insert
+            .map(|(time, position)| time.elapsed() <= timeout && position == cursor)
+            .unwrap_or(false);
+        if !continuing {
+            self.escape_input.clear();
+        }
+        self.escape_input.push(character);
+
+        if self.escape_input == sequence {
+            self.escape_input.clear();
+            return true;
+        }
+
+        // Start over if the input no longer matches the sequence,
+        // using the current character as a potential first step.
+        if !sequence.starts_with(&self.escape_input) {
+            self.escape_input.clear();
+            if sequence.starts_with(character) {
+                self.escape_input.push(character);
+            }
+        }
+
+        false
+    }
+
+    /// Records the time and resulting cursor position of an insert,
+    /// against which the next escape sequence input is compared.
+    pub fn record_insert(&mut self, cursor: Position) {
+        self.last_insert = Some((Instant::now(), cursor));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use std::time::Duration;
+    use super::InsertMode;
+
+    #[test]
+    fn track_escape_sequence_completes_consecutive_input() {
+        let mut mode = InsertMode::new();
+        let timeout = Duration::from_millis(500);
+
+        assert!(!mode.track_escape_sequence('j', "jk", timeout, Position{ line: 0, offset: 0 }));
+        mode.record_insert(Position{ line: 0, offset: 1 });
+        assert!(mode.track_escape_sequence('k', "jk", timeout, Position{ line: 0, offset: 1 }));
+    }
+
+    #[test]
+    fn track_escape_sequence_restarts_when_cursor_moves() {
+        let mut mode = InsertMode::new();
+        let timeout = Duration::from_millis(500);
+
+        mode.track_escape_sequence('j', "jk", timeout, Position{ line: 0, offset: 0 });
+        mode.record_insert(Position{ line: 0, offset: 1 });
+        assert!(!mode.track_escape_sequence('k', "jk", timeout, Position{ line: 1, offset: 0 }));
+    }
+
+    #[test]
+    fn track_escape_sequence_restarts_after_timeout() {
+        let mut mode = InsertMode::new();
+        let timeout = Duration::from_millis(0);
+
+        mode.track_escape_sequence('j', "jk", timeout, Position{ line: 0, offset: 0 });
+        mode.record_insert(Position{ line: 0, offset: 1 });
+        ::std::thread::sleep(Duration::from_millis(5));
+        assert!(!mode.track_escape_sequence('k', "jk", timeout, Position{ line: 0, offset: 1 }));
+    }
+
+    #[test]
+    fn track_escape_sequence_handles_repeated_characters() {
+        let mut mode = InsertMode::new();
+        let timeout = Duration::from_millis(500);
+
+        // A third "j" should complete a "jj" sequence started by the second.
+        mode.track_escape_sequence('j', "jk", timeout, Position{ line: 0, offset: 0 });
+        mode.record_insert(Position{ line: 0, offset: 1 });
+        mode.track_escape_sequence('j', "jk", timeout, Position{ line: 0, offset: 1 });
+        mode.record_insert(Position{ line: 0, offset: 2 });
+        assert!(mode.track_escape_sequence('k', "jk", timeout, Position{ line: 0, offset: 2 }));
     }
 }
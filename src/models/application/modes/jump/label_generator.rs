@@ -0,0 +1,47 @@
+/// Builds the shortest labels for the specified number of targets, using
+/// single characters until there are more targets than characters. Beyond
+/// that, characters from the end of the alphabet are reserved as prefixes for
+/// two-character labels, so that no label is the start of another. If there
+/// are more targets than two characters can label, fewer labels are returned.
+pub fn labels(alphabet: &[char], count: usize) -> Vec<String> {
+    // Each prefix trades one single-character label for a full set of
+    // two-character ones; reserve as few of them as possible.
+    let size = alphabet.len();
+    let prefixes = (0..=size)
+        .find(|&prefixes| size - prefixes + prefixes * size >= count)
+        .unwrap_or(size);
+
+    let (singles, prefixes) = alphabet.split_at(size - prefixes);
+    let mut labels: Vec<String> = singles.iter().map(|c| c.to_string()).collect();
+    for prefix in prefixes {
+        for c in alphabet {
+            labels.push(format!("{}{}", prefix, c));
+        }
+    }
+    labels.truncate(count);
+
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::labels;
+
+    #[test]
+    fn labels_use_single_characters_when_there_are_enough() {
+        assert_eq!(labels(&['a', 's', 'd'], 2), vec!["a", "s"]);
+        assert_eq!(labels(&['a', 's', 'd'], 3), vec!["a", "s", "d"]);
+    }
+
+    #[test]
+    fn labels_reserve_as_few_prefixes_as_possible() {
+        assert_eq!(labels(&['a', 's', 'd'], 5), vec!["a", "s", "da", "ds", "dd"]);
+        assert_eq!(labels(&['a', 's', 'd'], 6), vec!["a", "sa", "ss", "sd", "da", "ds"]);
+    }
+
+    #[test]
+    fn labels_stop_when_two_characters_run_out() {
+        assert_eq!(labels(&['a', 's'], 10), vec!["aa", "as", "sa", "ss"]);
+        assert!(labels(&[], 3).is_empty());
+    }
+}
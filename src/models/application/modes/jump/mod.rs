@@ -1,5 +1,4 @@
-mod tag_generator;
-mod single_character_tag_generator;
+mod label_generator;
 
 use luthor::token::Category;
 use crate::util::movement_lexer;
@@ -7,10 +6,11 @@ use std::collections::HashMap;
 use scribe::buffer::{Distance, Position};
 use crate::models::application::modes::select::SelectMode;
 use crate::models::application::modes::select_line::SelectLineMode;
-use self::tag_generator::TagGenerator;
-use self::single_character_tag_generator::SingleCharacterTagGenerator;
 use crate::view::{LexemeMapper, MappedLexeme};
 
+// Used to leave the first phase, so it can't label its targets.
+const SECOND_PHASE_KEY: char = 'f';
+
 /// Used to compose select and jump modes, allowing jump mode
 /// to be used for cursor navigation (to select a range of text).
 pub enum SelectModeOptions {
@@ -27,25 +27,47 @@ enum MappedLexemeValue {
 pub struct JumpMode {
     pub input: String,
     pub first_phase: bool,
+
+    /// Only target the start of each line (its first non-whitespace
+    /// character, or the start of the line, if it's blank).
+    pub line_starts: bool,
     cursor_line: usize,
     pub select_mode: SelectModeOptions,
+    alphabet: Vec<char>,
     tag_positions: HashMap<String, Position>,
-    tag_generator: TagGenerator,
-    single_characters: SingleCharacterTagGenerator,
+
+    /// Labels assigned to targets, keyed by their (line, offset).
+    labels: HashMap<(usize, usize), String>,
+
+    /// Targets found while mapping lexemes, to be labelled.
+    targets: Vec<Position>,
+    last_target_line: Option<usize>,
     current_position: Position,
     mapped_lexeme_values: Vec<MappedLexemeValue>,
 }
 
 impl JumpMode {
-    pub fn new(cursor_line: usize) -> JumpMode {
+    /// Builds a jump mode that labels targets using the specified characters,
+    /// in order of preference. Whitespace and repeated characters are ignored.
+    pub fn new(cursor_line: usize, alphabet: &str) -> JumpMode {
+        let mut characters: Vec<char> = Vec::new();
+        for character in alphabet.chars().filter(|c| !c.is_whitespace()) {
+            if !characters.contains(&character) {
+                characters.push(character);
+            }
+        }
+
         JumpMode {
             input: String::new(),
             first_phase: true,
+            line_starts: false,
             cursor_line,
             select_mode: SelectModeOptions::None,
+            alphabet: characters,
             tag_positions: HashMap::new(),
-            tag_generator: TagGenerator::new(),
-            single_characters: SingleCharacterTagGenerator::new(),
+            labels: HashMap::new(),
+            targets: Vec::new(),
+            last_target_line: None,
             current_position: Position{ line: 0, offset: 0 },
             mapped_lexeme_values: Vec::new(),
         }
@@ -55,19 +77,65 @@ impl JumpMode {
         self.tag_positions.get(tag)
     }
 
+    /// Whether the input is the start of a longer, visible tag.
+    pub fn is_tag_prefix(&self, input: &str) -> bool {
+        self.tag_positions.keys().any(|tag| tag.len() > input.len() && tag.starts_with(input))
+    }
+
     pub fn reset_display(&mut self) {
         self.tag_positions.clear();
-        self.tag_generator.reset();
-        self.single_characters.reset();
+        self.targets.clear();
+        self.last_target_line = None;
+    }
+
+    /// Labels the targets found since the display was last reset, giving
+    /// the shortest labels to those nearest the cursor. The first phase
+    /// only uses single-character labels, leaving any others unlabelled.
+    pub fn assign_labels(&mut self) {
+        let cursor_line = self.cursor_line;
+        let mut targets = self.targets.clone();
+        targets.sort_by_key(|position| (position.line as isize - cursor_line as isize).abs());
+
+        let (alphabet, count) = if self.first_phase {
+            let alphabet: Vec<char> = self.alphabet.iter()
+                .cloned()
+                .filter(|&c| c != SECOND_PHASE_KEY)
+                .collect();
+            let count = targets.len().min(alphabet.len());
+
+            (alphabet, count)
+        } else {
+            (self.alphabet.clone(), targets.len())
+        };
+
+        self.labels = targets
+            .into_iter()
+            .zip(label_generator::labels(&alphabet, count))
+            .map(|(position, label)| ((position.line, position.offset), label))
+            .collect();
+    }
+
+    /// The part of the target's label that's yet to be typed, if it has one
+    /// and it matches the input so far. Matching tags are tracked, so that
+    /// they can be resolved to positions later on.
+    fn remaining_tag(&mut self, position: Position) -> Option<String> {
+        let label = self.labels.get(&(position.line, position.offset))?;
+        if !label.starts_with(&self.input) {
+            return None;
+        }
+        self.tag_positions.insert(label.clone(), position);
+
+        Some(label[self.input.len()..].to_string())
     }
 }
 
 impl LexemeMapper for JumpMode {
     // Translates a regular set of tokens into one appropriate
-    // appropriate for jump mode. Lexemes of a size greater than 2
-    // have their leading characters replaced with a jump tag, and
-    // the set of categories is reduced to two: keywords (tags) and
-    // regular text.
+    // appropriate for jump mode. Targets (lexemes of a size greater
+    // than 1, or any lexeme after the cursor in the first phase) are
+    // collected for labelling, and those already labelled have their
+    // leading characters replaced with the rest of their tag. The set
+    // of categories is reduced to two: keywords (tags) and regular text.
     //
     // We also track jump tag locations so that tags can be
     // resolved to positions for performing the actual jump later on.
@@ -76,7 +144,21 @@ impl LexemeMapper for JumpMode {
         self.current_position = position;
 
         for subtoken in movement_lexer::lex(lexeme) {
-            if subtoken.category == Category::Whitespace {
+            let is_whitespace = subtoken.category == Category::Whitespace;
+            let is_target = if self.line_starts {
+                // Blank lines are targeted using their trailing whitespace.
+                self.last_target_line != Some(self.current_position.line) &&
+                    (!is_whitespace || subtoken.lexeme.contains('\n'))
+            } else if is_whitespace {
+                false
+            } else if self.first_phase {
+                // We ignore anything before the cursor.
+                self.current_position.line >= self.cursor_line
+            } else {
+                subtoken.lexeme.len() > 1
+            };
+
+            if is_whitespace && !is_target {
                 let distance = Distance::of_str(&subtoken.lexeme);
 
                 // We don't do anything to whitespace tokens.
@@ -90,21 +172,17 @@ impl LexemeMapper for JumpMode {
                 // Advance beyond this subtoken.
                 self.current_position += distance;
             } else {
-                let tag = if self.first_phase {
-                    if self.current_position.line >= self.cursor_line {
-                        self.single_characters.next()
-                    } else {
-                        None // We haven't reached the cursor yet.
-                    }
-                } else if subtoken.lexeme.len() > 1 {
-                    self.tag_generator.next()
+                let tag = if is_target {
+                    self.targets.push(self.current_position);
+                    self.last_target_line = Some(self.current_position.line);
+                    self.remaining_tag(self.current_position)
                 } else {
                     None
                 };
 
                 match tag {
                     Some(tag) => {
-                        let tag_len = tag.len();
+                        let tag_len = tag.chars().count();
 
                         // Keep a copy of the current tag
                         // that we'll use to loan out a lexeme.
@@ -185,9 +263,26 @@ mod tests {
     use scribe::buffer::Position;
     use super::JumpMode;
 
+    const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+    // Maps the lexemes to find their targets and labels them,
+    // as the presenter does before displaying them.
+    fn label_targets(jump_mode: &mut JumpMode, lexemes: &[(&str, Position)]) {
+        jump_mode.reset_display();
+        for &(lexeme, position) in lexemes {
+            jump_mode.map(lexeme, position);
+        }
+        jump_mode.assign_labels();
+        jump_mode.reset_display();
+    }
+
     #[test]
     fn map_returns_the_correct_lexemes_in_first_phase() {
-        let mut jump_mode = JumpMode::new(0);
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
+        label_targets(&mut jump_mode, &[
+            ("amp", Position{ line: 0, offset: 0 }),
+            ("editor", Position{ line: 0, offset: 3 }),
+        ]);
 
         assert_eq!(
             jump_mode.map("amp", Position{ line: 0, offset: 0 }),
@@ -207,125 +302,199 @@ mod tests {
     }
 
     #[test]
-    fn map_returns_the_correct_lexemes_in_second_phase() {
-        let mut jump_mode = JumpMode::new(0);
+    fn first_phase_labels_skip_the_second_phase_key_and_lines_before_the_cursor() {
+        let mut jump_mode = JumpMode::new(1, ALPHABET);
+        let mut lexemes = vec![("before", Position{ line: 0, offset: 0 })];
+        for offset in 0..6 {
+            lexemes.push(("x", Position{ line: 1, offset: offset * 2 }));
+        }
+        label_targets(&mut jump_mode, &lexemes);
+        for &(lexeme, position) in &lexemes {
+            jump_mode.map(lexeme, position);
+        }
+
+        assert_eq!(jump_mode.map_tag("g"), Some(&Position{ line: 1, offset: 10 }));
+        assert_eq!(jump_mode.map_tag("f"), None);
+        assert_eq!(jump_mode.tag_positions.len(), 6);
+    }
+
+    #[test]
+    fn map_uses_single_character_labels_when_there_are_enough_in_second_phase() {
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
         jump_mode.first_phase = false;
+        label_targets(&mut jump_mode, &[
+            ("amp", Position{ line: 0, offset: 0 }),
+            ("editor", Position{ line: 0, offset: 3 }),
+        ]);
 
         assert_eq!(
             jump_mode.map("amp", Position{ line: 0, offset: 0 }),
             vec![
-                MappedLexeme::Focused("aa"),
-                MappedLexeme::Blurred("p")
+                MappedLexeme::Focused("a"),
+                MappedLexeme::Blurred("mp")
             ]
         );
 
         assert_eq!(
             jump_mode.map("editor", Position{ line: 0, offset: 3 }),
             vec![
-                MappedLexeme::Focused("ab"),
-                MappedLexeme::Blurred("itor")
+                MappedLexeme::Focused("b"),
+                MappedLexeme::Blurred("ditor")
             ]
         );
     }
 
     #[test]
-    fn map_splits_passed_tokens_on_whitespace() {
-        let mut jump_mode = JumpMode::new(0);
+    fn map_narrows_two_character_labels_to_those_matching_the_input() {
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
         jump_mode.first_phase = false;
+        let lexemes: Vec<(&str, Position)> = (0..30)
+            .map(|line| ("amp", Position{ line, offset: 0 }))
+            .collect();
+        label_targets(&mut jump_mode, &lexemes);
 
+        // The 25 nearest targets get single characters, reserving "z" as a prefix.
         assert_eq!(
-            jump_mode.map("do a test", Position{ line: 0, offset: 0 }),
+            jump_mode.map("amp", Position{ line: 25, offset: 0 }),
             vec![
-                MappedLexeme::Focused("aa"),
-                MappedLexeme::Blurred(" "),
-                MappedLexeme::Blurred("a"),
-                MappedLexeme::Blurred(" "),
-                MappedLexeme::Focused("ab"),
-                MappedLexeme::Blurred("st")
+                MappedLexeme::Focused("za"),
+                MappedLexeme::Blurred("p")
             ]
-        )
+        );
+
+        jump_mode.input = String::from("z");
+        jump_mode.reset_display();
+        assert_eq!(
+            jump_mode.map("amp", Position{ line: 0, offset: 0 }),
+            vec![MappedLexeme::Blurred("amp")]
+        );
+        assert_eq!(
+            jump_mode.map("amp", Position{ line: 26, offset: 0 }),
+            vec![
+                MappedLexeme::Focused("b"),
+                MappedLexeme::Blurred("mp")
+            ]
+        );
+        assert!(jump_mode.is_tag_prefix("z"));
+        assert_eq!(jump_mode.map_tag("zb"), Some(&Position{ line: 26, offset: 0 }));
+        assert_eq!(jump_mode.map_tag("a"), None);
     }
 
     #[test]
-    fn map_tracks_the_positions_of_each_jump_token() {
-        let mut jump_mode = JumpMode::new(0);
+    fn assign_labels_prefers_targets_nearest_the_cursor() {
+        let mut jump_mode = JumpMode::new(2, ALPHABET);
         jump_mode.first_phase = false;
+        let lexemes = [
+            ("amp", Position{ line: 0, offset: 0 }),
+            ("editor", Position{ line: 2, offset: 0 }),
+            ("text", Position{ line: 3, offset: 0 }),
+        ];
+        label_targets(&mut jump_mode, &lexemes);
+        for &(lexeme, position) in &lexemes {
+            jump_mode.map(lexeme, position);
+        }
 
-        // Adding space to a lexeme invokes sublexeme handling, since we split
-        // based on whitespace. It's important to ensure the tracked positions
-        // take this into account, too, which is why there's leading whitespace.
-        jump_mode.map("  amp", Position{ line: 0, offset: 0 });
-        jump_mode.map("editor", Position{ line: 0, offset: 5 });
-
-        assert_eq!(*jump_mode.tag_positions.get("aa").unwrap(),
-                   Position {
-                       line: 0,
-                       offset: 2,
-                   });
-        assert_eq!(*jump_mode.tag_positions.get("ab").unwrap(),
-                   Position {
-                       line: 0,
-                       offset: 5,
-                   });
+        assert_eq!(jump_mode.map_tag("a"), Some(&Position{ line: 2, offset: 0 }));
+        assert_eq!(jump_mode.map_tag("b"), Some(&Position{ line: 3, offset: 0 }));
+        assert_eq!(jump_mode.map_tag("c"), Some(&Position{ line: 0, offset: 0 }));
     }
 
     #[test]
-    fn reset_display_restarts_single_character_token_generator() {
-        let mut jump_mode = JumpMode::new(0);
+    fn line_starts_target_the_first_non_whitespace_character_of_each_line() {
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
+        jump_mode.first_phase = false;
+        jump_mode.line_starts = true;
+        let lexemes = [
+            ("  amp editor\n", Position{ line: 0, offset: 0 }),
+            ("\n", Position{ line: 1, offset: 0 }),
+            ("}", Position{ line: 2, offset: 0 }),
+        ];
+        label_targets(&mut jump_mode, &lexemes);
 
         assert_eq!(
-            jump_mode.map("amp", Position{ line: 0, offset: 0 }),
+            jump_mode.map("  amp editor\n", Position{ line: 0, offset: 0 }),
             vec![
+                MappedLexeme::Blurred("  "),
                 MappedLexeme::Focused("a"),
-                MappedLexeme::Blurred("mp")
+                MappedLexeme::Blurred("mp"),
+                MappedLexeme::Blurred(" "),
+                MappedLexeme::Blurred("editor"),
+                MappedLexeme::Blurred("\n")
             ]
         );
-        jump_mode.reset_display();
+        assert_eq!(jump_mode.map("\n", Position{ line: 1, offset: 0 }), vec![MappedLexeme::Focused("b")]);
+        assert_eq!(jump_mode.map("}", Position{ line: 2, offset: 0 }), vec![MappedLexeme::Focused("c")]);
+        assert_eq!(jump_mode.map_tag("a"), Some(&Position{ line: 0, offset: 2 }));
+        assert_eq!(jump_mode.map_tag("b"), Some(&Position{ line: 1, offset: 0 }));
+    }
+
+    #[test]
+    fn map_splits_passed_tokens_on_whitespace() {
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
+        jump_mode.first_phase = false;
+        label_targets(&mut jump_mode, &[("do a test", Position{ line: 0, offset: 0 })]);
 
         assert_eq!(
-            jump_mode.map("editor", Position{ line: 0, offset: 3 }),
+            jump_mode.map("do a test", Position{ line: 0, offset: 0 }),
             vec![
                 MappedLexeme::Focused("a"),
-                MappedLexeme::Blurred("ditor")
+                MappedLexeme::Blurred("o"),
+                MappedLexeme::Blurred(" "),
+                MappedLexeme::Blurred("a"),
+                MappedLexeme::Blurred(" "),
+                MappedLexeme::Focused("b"),
+                MappedLexeme::Blurred("est")
             ]
-        );
+        )
     }
 
     #[test]
-    fn reset_display_restarts_double_character_token_generator() {
-        let mut jump_mode = JumpMode::new(0);
+    fn map_tracks_the_positions_of_each_jump_token() {
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
         jump_mode.first_phase = false;
 
-        assert_eq!(
-            jump_mode.map("amp", Position{ line: 0, offset: 0 }),
-            vec![
-                MappedLexeme::Focused("aa"),
-                MappedLexeme::Blurred("p")
-            ]
-        );
-        jump_mode.reset_display();
+        // Adding space to a lexeme invokes sublexeme handling, since we split
+        // based on whitespace. It's important to ensure the tracked positions
+        // take this into account, too, which is why there's leading whitespace.
+        let lexemes = [
+            ("  amp", Position{ line: 0, offset: 0 }),
+            ("editor", Position{ line: 0, offset: 5 }),
+        ];
+        label_targets(&mut jump_mode, &lexemes);
+        for &(lexeme, position) in &lexemes {
+            jump_mode.map(lexeme, position);
+        }
 
-        assert_eq!(
-            jump_mode.map("editor", Position{ line: 0, offset: 3 }),
-            vec![
-                MappedLexeme::Focused("aa"),
-                MappedLexeme::Blurred("itor")
-            ]
-        );
+        assert_eq!(*jump_mode.tag_positions.get("a").unwrap(),
+                   Position {
+                       line: 0,
+                       offset: 2,
+                   });
+        assert_eq!(*jump_mode.tag_positions.get("b").unwrap(),
+                   Position {
+                       line: 0,
+                       offset: 5,
+                   });
     }
 
     #[test]
     fn map_can_handle_unicode_data() {
-        let mut jump_mode = JumpMode::new(0);
+        let mut jump_mode = JumpMode::new(0, "ab");
         jump_mode.first_phase = false;
+        let lexemes = [
+            ("amp", Position{ line: 0, offset: 0 }),
+            ("eéditor", Position{ line: 1, offset: 0 }),
+            ("text", Position{ line: 2, offset: 0 }),
+        ];
+        label_targets(&mut jump_mode, &lexemes);
 
         // It's important to put the unicode character as the
         // second character to ensure splitting off the first
         // two characters would cause a panic.
         assert_eq!(
-            jump_mode.map("eéditor", Position{ line: 0, offset: 0 }),
+            jump_mode.map("eéditor", Position{ line: 1, offset: 0 }),
             vec![
-                MappedLexeme::Focused("aa"),
+                MappedLexeme::Focused("ba"),
                 MappedLexeme::Blurred("ditor")
             ]
         );
@@ -333,12 +502,18 @@ mod tests {
 
     #[test]
     fn map_tag_returns_position_when_available() {
-        let mut jump_mode = JumpMode::new(0);
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
         jump_mode.first_phase = false;
+        let lexemes = [
+            ("amp", Position{ line: 0, offset: 0 }),
+            ("editor", Position{ line: 1, offset: 3 }),
+        ];
+        label_targets(&mut jump_mode, &lexemes);
+        for &(lexeme, position) in &lexemes {
+            jump_mode.map(lexeme, position);
+        }
 
-        jump_mode.map("amp", Position{ line: 0, offset: 0 });
-        jump_mode.map("editor", Position{ line: 1, offset: 3 });
-        assert_eq!(jump_mode.map_tag("ab"),
+        assert_eq!(jump_mode.map_tag("b"),
                    Some(&Position {
                        line: 1,
                        offset: 3,
@@ -348,17 +523,18 @@ mod tests {
 
     #[test]
     fn map_splits_tokens_correctly_using_movement_lexer() {
-        let mut jump_mode = JumpMode::new(0);
+        let mut jump_mode = JumpMode::new(0, ALPHABET);
         jump_mode.first_phase = false;
+        label_targets(&mut jump_mode, &[("amp_editor", Position{ line: 0, offset: 0})]);
 
         assert_eq!(
             jump_mode.map("amp_editor", Position{ line: 0, offset: 0}),
             vec![
-                MappedLexeme::Focused("aa"),
-                MappedLexeme::Blurred("p"),
+                MappedLexeme::Focused("a"),
+                MappedLexeme::Blurred("mp"),
                 MappedLexeme::Blurred("_"),
-                MappedLexeme::Focused("ab"),
-                MappedLexeme::Blurred("itor")
+                MappedLexeme::Focused("b"),
+                MappedLexeme::Blurred("ditor")
             ]
         );
     }
@@ -0,0 +1,82 @@
+use crate::util::large_file::LargeFile;
+use scribe::buffer::Position;
+use std::fmt;
+
+/// Views a file too large to be loaded into a buffer, reading its lines
+/// from disk as they're displayed. Editing isn't supported.
+pub struct LargeFileMode {
+    pub file: LargeFile,
+    pub cursor: Position,
+    pub scroll_offset: usize,
+
+    /// The query being typed, while searching.
+    pub input: Option<String>,
+
+    /// The last query searched for, used to find subsequent matches.
+    pub query: Option<String>,
+}
+
+impl LargeFileMode {
+    pub fn new(file: LargeFile) -> LargeFileMode {
+        LargeFileMode {
+            file,
+            cursor: Position { line: 0, offset: 0 },
+            scroll_offset: 0,
+            input: None,
+            query: None,
+        }
+    }
+
+    /// Moves the cursor by the number of lines, staying within the file.
+    pub fn move_lines(&mut self, amount: isize) {
+        let last_line = self.file.line_count().saturating_sub(1) as isize;
+        let line = (self.cursor.line as isize + amount).max(0).min(last_line);
+        self.cursor = Position { line: line as usize, offset: 0 };
+    }
+
+    pub fn move_to_line(&mut self, line: usize) {
+        self.cursor = Position { line: 0, offset: 0 };
+        self.move_lines(line as isize);
+    }
+
+    /// Adjusts the scroll offset so that the cursor
+    /// is within the specified number of visible lines.
+    pub fn scroll_to_cursor(&mut self, height: usize) {
+        if self.cursor.line < self.scroll_offset {
+            self.scroll_offset = self.cursor.line;
+        } else if height > 0 && self.cursor.line >= self.scroll_offset + height {
+            self.scroll_offset = self.cursor.line + 1 - height;
+        }
+    }
+}
+
+impl fmt::Display for LargeFileMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LARGE FILE")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::large_file::LargeFile;
+    use std::fs;
+    use std::path::Path;
+    use super::LargeFileMode;
+
+    #[test]
+    fn move_lines_and_scroll_to_cursor_stay_within_the_file() {
+        let path = concat!(env!("OUT_DIR"), "/large_file_mode");
+        fs::write(path, "1\n2\n3\n4\n5\n").unwrap();
+        let mut mode = LargeFileMode::new(LargeFile::open(Path::new(path)).unwrap());
+
+        mode.move_lines(10);
+        mode.scroll_to_cursor(2);
+        assert_eq!(mode.cursor.line, 4);
+        assert_eq!(mode.scroll_offset, 3);
+
+        mode.move_lines(-10);
+        mode.scroll_to_cursor(2);
+        assert_eq!(mode.cursor.line, 0);
+        assert_eq!(mode.scroll_offset, 0);
+    }
+}
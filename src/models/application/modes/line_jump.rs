@@ -3,8 +3,95 @@ pub struct LineJumpMode {
     pub input: String,
 }
 
+/// The ways in which the line to jump to can be specified.
+#[derive(Debug, PartialEq)]
+pub enum LineTarget {
+    /// A one-based line number.
+    Absolute(usize),
+
+    /// An offset from the current line (e.g. "+10" or "-5").
+    Relative(isize),
+
+    /// A percentage of the way through the buffer (e.g. "50%").
+    Percentage(usize),
+}
+
 impl LineJumpMode {
     pub fn new() -> LineJumpMode {
         LineJumpMode::default()
     }
+
+    /// Parses the input, if it's valid.
+    pub fn target(&self) -> Option<LineTarget> {
+        let input = self.input.trim();
+
+        if let Some(percentage) = input.strip_suffix('%') {
+            percentage.parse().ok().map(LineTarget::Percentage)
+        } else if input.starts_with('+') || input.starts_with('-') {
+            input.parse().ok().map(LineTarget::Relative)
+        } else {
+            input.parse().ok().map(LineTarget::Absolute)
+        }
+    }
+
+    /// The (zero-based) line targeted by the input, clamped
+    /// to the buffer's lines. None if the input is invalid.
+    pub fn target_line(&self, current_line: usize, line_count: usize) -> Option<usize> {
+        // Saturate, so that very large input is clamped rather than overflowing.
+        let line = match self.target()? {
+            LineTarget::Absolute(line_number) => line_number.saturating_sub(1),
+            LineTarget::Relative(offset) if offset < 0 => {
+                current_line.saturating_sub(offset.unsigned_abs())
+            }
+            LineTarget::Relative(offset) => current_line.saturating_add(offset as usize),
+            LineTarget::Percentage(percentage) => {
+                // Round up, so that any non-zero percentage moves past the first line.
+                (percentage.saturating_mul(line_count).saturating_add(99) / 100).saturating_sub(1)
+            }
+        };
+
+        Some(line.min(line_count.saturating_sub(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineJumpMode, LineTarget};
+
+    fn mode(input: &str) -> LineJumpMode {
+        LineJumpMode { input: input.to_string() }
+    }
+
+    #[test]
+    fn target_parses_absolute_relative_and_percentage_input() {
+        assert_eq!(mode("12").target(), Some(LineTarget::Absolute(12)));
+        assert_eq!(mode("+10").target(), Some(LineTarget::Relative(10)));
+        assert_eq!(mode("-5").target(), Some(LineTarget::Relative(-5)));
+        assert_eq!(mode("50%").target(), Some(LineTarget::Percentage(50)));
+        assert_eq!(mode("").target(), None);
+        assert_eq!(mode("amp").target(), None);
+        assert_eq!(mode("+-5").target(), None);
+    }
+
+    #[test]
+    fn target_line_clamps_to_the_buffer() {
+        assert_eq!(mode("3").target_line(0, 10), Some(2));
+        assert_eq!(mode("30").target_line(0, 10), Some(9));
+        assert_eq!(mode("0").target_line(5, 10), Some(0));
+        assert_eq!(mode("+3").target_line(5, 10), Some(8));
+        assert_eq!(mode("+30").target_line(5, 10), Some(9));
+        assert_eq!(mode("-30").target_line(5, 10), Some(0));
+        assert_eq!(mode("50%").target_line(0, 10), Some(4));
+        assert_eq!(mode("150%").target_line(0, 10), Some(9));
+        assert_eq!(mode("0%").target_line(5, 10), Some(0));
+    }
+
+    #[test]
+    fn target_line_clamps_input_that_would_overflow() {
+        let max = usize::MAX.to_string();
+        assert_eq!(mode(&max).target_line(0, 10), Some(9));
+        assert_eq!(mode(&format!("{}%", max)).target_line(0, 10), Some(9));
+        assert_eq!(mode(&format!("+{}", isize::MAX)).target_line(5, 10), Some(9));
+        assert_eq!(mode(&isize::MIN.to_string()).target_line(5, 10), Some(0));
+    }
 }
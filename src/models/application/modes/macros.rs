@@ -0,0 +1,17 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MacroAction {
+    Record,
+    Play,
+}
+
+/// Waits for a register name to be typed, after which the
+/// macro in that register is either recorded or played.
+pub struct MacroMode {
+    pub action: MacroAction,
+}
+
+impl MacroMode {
+    pub fn new(action: MacroAction) -> MacroMode {
+        MacroMode { action }
+    }
+}
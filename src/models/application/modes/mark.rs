@@ -0,0 +1,17 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkAction {
+    Set,
+    Jump,
+}
+
+/// Waits for a mark name to be typed, after which the
+/// mark is either set at the cursor or jumped to.
+pub struct MarkMode {
+    pub action: MarkAction,
+}
+
+impl MarkMode {
+    pub fn new(action: MarkAction) -> MarkMode {
+        MarkMode { action }
+    }
+}
@@ -1,27 +1,67 @@
+mod bookmark;
+mod buffer_list;
 mod confirm;
+mod insert;
 mod command;
+mod completion;
+mod diff;
+mod encoding;
 pub mod jump;
+mod large_file;
 mod line_jump;
+mod macros;
+mod mark;
 pub mod open;
+mod operator;
 mod path;
+mod recent;
+mod recent_files;
+mod register;
+mod replace;
 mod search;
 mod search_select;
 mod select;
+mod select_block;
 mod select_line;
+mod shell;
+mod snippet;
+mod spelling;
 mod symbol_jump;
 mod syntax;
+mod tag_jump;
 mod theme;
+mod workspace_search;
 
-pub use self::confirm::ConfirmMode;
-pub use self::command::CommandMode;
+pub use self::bookmark::{Bookmark, BookmarkMode};
+pub use self::buffer_list::{BufferEntry, BufferListMode};
+pub use self::confirm::{ConfirmChoice, ConfirmMode};
+pub use self::insert::InsertMode;
+pub use self::command::{CommandMode, command_name, history_entry};
+pub use self::completion::CompletionMode;
+pub use self::diff::{DiffMode, DiffSide};
+pub use self::encoding::EncodingMode;
 pub use self::jump::JumpMode;
-pub use self::line_jump::LineJumpMode;
+pub use self::large_file::LargeFileMode;
+pub use self::line_jump::{LineJumpMode, LineTarget};
+pub use self::macros::{MacroAction, MacroMode};
+pub use self::mark::{MarkAction, MarkMode};
 pub use self::path::PathMode;
+pub use self::recent::RecentMode;
+pub use self::recent_files::RecentFilesMode;
+pub use self::register::RegisterMode;
+pub use self::replace::ReplaceMode;
 pub use self::open::OpenMode;
-pub use self::search::SearchMode;
+pub use self::operator::{FindMotion, Operator, OperatorMode};
+pub use self::search::{SearchConfig, SearchDirection, SearchMode, SearchSelection};
 pub use self::search_select::{SearchSelectMode, SearchSelectConfig};
 pub use self::select::SelectMode;
+pub use self::select_block::SelectBlockMode;
 pub use self::select_line::SelectLineMode;
+pub use self::shell::{ShellMode, ShellOutput};
+pub use self::snippet::SnippetMode;
+pub use self::spelling::SpellingMode;
 pub use self::symbol_jump::SymbolJumpMode;
 pub use self::syntax::SyntaxMode;
+pub use self::tag_jump::TagJumpMode;
 pub use self::theme::ThemeMode;
+pub use self::workspace_search::{WorkspaceSearchMode, WorkspaceSearchResults};
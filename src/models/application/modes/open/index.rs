@@ -0,0 +1,302 @@
+use bloodhound::ExclusionPattern;
+use crate::util;
+use fragment::matching::AsStr;
+use ignore::WalkBuilder;
+use ignore::gitignore::GitignoreBuilder;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The files beneath a directory, skipping those ignored by `.gitignore`
+/// and `.ignore` files, as well as any matching the exclusion patterns.
+#[derive(Debug, PartialEq)]
+pub struct Index {
+    path: PathBuf,
+    exclusions: Arc<Vec<ExclusionPattern>>,
+    entries: Vec<IndexedPath>,
+}
+
+/// A file created or removed beneath an index's directory.
+#[derive(Debug, PartialEq)]
+pub enum IndexChange {
+    Added(PathBuf),
+    Removed(PathBuf),
+}
+
+/// A path relative to the indexed directory. Large indices
+/// hold a lot of these, so they're stored as boxed strings.
+#[derive(Debug, PartialEq)]
+struct IndexedPath {
+    path: Box<str>,
+}
+
+impl IndexedPath {
+    fn new(path: &str) -> IndexedPath {
+        IndexedPath { path: path.into() }
+    }
+}
+
+impl AsStr for IndexedPath {
+    fn as_str(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Index {
+    pub fn new(path: PathBuf, exclusions: Option<Vec<ExclusionPattern>>) -> Index {
+        Index {
+            path,
+            exclusions: Arc::new(exclusions.unwrap_or_default()),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Walks the directory, replacing any previously indexed files.
+    pub fn populate(&mut self) {
+        let root = self.path.clone();
+        self.entries = self.walk(&root);
+        self.entries.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    /// Updates the index to reflect a file (or directory) having been added
+    /// or removed, without walking the rest of the indexed directory.
+    pub fn apply(&mut self, change: &IndexChange) {
+        match *change {
+            IndexChange::Added(ref path) => {
+                if self.ignored(path) {
+                    return;
+                }
+
+                for entry in self.walk(path) {
+                    if let Err(index) = self.entries.binary_search_by(|e| e.path.cmp(&entry.path)) {
+                        self.entries.insert(index, entry);
+                    }
+                }
+            }
+            IndexChange::Removed(ref path) => {
+                if let Ok(relative_path) = path.strip_prefix(&self.path) {
+                    self.entries.retain(|entry| !Path::new(&*entry.path).starts_with(relative_path));
+                }
+            }
+        }
+    }
+
+    /// Finds indexed paths (relative to the indexed directory) matching the
+    /// query, best matches first, along with their matched character indices.
+    pub fn find(&self, query: &str, limit: usize) -> Vec<(PathBuf, Vec<usize>)> {
+        util::fuzzy::find(query, &self.entries, limit)
+            .into_iter()
+            .map(|result| (PathBuf::from(&*result.item.path), result.indices))
+            .collect()
+    }
+
+    /// The indexed paths, relative to the indexed directory, in sorted order.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.entries.iter().map(|entry| Path::new(&*entry.path))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Collects the files at or beneath the path, skipping unreadable
+    /// entries (e.g. due to permissions) and those with non-UTF-8 names.
+    /// Symlinked directories aren't followed, and symlinked files are only
+    /// included if their targets aren't indexed under their own paths.
+    fn walk(&self, path: &Path) -> Vec<IndexedPath> {
+        let exclusions = self.exclusions.clone();
+        let walker = WalkBuilder::new(path)
+            .hidden(false)
+            .filter_entry(move |entry| {
+                !exclusions.iter().any(|exclusion| exclusion.matches_path(entry.path()))
+            })
+            .build();
+
+        walker
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| match entry.file_type() {
+                Some(file_type) if file_type.is_symlink() => self.includes_link(entry.path()),
+                Some(file_type) => file_type.is_file(),
+                None => false,
+            })
+            .filter_map(|entry| {
+                let relative_path = entry.path().strip_prefix(&self.path).ok()?;
+
+                Some(IndexedPath::new(relative_path.to_str()?))
+            })
+            .collect()
+    }
+
+    /// Whether a symlink should be indexed. Broken and circular links can't
+    /// be resolved, and are skipped along with links to directories.
+    fn includes_link(&self, path: &Path) -> bool {
+        let target = match path.canonicalize() {
+            Ok(target) => target,
+            Err(_) => return false,
+        };
+        if !target.is_file() {
+            return false;
+        }
+
+        // Targets beneath the indexed directory are listed under their own path,
+        // unless they're ignored. The index's own path may not be canonical.
+        match target.strip_prefix(util::canonical_path(&self.path)) {
+            Ok(relative_target) => self.ignored(&self.path.join(relative_target)),
+            Err(_) => true,
+        }
+    }
+
+    /// Whether the path (or one of its parents) would have been skipped when
+    /// populating the index. Only the indexed directory's own ignore files are
+    /// consulted; nested ones are picked up the next time it's populated.
+    fn ignored(&self, path: &Path) -> bool {
+        if !path.starts_with(&self.path) || path == self.path {
+            return true;
+        }
+
+        let excluded = path
+            .ancestors()
+            .take_while(|ancestor| *ancestor != self.path)
+            .any(|ancestor| self.exclusions.iter().any(|exclusion| exclusion.matches_path(ancestor)));
+        if excluded {
+            return true;
+        }
+
+        let mut builder = GitignoreBuilder::new(&self.path);
+        builder.add(self.path.join(".gitignore"));
+        builder.add(self.path.join(".ignore"));
+        builder
+            .build()
+            .map(|rules| rules.matched_path_or_any_parents(path, path.is_dir()).is_ignore())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Index, IndexChange};
+    use bloodhound::ExclusionPattern;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn find(index: &Index, query: &str) -> Vec<PathBuf> {
+        index.find(query, 10).into_iter().map(|(path, _)| path).collect()
+    }
+
+    fn build_index(name: &str) -> PathBuf {
+        // Build outside of the repository, whose ignore rules would otherwise apply.
+        let path = env::temp_dir().join("amp_open_mode_index").join(name);
+        fs::remove_dir_all(&path).ok();
+        fs::create_dir_all(path.join("target")).unwrap();
+        fs::create_dir_all(path.join("src")).unwrap();
+        fs::write(path.join(".ignore"), "target\n").unwrap();
+        fs::write(path.join("src/amp.rs"), "").unwrap();
+        fs::write(path.join("src/editor.rs"), "").unwrap();
+        fs::write(path.join("target/amp.rs"), "").unwrap();
+
+        path
+    }
+
+    #[test]
+    fn populate_skips_ignored_files() {
+        let path = build_index("ignored");
+        let mut index = Index::new(path, None);
+        index.populate();
+
+        assert_eq!(find(&index, "amp"), vec![PathBuf::from("src/amp.rs")]);
+    }
+
+    #[test]
+    fn populate_skips_excluded_files() {
+        let path = build_index("excluded");
+        let mut index = Index::new(path, Some(vec![ExclusionPattern::new("**/editor.rs").unwrap()]));
+        index.populate();
+
+        assert!(find(&index, "editor").is_empty());
+        assert_eq!(find(&index, "amp"), vec![PathBuf::from("src/amp.rs")]);
+    }
+
+    #[test]
+    fn find_matches_paths_regardless_of_case() {
+        let path = build_index("case");
+        fs::write(path.join("src/README.md"), "").unwrap();
+        let mut index = Index::new(path, None);
+        index.populate();
+
+        assert_eq!(find(&index, "readme"), vec![PathBuf::from("src/README.md")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn populate_skips_links_to_indexed_files_and_unresolvable_links() {
+        use std::os::unix::fs::symlink;
+
+        let path = build_index("symlinks");
+        let outside_path = env::temp_dir().join("amp_open_mode_index").join("symlinks_outside.rs");
+        fs::write(&outside_path, "").unwrap();
+        symlink(path.join("src/amp.rs"), path.join("src/amp_link.rs")).unwrap();
+        symlink(path.join("target/amp.rs"), path.join("src/ignored_link.rs")).unwrap();
+        symlink(&outside_path, path.join("src/outside_link.rs")).unwrap();
+        symlink(path.join("src/missing.rs"), path.join("src/broken_link.rs")).unwrap();
+        symlink(path.join("src/circular_link.rs"), path.join("src/circular_link.rs")).unwrap();
+        let mut index = Index::new(path, None);
+        index.populate();
+
+        assert_eq!(find(&index, "amp"), vec![PathBuf::from("src/amp.rs")]);
+        let mut links = find(&index, "link");
+        links.sort();
+        assert_eq!(links, vec![PathBuf::from("src/ignored_link.rs"), PathBuf::from("src/outside_link.rs")]);
+    }
+
+    #[test]
+    fn apply_adds_new_files_and_directories() {
+        let path = build_index("added");
+        let mut index = Index::new(path.clone(), None);
+        index.populate();
+
+        fs::write(path.join("src/buffer.rs"), "").unwrap();
+        index.apply(&IndexChange::Added(path.join("src/buffer.rs")));
+        index.apply(&IndexChange::Added(path.join("src/buffer.rs")));
+        assert_eq!(find(&index, "buffer"), vec![PathBuf::from("src/buffer.rs")]);
+        assert_eq!(index.len(), 4);
+
+        fs::create_dir_all(path.join("src/view")).unwrap();
+        fs::write(path.join("src/view/terminal.rs"), "").unwrap();
+        index.apply(&IndexChange::Added(path.join("src/view")));
+        assert_eq!(find(&index, "terminal"), vec![PathBuf::from("src/view/terminal.rs")]);
+    }
+
+    #[test]
+    fn apply_skips_ignored_and_excluded_additions() {
+        let path = build_index("skipped");
+        let mut index = Index::new(path.clone(), Some(vec![ExclusionPattern::new("**/.git").unwrap()]));
+        index.populate();
+
+        fs::write(path.join("target/buffer.rs"), "").unwrap();
+        index.apply(&IndexChange::Added(path.join("target/buffer.rs")));
+        fs::create_dir_all(path.join(".git")).unwrap();
+        fs::write(path.join(".git/index"), "").unwrap();
+        index.apply(&IndexChange::Added(path.join(".git/index")));
+
+        assert!(find(&index, "buffer").is_empty());
+        assert!(find(&index, "index").is_empty());
+    }
+
+    #[test]
+    fn apply_removes_files_and_directories() {
+        let path = build_index("removed");
+        let mut index = Index::new(path.clone(), None);
+        index.populate();
+
+        index.apply(&IndexChange::Removed(path.join("src/editor.rs")));
+        assert!(find(&index, "editor").is_empty());
+        assert_eq!(index.len(), 2);
+
+        index.apply(&IndexChange::Removed(path.join("src")));
+        assert!(find(&index, "amp").is_empty());
+    }
+}
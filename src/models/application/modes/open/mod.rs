@@ -1,5 +1,6 @@
 mod displayable_path;
 pub mod exclusions;
+mod index;
 
 use std::fmt;
 use std::path::PathBuf;
@@ -8,10 +9,16 @@ use bloodhound::ExclusionPattern;
 use crate::util::SelectableVec;
 use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
 use crate::models::application::Event;
-use std::sync::mpsc::Sender;
+use notify::{self, DebouncedEvent, RecursiveMode, Watcher};
+use std::sync::{Arc, Weak};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
 use std::thread;
-pub use bloodhound::Index;
+use std::time::Duration;
 pub use self::displayable_path::DisplayablePath;
+pub use self::index::{Index, IndexChange};
+
+const WATCH_DEBOUNCE_DELAY: u64 = 250;
+const WATCH_POLL_INTERVAL: u64 = 500;
 
 #[derive(PartialEq)]
 pub enum OpenModeIndex {
@@ -22,40 +29,96 @@ pub enum OpenModeIndex {
 pub struct OpenMode {
     pub insert: bool,
     pub input: String,
+    pub read_on_accept: bool,
     index: OpenModeIndex,
     pub results: SelectableVec<DisplayablePath>,
+    matched_indices: Vec<Vec<usize>>,
     config: SearchSelectConfig,
+
+    // Keeps the indexing thread watching for changes; dropped with the mode.
+    _watch_handle: Arc<()>,
 }
 
 impl OpenMode {
     pub fn new(path: PathBuf, exclusions: Option<Vec<ExclusionPattern>>, events: Sender<Event>, config: SearchSelectConfig) -> OpenMode {
-        // Build and populate the index in a separate thread.
+        // Build and populate the index in a separate thread, which then
+        // reports files created or removed for as long as the mode is active.
         let index_path = path.clone();
+        let watch_handle = Arc::new(());
+        let watch_reference = Arc::downgrade(&watch_handle);
         thread::spawn(move || {
-            let mut index = Index::new(index_path);
-            index.populate(exclusions, false);
-            let _ = events.send(
-                Event::OpenModeIndexComplete(index)
-            );
+            // Start watching first, so changes made while indexing aren't missed.
+            let (tx, rx) = mpsc::channel();
+            let watcher = notify::watcher(tx, Duration::from_millis(WATCH_DEBOUNCE_DELAY))
+                .and_then(|mut watcher| {
+                    watcher.watch(&index_path, RecursiveMode::Recursive)?;
+                    Ok(watcher)
+                })
+                .ok();
+
+            let mut index = Index::new(index_path, exclusions);
+            index.populate();
+            if events.send(Event::OpenModeIndexComplete(index)).is_err() || watcher.is_none() {
+                return;
+            }
+
+            forward_index_changes(&rx, &events, &watch_reference);
         });
 
         OpenMode {
             insert: true,
             input: String::new(),
+            read_on_accept: false,
             index: OpenModeIndex::Indexing(path),
             results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
             config,
+            _watch_handle: watch_handle,
         }
     }
 
     pub fn set_index(&mut self, index: Index) {
         self.index = OpenModeIndex::Complete(index)
     }
+
+    /// Applies a file being created or removed to the index, if it's been built.
+    pub fn update_index(&mut self, change: &IndexChange) {
+        if let OpenModeIndex::Complete(ref mut index) = self.index {
+            index.apply(change);
+        }
+    }
+}
+
+/// Sends index changes to the application until the mode is dropped.
+fn forward_index_changes(rx: &mpsc::Receiver<DebouncedEvent>, events: &Sender<Event>, mode: &Weak<()>) {
+    while mode.upgrade().is_some() {
+        let event = match rx.recv_timeout(Duration::from_millis(WATCH_POLL_INTERVAL)) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        let changes = match event {
+            DebouncedEvent::Create(path) => vec![IndexChange::Added(path)],
+            DebouncedEvent::Remove(path) => vec![IndexChange::Removed(path)],
+            DebouncedEvent::Rename(from, to) => vec![IndexChange::Removed(from), IndexChange::Added(to)],
+            _ => continue,
+        };
+
+        for change in changes {
+            if events.send(Event::OpenModeIndexChanged(change)).is_err() {
+                return;
+            }
+        }
+    }
 }
 
 impl fmt::Display for OpenMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "OPEN")
+        if self.read_on_accept {
+            write!(f, "READ")
+        } else {
+            write!(f, "OPEN")
+        }
     }
 }
 
@@ -63,17 +126,16 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
     fn search(&mut self) {
         let results =
             if let OpenModeIndex::Complete(ref index) = self.index {
-                index.find(
-                    &self.input.to_lowercase(),
-                    self.config.max_results
-                ).into_iter()
-                .map(|path| DisplayablePath(path.to_path_buf()))
-                .collect()
+                index.find(&self.input, self.config.max_results)
             } else {
                 vec![]
             };
 
-        self.results = SelectableVec::new(results);
+        let (paths, matched_indices) = results.into_iter()
+            .map(|(path, indices)| (DisplayablePath(path), indices))
+            .unzip();
+        self.results = SelectableVec::new(paths);
+        self.matched_indices = matched_indices;
     }
 
     fn query(&mut self) -> &mut String {
@@ -112,6 +174,10 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
         &self.config
     }
 
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+
     fn message(&mut self) -> Option<String> {
         if let OpenModeIndex::Indexing(ref path) = self.index {
             Some(format!("Indexing {}", path.to_string_lossy()))
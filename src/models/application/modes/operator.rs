@@ -0,0 +1,149 @@
+use scribe::buffer::Position;
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Change,
+    Delete,
+}
+
+/// Character-finding motions, mirroring vim's f, t, F, and T.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FindMotion {
+    /// Forward, onto the target character (inclusive).
+    Find,
+    /// Forward, up to the target character (exclusive).
+    Till,
+    /// Backward, onto the target character.
+    FindBackward,
+    /// Backward, up to the target character.
+    TillBackward,
+}
+
+pub struct OperatorMode {
+    pub operator: Operator,
+    pub motion: Option<FindMotion>,
+    /// The count typed before the operator (e.g. the 3 in "3Xf,"),
+    /// determining which occurrence of the target the motion finds.
+    pub count: usize,
+}
+
+impl OperatorMode {
+    pub fn new(operator: Operator) -> OperatorMode {
+        OperatorMode { operator, motion: None, count: 1 }
+    }
+}
+
+impl fmt::Display for OperatorMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operator = match self.operator {
+            Operator::Change => "CHANGE",
+            Operator::Delete => "DELETE",
+        };
+
+        match self.motion {
+            Some(FindMotion::Find) => write!(f, "{} FIND", operator),
+            Some(FindMotion::Till) => write!(f, "{} TILL", operator),
+            Some(FindMotion::FindBackward) => write!(f, "{} FIND BACKWARD", operator),
+            Some(FindMotion::TillBackward) => write!(f, "{} TILL BACKWARD", operator),
+            None => write!(f, "{}", operator),
+        }
+    }
+}
+
+impl FindMotion {
+    /// Finds the position on the cursor's line that the motion moves to, given
+    /// the line's content and a target character, repeating the motion until
+    /// the count-th occurrence of the target is reached. Forward motions return
+    /// the exclusive end of the range to operate on, which for inclusive motions
+    /// lies just beyond the target.
+    pub fn target(&self, line: &str, cursor: Position, target: &str, count: usize) -> Option<Position> {
+        let occurrence = count.max(1) - 1;
+        let graphemes: Vec<&str> = line.graphemes(true).collect();
+        let offset = match *self {
+            FindMotion::Find => graphemes
+                .iter()
+                .enumerate()
+                .skip(cursor.offset + 1)
+                .filter(|&(_, g)| *g == target)
+                .nth(occurrence)
+                .map(|(index, _)| index + 1),
+            FindMotion::Till => graphemes
+                .iter()
+                .enumerate()
+                .skip(cursor.offset + 1)
+                .filter(|&(_, g)| *g == target)
+                .nth(occurrence)
+                .map(|(index, _)| index),
+            FindMotion::FindBackward => graphemes
+                .iter()
+                .enumerate()
+                .take(cursor.offset)
+                .rev()
+                .filter(|&(_, g)| *g == target)
+                .nth(occurrence)
+                .map(|(index, _)| index),
+            FindMotion::TillBackward => graphemes
+                .iter()
+                .enumerate()
+                .take(cursor.offset)
+                .rev()
+                .filter(|&(_, g)| *g == target)
+                .nth(occurrence)
+                .map(|(index, _)| index + 1),
+        }?;
+
+        Some(Position{ line: cursor.line, offset })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use super::FindMotion;
+
+    #[test]
+    fn forward_motions_include_or_exclude_the_target() {
+        let cursor = Position{ line: 2, offset: 1 };
+
+        assert_eq!(
+            FindMotion::Find.target("f(amp, editor)", cursor, ")", 1),
+            Some(Position{ line: 2, offset: 14 })
+        );
+        assert_eq!(
+            FindMotion::Till.target("f(amp, editor)", cursor, ")", 1),
+            Some(Position{ line: 2, offset: 13 })
+        );
+    }
+
+    #[test]
+    fn backward_motions_include_or_exclude_the_target() {
+        let cursor = Position{ line: 0, offset: 10 };
+
+        assert_eq!(
+            FindMotion::FindBackward.target("f(amp, editor)", cursor, "(", 1),
+            Some(Position{ line: 0, offset: 1 })
+        );
+        assert_eq!(
+            FindMotion::TillBackward.target("f(amp, editor)", cursor, "(", 1),
+            Some(Position{ line: 0, offset: 2 })
+        );
+    }
+
+    #[test]
+    fn target_ignores_the_character_under_the_cursor() {
+        let cursor = Position{ line: 0, offset: 0 };
+
+        assert_eq!(FindMotion::Find.target("a", cursor, "a", 1), None);
+    }
+
+    #[test]
+    fn target_finds_the_counted_occurrence() {
+        let cursor = Position{ line: 0, offset: 0 };
+
+        assert_eq!(FindMotion::Find.target("a,b,c,d", cursor, ",", 3), Some(Position{ line: 0, offset: 6 }));
+        assert_eq!(FindMotion::Till.target("a,b,c,d", cursor, ",", 2), Some(Position{ line: 0, offset: 3 }));
+        assert_eq!(FindMotion::Find.target("a,b,c,d", cursor, ",", 4), None);
+    }
+}
@@ -1,22 +1,84 @@
+use std::env;
 use std::fmt;
+use std::fs;
+use std::path::PathBuf;
 
 pub struct PathMode {
     pub input: String,
     pub save_on_accept: bool,
+    completion: Option<PathCompletion>,
+}
+
+/// Directory entries matching the input when completion was requested.
+/// Repeated completion requests cycle through these candidates.
+struct PathCompletion {
+    directory: String,
+    candidates: Vec<String>,
+    selected_index: Option<usize>,
 }
 
 impl PathMode {
     pub fn new(initial_path: String) -> PathMode {
         PathMode {
             input: initial_path,
-            save_on_accept: false
+            save_on_accept: false,
+            completion: None,
         }
     }
     pub fn push_char(&mut self, c: char) {
         self.input.push(c);
+        self.completion = None;
     }
     pub fn pop_char(&mut self) {
         self.input.pop();
+        self.completion = None;
+    }
+
+    /// The path described by the input, with a leading "~" expanded.
+    pub fn path(&self) -> PathBuf {
+        expand_home(&self.input)
+    }
+
+    /// Completes the input using the entries of the directory it refers to.
+    /// A unique match is inserted outright (directories with a trailing slash,
+    /// to allow navigating into them), while several matches have their
+    /// common prefix inserted. Subsequent calls cycle through the matches.
+    pub fn complete(&mut self) {
+        if let Some(ref mut completion) = self.completion {
+            let index = completion.selected_index
+                .map(|index| (index + 1) % completion.candidates.len())
+                .unwrap_or(0);
+            completion.selected_index = Some(index);
+            self.input = format!("{}{}", completion.directory, completion.candidates[index]);
+
+            return;
+        }
+
+        let split_index = self.input.rfind('/').map(|index| index + 1).unwrap_or(0);
+        let (directory, prefix) = self.input.split_at(split_index);
+        let candidates = directory_entries(directory, prefix);
+
+        match candidates.len() {
+            0 => (),
+            1 => self.input = format!("{}{}", directory, candidates[0]),
+            _ => {
+                let directory = directory.to_string();
+                self.input = format!("{}{}", directory, common_prefix(&candidates));
+                self.completion = Some(PathCompletion {
+                    directory,
+                    candidates,
+                    selected_index: None,
+                });
+            }
+        }
+    }
+
+    /// Completion candidates for the current input, if
+    /// several were found, along with the selected one.
+    pub fn candidates(&self) -> Option<(&[String], Option<usize>)> {
+        self.completion.as_ref().map(|completion| {
+            (completion.candidates.as_slice(), completion.selected_index)
+        })
     }
 }
 
@@ -25,3 +87,123 @@ impl fmt::Display for PathMode {
         write!(f, "PATH")
     }
 }
+
+fn expand_home(path: &str) -> PathBuf {
+    if path == "~" || path.starts_with("~/") {
+        if let Some(home) = env::var_os("HOME") {
+            return PathBuf::from(home).join(path[1..].trim_start_matches('/'));
+        }
+    }
+
+    PathBuf::from(path)
+}
+
+/// Sorted names of the directory's entries starting with the prefix, with
+/// directories suffixed by a slash. Hidden entries are only included when
+/// the prefix asks for them, and unreadable directories have no entries.
+fn directory_entries(directory: &str, prefix: &str) -> Vec<String> {
+    let path = if directory.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_home(directory)
+    };
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let mut name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            if entry.path().is_dir() {
+                name.push('/');
+            }
+
+            Some(name)
+        })
+        .collect();
+    names.sort();
+
+    names
+}
+
+fn common_prefix(candidates: &[String]) -> &str {
+    let first = &candidates[0];
+    let length = candidates[1..].iter().fold(first.len(), |length, candidate| {
+        first[..length]
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map(|((index, _), _)| index)
+            .unwrap_or_else(|| length.min(candidate.len()))
+    });
+
+    &first[..length]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathMode, common_prefix, expand_home};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn complete_inserts_unique_matches_and_cycles_through_ambiguous_ones() {
+        let directory = concat!(env!("OUT_DIR"), "/path_completion");
+        fs::create_dir_all(format!("{}/nested", directory)).unwrap();
+        fs::write(format!("{}/notes.md", directory), "").unwrap();
+        fs::write(format!("{}/readme.md", directory), "").unwrap();
+
+        let mut mode = PathMode::new(format!("{}/r", directory));
+        mode.complete();
+        assert_eq!(mode.input, format!("{}/readme.md", directory));
+        assert!(mode.candidates().is_none());
+
+        let mut mode = PathMode::new(format!("{}/n", directory));
+        mode.complete();
+        assert_eq!(mode.input, format!("{}/n", directory));
+        assert_eq!(
+            mode.candidates(),
+            Some((&[String::from("nested/"), String::from("notes.md")][..], None))
+        );
+
+        mode.complete();
+        assert_eq!(mode.input, format!("{}/nested/", directory));
+        mode.complete();
+        assert_eq!(mode.input, format!("{}/notes.md", directory));
+        mode.complete();
+        assert_eq!(mode.input, format!("{}/nested/", directory));
+
+        mode.push_char('x');
+        assert!(mode.candidates().is_none());
+    }
+
+    #[test]
+    fn complete_ignores_missing_directories() {
+        let mut mode = PathMode::new(String::from("/nonexistent/amp/"));
+        mode.complete();
+
+        assert_eq!(mode.input, "/nonexistent/amp/");
+        assert!(mode.candidates().is_none());
+    }
+
+    #[test]
+    fn common_prefix_respects_candidate_lengths() {
+        let candidates = vec![String::from("amp.rs"), String::from("amp")];
+
+        assert_eq!(common_prefix(&candidates), "amp");
+    }
+
+    #[test]
+    fn expand_home_replaces_a_leading_tilde() {
+        let home = PathBuf::from(env::var_os("HOME").unwrap());
+
+        assert_eq!(expand_home("~/amp"), home.join("amp"));
+        assert_eq!(expand_home("amp/~"), PathBuf::from("amp/~"));
+    }
+}
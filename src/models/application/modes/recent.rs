@@ -0,0 +1,10 @@
+#[derive(Default)]
+pub struct RecentMode {
+    pub input: String,
+}
+
+impl RecentMode {
+    pub fn new() -> RecentMode {
+        RecentMode::default()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::util::{self, SelectableVec};
+use crate::util::fuzzy::FuzzyMatch;
+use std::fmt;
+use std::path::PathBuf;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+/// Browses files opened across all workspaces and sessions,
+/// listed from most to least recently opened.
+pub struct RecentFilesMode {
+    insert: bool,
+    input: String,
+    paths: Vec<String>,
+    results: SelectableVec<String>,
+    matched_indices: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+impl RecentFilesMode {
+    pub fn new(paths: &[PathBuf], config: SearchSelectConfig) -> RecentFilesMode {
+        RecentFilesMode {
+            insert: true,
+            input: String::new(),
+            paths: paths.iter().map(|path| path.to_string_lossy().into_owned()).collect(),
+            results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for RecentFilesMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RECENT FILES")
+    }
+}
+
+impl SearchSelectMode<String> for RecentFilesMode {
+    fn search(&mut self) {
+        // List paths by recency until a query is entered.
+        let results = if self.input.is_empty() {
+            self.paths
+                .iter()
+                .take(self.config.max_results)
+                .map(FuzzyMatch::unmatched)
+                .collect()
+        } else {
+            util::fuzzy::find(&self.input, &self.paths, self.config.max_results)
+        };
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<String> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&String> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecentFilesMode;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use std::path::PathBuf;
+
+    #[test]
+    fn search_lists_paths_by_recency_until_a_query_is_entered() {
+        let paths = vec![PathBuf::from("/notes/todo.md"), PathBuf::from("/amp/src/main.rs")];
+        let mut mode = RecentFilesMode::new(&paths, SearchSelectConfig::default());
+        mode.search();
+        assert_eq!(mode.results().cloned().collect::<Vec<String>>(), vec!["/notes/todo.md", "/amp/src/main.rs"]);
+
+        mode.query().push_str("main");
+        mode.search();
+        assert_eq!(mode.results().cloned().collect::<Vec<String>>(), vec!["/amp/src/main.rs"]);
+    }
+}
@@ -0,0 +1,13 @@
+use crate::models::application::Mode;
+
+/// Waits for a register name to be typed, after which
+/// the mode it was entered from is restored.
+pub struct RegisterMode {
+    pub previous: Box<Mode>,
+}
+
+impl RegisterMode {
+    pub fn new(previous: Mode) -> RegisterMode {
+        RegisterMode { previous: Box::new(previous) }
+    }
+}
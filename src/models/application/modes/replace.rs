@@ -0,0 +1,94 @@
+use crate::errors::*;
+use crate::models::application::modes::{SearchConfig, SearchMode};
+use scribe::buffer::{Buffer, Range};
+use std::fmt;
+
+/// Replaces occurrences of a search query one at a time, prompting before
+/// each. The query is collected using search mode, followed by the replacement.
+pub struct ReplaceMode {
+    pub search: SearchMode,
+    pub replacement: Option<String>,
+    pub replaced: usize,
+}
+
+impl ReplaceMode {
+    pub fn new(query: Option<String>, config: SearchConfig) -> ReplaceMode {
+        ReplaceMode {
+            search: SearchMode::new(query, config),
+            replacement: None,
+            replaced: 0,
+        }
+    }
+
+    pub fn insert_mode(&self) -> bool {
+        self.search.insert
+    }
+
+    /// The input currently being collected: the replacement,
+    /// once the query has been accepted, or the query itself.
+    pub fn input_mut(&mut self) -> &mut String {
+        match self.replacement {
+            Some(ref mut replacement) => replacement,
+            None => self.search.input.get_or_insert(String::new()),
+        }
+    }
+
+    /// Searches the buffer's current content (so that edits never leave
+    /// stale matches behind), selecting and returning the first match
+    /// at or after the cursor.
+    pub fn find_next(&mut self, buffer: &Buffer) -> Result<Option<Range>> {
+        self.search.search(buffer)?;
+        let results = self.search.results.as_mut().ok_or(NO_SEARCH_RESULTS)?;
+
+        let index = match results.iter().position(|r| r.start() >= *buffer.cursor) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        for _ in 0..index {
+            results.select_next();
+        }
+
+        Ok(results.selection().cloned())
+    }
+}
+
+impl fmt::Display for ReplaceMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REPLACE")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::SearchConfig;
+    use scribe::buffer::{Buffer, Position, Range};
+    use super::ReplaceMode;
+
+    #[test]
+    fn find_next_selects_the_first_match_after_the_cursor() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\namp\namp");
+        buffer.cursor.move_to(Position{ line: 0, offset: 1 });
+
+        let mut mode = ReplaceMode::new(Some(String::from("amp")), SearchConfig::default());
+        let result = mode.find_next(&buffer).unwrap();
+
+        assert_eq!(
+            result,
+            Some(Range::new(Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 3 }))
+        );
+        assert_eq!(mode.search.results.unwrap().selected_index(), 1);
+    }
+
+    #[test]
+    fn find_next_reflects_buffer_edits() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+
+        let mut mode = ReplaceMode::new(Some(String::from("amp")), SearchConfig::default());
+        assert!(mode.find_next(&buffer).unwrap().is_some());
+
+        buffer.delete_range(Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 3 }));
+        assert!(mode.find_next(&buffer).unwrap().is_none());
+    }
+}
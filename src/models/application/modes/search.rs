@@ -1,20 +1,71 @@
 use crate::errors::*;
+use crate::models::application::Mode;
 use crate::util::SelectableVec;
+use regex::{self, Regex};
 use std::fmt;
-use scribe::buffer::{Buffer, Distance, Range};
+use scribe::buffer::{Buffer, Distance, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Clone)]
+pub struct SearchConfig {
+    pub regex: bool,
+    pub smart_case: bool,
+    pub wrap: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> SearchConfig {
+        SearchConfig {
+            regex: false,
+            smart_case: false,
+            wrap: true,
+        }
+    }
+}
+
+/// The direction in which search results are stepped through, relative
+/// to the cursor; moving to the "previous" result goes the opposite way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
+impl SearchDirection {
+    pub fn reverse(self) -> SearchDirection {
+        match self {
+            SearchDirection::Forward => SearchDirection::Backward,
+            SearchDirection::Backward => SearchDirection::Forward,
+        }
+    }
+}
 
 pub struct SearchMode {
     pub insert: bool,
+    pub direction: SearchDirection,
     pub input: Option<String>,
     pub results: Option<SelectableVec<Range>>,
+    pub config: SearchConfig,
+    pub selection: Option<SearchSelection>,
+}
+
+/// The selection a search is confined to, along with the select
+/// mode and cursor position restored once the search is left.
+pub struct SearchSelection {
+    pub range: Range,
+    pub mode: Box<Mode>,
+    pub cursor: Position,
 }
 
 impl SearchMode {
-    pub fn new(query: Option<String>) -> SearchMode {
+    pub fn new(query: Option<String>, config: SearchConfig) -> SearchMode {
         SearchMode {
             insert: true,
+            direction: SearchDirection::Forward,
             input: query,
             results: None,
+            config,
+            selection: None,
         }
     }
 
@@ -22,45 +73,199 @@ impl SearchMode {
         self.insert
     }
 
+    /// Whether smart case applies to the current query, which is the case
+    /// when it has no uppercase characters (e.g. "amp", "_42", or "->").
+    pub fn ignoring_case(&self) -> bool {
+        self.config.smart_case && self.input.as_ref().map(|query| {
+            !query.chars().any(|c| c.is_uppercase())
+        }).unwrap_or(false)
+    }
+
     // Searches the specified buffer for the input string
     // and stores the result as a collection of ranges.
     pub fn search(&mut self, buffer: &Buffer) -> Result<()> {
         let query = self.input.as_ref().ok_or(SEARCH_QUERY_MISSING)?;
-        let distance = Distance::of_str(&query);
-
-        // Buffer search returns match starting positions, but we'd like ranges.
-        // This maps the positions to ranges using the search query distance
-        // before storing them.
-        self.results = Some(
-            SelectableVec::new(
-                buffer.search(&query)
-                    .into_iter()
-                    .map(|start| Range::new(start, start + distance))
-                    .collect()
-            )
-        );
+
+        let mut results: Vec<Range> = if self.config.regex || self.ignoring_case() {
+            regex_matches(&buffer.data(), &self.regex()?)
+        } else {
+            let distance = Distance::of_str(&query);
+
+            // Buffer search returns match starting positions, but we'd like ranges.
+            // This maps the positions to ranges using the search query distance
+            // before storing them.
+            buffer.search(&query)
+                .into_iter()
+                .map(|start| Range::new(start, start + distance))
+                .collect()
+        };
+        results.retain(|range| self.within_selection(range));
+        self.results = Some(SelectableVec::new(results));
 
         Ok(())
     }
+
+    /// Counts the query's matches in the specified data, stopping once
+    /// the limit is reached, so that it's cheap enough to run as the query
+    /// is typed. Empty and invalid queries have no matches.
+    pub fn count_matches(&self, data: &str, limit: usize) -> usize {
+        let query = match self.input {
+            Some(ref query) if !query.is_empty() => query,
+            _ => return 0,
+        };
+
+        // Matches are compared to the selection using byte offsets.
+        let bounds = self.selection.as_ref().map(|selection| {
+            (byte_offset(data, selection.range.start()), byte_offset(data, selection.range.end()))
+        });
+        let within_bounds = |start: usize, end: usize| {
+            bounds.map(|(lower, upper)| start >= lower && end <= upper).unwrap_or(true)
+        };
+
+        if self.config.regex || self.ignoring_case() {
+            self.regex()
+                .map(|regex| {
+                    regex.find_iter(data)
+                        .filter(|&(start, end)| start != end && within_bounds(start, end))
+                        .take(limit)
+                        .count()
+                })
+                .unwrap_or(0)
+        } else {
+            data.match_indices(query.as_str())
+                .filter(|&(start, matched)| within_bounds(start, start + matched.len()))
+                .take(limit)
+                .count()
+        }
+    }
+
+    /// Whether the range lies entirely within the selection the search is
+    /// confined to (if any). Matches straddling its edges are excluded.
+    fn within_selection(&self, range: &Range) -> bool {
+        self.selection.as_ref().map(|selection| {
+            range.start() >= selection.range.start() && range.end() <= selection.range.end()
+        }).unwrap_or(true)
+    }
+
+    /// Checks that the query can be used to search,
+    /// which, for regular expressions, means it compiles.
+    pub fn validate_query(&self) -> Result<()> {
+        if self.config.regex {
+            self.regex()?;
+        } else {
+            self.input.as_ref().ok_or(SEARCH_QUERY_MISSING)?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds the text used to replace a match, expanding capture group
+    /// references (e.g. $1) when searching with regular expressions.
+    pub fn expand_replacement(&self, matched: &str, replacement: &str) -> Result<String> {
+        if !self.config.regex {
+            return Ok(replacement.to_string());
+        }
+
+        Ok(self.regex()?.replace(matched, replacement))
+    }
+
+    /// Compiles the query, escaping it unless it's meant to be a regex,
+    /// and ignoring case when smart case applies.
+    fn regex(&self) -> Result<Regex> {
+        let query = self.input.as_ref().ok_or(SEARCH_QUERY_MISSING)?;
+        let mut pattern = if self.config.regex {
+            query.clone()
+        } else {
+            regex::quote(query)
+        };
+        if self.ignoring_case() {
+            pattern = format!("(?i){}", pattern);
+        }
+
+        Regex::new(&pattern).chain_err(|| format!("Invalid search pattern \"{}\"", query))
+    }
+}
+
+/// Maps the regex's (non-empty) matches to buffer ranges,
+/// converting their byte offsets to line/offset positions.
+fn regex_matches(data: &str, regex: &Regex) -> Vec<Range> {
+    let mut ranges = Vec::new();
+    let mut position = Position{ line: 0, offset: 0 };
+    let mut scanned = 0;
+
+    for (start, end) in regex.find_iter(data) {
+        if start == end {
+            continue;
+        }
+
+        advance(&mut position, &data[scanned..start]);
+        let match_start = position;
+        advance(&mut position, &data[start..end]);
+        ranges.push(Range::new(match_start, position));
+        scanned = end;
+    }
+
+    ranges
+}
+
+fn advance(position: &mut Position, text: &str) {
+    for grapheme in text.graphemes(true) {
+        if grapheme == "\n" || grapheme == "\r\n" {
+            position.line += 1;
+            position.offset = 0;
+        } else {
+            position.offset += 1;
+        }
+    }
+}
+
+/// Maps the position to a byte offset in the data; the inverse of advance.
+fn byte_offset(data: &str, position: Position) -> usize {
+    let mut current = Position{ line: 0, offset: 0 };
+
+    for (index, grapheme) in data.grapheme_indices(true) {
+        if current >= position {
+            return index;
+        }
+        advance(&mut current, grapheme);
+    }
+
+    data.len()
 }
 
 impl fmt::Display for SearchMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "SEARCH")
+        if self.direction == SearchDirection::Backward {
+            write!(f, "REVERSE ")?;
+        }
+        if self.selection.is_some() {
+            write!(f, "SEARCH SELECTION")
+        } else {
+            write!(f, "SEARCH")
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::models::application::Mode;
     use scribe::buffer::{Buffer, Position, Range};
-    use super::SearchMode;
+    use super::{SearchConfig, SearchMode, SearchSelection};
+
+    fn selection(start: Position, end: Position) -> Option<SearchSelection> {
+        Some(SearchSelection {
+            range: Range::new(start, end),
+            mode: Box::new(Mode::Normal),
+            cursor: end,
+        })
+    }
 
     #[test]
     fn search_populates_results_with_correct_ranges() {
         let mut buffer = Buffer::new();
         buffer.insert("test\ntest");
 
-        let mut mode = SearchMode::new(Some(String::from("test")));
+        let mut mode = SearchMode::new(Some(String::from("test")), SearchConfig::default());
         mode.search(&buffer).unwrap();
 
         assert_eq!(
@@ -77,4 +282,131 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn search_matches_regular_expressions_when_configured() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\nédit 42\n");
+
+        let config = SearchConfig{ regex: true, ..SearchConfig::default() };
+        let mut mode = SearchMode::new(Some(String::from(r"\d+|t\s")), config);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![
+                Range::new(
+                    Position{ line: 1, offset: 3 },
+                    Position{ line: 1, offset: 5 },
+                ),
+                Range::new(
+                    Position{ line: 1, offset: 5 },
+                    Position{ line: 1, offset: 7 },
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_reports_invalid_regular_expressions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+
+        let config = SearchConfig{ regex: true, ..SearchConfig::default() };
+        let mut mode = SearchMode::new(Some(String::from("(amp")), config);
+
+        assert!(mode.validate_query().is_err());
+        assert!(mode.search(&buffer).is_err());
+    }
+
+    #[test]
+    fn search_treats_queries_literally_by_default() {
+        let mut buffer = Buffer::new();
+        buffer.insert("a.p amp");
+
+        let mut mode = SearchMode::new(Some(String::from("a.p")), SearchConfig::default());
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(mode.results.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn expand_replacement_substitutes_capture_groups() {
+        let config = SearchConfig{ regex: true, ..SearchConfig::default() };
+        let mode = SearchMode::new(Some(String::from(r"(\w+)@(\w+)")), config);
+
+        assert_eq!(mode.expand_replacement("amp@rs", "$2@$1").unwrap(), "rs@amp");
+    }
+
+    #[test]
+    fn search_ignores_case_for_lowercase_queries_with_smart_case() {
+        let mut buffer = Buffer::new();
+        buffer.insert("Amp amp AMP");
+
+        let config = SearchConfig{ smart_case: true, ..SearchConfig::default() };
+        let mut mode = SearchMode::new(Some(String::from("amp")), config.clone());
+        mode.search(&buffer).unwrap();
+        assert!(mode.ignoring_case());
+        assert_eq!(mode.results.unwrap().len(), 3);
+
+        let mut mode = SearchMode::new(Some(String::from("Amp")), config);
+        mode.search(&buffer).unwrap();
+        assert!(!mode.ignoring_case());
+        assert_eq!(mode.results.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn search_ignores_case_for_symbol_queries_with_smart_case() {
+        let config = SearchConfig{ smart_case: true, ..SearchConfig::default() };
+        let mode = SearchMode::new(Some(String::from("-> 42")), config);
+
+        assert!(mode.ignoring_case());
+    }
+
+    #[test]
+    fn count_matches_stops_at_the_limit() {
+        let mode = SearchMode::new(Some(String::from("amp")), SearchConfig::default());
+
+        assert_eq!(mode.count_matches("amp amp amp", 10), 3);
+        assert_eq!(mode.count_matches("amp amp amp", 2), 2);
+    }
+
+    #[test]
+    fn count_matches_ignores_empty_queries() {
+        let mode = SearchMode::new(Some(String::new()), SearchConfig::default());
+
+        assert_eq!(mode.count_matches("amp", 10), 0);
+    }
+
+    #[test]
+    fn search_excludes_matches_outside_of_the_selection() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp amp\namp amp");
+
+        let mut mode = SearchMode::new(Some(String::from("amp")), SearchConfig::default());
+        mode.selection = selection(Position{ line: 0, offset: 5 }, Position{ line: 1, offset: 3 });
+        mode.search(&buffer).unwrap();
+
+        // The first line's second match straddles the start of the selection.
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![
+                Range::new(
+                    Position{ line: 1, offset: 0 },
+                    Position{ line: 1, offset: 3 },
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn count_matches_excludes_matches_outside_of_the_selection() {
+        let mut mode = SearchMode::new(Some(String::from("amp")), SearchConfig::default());
+        mode.selection = selection(Position{ line: 0, offset: 4 }, Position{ line: 1, offset: 2 });
+
+        assert_eq!(mode.count_matches("amp amp\namp amp", 10), 1);
+
+        mode.config.regex = true;
+        assert_eq!(mode.count_matches("amp amp\namp amp", 10), 1);
+    }
 }
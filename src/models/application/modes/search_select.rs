@@ -27,6 +27,12 @@ pub trait SearchSelectMode<T: Display>: Display {
     fn select_previous(&mut self);
     fn select_next(&mut self);
     fn config(&self) -> &SearchSelectConfig;
+
+    /// The indices of each result's characters matching the query.
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &[]
+    }
+
     fn message(&mut self) -> Option<String> {
         if self.query().is_empty() {
             Some(String::from("Enter a search query to start."))
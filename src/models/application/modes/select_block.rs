@@ -0,0 +1,102 @@
+use scribe::buffer::{Position, Range};
+use std::cmp;
+use crate::util::indent;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A rectangular selection, spanning the lines and columns between its
+/// anchor and the cursor. Its edges are display columns, so that tabs
+/// line up the way they're rendered. Lines too short to reach the rectangle's left
+/// edge are skipped, and those ending within it are selected to their end.
+pub struct SelectBlockMode {
+    pub anchor: Position,
+}
+
+impl SelectBlockMode {
+    pub fn new(anchor: Position) -> SelectBlockMode {
+        SelectBlockMode { anchor }
+    }
+
+    /// The first and last lines spanned by the rectangle.
+    pub fn lines(&self, cursor: &Position) -> (usize, usize) {
+        (cmp::min(self.anchor.line, cursor.line), cmp::max(self.anchor.line, cursor.line))
+    }
+
+    /// The selected range on each line that reaches the rectangle, from top to bottom.
+    pub fn to_ranges(&self, cursor: &Position, data: &str, tab_width: usize) -> Vec<Range> {
+        let (first_line, last_line) = self.lines(cursor);
+        let lines: Vec<&str> = data.split('\n').collect();
+        let column_of = |position: &Position| {
+            lines.get(position.line)
+                .map(|content| indent::column(content, position.offset, tab_width))
+                .unwrap_or(position.offset)
+        };
+        let anchor_column = column_of(&self.anchor);
+        let cursor_column = column_of(cursor);
+        let left = cmp::min(anchor_column, cursor_column);
+        let right = cmp::max(anchor_column, cursor_column);
+
+        lines.iter()
+            .enumerate()
+            .skip(first_line)
+            .take(last_line - first_line + 1)
+            .filter_map(|(line, content)| {
+                let length = content.graphemes(true).count();
+                if indent::column(content, length, tab_width) < left {
+                    return None;
+                }
+
+                let start = indent::offset(content, left, tab_width);
+                let end = if right > left {
+                    cmp::min(indent::offset(content, right - 1, tab_width) + 1, length)
+                } else {
+                    start
+                };
+
+                Some(Range::new(
+                    Position{ line, offset: start },
+                    Position{ line, offset: end }
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SelectBlockMode;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn to_ranges_skips_lines_short_of_the_rectangle() {
+        let mode = SelectBlockMode::new(Position{ line: 0, offset: 4 });
+        let ranges = mode.to_ranges(&Position{ line: 3, offset: 2 }, "amp editor\nab\nx\nbuffer\n", 4);
+
+        let bounds: Vec<(Position, Position)> = ranges
+            .iter()
+            .map(|range| (range.start(), range.end()))
+            .collect();
+
+        assert_eq!(bounds, vec![
+            (Position{ line: 0, offset: 2 }, Position{ line: 0, offset: 4 }),
+            (Position{ line: 1, offset: 2 }, Position{ line: 1, offset: 2 }),
+            (Position{ line: 3, offset: 2 }, Position{ line: 3, offset: 4 }),
+        ]);
+    }
+
+    #[test]
+    fn to_ranges_aligns_edges_to_display_columns() {
+        let mode = SelectBlockMode::new(Position{ line: 0, offset: 4 });
+        let ranges = mode.to_ranges(&Position{ line: 1, offset: 3 }, "abcdefgh\n\tamp\n", 4);
+
+        let bounds: Vec<(Position, Position)> = ranges
+            .iter()
+            .map(|range| (range.start(), range.end()))
+            .collect();
+
+        // The tab spans columns 0-3, so column 4 is the "a" at offset 1.
+        assert_eq!(bounds, vec![
+            (Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 6 }),
+            (Position{ line: 1, offset: 1 }, Position{ line: 1, offset: 3 }),
+        ]);
+    }
+}
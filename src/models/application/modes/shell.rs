@@ -0,0 +1,38 @@
+use scribe::buffer::Range;
+
+/// Where a shell command's output ends up.
+pub enum ShellOutput {
+    /// Opened in a new buffer, along with the command's exit status.
+    NewBuffer,
+
+    /// Replaces the range, which is piped through the command's input.
+    Selection(Range),
+
+    /// Inserted at the cursor, in insert mode.
+    Cursor,
+}
+
+pub struct ShellMode {
+    pub input: String,
+    pub output: ShellOutput,
+}
+
+impl ShellMode {
+    pub fn new() -> ShellMode {
+        ShellMode { input: String::new(), output: ShellOutput::NewBuffer }
+    }
+
+    pub fn filter(selection: Range) -> ShellMode {
+        ShellMode { input: String::new(), output: ShellOutput::Selection(selection) }
+    }
+
+    pub fn read() -> ShellMode {
+        ShellMode { input: String::new(), output: ShellOutput::Cursor }
+    }
+}
+
+impl Default for ShellMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
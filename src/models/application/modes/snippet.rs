@@ -0,0 +1,111 @@
+use crate::models::application::modes::InsertMode;
+
+/// Tracks the tab-stops of an expanded snippet as they're typed into, on top
+/// of insert mode, which is restored once the final stop has been reached.
+/// Stops are sets of (mirrored) ranges, described by grapheme indices (see
+/// util::grapheme_index) into the buffer's content, the first of which
+/// holds the cursor.
+pub struct SnippetMode {
+    pub insert_mode: InsertMode,
+    stops: Vec<Vec<(usize, usize)>>,
+    end: usize,
+}
+
+impl SnippetMode {
+    /// Starts at the first of the stops, which are specified by the indices
+    /// at which each of their ranges start, followed by the final position.
+    pub fn new(insert_mode: InsertMode, stops: Vec<Vec<usize>>, end: usize) -> SnippetMode {
+        SnippetMode {
+            insert_mode,
+            stops: stops
+                .into_iter()
+                .map(|stop| stop.into_iter().map(|index| (index, index)).collect())
+                .collect(),
+            end,
+        }
+    }
+
+    /// The ranges of the stop holding the cursor.
+    pub fn current_stop(&self) -> &[(usize, usize)] {
+        self.stops.first().map(|stop| stop.as_slice()).unwrap_or(&[])
+    }
+
+    /// Moves on to the next stop, returning the index at
+    /// which it starts, or None if there are none left.
+    pub fn next_stop(&mut self) -> Option<usize> {
+        if !self.stops.is_empty() {
+            self.stops.remove(0);
+        }
+
+        self.current_stop().first().map(|&(start, _)| start)
+    }
+
+    /// The index of the final ($0) position.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Whether the index falls within (or at either end of)
+    /// the range of the current stop holding the cursor.
+    pub fn within_current_stop(&self, index: usize) -> bool {
+        self.current_stop()
+            .first()
+            .map(|&(start, end)| start <= index && index <= end)
+            .unwrap_or(false)
+    }
+
+    /// Adjusts the stops and final position for an edit to the current stop's
+    /// range at the specified position (i.e. 0 for the one holding the cursor,
+    /// followed by its mirrors), which removed and then inserted a number of
+    /// graphemes at the index. The range grows or shrinks with the edit,
+    /// while everything after it is shifted accordingly.
+    pub fn track_edit(&mut self, edited: usize, index: usize, removed: usize, inserted: usize) {
+        let shift = |position: usize| if position >= index + removed {
+            position - removed + inserted
+        } else if position > index {
+            index
+        } else {
+            position
+        };
+
+        for (stop_index, stop) in self.stops.iter_mut().enumerate() {
+            for (range_index, range) in stop.iter_mut().enumerate() {
+                if stop_index == 0 && range_index == edited {
+                    range.1 = (range.1 + inserted).saturating_sub(removed).max(range.0);
+                } else {
+                    *range = (shift(range.0), shift(range.1));
+                }
+            }
+        }
+        self.end = shift(self.end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::InsertMode;
+    use super::SnippetMode;
+
+    #[test]
+    fn track_edit_grows_the_current_stop_and_shifts_those_after_it() {
+        // <$1>$2</$1>
+        let mut mode = SnippetMode::new(InsertMode::new(), vec![vec![1, 4], vec![2]], 5);
+        mode.track_edit(0, 1, 0, 3);
+
+        assert_eq!(mode.current_stop(), &[(1, 4), (7, 7)]);
+        assert_eq!(mode.next_stop(), Some(5));
+        assert_eq!(mode.next_stop(), None);
+        assert_eq!(mode.end(), 8);
+    }
+
+    #[test]
+    fn track_edit_shrinks_the_current_stop() {
+        let mut mode = SnippetMode::new(InsertMode::new(), vec![vec![1]], 5);
+        mode.track_edit(0, 1, 0, 3);
+        mode.track_edit(0, 3, 1, 0);
+
+        assert_eq!(mode.current_stop(), &[(1, 3)]);
+        assert!(mode.within_current_stop(3));
+        assert!(!mode.within_current_stop(4));
+    }
+}
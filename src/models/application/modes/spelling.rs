@@ -0,0 +1,95 @@
+use crate::util::{self, SelectableVec};
+use crate::util::fuzzy::FuzzyMatch;
+use scribe::buffer::Range;
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+/// Lists the suggested corrections for a misspelled word,
+/// which spans the range it'll be replaced within.
+pub struct SpellingMode {
+    insert: bool,
+    input: String,
+    pub range: Range,
+    suggestions: Vec<String>,
+    results: SelectableVec<String>,
+    matched_indices: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+impl SpellingMode {
+    pub fn new(range: Range, suggestions: Vec<String>, config: SearchSelectConfig) -> SpellingMode {
+        SpellingMode {
+            insert: true,
+            input: String::new(),
+            range,
+            suggestions,
+            results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for SpellingMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SPELLING")
+    }
+}
+
+impl SearchSelectMode<String> for SpellingMode {
+    fn search(&mut self) {
+        // Suggestions are listed closest first until a query narrows them down.
+        let results: Vec<FuzzyMatch<String>> = if self.input.is_empty() {
+            self.suggestions
+                .iter()
+                .take(self.config.max_results)
+                .map(FuzzyMatch::unmatched)
+                .collect()
+        } else {
+            util::fuzzy::find(&self.input, &self.suggestions, self.config.max_results)
+        };
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<String> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&String> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+}
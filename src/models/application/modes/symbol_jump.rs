@@ -1,8 +1,9 @@
-use fragment;
+use crate::errors::*;
 use fragment::matching::AsStr;
+use scribe::Buffer;
 use scribe::buffer::{Position, Token, TokenSet};
 use syntect::highlighting::ScopeSelectors;
-use crate::util::SelectableVec;
+use crate::util::{self, SelectableVec};
 use std::fmt;
 use std::iter::Iterator;
 use std::clone::Clone;
@@ -10,33 +11,84 @@ use std::str::FromStr;
 use std::slice::Iter;
 use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
 
+// Scope selectors identifying each kind of symbol, checked in order so
+// that the more specific kinds (e.g. methods over functions) win out.
+const SYMBOL_SCOPES: [(SymbolKind, &str); 8] = [
+    (
+        SymbolKind::Method,
+        "meta.class entity.name.function, meta.impl entity.name.function, \
+         entity.name.function.method, entity.name.method"
+    ),
+    (SymbolKind::Function, "entity.name.function"),
+    (SymbolKind::Class, "entity.name.class, entity.name.type.class"),
+    (SymbolKind::Struct, "entity.name.struct"),
+    (SymbolKind::Enum, "entity.name.enum"),
+    (SymbolKind::Interface, "entity.name.trait, entity.name.interface"),
+    (SymbolKind::Module, "entity.name.module, entity.name.namespace"),
+    (SymbolKind::Type, "entity.name.type"),
+];
+
 pub struct SymbolJumpMode {
     insert: bool,
     input: String,
     symbols: Vec<Symbol>,
+    revision: usize,
     results: SelectableVec<Symbol>,
+    matched_indices: Vec<Vec<usize>>,
     config: SearchSelectConfig,
 }
 
 #[derive(PartialEq, Debug)]
 pub struct Symbol {
     pub token: String,
+    pub kind: SymbolKind,
     pub position: Position,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SymbolKind {
+    Class,
+    Enum,
+    Function,
+    Interface,
+    Method,
+    Module,
+    Struct,
+    Type,
+}
+
 impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", &self.token)
+        // The name leads, so that matched characters line up when highlighted.
+        write!(f, "{}  ({}, line {})", &self.token, self.kind, self.position.line + 1)
+    }
+}
+
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            SymbolKind::Class => "class",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Function => "function",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Method => "method",
+            SymbolKind::Module => "module",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Type => "type",
+        };
+
+        write!(f, "{}", name)
     }
 }
 
 impl Clone for Symbol {
     fn clone(&self) -> Symbol {
-        Symbol{ token: self.token.clone(), position: self.position }
+        Symbol{ token: self.token.clone(), kind: self.kind, position: self.position }
     }
 
     fn clone_from(&mut self, source: &Self) {
         self.token = source.token.clone();
+        self.kind = source.kind;
         self.position = source.position;
     }
 }
@@ -48,17 +100,42 @@ impl AsStr for Symbol {
 }
 
 impl SymbolJumpMode {
-    pub fn new(tokens: &TokenSet, config: SearchSelectConfig) -> SymbolJumpMode {
+    /// Builds the mode using symbols found in the tokens, which were
+    /// produced from the specified revision of the buffer's content.
+    pub fn new(tokens: &TokenSet, revision: usize, config: SearchSelectConfig) -> SymbolJumpMode {
         let symbols = symbols(tokens.iter());
 
         SymbolJumpMode {
             insert: true,
             input: String::new(),
             symbols,
+            revision,
             results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
             config,
         }
     }
+
+    /// Returns the symbols whose names exactly match the specified identifier.
+    pub fn definitions_of(&self, identifier: &str) -> Vec<&Symbol> {
+        self.symbols.iter().filter(|symbol| symbol.token == identifier).collect()
+    }
+
+    /// Rebuilds the symbols (and search results) if the buffer has been
+    /// modified since they were built, so that they don't point to stale lines.
+    pub fn refresh(&mut self, buffer: &Buffer, revision: usize) -> Result<()> {
+        if revision == self.revision {
+            return Ok(());
+        }
+
+        let tokens = buffer.tokens()
+            .chain_err(|| "No tokens available for the current buffer")?;
+        self.symbols = symbols(tokens.iter());
+        self.revision = revision;
+        self.search();
+
+        Ok(())
+    }
 }
 
 impl fmt::Display for SymbolJumpMode {
@@ -70,11 +147,9 @@ impl fmt::Display for SymbolJumpMode {
 impl SearchSelectMode<Symbol> for SymbolJumpMode {
     fn search(&mut self) {
         // Find the symbols we're looking for using the query.
-        let results = fragment::matching::find(&self.input, &self.symbols, self.config.max_results);
-
-        // We don't care about the result objects; we just want
-        // the underlying symbols. Map the collection to get these.
-        self.results = SelectableVec::new(results.into_iter().map(|r| r.clone()).collect());
+        let results = util::fuzzy::find(&self.input, &self.symbols, self.config.max_results);
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
     }
 
     fn query(&mut self) -> &mut String {
@@ -112,18 +187,29 @@ impl SearchSelectMode<Symbol> for SymbolJumpMode {
     fn config(&self) -> &SearchSelectConfig {
         &self.config
     }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
 }
 
 fn symbols<'a, T>(tokens: T) -> Vec<Symbol> where T: Iterator<Item=Token<'a>> {
-    let eligible_scopes = ScopeSelectors::from_str(
-        "entity.name.function, entity.name.class, entity.name.struct"
-    ).unwrap();
+    let kinds: Vec<(SymbolKind, ScopeSelectors)> = SYMBOL_SCOPES
+        .iter()
+        .map(|&(kind, selectors)| (kind, ScopeSelectors::from_str(selectors).unwrap()))
+        .collect();
+
     tokens.filter_map(|token| {
           if let Token::Lexeme(lexeme) = token {
-              // Build a symbol, provided it's of the right type.
-              if eligible_scopes.does_match(lexeme.scope.as_slice()).is_some() {
+              // Build a symbol, provided it's of a recognized kind.
+              let kind = kinds
+                  .iter()
+                  .find(|(_, selectors)| selectors.does_match(lexeme.scope.as_slice()).is_some())
+                  .map(|&(kind, _)| kind);
+              if let Some(kind) = kind {
                   return Some(Symbol {
                       token: lexeme.value.to_string(),
+                      kind,
                       position: lexeme.position,
                   })
               }
@@ -137,7 +223,7 @@ fn symbols<'a, T>(tokens: T) -> Vec<Symbol> where T: Iterator<Item=Token<'a>> {
 mod tests {
     use scribe::buffer::{Lexeme, Position, ScopeStack, Token};
     use std::str::FromStr;
-    use super::{Symbol, symbols};
+    use super::{Symbol, SymbolKind, symbols};
 
     #[test]
     fn symbols_are_limited_to_functions() {
@@ -176,6 +262,48 @@ mod tests {
 
         let results = symbols(tokens.into_iter());
         assert_eq!(results.len(), 1);
-        assert_eq!(results.first().unwrap(), &Symbol{ token: "function".to_string(), position: Position{ line: 1, offset: 0 }});
+        assert_eq!(
+            results.first().unwrap(),
+            &Symbol{
+                token: "function".to_string(),
+                kind: SymbolKind::Function,
+                position: Position{ line: 1, offset: 0 }
+            }
+        );
+    }
+
+    #[test]
+    fn symbols_are_classified_by_kind() {
+        let scopes = [
+            ("source.python meta.class.python meta.function.python entity.name.function.python", SymbolKind::Method),
+            ("source.rust meta.function.rust entity.name.function.rust", SymbolKind::Function),
+            ("source.python meta.class.python entity.name.class.python", SymbolKind::Class),
+            ("source.rust entity.name.struct.rust", SymbolKind::Struct),
+            ("source.rust entity.name.trait.rust", SymbolKind::Interface),
+            ("source.go entity.name.type.go", SymbolKind::Type),
+        ];
+        let tokens = scopes.iter().enumerate().map(|(line, &(scope, _))| {
+            Token::Lexeme(
+                Lexeme{
+                    value: "amp",
+                    position: Position{ line, offset: 0 },
+                    scope: ScopeStack::from_str(scope).unwrap()
+                }
+            )
+        });
+
+        let kinds: Vec<SymbolKind> = symbols(tokens).iter().map(|symbol| symbol.kind).collect();
+        assert_eq!(kinds, scopes.iter().map(|&(_, kind)| kind).collect::<Vec<SymbolKind>>());
+    }
+
+    #[test]
+    fn symbols_display_their_kind_and_line_number() {
+        let symbol = Symbol{
+            token: "amp".to_string(),
+            kind: SymbolKind::Method,
+            position: Position{ line: 2, offset: 4 }
+        };
+
+        assert_eq!(symbol.to_string(), "amp  (method, line 3)");
     }
 }
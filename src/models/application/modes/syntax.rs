@@ -1,5 +1,4 @@
-use fragment;
-use crate::util::SelectableVec;
+use crate::util::{self, SelectableVec};
 use std::fmt;
 use std::slice::Iter;
 use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
@@ -9,6 +8,7 @@ pub struct SyntaxMode {
     input: String,
     syntaxes: Vec<String>,
     results: SelectableVec<String>,
+    matched_indices: Vec<Vec<usize>>,
     config: SearchSelectConfig,
 }
 
@@ -19,6 +19,7 @@ impl SyntaxMode {
             input: String::new(),
             syntaxes,
             results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
             config,
         }
     }
@@ -32,21 +33,10 @@ impl fmt::Display for SyntaxMode {
 
 impl SearchSelectMode<String> for SyntaxMode {
     fn search(&mut self) {
-        // Find the themes we're looking for using the query.
-        let results = fragment::matching::find(
-            &self.input,
-            &self.syntaxes,
-            self.config.max_results
-        );
-
-        // We don't care about the result objects; we just want
-        // the underlying symbols. Map the collection to get these.
-        self.results = SelectableVec::new(
-            results
-            .into_iter()
-            .map(|r| r.clone())
-            .collect()
-        );
+        // Find the syntaxes we're looking for using the query.
+        let results = util::fuzzy::find(&self.input, &self.syntaxes, self.config.max_results);
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
     }
 
     fn query(&mut self) -> &mut String {
@@ -84,4 +74,8 @@ impl SearchSelectMode<String> for SyntaxMode {
     fn config(&self) -> &SearchSelectConfig {
         &self.config
     }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
 }
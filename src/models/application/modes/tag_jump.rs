@@ -0,0 +1,159 @@
+use fragment::matching::AsStr;
+use crate::util::{self, SelectableVec};
+use crate::util::ctags::{Address, Tag};
+use crate::util::fuzzy::FuzzyMatch;
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+pub struct TagJumpMode {
+    insert: bool,
+    input: String,
+    entries: Vec<TagEntry>,
+    results: SelectableVec<TagEntry>,
+    matched_indices: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagEntry {
+    pub tag: Tag,
+    label: String,
+}
+
+impl TagEntry {
+    /// Builds an entry labelled with the tag's path, its line number or
+    /// (trimmed) search pattern, and its kind, when specified.
+    pub fn new(tag: Tag) -> TagEntry {
+        let path = tag.path.to_string_lossy();
+        let location = match tag.address {
+            Address::Line(line) => format!("{}:{}", path, line + 1),
+            Address::Pattern(ref pattern) => format!(
+                "{}  {}",
+                path,
+                pattern.trim_start_matches('^').trim_end_matches('$').trim()
+            ),
+        };
+        let label = match tag.kind {
+            Some(ref kind) => format!("{}  ({})", location, kind),
+            None => location,
+        };
+
+        TagEntry { tag, label }
+    }
+}
+
+impl fmt::Display for TagEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", &self.label)
+    }
+}
+
+impl AsStr for TagEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+impl TagJumpMode {
+    pub fn new(tags: Vec<Tag>, config: SearchSelectConfig) -> TagJumpMode {
+        TagJumpMode {
+            insert: true,
+            input: String::new(),
+            entries: tags.into_iter().map(TagEntry::new).collect(),
+            results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for TagJumpMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TAGS")
+    }
+}
+
+impl SearchSelectMode<TagEntry> for TagJumpMode {
+    fn search(&mut self) {
+        // List all definitions until a query is entered.
+        let results = if self.input.is_empty() {
+            self.entries
+                .iter()
+                .take(self.config.max_results)
+                .map(FuzzyMatch::unmatched)
+                .collect()
+        } else {
+            util::fuzzy::find(&self.input, &self.entries, self.config.max_results)
+        };
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<TagEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&TagEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.results().count() == 0 {
+            Some(String::from("No matching definitions found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use crate::util::ctags::parse;
+    use super::TagJumpMode;
+
+    #[test]
+    fn search_lists_definitions_with_their_locations_and_kinds() {
+        let tags = parse("amp\tsrc/amp.rs\t/^fn amp() {$/;\"\tf\namp\tsrc/lib.rs\t12\n");
+        let mut mode = TagJumpMode::new(tags, SearchSelectConfig::default());
+        mode.search();
+
+        assert_eq!(
+            mode.results().map(|entry| entry.to_string()).collect::<Vec<String>>(),
+            vec!["src/amp.rs  fn amp() {  (f)", "src/lib.rs:12"]
+        );
+    }
+}
@@ -1,5 +1,4 @@
-use fragment;
-use crate::util::SelectableVec;
+use crate::util::{self, SelectableVec};
 use std::fmt;
 use std::slice::Iter;
 use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
@@ -9,19 +8,28 @@ pub struct ThemeMode {
     input: String,
     themes: Vec<String>,
     results: SelectableVec<String>,
+    matched_indices: Vec<Vec<usize>>,
     config: SearchSelectConfig,
+    original_theme: String,
 }
 
 impl ThemeMode {
-    pub fn new(themes: Vec<String>, config: SearchSelectConfig) -> ThemeMode {
+    pub fn new(themes: Vec<String>, original_theme: String, config: SearchSelectConfig) -> ThemeMode {
         ThemeMode {
             insert: true,
             input: String::new(),
             themes,
             results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
             config,
+            original_theme,
         }
     }
+
+    /// The theme in use before browsing, restored if no theme is chosen.
+    pub fn original_theme(&self) -> &str {
+        &self.original_theme
+    }
 }
 
 impl fmt::Display for ThemeMode {
@@ -33,16 +41,9 @@ impl fmt::Display for ThemeMode {
 impl SearchSelectMode<String> for ThemeMode {
     fn search(&mut self) {
         // Find the themes we're looking for using the query.
-        let results = fragment::matching::find(&self.input, &self.themes, self.config.max_results);
-
-        // We don't care about the result objects; we just want
-        // the underlying symbols. Map the collection to get these.
-        self.results = SelectableVec::new(
-            results
-            .into_iter()
-            .map(|r| r.clone())
-            .collect()
-        );
+        let results = util::fuzzy::find(&self.input, &self.themes, self.config.max_results);
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
     }
 
     fn query(&mut self) -> &mut String {
@@ -80,4 +81,8 @@ impl SearchSelectMode<String> for ThemeMode {
     fn config(&self) -> &SearchSelectConfig {
         &self.config
     }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
 }
@@ -0,0 +1,257 @@
+use bloodhound::ExclusionPattern;
+use fragment::matching::AsStr;
+use crate::util::{self, SelectableVec};
+use crate::util::fuzzy::FuzzyMatch;
+use crate::models::application::Event;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::modes::open::Index;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::slice::Iter;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+// Searches for common words can match nearly every line in a
+// workspace; past this point, the results are of little use.
+const MAX_MATCHES: usize = 1_000;
+
+// Long lines (e.g. minified sources) are cut short in result labels.
+const MAX_PREVIEW_LENGTH: usize = 120;
+
+pub struct WorkspaceSearchMode {
+    insert: bool,
+    input: String,
+    pub query: String,
+    pending: bool,
+    truncated: bool,
+    entries: Vec<WorkspaceMatch>,
+    results: SelectableVec<WorkspaceMatch>,
+    matched_indices: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+/// A line containing the search query, relative to the workspace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkspaceMatch {
+    pub path: PathBuf,
+    pub line: usize,
+    label: String,
+}
+
+/// The outcome of searching a workspace's files for a query.
+#[derive(Debug, PartialEq)]
+pub struct WorkspaceSearchResults {
+    pub query: String,
+    pub matches: Vec<WorkspaceMatch>,
+    pub truncated: bool,
+}
+
+impl WorkspaceMatch {
+    /// Builds a match labelled with its path, (one-based) line number,
+    /// and a trimmed preview of the matching line's content.
+    pub fn new(path: PathBuf, line: usize, content: &str) -> WorkspaceMatch {
+        let preview: String = content.trim().chars().take(MAX_PREVIEW_LENGTH).collect();
+        let label = format!("{}:{}: {}", path.to_string_lossy(), line + 1, preview);
+
+        WorkspaceMatch { path, line, label }
+    }
+}
+
+impl fmt::Display for WorkspaceMatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", &self.label)
+    }
+}
+
+impl AsStr for WorkspaceMatch {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+impl WorkspaceSearchMode {
+    /// Searches the workspace for the query in a separate thread, which
+    /// reports its results as an event once every file has been checked.
+    pub fn new(path: PathBuf, query: String, exclusions: Option<Vec<ExclusionPattern>>, events: Sender<Event>, config: SearchSelectConfig) -> WorkspaceSearchMode {
+        let search_query = query.clone();
+        thread::spawn(move || {
+            let results = search(&path, &search_query, exclusions, MAX_MATCHES);
+            let _ = events.send(Event::WorkspaceSearchComplete(results));
+        });
+
+        WorkspaceSearchMode {
+            insert: true,
+            input: String::new(),
+            query,
+            pending: true,
+            truncated: false,
+            entries: Vec::new(),
+            results: SelectableVec::new(Vec::new()),
+            matched_indices: Vec::new(),
+            config,
+        }
+    }
+
+    pub fn set_results(&mut self, results: WorkspaceSearchResults) {
+        self.entries = results.matches;
+        self.truncated = results.truncated;
+        self.pending = false;
+    }
+}
+
+/// Finds lines containing the query in the files beneath the path, skipping
+/// ignored and excluded files, as well as those that can't be read as UTF-8.
+/// Stops after the limit is reached, flagging the results as truncated.
+pub fn search(path: &Path, query: &str, exclusions: Option<Vec<ExclusionPattern>>, limit: usize) -> WorkspaceSearchResults {
+    let mut index = Index::new(path.to_path_buf(), exclusions);
+    index.populate();
+
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    'files: for relative_path in index.paths() {
+        let content = match fs::read_to_string(path.join(relative_path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        for (line, line_content) in content.lines().enumerate() {
+            if !line_content.contains(query) {
+                continue;
+            }
+            if matches.len() == limit {
+                truncated = true;
+                break 'files;
+            }
+            matches.push(WorkspaceMatch::new(relative_path.to_path_buf(), line, line_content));
+        }
+    }
+
+    WorkspaceSearchResults {
+        query: query.to_string(),
+        matches,
+        truncated,
+    }
+}
+
+impl fmt::Display for WorkspaceSearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.truncated {
+            write!(f, "WORKSPACE SEARCH: {} (results truncated to {})", self.query, self.entries.len())
+        } else {
+            write!(f, "WORKSPACE SEARCH: {}", self.query)
+        }
+    }
+}
+
+impl SearchSelectMode<WorkspaceMatch> for WorkspaceSearchMode {
+    fn search(&mut self) {
+        // List all matches until a filter is entered.
+        let results = if self.input.is_empty() {
+            self.entries
+                .iter()
+                .take(self.config.max_results)
+                .map(FuzzyMatch::unmatched)
+                .collect()
+        } else {
+            util::fuzzy::find(&self.input, &self.entries, self.config.max_results)
+        };
+        self.matched_indices = results.iter().map(|r| r.indices.clone()).collect();
+        self.results = SelectableVec::new(results.into_iter().map(|r| r.item.clone()).collect());
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<WorkspaceMatch> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&WorkspaceMatch> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn matched_indices(&self) -> &[Vec<usize>] {
+        &self.matched_indices
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.pending {
+            Some(format!("Searching the workspace for \"{}\"...", self.query))
+        } else if self.results().count() == 0 {
+            Some(String::from("No matches found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::search;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn build_workspace(name: &str) -> PathBuf {
+        // Build outside of the repository, whose ignore rules would otherwise apply.
+        let path = env::temp_dir().join("amp_workspace_search").join(name);
+        fs::remove_dir_all(&path).ok();
+        fs::create_dir_all(path.join("target")).unwrap();
+        fs::create_dir_all(path.join("src")).unwrap();
+        fs::write(path.join(".ignore"), "target\n").unwrap();
+        fs::write(path.join("src/amp.rs"), "fn amp() {\n    editor();\n}\n").unwrap();
+        fs::write(path.join("src/editor.rs"), "  fn editor() {}\n").unwrap();
+        fs::write(path.join("target/amp.rs"), "editor();\n").unwrap();
+
+        path
+    }
+
+    #[test]
+    fn search_labels_matching_lines_in_non_ignored_files() {
+        let path = build_workspace("labels");
+        let results = search(&path, "editor", None, 10);
+
+        assert_eq!(
+            results.matches.iter().map(|m| m.to_string()).collect::<Vec<String>>(),
+            vec!["src/amp.rs:2: editor();", "src/editor.rs:1: fn editor() {}"]
+        );
+        assert!(!results.truncated);
+    }
+
+    #[test]
+    fn search_truncates_results_beyond_the_limit() {
+        let path = build_workspace("truncated");
+        let results = search(&path, "editor", None, 1);
+
+        assert_eq!(results.matches.len(), 1);
+        assert_eq!(results.matches[0].path, PathBuf::from("src/amp.rs"));
+        assert_eq!(results.matches[0].line, 1);
+        assert!(results.truncated);
+    }
+}
@@ -1,42 +1,140 @@
-use app_dirs::{app_dir, app_root, get_app_root, AppDataType, AppInfo};
+use app_dirs::{app_dir, app_root, get_app_dir, get_app_root, AppDataType, AppInfo};
 use bloodhound::ExclusionPattern;
 use crate::errors::*;
 use crate::input::KeyMap;
 use crate::models::application::modes::open;
 use scribe::Buffer;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::yaml::yaml::{Hash, Yaml, YamlLoader};
-use crate::models::application::modes::SearchSelectConfig;
+use crate::models::application::modes::{SearchConfig, SearchSelectConfig};
+use crate::view::color::RGBColor;
+use crate::view::Colors;
+use crate::util::editor_config::EditorConfig;
+use std::collections::HashMap;
 
 const APP_INFO: AppInfo = AppInfo {
     name: "amp",
     author: "Jordan MacDonald",
 };
+const AUTOINDENT_DEFAULT: bool = true;
+const AUTO_PAIRS_DEFAULT: [(char, char); 5] = [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+const AUTO_PAIRS_KEY: &str = "auto_pairs";
+const AUTOSAVE_INTERVAL_DEFAULT: u64 = 0;
+const AUTOSAVE_INTERVAL_KEY: &str = "autosave_interval";
+const AUTOSAVE_PATH: &str = "autosave";
+const AUTOSAVE_UNNAMED_BUFFERS_DEFAULT: bool = false;
+const AUTOSAVE_UNNAMED_BUFFERS_KEY: &str = "autosave_unnamed_buffers";
+const AUTOINDENT_KEY: &str = "autoindent";
+const BACKSPACE_DEFAULT: bool = true;
+const BACKSPACE_KEY: &str = "backspace";
+const CLIPBOARD_KEY: &str = "clipboard";
+const CLIPBOARD_SYSTEM_DEFAULT: bool = true;
+const CONFIRM_QUIT_DEFAULT: bool = false;
+const CONFIRM_QUIT_KEY: &str = "confirm_quit";
+const CONFIRM_QUIT_TIMEOUT_DEFAULT: u64 = 1000;
+const CONFIRM_QUIT_TIMEOUT_KEY: &str = "confirm_quit_timeout";
+const DIRECTIVE_PAIRS_DEFAULT: [(&str, &str); 2] = [("#if", "#endif"), ("#region", "#endregion")];
+const DIRECTIVE_PAIRS_KEY: &str = "directive_pairs";
+const FILETYPES_KEY: &str = "filetypes";
 const FILE_NAME: &str = "config.yml";
+const HIGHLIGHT_WORD_OCCURRENCES_DEFAULT: bool = false;
+const HIGHLIGHT_WORD_OCCURRENCES_KEY: &str = "highlight_word_occurrences";
+const HISTORY_PATH: &str = "history";
+const INSERT_ESCAPE_SEQUENCE_KEY: &str = "insert_escape_sequence";
+const INSERT_ESCAPE_TIMEOUT_DEFAULT: u64 = 500;
+const INSERT_ESCAPE_TIMEOUT_KEY: &str = "insert_escape_timeout";
+const INSERT_ON_EMPTY_FILE_DEFAULT: bool = false;
+const INSERT_ON_EMPTY_FILE_KEY: &str = "insert_on_empty_file";
+const JUMP_LABELS_DEFAULT: &str = "abcdefghijklmnopqrstuvwxyz";
+const JUMP_LABELS_KEY: &str = "jump_labels";
+const JUMP_POSITION_DEFAULT: f32 = 0.5;
+const JUMP_POSITION_KEY: &str = "jump_position";
+const KEEP_SELECTION_AFTER_YANK_DEFAULT: bool = false;
+const KEEP_SELECTION_AFTER_YANK_KEY: &str = "keep_selection_after_yank";
+const LANGUAGE_SERVER_KEY: &str = "language_server";
+const LARGE_FILE_THRESHOLD_DEFAULT: u64 = 10 * 1024 * 1024;
+const LARGE_FILE_THRESHOLD_KEY: &str = "large_file_threshold";
+const LAST_LINE_FIRST_WORD_DEFAULT: bool = false;
+const LAST_LINE_FIRST_WORD_KEY: &str = "last_line_first_word";
 const LINE_LENGTH_GUIDE_DEFAULT: usize = 80;
 const LINE_LENGTH_GUIDE_KEY: &str = "line_length_guide";
+const LINE_NUMBERS_KEY: &str = "line_numbers";
+const LINE_NUMBERS_RELATIVE_DEFAULT: bool = false;
 const LINE_WRAPPING_DEFAULT: bool = true;
 const LINE_WRAPPING_KEY: &str = "line_wrapping";
+const MOUSE_DEFAULT: bool = false;
+const MOUSE_KEY: &str = "mouse";
+const ON_LAST_BUFFER_CLOSE_DEFAULT: &str = "greeter";
+const ON_LAST_BUFFER_CLOSE_KEY: &str = "on_last_buffer_close";
+const PROJECT_FILE_NAME: &str = ".amp.yml";
+const RECENT_FILES_SWITCH_DIRECTORY_DEFAULT: bool = false;
+const RECENT_FILES_SWITCH_DIRECTORY_KEY: &str = "recent_files_switch_directory";
+const RESTORE_SESSION_DEFAULT: bool = false;
+const RESTORE_SESSION_KEY: &str = "restore_session";
+const REUSE_OPEN_BUFFERS_DEFAULT: bool = true;
+const REUSE_OPEN_BUFFERS_KEY: &str = "reuse_open_buffers";
+const SEARCH_KEY: &str = "search";
 const SEARCH_SELECT_KEY: &str = "search_select";
+const SESSION_PATH: &str = "sessions";
+const SHELL_TIMEOUT_DEFAULT: u64 = 10;
+const SHELL_TIMEOUT_KEY: &str = "shell_timeout";
+const SHOW_CONTROL_CHARS_DEFAULT: bool = false;
+const SHOW_CONTROL_CHARS_KEY: &str = "show_control_chars";
+const SHOW_WHITESPACE_DEFAULT: bool = false;
+const SHOW_WHITESPACE_KEY: &str = "show_whitespace";
+const SNIPPETS_KEY: &str = "snippets";
+const STATUS_LINE_KEY: &str = "status_line";
+const SWAP_FILES_DEFAULT: bool = true;
+const SWAP_FILES_KEY: &str = "swap_files";
+const SWAP_PATH: &str = "swap";
+const STATUS_LINE_SHOW_STATS_DEFAULT: bool = false;
 const SOFT_TABS_DEFAULT: bool = true;
 const SOFT_TABS_KEY: &str = "soft_tabs";
+const SPELL_DICTIONARY_DEFAULT: &str = "/usr/share/dict/words";
+const SPELL_DICTIONARY_KEY: &str = "spell_dictionary";
 const SYNTAX_PATH: &str = "syntaxes";
 const TAB_WIDTH_DEFAULT: usize = 2;
 const TAB_WIDTH_KEY: &str = "tab_width";
+pub const TEMPLATES_KEY: &str = "templates";
+const TEMPLATE_PATH: &str = "templates";
 pub const THEME_DEFAULT: &str = "solarized_dark";
 const THEME_KEY: &str = "theme";
 const THEME_PATH: &str = "themes";
 const TYPES_KEY: &str = "types";
+const WHICHWRAP_DEFAULT: bool = false;
+const WHICHWRAP_KEY: &str = "whichwrap";
+const WRAP_INDENT_DEFAULT: bool = false;
+const WRAP_INDENT_KEY: &str = "wrap_indent";
+const WRAP_MOVEMENT_DEFAULT: bool = false;
+const WRAP_MOVEMENT_KEY: &str = "wrap_movement";
 
 /// Loads, creates, and provides default values for application preferences.
 /// Values are immutable once loaded, with the exception of those that provide
 /// expicit setter methods (e.g. `theme`).
+///
+/// Values that can vary by buffer (tab width, soft tabs, line wrapping, and
+/// theme) are resolved in the following order, the first one set winning:
+///
+///   1. the filetypes section for the buffer's syntax (e.g. `filetypes: Rust:`),
+///      with the project's sections merged over the user's,
+///   2. the project's preferences (.amp.yml),
+///   3. the user's preferences,
+///   4. the default value.
+///
+/// EditorConfig settings sit between the project's preferences and the user's,
+/// for the values they cover.
 pub struct Preferences {
     data: Option<Yaml>,
     keymap: KeyMap,
     theme: Option<String>,
+    project_path: Option<PathBuf>,
+    project_data: Option<Yaml>,
+    editor_configs: HashMap<PathBuf, EditorConfig>,
+    syntaxes: HashMap<PathBuf, String>,
+    line_wrapping: Option<bool>,
+    show_whitespace: Option<bool>,
 }
 
 impl Preferences {
@@ -45,34 +143,110 @@ impl Preferences {
         Preferences {
             data,
             keymap: KeyMap::default().expect("Failed to load default keymap!"),
-            theme: None
+            theme: None,
+            project_path: None,
+            project_data: None,
+            editor_configs: HashMap::new(),
+            syntaxes: HashMap::new(),
+            line_wrapping: None,
+            show_whitespace: None,
         }
     }
 
     /// Loads preferences from disk, returning any filesystem or parse errors.
+    /// An invalid keymap falls back to the default one, so that the rest of
+    /// the preferences still apply; use `check_keymap` to report its errors.
     pub fn load() -> Result<Preferences> {
         let data = load_document()?;
         let keymap = load_keymap(
             data.as_ref().and_then(|data| data["keymap"].as_hash())
-        )?;
+        ).or_else(|_| KeyMap::default())?;
 
-        Ok(Preferences { data, keymap, theme: None })
+        Ok(Preferences {
+            data,
+            keymap,
+            theme: None,
+            project_path: None,
+            project_data: None,
+            editor_configs: HashMap::new(),
+            syntaxes: HashMap::new(),
+            line_wrapping: None,
+            show_whitespace: None,
+        })
     }
 
-    /// Reloads all user preferences from disk and merges them with defaults.
+    /// Reloads all user preferences from disk and merges them with defaults,
+    /// re-applying the workspace's project preferences, if any were loaded.
+    /// The theme selected (and settings toggled) during the session are
+    /// discarded in favour of the configured ones; if either file can't be
+    /// read or parsed, nothing changes.
     pub fn reload(&mut self) -> Result<()> {
-        let data = load_document()?;
+        let mut data = load_document()?;
+        let mut project_data = None;
+        if let Some(ref path) = self.project_path {
+            project_data = load_project_document(path)?;
+            data = merge_documents(data, project_data.clone());
+        }
         let keymap = load_keymap(
             data.as_ref().and_then(|data| data["keymap"].as_hash())
         )?;
 
         self.data = data;
+        self.project_data = project_data;
         self.keymap = keymap;
         self.theme = None;
+        self.line_wrapping = None;
+        self.show_whitespace = None;
+
+        Ok(())
+    }
+
+    /// Merges the workspace's project preferences (found in a .amp.yml file
+    /// at its root) over the current ones, key by key, so that they only
+    /// override the values they specify. The project file is remembered,
+    /// so that reloading preferences re-applies it.
+    pub fn load_project(&mut self, workspace_path: &Path) -> Result<()> {
+        let path = workspace_path.join(PROJECT_FILE_NAME);
+        let project_data = load_project_document(&path)?;
+        self.project_path = Some(path);
+        if project_data.is_none() {
+            return Ok(());
+        }
+
+        let data = merge_documents(self.data.take(), project_data.clone());
+        self.keymap = load_keymap(
+            data.as_ref().and_then(|data| data["keymap"].as_hash())
+        )?;
+        self.data = data;
+        self.project_data = project_data;
 
         Ok(())
     }
 
+    /// Replaces the workspace's project preferences with those of another
+    /// workspace, keeping any theme selected (or setting toggled) since
+    /// they were loaded.
+    pub fn change_project(&mut self, workspace_path: &Path) -> Result<()> {
+        let theme = self.theme.take();
+        let line_wrapping = self.line_wrapping;
+        let show_whitespace = self.show_whitespace;
+        self.project_path = Some(workspace_path.join(PROJECT_FILE_NAME));
+        self.reload()?;
+        self.theme = theme;
+        self.line_wrapping = line_wrapping;
+        self.show_whitespace = show_whitespace;
+
+        Ok(())
+    }
+
+    /// Parses the keymap overrides, returning the first problem found with
+    /// them (e.g. an unknown mode or command name), if there is one.
+    pub fn check_keymap(&self) -> Result<()> {
+        load_keymap(
+            self.data.as_ref().and_then(|data| data["keymap"].as_hash())
+        ).map(|_| ())
+    }
+
     /// Read-only keymap accessor method.
     pub fn keymap(&self) -> &KeyMap {
         &self.keymap
@@ -90,6 +264,43 @@ impl Preferences {
             .chain_err(|| "Couldn't create syntax directory or build a path to it.")
     }
 
+    /// A path pointing to the named history file (e.g. search queries),
+    /// creating the history directory if it doesn't already exist.
+    pub fn history_path(name: &str) -> Result<PathBuf> {
+        app_dir(AppDataType::UserConfig, &APP_INFO, HISTORY_PATH)
+            .map(|path| path.join(name))
+            .chain_err(|| "Couldn't create history directory or build a path to it.")
+    }
+
+    /// A path pointing to the autosaved copy of an unnamed buffer,
+    /// creating the autosave directory if it doesn't already exist.
+    pub fn autosave_path(name: &str) -> Result<PathBuf> {
+        app_dir(AppDataType::UserConfig, &APP_INFO, AUTOSAVE_PATH)
+            .map(|path| path.join(name))
+            .chain_err(|| "Couldn't create autosave directory or build a path to it.")
+    }
+
+    /// A path pointing to the named swap file,
+    /// creating the swap directory if it doesn't already exist.
+    pub fn swap_path(name: &str) -> Result<PathBuf> {
+        app_dir(AppDataType::UserConfig, &APP_INFO, SWAP_PATH)
+            .map(|path| path.join(name))
+            .chain_err(|| "Couldn't create swap directory or build a path to it.")
+    }
+
+    /// A path pointing to the session file for the specified workspace,
+    /// creating the session directory if it doesn't already exist.
+    pub fn session_path(workspace_path: &Path) -> Result<PathBuf> {
+        // Flatten the workspace path into a file name that identifies it.
+        let name = workspace_path
+            .to_string_lossy()
+            .replace(|c: char| c == '/' || c == '\\' || c == ':', "%");
+
+        app_dir(AppDataType::UserConfig, &APP_INFO, SESSION_PATH)
+            .map(|path| path.join(name))
+            .chain_err(|| "Couldn't create session directory or build a path to it.")
+    }
+
     /// Returns the preference file loaded into a buffer for editing.
     /// If the file doesn't already exist, it will return a new in-memory buffer
     /// with a pre-populated path, creating the parent config directories
@@ -110,6 +321,19 @@ impl Preferences {
         })
     }
 
+    /// The theme for the path: the in-memory theme, if one's been selected,
+    /// otherwise the one configured for its filetype, if any, and then the
+    /// same fallbacks as `theme`.
+    pub fn theme_for(&self, path: Option<&PathBuf>) -> &str {
+        if self.theme.is_none() {
+            if let Some(theme) = self.filetype_value(path, THEME_KEY).and_then(|theme| theme.as_str()) {
+                return theme;
+            }
+        }
+
+        self.theme()
+    }
+
     /// If set, returns the in-memory theme, falling back to the value set via
     /// the configuration file, and then the default value.
     pub fn theme(&self) -> &str {
@@ -126,6 +350,15 @@ impl Preferences {
             .unwrap_or(THEME_DEFAULT)
     }
 
+    /// The theme configured as the default light or dark variant,
+    /// using the light_theme and dark_theme keys, respectively.
+    pub fn theme_variant(&self, variant: &str) -> Option<String> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[format!("{}_theme", variant).as_str()].as_str())
+            .map(|theme| theme.to_string())
+    }
+
     /// Returns the theme path, making sure the directory exists.
     pub fn theme_path(&self) -> Result<PathBuf> {
         app_dir(AppDataType::UserConfig, &APP_INFO, THEME_PATH)
@@ -137,25 +370,45 @@ impl Preferences {
         self.theme = Some(theme.into());
     }
 
-    pub fn tab_width(&self, path: Option<&PathBuf>) -> usize {
-        self.data
-            .as_ref()
-            .and_then(|data| {
-                if let Some(extension) = path_extension(path) {
-                    if let Yaml::Integer(tab_width) = data[TYPES_KEY][extension][TAB_WIDTH_KEY] {
-                        return Some(tab_width as usize);
-                    } else if let Yaml::Integer(tab_width) = data[TAB_WIDTH_KEY] {
-                        return Some(tab_width as usize);
-                    }
-                } else if let Yaml::Integer(tab_width) = data[TAB_WIDTH_KEY] {
-                    return Some(tab_width as usize);
-                }
+    /// Updates the in-memory line wrapping value, which applies to every buffer.
+    pub fn set_line_wrapping(&mut self, wrapping: bool) {
+        self.line_wrapping = Some(wrapping);
+    }
 
-                None
-            })
+    /// Updates the in-memory whitespace rendering value.
+    pub fn set_show_whitespace(&mut self, show: bool) {
+        self.show_whitespace = Some(show);
+    }
+
+    /// The tab width for the path, as set by its filetype's preferences, the
+    /// project's preferences, the path's EditorConfig settings, or the user's
+    /// preferences, in that order.
+    pub fn tab_width(&self, path: Option<&PathBuf>) -> usize {
+        self.filetype_value(path, TAB_WIDTH_KEY)
+            .and_then(|tab_width| tab_width.as_i64())
+            .map(|tab_width| tab_width as usize)
+            .or_else(|| self.project_data.as_ref().and_then(|data| configured_tab_width(data, path)))
+            .or_else(|| self.editor_config(path).and_then(|config| config.tab_width))
+            .or_else(|| self.data.as_ref().and_then(|data| configured_tab_width(data, path)))
             .unwrap_or(TAB_WIDTH_DEFAULT)
     }
 
+    pub fn search_config(&self) -> SearchConfig {
+        let mut result = SearchConfig::default();
+        if let Some(ref data) = self.data {
+            if let Yaml::Boolean(regex) = data[SEARCH_KEY]["regex"] {
+                result.regex = regex;
+            }
+            if let Yaml::Boolean(smart_case) = data[SEARCH_KEY]["smart_case"] {
+                result.smart_case = smart_case;
+            }
+            if let Yaml::Boolean(wrap) = data[SEARCH_KEY]["wrap"] {
+                result.wrap = wrap;
+            }
+        }
+        result
+    }
+
     pub fn search_select_config(&self) -> SearchSelectConfig {
         let mut result = SearchSelectConfig::default();
         if let Some(ref data) = self.data {
@@ -166,23 +419,295 @@ impl Preferences {
         result
     }
 
+    /// Whether the path is indented using spaces, with the same
+    /// precedence as `tab_width`.
     pub fn soft_tabs(&self, path: Option<&PathBuf>) -> bool {
+        self.filetype_value(path, SOFT_TABS_KEY)
+            .and_then(|soft_tabs| soft_tabs.as_bool())
+            .or_else(|| self.project_data.as_ref().and_then(|data| configured_soft_tabs(data, path)))
+            .or_else(|| self.editor_config(path).and_then(|config| config.soft_tabs))
+            .or_else(|| self.data.as_ref().and_then(|data| configured_soft_tabs(data, path)))
+            .unwrap_or(SOFT_TABS_DEFAULT)
+    }
+
+    /// Reads the EditorConfig settings for the path, which
+    /// are used until they're loaded again (e.g. on save).
+    pub fn load_editor_config(&mut self, path: &Path) {
+        self.editor_configs.insert(path.to_path_buf(), EditorConfig::load(path));
+    }
+
+    /// The EditorConfig settings for the path, if they've been loaded.
+    pub fn editor_config(&self, path: Option<&PathBuf>) -> Option<&EditorConfig> {
+        path.and_then(|path| self.editor_configs.get(path))
+    }
+
+    /// Associates the buffer's path with its syntax, so that the preferences
+    /// for that filetype apply to it. This needs to be repeated whenever the
+    /// buffer's syntax changes.
+    pub fn set_syntax(&mut self, buffer: &Buffer) {
+        if let Some(ref path) = buffer.path {
+            match buffer.syntax_definition {
+                Some(ref syntax) => self.syntaxes.insert(path.clone(), syntax.name.clone()),
+                None => self.syntaxes.remove(path),
+            };
+        }
+    }
+
+    /// The value configured for the key in the filetypes section
+    /// for the path's syntax, if it has been associated with one.
+    fn filetype_value(&self, path: Option<&PathBuf>, key: &str) -> Option<&Yaml> {
+        let syntax = self.syntaxes.get(path?)?;
+        match self.data.as_ref()?[FILETYPES_KEY][syntax.as_str()][key] {
+            Yaml::BadValue => None,
+            ref value => Some(value),
+        }
+    }
+
+    /// Whether new lines are indented to match the line above them.
+    pub fn autoindent(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(autoindent) = data[AUTOINDENT_KEY] {
+                          Some(autoindent)
+                      } else {
+                          None
+                      })
+            .unwrap_or(AUTOINDENT_DEFAULT)
+    }
+
+    /// How long (in seconds) to wait after the last input before
+    /// saving modified buffers. Zero disables autosaving.
+    pub fn autosave_interval(&self) -> u64 {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Integer(interval) = data[AUTOSAVE_INTERVAL_KEY] {
+                          Some(interval.max(0) as u64)
+                      } else {
+                          None
+                      })
+            .unwrap_or(AUTOSAVE_INTERVAL_DEFAULT)
+    }
+
+    /// Whether autosaving copies buffers without a path to the autosave directory.
+    pub fn autosave_unnamed_buffers(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(enabled) = data[AUTOSAVE_UNNAMED_BUFFERS_KEY] {
+                          Some(enabled)
+                      } else {
+                          None
+                      })
+            .unwrap_or(AUTOSAVE_UNNAMED_BUFFERS_DEFAULT)
+    }
+
+    /// The pairs of characters closed automatically when the first of them
+    /// is typed in insert mode, configured as two-character strings (e.g.
+    /// "()"). There are none unless the auto_pairs.enabled option is set.
+    pub fn auto_pairs(&self) -> Vec<(char, char)> {
+        let data = match self.data {
+            Some(ref data) => data,
+            None => return Vec::new(),
+        };
+        if data[AUTO_PAIRS_KEY]["enabled"].as_bool() != Some(true) {
+            return Vec::new();
+        }
+
+        match data[AUTO_PAIRS_KEY]["pairs"] {
+            Yaml::Array(ref pairs) => pairs
+                .iter()
+                .filter_map(|pair| {
+                    let mut characters = pair.as_str()?.chars();
+                    match (characters.next(), characters.next(), characters.next()) {
+                        (Some(opener), Some(closer), None) => Some((opener, closer)),
+                        _ => None,
+                    }
+                })
+                .collect(),
+            _ => AUTO_PAIRS_DEFAULT.to_vec(),
+        }
+    }
+
+    /// Whether backspace can delete past the specified boundary: "eol"
+    /// (joining lines), "indent" (outdenting), or "start" (of insertion).
+    pub fn backspace(&self, boundary: &str) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| match data[BACKSPACE_KEY] {
+                          Yaml::Boolean(enabled) => Some(enabled),
+                          Yaml::Array(ref boundaries) => Some(
+                              boundaries.iter().any(|b| b.as_str() == Some(boundary))
+                          ),
+                          _ => None,
+                      })
+            .unwrap_or(BACKSPACE_DEFAULT)
+    }
+
+    /// Whether copied content is synchronized with the system clipboard.
+    pub fn system_clipboard(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(system) = data[CLIPBOARD_KEY]["system"] {
+                          Some(system)
+                      } else {
+                          None
+                      })
+            .unwrap_or(CLIPBOARD_SYSTEM_DEFAULT)
+    }
+
+    /// Whether quitting requires the quit command to be run twice.
+    pub fn confirm_quit(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(confirm) = data[CONFIRM_QUIT_KEY] {
+                          Some(confirm)
+                      } else {
+                          None
+                      })
+            .unwrap_or(CONFIRM_QUIT_DEFAULT)
+    }
+
+    /// The maximum delay, in milliseconds, between
+    /// the two presses of a confirmed quit.
+    pub fn confirm_quit_timeout(&self) -> u64 {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Integer(timeout) = data[CONFIRM_QUIT_TIMEOUT_KEY] {
+                          Some(timeout as u64)
+                      } else {
+                          None
+                      })
+            .unwrap_or(CONFIRM_QUIT_TIMEOUT_DEFAULT)
+    }
+
+    /// Opening and closing directive prefixes used to find matching
+    /// directives, configured as a list of two-element lists.
+    pub fn directive_pairs(&self, path: Option<&PathBuf>) -> Vec<(String, String)> {
         self.data
             .as_ref()
             .and_then(|data| {
                 if let Some(extension) = path_extension(path) {
-                    if let Yaml::Boolean(soft_tabs) = data[TYPES_KEY][extension][SOFT_TABS_KEY] {
-                        return Some(soft_tabs);
-                    } else if let Yaml::Boolean(soft_tabs) = data[SOFT_TABS_KEY] {
-                        return Some(soft_tabs);
+                    if let Yaml::Array(ref pairs) = data[TYPES_KEY][extension][DIRECTIVE_PAIRS_KEY] {
+                        return Some(parse_directive_pairs(pairs));
                     }
-                } else if let Yaml::Boolean(soft_tabs) = data[SOFT_TABS_KEY] {
-                    return Some(soft_tabs);
+                }
+                if let Yaml::Array(ref pairs) = data[DIRECTIVE_PAIRS_KEY] {
+                    return Some(parse_directive_pairs(pairs));
                 }
 
                 None
             })
-            .unwrap_or(SOFT_TABS_DEFAULT)
+            .unwrap_or_else(|| {
+                DIRECTIVE_PAIRS_DEFAULT
+                    .iter()
+                    .map(|&(opener, closer)| (opener.to_string(), closer.to_string()))
+                    .collect()
+            })
+    }
+
+    pub fn insert_escape_sequence(&self) -> Option<String> {
+        self.data
+            .as_ref()
+            .and_then(|data| match data[INSERT_ESCAPE_SEQUENCE_KEY] {
+                          Yaml::String(ref sequence) if !sequence.is_empty() => Some(sequence.clone()),
+                          _ => None,
+                      })
+    }
+
+    /// The maximum delay, in milliseconds, between the
+    /// keys of an insert mode escape sequence.
+    pub fn insert_escape_timeout(&self) -> u64 {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Integer(timeout) = data[INSERT_ESCAPE_TIMEOUT_KEY] {
+                          Some(timeout as u64)
+                      } else {
+                          None
+                      })
+            .unwrap_or(INSERT_ESCAPE_TIMEOUT_DEFAULT)
+    }
+
+    /// Whether to start in insert mode when opening an empty buffer. New files
+    /// qualify when enabled; existing (empty) files only when set to "always".
+    pub fn insert_on_empty_file(&self, existing_file: bool) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| match data[INSERT_ON_EMPTY_FILE_KEY] {
+                          Yaml::Boolean(insert) => Some(insert && !existing_file),
+                          Yaml::String(ref value) => Some(value == "always"),
+                          _ => None,
+                      })
+            .unwrap_or(INSERT_ON_EMPTY_FILE_DEFAULT)
+    }
+
+    /// The characters used to label jump mode targets, in order of preference.
+    pub fn jump_labels(&self) -> &str {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::String(ref labels) = data[JUMP_LABELS_KEY] {
+                          Some(labels.as_str())
+                      } else {
+                          None
+                      })
+            .filter(|labels| !labels.trim().is_empty())
+            .unwrap_or(JUMP_LABELS_DEFAULT)
+    }
+
+    /// Where lines that have been jumped to are placed in the viewport, as a
+    /// fraction of its height. Accepts "top", "center", or a decimal value.
+    pub fn jump_position(&self) -> f32 {
+        self.data
+            .as_ref()
+            .and_then(|data| match data[JUMP_POSITION_KEY] {
+                          Yaml::String(ref position) if position == "top" => Some(0.0),
+                          Yaml::String(ref position) if position == "center" => Some(0.5),
+                          ref position @ Yaml::Real(_) => position.as_f64().map(|f| f as f32),
+                          Yaml::Integer(position) => Some(position as f32),
+                          _ => None,
+                      })
+            .map(|fraction| fraction.max(0.0).min(1.0))
+            .unwrap_or(JUMP_POSITION_DEFAULT)
+    }
+
+    pub fn keep_selection_after_yank(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(keep) = data[KEEP_SELECTION_AFTER_YANK_KEY] {
+                          Some(keep)
+                      } else {
+                          None
+                      })
+            .unwrap_or(KEEP_SELECTION_AFTER_YANK_DEFAULT)
+    }
+
+    /// The command (followed by its arguments) used to start a language server
+    /// for files with the specified path's extension, configured per type as
+    /// either a command line string or a list. Returns None when unset.
+    pub fn language_server(&self, path: Option<&PathBuf>) -> Option<Vec<String>> {
+        let extension = path_extension(path)?;
+        let command: Vec<String> = match self.data.as_ref()?[TYPES_KEY][extension][LANGUAGE_SERVER_KEY] {
+            Yaml::String(ref command) => command.split_whitespace().map(String::from).collect(),
+            Yaml::Array(ref command) => command.iter().filter_map(|arg| arg.as_str().map(String::from)).collect(),
+            _ => return None,
+        };
+
+        if command.is_empty() {
+            None
+        } else {
+            Some(command)
+        }
+    }
+
+    /// The size, in bytes, above which files are considered too large
+    /// to be loaded in their entirety (e.g. when opening or inserting them).
+    pub fn large_file_threshold(&self) -> u64 {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Integer(threshold) = data[LARGE_FILE_THRESHOLD_KEY] {
+                          Some(threshold as u64)
+                      } else {
+                          None
+                      })
+            .unwrap_or(LARGE_FILE_THRESHOLD_DEFAULT)
     }
 
     pub fn line_length_guide(&self) -> Option<usize> {
@@ -201,15 +726,292 @@ impl Preferences {
                       })
     }
 
-    pub fn line_wrapping(&self) -> bool {
+    /// Whether the path's long lines are wrapped, preferring the value
+    /// set during the session, and then the one configured for its filetype.
+    pub fn line_wrapping(&self, path: Option<&PathBuf>) -> bool {
+        if let Some(wrapping) = self.line_wrapping {
+            return wrapping;
+        }
+
+        self.filetype_value(path, LINE_WRAPPING_KEY)
+            .and_then(|wrapping| wrapping.as_bool())
+            .or_else(|| self.data.as_ref().and_then(|data| data[LINE_WRAPPING_KEY].as_bool()))
+            .unwrap_or(LINE_WRAPPING_DEFAULT)
+    }
+
+    /// Whether the terminal should report mouse input, which is used
+    /// to place the cursor, select text, and scroll. Enabling this
+    /// takes over the terminal's own (native) text selection.
+    pub fn mouse(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(mouse) = data[MOUSE_KEY] {
+                          Some(mouse)
+                      } else {
+                          None
+                      })
+            .unwrap_or(MOUSE_DEFAULT)
+    }
+
+    /// What to do once the last open buffer is closed: "quit" the
+    /// editor, open an empty "scratch" buffer, or show the "greeter".
+    pub fn on_last_buffer_close(&self) -> &str {
         self.data
             .as_ref()
-            .and_then(|data| if let Yaml::Boolean(wrapping) = data[LINE_WRAPPING_KEY] {
-                          Some(wrapping)
+            .and_then(|data| if let Yaml::String(ref action) = data[ON_LAST_BUFFER_CLOSE_KEY] {
+                          Some(action.as_str())
                       } else {
                           None
                       })
-            .unwrap_or(LINE_WRAPPING_DEFAULT)
+            .unwrap_or(ON_LAST_BUFFER_CLOSE_DEFAULT)
+    }
+
+    /// Whether reopening a recent file from outside the workspace
+    /// moves the workspace to that file's directory.
+    pub fn recent_files_switch_directory(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(switch) = data[RECENT_FILES_SWITCH_DIRECTORY_KEY] {
+                          Some(switch)
+                      } else {
+                          None
+                      })
+            .unwrap_or(RECENT_FILES_SWITCH_DIRECTORY_DEFAULT)
+    }
+
+    pub fn restore_session(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(restore) = data[RESTORE_SESSION_KEY] {
+                          Some(restore)
+                      } else {
+                          None
+                      })
+            .unwrap_or(RESTORE_SESSION_DEFAULT)
+    }
+
+    pub fn reuse_open_buffers(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(reuse) = data[REUSE_OPEN_BUFFERS_KEY] {
+                          Some(reuse)
+                      } else {
+                          None
+                      })
+            .unwrap_or(REUSE_OPEN_BUFFERS_DEFAULT)
+    }
+
+    /// Whether recovery copies of modified buffers are kept, in case Amp crashes.
+    pub fn swap_files(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(enabled) = data[SWAP_FILES_KEY] {
+                          Some(enabled)
+                      } else {
+                          None
+                      })
+            .unwrap_or(SWAP_FILES_DEFAULT)
+    }
+
+    pub fn show_control_chars(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(show) = data[SHOW_CONTROL_CHARS_KEY] {
+                          Some(show)
+                      } else {
+                          None
+                      })
+            .unwrap_or(SHOW_CONTROL_CHARS_DEFAULT)
+    }
+
+    /// Whether tabs, trailing spaces, and line endings are drawn
+    /// with visible symbols in normal and insert modes.
+    pub fn show_whitespace(&self) -> bool {
+        if let Some(show) = self.show_whitespace {
+            return show;
+        }
+
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(show) = data[SHOW_WHITESPACE_KEY] {
+                          Some(show)
+                      } else {
+                          None
+                      })
+            .unwrap_or(SHOW_WHITESPACE_DEFAULT)
+    }
+
+    /// The number of seconds shell commands are given to
+    /// finish before they're killed, so that a hung command
+    /// can't freeze the editor.
+    pub fn shell_timeout(&self) -> u64 {
+        self.data
+            .as_ref()
+            .and_then(|data| match data[SHELL_TIMEOUT_KEY] {
+                          Yaml::Integer(timeout) if timeout >= 1 => Some(timeout as u64),
+                          _ => None,
+                      })
+            .unwrap_or(SHELL_TIMEOUT_DEFAULT)
+    }
+
+    /// The body of the snippet with the specified trigger, preferring those
+    /// defined for the path's type over those available for all files.
+    pub fn snippet(&self, path: Option<&PathBuf>, trigger: &str) -> Option<String> {
+        let data = self.data.as_ref()?;
+        if let Some(extension) = path_extension(path) {
+            if let Some(body) = data[TYPES_KEY][extension][SNIPPETS_KEY][trigger].as_str() {
+                return Some(body.to_string());
+            }
+        }
+
+        data[SNIPPETS_KEY][trigger].as_str().map(String::from)
+    }
+
+    /// The word list (or hunspell dictionary) used for spell checking.
+    pub fn spell_dictionary(&self) -> PathBuf {
+        self.data
+            .as_ref()
+            .and_then(|data| data[SPELL_DICTIONARY_KEY].as_str())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(SPELL_DICTIONARY_DEFAULT))
+    }
+
+    /// Whether horizontal motions triggered by the specified key (e.g. "h"
+    /// or "left") should wrap onto adjacent lines at line boundaries.
+    pub fn whichwrap(&self, key: &str) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| match data[WHICHWRAP_KEY] {
+                          Yaml::Boolean(wrap) => Some(wrap),
+                          Yaml::Array(ref keys) => Some(
+                              keys.iter().any(|k| k.as_str() == Some(key))
+                          ),
+                          _ => None,
+                      })
+            .unwrap_or(WHICHWRAP_DEFAULT)
+    }
+
+    /// Whether line numbers are displayed relative to the cursor line,
+    /// which itself shows its absolute line number.
+    pub fn relative_line_numbers(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(relative) = data[LINE_NUMBERS_KEY]["relative"] {
+                          Some(relative)
+                      } else {
+                          None
+                      })
+            .unwrap_or(LINE_NUMBERS_RELATIVE_DEFAULT)
+    }
+
+    /// Whether occurrences of the word under the cursor
+    /// are highlighted throughout the visible buffer.
+    pub fn highlight_word_occurrences(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(highlight) = data[HIGHLIGHT_WORD_OCCURRENCES_KEY] {
+                          Some(highlight)
+                      } else {
+                          None
+                      })
+            .unwrap_or(HIGHLIGHT_WORD_OCCURRENCES_DEFAULT)
+    }
+
+    /// Whether jumping to the last line lands on its first
+    /// non-whitespace character, rather than keeping the column.
+    pub fn last_line_first_word(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(first_word) = data[LAST_LINE_FIRST_WORD_KEY] {
+                          Some(first_word)
+                      } else {
+                          None
+                      })
+            .unwrap_or(LAST_LINE_FIRST_WORD_DEFAULT)
+    }
+
+    /// Whether the continuation rows of wrapped lines
+    /// are indented to match the line's leading whitespace.
+    pub fn wrap_indent(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(indent) = data[WRAP_INDENT_KEY] {
+                          Some(indent)
+                      } else {
+                          None
+                      })
+            .unwrap_or(WRAP_INDENT_DEFAULT)
+    }
+
+    /// Whether vertical movement should follow visual (wrapped)
+    /// lines rather than logical ones, when line wrapping is enabled.
+    pub fn wrap_movement(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(wrap) = data[WRAP_MOVEMENT_KEY] {
+                          Some(wrap)
+                      } else {
+                          None
+                      })
+            .unwrap_or(WRAP_MOVEMENT_DEFAULT)
+    }
+
+    /// Whether the status line shows line, word, and character counts
+    /// (for the buffer or selection), along with the cursor position.
+    pub fn status_line_stats(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(show_stats) = data[STATUS_LINE_KEY]["show_stats"] {
+                          Some(show_stats)
+                      } else {
+                          None
+                      })
+            .unwrap_or(STATUS_LINE_SHOW_STATS_DEFAULT)
+    }
+
+    /// The names of the status line segments to display, in order.
+    /// Returns None when unset, to use the default layout.
+    pub fn status_line_segments(&self) -> Option<Vec<String>> {
+        match self.data.as_ref()?[STATUS_LINE_KEY]["segments"] {
+            Yaml::Array(ref segments) => Some(
+                segments.iter().filter_map(|segment| segment.as_str().map(String::from)).collect()
+            ),
+            _ => None,
+        }
+    }
+
+    /// User-defined status line colors for the specified mode (e.g. "insert"),
+    /// configured either as a background color or as a foreground/background
+    /// pair of hex values. Returns None when unset, to use theme defaults.
+    pub fn status_line_mode_colors(&self, mode: &str) -> Option<Colors> {
+        self.data
+            .as_ref()
+            .and_then(|data| match data[STATUS_LINE_KEY]["mode_colors"][mode] {
+                          Yaml::String(ref background) => parse_hex_color(background).map(|bg|
+                              Colors::Custom(RGBColor(255, 255, 255), bg)
+                          ),
+                          ref colors @ Yaml::Hash(_) => {
+                              let foreground = colors["foreground"].as_str().and_then(parse_hex_color);
+                              let background = colors["background"].as_str().and_then(parse_hex_color);
+                              match (foreground, background) {
+                                  (Some(fg), Some(bg)) => Some(Colors::Custom(fg, bg)),
+                                  (None, Some(bg)) => Some(Colors::Custom(RGBColor(255, 255, 255), bg)),
+                                  (Some(fg), None) => Some(Colors::CustomForeground(fg)),
+                                  (None, None) => None,
+                              }
+                          }
+                          _ => None,
+                      })
+    }
+
+    /// The template file configured for new files with the specified path's
+    /// extension, resolved relative to the templates preference directory.
+    pub fn template_path(&self, path: Option<&PathBuf>) -> Option<PathBuf> {
+        let extension = path_extension(path)?;
+        let template = self.data.as_ref()?[TEMPLATES_KEY][extension].as_str()?;
+        let template_dir = get_app_dir(AppDataType::UserConfig, &APP_INFO, TEMPLATE_PATH).ok()?;
+
+        Some(template_dir.join(template))
     }
 
     pub fn tab_content(&self, path: Option<&PathBuf>) -> String {
@@ -238,6 +1040,17 @@ impl Preferences {
     }
 }
 
+/// Parses a "#rrggbb" hex color, ignoring invalid values.
+fn parse_hex_color(value: &str) -> Option<RGBColor> {
+    let hex = value.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let component = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    Some(RGBColor(component(0)?, component(2)?, component(4)?))
+}
+
 /// Loads the first YAML document in the user's config file.
 fn load_document() -> Result<Option<Yaml>> {
     // Build a path to the config file.
@@ -264,13 +1077,87 @@ fn load_document() -> Result<Option<Yaml>> {
     Ok(parsed_data.into_iter().nth(0))
 }
 
+/// The tab width configured in the document, preferring type-specific values.
+fn configured_tab_width(data: &Yaml, path: Option<&PathBuf>) -> Option<usize> {
+    if let Some(extension) = path_extension(path) {
+        if let Yaml::Integer(tab_width) = data[TYPES_KEY][extension][TAB_WIDTH_KEY] {
+            return Some(tab_width as usize);
+        }
+    }
+    if let Yaml::Integer(tab_width) = data[TAB_WIDTH_KEY] {
+        return Some(tab_width as usize);
+    }
+
+    None
+}
+
+/// The soft tabs setting configured in the document, preferring type-specific values.
+fn configured_soft_tabs(data: &Yaml, path: Option<&PathBuf>) -> Option<bool> {
+    if let Some(extension) = path_extension(path) {
+        if let Yaml::Boolean(soft_tabs) = data[TYPES_KEY][extension][SOFT_TABS_KEY] {
+            return Some(soft_tabs);
+        }
+    }
+    if let Yaml::Boolean(soft_tabs) = data[SOFT_TABS_KEY] {
+        return Some(soft_tabs);
+    }
+
+    None
+}
+
+/// Reads the project preference file, if the workspace has one.
+fn load_project_document(path: &Path) -> Result<Option<Yaml>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let data = fs::read_to_string(path)
+        .chain_err(|| "Couldn't read project preferences file")?;
+    let parsed_data = YamlLoader::load_from_str(&data)
+        .chain_err(|| "Couldn't parse project preferences file")?;
+
+    Ok(parsed_data.into_iter().nth(0))
+}
+
+/// Merges the overriding document into the base one. Hashes are merged
+/// recursively, while any other values (including arrays) are replaced.
+fn merge_documents(base: Option<Yaml>, overrides: Option<Yaml>) -> Option<Yaml> {
+    match (base, overrides) {
+        (Some(base), Some(overrides)) => Some(merge_values(base, overrides)),
+        (base, overrides) => overrides.or(base),
+    }
+}
+
+fn merge_values(base: Yaml, overrides: Yaml) -> Yaml {
+    match (base, overrides) {
+        (Yaml::Hash(mut base), Yaml::Hash(overrides)) => {
+            for (key, value) in overrides {
+                let merged_value = match base.remove(&key) {
+                    Some(base_value) => merge_values(base_value, value),
+                    None => value,
+                };
+                base.insert(key, merged_value);
+            }
+
+            Yaml::Hash(base)
+        }
+        (_, overrides) => overrides,
+    }
+}
+
 /// Loads default keymaps, merging in the provided overrides.
 fn load_keymap(keymap_overrides: Option<&Hash>) -> Result<KeyMap> {
     let mut keymap = KeyMap::default()?;
 
     // Merge user-defined keymaps into defaults.
     if let Some(keymap_data) = keymap_overrides {
-        KeyMap::from(keymap_data).map(|data| keymap.merge(data))?;
+        let overrides = KeyMap::from(keymap_data)?;
+
+        // Overrides for unknown modes would otherwise be silently discarded.
+        if let Some(mode) = overrides.keys().find(|mode| !keymap.contains_key(*mode)) {
+            bail!("Keymap mode \"{}\" doesn't exist", mode);
+        }
+        keymap.merge(overrides);
     }
 
     Ok(keymap)
@@ -283,6 +1170,18 @@ fn path_extension(path: Option<&PathBuf>) -> Option<&str> {
         .and_then(|e| e.to_str())
 }
 
+/// Reads a list of [opener, closer] directive prefixes, skipping any
+/// that aren't made up of two strings.
+fn parse_directive_pairs(pairs: &[Yaml]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .filter_map(|pair| match (pair[0].as_str(), pair[1].as_str()) {
+            (Some(opener), Some(closer)) => Some((opener.to_string(), closer.to_string())),
+            _ => None,
+        })
+        .collect()
+}
+
 fn default_open_mode_exclusions() -> Result<Option<Vec<ExclusionPattern>>> {
     let default_pattern = ExclusionPattern::new("**/.git")
         .chain_err(|| "Failed to parse default git directory exclusion pattern")?;
@@ -291,11 +1190,135 @@ fn default_open_mode_exclusions() -> Result<Option<Vec<ExclusionPattern>>> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ExclusionPattern, Preferences, YamlLoader};
-    use std::path::PathBuf;
-    use crate::input::KeyMap;
+    use super::{Colors, ExclusionPattern, Preferences, RGBColor, YamlLoader, merge_documents};
+    use scribe::{Buffer, Workspace};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use crate::input::{Key, KeyMap};
     use crate::yaml::yaml::Hash;
 
+    #[test]
+    fn merge_documents_merges_nested_hashes_key_by_key() {
+        let base = YamlLoader::load_from_str("tab_width: 2\nsoft_tabs: true\ntypes:\n  rs:\n    tab_width: 4\n    soft_tabs: true").unwrap();
+        let overrides = YamlLoader::load_from_str("tab_width: 8\ntypes:\n  rs:\n    soft_tabs: false").unwrap();
+        let preferences = Preferences::new(merge_documents(base.into_iter().nth(0), overrides.into_iter().nth(0)));
+        let path = PathBuf::from("main.rs");
+
+        assert_eq!(preferences.tab_width(None), 8);
+        assert_eq!(preferences.soft_tabs(None), true);
+        assert_eq!(preferences.tab_width(Some(&path)), 4);
+        assert_eq!(preferences.soft_tabs(Some(&path)), false);
+    }
+
+    #[test]
+    fn auto_pairs_are_only_returned_when_enabled() {
+        assert!(Preferences::new(None).auto_pairs().is_empty());
+
+        let data = YamlLoader::load_from_str("auto_pairs:\n  enabled: true").unwrap();
+        assert_eq!(Preferences::new(data.into_iter().nth(0)).auto_pairs().len(), 5);
+
+        let data = YamlLoader::load_from_str("auto_pairs:\n  enabled: true\n  pairs:\n    - \"<>\"\n    - \"invalid\"").unwrap();
+        assert_eq!(Preferences::new(data.into_iter().nth(0)).auto_pairs(), vec![('<', '>')]);
+    }
+
+    #[test]
+    fn merge_documents_uses_whichever_document_is_present() {
+        let data = YamlLoader::load_from_str("tab_width: 8").unwrap().into_iter().nth(0);
+
+        assert_eq!(merge_documents(None, data.clone()), data);
+        assert_eq!(merge_documents(data.clone(), None), data);
+    }
+
+    #[test]
+    fn load_project_merges_project_preferences_over_existing_ones() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/project_preferences"));
+        fs::create_dir_all(path).unwrap();
+        fs::write(path.join(".amp.yml"), "theme: project_theme\nkeymap:\n  normal:\n    é: application::suspend").unwrap();
+
+        let data = YamlLoader::load_from_str("theme: global_theme\ntab_width: 4").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        preferences.load_project(path).unwrap();
+
+        assert_eq!(preferences.theme(), "project_theme");
+        assert_eq!(preferences.tab_width(None), 4);
+        assert!(preferences.keymap().commands_for("normal", &Key::Char('é')).is_some());
+        assert!(preferences.keymap().commands_for("normal", &Key::Char('i')).is_some());
+    }
+
+    #[test]
+    fn filetype_preferences_take_precedence_once_a_buffer_has_been_associated_with_its_syntax() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/filetype_preferences"));
+        fs::create_dir_all(path).unwrap();
+        fs::write(path.join(".amp.yml"), "tab_width: 3\nfiletypes:\n  Rust:\n    soft_tabs: false").unwrap();
+
+        let data = YamlLoader::load_from_str(
+            "tab_width: 4\nline_wrapping: true\nfiletypes:\n  Rust:\n    tab_width: 8\n    line_wrapping: false\n    theme: rust_theme"
+        ).unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        preferences.load_project(path).unwrap();
+
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(path.join("main.rs"));
+        buffer.syntax_definition = workspace.syntax_set.find_syntax_by_name("Rust").cloned();
+        let plain_text = workspace.syntax_set.find_syntax_by_name("Plain Text").cloned();
+        workspace.add_buffer(buffer);
+        let buffer = workspace.current_buffer().unwrap();
+        let buffer_path = buffer.path.clone();
+
+        // The project's preferences apply until the syntax is associated.
+        assert_eq!(preferences.tab_width(buffer_path.as_ref()), 3);
+        assert_eq!(preferences.theme_for(buffer_path.as_ref()), preferences.theme());
+
+        preferences.set_syntax(buffer);
+        assert_eq!(preferences.tab_width(buffer_path.as_ref()), 8);
+        assert_eq!(preferences.soft_tabs(buffer_path.as_ref()), false);
+        assert_eq!(preferences.line_wrapping(buffer_path.as_ref()), false);
+        assert_eq!(preferences.theme_for(buffer_path.as_ref()), "rust_theme");
+        assert_eq!(preferences.tab_width(None), 3);
+        assert_eq!(preferences.line_wrapping(None), true);
+
+        // Changing the buffer's syntax re-resolves its preferences.
+        buffer.syntax_definition = plain_text;
+        preferences.set_syntax(buffer);
+        assert_eq!(preferences.tab_width(buffer_path.as_ref()), 3);
+
+        // Themes selected during the session override the filetype's.
+        preferences.set_theme("selected_theme");
+        assert_eq!(preferences.theme_for(buffer_path.as_ref()), "selected_theme");
+    }
+
+    #[test]
+    fn editor_config_settings_are_layered_between_user_and_project_preferences() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/editor_config_preferences"));
+        fs::create_dir_all(path).unwrap();
+        fs::write(path.join(".editorconfig"), "root = true\n[*]\nindent_style = tab\nindent_size = 8\n").unwrap();
+        fs::write(path.join(".amp.yml"), "types:\n  rs:\n    tab_width: 3").unwrap();
+        let rust_file = path.join("main.rs");
+        let text_file = path.join("notes.txt");
+
+        let data = YamlLoader::load_from_str("tab_width: 4\nsoft_tabs: true").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        preferences.load_project(path).unwrap();
+        preferences.load_editor_config(&rust_file);
+        preferences.load_editor_config(&text_file);
+
+        assert_eq!(preferences.tab_width(Some(&rust_file)), 3);
+        assert_eq!(preferences.tab_width(Some(&text_file)), 8);
+        assert_eq!(preferences.soft_tabs(Some(&text_file)), false);
+        assert_eq!(preferences.tab_width(None), 4);
+    }
+
+    #[test]
+    fn load_project_ignores_workspaces_without_project_preferences() {
+        let data = YamlLoader::load_from_str("tab_width: 4").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        preferences.load_project(Path::new("/nonexistent/amp")).unwrap();
+
+        assert_eq!(preferences.tab_width(None), 4);
+    }
+
     #[test]
     fn preferences_returns_user_defined_theme_name() {
         let data = YamlLoader::load_from_str("theme: \"my_theme\"").unwrap();
@@ -339,6 +1362,251 @@ mod tests {
                    12);
     }
 
+    #[test]
+    fn directive_pairs_returns_defaults_when_not_configured() {
+        let preferences = Preferences::new(None);
+
+        assert_eq!(
+            preferences.directive_pairs(None),
+            vec![
+                (String::from("#if"), String::from("#endif")),
+                (String::from("#region"), String::from("#endregion")),
+            ]
+        );
+    }
+
+    #[test]
+    fn directive_pairs_returns_user_defined_type_specific_data() {
+        let data = YamlLoader::load_from_str(
+            "directive_pairs: [[\"#if\", \"#endif\"]]\ntypes:\n  lua:\n    directive_pairs: [[\"--region\", \"--endregion\"]]"
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.directive_pairs(Some(PathBuf::from("init.lua")).as_ref()),
+            vec![(String::from("--region"), String::from("--endregion"))]
+        );
+        assert_eq!(
+            preferences.directive_pairs(Some(PathBuf::from("main.c")).as_ref()),
+            vec![(String::from("#if"), String::from("#endif"))]
+        );
+    }
+
+    #[test]
+    fn insert_on_empty_file_only_applies_to_new_files_when_enabled() {
+        let data = YamlLoader::load_from_str("insert_on_empty_file: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.insert_on_empty_file(false));
+        assert!(!preferences.insert_on_empty_file(true));
+        assert!(!Preferences::new(None).insert_on_empty_file(false));
+    }
+
+    #[test]
+    fn insert_on_empty_file_applies_to_existing_files_when_set_to_always() {
+        let data = YamlLoader::load_from_str("insert_on_empty_file: always").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.insert_on_empty_file(true));
+    }
+
+    #[test]
+    fn relative_line_numbers_uses_line_numbers_relative_setting() {
+        let data = YamlLoader::load_from_str("line_numbers:\n  relative: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.relative_line_numbers());
+        assert!(!Preferences::new(None).relative_line_numbers());
+    }
+
+    #[test]
+    fn in_memory_line_wrapping_and_whitespace_values_take_precedence() {
+        let data = YamlLoader::load_from_str(
+            "line_wrapping: true\nshow_whitespace: true\nfiletypes:\n  Rust:\n    line_wrapping: true"
+        ).unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        let path = PathBuf::from("main.rs");
+        preferences.syntaxes.insert(path.clone(), String::from("Rust"));
+        assert!(preferences.line_wrapping(Some(&path)));
+        assert!(preferences.show_whitespace());
+
+        preferences.set_line_wrapping(false);
+        preferences.set_show_whitespace(false);
+        assert!(!preferences.line_wrapping(Some(&path)));
+        assert!(!preferences.line_wrapping(None));
+        assert!(!preferences.show_whitespace());
+    }
+
+    #[test]
+    fn highlight_word_occurrences_is_disabled_by_default() {
+        let data = YamlLoader::load_from_str("highlight_word_occurrences: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.highlight_word_occurrences());
+        assert!(!Preferences::new(None).highlight_word_occurrences());
+    }
+
+    #[test]
+    fn last_line_first_word_is_disabled_by_default() {
+        let data = YamlLoader::load_from_str("last_line_first_word: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.last_line_first_word());
+        assert!(!Preferences::new(None).last_line_first_word());
+    }
+
+    #[test]
+    fn check_keymap_reports_unknown_modes_and_commands() {
+        let data = YamlLoader::load_from_str("keymap:\n  normal:\n    k: cursor::move_up").unwrap();
+        assert!(Preferences::new(data.into_iter().nth(0)).check_keymap().is_ok());
+
+        let data = YamlLoader::load_from_str("keymap:\n  normal:\n    k: cursor::move_upward").unwrap();
+        let error = Preferences::new(data.into_iter().nth(0)).check_keymap().unwrap_err();
+        assert_eq!(
+            error.iter().nth(1).map(|cause| cause.to_string()),
+            Some(String::from("Keymap command \"cursor::move_upward\" doesn't exist"))
+        );
+
+        let data = YamlLoader::load_from_str("keymap:\n  norml:\n    k: cursor::move_up").unwrap();
+        let error = Preferences::new(data.into_iter().nth(0)).check_keymap().unwrap_err();
+        assert_eq!(error.to_string(), "Keymap mode \"norml\" doesn't exist");
+    }
+
+    #[test]
+    fn language_server_returns_type_specific_commands() {
+        let data = YamlLoader::load_from_str(
+            "types:\n  rs:\n    language_server: rust-analyzer\n  py:\n    language_server:\n      - pyls\n      - -v"
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.language_server(Some(&PathBuf::from("main.rs"))),
+            Some(vec![String::from("rust-analyzer")])
+        );
+        assert_eq!(
+            preferences.language_server(Some(&PathBuf::from("main.py"))),
+            Some(vec![String::from("pyls"), String::from("-v")])
+        );
+        assert_eq!(preferences.language_server(Some(&PathBuf::from("main.go"))), None);
+        assert_eq!(preferences.language_server(None), None);
+    }
+
+    #[test]
+    fn snippet_prefers_type_specific_snippets() {
+        let data = YamlLoader::load_from_str(
+            "snippets:\n  fn: \"function $1() {}\"\n  if: \"if $1\"\ntypes:\n  rs:\n    snippets:\n      fn: \"fn $1() {}\""
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.snippet(Some(&PathBuf::from("main.rs")), "fn"), Some(String::from("fn $1() {}")));
+        assert_eq!(preferences.snippet(Some(&PathBuf::from("main.rs")), "if"), Some(String::from("if $1")));
+        assert_eq!(preferences.snippet(None, "fn"), Some(String::from("function $1() {}")));
+        assert_eq!(preferences.snippet(None, "for"), None);
+    }
+
+    #[test]
+    fn wrap_indent_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("wrap_indent: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.wrap_indent());
+        assert!(!Preferences::new(None).wrap_indent());
+    }
+
+    #[test]
+    fn wrap_movement_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("wrap_movement: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.wrap_movement());
+        assert!(!Preferences::new(None).wrap_movement());
+    }
+
+    #[test]
+    fn status_line_stats_uses_status_line_show_stats_setting() {
+        let data = YamlLoader::load_from_str("status_line:\n  show_stats: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.status_line_stats());
+        assert!(!Preferences::new(None).status_line_stats());
+    }
+
+    #[test]
+    fn status_line_segments_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("status_line:\n  segments: [mode, path]").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.status_line_segments(),
+            Some(vec![String::from("mode"), String::from("path")])
+        );
+        assert_eq!(Preferences::new(None).status_line_segments(), None);
+    }
+
+    #[test]
+    fn status_line_mode_colors_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str(
+            "status_line:\n  mode_colors:\n    insert: \"#00b400\"\n    normal:\n      foreground: \"#000000\"\n      background: \"#268bd2\""
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.status_line_mode_colors("insert"),
+            Some(Colors::Custom(RGBColor(255, 255, 255), RGBColor(0, 180, 0)))
+        );
+        assert_eq!(
+            preferences.status_line_mode_colors("normal"),
+            Some(Colors::Custom(RGBColor(0, 0, 0), RGBColor(38, 139, 210)))
+        );
+        assert_eq!(preferences.status_line_mode_colors("select"), None);
+    }
+
+    #[test]
+    fn status_line_mode_colors_ignores_invalid_colors() {
+        let data = YamlLoader::load_from_str(
+            "status_line:\n  mode_colors:\n    insert: \"green\""
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.status_line_mode_colors("insert"), None);
+    }
+
+    #[test]
+    fn template_path_returns_the_template_for_the_path_extension() {
+        let data = YamlLoader::load_from_str("templates:\n  rs: /amp/module.rs").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.template_path(Some(&PathBuf::from("src/amp.rs"))),
+            Some(PathBuf::from("/amp/module.rs"))
+        );
+        assert_eq!(preferences.template_path(Some(&PathBuf::from("amp.html"))), None);
+        assert_eq!(preferences.template_path(None), None);
+    }
+
+    #[test]
+    fn jump_labels_falls_back_to_the_alphabet_when_blank() {
+        let data = YamlLoader::load_from_str("jump_labels: asdfjkl").unwrap();
+        assert_eq!(Preferences::new(data.into_iter().nth(0)).jump_labels(), "asdfjkl");
+
+        let data = YamlLoader::load_from_str("jump_labels: ' '").unwrap();
+        assert_eq!(Preferences::new(data.into_iter().nth(0)).jump_labels(), "abcdefghijklmnopqrstuvwxyz");
+    }
+
+    #[test]
+    fn jump_position_accepts_named_and_fractional_positions() {
+        let data = YamlLoader::load_from_str("jump_position: top").unwrap();
+        assert_eq!(Preferences::new(data.into_iter().nth(0)).jump_position(), 0.0);
+
+        let data = YamlLoader::load_from_str("jump_position: 0.25").unwrap();
+        assert_eq!(Preferences::new(data.into_iter().nth(0)).jump_position(), 0.25);
+
+        let data = YamlLoader::load_from_str("jump_position: 2.0").unwrap();
+        assert_eq!(Preferences::new(data.into_iter().nth(0)).jump_position(), 1.0);
+
+        assert_eq!(Preferences::new(None).jump_position(), 0.5);
+    }
+
     #[test]
     fn soft_tabs_returns_user_defined_data() {
         let data = YamlLoader::load_from_str("soft_tabs: false").unwrap();
@@ -400,7 +1668,168 @@ mod tests {
         let data = YamlLoader::load_from_str("line_wrapping: false").unwrap();
         let preferences = Preferences::new(data.into_iter().nth(0));
 
-        assert_eq!(preferences.line_wrapping(), false);
+        assert_eq!(preferences.line_wrapping(None), false);
+    }
+
+    #[test]
+    fn autoindent_is_enabled_by_default() {
+        let preferences = Preferences::new(None);
+
+        assert!(preferences.autoindent());
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_autoindent() {
+        let data = YamlLoader::load_from_str("autoindent: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.autoindent());
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_autosave_settings() {
+        let data = YamlLoader::load_from_str("autosave_interval: 5\nautosave_unnamed_buffers: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.autosave_interval(), 5);
+        assert!(preferences.autosave_unnamed_buffers());
+        assert_eq!(Preferences::new(None).autosave_interval(), 0);
+        assert!(!Preferences::new(None).autosave_unnamed_buffers());
+    }
+
+    #[test]
+    fn insert_escape_sequence_is_disabled_by_default() {
+        let preferences = Preferences::new(None);
+
+        assert_eq!(preferences.insert_escape_sequence(), None);
+        assert_eq!(preferences.insert_escape_timeout(), 500);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_insert_escape_sequence() {
+        let data = YamlLoader::load_from_str("insert_escape_sequence: jk\ninsert_escape_timeout: 200").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.insert_escape_sequence(), Some(String::from("jk")));
+        assert_eq!(preferences.insert_escape_timeout(), 200);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_shell_timeout() {
+        let data = YamlLoader::load_from_str("shell_timeout: 30").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.shell_timeout(), 30);
+        assert_eq!(Preferences::new(None).shell_timeout(), 10);
+    }
+
+    #[test]
+    fn preferences_ignores_shell_timeouts_below_one_second() {
+        for value in vec!["0", "-5"] {
+            let data = YamlLoader::load_from_str(&format!("shell_timeout: {}", value)).unwrap();
+            let preferences = Preferences::new(data.into_iter().nth(0));
+
+            assert_eq!(preferences.shell_timeout(), 10);
+        }
+    }
+
+    #[test]
+    fn on_last_buffer_close_defaults_to_greeter() {
+        let preferences = Preferences::new(None);
+
+        assert_eq!(preferences.on_last_buffer_close(), "greeter");
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_on_last_buffer_close() {
+        let data = YamlLoader::load_from_str("on_last_buffer_close: quit").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.on_last_buffer_close(), "quit");
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_keep_selection_after_yank() {
+        let data = YamlLoader::load_from_str("keep_selection_after_yank: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.keep_selection_after_yank(), true);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_mouse() {
+        let data = YamlLoader::load_from_str("mouse: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.mouse(), true);
+        assert_eq!(Preferences::new(None).mouse(), false);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_recent_files_switch_directory() {
+        let data = YamlLoader::load_from_str("recent_files_switch_directory: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.recent_files_switch_directory(), true);
+        assert_eq!(Preferences::new(None).recent_files_switch_directory(), false);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_restore_session() {
+        let data = YamlLoader::load_from_str("restore_session: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.restore_session(), true);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_reuse_open_buffers() {
+        let data = YamlLoader::load_from_str("reuse_open_buffers: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.reuse_open_buffers(), false);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_swap_files() {
+        let data = YamlLoader::load_from_str("swap_files: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.swap_files());
+        assert!(Preferences::new(None).swap_files());
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_show_control_chars() {
+        let data = YamlLoader::load_from_str("show_control_chars: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.show_control_chars(), true);
+    }
+
+    #[test]
+    fn whichwrap_defaults_to_false() {
+        let preferences = Preferences::new(None);
+
+        assert_eq!(preferences.whichwrap("h"), false);
+    }
+
+    #[test]
+    fn whichwrap_applies_to_specified_keys() {
+        let data = YamlLoader::load_from_str("whichwrap: [h, right]").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.whichwrap("h"), true);
+        assert_eq!(preferences.whichwrap("right"), true);
+        assert_eq!(preferences.whichwrap("l"), false);
+    }
+
+    #[test]
+    fn whichwrap_applies_to_all_keys_when_enabled() {
+        let data = YamlLoader::load_from_str("whichwrap: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.whichwrap("left"), true);
     }
 
     #[test]
@@ -497,11 +1926,65 @@ mod tests {
         let mut preferences = Preferences{
             data: None,
             keymap: KeyMap::from(&Hash::new()).unwrap(),
-            theme: None
+            theme: None,
+            project_path: None,
+            project_data: None,
+            editor_configs: HashMap::new(),
+            syntaxes: HashMap::new(),
+            line_wrapping: None,
+            show_whitespace: None,
         };
 
         // Reload the preferences, ensuring that it refreshes the keymap.
         preferences.reload().unwrap();
         assert!(preferences.keymap().get("normal").is_some());
     }
+
+    #[test]
+    fn search_config_defaults_to_literal_matching() {
+        let preferences = Preferences::new(None);
+
+        assert!(!preferences.search_config().regex);
+        assert!(!preferences.search_config().smart_case);
+        assert!(preferences.search_config().wrap);
+    }
+
+    #[test]
+    fn search_config_uses_regex_setting() {
+        let data = YamlLoader::load_from_str("search:\n  regex: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.search_config().regex);
+    }
+
+    #[test]
+    fn search_config_uses_smart_case_setting() {
+        let data = YamlLoader::load_from_str("search:\n  smart_case: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.search_config().smart_case);
+    }
+
+    #[test]
+    fn search_config_uses_wrap_setting() {
+        let data = YamlLoader::load_from_str("search:\n  wrap: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.search_config().wrap);
+    }
+
+    #[test]
+    fn system_clipboard_defaults_to_true() {
+        let preferences = Preferences::new(None);
+
+        assert!(preferences.system_clipboard());
+    }
+
+    #[test]
+    fn system_clipboard_uses_clipboard_system_setting() {
+        let data = YamlLoader::load_from_str("clipboard:\n  system: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.system_clipboard());
+    }
 }
@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RECENT_FILES_LIMIT: usize = 200;
+
+/// Files opened across all workspaces and sessions, from most to least
+/// recently opened. Entries are persisted to disk when the list has a path.
+#[derive(Default)]
+pub struct RecentFiles {
+    paths: Vec<PathBuf>,
+    path: Option<PathBuf>,
+}
+
+impl RecentFiles {
+    /// Loads the list stored at the specified path, starting
+    /// with an empty list if it doesn't exist or can't be read.
+    pub fn load(path: PathBuf) -> RecentFiles {
+        let paths = fs::read_to_string(&path)
+            .map(|data| data.lines().map(PathBuf::from).collect())
+            .unwrap_or_else(|_| Vec::new());
+
+        RecentFiles { paths, path: Some(path) }
+    }
+
+    /// Moves the (canonical) path to the front of the list, discarding
+    /// the least recently opened paths past the limit.
+    pub fn add(&mut self, path: &Path) {
+        if self.paths.first().map(|p| p == path).unwrap_or(false) {
+            return;
+        }
+        // Paths are stored one per line.
+        if path.to_string_lossy().contains('\n') {
+            return;
+        }
+
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(RECENT_FILES_LIMIT);
+
+        // The list is a convenience; failing to persist it shouldn't get in the way.
+        if let Some(ref file) = self.path {
+            let data: Vec<String> = self.paths.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+            let _ = fs::write(file, data.join("\n"));
+        }
+    }
+
+    /// The paths, from most to least recently opened.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecentFiles, RECENT_FILES_LIMIT};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn add_moves_existing_paths_to_the_front() {
+        let mut recent_files = RecentFiles::default();
+        recent_files.add(Path::new("/amp/src/main.rs"));
+        recent_files.add(Path::new("/notes/todo.md"));
+        recent_files.add(Path::new("/amp/src/main.rs"));
+
+        assert_eq!(recent_files.paths(), &[
+            PathBuf::from("/amp/src/main.rs"),
+            PathBuf::from("/notes/todo.md"),
+        ]);
+    }
+
+    #[test]
+    fn add_discards_the_oldest_paths_past_the_limit() {
+        let mut recent_files = RecentFiles::default();
+        for i in 0..RECENT_FILES_LIMIT + 2 {
+            recent_files.add(Path::new(&format!("/files/{}", i)));
+        }
+
+        assert_eq!(recent_files.paths().len(), RECENT_FILES_LIMIT);
+        assert_eq!(recent_files.paths().last(), Some(&PathBuf::from("/files/2")));
+    }
+}
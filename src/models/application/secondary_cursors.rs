@@ -0,0 +1,64 @@
+use scribe::buffer::Position;
+
+/// Additional cursors in the current buffer, at which insertions and
+/// deletions are repeated alongside the buffer's own (primary) cursor.
+#[derive(Default)]
+pub struct SecondaryCursors {
+    positions: Vec<Position>,
+}
+
+impl SecondaryCursors {
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn add(&mut self, position: Position, primary: &Position) {
+        let mut positions = self.positions.clone();
+        positions.push(position);
+        self.replace(positions, primary);
+    }
+
+    /// Replaces the cursors, sorting them and dropping any that
+    /// overlap one another or the primary cursor.
+    pub fn replace(&mut self, mut positions: Vec<Position>, primary: &Position) {
+        positions.sort_by_key(|position| (position.line, position.offset));
+        positions.dedup();
+        positions.retain(|position| position != primary);
+        self.positions = positions;
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecondaryCursors;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn replace_sorts_and_removes_overlapping_cursors() {
+        let mut cursors = SecondaryCursors::default();
+        let primary = Position{ line: 0, offset: 0 };
+        cursors.replace(vec![
+            Position{ line: 2, offset: 1 },
+            Position{ line: 0, offset: 0 },
+            Position{ line: 1, offset: 3 },
+            Position{ line: 2, offset: 1 },
+        ], &primary);
+
+        assert_eq!(cursors.positions(), &[
+            Position{ line: 1, offset: 3 },
+            Position{ line: 2, offset: 1 },
+        ]);
+    }
+}
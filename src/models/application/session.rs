@@ -0,0 +1,173 @@
+use crate::errors::*;
+use crate::view::View;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The files open in a workspace, along with their cursor positions and
+/// bookmarked lines, persisted on exit so that they can be restored in a later session.
+#[derive(Debug, Default, PartialEq)]
+pub struct Session {
+    pub entries: Vec<SessionEntry>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SessionEntry {
+    pub path: PathBuf,
+    pub cursor: Position,
+    pub bookmarks: Vec<usize>,
+}
+
+impl Session {
+    /// Builds a session from the workspace's buffers, ordered so that the
+    /// current buffer comes last (and is current again once restored).
+    /// Buffers without a path are omitted.
+    pub fn from_workspace(workspace: &mut Workspace, view: &View) -> Session {
+        let mut entries = Vec::new();
+        let original_id = match workspace.current_buffer() {
+            Some(buffer) => buffer.id,
+            None => return Session::default(),
+        };
+
+        loop {
+            workspace.next_buffer();
+            if let Some(buffer) = workspace.current_buffer() {
+                if let Some(ref path) = buffer.path {
+                    entries.push(SessionEntry {
+                        path: path.clone(),
+                        cursor: *buffer.cursor,
+                        bookmarks: view.bookmarks(buffer).unwrap_or_default(),
+                    });
+                }
+            }
+
+            if workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+                break;
+            }
+        }
+
+        Session { entries }
+    }
+
+    pub fn load(path: &Path) -> Result<Session> {
+        let data = fs::read_to_string(path).chain_err(|| "Couldn't read the session file")?;
+
+        Ok(Session::parse(&data))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.serialize()).chain_err(|| "Couldn't write the session file")
+    }
+
+    // Each entry is stored on its own line as "line\toffset\tbookmarks\tpath",
+    // where bookmarks is a comma-separated list of lines; the path comes last
+    // so that it can contain tabs. Entries saved before bookmarks were stored
+    // are read without them, and malformed entries are skipped.
+    fn parse(data: &str) -> Session {
+        let entries = data.lines().filter_map(|entry| {
+            let mut fields = entry.splitn(3, '\t');
+            let line = fields.next()?.parse().ok()?;
+            let offset = fields.next()?.parse().ok()?;
+            let remainder = fields.next()?;
+            let mut fields = remainder.splitn(2, '\t');
+            let (bookmarks, path) = match (fields.next().and_then(parse_bookmarks), fields.next()) {
+                (Some(bookmarks), Some(path)) => (bookmarks, path),
+                _ => (Vec::new(), remainder),
+            };
+            if path.is_empty() {
+                return None;
+            }
+
+            Some(SessionEntry { path: PathBuf::from(path), cursor: Position { line, offset }, bookmarks })
+        }).collect();
+
+        Session { entries }
+    }
+
+    fn serialize(&self) -> String {
+        self.entries.iter().map(|entry| {
+            let bookmarks: Vec<String> = entry.bookmarks.iter().map(|line| line.to_string()).collect();
+
+            format!(
+                "{}\t{}\t{}\t{}",
+                entry.cursor.line,
+                entry.cursor.offset,
+                bookmarks.join(","),
+                entry.path.to_string_lossy()
+            )
+        }).collect::<Vec<String>>().join("\n")
+    }
+}
+
+/// Reads a comma-separated list of bookmarked lines, which may be empty.
+fn parse_bookmarks(data: &str) -> Option<Vec<usize>> {
+    if data.is_empty() {
+        return Some(Vec::new());
+    }
+
+    data.split(',').map(|line| line.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Session, SessionEntry};
+    use crate::models::application::Preferences;
+    use crate::view::View;
+    use scribe::{Buffer, Workspace};
+    use scribe::buffer::Position;
+    use std::cell::RefCell;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    #[test]
+    fn parse_reads_serialized_entries() {
+        let session = Session {
+            entries: vec![
+                SessionEntry { path: PathBuf::from("/amp/src/lib.rs"), cursor: Position{ line: 3, offset: 2 }, bookmarks: vec![1, 4] },
+                SessionEntry { path: PathBuf::from("/amp/Cargo.toml"), cursor: Position{ line: 0, offset: 0 }, bookmarks: Vec::new() },
+            ]
+        };
+
+        assert_eq!(Session::parse(&session.serialize()), session);
+    }
+
+    #[test]
+    fn parse_skips_malformed_entries() {
+        let session = Session::parse("1\t2\t3\t/amp/src/lib.rs\nnot an entry\n3\tfour\t\t/amp/Cargo.toml\n5\t6\t\t");
+
+        assert_eq!(session.entries, vec![
+            SessionEntry { path: PathBuf::from("/amp/src/lib.rs"), cursor: Position{ line: 1, offset: 2 }, bookmarks: vec![3] }
+        ]);
+    }
+
+    #[test]
+    fn parse_reads_entries_without_bookmarks() {
+        let session = Session::parse("1\t2\t/amp/src/lib.rs");
+
+        assert_eq!(session.entries, vec![
+            SessionEntry { path: PathBuf::from("/amp/src/lib.rs"), cursor: Position{ line: 1, offset: 2 }, bookmarks: Vec::new() }
+        ]);
+    }
+
+    #[test]
+    fn from_workspace_round_trips_bookmarks() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::from_file(Path::new("Cargo.toml")).unwrap());
+        let buffer = workspace.current_buffer().unwrap();
+        view.initialize_buffer(buffer).unwrap();
+        buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+        view.toggle_bookmark(buffer).unwrap();
+
+        let session = Session::parse(&Session::from_workspace(&mut workspace, &view).serialize());
+
+        assert_eq!(session.entries.len(), 1);
+        assert!(session.entries[0].path.ends_with("Cargo.toml"));
+        assert_eq!(session.entries[0].cursor, Position{ line: 2, offset: 0 });
+        assert_eq!(session.entries[0].bookmarks, vec![2]);
+    }
+}
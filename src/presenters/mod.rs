@@ -1,28 +1,33 @@
 pub mod error;
+pub mod message;
 pub mod modes;
+pub mod status_line;
 
 use std::path::{Path, PathBuf};
 use scribe::Workspace;
-use crate::view::{Colors, StatusLineData, Style};
+use scribe::buffer::Position;
+use crate::util::{self, line_ending::LineEndings};
+use crate::view::{Colors, StatusLineData, Style, TextStats};
 use git2::{self, Repository, Status};
 
-fn path_as_title(path: &Path) -> String {
-    format!(" {}", path.to_string_lossy())
+fn path_as_title(path: &Path, workspace_path: &Path) -> String {
+    format!(" {}", util::display_path(path, workspace_path))
 }
 
 fn current_buffer_status_line_data(workspace: &mut Workspace) -> StatusLineData {
     let modified = workspace.current_buffer().map(|b| b.modified()).unwrap_or(false);
+    let path = workspace.current_buffer().and_then(|b| b.path.clone());
 
-    let (content, style) = workspace.current_buffer_path().map(|path| {
+    let (content, style) = path.map(|path| {
         // Determine buffer title styles based on its modification status.
         if modified {
             // Use an emboldened path with an asterisk.
-            let mut title = path_as_title(path);
+            let mut title = path_as_title(&path, &workspace.path);
             title.push('*');
 
             (title, Style::Bold)
         } else {
-            (path_as_title(path), Style::Default)
+            (path_as_title(&path, &workspace.path), Style::Default)
         }
     }).unwrap_or((String::new(), Style::Default));
 
@@ -33,6 +38,66 @@ fn current_buffer_status_line_data(workspace: &mut Workspace) -> StatusLineData
     }
 }
 
+/// Displays the buffer's line ending, flagging files with mixed line endings.
+fn line_ending_status_line_data(endings: Option<LineEndings>) -> StatusLineData {
+    match endings {
+        Some(LineEndings { ending, mixed: true }) => StatusLineData {
+            content: format!(" {} (mixed) ", ending.name()),
+            style: Style::Bold,
+            colors: Colors::Warning,
+        },
+        Some(LineEndings { ending, .. }) => StatusLineData {
+            content: format!(" {} ", ending.name()),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        None => StatusLineData {
+            content: String::new(),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    }
+}
+
+/// Displays the number of steps that can be undone and redone, if any.
+fn undo_status_line_data((undo, redo): (usize, usize)) -> StatusLineData {
+    let content = if undo == 0 && redo == 0 {
+        String::new()
+    } else {
+        format!(" undo: {}  redo: {} ", undo, redo)
+    };
+
+    StatusLineData {
+        content,
+        style: Style::Default,
+        colors: Colors::Focused,
+    }
+}
+
+/// Displays line, word, and character counts (for the buffer, or the selection
+/// if there is one), followed by the cursor's line and column. Empty when the
+/// stats aren't being shown.
+fn stats_status_line_data(stats: Option<TextStats>, cursor: &Position, selected: bool) -> StatusLineData {
+    let content = match stats {
+        Some(stats) => format!(
+            " {}{} lines  {} words  {} chars  {}:{} ",
+            if selected { "selected: " } else { "" },
+            stats.lines,
+            stats.words,
+            stats.characters,
+            cursor.line + 1,
+            cursor.offset + 1
+        ),
+        None => String::new(),
+    };
+
+    StatusLineData {
+        content,
+        style: Style::Default,
+        colors: Colors::Focused,
+    }
+}
+
 fn git_status_line_data(repo: &Option<Repository>, path: &Option<PathBuf>) -> StatusLineData {
     // Build a display value for the current buffer's git status.
     let mut content = String::new();
@@ -86,7 +151,25 @@ fn presentable_status(status: &Status) -> &str {
 #[cfg(test)]
 mod tests {
     use git2;
-    use super::presentable_status;
+    use scribe::buffer::Position;
+    use super::{presentable_status, stats_status_line_data};
+    use crate::view::TextStats;
+
+    #[test]
+    fn stats_status_line_data_shows_counts_and_the_cursor_position() {
+        let stats = TextStats { lines: 2, words: 5, characters: 23 };
+        let cursor = Position{ line: 1, offset: 4 };
+
+        assert_eq!(
+            stats_status_line_data(Some(stats), &cursor, false).content,
+            " 2 lines  5 words  23 chars  2:5 "
+        );
+        assert_eq!(
+            stats_status_line_data(Some(stats), &cursor, true).content,
+            " selected: 2 lines  5 words  23 chars  2:5 "
+        );
+        assert_eq!(stats_status_line_data(None, &cursor, false).content, "");
+    }
 
     #[test]
     pub fn presentable_status_returns_untracked_when_status_is_locally_new() {
@@ -0,0 +1,116 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::models::application::modes::CompletionMode;
+use crate::presenters::status_line::{self, ModeStatus};
+use git2::Repository;
+use pad::PadStr;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::view::{Colors, StatusLineData, Style, View};
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn display(workspace: &mut Workspace, mode: &CompletionMode, view: &mut View, repo: &Option<Repository>, preferences: &Preferences, secondary_cursors: &[Position]) -> Result<()> {
+    let mode_status = ModeStatus::new(vec![
+        StatusLineData {
+            content: " INSERT ".to_string(),
+            style: Style::Default,
+            colors: Colors::Insert,
+        }
+    ]);
+    let status_line = status_line::build(mode_status, workspace, view, repo, preferences)?;
+
+    let mut presenter = view.build_presenter()?;
+    presenter.set_secondary_cursors(secondary_cursors);
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+
+    // Draw the visible set of tokens to the terminal.
+    presenter.print_buffer(buf, &data, None, None)?;
+
+    presenter.print_status_line_with_mode(&status_line.entries, status_line.mode_index);
+
+    // Draw the candidates over the buffer, aligned with the word being typed.
+    if let Some(cursor) = presenter.cursor_position() {
+        let candidates: Vec<String> = mode.candidates().map(|c| format!(" {} ", c)).collect();
+        let width = candidates.iter().map(|c| c.graphemes(true).count()).max().unwrap_or(0);
+        let origin = popup_origin(
+            &cursor,
+            mode.prefix.graphemes(true).count() + 1,
+            candidates.len(),
+            width,
+            presenter.width(),
+            presenter.height() - 1,
+        );
+
+        for (index, candidate) in candidates.iter().enumerate() {
+            let line = origin.line + index;
+            if line >= presenter.height() - 1 {
+                break;
+            }
+            let colors = if index == mode.selected_index() {
+                Colors::Inverted
+            } else {
+                Colors::Focused
+            };
+            let content = candidate
+                .pad_to_width(width)
+                .graphemes(true)
+                .take(presenter.width() - origin.offset)
+                .collect::<String>();
+
+            presenter.print(&Position{ line, offset: origin.offset }, Style::Default, colors, content);
+        }
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
+
+/// Places a popup below the cursor (or above it, if there isn't room), with
+/// its content aligned with the start of the word preceding the cursor (the
+/// indent being the number of columns between the two). Popups are shifted
+/// left to keep them from running off the edge of the screen.
+fn popup_origin(cursor: &Position, indent: usize, height: usize, width: usize, screen_width: usize, screen_height: usize) -> Position {
+    let line = if cursor.line + 1 + height <= screen_height || cursor.line < height {
+        cursor.line + 1
+    } else {
+        cursor.line - height
+    };
+    let offset = cursor.offset
+        .saturating_sub(indent)
+        .min(screen_width.saturating_sub(width));
+
+    Position{ line, offset }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use super::popup_origin;
+
+    #[test]
+    fn popup_origin_aligns_the_popup_below_the_word() {
+        assert_eq!(
+            popup_origin(&Position{ line: 2, offset: 10 }, 4, 3, 8, 80, 20),
+            Position{ line: 3, offset: 6 }
+        );
+    }
+
+    #[test]
+    fn popup_origin_places_the_popup_above_the_cursor_when_there_is_no_room_below() {
+        assert_eq!(
+            popup_origin(&Position{ line: 18, offset: 10 }, 4, 3, 8, 80, 20),
+            Position{ line: 15, offset: 6 }
+        );
+    }
+
+    #[test]
+    fn popup_origin_keeps_the_popup_on_the_screen() {
+        assert_eq!(
+            popup_origin(&Position{ line: 2, offset: 78 }, 4, 3, 8, 80, 20),
+            Position{ line: 3, offset: 72 }
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use crate::errors::*;
+use pad::PadStr;
 use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::ConfirmMode;
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
+pub fn display(workspace: &mut Workspace, mode: &ConfirmMode, view: &mut View) -> Result<()> {
     let mut presenter = view.build_presenter()?;
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let data = buf.data();
@@ -10,11 +13,27 @@ pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
     // Draw the visible set of tokens to the terminal.
     presenter.print_buffer(buf, &data, None, None)?;
 
-    // Draw the status line as a search prompt.
-    let confirmation = "Are you sure? (y/n)".to_string();
+    // List the choices above the status line, marking the default.
+    let width = presenter.width();
+    let first_line = presenter.height().saturating_sub(mode.choices.len() + 1);
+    for (index, choice) in mode.choices.iter().enumerate() {
+        let (content, style) = if mode.default == Some(index) {
+            (format!(" {}  {} (default)", choice.key, choice.label), Style::Bold)
+        } else {
+            (format!(" {}  {}", choice.key, choice.label), Style::Default)
+        };
+        presenter.print(
+            &Position{ line: first_line + index, offset: 0 },
+            style,
+            Colors::Focused,
+            content.pad_to_width(width)
+        );
+    }
+
+    // Draw the status line as a confirmation prompt.
     presenter.print_status_line(&[
         StatusLineData {
-            content: confirmation,
+            content: mode.prompt.clone(),
             style: Style::Bold,
             colors: Colors::Warning,
         }
@@ -0,0 +1,116 @@
+use crate::errors::*;
+use crate::models::application::modes::{DiffMode, DiffSide};
+use crate::util::diff::RowKind;
+use scribe::buffer::Position;
+use crate::view::{Colors, RGBColor, StatusLineData, Style, View};
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn display(mode: &mut DiffMode, view: &mut View, tab_width: usize) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Split the screen into two columns around a divider, leaving a row for the status line.
+    let visible_height = presenter.height().saturating_sub(1);
+    let column_width = presenter.width().saturating_sub(1) / 2;
+    mode.scroll_to_cursor(visible_height);
+
+    let right_width = presenter.width().saturating_sub(column_width + 1);
+    for (index, row) in mode.rows.iter().skip(mode.scroll_offset).take(visible_height).enumerate() {
+        let on_cursor_row = mode.scroll_offset + index == mode.cursor;
+        let (left_color, right_color) = match row.kind {
+            RowKind::Unchanged => (None, None),
+            RowKind::Added => (None, Some(RGBColor(0, 200, 0))),
+            RowKind::Removed => (Some(RGBColor(220, 0, 0)), None),
+            RowKind::Changed => (Some(RGBColor(220, 180, 0)), Some(RGBColor(220, 180, 0))),
+        };
+        let style = if on_cursor_row { Style::Bold } else { Style::Default };
+        let columns = [
+            (0, column_cells(&mode.left, row.left, left_color, on_cursor_row, column_width, tab_width)),
+            (column_width + 1, column_cells(&mode.right, row.right, right_color, on_cursor_row, right_width, tab_width)),
+        ];
+
+        for (column_offset, cells) in columns.iter() {
+            for (offset, (content, colors)) in cells.iter().enumerate() {
+                let position = Position { line: index, offset: column_offset + offset };
+                presenter.print(&position, style, *colors, content.clone());
+            }
+        }
+        presenter.print(&Position { line: index, offset: column_width }, Style::Default, Colors::Focused, "│");
+    }
+
+    let hunks = mode.hunks();
+    let hunk_position = match hunks.iter().rposition(|&hunk| hunk <= mode.cursor) {
+        Some(index) => format!(" hunk {}/{}", index + 1, hunks.len()),
+        None => format!(" {} hunks", hunks.len()),
+    };
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: format!(" {} ", mode),
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        StatusLineData {
+            content: format!(" {} │ {}", mode.left.title, mode.right.title),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        StatusLineData {
+            content: format!("{} {}/{} ", hunk_position, mode.cursor + 1, mode.rows.len()),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+    presenter.set_cursor(None);
+    presenter.present();
+
+    Ok(())
+}
+
+/// The cells making up one side of a row, starting with a line number gutter
+/// and truncated to the column width. Lines missing from this side are drawn
+/// as a filler row.
+fn column_cells(
+    side: &DiffSide,
+    line: Option<usize>,
+    color: Option<RGBColor>,
+    on_cursor_row: bool,
+    width: usize,
+    tab_width: usize,
+) -> Vec<(String, Colors)> {
+    let line = match line.and_then(|line| side.lines.get(line).map(|content| (line, content))) {
+        Some(line) => line,
+        None => return vec![(String::from("-"), Colors::Focused); width],
+    };
+    let colors = match (color, on_cursor_row) {
+        (Some(color), true) => Colors::CustomFocusedForeground(color),
+        (Some(color), false) => Colors::CustomForeground(color),
+        (None, true) => Colors::Focused,
+        (None, false) => Colors::Default,
+    };
+    let gutter_width = side.lines.len().to_string().len() + 1;
+    let mut cells: Vec<(String, Colors)> = format!("{:>width$} ", line.0 + 1, width = gutter_width - 1)
+        .chars()
+        .map(|character| (character.to_string(), Colors::Focused))
+        .collect();
+
+    // Expand tabs, so that both columns line up.
+    for grapheme in line.1.graphemes(true) {
+        if grapheme == "\t" {
+            let width = tab_width - (cells.len() - gutter_width) % tab_width;
+            for _ in 0..width {
+                cells.push((String::from(" "), colors));
+            }
+        } else {
+            cells.push((grapheme.to_string(), colors));
+        }
+    }
+
+    // Extend the cursor row's background across the column.
+    if on_cursor_row {
+        while cells.len() < width {
+            cells.push((String::from(" "), colors));
+        }
+    }
+    cells.truncate(width);
+
+    cells
+}
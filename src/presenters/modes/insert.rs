@@ -1,25 +1,31 @@
 use crate::errors::*;
-use crate::presenters::current_buffer_status_line_data;
+use crate::models::application::Preferences;
+use crate::presenters::status_line::{self, ModeStatus};
+use git2::Repository;
 use scribe::Workspace;
+use scribe::buffer::Position;
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
+pub fn display(workspace: &mut Workspace, view: &mut View, repo: &Option<Repository>, preferences: &Preferences, secondary_cursors: &[Position]) -> Result<()> {
+    let mode_status = ModeStatus::new(vec![
+        StatusLineData {
+            content: " INSERT ".to_string(),
+            style: Style::Default,
+            colors: Colors::Insert,
+        }
+    ]);
+    let status_line = status_line::build(mode_status, workspace, view, repo, preferences)?;
+
     let mut presenter = view.build_presenter()?;
-    let buffer_status = current_buffer_status_line_data(workspace);
+    presenter.set_secondary_cursors(secondary_cursors);
+    presenter.set_show_whitespace(preferences.show_whitespace());
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let data = buf.data();
 
     // Draw the visible set of tokens to the terminal.
     presenter.print_buffer(buf, &data, None, None)?;
 
-    presenter.print_status_line(&[
-        StatusLineData {
-            content: " INSERT ".to_string(),
-            style: Style::Default,
-            colors: Colors::Insert,
-        },
-        buffer_status
-    ]);
+    presenter.print_status_line_with_mode(&status_line.entries, status_line.mode_index);
 
     // Render the changes to the screen.
     presenter.present();
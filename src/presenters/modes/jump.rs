@@ -5,14 +5,19 @@ use crate::models::application::modes::JumpMode;
 use crate::view::{Colors, StatusLineData, Style, View};
 
 pub fn display(workspace: &mut Workspace, mode: &mut JumpMode, view: &mut View) -> Result<()> {
-    let mut presenter = view.build_presenter()?;
     let buffer_status = current_buffer_status_line_data(workspace);
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let data = buf.data();
 
+    // Find the visible targets with a throwaway render, so that
+    // they can be labelled before they're drawn to the terminal.
+    mode.reset_display();
+    view.build_presenter()?.print_buffer(buf, &data, None, Some(&mut *mode))?;
+    mode.assign_labels();
     mode.reset_display();
 
     // Draw the visible set of tokens to the terminal.
+    let mut presenter = view.build_presenter()?;
     presenter.print_buffer(buf, &data, None, Some(mode))?;
 
     presenter.print_status_line(&[
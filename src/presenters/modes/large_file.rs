@@ -0,0 +1,96 @@
+use crate::errors::*;
+use crate::models::application::modes::LargeFileMode;
+use scribe::buffer::Position;
+use crate::view::{Colors, StatusLineData, Style, View};
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn display(mode: &mut LargeFileMode, view: &mut View, tab_width: usize) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Only the visible lines are read from disk, leaving a row for the status line.
+    let visible_height = presenter.height().saturating_sub(1);
+    mode.scroll_to_cursor(visible_height);
+    let lines = mode.file.lines(mode.scroll_offset, visible_height)?;
+    let gutter_width = mode.file.line_count().to_string().len() + 1;
+    let match_width = mode.query.as_ref().map(|query| query.graphemes(true).count()).unwrap_or(0);
+    let mut cursor = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let line_number = mode.scroll_offset + index;
+        let on_cursor_line = line_number == mode.cursor.line;
+        let colors = if on_cursor_line { Colors::Focused } else { Colors::Default };
+        presenter.print(
+            &Position { line: index, offset: 0 },
+            if on_cursor_line { Style::Bold } else { Style::Default },
+            Colors::Focused,
+            format!("{:>width$} ", line_number + 1, width = gutter_width - 1)
+        );
+
+        // Expand tabs, highlighting the current search match.
+        let mut offset = gutter_width;
+        for (grapheme_index, grapheme) in line.graphemes(true).enumerate() {
+            if on_cursor_line && grapheme_index == mode.cursor.offset {
+                cursor = Some(Position { line: index, offset });
+            }
+            let highlighted = on_cursor_line &&
+                grapheme_index >= mode.cursor.offset &&
+                grapheme_index < mode.cursor.offset + match_width;
+            let style = if highlighted { Style::Inverted } else { Style::Default };
+            let (content, width) = if grapheme == "\t" {
+                (" ", tab_width - (offset - gutter_width) % tab_width)
+            } else {
+                (grapheme, 1)
+            };
+            for _ in 0..width {
+                if offset < presenter.width() {
+                    presenter.print(&Position { line: index, offset }, style, colors, content.to_string());
+                }
+                offset += 1;
+            }
+        }
+        if on_cursor_line {
+            for blank_offset in offset..presenter.width() {
+                presenter.print(&Position { line: index, offset: blank_offset }, Style::Default, colors, " ");
+            }
+            cursor = cursor.or(Some(Position { line: index, offset: gutter_width }));
+        }
+    }
+
+    // The status line doubles as a prompt while a search query is typed.
+    let line = presenter.height() - 1;
+    if let Some(ref input) = mode.input {
+        let prompt = format!("Search: {}", input);
+        let prompt_length = prompt.graphemes(true).count();
+        presenter.print_status_line(&[
+            StatusLineData {
+                content: prompt,
+                style: Style::Default,
+                colors: Colors::Default,
+            }
+        ]);
+        presenter.set_cursor(Some(Position { line, offset: prompt_length }));
+    } else {
+        presenter.print_status_line(&[
+            StatusLineData {
+                content: format!(" {} ", mode),
+                style: Style::Default,
+                colors: Colors::Inverted,
+            },
+            StatusLineData {
+                content: format!(" {} [read-only]", mode.file.path().to_string_lossy()),
+                style: Style::Default,
+                colors: Colors::Focused,
+            },
+            StatusLineData {
+                content: format!(" {}/{} ", mode.cursor.line + 1, mode.file.line_count()),
+                style: Style::Default,
+                colors: Colors::Focused,
+            },
+        ]);
+        presenter.set_cursor(cursor);
+    }
+
+    presenter.present();
+
+    Ok(())
+}
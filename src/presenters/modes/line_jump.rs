@@ -1,7 +1,7 @@
 use crate::errors::*;
 use scribe::Workspace;
 use scribe::buffer::Position;
-use crate::models::application::modes::LineJumpMode;
+use crate::models::application::modes::{LineJumpMode, LineTarget};
 use crate::view::{Colors, StatusLineData, Style, View};
 
 pub fn display(workspace: &mut Workspace, mode: &LineJumpMode, view: &mut View) -> Result<()> {
@@ -10,6 +10,15 @@ pub fn display(workspace: &mut Workspace, mode: &LineJumpMode, view: &mut View)
     let data = buf.data();
     presenter.print_buffer(buf, &data, None, None)?;
 
+    // Relative offsets and percentages are previewed as the line they resolve to.
+    let preview = match mode.target() {
+        Some(LineTarget::Absolute(_)) | None => String::new(),
+        Some(_) => mode
+            .target_line(buf.cursor.line, buf.line_count())
+            .map(|line| format!(" (line {})", line + 1))
+            .unwrap_or_default(),
+    };
+
     // Draw the status line as an input prompt.
     let input_prompt = format!("Go to line: {}", mode.input);
     let input_prompt_len = input_prompt.len();
@@ -18,7 +27,12 @@ pub fn display(workspace: &mut Workspace, mode: &LineJumpMode, view: &mut View)
             content: input_prompt,
             style: Style::Default,
             colors: Colors::Default,
-        }
+        },
+        StatusLineData {
+            content: preview,
+            style: Style::Default,
+            colors: Colors::Default,
+        },
     ]);
 
     // Move the cursor to the end of the search query input.
@@ -0,0 +1,40 @@
+use crate::errors::*;
+use crate::models::application::modes::{MacroAction, MacroMode};
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &MacroMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Draw the visible set of tokens to the terminal.
+    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    presenter.print_buffer(buffer, &data, None, None)?;
+
+    // Draw the status line as an input prompt.
+    let input_prompt = match mode.action {
+        MacroAction::Record => String::from("Record macro: "),
+        MacroAction::Play => String::from("Play macro: @"),
+    };
+    let input_prompt_len = input_prompt.chars().count();
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: input_prompt,
+            style: Style::Default,
+            colors: Colors::Default,
+        }
+    ]);
+
+    // Move the cursor to the end of the prompt.
+    let cursor_line = presenter.height() - 1;
+    presenter.set_cursor(Some(Position {
+        line: cursor_line,
+        offset: input_prompt_len,
+    }));
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -1,10 +1,22 @@
+pub mod completion;
 pub mod confirm;
+pub mod diff;
 pub mod insert;
 pub mod jump;
+pub mod large_file;
 pub mod line_jump;
+pub mod macros;
+pub mod mark;
+pub mod operator;
+pub mod pane;
 pub mod path;
+pub mod recent;
+pub mod register;
+pub mod replace;
 pub mod normal;
 pub mod search;
 pub mod search_select;
 pub mod select;
+pub mod select_block;
 pub mod select_line;
+pub mod shell;
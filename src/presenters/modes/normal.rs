@@ -1,36 +1,88 @@
 use crate::errors::*;
+use crate::models::application::Preferences;
 use scribe::Workspace;
-use scribe::buffer::Position;
-use crate::presenters::{current_buffer_status_line_data, git_status_line_data};
+use scribe::buffer::{Buffer, Position, Range};
+use crate::presenters::status_line::{self, ModeStatus};
+use crate::util::{git, token};
 use git2::Repository;
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, view: &mut View, repo: &Option<Repository>) -> Result<()> {
-    let mut presenter = view.build_presenter()?;
-    let buffer_status = current_buffer_status_line_data(workspace);
+// Lines beyond the visible region scanned for word occurrences,
+// so that they're already highlighted when scrolled into view.
+const WORD_HIGHLIGHT_MARGIN: usize = 10;
 
+pub fn display(workspace: &mut Workspace, view: &mut View, repo: &Option<Repository>, preferences: &Preferences, secondary_cursors: &[Position], pending_count: Option<usize>) -> Result<()> {
+    // Git change signs are computed lazily and cached until the buffer is saved.
     if let Some(buf) = workspace.current_buffer() {
+        if view.line_changes(buf)?.is_none() {
+            let changes = match (repo, &buf.path) {
+                (Some(repo), Some(path)) => git::line_changes(repo, path).unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            view.set_line_changes(buf, changes)?;
+        }
+    }
+
+    // Build the status line before the presenter takes hold of the view.
+    let modified = workspace.current_buffer().map(|buf| buf.modified());
+    let status_line = match modified {
+        Some(modified) => {
+            // Determine mode display color based on buffer modification status.
+            let colors = if modified {
+                Colors::Warning
+            } else {
+                Colors::Inverted
+            };
+            let mut entries = vec![
+                StatusLineData {
+                    content: " NORMAL ".to_string(),
+                    style: Style::Default,
+                    colors,
+                }
+            ];
+
+            // Show the count that'll be applied to the next command.
+            if let Some(count) = pending_count {
+                entries.push(StatusLineData {
+                    content: format!(" {} ", count),
+                    style: Style::Bold,
+                    colors: Colors::Focused,
+                });
+            }
+            let mode_status = ModeStatus::new(entries);
+
+            Some(status_line::build(mode_status, workspace, view, repo, preferences)?)
+        }
+        None => None,
+    };
+
+    let word_highlights = match workspace.current_buffer() {
+        Some(buf) if view.word_highlights() => word_occurrences(buf, view)?,
+        _ => Vec::new(),
+    };
+
+    // Merge conflict markers are highlighted, when present.
+    let conflict_markers = match workspace.current_buffer() {
+        Some(buf) => view.conflict_markers(buf),
+        None => Vec::new(),
+    };
+
+    let mut presenter = view.build_presenter()?;
+    presenter.set_relative_line_numbers(preferences.relative_line_numbers());
+    presenter.set_show_whitespace(preferences.show_whitespace());
+    presenter.set_secondary_cursors(secondary_cursors);
+    presenter.set_word_highlights(&word_highlights);
+
+    if let (Some(buf), Some(status_line)) = (workspace.current_buffer(), status_line) {
         // Draw the visible set of tokens to the terminal.
         let data = buf.data();
-        presenter.print_buffer(buf, &data, None, None)?;
-
-        // Determine mode display color based on buffer modification status.
-        let colors = if buf.modified() {
-            Colors::Warning
+        let highlights = if conflict_markers.is_empty() {
+            None
         } else {
-            Colors::Inverted
+            Some(conflict_markers.as_slice())
         };
-
-        // Build the status line mode and buffer title display.
-        presenter.print_status_line(&[
-            StatusLineData {
-                content: " NORMAL ".to_string(),
-                style: Style::Default,
-                colors,
-            },
-            buffer_status,
-            git_status_line_data(&repo, &buf.path)
-        ]);
+        presenter.print_buffer(buf, &data, highlights, None)?;
+        presenter.print_status_line_with_mode(&status_line.entries, status_line.mode_index);
 
         presenter.present();
     } else {
@@ -58,3 +110,19 @@ pub fn display(workspace: &mut Workspace, view: &mut View, repo: &Option<Reposit
 
     Ok(())
 }
+
+/// Finds occurrences of the identifier under the cursor on and around the
+/// visible lines, or none at all if the cursor isn't on an identifier.
+fn word_occurrences(buffer: &Buffer, view: &mut View) -> Result<Vec<Range>> {
+    let identifier = match token::identifier_at_cursor(buffer) {
+        Some(identifier) => identifier,
+        None => return Ok(Vec::new()),
+    };
+    let (first_line, last_line) = view.visible_lines(buffer)?;
+    let lines = (
+        first_line.saturating_sub(WORD_HIGHLIGHT_MARGIN),
+        last_line + WORD_HIGHLIGHT_MARGIN
+    );
+
+    Ok(token::identifier_ranges(&buffer.data(), &identifier, lines))
+}
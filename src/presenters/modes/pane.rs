@@ -0,0 +1,31 @@
+use crate::errors::*;
+use scribe::Workspace;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Draw the visible set of tokens to the terminal.
+    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    presenter.print_buffer(buffer, &data, None, None)?;
+
+    // List the pane commands in the status line.
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: String::from(" PANE "),
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        StatusLineData {
+            content: String::from(" v/s split, h/j/k/l focus, w next, q close, = balance, d/g diff"),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -4,8 +4,12 @@ use scribe::buffer::Position;
 use crate::models::application::modes::PathMode;
 use unicode_segmentation::UnicodeSegmentation;
 use crate::view::{Colors, StatusLineData, Style, View};
+use pad::PadStr;
+
+const MAX_CANDIDATES: usize = 5;
 
 pub fn display(workspace: &mut Workspace, mode: &PathMode, view: &mut View) -> Result<()> {
+    let mut candidate_lines = Vec::new();
     let mut presenter = view.build_presenter()?;
 
     // Draw the visible set of tokens to the terminal.
@@ -36,6 +40,32 @@ pub fn display(workspace: &mut Workspace, mode: &PathMode, view: &mut View) -> R
         },
     ]);
 
+    // Draw completion candidates above the status line, scrolling
+    // through them to keep the selected candidate visible.
+    if let Some((candidates, selected_index)) = mode.candidates() {
+        let visible_count = candidates.len().min(MAX_CANDIDATES).min(presenter.height() - 1);
+        let first_index = selected_index
+            .map(|index| (index + 1).saturating_sub(visible_count))
+            .unwrap_or(0);
+        let first_line = presenter.height() - 1 - visible_count;
+        for (line, index) in (first_line..).zip(first_index..first_index + visible_count) {
+            let (colors, style) = if Some(index) == selected_index {
+                (Colors::Focused, Style::Bold)
+            } else {
+                (Colors::Default, Style::Default)
+            };
+            candidate_lines.push((
+                Position{ line, offset: 0 },
+                style,
+                colors,
+                format!(" {}", candidates[index]).pad_to_width(presenter.width())
+            ));
+        }
+        for (position, style, colors, content) in candidate_lines.iter() {
+            presenter.print(position, *style, *colors, content);
+        }
+    }
+
     // Move the cursor to the end of the search query input.
     {
         let cursor_line = presenter.height() - 1;
@@ -0,0 +1,39 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::RecentMode;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &RecentMode, view: &mut View) -> Result<()> {
+    let data;
+    let mut presenter = view.build_presenter()?;
+
+    // A recent file can be opened without an open buffer.
+    if let Some(buf) = workspace.current_buffer() {
+        data = buf.data();
+        presenter.print_buffer(buf, &data, None, None)?;
+    }
+
+    // Draw the status line as an input prompt.
+    let input_prompt = format!("Recent: {}", mode.input);
+    let input_prompt_len = input_prompt.chars().count();
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: input_prompt,
+            style: Style::Default,
+            colors: Colors::Default,
+        }
+    ]);
+
+    // Move the cursor to the end of the pattern input.
+    let cursor_line = presenter.height() - 1;
+    presenter.set_cursor(Some(Position {
+        line: cursor_line,
+        offset: input_prompt_len,
+    }));
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
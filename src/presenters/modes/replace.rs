@@ -0,0 +1,70 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::ReplaceMode;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &ReplaceMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Draw the visible set of tokens to the terminal, highlighting matches.
+    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let results = mode.search.results.as_ref().filter(|_| !mode.insert_mode());
+    presenter.print_buffer(buffer, &data, results.map(|r| r.as_slice()), None)?;
+
+    let mode_display = format!(" {} ", mode);
+    let query = mode.search.input.as_ref().map(|q| q.as_str()).unwrap_or("");
+    let replace_input = match mode.replacement {
+        Some(ref replacement) => format!(" {} -> {}", query, replacement),
+        None => format!(" {}", query),
+    };
+    let result_display = if mode.insert_mode() {
+        String::new()
+    } else if let Some(ref results) = mode.search.results {
+        format!(
+            "{} of {} matches; replace? (y/n/a)",
+            results.selected_index() + 1,
+            results.len()
+        )
+    } else {
+        String::new()
+    };
+
+    let cursor_offset =
+        mode_display.graphemes(true).count() +
+        replace_input.graphemes(true).count();
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: mode_display,
+            style: Style::Default,
+            colors: Colors::SearchMode,
+        },
+        StatusLineData {
+            content: replace_input,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        StatusLineData {
+            content: result_display,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Move the cursor to the end of the query/replacement input.
+    if mode.insert_mode() {
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset
+        }));
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -1,40 +1,52 @@
 use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::presenters::status_line::{self, ModeStatus};
+use git2::Repository;
 use scribe::Workspace;
 use scribe::buffer::Position;
 use crate::models::application::modes::SearchMode;
 use unicode_segmentation::UnicodeSegmentation;
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, mode: &SearchMode, view: &mut View) -> Result<()> {
-    let mut presenter = view.build_presenter()?;
+// Counting stops here while typing, to keep rendering responsive.
+const MATCH_COUNT_LIMIT: usize = 1000;
 
-    // Draw the visible set of tokens to the terminal.
-    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let data = buffer.data();
-    presenter.print_buffer(buffer, &data, mode.results.as_ref().map(|r| r.as_slice()), None)?;
+pub fn display(workspace: &mut Workspace, mode: &SearchMode, view: &mut View, repo: &Option<Repository>, preferences: &Preferences) -> Result<()> {
+    let data = workspace.current_buffer().ok_or(BUFFER_MISSING)?.data();
 
     let mode_display = format!(" {} ", mode);
     let search_input = format!(
         " {}",
         mode.input.as_ref().unwrap_or(&String::new())
     );
-    let result_display = if mode.insert {
+    let query_present = mode.input.as_ref().map(|q| !q.is_empty()).unwrap_or(false);
+    let mut result_display = if !query_present {
         String::new()
+    } else if mode.insert {
+        // There's no selected result yet; count matches for the query so far.
+        let count = mode.count_matches(&data, MATCH_COUNT_LIMIT);
+        if count == MATCH_COUNT_LIMIT {
+            format!("[{}+]", MATCH_COUNT_LIMIT)
+        } else {
+            format!("[{}]", count)
+        }
     } else if let Some(ref results) = mode.results {
-        if results.len() == 1 {
-            String::from("1 match")
+        if results.is_empty() {
+            String::from("[0/0]")
         } else {
-            format!("{} of {} matches", results.selected_index() + 1, results.len())
+            format!("[{}/{}]", results.selected_index() + 1, results.len())
         }
     } else {
         String::new()
     };
 
-    let cursor_offset =
-        mode_display.graphemes(true).count() +
-        search_input.graphemes(true).count();
+    // Let the user know that smart case is ignoring the query's case.
+    if mode.ignoring_case() {
+        result_display = format!("[ignoring case] {}", result_display);
+    }
 
-    presenter.print_status_line(&[
+    // The query input follows the mode display, wherever it's placed.
+    let mode_status = ModeStatus::new(vec![
         StatusLineData {
             content: mode_display,
             style: Style::Default,
@@ -51,9 +63,22 @@ pub fn display(workspace: &mut Workspace, mode: &SearchMode, view: &mut View) ->
             colors: Colors::Focused,
         },
     ]);
+    let status_line = status_line::build(mode_status, workspace, view, repo, preferences)?;
+
+    let mut presenter = view.build_presenter()?;
+
+    // Draw the visible set of tokens to the terminal.
+    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    presenter.print_buffer(buffer, &data, mode.results.as_ref().map(|r| r.as_slice()), None)?;
+
+    presenter.print_status_line_with_mode(&status_line.entries, status_line.mode_index);
 
     // Move the cursor to the end of the search query input.
-    if mode.insert {
+    if let (true, Some(mode_index)) = (mode.insert, status_line.mode_index) {
+        let cursor_offset = status_line.entries[..mode_index + 2]
+            .iter()
+            .map(|entry| entry.content.graphemes(true).count())
+            .sum();
         let cursor_line = presenter.height() - 1;
         presenter.set_cursor(Some(Position {
             line: cursor_line,
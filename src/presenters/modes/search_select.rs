@@ -40,19 +40,24 @@ pub fn display<T: Display>(workspace: &mut Workspace, mode: &mut SearchSelectMod
                    Colors::Default,
                    &padded_message);
     } else {
-        // Draw the list of search results.
+        // Draw the list of search results, emboldening matched characters.
+        let matched_indices = mode.matched_indices();
         for (line, result) in mode.results().enumerate() {
-            let (content, colors, style) = if line == mode.selected_index() {
-                (format!("> {}", result), Colors::Focused, Style::Bold)
+            let (prefix, colors) = if line == mode.selected_index() {
+                ("> ", Colors::Focused)
             } else {
-                (format!("  {}", result), Colors::Default, Style::Default)
+                ("  ", Colors::Default)
             };
-            padded_content.push((
-                Position{ line, offset: 0 },
-                style,
-                colors,
-                content.pad_to_width(presenter.width())
-            ));
+            let content = format!("{}{}", prefix, result).pad_to_width(presenter.width());
+            let indices = matched_indices.get(line).map(|indices| indices.as_slice()).unwrap_or(&[]);
+
+            let mut offset = 0;
+            for (matched, segment) in highlighted_segments(&content, prefix.len(), indices) {
+                let style = if matched { Style::Bold } else { Style::Default };
+                let width = segment.graphemes(true).count();
+                padded_content.push((Position{ line, offset }, style, colors, segment));
+                offset += width;
+            }
         }
 
         for (position, style, colors, content) in padded_content.iter() {
@@ -97,3 +102,37 @@ pub fn display<T: Display>(workspace: &mut Workspace, mode: &mut SearchSelectMod
 
     Ok(())
 }
+
+/// Splits the content into runs of matched and unmatched characters,
+/// using match indices that are offset by the length of a prefix.
+fn highlighted_segments(content: &str, prefix_length: usize, indices: &[usize]) -> Vec<(bool, String)> {
+    let mut segments: Vec<(bool, String)> = Vec::new();
+    for (index, character) in content.chars().enumerate() {
+        let matched = index >= prefix_length && indices.binary_search(&(index - prefix_length)).is_ok();
+        match segments.last_mut() {
+            Some((segment_matched, segment)) if *segment_matched == matched => segment.push(character),
+            _ => segments.push((matched, character.to_string())),
+        }
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::highlighted_segments;
+
+    #[test]
+    fn highlighted_segments_groups_matched_characters() {
+        assert_eq!(
+            highlighted_segments("> amp.rs", 2, &[0, 1, 4]),
+            vec![
+                (false, String::from("> ")),
+                (true, String::from("am")),
+                (false, String::from("p.")),
+                (true, String::from("r")),
+                (false, String::from("s")),
+            ]
+        );
+    }
+}
@@ -1,28 +1,34 @@
 use crate::errors::*;
+use crate::models::application::Preferences;
 use crate::models::application::modes::SelectMode;
+use crate::presenters::status_line::{self, ModeStatus};
+use git2::Repository;
 use scribe::Workspace;
-use scribe::buffer::Range;
-use crate::presenters::current_buffer_status_line_data;
+use scribe::buffer::{Position, Range};
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, mode: &SelectMode, view: &mut View) -> Result<()> {
-    let mut presenter = view.build_presenter()?;
-    let buffer_status = current_buffer_status_line_data(workspace);
+pub fn display(workspace: &mut Workspace, mode: &SelectMode, view: &mut View, repo: &Option<Repository>, preferences: &Preferences, secondary_cursors: &[Position]) -> Result<()> {
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let selected_range = Range::new(mode.anchor, *buf.cursor.clone());
-    let data = buf.data();
-
-    // Draw the visible set of tokens to the terminal.
-    presenter.print_buffer(buf, &data, Some(&[selected_range]), None)?;
-
-    presenter.print_status_line(&[
+    let mut mode_status = ModeStatus::new(vec![
         StatusLineData {
             content: " SELECT ".to_string(),
             style: Style::Default,
             colors: Colors::SelectMode,
-        },
-        buffer_status
+        }
     ]);
+    mode_status.selection = Some(selected_range.clone());
+    let status_line = status_line::build(mode_status, workspace, view, repo, preferences)?;
+
+    let mut presenter = view.build_presenter()?;
+    presenter.set_secondary_cursors(secondary_cursors);
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+
+    // Draw the visible set of tokens to the terminal.
+    presenter.print_buffer(buf, &data, Some(&[selected_range]), None)?;
+
+    presenter.print_status_line_with_mode(&status_line.entries, status_line.mode_index);
 
     // Render the changes to the screen.
     presenter.present();
@@ -1,27 +1,32 @@
 use crate::errors::*;
+use crate::models::application::Preferences;
 use crate::models::application::modes::SelectLineMode;
+use crate::presenters::status_line::{self, ModeStatus};
+use git2::Repository;
 use scribe::Workspace;
-use crate::presenters::current_buffer_status_line_data;
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, mode: &SelectLineMode, view: &mut View) -> Result<()> {
-    let mut presenter = view.build_presenter()?;
-    let buffer_status = current_buffer_status_line_data(workspace);
+pub fn display(workspace: &mut Workspace, mode: &SelectLineMode, view: &mut View, repo: &Option<Repository>, preferences: &Preferences) -> Result<()> {
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let selected_range = mode.to_range(&*buf.cursor);
-    let data = buf.data();
-
-    // Draw the visible set of tokens to the terminal.
-    presenter.print_buffer(buf, &data, Some(&[selected_range]), None)?;
-
-    presenter.print_status_line(&[
+    let mut mode_status = ModeStatus::new(vec![
         StatusLineData {
             content: " SELECT LINE ".to_string(),
             style: Style::Default,
             colors: Colors::SelectMode,
-        },
-        buffer_status
+        }
     ]);
+    mode_status.selection = Some(selected_range.clone());
+    let status_line = status_line::build(mode_status, workspace, view, repo, preferences)?;
+
+    let mut presenter = view.build_presenter()?;
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+
+    // Draw the visible set of tokens to the terminal.
+    presenter.print_buffer(buf, &data, Some(&[selected_range]), None)?;
+
+    presenter.print_status_line_with_mode(&status_line.entries, status_line.mode_index);
 
     // Render the changes to the screen.
     presenter.present();
@@ -0,0 +1,44 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::{ShellMode, ShellOutput};
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &ShellMode, view: &mut View) -> Result<()> {
+    let data;
+    let mut presenter = view.build_presenter()?;
+
+    // A command can be run without an open buffer.
+    if let Some(buf) = workspace.current_buffer() {
+        data = buf.data();
+        presenter.print_buffer(buf, &data, None, None)?;
+    }
+
+    // Draw the status line as an input prompt.
+    let label = match mode.output {
+        ShellOutput::NewBuffer => "Run",
+        ShellOutput::Selection(_) => "Filter",
+        ShellOutput::Cursor => "Read",
+    };
+    let input_prompt = format!("{}: {}", label, mode.input);
+    let input_prompt_len = input_prompt.chars().count();
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: input_prompt,
+            style: Style::Default,
+            colors: Colors::Default,
+        }
+    ]);
+
+    // Move the cursor to the end of the command input.
+    let cursor_line = presenter.height() - 1;
+    presenter.set_cursor(Some(Position {
+        line: cursor_line,
+        offset: input_prompt_len,
+    }));
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
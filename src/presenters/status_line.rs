@@ -0,0 +1,283 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::presenters::{current_buffer_status_line_data, git_status_line_data, line_ending_status_line_data};
+use crate::presenters::{stats_status_line_data, undo_status_line_data};
+use crate::util::git;
+use crate::util::lsp::Diagnostic;
+use crate::view::{Colors, StatusLineData, Style, TextStats, View};
+use git2::Repository;
+use scribe::Workspace;
+use scribe::buffer::Range;
+
+// Longer diagnostic messages are truncated, to leave room for other segments.
+const DIAGNOSTIC_LENGTH_LIMIT: usize = 60;
+
+const DEFAULT_SEGMENTS: [Segment; 9] = [
+    Segment::Mode,
+    Segment::Path,
+    Segment::Diagnostic,
+    Segment::Undo,
+    Segment::Stats,
+    Segment::LineEnding,
+    Segment::Filetype,
+    Segment::Git,
+    Segment::GitBranch,
+];
+
+/// A section of the status line. Segments are configured by name,
+/// in display order, using the status_line.segments preference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment {
+    Mode,
+    Path,
+    Diagnostic,
+    Undo,
+    Stats,
+    LineEnding,
+    Git,
+    GitBranch,
+    Position,
+    Filetype,
+    Encoding,
+}
+
+impl Segment {
+    fn from_name(name: &str) -> Option<Segment> {
+        match name {
+            "mode" => Some(Segment::Mode),
+            "path" => Some(Segment::Path),
+            "diagnostic" => Some(Segment::Diagnostic),
+            "undo" => Some(Segment::Undo),
+            "stats" => Some(Segment::Stats),
+            "line_ending" => Some(Segment::LineEnding),
+            "git" => Some(Segment::Git),
+            "git_branch" => Some(Segment::GitBranch),
+            "position" => Some(Segment::Position),
+            "filetype" => Some(Segment::Filetype),
+            "encoding" => Some(Segment::Encoding),
+            _ => None,
+        }
+    }
+}
+
+/// The configured segments, in display order. Unrecognized names are skipped.
+pub fn segments(preferences: &Preferences) -> Vec<Segment> {
+    match preferences.status_line_segments() {
+        Some(names) => names.iter().filter_map(|name| Segment::from_name(name)).collect(),
+        None => DEFAULT_SEGMENTS.to_vec(),
+    }
+}
+
+/// Describes any configured segment names that aren't recognized,
+/// so that they can be reported rather than silently ignored.
+pub fn unknown_segments_warning(preferences: &Preferences) -> Option<String> {
+    let unknown_names: Vec<String> = preferences
+        .status_line_segments()?
+        .into_iter()
+        .filter(|name| Segment::from_name(name).is_none())
+        .collect();
+
+    if unknown_names.is_empty() {
+        None
+    } else {
+        Some(format!("Ignoring unknown status line segments: {}", unknown_names.join(", ")))
+    }
+}
+
+/// Mode-specific status line content, around which the configured segments are laid out.
+pub struct ModeStatus {
+    /// Entries displayed in place of the mode segment (e.g. the mode's
+    /// name, followed by the query being entered in search mode).
+    pub entries: Vec<StatusLineData>,
+
+    /// The selected range, whose stats are shown instead of the buffer's.
+    pub selection: Option<Range>,
+}
+
+impl ModeStatus {
+    pub fn new(entries: Vec<StatusLineData>) -> ModeStatus {
+        ModeStatus {
+            entries,
+            selection: None,
+        }
+    }
+}
+
+/// Status line entries built from the configured segments.
+pub struct StatusLine {
+    pub entries: Vec<StatusLineData>,
+
+    /// The index of the first mode entry, if the mode segment is displayed.
+    pub mode_index: Option<usize>,
+}
+
+/// Builds the configured segments for the current buffer. Segments
+/// without anything to display (e.g. undo history for an unchanged
+/// buffer) are kept as empty entries, so that the layout is stable.
+pub fn build(mode: ModeStatus, workspace: &mut Workspace, view: &mut View, repo: &Option<Repository>, preferences: &Preferences) -> Result<StatusLine> {
+    let mut path_data = Some(current_buffer_status_line_data(workspace));
+    let mut mode_entries = Some(mode.entries);
+    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let mut status_line = StatusLine {
+        entries: Vec::new(),
+        mode_index: None,
+    };
+
+    for segment in segments(preferences) {
+        match segment {
+            Segment::Mode => if let Some(entries) = mode_entries.take() {
+                status_line.mode_index = Some(status_line.entries.len());
+                status_line.entries.extend(entries);
+            },
+            Segment::Path => if let Some(mut data) = path_data.take() {
+                if view.read_only(buffer) {
+                    data.content.push_str(" [read-only]");
+                }
+                if let Some((position, count)) = view.pane_position() {
+                    data.content.push_str(&format!(" [pane {}/{}]", position, count));
+                }
+                status_line.entries.push(data);
+            },
+            Segment::Diagnostic => {
+                // Only the cursor line's most severe diagnostic is shown.
+                let content = view
+                    .diagnostics(buffer)
+                    .iter()
+                    .filter(|diagnostic| diagnostic.line == buffer.cursor.line)
+                    .min_by_key(|diagnostic| diagnostic.severity)
+                    .map(diagnostic_summary)
+                    .unwrap_or_default();
+                status_line.entries.push(segment_data(content));
+            }
+            Segment::Undo => status_line.entries.push(
+                undo_status_line_data(view.undo_depth(buffer)?)
+            ),
+            Segment::Stats => {
+                let stats = if !preferences.status_line_stats() {
+                    None
+                } else if let Some(ref selection) = mode.selection {
+                    Some(TextStats::new(&buffer.read(selection).unwrap_or_default()))
+                } else {
+                    Some(view.text_stats(buffer))
+                };
+                status_line.entries.push(
+                    stats_status_line_data(stats, &*buffer.cursor, mode.selection.is_some())
+                );
+            }
+            Segment::LineEnding => status_line.entries.push(
+                line_ending_status_line_data(view.line_endings(buffer))
+            ),
+            Segment::Git => status_line.entries.push(
+                git_status_line_data(repo, &buffer.path)
+            ),
+            Segment::GitBranch => {
+                let content = match (repo, repo.as_ref().and_then(git::head_name)) {
+                    (Some(repo), Some(name)) => {
+                        // The dirty check is throttled, since it scans the working tree.
+                        let dirty = view.git_dirty().unwrap_or_else(|| {
+                            let dirty = git::is_dirty(repo);
+                            view.set_git_dirty(dirty);
+                            dirty
+                        });
+
+                        format!(" {}{} ", name, if dirty { "*" } else { "" })
+                    }
+                    _ => String::new(),
+                };
+                status_line.entries.push(segment_data(content));
+            }
+            Segment::Position => status_line.entries.push(segment_data(
+                format!(" {}:{} ", buffer.cursor.line + 1, buffer.cursor.offset + 1)
+            )),
+            Segment::Filetype => {
+                let filetype = buffer
+                    .syntax_definition
+                    .as_ref()
+                    .map(|syntax| format!(" {} ", syntax.name));
+                status_line.entries.push(segment_data(filetype.unwrap_or_default()));
+            }
+            Segment::Encoding => {
+                // Buffers are only assigned an encoding when it isn't UTF-8.
+                let encoding = match view.encoding(buffer) {
+                    Some(encoding) if encoding.bom => format!(" {} (BOM) ", encoding.name()),
+                    Some(encoding) => format!(" {} ", encoding.name()),
+                    None => String::from(" UTF-8 "),
+                };
+                status_line.entries.push(segment_data(encoding));
+            }
+        }
+    }
+
+    Ok(status_line)
+}
+
+/// The diagnostic's severity and the first line of its message, truncated to fit.
+fn diagnostic_summary(diagnostic: &Diagnostic) -> String {
+    let message = diagnostic.message.lines().next().unwrap_or("");
+    let mut summary: String = message.chars().take(DIAGNOSTIC_LENGTH_LIMIT).collect();
+    if message.chars().count() > DIAGNOSTIC_LENGTH_LIMIT {
+        summary.push('…');
+    }
+
+    format!(" {}: {} ", diagnostic.severity.sign(), summary)
+}
+
+fn segment_data(content: String) -> StatusLineData {
+    StatusLineData {
+        content,
+        style: Style::Default,
+        colors: Colors::Focused,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Segment, diagnostic_summary, segments, unknown_segments_warning};
+    use crate::models::application::Preferences;
+    use crate::util::lsp::{Diagnostic, Severity};
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn segments_defaults_to_the_standard_layout() {
+        assert_eq!(
+            segments(&Preferences::new(None)),
+            vec![
+                Segment::Mode,
+                Segment::Path,
+                Segment::Diagnostic,
+                Segment::Undo,
+                Segment::Stats,
+                Segment::LineEnding,
+                Segment::Filetype,
+                Segment::Git,
+                Segment::GitBranch,
+            ]
+        );
+        assert_eq!(unknown_segments_warning(&Preferences::new(None)), None);
+    }
+
+    #[test]
+    fn segments_uses_the_configured_order_and_skips_unknown_names() {
+        let data = YamlLoader::load_from_str(
+            "status_line:\n  segments:\n    - position\n    - clock\n    - mode\n    - git_branch"
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(segments(&preferences), vec![Segment::Position, Segment::Mode, Segment::GitBranch]);
+        assert_eq!(
+            unknown_segments_warning(&preferences),
+            Some(String::from("Ignoring unknown status line segments: clock"))
+        );
+    }
+
+    #[test]
+    fn diagnostic_summary_uses_the_first_line_of_the_message() {
+        let diagnostic = Diagnostic {
+            line: 0,
+            severity: Severity::Warning,
+            message: format!("{}\nnote: amp", "x".repeat(70)),
+        };
+
+        assert_eq!(diagnostic_summary(&diagnostic), format!(" W: {}… ", "x".repeat(60)));
+    }
+}
@@ -0,0 +1,173 @@
+/// The tokens used to comment out a line in a particular language.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommentTokens {
+    Line(&'static str),
+    Block(&'static str, &'static str),
+}
+
+/// Looks up the comment tokens for the named syntax. Languages without
+/// line comments fall back to wrapping each line in a block comment.
+pub fn tokens(syntax_name: &str) -> Option<CommentTokens> {
+    let tokens = match syntax_name {
+        "C" | "C++" | "C#" | "D" | "Go" | "Groovy" | "Java" | "JavaScript" |
+        "Objective-C" | "Objective-C++" | "Rust" | "Scala" |
+        "Swift" | "TypeScript" | "TypeScriptReact" | "Kotlin" | "SCSS" |
+        "Sass" | "Less" => CommentTokens::Line("//"),
+        "Bourne Again Shell (bash)" | "Shell-Unix-Generic" | "Python" |
+        "Ruby" | "Perl" | "R" | "YAML" | "TOML" | "Makefile" | "Elixir" |
+        "CoffeeScript" | "Crystal" | "Nim" | "Dockerfile" | "fish" |
+        "Git Config" | "Git Ignore" | "NAnt Build File" => CommentTokens::Line("#"),
+        "Haskell" | "Lua" | "SQL" | "Elm" | "Ada" => CommentTokens::Line("--"),
+        "Erlang" | "LaTeX" | "TeX" | "MATLAB" => CommentTokens::Line("%"),
+        "Lisp" | "Clojure" | "Scheme" | "INI" | "Assembly x86 (NASM)" => CommentTokens::Line(";"),
+        "Batch File" => CommentTokens::Line("REM"),
+        "VimL" => CommentTokens::Line("\""),
+        "HTML" | "XML" | "Markdown" => CommentTokens::Block("<!--", "-->"),
+        "CSS" => CommentTokens::Block("/*", "*/"),
+        "OCaml" => CommentTokens::Block("(*", "*)"),
+        _ => return None,
+    };
+
+    Some(tokens)
+}
+
+/// Comments out the specified lines, unless every non-blank line is already
+/// commented, in which case their comments are removed instead. Tokens are
+/// inserted after leading whitespace, and blank lines are left untouched.
+pub fn toggle(lines: &[&str], tokens: CommentTokens) -> Vec<String> {
+    let mut non_blank_lines = lines.iter().filter(|line| !line.trim().is_empty()).peekable();
+    let commented = non_blank_lines.peek().is_some() &&
+        non_blank_lines.all(|line| is_commented(line, tokens));
+
+    lines.iter().map(|line| {
+        if line.trim().is_empty() {
+            line.to_string()
+        } else if commented {
+            uncomment(line, tokens)
+        } else {
+            comment(line, tokens)
+        }
+    }).collect()
+}
+
+fn is_commented(line: &str, tokens: CommentTokens) -> bool {
+    let (_, content) = split_indentation(line);
+    let content = content.trim_end();
+    match tokens {
+        CommentTokens::Line(prefix) => content.starts_with(prefix),
+        CommentTokens::Block(open, close) => {
+            content.len() >= open.len() + close.len() &&
+                content.starts_with(open) &&
+                content.ends_with(close)
+        }
+    }
+}
+
+fn comment(line: &str, tokens: CommentTokens) -> String {
+    let (line, line_ending) = split_line_ending(line);
+    let (indentation, content) = split_indentation(line);
+    match tokens {
+        CommentTokens::Line(prefix) => format!("{}{} {}{}", indentation, prefix, content, line_ending),
+        CommentTokens::Block(open, close) => {
+            format!("{}{} {} {}{}", indentation, open, content.trim_end(), close, line_ending)
+        }
+    }
+}
+
+fn uncomment(line: &str, tokens: CommentTokens) -> String {
+    let (line, line_ending) = split_line_ending(line);
+    let (indentation, content) = split_indentation(line);
+    let content = match tokens {
+        CommentTokens::Line(prefix) => {
+            let content = &content[prefix.len()..];
+            content.strip_prefix(' ').unwrap_or(content).to_string()
+        }
+        CommentTokens::Block(open, close) => {
+            let content = content.trim_end();
+            let content = &content[open.len()..content.len() - close.len()];
+            let content = content.strip_prefix(' ').unwrap_or(content);
+            content.strip_suffix(' ').unwrap_or(content).to_string()
+        }
+    };
+
+    format!("{}{}{}", indentation, content, line_ending)
+}
+
+// Splits the line's leading whitespace from its content. This is the same
+// whitespace that's trimmed when checking whether the line is commented.
+fn split_indentation(line: &str) -> (&str, &str) {
+    let content = line.trim_start();
+    line.split_at(line.len() - content.len())
+}
+
+// Splits off the carriage return ending a CRLF line, so that it's kept.
+fn split_line_ending(line: &str) -> (&str, &str) {
+    match line.strip_suffix('\r') {
+        Some(content) => (content, "\r"),
+        None => (line, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommentTokens, tokens, toggle};
+
+    #[test]
+    fn toggle_comments_lines_after_their_indentation() {
+        assert_eq!(
+            toggle(&["fn amp() {", "    editor();", "", "}"], CommentTokens::Line("//")),
+            vec!["// fn amp() {", "    // editor();", "", "// }"]
+        );
+    }
+
+    #[test]
+    fn toggle_uncomments_lines_when_all_are_commented() {
+        assert_eq!(
+            toggle(&["  # amp", "", "  #editor"], CommentTokens::Line("#")),
+            vec!["  amp", "", "  editor"]
+        );
+    }
+
+    #[test]
+    fn toggle_comments_lines_when_only_some_are_commented() {
+        assert_eq!(
+            toggle(&["# amp", "editor"], CommentTokens::Line("#")),
+            vec!["# # amp", "# editor"]
+        );
+    }
+
+    #[test]
+    fn toggle_wraps_and_unwraps_block_comments() {
+        let tokens = CommentTokens::Block("<!--", "-->");
+        let commented = toggle(&["  <p>amp</p>"], tokens);
+        assert_eq!(commented, vec!["  <!-- <p>amp</p> -->"]);
+
+        let lines: Vec<&str> = commented.iter().map(|line| line.as_str()).collect();
+        assert_eq!(toggle(&lines, tokens), vec!["  <p>amp</p>"]);
+    }
+
+    #[test]
+    fn toggle_treats_all_leading_whitespace_as_indentation() {
+        assert_eq!(
+            toggle(&["\u{a0}# amp"], CommentTokens::Line("#")),
+            vec!["\u{a0}amp"]
+        );
+    }
+
+    #[test]
+    fn toggle_keeps_carriage_returns_on_crlf_lines() {
+        let tokens = CommentTokens::Block("<!--", "-->");
+        let commented = toggle(&["<p>\r"], tokens);
+        assert_eq!(commented, vec!["<!-- <p> -->\r"]);
+
+        let lines: Vec<&str> = commented.iter().map(|line| line.as_str()).collect();
+        assert_eq!(toggle(&lines, tokens), vec!["<p>\r"]);
+        assert_eq!(toggle(&["# amp\r"], CommentTokens::Line("#")), vec!["amp\r"]);
+    }
+
+    #[test]
+    fn tokens_returns_none_for_unknown_syntaxes() {
+        assert_eq!(tokens("Rust"), Some(CommentTokens::Line("//")));
+        assert_eq!(tokens("Plain Text"), None);
+    }
+}
@@ -0,0 +1,137 @@
+use scribe::buffer::Position;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How close to the cursor and how often a candidate appears.
+#[derive(Default)]
+struct Occurrences {
+    distance: Option<usize>,
+    count: usize,
+}
+
+/// The word characters immediately preceding the offset in the line.
+pub fn prefix(line: &str, offset: usize) -> String {
+    let preceding: Vec<&str> = line.graphemes(true).take(offset).collect();
+    let start = preceding
+        .iter()
+        .rposition(|grapheme| !is_word(grapheme))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+
+    preceding[start..].concat()
+}
+
+/// Finds the words that complete the prefix in the current buffer's data and
+/// that of other buffers, without duplicates. Words closest to the cursor come
+/// first, followed by those only found in other buffers; ties are broken by
+/// frequency. The word at the cursor (i.e. the one being typed) is left out.
+pub fn candidates(prefix: &str, data: &str, cursor: &Position, others: &[String]) -> Vec<String> {
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+
+    let mut occurrences: HashMap<&str, Occurrences> = HashMap::new();
+
+    for (line, offset, word) in words(data) {
+        let length = word.graphemes(true).count();
+        if line == cursor.line && offset <= cursor.offset && cursor.offset <= offset + length {
+            continue;
+        }
+
+        if completes(word, prefix) {
+            let distance = if line > cursor.line { line - cursor.line } else { cursor.line - line };
+            let entry = occurrences.entry(word).or_insert_with(Occurrences::default);
+            entry.distance = Some(entry.distance.map_or(distance, |d| d.min(distance)));
+            entry.count += 1;
+        }
+    }
+    for other in others {
+        for (_, _, word) in words(other) {
+            if completes(word, prefix) {
+                occurrences.entry(word).or_insert_with(Occurrences::default).count += 1;
+            }
+        }
+    }
+
+    let mut candidates: Vec<(&str, Occurrences)> = occurrences.into_iter().collect();
+    candidates.sort_by_key(|&(word, ref occurrences)| {
+        (occurrences.distance.unwrap_or(usize::max_value()), Reverse(occurrences.count), word)
+    });
+
+    candidates.into_iter().map(|(word, _)| word.to_string()).collect()
+}
+
+fn completes(word: &str, prefix: &str) -> bool {
+    word.len() > prefix.len() && word.starts_with(prefix)
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// The identifier-like words in the data (i.e. those that don't
+/// start with a digit), along with their lines and offsets.
+fn words(data: &str) -> Vec<(usize, usize, &str)> {
+    let mut words = Vec::new();
+    for (line, content) in data.lines().enumerate() {
+        let mut start = None;
+        let graphemes = content.grapheme_indices(true).chain(Some((content.len(), " ")));
+        for (offset, (index, grapheme)) in graphemes.enumerate() {
+            match (start, is_word(grapheme)) {
+                (None, true) => start = Some((offset, index)),
+                (Some((start_offset, start_index)), false) => {
+                    let word = &content[start_index..index];
+                    if !word.starts_with(|c: char| c.is_ascii_digit()) {
+                        words.push((line, start_offset, word));
+                    }
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use super::{candidates, prefix};
+
+    #[test]
+    fn prefix_returns_the_word_characters_before_the_offset() {
+        assert_eq!(prefix("let x = amp_ed", 14), "amp_ed");
+        assert_eq!(prefix("let x = amp_ed", 11), "amp");
+        assert_eq!(prefix("let x = ", 8), "");
+    }
+
+    #[test]
+    fn candidates_are_unique_and_ranked_by_proximity() {
+        let data = "amplify\nampere\nam\namplify\n";
+        let cursor = Position{ line: 2, offset: 2 };
+
+        assert_eq!(
+            candidates("am", data, &cursor, &[]),
+            vec!["amplify", "ampere"]
+        );
+    }
+
+    #[test]
+    fn candidates_from_other_buffers_follow_and_are_ranked_by_frequency() {
+        let data = "amp\namperage\n";
+        let cursor = Position{ line: 0, offset: 3 };
+        let others = vec![String::from("ample amplify 2amps\n"), String::from("amplify\n")];
+
+        assert_eq!(
+            candidates("amp", data, &cursor, &others),
+            vec!["amperage", "amplify", "ample"]
+        );
+    }
+
+    #[test]
+    fn candidates_are_not_offered_without_a_prefix() {
+        assert!(candidates("", "amp editor\n", &Position{ line: 1, offset: 0 }, &[]).is_empty());
+    }
+}
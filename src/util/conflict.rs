@@ -0,0 +1,116 @@
+use scribe::buffer::{Position, Range};
+
+const START_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const END_MARKER: &str = ">>>>>>>";
+
+/// The marker lines of a git merge conflict, including
+/// the base marker found in diff3-style conflicts.
+#[derive(Debug, PartialEq)]
+pub struct Conflict {
+    pub start: usize,
+    pub base: Option<usize>,
+    pub separator: usize,
+    pub end: usize,
+}
+
+impl Conflict {
+    /// Whether the conflict, including its markers, spans the specified line.
+    pub fn includes(&self, line: usize) -> bool {
+        self.start <= line && line <= self.end
+    }
+
+    /// The lines holding the current branch's side of the conflict.
+    pub fn ours<'a>(&self, lines: &[&'a str]) -> Vec<&'a str> {
+        lines[self.start + 1..self.base.unwrap_or(self.separator)].to_vec()
+    }
+
+    /// The lines holding the incoming branch's side of the conflict.
+    pub fn theirs<'a>(&self, lines: &[&'a str]) -> Vec<&'a str> {
+        lines[self.separator + 1..self.end].to_vec()
+    }
+
+    fn marker_lines(&self) -> Vec<usize> {
+        let mut lines = vec![self.start];
+        lines.extend(self.base);
+        lines.push(self.separator);
+        lines.push(self.end);
+
+        lines
+    }
+}
+
+/// Finds complete merge conflicts in the data. Incomplete
+/// or malformed sets of markers are ignored.
+pub fn conflicts(data: &str) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut start = None;
+    let mut base = None;
+    let mut separator = None;
+
+    for (line_number, line) in data.lines().enumerate() {
+        if line.starts_with(START_MARKER) {
+            start = Some(line_number);
+            base = None;
+            separator = None;
+        } else if line.starts_with(BASE_MARKER) && start.is_some() && separator.is_none() {
+            base = Some(line_number);
+        } else if line == SEPARATOR_MARKER && start.is_some() {
+            separator = Some(line_number);
+        } else if line.starts_with(END_MARKER) {
+            if let (Some(start), Some(separator)) = (start, separator) {
+                conflicts.push(Conflict { start, base, separator, end: line_number });
+            }
+            start = None;
+            base = None;
+            separator = None;
+        }
+    }
+
+    conflicts
+}
+
+/// Builds ranges covering the marker lines of all conflicts in the data.
+pub fn marker_ranges(data: &str) -> Vec<Range> {
+    // Avoid scanning data that can't contain conflicts.
+    if !data.contains(START_MARKER) {
+        return Vec::new();
+    }
+
+    let lines: Vec<&str> = data.lines().collect();
+    conflicts(data)
+        .iter()
+        .flat_map(|conflict| conflict.marker_lines())
+        .map(|line| Range::new(
+            Position{ line, offset: 0 },
+            Position{ line, offset: lines[line].chars().count() }
+        ))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Conflict;
+
+    #[test]
+    fn conflicts_finds_complete_conflicts() {
+        let data = "amp\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n<<<<<<< HEAD\nincomplete\n";
+
+        assert_eq!(
+            super::conflicts(data),
+            vec![Conflict{ start: 1, base: None, separator: 3, end: 5 }]
+        );
+    }
+
+    #[test]
+    fn conflicts_supports_diff3_base_sections() {
+        let data = "<<<<<<< HEAD\nours\n||||||| base\noriginal\n=======\ntheirs\n>>>>>>> branch\n";
+        let lines: Vec<&str> = data.lines().collect();
+        let conflicts = super::conflicts(data);
+
+        assert_eq!(conflicts, vec![Conflict{ start: 0, base: Some(2), separator: 4, end: 6 }]);
+        assert_eq!(conflicts[0].ours(&lines), vec!["ours"]);
+        assert_eq!(conflicts[0].theirs(&lines), vec!["theirs"]);
+    }
+}
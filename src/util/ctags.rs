@@ -0,0 +1,206 @@
+use crate::errors::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The name of the tags file, read from the workspace root.
+pub const TAGS_FILE: &str = "tags";
+
+/// A definition listed in a ctags index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+
+    /// The path of the file containing the definition,
+    /// relative to the directory containing the tags file.
+    pub path: PathBuf,
+    pub address: Address,
+
+    /// The kind of definition (e.g. "f" or "function"), if specified.
+    pub kind: Option<String>,
+}
+
+/// Where a tag's definition can be found within its file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Address {
+    /// A zero-based line number.
+    Line(usize),
+
+    /// A search pattern, optionally anchored to the start (^) and end ($) of a line.
+    Pattern(String),
+}
+
+impl Tag {
+    /// Finds the (zero-based) line containing the tag's definition in the
+    /// file's content, returning None if it's no longer there (i.e. the tag
+    /// is stale).
+    pub fn line(&self, data: &str) -> Option<usize> {
+        match self.address {
+            Address::Line(line) => if line < data.lines().count() {
+                Some(line)
+            } else {
+                None
+            },
+            Address::Pattern(ref pattern) => {
+                data.lines().position(|line| pattern_matches(pattern, line))
+            }
+        }
+    }
+}
+
+/// Reads the tags file in the specified directory, returning
+/// the definitions whose names exactly match the identifier.
+pub fn definitions(root: &Path, identifier: &str) -> Result<Vec<Tag>> {
+    let path = root.join(TAGS_FILE);
+    if !path.exists() {
+        bail!("No tags file found at {}; generate one using ctags", path.display());
+    }
+    let content = fs::read_to_string(&path)
+        .chain_err(|| format!("Couldn't read the tags file at {}", path.display()))?;
+
+    Ok(parse(&content).into_iter().filter(|tag| tag.name == identifier).collect())
+}
+
+/// Parses the content of a tags file, skipping
+/// pseudo-tags (e.g. !_TAG_FILE_FORMAT) and malformed lines.
+pub fn parse(content: &str) -> Vec<Tag> {
+    content.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<Tag> {
+    if line.starts_with("!_TAG_") {
+        return None;
+    }
+
+    let mut fields = line.splitn(3, '\t');
+    let name = fields.next()?;
+    let path = fields.next()?;
+    let (address, remainder) = parse_address(fields.next()?)?;
+
+    // Extension fields follow the address, after a ;" separator. The kind
+    // is either the first field without a name, or named explicitly.
+    let kind = if remainder.starts_with(";\"") {
+        remainder[2..].split('\t').find_map(|field| {
+            if field.starts_with("kind:") {
+                Some(field[5..].to_string())
+            } else if !field.is_empty() && !field.contains(':') {
+                Some(field.to_string())
+            } else {
+                None
+            }
+        })
+    } else {
+        None
+    };
+
+    Some(Tag {
+        name: name.to_string(),
+        path: PathBuf::from(path),
+        address,
+        kind,
+    })
+}
+
+/// Parses a line number or delimited search pattern from the start of the
+/// field, returning it along with whatever follows it.
+fn parse_address(field: &str) -> Option<(Address, &str)> {
+    let delimiter = field.chars().next()?;
+    if delimiter != '/' && delimiter != '?' {
+        let digit_count = field.chars().take_while(|c| c.is_ascii_digit()).count();
+        let line = field[..digit_count].parse::<usize>().ok()?;
+
+        // Line numbers are one-based.
+        return Some((Address::Line(line.checked_sub(1)?), &field[digit_count..]));
+    }
+
+    let mut pattern = String::new();
+    let mut chars = field.char_indices().skip(1);
+    while let Some((_, c)) = chars.next() {
+        if c == '\\' {
+            // Delimiters and backslashes are escaped within patterns.
+            match chars.next() {
+                Some((_, escaped)) if escaped == delimiter || escaped == '\\' => pattern.push(escaped),
+                Some((_, escaped)) => {
+                    pattern.push('\\');
+                    pattern.push(escaped);
+                }
+                None => return None,
+            }
+        } else if c == delimiter {
+            return Some((Address::Pattern(pattern), chars.next().map(|(i, _)| &field[i..]).unwrap_or("")));
+        } else {
+            pattern.push(c);
+        }
+    }
+
+    None
+}
+
+/// Whether the line matches the tag pattern, which is otherwise taken
+/// literally (ctags escapes anything that'd be treated specially).
+fn pattern_matches(pattern: &str, line: &str) -> bool {
+    let start_anchored = pattern.starts_with('^');
+    let end_anchored = pattern.ends_with('$') && !pattern.ends_with("\\$");
+    let mut literal = pattern;
+    if start_anchored {
+        literal = &literal[1..];
+    }
+    if end_anchored {
+        literal = &literal[..literal.len() - 1];
+    }
+
+    match (start_anchored, end_anchored) {
+        (true, true) => line == literal,
+        (true, false) => line.starts_with(literal),
+        (false, true) => line.ends_with(literal),
+        (false, false) => line.contains(literal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Address, Tag, parse};
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_reads_patterns_line_numbers_and_kinds() {
+        let content = "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+                       amp\tsrc/amp.rs\t/^fn amp() {$/;\"\tf\tline:3\n\
+                       editor\tsrc/editor.rs\t12;\"\tkind:struct\n\
+                       malformed\n";
+
+        assert_eq!(
+            parse(content),
+            vec![
+                Tag {
+                    name: String::from("amp"),
+                    path: PathBuf::from("src/amp.rs"),
+                    address: Address::Pattern(String::from("^fn amp() {$")),
+                    kind: Some(String::from("f")),
+                },
+                Tag {
+                    name: String::from("editor"),
+                    path: PathBuf::from("src/editor.rs"),
+                    address: Address::Line(11),
+                    kind: Some(String::from("struct")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unescapes_patterns() {
+        let tags = parse("amp\tamp.js\t/^  path = \\/amp\\\\\\/;$/\n");
+
+        assert_eq!(tags[0].address, Address::Pattern(String::from("^  path = /amp\\/;$")));
+    }
+
+    #[test]
+    fn line_finds_the_definition_or_reports_a_stale_tag() {
+        let tags = parse("amp\tamp.rs\t/^fn amp() {$/\neditor\tamp.rs\t9\n");
+        let data = "// amp\nfn amp() {\n}\n";
+
+        assert_eq!(tags[0].line(data), Some(1));
+        assert_eq!(tags[0].line("fn amp(x: usize) {\n}\n"), None);
+        assert_eq!(tags[1].line(data), None);
+    }
+}
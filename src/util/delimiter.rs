@@ -0,0 +1,295 @@
+use scribe::buffer::{Position, Range};
+
+const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+/// Angle brackets are left out when matching brackets, as
+/// they're just as likely to be comparison operators.
+const MATCHED_BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+/// Finds the delimiter pairs (brackets or quotes) enclosing the specified
+/// position, ordered from the innermost outwards. Each pair is described by
+/// the positions of its opening and closing delimiters. A position on a
+/// delimiter is considered to be enclosed by its pair. Brackets can span
+/// lines and account for nesting; quotes are matched on the current line.
+pub fn enclosing_pairs(data: &str, position: &Position) -> Vec<(Position, Position)> {
+    let mut positions = Vec::new();
+    let mut chars = Vec::new();
+    for (line, content) in data.lines().enumerate() {
+        for (offset, c) in content.chars().chain(Some('\n')).enumerate() {
+            positions.push(Position{ line, offset });
+            chars.push(c);
+        }
+    }
+    let index = match positions.iter().position(|p| p == position) {
+        Some(index) => index,
+        None => return Vec::new(),
+    };
+
+    let mut pairs = Vec::new();
+    for &(opener, closer) in BRACKETS.iter() {
+        let mut opening = if chars[index] == opener {
+            Some(index)
+        } else if chars[index] == closer {
+            index.checked_sub(1).and_then(|i| find_opener(&chars, i, opener, closer))
+        } else {
+            find_opener(&chars, index, opener, closer)
+        };
+
+        // Collect this bracket type's enclosing pairs, moving outwards.
+        while let Some(start) = opening {
+            match find_closer(&chars, start, opener, closer) {
+                Some(end) => pairs.push((start, end)),
+                None => break,
+            }
+            opening = start.checked_sub(1).and_then(|i| find_opener(&chars, i, opener, closer));
+        }
+    }
+    pairs.extend(quote_pairs(&chars, index));
+
+    // The innermost pair is the one that opens closest to the position.
+    pairs.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    pairs.into_iter().map(|(start, end)| (positions[start], positions[end])).collect()
+}
+
+/// Finds the bracket matching the one at the specified position or, if there
+/// isn't one there, the next bracket on the same line. Brackets within the
+/// ignored ranges (e.g. strings and comments) are skipped, unless the
+/// starting bracket is itself in one of them. Ignored ranges must be in order;
+/// they're only read as far as the search reaches.
+pub fn matching_bracket<I>(data: &str, position: &Position, ignored: I) -> Option<Position>
+    where I: Iterator<Item=Range>
+{
+    let mut positions = Vec::new();
+    let mut chars = Vec::new();
+    for (line, content) in data.lines().enumerate() {
+        for (offset, c) in content.chars().chain(Some('\n')).enumerate() {
+            positions.push(Position{ line, offset });
+            chars.push(c);
+        }
+    }
+    let mut ignored = IgnoredRanges::new(ignored);
+    let bracket = |index: usize| MATCHED_BRACKETS
+        .iter()
+        .find(|&&(opener, closer)| chars[index] == opener || chars[index] == closer)
+        .cloned();
+
+    // Find the starting bracket, scanning forward on the current line.
+    let mut start = positions.iter().position(|p| p == position)?;
+    while bracket(start).is_none() || ignored.contains(&positions[start]) {
+        if chars[start] == '\n' {
+            return None;
+        }
+        start += 1;
+    }
+    let (opener, closer) = bracket(start)?;
+    let skip_ignored = !ignored.contains(&positions[start]);
+
+    let mut depth = 0;
+    let candidates: Box<dyn Iterator<Item=usize>> = if chars[start] == opener {
+        Box::new(start + 1..chars.len())
+    } else {
+        Box::new((0..start).rev())
+    };
+    for index in candidates {
+        let c = chars[index];
+        if (c != opener && c != closer) || (skip_ignored && ignored.contains(&positions[index])) {
+            continue;
+        }
+
+        if c == chars[start] {
+            depth += 1;
+        } else if depth == 0 {
+            return Some(positions[index]);
+        } else {
+            depth -= 1;
+        }
+    }
+
+    None
+}
+
+/// Ordered ranges in which delimiters are ignored, read from an iterator
+/// as positions further into the content are checked against them.
+struct IgnoredRanges<I> {
+    ranges: Vec<Range>,
+    remaining: I,
+}
+
+impl<I: Iterator<Item=Range>> IgnoredRanges<I> {
+    fn new(remaining: I) -> IgnoredRanges<I> {
+        IgnoredRanges { ranges: Vec::new(), remaining }
+    }
+
+    fn contains(&mut self, position: &Position) -> bool {
+        // Read ranges until one starts beyond the position.
+        while self.ranges.last().map_or(true, |range| range.start() <= *position) {
+            match self.remaining.next() {
+                Some(range) => self.ranges.push(range),
+                None => break,
+            }
+        }
+
+        // Only the last range starting at or before the position can contain it.
+        let index = self.ranges.partition_point(|range| range.start() <= *position);
+        index > 0 && *position < self.ranges[index - 1].end()
+    }
+}
+
+/// Scans backwards from (and including) the specified index for
+/// an opening bracket that hasn't been closed before the index.
+fn find_opener(chars: &[char], from: usize, opener: char, closer: char) -> Option<usize> {
+    let mut depth = 0;
+    for index in (0..=from).rev() {
+        if chars[index] == closer {
+            depth += 1;
+        } else if chars[index] == opener {
+            if depth == 0 {
+                return Some(index);
+            }
+            depth -= 1;
+        }
+    }
+
+    None
+}
+
+/// Scans forwards from an opening bracket for its closing counterpart.
+fn find_closer(chars: &[char], opening: usize, opener: char, closer: char) -> Option<usize> {
+    let mut depth = 0;
+    for index in opening + 1..chars.len() {
+        if chars[index] == opener {
+            depth += 1;
+        } else if chars[index] == closer {
+            if depth == 0 {
+                return Some(index);
+            }
+            depth -= 1;
+        }
+    }
+
+    None
+}
+
+/// Pairs up the quotes on the line containing the specified index,
+/// ignoring escaped quotes, returning those enclosing the index.
+fn quote_pairs(chars: &[char], index: usize) -> Vec<(usize, usize)> {
+    let line_start = chars[..index].iter().rposition(|&c| c == '\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = chars[index..].iter().position(|&c| c == '\n').map(|i| index + i).unwrap_or(chars.len());
+
+    let mut pairs = Vec::new();
+    for &quote in QUOTES.iter() {
+        let mut opening = None;
+        for i in line_start..line_end {
+            if chars[i] != quote || (i > line_start && chars[i - 1] == '\\') {
+                continue;
+            }
+
+            match opening.take() {
+                Some(start) => if start <= index && index <= i {
+                    pairs.push((start, i));
+                },
+                None => opening = Some(i),
+            }
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::{Position, Range};
+    use std::iter;
+    use super::{enclosing_pairs, matching_bracket};
+
+    #[test]
+    fn enclosing_pairs_returns_nested_brackets_from_the_innermost_outwards() {
+        let data = "amp(editor[0], {\n  buffer\n})";
+
+        assert_eq!(
+            enclosing_pairs(data, &Position{ line: 1, offset: 3 }),
+            vec![
+                (Position{ line: 0, offset: 15 }, Position{ line: 2, offset: 0 }),
+                (Position{ line: 0, offset: 3 }, Position{ line: 2, offset: 1 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn enclosing_pairs_skips_balanced_pairs_preceding_the_position() {
+        let data = "(amp (editor) buffer)";
+
+        assert_eq!(
+            enclosing_pairs(data, &Position{ line: 0, offset: 15 }),
+            vec![(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 20 })]
+        );
+    }
+
+    #[test]
+    fn enclosing_pairs_treats_delimiters_as_part_of_their_pair() {
+        let data = "(amp)";
+        let pair = (Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 4 });
+
+        assert_eq!(enclosing_pairs(data, &Position{ line: 0, offset: 0 }), vec![pair]);
+        assert_eq!(enclosing_pairs(data, &Position{ line: 0, offset: 4 }), vec![pair]);
+    }
+
+    #[test]
+    fn enclosing_pairs_includes_quotes_on_the_current_line() {
+        let data = "amp(\"edi\\\"tor\")";
+
+        assert_eq!(
+            enclosing_pairs(data, &Position{ line: 0, offset: 6 }),
+            vec![
+                (Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 13 }),
+                (Position{ line: 0, offset: 3 }, Position{ line: 0, offset: 14 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn enclosing_pairs_returns_nothing_outside_of_delimiters() {
+        assert!(enclosing_pairs("amp (editor)", &Position{ line: 0, offset: 1 }).is_empty());
+    }
+
+    #[test]
+    fn matching_bracket_moves_between_nested_pairs() {
+        let data = "amp(editor[0], {\n  buffer\n})";
+
+        assert_eq!(matching_bracket(data, &Position{ line: 0, offset: 3 }, iter::empty()), Some(Position{ line: 2, offset: 1 }));
+        assert_eq!(matching_bracket(data, &Position{ line: 2, offset: 0 }, iter::empty()), Some(Position{ line: 0, offset: 15 }));
+    }
+
+    #[test]
+    fn matching_bracket_scans_forward_on_the_current_line() {
+        let data = "amp(editor)\n(buffer)";
+
+        assert_eq!(matching_bracket(data, &Position{ line: 0, offset: 0 }, iter::empty()), Some(Position{ line: 0, offset: 10 }));
+        assert_eq!(matching_bracket("amp\n(editor)", &Position{ line: 0, offset: 0 }, iter::empty()), None);
+    }
+
+    #[test]
+    fn matching_bracket_skips_ignored_ranges() {
+        let data = "amp(\")\")";
+        let ignored = [Range::new(Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 7 })];
+
+        assert_eq!(
+            matching_bracket(data, &Position{ line: 0, offset: 3 }, ignored.iter().cloned()),
+            Some(Position{ line: 0, offset: 7 })
+        );
+        assert_eq!(
+            matching_bracket(data, &Position{ line: 0, offset: 7 }, ignored.iter().cloned()),
+            Some(Position{ line: 0, offset: 3 })
+        );
+    }
+
+    #[test]
+    fn matching_bracket_only_reads_ignored_ranges_as_far_as_it_searches() {
+        let data = "(amp) \"(\" \")\"";
+        let ignored = iter::once(Range::new(Position{ line: 0, offset: 6 }, Position{ line: 0, offset: 9 }))
+            .chain(iter::from_fn(|| -> Option<Range> { panic!("Read ranges beyond the search") }));
+
+        assert_eq!(matching_bracket(data, &Position{ line: 0, offset: 0 }, ignored), Some(Position{ line: 0, offset: 4 }));
+    }
+}
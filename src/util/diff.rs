@@ -0,0 +1,238 @@
+use std::iter;
+
+// Finding the shortest edit sequence takes time and memory growing with the
+// square of the number of edits; past this many, the differing lines are
+// treated as a single changed block instead.
+const MAX_EDIT_DISTANCE: usize = 1_000;
+
+/// How a row of a side-by-side diff differs between its two sides.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RowKind {
+    Unchanged,
+    /// The line only exists on the right-hand side.
+    Added,
+    /// The line only exists on the left-hand side.
+    Removed,
+    /// The lines on either side differ.
+    Changed,
+}
+
+/// A row of a side-by-side diff, pairing a (zero-based) line from each side.
+/// Lines only present on one side are paired with a filler row (None).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Row {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    pub kind: RowKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Aligns the lines of both sides, so that unchanged lines share a row.
+/// Within each hunk, removed lines are paired with added ones as changed
+/// rows, and any left over are paired with filler rows.
+pub fn rows<T: PartialEq>(left: &[T], right: &[T]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut left_line = 0;
+    let mut right_line = 0;
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    for edit in edits(left, right).into_iter().chain(Some(Edit::Equal)) {
+        match edit {
+            Edit::Delete => {
+                removed.push(left_line);
+                left_line += 1;
+            }
+            Edit::Insert => {
+                added.push(right_line);
+                right_line += 1;
+            }
+            Edit::Equal => {
+                // Finish the hunk preceding these lines.
+                for index in 0..removed.len().max(added.len()) {
+                    let (left, right) = (removed.get(index).cloned(), added.get(index).cloned());
+                    let kind = match (left, right) {
+                        (Some(_), Some(_)) => RowKind::Changed,
+                        (Some(_), None) => RowKind::Removed,
+                        _ => RowKind::Added,
+                    };
+                    rows.push(Row { left, right, kind });
+                }
+                removed.clear();
+                added.clear();
+
+                // The trailing edit only flushes the last hunk.
+                if left_line < left.len() && right_line < right.len() {
+                    rows.push(Row { left: Some(left_line), right: Some(right_line), kind: RowKind::Unchanged });
+                    left_line += 1;
+                    right_line += 1;
+                }
+            }
+        }
+    }
+
+    rows
+}
+
+/// The edits transforming the left-hand lines into the right-hand ones.
+/// Lines shared at either end are matched up front; if those in between
+/// differ by more than MAX_EDIT_DISTANCE edits, they're treated as a single
+/// changed block rather than searched for the shortest edit sequence.
+fn edits<T: PartialEq>(left: &[T], right: &[T]) -> Vec<Edit> {
+    let prefix = left.iter().zip(right).take_while(|(l, r)| l == r).count();
+    let suffix = left[prefix..].iter().rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let (left, right) = (&left[prefix..left.len() - suffix], &right[prefix..right.len() - suffix]);
+
+    let mut edits = vec![Edit::Equal; prefix];
+    match shortest_edits(left, right, MAX_EDIT_DISTANCE) {
+        Some(shortest) => edits.extend(shortest),
+        None => {
+            edits.extend(iter::repeat(Edit::Delete).take(left.len()));
+            edits.extend(iter::repeat(Edit::Insert).take(right.len()));
+        }
+    }
+    edits.extend(iter::repeat(Edit::Equal).take(suffix));
+
+    edits
+}
+
+/// The shortest sequence of edits transforming the left-hand lines into
+/// the right-hand ones, found using Myers' algorithm, provided it doesn't
+/// exceed the limit.
+fn shortest_edits<T: PartialEq>(left: &[T], right: &[T], limit: usize) -> Option<Vec<Edit>> {
+    let (n, m) = (left.len() as isize, right.len() as isize);
+    let max = (n + m) as usize;
+    let rounds = max.min(limit) as isize;
+    let offset = rounds + 1;
+
+    // The furthest-reaching x position on each diagonal (k = x - y). Before
+    // each round d, the diagonals it can reach (-d..=d) are recorded, so
+    // that the path can be retraced afterwards.
+    let mut furthest = vec![0isize; 2 * rounds as usize + 3];
+    let mut trace = Vec::new();
+    let mut found = false;
+    'search: for d in 0..=rounds {
+        trace.push(furthest[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && furthest[index - 1] < furthest[index + 1]) {
+                furthest[index + 1]
+            } else {
+                furthest[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && left[x as usize] == right[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[index] = x;
+
+            if x >= n && y >= m {
+                found = true;
+                break 'search;
+            }
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, round) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let furthest = |k: isize| round[(k + d) as usize];
+        let k = x - y;
+        let (previous_x, previous_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let previous_k = if k == -d || (k != d && furthest(k - 1) < furthest(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            let previous_x = furthest(previous_k);
+
+            (previous_x, previous_x - previous_k)
+        };
+
+        while x > previous_x && y > previous_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == previous_x { Edit::Insert } else { Edit::Delete });
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    edits.reverse();
+
+    Some(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rows, Row, RowKind};
+
+    fn row(left: Option<usize>, right: Option<usize>, kind: RowKind) -> Row {
+        Row { left, right, kind }
+    }
+
+    #[test]
+    fn rows_pair_changed_lines_and_pad_added_ones() {
+        assert_eq!(rows(&["a", "b", "c"], &["a", "x", "c", "d"]), vec![
+            row(Some(0), Some(0), RowKind::Unchanged),
+            row(Some(1), Some(1), RowKind::Changed),
+            row(Some(2), Some(2), RowKind::Unchanged),
+            row(None, Some(3), RowKind::Added),
+        ]);
+    }
+
+    #[test]
+    fn rows_pad_removed_lines() {
+        assert_eq!(rows(&["a", "b", "c", "d"], &["a", "x", "d"]), vec![
+            row(Some(0), Some(0), RowKind::Unchanged),
+            row(Some(1), Some(1), RowKind::Changed),
+            row(Some(2), None, RowKind::Removed),
+            row(Some(3), Some(2), RowKind::Unchanged),
+        ]);
+    }
+
+    #[test]
+    fn rows_handle_empty_sides() {
+        let empty: [&str; 0] = [];
+
+        assert!(rows(&empty, &empty).is_empty());
+        assert_eq!(rows(&empty, &["a"]), vec![row(None, Some(0), RowKind::Added)]);
+        assert_eq!(rows(&["a"], &empty), vec![row(Some(0), None, RowKind::Removed)]);
+    }
+
+    #[test]
+    fn rows_treat_large_disjoint_blocks_as_changed() {
+        let mut left: Vec<String> = (0..3_000).map(|line| format!("left {}", line)).collect();
+        let mut right: Vec<String> = (0..3_000).map(|line| format!("right {}", line)).collect();
+        left.insert(0, String::from("amp"));
+        right.insert(0, String::from("amp"));
+        left.push(String::from("editor"));
+        right.push(String::from("editor"));
+        let rows = rows(&left, &right);
+
+        assert_eq!(rows.len(), 3_002);
+        assert_eq!(rows[0], row(Some(0), Some(0), RowKind::Unchanged));
+        assert!(rows[1..3_001].iter().enumerate().all(|(index, changed)| {
+            *changed == row(Some(index + 1), Some(index + 1), RowKind::Changed)
+        }));
+        assert_eq!(rows[3_001], row(Some(3_001), Some(3_001), RowKind::Unchanged));
+    }
+}
@@ -0,0 +1,73 @@
+use crate::errors::*;
+
+/// Finds the line holding the directive paired with the one on the specified
+/// line, accounting for nested pairs. Directives are matched as prefixes of
+/// trimmed lines, so an "#if" opener also matches "#ifdef" and "#ifndef".
+pub fn matching_directive(data: &str, line: usize, pairs: &[(String, String)]) -> Result<usize> {
+    let lines: Vec<&str> = data.lines().map(|l| l.trim_start()).collect();
+    let current_line = lines.get(line).ok_or("Couldn't find the current line")?;
+
+    for (opener, closer) in pairs {
+        let forward = if current_line.starts_with(opener.as_str()) {
+            true
+        } else if current_line.starts_with(closer.as_str()) {
+            false
+        } else {
+            continue;
+        };
+
+        let candidates: Box<dyn Iterator<Item=(usize, &&str)>> = if forward {
+            Box::new(lines.iter().enumerate().skip(line))
+        } else {
+            Box::new(lines.iter().enumerate().take(line + 1).rev())
+        };
+
+        let mut depth = 0;
+        for (index, content) in candidates {
+            if content.starts_with(opener.as_str()) {
+                depth += if forward { 1 } else { -1 };
+            } else if content.starts_with(closer.as_str()) {
+                depth += if forward { -1 } else { 1 };
+            }
+
+            if depth == 0 {
+                return Ok(index);
+            }
+        }
+
+        let directive = if forward { opener } else { closer };
+        bail!("Couldn't find a match for the {} directive on line {}", directive, line + 1);
+    }
+
+    bail!("No directive found on the current line")
+}
+
+#[cfg(test)]
+mod tests {
+    fn pairs() -> Vec<(String, String)> {
+        vec![
+            (String::from("#if"), String::from("#endif")),
+            (String::from("#region"), String::from("#endregion")),
+        ]
+    }
+
+    #[test]
+    fn matching_directive_handles_nesting_in_both_directions() {
+        let data = "#ifdef AMP\n  #if EDITOR\n  #endif\n#endif\n";
+
+        assert_eq!(super::matching_directive(data, 0, &pairs()).unwrap(), 3);
+        assert_eq!(super::matching_directive(data, 3, &pairs()).unwrap(), 0);
+        assert_eq!(super::matching_directive(data, 2, &pairs()).unwrap(), 1);
+    }
+
+    #[test]
+    fn matching_directive_reports_unmatched_directives() {
+        let data = "#region amp\n#if EDITOR\n#endregion\n";
+
+        assert_eq!(
+            super::matching_directive(data, 1, &pairs()).unwrap_err().to_string(),
+            "Couldn't find a match for the #if directive on line 2"
+        );
+        assert!(super::matching_directive(data, 3, &pairs()).is_err());
+    }
+}
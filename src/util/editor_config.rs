@@ -0,0 +1,435 @@
+use crate::util::line_ending::LineEnding;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = ".editorconfig";
+
+// Numeric ranges (e.g. "{1..3}") are expanded into their
+// members; those larger than this are matched literally.
+const MAX_RANGE_LENGTH: i64 = 1000;
+
+/// Settings for a file, as specified by the .editorconfig files in its
+/// directory and those above it. Unspecified (or invalid) settings are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EditorConfig {
+    pub soft_tabs: Option<bool>,
+    pub tab_width: Option<usize>,
+    pub end_of_line: Option<LineEnding>,
+    pub insert_final_newline: Option<bool>,
+    pub trim_trailing_whitespace: Option<bool>,
+}
+
+/// A parsed .editorconfig file.
+struct ConfigFile {
+    root: bool,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    pattern: String,
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfig {
+    /// Reads the .editorconfig files applying to the path, starting with its
+    /// directory and moving up until one declaring itself the root is found.
+    /// Files closer to the path take precedence, as do later sections within
+    /// a file. Missing or unreadable files are skipped.
+    pub fn load(path: &Path) -> EditorConfig {
+        let path = match absolute_path(path) {
+            Some(path) => path,
+            None => return EditorConfig::default(),
+        };
+
+        let mut files = Vec::new();
+        for directory in path.ancestors().skip(1) {
+            if let Ok(content) = fs::read_to_string(directory.join(FILE_NAME)) {
+                let file = ConfigFile::parse(&content);
+                let root = file.root;
+                files.push((directory, file));
+
+                if root {
+                    break;
+                }
+            }
+        }
+
+        let mut properties = HashMap::new();
+        for (directory, file) in files.iter().rev() {
+            if let Some(relative_path) = path.strip_prefix(directory).ok().and_then(|p| p.to_str()) {
+                file.apply(relative_path, &mut properties);
+            }
+        }
+
+        EditorConfig::from_properties(&properties)
+    }
+
+    fn from_properties(properties: &HashMap<String, String>) -> EditorConfig {
+        let value = |key: &str| properties.get(key).map(|value| value.to_lowercase());
+        let boolean = |key: &str| match value(key).as_ref().map(String::as_str) {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            _ => None,
+        };
+        let width = |key: &str| value(key).and_then(|value| value.parse::<usize>().ok()).filter(|&width| width > 0);
+
+        EditorConfig {
+            soft_tabs: match value("indent_style").as_ref().map(String::as_str) {
+                Some("space") => Some(true),
+                Some("tab") => Some(false),
+                _ => None,
+            },
+            // Amp uses a single width for both tabs and indentation; an
+            // indent_size of "tab" defers to the tab_width property.
+            tab_width: width("indent_size").or_else(|| width("tab_width")),
+            end_of_line: match value("end_of_line").as_ref().map(String::as_str) {
+                Some("lf") => Some(LineEnding::Lf),
+                Some("crlf") => Some(LineEnding::Crlf),
+                Some("cr") => Some(LineEnding::Cr),
+                _ => None,
+            },
+            insert_final_newline: boolean("insert_final_newline"),
+            trim_trailing_whitespace: boolean("trim_trailing_whitespace"),
+        }
+    }
+}
+
+impl ConfigFile {
+    fn parse(content: &str) -> ConfigFile {
+        let mut root = false;
+        let mut sections: Vec<Section> = Vec::new();
+
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                if let Some(end) = line.rfind(']').filter(|&end| end > 1) {
+                    sections.push(Section {
+                        pattern: line[1..end].to_string(),
+                        properties: Vec::new(),
+                    });
+                }
+            } else if let Some(index) = line.find('=') {
+                let key = line[..index].trim().to_lowercase();
+                let value = line[index + 1..].trim().to_string();
+
+                match sections.last_mut() {
+                    Some(section) => section.properties.push((key, value)),
+                    None if key == "root" => root = value.eq_ignore_ascii_case("true"),
+                    None => (),
+                }
+            }
+        }
+
+        ConfigFile { root, sections }
+    }
+
+    /// Applies the properties of sections matching the path (relative to
+    /// the file's directory), with "unset" clearing previous values.
+    fn apply(&self, relative_path: &str, properties: &mut HashMap<String, String>) {
+        for section in self.sections.iter().filter(|s| section_matches(&s.pattern, relative_path)) {
+            for (key, value) in &section.properties {
+                if value.eq_ignore_ascii_case("unset") {
+                    properties.remove(key);
+                } else {
+                    properties.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+fn absolute_path(path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        Some(path.to_path_buf())
+    } else {
+        env::current_dir().ok().map(|directory| directory.join(path))
+    }
+}
+
+/// Section patterns without a slash match file names in any directory,
+/// while those with one are relative to the .editorconfig's directory.
+fn section_matches(pattern: &str, relative_path: &str) -> bool {
+    let pattern = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+    let path: Vec<char> = relative_path.chars().collect();
+
+    expand_braces(&pattern).iter().any(|pattern| {
+        let pattern: Vec<char> = pattern.chars().collect();
+
+        glob_matches(&pattern, &path)
+    })
+}
+
+/// Expands brace expressions into the patterns they describe: "{a,b}" into
+/// one for each alternative, and "{1..3}" into one for each number in the
+/// range. Other braces (e.g. "{a}") are escaped, to be matched literally.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let (start, end) = match brace_expression(pattern) {
+        Some(bounds) => bounds,
+        None => return vec![pattern.to_string()],
+    };
+    let (prefix, content, suffix) = (&pattern[..start], &pattern[start + 1..end], &pattern[end + 1..]);
+
+    let alternatives = split_alternatives(content);
+    let members = if alternatives.len() > 1 {
+        alternatives
+    } else if let Some(numbers) = numeric_range(content) {
+        numbers
+    } else {
+        let escaped = format!("{}\\{{{}\\}}", prefix, content);
+
+        return expand_braces(suffix)
+            .into_iter()
+            .map(|suffix| format!("{}{}", escaped, suffix))
+            .collect();
+    };
+
+    members
+        .iter()
+        .flat_map(|member| expand_braces(&format!("{}{}{}", prefix, member, suffix)))
+        .collect()
+}
+
+/// The byte indices of the first unescaped brace pair, matched with nesting.
+fn brace_expression(pattern: &str) -> Option<(usize, usize)> {
+    let mut start = None;
+    let mut depth = 0;
+    let mut characters = pattern.char_indices();
+
+    while let Some((index, character)) = characters.next() {
+        match character {
+            '\\' => { characters.next(); }
+            '{' => {
+                if start.is_none() {
+                    start = Some(index);
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return start.map(|start| (start, index));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Splits a brace expression's content on its top-level commas.
+fn split_alternatives(content: &str) -> Vec<String> {
+    let mut alternatives = vec![String::new()];
+    let mut depth = 0;
+    let mut characters = content.chars();
+
+    while let Some(character) = characters.next() {
+        match character {
+            ',' if depth == 0 => {
+                alternatives.push(String::new());
+                continue;
+            }
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => (),
+        }
+
+        let alternative = alternatives.last_mut().unwrap();
+        alternative.push(character);
+        if character == '\\' {
+            if let Some(escaped) = characters.next() {
+                alternative.push(escaped);
+            }
+        }
+    }
+
+    alternatives
+}
+
+fn numeric_range(content: &str) -> Option<Vec<String>> {
+    let index = content.find("..")?;
+    let first: i64 = content[..index].parse().ok()?;
+    let last: i64 = content[index + 2..].parse().ok()?;
+    if (last - first).abs() > MAX_RANGE_LENGTH {
+        return None;
+    }
+
+    Some((first.min(last)..=first.max(last)).map(|number| number.to_string()).collect())
+}
+
+/// Matches a path against a (brace-expanded) glob pattern, where "*" matches
+/// anything but slashes, "**" matches anything, "?" matches any character but
+/// a slash, "[abc]", "[a-c]", and "[!abc]" match character classes, and a
+/// backslash escapes the character that follows it.
+fn glob_matches(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+
+            // A leading "**/" also matches files without any parent directories.
+            if rest.first() == Some(&'/') && glob_matches(&rest[1..], path) {
+                return true;
+            }
+
+            (0..=path.len()).any(|index| glob_matches(rest, &path[index..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for index in 0..=path.len() {
+                if glob_matches(rest, &path[index..]) {
+                    return true;
+                }
+                if path.get(index) == Some(&'/') {
+                    break;
+                }
+            }
+
+            false
+        }
+        Some('?') => {
+            path.first().map(|&c| c != '/').unwrap_or(false) && glob_matches(&pattern[1..], &path[1..])
+        }
+        Some('[') => {
+            let character = match path.first() {
+                Some(&character) => character,
+                None => return false,
+            };
+
+            match match_class(&pattern[1..], character) {
+                Some((matched, length)) => {
+                    matched && character != '/' && glob_matches(&pattern[1 + length..], &path[1..])
+                }
+                // Unterminated classes are matched literally.
+                None => character == '[' && glob_matches(&pattern[1..], &path[1..]),
+            }
+        }
+        Some('\\') if pattern.len() > 1 => {
+            path.first() == Some(&pattern[1]) && glob_matches(&pattern[2..], &path[1..])
+        }
+        Some(character) => {
+            path.first() == Some(character) && glob_matches(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+/// Matches a character against the class following an opening bracket,
+/// returning the result and the class's length (including its closing
+/// bracket), or `None` if the class isn't terminated.
+fn match_class(class: &[char], character: char) -> Option<(bool, usize)> {
+    let negated = class.first() == Some(&'!');
+    let start = if negated { 1 } else { 0 };
+
+    // A closing bracket immediately following the opening one is a member.
+    let end = (start + 1..class.len()).find(|&index| class[index] == ']')?;
+    let members = &class[start..end];
+
+    let mut matched = false;
+    let mut index = 0;
+    while index < members.len() {
+        if index + 2 < members.len() && members[index + 1] == '-' {
+            matched |= members[index] <= character && character <= members[index + 2];
+            index += 3;
+        } else {
+            matched |= members[index] == character;
+            index += 1;
+        }
+    }
+
+    Some((matched != negated, end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EditorConfig, expand_braces, section_matches};
+    use crate::util::line_ending::LineEnding;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn section_matches_file_names_in_any_directory_without_a_slash() {
+        assert!(section_matches("*.rs", "main.rs"));
+        assert!(section_matches("*.rs", "src/view/mod.rs"));
+        assert!(section_matches("Makefile", "src/Makefile"));
+        assert!(!section_matches("*.rs", "main.rs.bak"));
+    }
+
+    #[test]
+    fn section_matches_relative_paths_with_a_slash() {
+        assert!(section_matches("src/*.rs", "src/main.rs"));
+        assert!(section_matches("/src/*.rs", "src/main.rs"));
+        assert!(!section_matches("src/*.rs", "src/view/mod.rs"));
+        assert!(!section_matches("src/*.rs", "lib/src/main.rs"));
+        assert!(section_matches("src/**.rs", "src/view/mod.rs"));
+        assert!(section_matches("src/**/mod.rs", "src/mod.rs"));
+    }
+
+    #[test]
+    fn section_matches_classes_braces_and_ranges() {
+        assert!(section_matches("*.{js,ts}", "app.ts"));
+        assert!(!section_matches("*.{js,ts}", "app.rs"));
+        assert!(section_matches("file[0-9].txt", "file5.txt"));
+        assert!(!section_matches("file[!0-9].txt", "file5.txt"));
+        assert!(section_matches("file{1..10}.txt", "file10.txt"));
+        assert!(!section_matches("file{1..10}.txt", "file11.txt"));
+        assert!(section_matches("{single}", "{single}"));
+        assert!(section_matches("?.rs", "a.rs"));
+    }
+
+    #[test]
+    fn expand_braces_handles_nested_expressions() {
+        assert_eq!(expand_braces("{a,{b,c}}d"), vec!["ad", "bd", "cd"]);
+    }
+
+    #[test]
+    fn load_applies_files_up_to_the_root_with_nearest_taking_precedence() {
+        let root = Path::new(concat!(env!("OUT_DIR"), "/editor_config"));
+        fs::create_dir_all(root.join("project/src")).unwrap();
+        fs::write(root.join(".editorconfig"), "[*]\nindent_size = 8\ncharset = latin1\n").unwrap();
+        fs::write(
+            root.join("project/.editorconfig"),
+            "root = true\n\n[*]\nindent_style = space\nindent_size = 2\nend_of_line = crlf\n\n\
+             [*.rs]\nindent_size = 4\ninsert_final_newline = true\n"
+        ).unwrap();
+        fs::write(
+            root.join("project/src/.editorconfig"),
+            "[*.rs]\ntrim_trailing_whitespace = false\nend_of_line = unset\n"
+        ).unwrap();
+
+        let config = EditorConfig::load(&root.join("project/src/main.rs"));
+        assert_eq!(config, EditorConfig {
+            soft_tabs: Some(true),
+            tab_width: Some(4),
+            end_of_line: None,
+            insert_final_newline: Some(true),
+            trim_trailing_whitespace: Some(false),
+        });
+
+        let config = EditorConfig::load(&root.join("project/README.md"));
+        assert_eq!(config.tab_width, Some(2));
+        assert_eq!(config.end_of_line, Some(LineEnding::Crlf));
+    }
+
+    #[test]
+    fn load_defers_to_tab_width_for_tab_indent_sizes() {
+        let root = Path::new(concat!(env!("OUT_DIR"), "/editor_config_tabs"));
+        fs::create_dir_all(root).unwrap();
+        fs::write(
+            root.join(".editorconfig"),
+            "root = true\n[Makefile]\nindent_style = tab\nindent_size = tab\ntab_width = 8\n"
+        ).unwrap();
+
+        let config = EditorConfig::load(&root.join("Makefile"));
+        assert_eq!(config.soft_tabs, Some(false));
+        assert_eq!(config.tab_width, Some(8));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::errors::*;
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use scribe::Buffer;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The encodings offered when forcing a buffer to be reloaded using
+/// a specific encoding (any label known to encoding_rs will work).
+pub const ENCODINGS: [&str; 12] = [
+    "UTF-8",
+    "UTF-16LE",
+    "UTF-16BE",
+    "windows-1252",
+    "ISO-8859-2",
+    "ISO-8859-15",
+    "windows-1251",
+    "KOI8-R",
+    "Shift_JIS",
+    "EUC-JP",
+    "GBK",
+    "Big5",
+];
+
+static STAGED_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// Staged file names that are already taken are skipped, up to this many times.
+const STAGING_ATTEMPTS: usize = 10;
+
+// Buffers are "saved" here to mark them as such, without writing anything to disk.
+const NULL_DEVICE: &str = "/dev/null";
+
+/// The encoding of a file on disk, which its content
+/// is converted back to when its buffer is saved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SourceEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+}
+
+impl SourceEncoding {
+    /// Uses the specified encoding for the data,
+    /// keeping its byte order mark if it has one.
+    pub fn forced(encoding: &'static Encoding, bytes: &[u8]) -> SourceEncoding {
+        let bom = Encoding::for_bom(bytes)
+            .map(|(bom_encoding, _)| bom_encoding == encoding)
+            .unwrap_or(false);
+
+        SourceEncoding { encoding, bom }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+}
+
+/// Determines the data's encoding using its byte order mark, if present.
+/// Otherwise, data that's mostly ASCII interleaved with null bytes is
+/// assumed to be UTF-16, and anything that isn't valid UTF-8 is assumed
+/// to be windows-1252 (a superset of Latin-1).
+pub fn detect(bytes: &[u8]) -> SourceEncoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return SourceEncoding { encoding, bom: true };
+    }
+
+    let encoding = if let Some(encoding) = detect_utf16(bytes) {
+        encoding
+    } else if str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    };
+
+    SourceEncoding { encoding, bom: false }
+}
+
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(1024)];
+    if sample.len() < 2 || sample.len() % 2 != 0 {
+        return None;
+    }
+
+    let pairs = sample.len() / 2;
+    let even_nulls = sample.iter().step_by(2).filter(|&&byte| byte == 0).count();
+    let odd_nulls = sample.iter().skip(1).step_by(2).filter(|&&byte| byte == 0).count();
+    if odd_nulls > pairs / 2 && even_nulls == 0 {
+        Some(UTF_16LE)
+    } else if even_nulls > pairs / 2 && odd_nulls == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Decodes the data, replacing invalid sequences with U+FFFD (�).
+/// Returns the decoded content, and whether any replacements were made.
+pub fn decode(bytes: &[u8], source: SourceEncoding) -> (String, bool) {
+    let bom_length = if source.bom {
+        Encoding::for_bom(bytes).map(|(_, length)| length).unwrap_or(0)
+    } else {
+        0
+    };
+    let (content, had_errors) = source.encoding.decode_without_bom_handling(&bytes[bom_length..]);
+
+    (content.into_owned(), had_errors)
+}
+
+/// Encodes the data using the source encoding, restoring its byte order
+/// mark. Fails rather than mangling characters the encoding can't represent.
+pub fn encode(data: &str, source: SourceEncoding) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+
+    // encoding_rs doesn't encode UTF-16, so we handle that ourselves.
+    if source.encoding == UTF_16LE || source.encoding == UTF_16BE {
+        let little_endian = source.encoding == UTF_16LE;
+        let bom = if source.bom { Some(0xFEFF) } else { None };
+        for unit in bom.into_iter().chain(data.encode_utf16()) {
+            bytes.extend_from_slice(&if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() });
+        }
+
+        return Ok(bytes);
+    }
+
+    if source.bom && source.encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xEF\xBB\xBF");
+    }
+    let (encoded_data, _, unmappable) = source.encoding.encode(data);
+    if unmappable {
+        bail!("The buffer contains characters that can't be encoded as {}", source.name());
+    }
+    bytes.extend_from_slice(&encoded_data);
+
+    Ok(bytes)
+}
+
+/// Opens a buffer for the file, decoding it if it isn't UTF-8. The file's
+/// encoding is returned alongside the buffer in that case, for use on save.
+pub fn read_buffer(path: &Path) -> Result<(Buffer, Option<SourceEncoding>)> {
+    let bytes = fs::read(path).chain_err(|| "Couldn't read the specified file")?;
+    let source = detect(&bytes);
+    if source.encoding == UTF_8 {
+        return Ok((Buffer::from_file(path)?, None));
+    }
+
+    let (content, _) = decode(&bytes, source);
+    let mut buffer = stage(&content, |staged_path| Ok(Buffer::from_file(staged_path)?))?;
+    buffer.path = Some(path.canonicalize()?);
+
+    Ok((buffer, Some(source)))
+}
+
+/// Replaces the buffer's content with its file's, decoded using the specified
+/// encoding. Returns whether any invalid sequences had to be replaced.
+pub fn reload_buffer(buffer: &mut Buffer, source: SourceEncoding, bytes: &[u8]) -> Result<bool> {
+    let (content, had_errors) = decode(bytes, source);
+
+    // Reload from the decoded content, restoring the buffer's path afterwards.
+    let path = buffer.path.take();
+    let result = stage(&content, |staged_path| {
+        buffer.path = Some(staged_path.to_path_buf());
+        Ok(buffer.reload()?)
+    });
+    buffer.path = path;
+    result?;
+
+    Ok(had_errors)
+}
+
+/// Marks the buffer as saved, once its encoded content has been written to
+/// its file. Scribe only does so when saving the content (as UTF-8) itself,
+/// so it's saved to the null device instead, leaving the buffer's file alone.
+pub fn mark_saved(buffer: &mut Buffer) -> Result<()> {
+    let path = buffer.path.replace(PathBuf::from(NULL_DEVICE));
+    let result = buffer.save();
+    buffer.path = path;
+
+    Ok(result?)
+}
+
+/// Scribe buffers can only be loaded from UTF-8 files, so decoded
+/// content is written to a temporary file to be loaded from there.
+///
+/// Building the buffer from the decoded content directly (inserting it into
+/// a new buffer) would leave that insertion in its undo history, and mark it
+/// as modified; scribe keeps both its history and the mark recording the
+/// saved state private. Staging can go once scribe can load from a string.
+fn stage<T, F>(content: &str, load: F) -> Result<T>
+    where F: FnOnce(&Path) -> Result<T>
+{
+    let (staged_path, mut file) = create_staged_file()?;
+    let written = file.write_all(content.as_bytes());
+    drop(file);
+    let result = written
+        .chain_err(|| "Couldn't stage decoded file content")
+        .and_then(|_| load(&staged_path));
+    fs::remove_file(&staged_path).ok();
+
+    result
+}
+
+/// Creates a temporary file that only we can read, refusing to open anything
+/// already at its path (e.g. a symlink planted there to redirect the write).
+fn create_staged_file() -> Result<(PathBuf, File)> {
+    for _ in 0..STAGING_ATTEMPTS {
+        let path = env::temp_dir().join(format!(
+            "amp-{}-{}",
+            process::id(),
+            STAGED_FILE_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        match OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(ref error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error).chain_err(|| "Couldn't stage decoded file content"),
+        }
+    }
+
+    bail!("Couldn't stage decoded file content")
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+    use scribe::Buffer;
+    use std::fs;
+    use std::path::PathBuf;
+    use super::{SourceEncoding, decode, detect, encode, mark_saved};
+
+    #[test]
+    fn detect_recognizes_byte_order_marks() {
+        assert_eq!(detect(b"\xFF\xFEa\x00"), SourceEncoding { encoding: UTF_16LE, bom: true });
+        assert_eq!(detect(b"\xFE\xFF\x00a"), SourceEncoding { encoding: UTF_16BE, bom: true });
+    }
+
+    #[test]
+    fn detect_recognizes_utf16_without_a_byte_order_mark() {
+        assert_eq!(detect(b"a\x00m\x00p\x00"), SourceEncoding { encoding: UTF_16LE, bom: false });
+        assert_eq!(detect(b"\x00a\x00m\x00p"), SourceEncoding { encoding: UTF_16BE, bom: false });
+    }
+
+    #[test]
+    fn detect_falls_back_to_windows_1252_for_invalid_utf8() {
+        assert_eq!(detect("amp ✓".as_bytes()).encoding, UTF_8);
+        assert_eq!(detect(b"caf\xE9").encoding, WINDOWS_1252);
+    }
+
+    #[test]
+    fn decode_replaces_invalid_sequences_visibly() {
+        let source = SourceEncoding { encoding: UTF_8, bom: false };
+        assert_eq!(decode(b"amp\xFF", source), (String::from("amp\u{fffd}"), true));
+    }
+
+    #[test]
+    fn encode_round_trips_decoded_content() {
+        let bytes = b"\xFF\xFEa\x00m\x00p\x00";
+        let source = detect(bytes);
+        let (content, had_errors) = decode(bytes, source);
+
+        assert_eq!(content, "amp");
+        assert!(!had_errors);
+        assert_eq!(encode(&content, source).unwrap(), bytes.to_vec());
+
+        let source = SourceEncoding { encoding: WINDOWS_1252, bom: false };
+        assert_eq!(encode("café", source).unwrap(), b"caf\xE9".to_vec());
+    }
+
+    #[test]
+    fn encode_rejects_characters_the_encoding_cannot_represent() {
+        let source = SourceEncoding { encoding: WINDOWS_1252, bom: false };
+
+        assert!(encode("✓", source).is_err());
+    }
+
+    #[test]
+    fn mark_saved_leaves_the_buffers_file_untouched() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/mark_saved.txt"));
+        fs::write(&path, b"caf\xE9").unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("café");
+        buffer.path = Some(path.clone());
+
+        mark_saved(&mut buffer).unwrap();
+        assert!(!buffer.modified());
+        assert_eq!(buffer.path, Some(path.clone()));
+        assert_eq!(fs::read(&path).unwrap(), b"caf\xE9".to_vec());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::errors::*;
+
+/// Evaluates an arithmetic expression, supporting `+ - * / %`, parentheses,
+/// and the integer-only bitwise operators `& | ^ ~ << >>`. Numbers can be
+/// decimal (optionally fractional) or hexadecimal (e.g. `0xff`). Results
+/// without a fractional component are formatted as integers.
+pub fn evaluate(expression: &str) -> Result<String> {
+    let mut parser = Parser {
+        data: expression.chars().collect(),
+        index: 0,
+    };
+
+    let value = parser.bitwise_or()?;
+    parser.skip_whitespace();
+    if parser.index < parser.data.len() {
+        bail!(parser.error("unexpected content after expression"));
+    }
+
+    if !value.is_finite() {
+        bail!("Invalid expression: result is not a finite number");
+    }
+
+    Ok(format_value(value))
+}
+
+struct Parser {
+    data: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    fn bitwise_or(&mut self) -> Result<f64> {
+        let mut value = self.bitwise_xor()?;
+        while self.consume("|") {
+            let rhs = self.bitwise_xor()?;
+            value = (self.integer(value)? | self.integer(rhs)?) as f64;
+        }
+
+        Ok(value)
+    }
+
+    fn bitwise_xor(&mut self) -> Result<f64> {
+        let mut value = self.bitwise_and()?;
+        while self.consume("^") {
+            let rhs = self.bitwise_and()?;
+            value = (self.integer(value)? ^ self.integer(rhs)?) as f64;
+        }
+
+        Ok(value)
+    }
+
+    fn bitwise_and(&mut self) -> Result<f64> {
+        let mut value = self.shift()?;
+        while self.consume("&") {
+            let rhs = self.shift()?;
+            value = (self.integer(value)? & self.integer(rhs)?) as f64;
+        }
+
+        Ok(value)
+    }
+
+    fn shift(&mut self) -> Result<f64> {
+        let mut value = self.additive()?;
+        loop {
+            if self.consume("<<") {
+                let rhs = self.shift_amount()?;
+                value = (self.integer(value)? << rhs) as f64;
+            } else if self.consume(">>") {
+                let rhs = self.shift_amount()?;
+                value = (self.integer(value)? >> rhs) as f64;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn additive(&mut self) -> Result<f64> {
+        let mut value = self.multiplicative()?;
+        loop {
+            if self.consume("+") {
+                value += self.multiplicative()?;
+            } else if self.consume("-") {
+                value -= self.multiplicative()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<f64> {
+        let mut value = self.unary()?;
+        loop {
+            let operator = if self.consume("*") {
+                '*'
+            } else if self.consume("/") {
+                '/'
+            } else if self.consume("%") {
+                '%'
+            } else {
+                return Ok(value);
+            };
+
+            self.skip_whitespace();
+            let position = self.index;
+            let rhs = self.unary()?;
+            if operator != '*' && rhs == 0.0 {
+                self.index = position;
+                bail!(self.error("division by zero"));
+            }
+
+            match operator {
+                '*' => value *= rhs,
+                '/' => value /= rhs,
+                _ => value %= rhs,
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64> {
+        if self.consume("-") {
+            Ok(-self.unary()?)
+        } else if self.consume("+") {
+            self.unary()
+        } else if self.consume("~") {
+            let value = self.unary()?;
+            Ok(!self.integer(value)? as f64)
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<f64> {
+        self.skip_whitespace();
+
+        match self.current() {
+            Some('(') => {
+                self.index += 1;
+                let value = self.bitwise_or()?;
+                if !self.consume(")") {
+                    bail!(self.error("expected ')'"));
+                }
+
+                Ok(value)
+            },
+            Some(c) if c.is_digit(10) || c == '.' => self.number(),
+            Some(c) => bail!(self.error(&format!("unexpected character '{}'", c))),
+            None => bail!(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        let start = self.index;
+
+        // Hexadecimal literals.
+        if self.data[start..].starts_with(&['0', 'x']) {
+            self.index += 2;
+            while self.current().map(|c| c.is_digit(16)).unwrap_or(false) {
+                self.index += 1;
+            }
+            let digits: String = self.data[start + 2..self.index].iter().collect();
+
+            match i64::from_str_radix(&digits, 16) {
+                Ok(number) => return Ok(number as f64),
+                Err(_) => {
+                    self.index = start;
+                    bail!(self.error("invalid hexadecimal number"));
+                }
+            }
+        }
+
+        while self.current().map(|c| c.is_digit(10) || c == '.').unwrap_or(false) {
+            self.index += 1;
+        }
+        let content: String = self.data[start..self.index].iter().collect();
+
+        match content.parse::<f64>() {
+            Ok(number) => Ok(number),
+            Err(_) => {
+                self.index = start;
+                bail!(self.error(&format!("invalid number \"{}\"", content)));
+            }
+        }
+    }
+
+    /// Converts a value to an integer, for use with bitwise operators.
+    fn integer(&self, value: f64) -> Result<i64> {
+        if value.fract() != 0.0 {
+            bail!("Invalid expression: bitwise operators require integers, found {}", value);
+        }
+
+        Ok(value as i64)
+    }
+
+    fn shift_amount(&mut self) -> Result<u32> {
+        self.skip_whitespace();
+        let position = self.index;
+        let value = self.additive()?;
+        if value < 0.0 || value >= 64.0 || value.fract() != 0.0 {
+            self.index = position;
+            bail!(self.error("shift amounts must be integers between 0 and 63"));
+        }
+
+        Ok(value as u32)
+    }
+
+    /// Skips whitespace and consumes the operator, if present.
+    fn consume(&mut self, operator: &str) -> bool {
+        self.skip_whitespace();
+
+        let operator: Vec<char> = operator.chars().collect();
+        if !self.data[self.index..].starts_with(&operator) {
+            return false;
+        }
+
+        self.index += operator.len();
+        true
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.current().map(|c| c.is_whitespace()).unwrap_or(false) {
+            self.index += 1;
+        }
+    }
+
+    fn current(&self) -> Option<char> {
+        self.data.get(self.index).cloned()
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Invalid expression at column {}: {}", self.index + 1, message)
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < i64::max_value() as f64 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn evaluate_respects_operator_precedence() {
+        assert_eq!(super::evaluate("2 + 3 * (4 - 1) % 5").unwrap(), "6");
+        assert_eq!(super::evaluate("-7 / 2").unwrap(), "-3.5");
+        assert_eq!(super::evaluate("1 << 4 | 0x0f & ~1").unwrap(), "30");
+    }
+
+    #[test]
+    fn evaluate_reports_invalid_expressions() {
+        assert_eq!(
+            super::evaluate("2 * (3 + )").unwrap_err().to_string(),
+            "Invalid expression at column 10: unexpected character ')'"
+        );
+        assert_eq!(
+            super::evaluate("1 / 0").unwrap_err().to_string(),
+            "Invalid expression at column 5: division by zero"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::util::indent;
+use scribe::buffer::{Position, Range};
+
+const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+/// Finds the block headed by the specified line (i.e. followed by lines
+/// indented further) or, failing that, the block enclosing it. Blocks span
+/// their header through the last line indented further than it, ignoring
+/// trailing blank lines, and are described by their first and last lines.
+pub fn indentation_range(data: &str, line: usize, tab_width: usize) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = data.lines().collect();
+    let level = |index: usize| if lines[index].trim().is_empty() {
+        None
+    } else {
+        Some(indent::width(lines[index], tab_width))
+    };
+    if line >= lines.len() {
+        return None;
+    }
+
+    let next_level = (line + 1..lines.len()).filter_map(level).next();
+    let header = match (level(line), next_level) {
+        (Some(current), Some(next)) if next > current => line,
+        (current, next) => {
+            // Blank lines belong to the block containing the line that follows them.
+            let current = current.or(next)?;
+            (0..line).rev().find(|&index| level(index).map(|l| l < current).unwrap_or(false))?
+        }
+    };
+    let header_level = level(header)?;
+
+    let mut end = header;
+    for index in header + 1..lines.len() {
+        match level(index) {
+            Some(l) if l <= header_level => break,
+            Some(_) => end = index,
+            None => (),
+        }
+    }
+
+    if end > header {
+        Some((header, end))
+    } else {
+        None
+    }
+}
+
+/// Finds the lines within the outermost bracket pair opened on the specified
+/// line or, failing that, the innermost pair enclosing it. The range starts
+/// with the opening line and ends before the closing one, which is left out
+/// so that it stays visible. Brackets within the ignored ranges (e.g. strings
+/// and comments) are skipped.
+pub fn bracket_range(data: &str, line: usize, ignored: &[Range]) -> Option<(usize, usize)> {
+    let pairs: Vec<(usize, usize)> = bracket_lines(data, ignored)
+        .into_iter()
+        .filter(|&(open, close)| close > open + 1)
+        .collect();
+
+    pairs.iter()
+        .filter(|&&(open, _)| open == line)
+        .max_by_key(|&&(_, close)| close)
+        .or_else(|| {
+            pairs.iter()
+                .filter(|&&(open, close)| open < line && line < close)
+                .max_by_key(|&&(open, _)| open)
+        })
+        .map(|&(open, close)| (open, close - 1))
+}
+
+/// A foldable block, described by its first and last lines, along with
+/// its depth (1 for outermost blocks, 2 for those nested within them, etc.).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+    pub depth: usize,
+}
+
+/// Finds every foldable block in the data, in ascending order. Blocks are
+/// delimited by brackets if ignored ranges are provided (i.e. the language
+/// uses braces) and any are found, and by indentation otherwise, matching
+/// the ranges found for individual lines by `bracket_range` and
+/// `indentation_range`.
+pub fn blocks(data: &str, tab_width: usize, ignored: Option<&[Range]>) -> Vec<Block> {
+    let mut ranges = ignored.map(|ignored| {
+        let mut ranges: Vec<(usize, usize)> = bracket_lines(data, ignored)
+            .into_iter()
+            .filter(|&(open, close)| close > open + 1)
+            .map(|(open, close)| (open, close - 1))
+            .collect();
+
+        // Only the outermost pair opened on each line is foldable.
+        ranges.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        ranges.dedup_by_key(|range| range.0);
+        ranges
+    }).unwrap_or_default();
+    if ranges.is_empty() {
+        ranges = indentation_blocks(data, tab_width);
+    }
+
+    // Blocks are properly nested, so any still open
+    // when one starts enclose it, determining its depth.
+    let mut enclosing: Vec<usize> = Vec::new();
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            while enclosing.last().map(|&e| e < start).unwrap_or(false) {
+                enclosing.pop();
+            }
+            enclosing.push(end);
+
+            Block { start, end, depth: enclosing.len() }
+        })
+        .collect()
+}
+
+/// The header and last lines of each indentation block, in ascending order.
+fn indentation_blocks(data: &str, tab_width: usize) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut headers: Vec<(usize, usize)> = Vec::new();
+    let mut last_line = 0;
+
+    for (line, content) in data.lines().enumerate() {
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        // Blocks end with the last non-blank line indented further than their header.
+        let level = indent::width(content, tab_width);
+        while headers.last().map(|&(_, l)| l >= level).unwrap_or(false) {
+            if let Some((header, _)) = headers.pop() {
+                if last_line > header {
+                    ranges.push((header, last_line));
+                }
+            }
+        }
+        headers.push((line, level));
+        last_line = line;
+    }
+    for (header, _) in headers {
+        if last_line > header {
+            ranges.push((header, last_line));
+        }
+    }
+    ranges.sort();
+
+    ranges
+}
+
+/// The opening and closing lines of each bracket pair in the data.
+fn bracket_lines(data: &str, ignored: &[Range]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let mut open_brackets: Vec<(char, usize)> = Vec::new();
+
+    for (line, content) in data.lines().enumerate() {
+        for (offset, c) in content.chars().enumerate() {
+            let bracket = BRACKETS.iter().find(|&&(opener, closer)| c == opener || c == closer);
+            let &(opener, _) = match bracket {
+                Some(bracket) => bracket,
+                None => continue,
+            };
+            let position = Position{ line, offset };
+            if ignored.iter().any(|range| range.start() <= position && position < range.end()) {
+                continue;
+            }
+
+            if c == opener {
+                open_brackets.push((opener, line));
+            } else if open_brackets.last().map(|&(o, _)| o == opener).unwrap_or(false) {
+                if let Some((_, open_line)) = open_brackets.pop() {
+                    pairs.push((open_line, line));
+                }
+            }
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::{Position, Range};
+    use super::{blocks, bracket_range, indentation_range, Block};
+
+    #[test]
+    fn indentation_range_folds_the_block_headed_by_the_line() {
+        let data = "def amp():\n    x = 1\n\n    y = 2\n\nprint(1)\n";
+
+        assert_eq!(indentation_range(data, 0, 4), Some((0, 3)));
+    }
+
+    #[test]
+    fn indentation_range_folds_the_enclosing_block() {
+        let data = "class Amp:\n    def edit(self):\n        pass\n    x = 1\n";
+
+        assert_eq!(indentation_range(data, 3, 4), Some((0, 3)));
+        assert_eq!(indentation_range(data, 2, 4), Some((1, 2)));
+    }
+
+    #[test]
+    fn indentation_range_returns_none_outside_of_blocks() {
+        assert_eq!(indentation_range("amp\neditor\n", 1, 4), None);
+    }
+
+    #[test]
+    fn bracket_range_folds_up_to_the_closing_line() {
+        let data = "fn amp() {\n    if x {\n        y();\n    }\n}\n";
+
+        assert_eq!(bracket_range(data, 0, &[]), Some((0, 3)));
+        assert_eq!(bracket_range(data, 2, &[]), Some((1, 2)));
+    }
+
+    #[test]
+    fn bracket_range_skips_ignored_brackets() {
+        let data = "let x = \"{\";\namp\neditor\n}\n";
+        let ignored = [Range::new(Position{ line: 0, offset: 8 }, Position{ line: 0, offset: 11 })];
+
+        assert_eq!(bracket_range(data, 0, &[]), Some((0, 2)));
+        assert_eq!(bracket_range(data, 0, &ignored), None);
+    }
+
+    #[test]
+    fn blocks_finds_nested_indentation_blocks() {
+        let data = "class Amp:\n    def edit(self):\n        pass\n\n    x = 1\nprint(1)\n";
+
+        assert_eq!(blocks(data, 4, None), vec![
+            Block { start: 0, end: 4, depth: 1 },
+            Block { start: 1, end: 2, depth: 2 },
+        ]);
+    }
+
+    #[test]
+    fn blocks_finds_nested_bracket_blocks() {
+        let data = "fn amp() {\n    if x {\n        y();\n    }\n}\nfn editor() {\n    z();\n}\n";
+
+        assert_eq!(blocks(data, 4, Some(&[])), vec![
+            Block { start: 0, end: 3, depth: 1 },
+            Block { start: 1, end: 2, depth: 2 },
+            Block { start: 5, end: 6, depth: 1 },
+        ]);
+    }
+}
@@ -0,0 +1,366 @@
+use crate::errors::*;
+
+/// Re-indents JSON data, using the provided content for each indent level.
+/// The data is validated as it's formatted; errors include the line and
+/// column (both one-based) at which the problem was found.
+pub fn json(data: &str, indent: &str) -> Result<String> {
+    let mut formatter = JsonFormatter {
+        data: data.chars().collect(),
+        index: 0,
+        indent,
+        output: String::new(),
+    };
+
+    formatter.value(0)?;
+    formatter.skip_whitespace();
+    if formatter.index < formatter.data.len() {
+        bail!(formatter.error("unexpected content after JSON value"));
+    }
+    formatter.output.push('\n');
+
+    Ok(formatter.output)
+}
+
+/// Re-indents XML data, using the provided content for each indent level.
+/// Elements containing only text are kept on a single line. Mismatched or
+/// unclosed tags are reported with the line and column (both one-based)
+/// at which they were found.
+pub fn xml(data: &str, indent: &str) -> Result<String> {
+    let characters: Vec<char> = data.chars().collect();
+    let nodes = xml_nodes(&characters)?;
+    let mut output = String::new();
+    let mut open_tags: Vec<(String, usize)> = Vec::new();
+    let mut index = 0;
+
+    while index < nodes.len() {
+        let depth = open_tags.len();
+        match nodes[index] {
+            XmlNode::Open(ref name, ref content, position) => {
+                // Keep text-only elements on a single line.
+                if let (Some(&XmlNode::Text(ref text)), Some(&XmlNode::Close(ref close_name, _))) =
+                    (nodes.get(index + 1), nodes.get(index + 2)) {
+                    if close_name == name {
+                        push_line(&mut output, indent, depth, &format!("{}{}</{}>", content, text, name));
+                        index += 3;
+                        continue;
+                    }
+                }
+
+                push_line(&mut output, indent, depth, content);
+                open_tags.push((name.clone(), position));
+            },
+            XmlNode::Close(ref name, position) => {
+                match open_tags.pop() {
+                    Some((ref open_name, _)) if open_name == name => (),
+                    Some((ref open_name, _)) => bail!(
+                        "Invalid XML at {}: expected </{}>, found </{}>",
+                        location(&characters, position), open_name, name
+                    ),
+                    None => bail!(
+                        "Invalid XML at {}: unexpected </{}>",
+                        location(&characters, position), name
+                    ),
+                }
+                push_line(&mut output, indent, open_tags.len(), &format!("</{}>", name));
+            },
+            XmlNode::Other(ref content) | XmlNode::Text(ref content) => {
+                push_line(&mut output, indent, depth, content);
+            },
+        }
+
+        index += 1;
+    }
+
+    if let Some((name, position)) = open_tags.pop() {
+        bail!("Invalid XML at {}: <{}> is never closed", location(&characters, position), name);
+    }
+
+    Ok(output)
+}
+
+struct JsonFormatter<'a> {
+    data: Vec<char>,
+    index: usize,
+    indent: &'a str,
+    output: String,
+}
+
+impl<'a> JsonFormatter<'a> {
+    fn value(&mut self, depth: usize) -> Result<()> {
+        self.skip_whitespace();
+
+        match self.current() {
+            Some('{') => self.collection(depth, '}', true),
+            Some('[') => self.collection(depth, ']', false),
+            Some('"') => self.string(),
+            Some(c) if c == '-' || c.is_digit(10) => self.number(),
+            Some(c) if c.is_alphabetic() => self.literal(),
+            Some(c) => bail!(self.error(&format!("unexpected character '{}'", c))),
+            None => bail!(self.error("unexpected end of data")),
+        }
+    }
+
+    fn collection(&mut self, depth: usize, terminator: char, keyed: bool) -> Result<()> {
+        // Copy the opening character.
+        self.output.push(self.data[self.index]);
+        self.index += 1;
+        self.skip_whitespace();
+
+        // Keep empty collections compact.
+        if self.current() == Some(terminator) {
+            self.output.push(terminator);
+            self.index += 1;
+            return Ok(());
+        }
+
+        loop {
+            self.output.push('\n');
+            self.push_indent(depth + 1);
+
+            if keyed {
+                self.skip_whitespace();
+                if self.current() != Some('"') {
+                    bail!(self.error("expected a string key"));
+                }
+                self.string()?;
+                self.skip_whitespace();
+                if self.current() != Some(':') {
+                    bail!(self.error("expected ':'"));
+                }
+                self.output.push_str(": ");
+                self.index += 1;
+            }
+            self.value(depth + 1)?;
+            self.skip_whitespace();
+
+            match self.current() {
+                Some(',') => {
+                    self.output.push(',');
+                    self.index += 1;
+                },
+                Some(c) if c == terminator => {
+                    self.output.push('\n');
+                    self.push_indent(depth);
+                    self.output.push(terminator);
+                    self.index += 1;
+
+                    return Ok(());
+                },
+                _ => bail!(self.error(&format!("expected ',' or '{}'", terminator))),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<()> {
+        let start = self.index;
+        self.index += 1;
+
+        loop {
+            match self.current() {
+                Some('"') => break,
+                Some('\\') => self.index += 2,
+                Some('\n') | None => {
+                    self.index = start;
+                    bail!(self.error("unterminated string"));
+                },
+                _ => self.index += 1,
+            }
+        }
+        self.index += 1;
+
+        let content: String = self.data[start..self.index].iter().collect();
+        self.output.push_str(&content);
+
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<()> {
+        let start = self.index;
+        while let Some(c) = self.current() {
+            if c.is_digit(10) || c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E' {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+
+        let content: String = self.data[start..self.index].iter().collect();
+        if content.parse::<f64>().is_err() {
+            self.index = start;
+            bail!(self.error(&format!("invalid number \"{}\"", content)));
+        }
+        self.output.push_str(&content);
+
+        Ok(())
+    }
+
+    fn literal(&mut self) -> Result<()> {
+        let start = self.index;
+        while self.current().map(|c| c.is_alphabetic()).unwrap_or(false) {
+            self.index += 1;
+        }
+
+        let content: String = self.data[start..self.index].iter().collect();
+        match content.as_str() {
+            "true" | "false" | "null" => self.output.push_str(&content),
+            _ => {
+                self.index = start;
+                bail!(self.error(&format!("unexpected literal \"{}\"", content)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.current().map(|c| c.is_whitespace()).unwrap_or(false) {
+            self.index += 1;
+        }
+    }
+
+    fn push_indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.output.push_str(self.indent);
+        }
+    }
+
+    fn current(&self) -> Option<char> {
+        self.data.get(self.index).cloned()
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Invalid JSON at {}: {}", location(&self.data, self.index), message)
+    }
+}
+
+enum XmlNode {
+    Open(String, String, usize),
+    Close(String, usize),
+    Text(String),
+    Other(String),
+}
+
+/// Splits XML data into tags and trimmed, non-empty text nodes.
+fn xml_nodes(data: &[char]) -> Result<Vec<XmlNode>> {
+    let mut nodes = Vec::new();
+    let mut index = 0;
+
+    while index < data.len() {
+        if data[index] == '<' {
+            let start = index;
+            let remaining: String = data[index..].iter().take(9).collect();
+            let terminator = if remaining.starts_with("<!--") {
+                "-->"
+            } else if remaining.starts_with("<![CDATA[") {
+                "]]>"
+            } else {
+                ">"
+            };
+
+            // Find the end of the tag, ignoring terminators inside quoted values.
+            let mut quote = None;
+            let mut end = None;
+            let mut cursor = index + 1;
+            while cursor < data.len() {
+                let c = data[cursor];
+                match quote {
+                    Some(q) if c == q => quote = None,
+                    Some(_) => (),
+                    None if terminator == ">" && (c == '"' || c == '\'') => quote = Some(c),
+                    None => {
+                        let candidate: String = data[cursor..].iter().take(terminator.len()).collect();
+                        if candidate == terminator {
+                            end = Some(cursor + terminator.len());
+                            break;
+                        }
+                    }
+                }
+                cursor += 1;
+            }
+            let end = end.ok_or_else(|| format!("Invalid XML at {}: unterminated tag", location(data, start)))?;
+            let content: String = data[start..end].iter().collect();
+            index = end;
+
+            if terminator != ">" || content.starts_with("<?") || content.starts_with("<!") || content.ends_with("/>") {
+                nodes.push(XmlNode::Other(content));
+            } else if content.starts_with("</") {
+                nodes.push(XmlNode::Close(tag_name(&content[2..]), start));
+            } else {
+                nodes.push(XmlNode::Open(tag_name(&content[1..]), content, start));
+            }
+        } else {
+            let start = index;
+            while index < data.len() && data[index] != '<' {
+                index += 1;
+            }
+            let content: String = data[start..index].iter().collect();
+            let text = content.trim();
+            if !text.is_empty() {
+                nodes.push(XmlNode::Text(text.to_string()));
+            }
+        }
+    }
+
+    Ok(nodes)
+}
+
+fn tag_name(content: &str) -> String {
+    content
+        .chars()
+        .take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/')
+        .collect()
+}
+
+fn push_line(output: &mut String, indent: &str, depth: usize, content: &str) {
+    for _ in 0..depth {
+        output.push_str(indent);
+    }
+    output.push_str(content);
+    output.push('\n');
+}
+
+/// Describes a character index as a one-based line and column.
+fn location(data: &[char], index: usize) -> String {
+    let preceding = &data[..index.min(data.len())];
+    let line = preceding.iter().filter(|c| **c == '\n').count() + 1;
+    let column = preceding.iter().rev().take_while(|c| **c != '\n').count() + 1;
+
+    format!("line {}, column {}", line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn json_indents_nested_collections() {
+        let data = "{\"amp\": [1, 2.5, {}], \"editor\":{\"name\" : \"a\\\"b\", \"active\":true}}";
+
+        assert_eq!(
+            super::json(data, "  ").unwrap(),
+            "{\n  \"amp\": [\n    1,\n    2.5,\n    {}\n  ],\n  \"editor\": {\n    \"name\": \"a\\\"b\",\n    \"active\": true\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn json_reports_the_location_of_invalid_data() {
+        let error = super::json("{\n  \"amp\" 1\n}", "  ").unwrap_err();
+
+        assert_eq!(error.to_string(), "Invalid JSON at line 2, column 9: expected ':'");
+    }
+
+    #[test]
+    fn xml_indents_nested_elements() {
+        let data = "<?xml version=\"1.0\"?><amp><editor name=\"a>b\"><name>amp</name><empty/></editor></amp>";
+
+        assert_eq!(
+            super::xml(data, "  ").unwrap(),
+            "<?xml version=\"1.0\"?>\n<amp>\n  <editor name=\"a>b\">\n    <name>amp</name>\n    <empty/>\n  </editor>\n</amp>\n"
+        );
+    }
+
+    #[test]
+    fn xml_reports_mismatched_tags() {
+        let error = super::xml("<amp>\n  <editor></amp>", "  ").unwrap_err();
+
+        assert_eq!(error.to_string(), "Invalid XML at line 2, column 11: expected </editor>, found </amp>");
+    }
+}
@@ -0,0 +1,103 @@
+use fragment::matching::AsStr;
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+
+/// An item matching a search query, along with the (character) indices
+/// of its matched characters, which can be used to highlight them.
+pub struct FuzzyMatch<'a, T> {
+    pub item: &'a T,
+    pub indices: Vec<usize>,
+    score: i64,
+}
+
+impl<'a, T> FuzzyMatch<'a, T> {
+    /// Wraps an item listed without a query, with nothing to highlight.
+    pub fn unmatched(item: &'a T) -> FuzzyMatch<'a, T> {
+        FuzzyMatch { item, indices: Vec::new(), score: 0 }
+    }
+}
+
+/// Finds the items matching the query using the Skim scoring algorithm, which
+/// rewards consecutive matches and those at word boundaries. The query's
+/// whitespace-separated terms are matched independently, in any order, and
+/// matching is case-insensitive. Equally scored items are ranked shortest
+/// first, and then by their order in the original collection.
+pub fn find<'a, T: AsStr>(query: &str, items: &'a [T], limit: usize) -> Vec<FuzzyMatch<'a, T>> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+
+    let mut matches: Vec<FuzzyMatch<T>> = items
+        .iter()
+        .filter_map(|item| {
+            let mut score = 0;
+            let mut indices = Vec::new();
+            for term in &terms {
+                let (term_score, term_indices) = matcher.fuzzy_indices(item.as_str(), term)?;
+                score += term_score;
+                indices.extend(term_indices);
+            }
+            indices.sort();
+            indices.dedup();
+
+            Some(FuzzyMatch { item, indices, score })
+        })
+        .collect();
+
+    // Sorting is stable, preserving the original order of otherwise equal matches.
+    matches.sort_by(|a, b| {
+        b.score.cmp(&a.score).then_with(|| {
+            a.item.as_str().chars().count().cmp(&b.item.as_str().chars().count())
+        })
+    });
+    matches.truncate(limit);
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find;
+
+    fn find_items<'a>(query: &str, items: &'a [&'a str]) -> Vec<&'a str> {
+        find(query, items, 10).into_iter().map(|result| *result.item).collect()
+    }
+
+    #[test]
+    fn find_prefers_consecutive_and_word_boundary_matches() {
+        let items = ["src/models/application.rs", "src/main.rs"];
+
+        assert_eq!(find_items("main", &items)[0], "src/main.rs");
+        assert_eq!(find_items("app", &items), vec!["src/models/application.rs"]);
+        assert_eq!(find_items("mod app", &items), vec!["src/models/application.rs"]);
+    }
+
+    #[test]
+    fn find_ranks_shorter_items_first_for_equal_scores() {
+        let items = ["src/amp.rs.bak", "src/amp.rs", "lib/amp.rs"];
+
+        assert_eq!(find_items("amp.rs", &items), vec!["src/amp.rs", "lib/amp.rs", "src/amp.rs.bak"]);
+    }
+
+    #[test]
+    fn find_matches_terms_in_any_order_and_case() {
+        let items = ["src/Buffer.rs"];
+
+        assert_eq!(find_items("rs BUF", &items), vec!["src/Buffer.rs"]);
+        assert!(find_items("buf missing", &items).is_empty());
+    }
+
+    #[test]
+    fn find_returns_the_indices_of_matched_characters() {
+        let items = ["amp.rs"];
+        let results = find("amp rs", &items, 10);
+
+        assert_eq!(results[0].indices, vec![0, 1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn find_limits_results() {
+        let items = ["amp", "amp", "amp"];
+
+        assert_eq!(find("amp", &items, 2).len(), 2);
+    }
+}
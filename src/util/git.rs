@@ -0,0 +1,183 @@
+use crate::errors::*;
+use git2::{DiffOptions, Repository, StatusOptions};
+use std::path::{Path, PathBuf};
+
+/// Describes how a line differs from its version in the git index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineChange {
+    Added,
+    Modified,
+    /// Lines were removed immediately after this one.
+    Removed,
+}
+
+// Detached HEADs are described using this many characters of the commit hash.
+const SHORT_HASH_LENGTH: usize = 7;
+
+/// The name of the checked out branch or, with a detached HEAD,
+/// the abbreviated hash of the checked out commit. Branches
+/// without any commits yet are still named.
+pub fn head_name(repo: &Repository) -> Option<String> {
+    match repo.head() {
+        Ok(head) => if head.is_branch() {
+            head.shorthand().map(String::from)
+        } else {
+            head.target().map(|id| id.to_string().chars().take(SHORT_HASH_LENGTH).collect())
+        },
+        Err(_) => {
+            // An unborn branch has no commit to point to, but HEAD still refers to it.
+            let head = repo.find_reference("HEAD").ok()?;
+            let target = head.symbolic_target()?;
+
+            Some(target.trim_start_matches("refs/heads/").to_string())
+        }
+    }
+}
+
+/// Whether any tracked files have uncommitted (staged or unstaged) changes.
+/// This scans the working tree, so it's relatively expensive to run.
+pub fn is_dirty(repo: &Repository) -> bool {
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+
+    repo.statuses(Some(&mut options))
+        .map(|statuses| !statuses.is_empty())
+        .unwrap_or(false)
+}
+
+/// Compares the file at the specified path against the git index,
+/// returning the (zero-based) lines that have changed, in order.
+pub fn line_changes(repo: &Repository, path: &Path) -> Result<Vec<(usize, LineChange)>> {
+    let relative_path = relative_path(repo, path)?;
+
+    let mut options = DiffOptions::new();
+    options.pathspec(&relative_path).context_lines(0);
+    let diff = repo.diff_index_to_workdir(None, Some(&mut options))
+        .chain_err(|| "Couldn't diff the file against the index")?;
+
+    let mut changes = Vec::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        Some(&mut |_, hunk| {
+            changes.extend(hunk_changes(
+                hunk.new_start() as usize,
+                hunk.new_lines() as usize,
+                hunk.old_lines() as usize
+            ));
+            true
+        }),
+        None
+    ).chain_err(|| "Couldn't read the file's diff")?;
+
+    Ok(changes)
+}
+
+/// The content of the file at the specified path as of the HEAD commit.
+/// Invalid UTF-8 sequences are replaced.
+pub fn head_content(repo: &Repository, path: &Path) -> Result<String> {
+    let relative_path = relative_path(repo, path)?;
+    let head_id = repo.head()
+        .chain_err(|| "Couldn't find the HEAD commit")?
+        .target()
+        .ok_or("Couldn't find the HEAD commit")?;
+    let tree = repo.find_commit(head_id)
+        .and_then(|commit| commit.tree())
+        .chain_err(|| "Couldn't read the HEAD commit")?;
+    let entry = tree.get_path(&relative_path)
+        .chain_err(|| format!("{} isn't in the HEAD commit", relative_path.display()))?;
+    let blob = repo.find_blob(entry.id())
+        .chain_err(|| format!("Couldn't read {} from the HEAD commit", relative_path.display()))?;
+
+    Ok(String::from_utf8_lossy(blob.content()).into_owned())
+}
+
+/// The path, relative to the repository's working directory. Both are
+/// canonicalized first, so that symlinks and relative components match up.
+pub fn relative_path(repo: &Repository, path: &Path) -> Result<PathBuf> {
+    let workdir = repo.workdir()
+        .ok_or("Repository doesn't have a working directory")?
+        .canonicalize()?;
+    let relative_path = path.canonicalize()?
+        .strip_prefix(&workdir)
+        .chain_err(|| "Path isn't inside the repository")?
+        .to_path_buf();
+
+    Ok(relative_path)
+}
+
+/// Maps a hunk's (one-based) start line and line counts to line changes.
+fn hunk_changes(new_start: usize, new_lines: usize, old_lines: usize) -> Vec<(usize, LineChange)> {
+    if new_lines == 0 {
+        // Removals are anchored to the line preceding them.
+        return vec![(new_start.saturating_sub(1), LineChange::Removed)];
+    }
+
+    let change = if old_lines == 0 {
+        LineChange::Added
+    } else {
+        LineChange::Modified
+    };
+    let first_line = new_start - 1;
+
+    (first_line..first_line + new_lines).map(|line| (line, change)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{head_content, head_name, hunk_changes, is_dirty, LineChange};
+    use git2::{Repository, Signature};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn head_name_uses_the_short_commit_hash_for_detached_heads() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/git_head_name"));
+        let _ = fs::remove_dir_all(&path);
+        let repo = Repository::init(&path).unwrap();
+        let signature = Signature::now("amp", "amp@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let commit_id = repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[]).unwrap();
+        assert!(!head_name(&repo).unwrap().is_empty());
+        assert!(!is_dirty(&repo));
+
+        repo.set_head_detached(commit_id).unwrap();
+        assert_eq!(head_name(&repo), Some(commit_id.to_string()[..7].to_string()));
+
+        // Staged changes count as uncommitted.
+        fs::write(path.join("amp.txt"), "amp").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("amp.txt")).unwrap();
+        index.write().unwrap();
+        assert!(is_dirty(&repo));
+    }
+
+    #[test]
+    fn head_content_reads_the_committed_version_of_the_file() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/git_head_content"));
+        let _ = fs::remove_dir_all(&path);
+        let repo = Repository::init(&path).unwrap();
+        let signature = Signature::now("amp", "amp@example.com").unwrap();
+        fs::write(path.join("amp.txt"), "committed").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("amp.txt")).unwrap();
+        index.write().unwrap();
+        let tree_id = index.write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[]).unwrap();
+
+        fs::write(path.join("amp.txt"), "modified").unwrap();
+        assert_eq!(head_content(&repo, &path.join("amp.txt")).unwrap(), "committed");
+        fs::write(path.join("new.txt"), "new").unwrap();
+        assert!(head_content(&repo, &path.join("new.txt")).is_err());
+    }
+
+    #[test]
+    fn hunk_changes_classifies_added_modified_and_removed_lines() {
+        assert_eq!(hunk_changes(3, 2, 0), vec![(2, LineChange::Added), (3, LineChange::Added)]);
+        assert_eq!(hunk_changes(5, 1, 2), vec![(4, LineChange::Modified)]);
+        assert_eq!(hunk_changes(7, 0, 3), vec![(6, LineChange::Removed)]);
+        assert_eq!(hunk_changes(0, 0, 1), vec![(0, LineChange::Removed)]);
+    }
+}
@@ -0,0 +1,64 @@
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Converts a position to an absolute grapheme index,
+/// counting line breaks as a single grapheme.
+pub fn position_index(data: &str, position: &Position) -> usize {
+    let mut index = 0;
+
+    for (line_number, line) in data.split('\n').enumerate() {
+        let length = line.graphemes(true).count();
+        if line_number == position.line {
+            return index + position.offset.min(length);
+        }
+        index += length + 1;
+    }
+
+    // The position is beyond the end of the data.
+    index.saturating_sub(1)
+}
+
+/// Converts an absolute grapheme index (see position_index) to a position,
+/// clamping indices beyond the end of the data to its last position.
+pub fn index_position(data: &str, index: usize) -> Position {
+    let mut remaining = index;
+    let mut last = Position{ line: 0, offset: 0 };
+
+    for (line_number, line) in data.split('\n').enumerate() {
+        let length = line.graphemes(true).count();
+        if remaining <= length {
+            return Position{ line: line_number, offset: remaining };
+        }
+        remaining -= length + 1;
+        last = Position{ line: line_number, offset: length };
+    }
+
+    last
+}
+
+/// The number of graphemes in the data, as counted by position_index.
+pub fn content_length(data: &str) -> usize {
+    data.split('\n').map(|line| line.graphemes(true).count() + 1).sum::<usize>() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_length, index_position, position_index};
+    use scribe::buffer::Position;
+
+    #[test]
+    fn position_index_and_index_position_round_trip() {
+        let data = "amp\néditor\n";
+        for &(line, offset, index) in &[(0, 0, 0), (0, 3, 3), (1, 0, 4), (1, 6, 10), (2, 0, 11)] {
+            let position = Position{ line, offset };
+            assert_eq!(position_index(data, &position), index);
+            assert_eq!(index_position(data, index), position);
+        }
+        assert_eq!(content_length(data), 11);
+    }
+
+    #[test]
+    fn index_position_clamps_to_the_end_of_the_data() {
+        assert_eq!(index_position("amp\neditor", 50), Position{ line: 1, offset: 6 });
+    }
+}
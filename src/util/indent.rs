@@ -0,0 +1,216 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The visual width of a line's leading whitespace, expanding tabs
+/// to the next multiple of the specified tab width.
+pub fn width(line: &str, tab_width: usize) -> usize {
+    line.chars()
+        .take_while(|&c| c == ' ' || c == '\t')
+        .fold(0, |width, c| if c == '\t' {
+            width + tab_width - width % tab_width.max(1)
+        } else {
+            width + 1
+        })
+}
+
+/// Builds leading whitespace of the specified visual width. Hard tabs are
+/// used for as much of it as possible, unless soft tabs are enabled.
+pub fn content(width: usize, tab_width: usize, soft_tabs: bool) -> String {
+    if soft_tabs || tab_width == 0 {
+        " ".repeat(width)
+    } else {
+        format!("{}{}", "\t".repeat(width / tab_width), " ".repeat(width % tab_width))
+    }
+}
+
+/// Shifts a block of lines so that its shallowest line is indented to the
+/// target width, preserving relative indentation and normalizing leading
+/// whitespace to the specified style. Blank lines are emptied.
+pub fn reindent(data: &str, target_width: usize, tab_width: usize, soft_tabs: bool) -> String {
+    let is_blank = |line: &str| line.trim().is_empty();
+    let shallowest_width = data.split('\n')
+        .filter(|line| !is_blank(line))
+        .map(|line| width(line, tab_width))
+        .min()
+        .unwrap_or(0);
+
+    data.split('\n')
+        .map(|line| if is_blank(line) {
+            String::new()
+        } else {
+            let relative_width = width(line, tab_width) - shallowest_width;
+            format!(
+                "{}{}",
+                content(target_width + relative_width, tab_width, soft_tabs),
+                line.trim_start_matches(|c| c == ' ' || c == '\t')
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Rebuilds a line's leading whitespace in the specified style,
+/// preserving its visual width. The rest of the line is left as-is.
+pub fn retab(line: &str, tab_width: usize, soft_tabs: bool) -> String {
+    let rest = line.trim_start_matches(|c| c == ' ' || c == '\t');
+    format!("{}{}", content(width(line, tab_width), tab_width, soft_tabs), rest)
+}
+
+/// The visual column at which the grapheme at the specified offset starts,
+/// expanding tabs to the next multiple of the specified tab width.
+pub fn column(line: &str, offset: usize, tab_width: usize) -> usize {
+    line.graphemes(true)
+        .take(offset)
+        .fold(0, |column, grapheme| advance(column, grapheme, tab_width))
+}
+
+/// The offset of the grapheme spanning the specified visual column, or the
+/// line's length if it ends before reaching it. This is the inverse of `column`.
+pub fn offset(line: &str, column: usize, tab_width: usize) -> usize {
+    let mut end = 0;
+    for (offset, grapheme) in line.graphemes(true).enumerate() {
+        end = advance(end, grapheme, tab_width);
+        if end > column {
+            return offset;
+        }
+    }
+
+    line.graphemes(true).count()
+}
+
+fn advance(column: usize, grapheme: &str, tab_width: usize) -> usize {
+    if grapheme == "\t" {
+        column + tab_width - column % tab_width.max(1)
+    } else {
+        column + 1
+    }
+}
+
+/// Replaces every tab in a line with the spaces needed to reach the next tab stop.
+pub fn expand_tabs(line: &str, tab_width: usize) -> String {
+    let mut result = String::new();
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = tab_width - column % tab_width.max(1);
+            result.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            result.push(c);
+            column += 1;
+        }
+    }
+
+    result
+}
+
+/// Replaces runs of two or more spaces (and existing tabs) ending at a tab
+/// stop with tabs, so that text after them stays aligned at the same column.
+pub fn unexpand_spaces(line: &str, tab_width: usize) -> String {
+    if tab_width == 0 {
+        return line.to_string();
+    }
+
+    let mut result = String::new();
+    let mut spaces = 0;
+    for (index, c) in expand_tabs(line, tab_width).chars().enumerate() {
+        if c == ' ' {
+            spaces += 1;
+            if (index + 1) % tab_width == 0 {
+                result.push(if spaces > 1 { '\t' } else { ' ' });
+                spaces = 0;
+            }
+        } else {
+            result.push_str(&" ".repeat(spaces));
+            result.push(c);
+            spaces = 0;
+        }
+    }
+    result.push_str(&" ".repeat(spaces));
+
+    result
+}
+
+/// The indentation applied to the continuation rows of a wrapped line,
+/// matching its leading whitespace, but never exceeding half of the width
+/// available to its content, so that each row has room for some of it.
+pub fn wrap_width(line: &str, tab_width: usize, content_width: usize) -> usize {
+    width(line, tab_width).min(content_width / 2)
+}
+
+/// Whether the named syntax delimits blocks with braces, such that
+/// lines following an opening brace should be indented a level deeper.
+pub fn uses_braces(syntax_name: &str) -> bool {
+    matches!(
+        syntax_name,
+        "C" | "C++" | "C#" | "CSS" | "D" | "Go" | "Groovy" | "Java" |
+        "JavaScript" | "JSON" | "Kotlin" | "Less" | "Objective-C" |
+        "Objective-C++" | "PHP" | "Rust" | "Scala" | "SCSS" | "Swift" |
+        "TypeScript" | "TypeScriptReact"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{column, content, expand_tabs, offset, reindent, retab, unexpand_spaces, uses_braces, width, wrap_width};
+
+    #[test]
+    fn width_expands_tabs_to_tab_stops() {
+        assert_eq!(width("  \tamp", 4), 4);
+        assert_eq!(width("\t  amp", 4), 6);
+        assert_eq!(width("amp", 4), 0);
+    }
+
+    #[test]
+    fn wrap_width_is_limited_to_half_of_the_content_width() {
+        assert_eq!(wrap_width("    amp", 4, 20), 4);
+        assert_eq!(wrap_width("\t\tamp", 4, 10), 5);
+    }
+
+    #[test]
+    fn content_uses_hard_tabs_unless_soft_tabs_are_enabled() {
+        assert_eq!(content(6, 4, false), "\t  ");
+        assert_eq!(content(6, 4, true), "      ");
+    }
+
+    #[test]
+    fn reindent_aligns_the_shallowest_line_and_preserves_relative_indentation() {
+        assert_eq!(
+            reindent("\t\tif amp {\n\t\t    editor();\n\n\t\t}\n", 4, 4, true),
+            "    if amp {\n        editor();\n\n    }\n"
+        );
+    }
+
+    #[test]
+    fn uses_braces_recognizes_brace_delimited_languages() {
+        assert!(uses_braces("Rust"));
+        assert!(!uses_braces("Python"));
+    }
+
+    #[test]
+    fn retab_converts_only_leading_whitespace() {
+        assert_eq!(retab("\t  amp\teditor", 4, true), "      amp\teditor");
+        assert_eq!(retab("      amp  editor", 4, false), "\t  amp  editor");
+    }
+
+    #[test]
+    fn column_and_offset_map_between_graphemes_and_tab_stops() {
+        assert_eq!(column("a\tb", 2, 4), 4);
+        assert_eq!(column("a\tb", 1, 4), 1);
+        assert_eq!(offset("a\tb", 2, 4), 1);
+        assert_eq!(offset("a\tb", 4, 4), 2);
+        assert_eq!(offset("a\tb", 9, 4), 3);
+    }
+
+    #[test]
+    fn expand_tabs_aligns_to_tab_stops() {
+        assert_eq!(expand_tabs("\tamp\teditor", 4), "    amp editor");
+        assert_eq!(expand_tabs("  \tamp", 4), "    amp");
+    }
+
+    #[test]
+    fn unexpand_spaces_preserves_alignment() {
+        assert_eq!(unexpand_spaces("        am  = 1", 4), "\t\tam\t= 1");
+        assert_eq!(unexpand_spaces("amp editor", 4), "amp editor");
+        assert_eq!(unexpand_spaces("  \tamp", 4), "\tamp");
+    }
+}
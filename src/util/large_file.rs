@@ -0,0 +1,226 @@
+use crate::errors::*;
+use scribe::buffer::Position;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The number of bytes read at a time while indexing a file's lines.
+const INDEX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of lines read at a time while searching.
+const SEARCH_BLOCK_SIZE: usize = 10_000;
+
+/// A read-only file too large to be loaded into a buffer. Only the offsets
+/// at which its lines start are kept in memory; line content is read from
+/// disk as it's needed (e.g. to display the visible lines).
+pub struct LargeFile {
+    path: PathBuf,
+    line_offsets: Vec<u64>,
+    length: u64,
+}
+
+impl LargeFile {
+    /// Indexes the file's lines by streaming through it in chunks.
+    pub fn open(path: &Path) -> Result<LargeFile> {
+        let file = File::open(path)
+            .chain_err(|| format!("Couldn't open {}", path.display()))?;
+        let mut reader = BufReader::new(file);
+        let mut line_offsets = vec![0];
+        let mut chunk = vec![0; INDEX_CHUNK_SIZE];
+        let mut length = 0;
+
+        loop {
+            let read = reader.read(&mut chunk).chain_err(|| format!("Couldn't read {}", path.display()))?;
+            if read == 0 {
+                break;
+            }
+            for (index, byte) in chunk[..read].iter().enumerate() {
+                if *byte == b'\n' {
+                    line_offsets.push(length + index as u64 + 1);
+                }
+            }
+            length += read as u64;
+        }
+
+        // A trailing newline ends the last line, rather than starting another.
+        if line_offsets.len() > 1 && line_offsets.last() == Some(&length) {
+            line_offsets.pop();
+        }
+
+        Ok(LargeFile { path: path.to_path_buf(), line_offsets, length })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_offsets.len()
+    }
+
+    /// Reads up to count lines, starting at the specified (zero-based) line,
+    /// without their line endings. Invalid UTF-8 sequences are replaced.
+    pub fn lines(&self, start: usize, count: usize) -> Result<Vec<String>> {
+        if start >= self.line_count() || count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let start_offset = self.line_offsets[start];
+        let end_offset = self.line_offsets.get(start + count).cloned().unwrap_or(self.length);
+        let mut file = File::open(&self.path)
+            .chain_err(|| format!("Couldn't open {}", self.path.display()))?;
+        file.seek(SeekFrom::Start(start_offset))
+            .chain_err(|| format!("Couldn't read {}", self.path.display()))?;
+        let mut bytes = Vec::new();
+        file.take(end_offset - start_offset)
+            .read_to_end(&mut bytes)
+            .chain_err(|| format!("Couldn't read {}", self.path.display()))?;
+
+        let content = String::from_utf8_lossy(&bytes);
+        let content = if content.ends_with('\n') {
+            &content[..content.len() - 1]
+        } else {
+            &content[..]
+        };
+
+        Ok(content.split('\n').map(|line| line.trim_end_matches('\r').to_string()).collect())
+    }
+
+    /// Finds the first occurrence of the query following the position,
+    /// wrapping around to the start of the file. Lines are read in blocks,
+    /// so that the file is never loaded into memory all at once.
+    pub fn find_next(&self, query: &str, position: &Position) -> Result<Option<Position>> {
+        // The rest of the current line is searched first,
+        // and the portion preceding the position, last.
+        let line_count = self.line_count();
+        let mut start = position.line;
+        for wrapped in &[false, true] {
+            let end = if *wrapped { position.line + 1 } else { line_count };
+            while start < end {
+                let lines = self.lines(start, SEARCH_BLOCK_SIZE.min(end - start))?;
+                for (index, line) in lines.iter().enumerate() {
+                    let line_number = start + index;
+                    let min_offset = if line_number == position.line && !*wrapped {
+                        position.offset + 1
+                    } else {
+                        0
+                    };
+                    let found = matches(line, query)
+                        .into_iter()
+                        .find(|&offset| offset >= min_offset && (!*wrapped || line_number != position.line || offset <= position.offset));
+                    if let Some(offset) = found {
+                        return Ok(Some(Position { line: line_number, offset }));
+                    }
+                }
+                start += lines.len().max(1);
+            }
+            start = 0;
+        }
+
+        Ok(None)
+    }
+
+    /// Finds the last occurrence of the query preceding the
+    /// position, wrapping around to the end of the file.
+    pub fn find_previous(&self, query: &str, position: &Position) -> Result<Option<Position>> {
+        let line_count = self.line_count();
+        let mut end = position.line + 1;
+        for wrapped in &[false, true] {
+            let start_limit = if *wrapped { position.line } else { 0 };
+            while end > start_limit {
+                let start = end.saturating_sub(SEARCH_BLOCK_SIZE).max(start_limit);
+                let lines = self.lines(start, end - start)?;
+                for (index, line) in lines.iter().enumerate().rev() {
+                    let line_number = start + index;
+                    let found = matches(line, query).into_iter().rev().find(|&offset| {
+                        line_number != position.line ||
+                            (!*wrapped && offset < position.offset) ||
+                            (*wrapped && offset >= position.offset)
+                    });
+                    if let Some(offset) = found {
+                        return Ok(Some(Position { line: line_number, offset }));
+                    }
+                }
+                end = start;
+            }
+            end = line_count;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Whether the file is larger than the specified number of bytes.
+pub fn exceeds(path: &Path, threshold: u64) -> bool {
+    fs::metadata(path).map(|metadata| metadata.len() > threshold).unwrap_or(false)
+}
+
+/// The (grapheme) offsets at which the query occurs in the line.
+fn matches(line: &str, query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    line.match_indices(query)
+        .map(|(index, _)| line[..index].graphemes(true).count())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use std::fs;
+    use std::path::Path;
+    use super::LargeFile;
+
+    fn large_file(name: &str, content: &str) -> LargeFile {
+        let path = Path::new(env!("OUT_DIR")).join(name);
+        fs::write(&path, content).unwrap();
+
+        LargeFile::open(&path).unwrap()
+    }
+
+    #[test]
+    fn lines_reads_the_requested_range() {
+        let file = large_file("amp_large_file_lines", "first\r\nsecond\nthird\n");
+
+        assert_eq!(file.line_count(), 3);
+        assert_eq!(file.lines(1, 5).unwrap(), vec!["second", "third"]);
+        assert_eq!(file.lines(0, 1).unwrap(), vec!["first"]);
+        assert!(file.lines(3, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn find_next_wraps_around_to_the_start() {
+        let file = large_file("amp_large_file_find_next", "amp\neditor\namp amp\n");
+
+        assert_eq!(
+            file.find_next("amp", &Position{ line: 0, offset: 0 }).unwrap(),
+            Some(Position{ line: 2, offset: 0 })
+        );
+        assert_eq!(
+            file.find_next("amp", &Position{ line: 2, offset: 0 }).unwrap(),
+            Some(Position{ line: 2, offset: 4 })
+        );
+        assert_eq!(
+            file.find_next("amp", &Position{ line: 2, offset: 4 }).unwrap(),
+            Some(Position{ line: 0, offset: 0 })
+        );
+        assert_eq!(file.find_next("vim", &Position{ line: 0, offset: 0 }).unwrap(), None);
+    }
+
+    #[test]
+    fn find_previous_wraps_around_to_the_end() {
+        let file = large_file("amp_large_file_find_previous", "amp\neditor\namp amp\n");
+
+        assert_eq!(
+            file.find_previous("amp", &Position{ line: 2, offset: 4 }).unwrap(),
+            Some(Position{ line: 2, offset: 0 })
+        );
+        assert_eq!(
+            file.find_previous("amp", &Position{ line: 0, offset: 0 }).unwrap(),
+            Some(Position{ line: 2, offset: 4 })
+        );
+    }
+}
@@ -0,0 +1,108 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn name(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    pub fn sequence(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// The line ending used by a buffer, and whether
+/// it also contains lines ending in other ways.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineEndings {
+    pub ending: LineEnding,
+    pub mixed: bool,
+}
+
+/// Finds the most common line ending in the data. Content without
+/// any line breaks is assumed to use LF, as are ties involving it.
+pub fn detect(data: &str) -> LineEndings {
+    let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+    let mut characters = data.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\n' => lf += 1,
+            '\r' if characters.peek() == Some(&'\n') => {
+                characters.next();
+                crlf += 1;
+            }
+            '\r' => cr += 1,
+            _ => (),
+        }
+    }
+
+    let ending = if lf >= crlf && lf >= cr {
+        LineEnding::Lf
+    } else if crlf >= cr {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Cr
+    };
+    let kinds = [lf, crlf, cr].iter().filter(|&&count| count > 0).count();
+
+    LineEndings { ending, mixed: kinds > 1 }
+}
+
+/// Replaces every line break in the data with the specified line ending.
+pub fn convert(data: &str, ending: LineEnding) -> String {
+    let mut converted = String::with_capacity(data.len());
+    let mut characters = data.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\n' => converted.push_str(ending.sequence()),
+            '\r' => {
+                if characters.peek() == Some(&'\n') {
+                    characters.next();
+                }
+                converted.push_str(ending.sequence());
+            }
+            c => converted.push(c),
+        }
+    }
+
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert, detect, LineEnding, LineEndings};
+
+    #[test]
+    fn detect_finds_the_most_common_line_ending() {
+        assert_eq!(detect("amp\r\neditor\r\n"), LineEndings { ending: LineEnding::Crlf, mixed: false });
+        assert_eq!(detect("amp\reditor\r"), LineEndings { ending: LineEnding::Cr, mixed: false });
+        assert_eq!(detect("amp"), LineEndings { ending: LineEnding::Lf, mixed: false });
+    }
+
+    #[test]
+    fn detect_flags_mixed_line_endings() {
+        assert_eq!(
+            detect("amp\r\neditor\r\nbuffer\n"),
+            LineEndings { ending: LineEnding::Crlf, mixed: true }
+        );
+    }
+
+    #[test]
+    fn convert_replaces_all_line_endings() {
+        assert_eq!(convert("amp\r\neditor\nbuffer\r", LineEnding::Lf), "amp\neditor\nbuffer\n");
+        assert_eq!(convert("amp\neditor\r\n", LineEnding::Crlf), "amp\r\neditor\r\n");
+        assert_eq!(convert("amp\r\neditor\n", LineEnding::Cr), "amp\reditor\r");
+    }
+}
@@ -0,0 +1,219 @@
+use crate::errors::*;
+use serde_json::{self, Value};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+const CONTENT_LENGTH_HEADER: &str = "content-length:";
+
+/// A problem reported by a language server for a line of a file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The zero-based line the problem starts on.
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+// Ordered from most to least severe.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    /// The gutter sign used to flag lines with diagnostics of this severity.
+    pub fn sign(self) -> &'static str {
+        match self {
+            Severity::Error => "E",
+            Severity::Warning => "W",
+            Severity::Information => "I",
+            Severity::Hint => "H",
+        }
+    }
+
+    fn from_code(code: u64) -> Severity {
+        match code {
+            2 => Severity::Warning,
+            3 => Severity::Information,
+            4 => Severity::Hint,
+            _ => Severity::Error,
+        }
+    }
+}
+
+pub fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// Frames the message with the Content-Length header that language servers expect.
+pub fn encode(message: &Value) -> String {
+    let content = message.to_string();
+
+    format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+}
+
+/// Reads the next framed message, returning None once the stream has ended.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        // Headers are separated from the content by an empty line.
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if header.to_lowercase().starts_with(CONTENT_LENGTH_HEADER) {
+            content_length = header[CONTENT_LENGTH_HEADER.len()..].trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = content_length.ok_or("Language server message is missing its length")?;
+    let mut content = vec![0; length];
+    reader.read_exact(&mut content)?;
+
+    serde_json::from_slice(&content)
+        .chain_err(|| "Couldn't parse language server message")
+        .map(Some)
+}
+
+/// Builds a file URI for the path, escaping characters that aren't allowed in URIs.
+pub fn path_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+/// The path referred to by a file URI, or None if it's not a file URI.
+pub fn uri_path(uri: &str) -> Option<PathBuf> {
+    if !uri.starts_with("file://") {
+        return None;
+    }
+
+    let encoded = uri["file://".len()..].as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+    while index < encoded.len() {
+        let escaped = encoded.get(index + 1..index + 3)
+            .filter(|_| encoded[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                index += 3;
+            }
+            None => {
+                bytes.push(encoded[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// Reads the path and diagnostics from the parameters of a
+/// textDocument/publishDiagnostics notification, ordered by line.
+pub fn parse_diagnostics(params: &Value) -> Option<(PathBuf, Vec<Diagnostic>)> {
+    let path = uri_path(params["uri"].as_str()?)?;
+    let mut diagnostics: Vec<Diagnostic> = params["diagnostics"]
+        .as_array()?
+        .iter()
+        .filter_map(|diagnostic| {
+            Some(Diagnostic {
+                line: diagnostic["range"]["start"]["line"].as_u64()? as usize,
+                severity: Severity::from_code(diagnostic["severity"].as_u64().unwrap_or(1)),
+                message: diagnostic["message"].as_str()?.to_string(),
+            })
+        })
+        .collect();
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+
+    Some((path, diagnostics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, Severity, encode, parse_diagnostics, path_uri, read_message, uri_path};
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn read_message_reads_encoded_messages() {
+        let message = json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} });
+        let mut reader = Cursor::new(format!("{}{}", encode(&message), encode(&message)));
+
+        assert_eq!(read_message(&mut reader).unwrap(), Some(message.clone()));
+        assert_eq!(read_message(&mut reader).unwrap(), Some(message));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn path_uri_and_uri_path_escape_and_unescape_paths() {
+        let uri = path_uri(Path::new("/amp/src/my file.rs"));
+
+        assert_eq!(uri, "file:///amp/src/my%20file.rs");
+        assert_eq!(uri_path(&uri), Some(PathBuf::from("/amp/src/my file.rs")));
+        assert_eq!(uri_path("https://amp.rs"), None);
+    }
+
+    #[test]
+    fn parse_diagnostics_orders_diagnostics_by_line() {
+        let params = json!({
+            "uri": "file:///amp/src/main.rs",
+            "diagnostics": [
+                {
+                    "range": { "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 5 } },
+                    "severity": 2,
+                    "message": "unused variable"
+                },
+                {
+                    "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 3 } },
+                    "message": "expected expression"
+                }
+            ]
+        });
+
+        assert_eq!(
+            parse_diagnostics(&params),
+            Some((
+                PathBuf::from("/amp/src/main.rs"),
+                vec![
+                    Diagnostic {
+                        line: 1,
+                        severity: Severity::Error,
+                        message: String::from("expected expression"),
+                    },
+                    Diagnostic {
+                        line: 4,
+                        severity: Severity::Warning,
+                        message: String::from("unused variable"),
+                    },
+                ]
+            ))
+        );
+    }
+}
@@ -1,12 +1,44 @@
 pub use self::selectable_vec::SelectableVec;
 
+pub mod comment;
+pub mod completion;
+pub mod conflict;
+pub mod ctags;
+pub mod delimiter;
+pub mod diff;
+pub mod directive;
+pub mod editor_config;
+pub mod encoding;
+pub mod expression;
+pub mod fold;
+pub mod format;
+pub mod fuzzy;
+pub mod git;
+pub mod grapheme_index;
+pub mod indent;
+pub mod large_file;
+pub mod line_ending;
+pub mod lsp;
 pub mod movement_lexer;
+pub mod number;
 mod selectable_vec;
+pub mod snippet;
+pub mod sort;
+pub mod spell;
+pub mod swap;
+pub mod template;
+pub mod text_object;
 pub mod token;
 
 use crate::errors::*;
 use crate::models::Application;
+use crate::models::application::Mode;
+use crate::models::application::modes::LargeFileMode;
+use self::large_file::LargeFile;
+use scribe::Workspace;
 use scribe::buffer::{Buffer, LineRange, Position, Range};
+use std::env;
+use std::path::{Path, PathBuf};
 
 /// Translates a line range to a regular range, including its last line.
 /// Handles ranges including and end line without trailing newline character.
@@ -49,18 +81,244 @@ pub fn inclusive_range(line_range: &LineRange, buffer: &mut Buffer) -> Range {
                end_position)
 }
 
-/// Convenience method to initialize and add a buffer to the workspace.
+/// Convenience method to initialize and add a buffer to the workspace. If
+/// buffers are being reused and one is already open for the same file (e.g.
+/// via a symlink), it's selected instead, and the new buffer is discarded.
 pub fn add_buffer(buffer: Buffer, app: &mut Application) -> Result<()> {
+    if let Some(ref path) = buffer.path {
+        if app.preferences.borrow().reuse_open_buffers() && select_buffer(path, app) {
+            return Ok(());
+        }
+        app.preferences.borrow_mut().load_editor_config(path);
+    }
     app.workspace.add_buffer(buffer);
-    app.view.initialize_buffer(app.workspace.current_buffer().unwrap())?;
+    let buffer = app.workspace.current_buffer().unwrap();
+    app.preferences.borrow_mut().set_syntax(buffer);
+    app.view.initialize_buffer(buffer)?;
+    if let Some(ref path) = buffer.path {
+        app.file_watcher.watch(path);
+    }
+
+    Ok(())
+}
+
+/// Opens a buffer for the file (decoding it, if it isn't UTF-8),
+/// adds it to the workspace, and remembers its encoding for saving.
+/// Already open files are selected instead, if buffers are being reused.
+/// Files above the large file threshold are viewed in large file mode,
+/// leaving the current buffer as-is.
+pub fn open_buffer(path: &Path, app: &mut Application) -> Result<()> {
+    // Files too large to load are viewed by reading their lines on
+    // demand; there's no buffer to edit, so they're always read-only.
+    if large_file::exceeds(path, app.preferences.borrow().large_file_threshold()) {
+        app.mode = Mode::LargeFile(LargeFileMode::new(LargeFile::open(path)?));
+        return Ok(());
+    }
+
+    if app.preferences.borrow().reuse_open_buffers() && select_buffer(path, app) {
+        return Ok(());
+    }
+
+    let (buffer, source_encoding) = encoding::read_buffer(path)?;
+    add_buffer(buffer, app)?;
+    let buffer = app.workspace.current_buffer().unwrap();
+
+    app.view.set_encoding(buffer, source_encoding)
+}
+
+/// Refuses to modify read-only buffers (see View::set_read_only). Commands
+/// that edit the current buffer call this before doing anything else, so
+/// that refused edits leave the buffer, cursor, and mode untouched.
+pub fn ensure_writable(app: &mut Application) -> Result<()> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if app.view.read_only(buffer) {
+        bail!("Buffer is read-only");
+    }
 
     Ok(())
 }
 
+/// Maps invisible control characters, byte order marks, and non-breaking
+/// spaces to a visible representation. Tabs and line-ending characters
+/// are left alone.
+pub fn control_character_symbol(character: char) -> Option<String> {
+    match character {
+        '\t' | '\n' | '\r' => None,
+        '\u{feff}' => Some(String::from("<BOM>")),
+        '\u{a0}' => Some(String::from("·")),
+        '\u{7f}' => Some(String::from("^?")),
+        c if (c as u32) < 0x20 => Some(format!("^{}", ((c as u8) + b'@') as char)),
+        _ => None,
+    }
+}
+
+/// Makes the open buffer pointing to the specified path the current one,
+/// comparing canonical paths. Returns false, leaving the current buffer
+/// unchanged, if no such buffer is open.
+pub fn select_buffer(path: &Path, app: &mut Application) -> bool {
+    select_workspace_buffer(path, &mut app.workspace)
+}
+
+/// Like select_buffer, for use before the application has been initialized.
+pub fn select_workspace_buffer(path: &Path, workspace: &mut Workspace) -> bool {
+    let target_path = canonical_path(path);
+
+    select_buffer_matching(workspace, |buffer| {
+        buffer.path.as_ref().map(|p| canonical_path(p) == target_path).unwrap_or(false)
+    })
+}
+
+/// Makes the open buffer with the specified id the current one. Returns
+/// false, leaving the current buffer unchanged, if no such buffer is open.
+pub fn select_buffer_by_id(id: usize, app: &mut Application) -> bool {
+    select_buffer_matching(&mut app.workspace, |buffer| buffer.id == Some(id))
+}
+
+fn select_buffer_matching<F: Fn(&Buffer) -> bool>(workspace: &mut Workspace, predicate: F) -> bool {
+    let original_id = match workspace.current_buffer() {
+        Some(buffer) => buffer.id,
+        None => return false,
+    };
+
+    loop {
+        let found = workspace
+            .current_buffer()
+            .map(|buffer| predicate(buffer))
+            .unwrap_or(false);
+        if found {
+            return true;
+        }
+
+        // Advance to the next buffer, stopping once we've looped around.
+        workspace.next_buffer();
+        if workspace.current_buffer().map(|b| b.id) == Some(original_id) {
+            return false;
+        }
+    }
+}
+
+/// Resolves symlinks and relative components, falling back to the original
+/// path if it can't be resolved (e.g. a new buffer, or a broken or circular
+/// symlink).
+pub fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Formats a path for display: relative to the workspace when it's beneath
+/// it, abbreviating the home directory to "~" when it's beneath that, and in
+/// full otherwise. Paths on another drive or root share neither prefix, so
+/// they're always shown in full, rather than with "../" components.
+pub fn display_path(path: &Path, workspace_path: &Path) -> String {
+    if let Ok(relative_path) = path.strip_prefix(workspace_path) {
+        if !relative_path.as_os_str().is_empty() {
+            return relative_path.to_string_lossy().into_owned();
+        }
+    }
+    if let Some(home) = env::var_os("HOME") {
+        if let Ok(relative_path) = path.strip_prefix(&home) {
+            return if relative_path.as_os_str().is_empty() {
+                String::from("~")
+            } else {
+                format!("~/{}", relative_path.to_string_lossy())
+            };
+        }
+    }
+
+    path.to_string_lossy().into_owned()
+}
+
+/// Splits a trailing ":line" or ":line:column" suffix (both one-based) from a
+/// path argument, returning the path and a zero-based cursor position. Paths
+/// that exist as specified are used literally, even if they contain colons.
+pub fn parse_path_argument(argument: &str) -> (PathBuf, Option<Position>) {
+    if Path::new(argument).exists() {
+        return (PathBuf::from(argument), None);
+    }
+
+    let is_number = |segment: &str| !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit());
+    let segments: Vec<&str> = argument.rsplitn(3, ':').collect();
+    let (path, line, column) = if segments.len() == 3 && is_number(segments[1]) && is_number(segments[0]) {
+        (segments[2], segments[1], segments[0])
+    } else if segments.len() > 1 && is_number(segments[0]) {
+        (&argument[..argument.len() - segments[0].len() - 1], segments[0], "1")
+    } else {
+        return (PathBuf::from(argument), None);
+    };
+
+    if path.is_empty() {
+        return (PathBuf::from(argument), None);
+    }
+
+    // Numbers too large to parse are out of range, and clamped later.
+    let index = |number: &str| number.parse::<usize>().unwrap_or(usize::max_value()).saturating_sub(1);
+    let position = Position {
+        line: index(line),
+        offset: index(column),
+    };
+
+    (PathBuf::from(path), Some(position))
+}
+
+/// Resolves a path argument (with an optional line/column suffix) against each
+/// of the base directories in turn, returning the first existing file found.
+pub fn resolve_path_argument(argument: &str, bases: &[PathBuf]) -> Option<(PathBuf, Option<Position>)> {
+    let literal_path = bases.iter()
+        .map(|base| base.join(argument))
+        .find(|path| path.is_file());
+    if let Some(path) = literal_path {
+        return Some((path, None));
+    }
+
+    let (path, position) = parse_path_argument(argument);
+    let position = position?;
+
+    bases.iter()
+        .map(|base| base.join(&path))
+        .find(|path| path.is_file())
+        .map(|path| (path, Some(position)))
+}
+
+/// Moves the cursor to the specified position, clamping it to the last line
+/// and the end of the target line, rather than failing when out of range.
+pub fn move_cursor_within_bounds(buffer: &mut Buffer, position: Position) {
+    let data = buffer.data();
+    let last_line = data.lines().count().saturating_sub(1);
+    let line = position.line.min(last_line);
+    let line_length = data.lines().nth(line).map(|l| l.chars().count()).unwrap_or(0);
+
+    buffer.cursor.move_to(Position {
+        line,
+        offset: position.offset.min(line_length),
+    });
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::models::Application;
+    use crate::models::application::{Mode, Preferences};
     use scribe::Buffer;
     use scribe::buffer::{LineRange, Position, Range};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn display_path_is_relative_to_the_workspace_or_home_directory() {
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        let workspace = home.join("project");
+
+        assert_eq!(super::display_path(&workspace.join("src/main.rs"), &workspace), "src/main.rs");
+        assert_eq!(super::display_path(&home.join("notes.txt"), &workspace), "~/notes.txt");
+        assert_eq!(super::display_path(&home, &workspace), "~");
+    }
+
+    #[test]
+    fn display_path_shows_paths_outside_the_workspace_in_full() {
+        let workspace = Path::new("/amp_workspace/project");
+
+        assert_eq!(super::display_path(Path::new("/amp_other/main.rs"), workspace), "/amp_other/main.rs");
+        assert_eq!(super::display_path(Path::new("/amp_workspace/main.rs"), workspace), "/amp_workspace/main.rs");
+    }
 
     #[test]
     fn inclusive_range_works_correctly_without_trailing_newline() {
@@ -95,4 +353,88 @@ mod tests {
                                   offset: 0,
                               }));
     }
+
+    #[test]
+    fn select_buffer_selects_an_open_buffer_with_the_same_path() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(Path::new("Cargo.toml")).unwrap();
+        super::add_buffer(Buffer::new(), &mut app).unwrap();
+
+        assert!(super::select_buffer(Path::new("./Cargo.toml"), &mut app));
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().path.as_ref().unwrap().canonicalize().unwrap(),
+            Path::new("Cargo.toml").canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn select_buffer_leaves_the_current_buffer_selected_when_not_found() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(Path::new("Cargo.toml")).unwrap();
+        let id = app.workspace.current_buffer().unwrap().id;
+
+        assert!(!super::select_buffer(Path::new("Cargo.lock"), &mut app));
+        assert_eq!(app.workspace.current_buffer().unwrap().id, id);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn open_buffer_selects_a_buffer_already_open_via_a_symlink() {
+        let directory = Path::new(concat!(env!("OUT_DIR"), "/symlinked_buffer"));
+        fs::create_dir_all(directory).unwrap();
+        let target = directory.join("target.txt");
+        let link = directory.join("link.txt");
+        fs::write(&target, "amp").unwrap();
+        fs::remove_file(&link).ok();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        super::open_buffer(&target, &mut app).unwrap();
+        let id = app.workspace.current_buffer().unwrap().id;
+        super::add_buffer(Buffer::new(), &mut app).unwrap();
+        super::open_buffer(&link, &mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().id, id);
+    }
+
+    #[test]
+    fn open_buffer_views_files_above_the_large_file_threshold() {
+        let path = Path::new(concat!(env!("OUT_DIR"), "/open_large_file.txt"));
+        fs::write(path, "amp\neditor\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("large_file_threshold: 4").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        super::add_buffer(Buffer::new(), &mut app).unwrap();
+
+        super::open_buffer(path, &mut app).unwrap();
+        assert!(match app.mode { Mode::LargeFile(_) => true, _ => false });
+        assert_eq!(app.workspace.current_buffer().unwrap().path, None);
+    }
+
+    #[test]
+    fn control_character_symbol_uses_caret_notation() {
+        assert_eq!(super::control_character_symbol('\u{1b}'), Some(String::from("^[")));
+        assert_eq!(super::control_character_symbol('\u{feff}'), Some(String::from("<BOM>")));
+        assert_eq!(super::control_character_symbol('\t'), None);
+        assert_eq!(super::control_character_symbol('\r'), None);
+        assert_eq!(super::control_character_symbol('a'), None);
+    }
+
+    #[test]
+    fn parse_path_argument_splits_line_and_column_suffixes() {
+        assert_eq!(
+            super::parse_path_argument("src/main.rs:42"),
+            (PathBuf::from("src/main.rs"), Some(Position{ line: 41, offset: 0 }))
+        );
+        assert_eq!(
+            super::parse_path_argument("src/main.rs:42:7"),
+            (PathBuf::from("src/main.rs"), Some(Position{ line: 41, offset: 6 }))
+        );
+        assert_eq!(
+            super::parse_path_argument("notes:draft.md:3"),
+            (PathBuf::from("notes:draft.md"), Some(Position{ line: 2, offset: 0 }))
+        );
+        assert_eq!(super::parse_path_argument("notes:draft.md"), (PathBuf::from("notes:draft.md"), None));
+        assert_eq!(super::parse_path_argument(":42"), (PathBuf::from(":42"), None));
+    }
 }
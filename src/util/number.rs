@@ -0,0 +1,128 @@
+/// Finds the integer under or after the specified offset in the line and
+/// adds the amount to it, returning the offsets it spans (with the end being
+/// exclusive) and its replacement. Decimal numbers keep their leading zeros
+/// and may be negative; hexadecimal (0x) numbers keep their width and case,
+/// wrapping around at zero. Returns None if there's no number to adjust.
+pub fn adjust(line: &str, offset: usize, amount: i64) -> Option<(usize, usize, String)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut index = 0;
+
+    while index < chars.len() {
+        if let Some(end) = hexadecimal_end(&chars, index) {
+            if end > offset {
+                let digits: String = chars[index + 2..end].iter().collect();
+                return Some((index, end, adjust_hexadecimal(&chars[index..index + 2], &digits, amount)?));
+            }
+            index = end;
+        } else if chars[index].is_ascii_digit() {
+            let end = index + chars[index..].iter().take_while(|c| c.is_ascii_digit()).count();
+            if end > offset {
+                let negative = is_sign(&chars, index);
+                let start = if negative { index - 1 } else { index };
+                let digits: String = chars[index..end].iter().collect();
+                return Some((start, end, adjust_decimal(negative, &digits, amount)?));
+            }
+            index = end;
+        } else {
+            index += 1;
+        }
+    }
+
+    None
+}
+
+/// The end of the hexadecimal number starting at the index, if there is one.
+fn hexadecimal_end(chars: &[char], index: usize) -> Option<usize> {
+    if chars[index] != '0' || !chars.get(index + 1).map(|&c| c == 'x' || c == 'X').unwrap_or(false) {
+        return None;
+    }
+    let digit_count = chars[index + 2..].iter().take_while(|c| c.is_ascii_hexdigit()).count();
+    if digit_count == 0 {
+        return None;
+    }
+
+    Some(index + 2 + digit_count)
+}
+
+/// Whether the digits at the index are preceded by a minus sign,
+/// as opposed to a hyphen within a word (e.g. "utf-8").
+fn is_sign(chars: &[char], index: usize) -> bool {
+    index > 0 && chars[index - 1] == '-' &&
+        (index < 2 || !(chars[index - 2].is_alphanumeric() || chars[index - 2] == '_'))
+}
+
+fn adjust_decimal(negative: bool, digits: &str, amount: i64) -> Option<String> {
+    let magnitude = digits.parse::<i128>().ok()?;
+    let value = if negative { -magnitude } else { magnitude };
+    let adjusted = value.checked_add(i128::from(amount))?;
+
+    // Numbers with leading zeros keep their width.
+    let width = if digits.len() > 1 && digits.starts_with('0') {
+        digits.len()
+    } else {
+        0
+    };
+    let sign = if adjusted < 0 { "-" } else { "" };
+
+    Some(format!("{}{:0width$}", sign, adjusted.abs(), width = width))
+}
+
+fn adjust_hexadecimal(prefix: &[char], digits: &str, amount: i64) -> Option<String> {
+    let value = u64::from_str_radix(digits, 16).ok()?;
+    let adjusted = value.wrapping_add(amount as u64);
+    let prefix: String = prefix.iter().collect();
+    let width = digits.len();
+
+    // Keep the case of any letters in the original number.
+    if digits.chars().any(|c| c.is_ascii_uppercase()) {
+        Some(format!("{}{:0width$X}", prefix, adjusted, width = width))
+    } else {
+        Some(format!("{}{:0width$x}", prefix, adjusted, width = width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::adjust;
+
+    #[test]
+    fn adjust_changes_the_number_under_the_cursor() {
+        assert_eq!(adjust("let x = 41;", 9, 1), Some((8, 10, String::from("42"))));
+    }
+
+    #[test]
+    fn adjust_changes_the_next_number_on_the_line() {
+        assert_eq!(adjust("width: 9, height: 5", 8, 3), Some((18, 19, String::from("8"))));
+    }
+
+    #[test]
+    fn adjust_returns_none_without_a_number() {
+        assert_eq!(adjust("amp editor", 0, 1), None);
+        assert_eq!(adjust("amp 3 editor", 5, 1), None);
+    }
+
+    #[test]
+    fn adjust_preserves_leading_zeros() {
+        assert_eq!(adjust("007", 0, 1), Some((0, 3, String::from("008"))));
+        assert_eq!(adjust("v099", 0, 1), Some((1, 4, String::from("100"))));
+    }
+
+    #[test]
+    fn adjust_handles_negative_numbers() {
+        assert_eq!(adjust("x = -1", 0, 1), Some((4, 6, String::from("0"))));
+        assert_eq!(adjust("x = 0", 0, -2), Some((4, 5, String::from("-2"))));
+        assert_eq!(adjust("x = -05", 0, -5), Some((4, 7, String::from("-10"))));
+    }
+
+    #[test]
+    fn adjust_treats_hyphens_within_words_as_separators() {
+        assert_eq!(adjust("utf-8", 0, 1), Some((4, 5, String::from("9"))));
+    }
+
+    #[test]
+    fn adjust_recognizes_hexadecimal_numbers() {
+        assert_eq!(adjust("0x0f", 3, 1), Some((0, 4, String::from("0x10"))));
+        assert_eq!(adjust("0XFF", 0, 1), Some((0, 4, String::from("0X100"))));
+        assert_eq!(adjust("0x00", 0, -1), Some((0, 4, String::from("0xffffffffffffffff"))));
+    }
+}
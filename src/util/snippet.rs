@@ -0,0 +1,106 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A parsed snippet body, with its tab-stop markers removed.
+#[derive(Debug, PartialEq)]
+pub struct Snippet {
+    pub content: String,
+
+    /// The numbered tab-stops, in the order they're visited (by number),
+    /// each with the grapheme offsets at which it appears in the content.
+    /// Stops appearing more than once are mirrored.
+    pub stops: Vec<Vec<usize>>,
+
+    /// The grapheme offset of the final ($0) position,
+    /// which defaults to the end of the content.
+    pub end: usize,
+}
+
+/// Parses a snippet body, in which $1, $2, etc. mark tab-stops and $0 marks
+/// the final cursor position. Literal dollar signs are escaped as \$.
+pub fn parse(body: &str) -> Snippet {
+    let mut content = String::new();
+    let mut markers: Vec<(usize, usize)> = Vec::new();
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'$') => content.extend(chars.next()),
+            '$' if chars.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) => {
+                let mut number = String::new();
+                while let Some(digit) = chars.peek().cloned().filter(|c| c.is_ascii_digit()) {
+                    number.push(digit);
+                    chars.next();
+                }
+                if let Ok(number) = number.parse() {
+                    markers.push((number, content.graphemes(true).count()));
+                }
+            }
+            _ => content.push(c),
+        }
+    }
+
+    let mut numbers: Vec<usize> = markers.iter().map(|&(number, _)| number).filter(|&n| n > 0).collect();
+    numbers.sort();
+    numbers.dedup();
+    let stops = numbers
+        .into_iter()
+        .map(|number| {
+            markers.iter().filter(|&&(n, _)| n == number).map(|&(_, offset)| offset).collect()
+        })
+        .collect();
+    let end = markers
+        .iter()
+        .find(|&&(number, _)| number == 0)
+        .map(|&(_, offset)| offset)
+        .unwrap_or_else(|| content.graphemes(true).count());
+
+    Snippet { content, stops, end }
+}
+
+/// Prefixes each of the body's lines (other than the first, which
+/// is inserted at the cursor) with the specified indentation.
+pub fn indent(body: &str, indentation: &str) -> String {
+    body.replace('\n', &format!("\n{}", indentation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Snippet, indent, parse};
+
+    #[test]
+    fn parse_orders_stops_by_number_and_finds_the_final_position() {
+        assert_eq!(
+            parse("fn $2($1) {\n    $0\n}"),
+            Snippet {
+                content: String::from("fn () {\n    \n}"),
+                stops: vec![vec![4], vec![3]],
+                end: 12,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_groups_mirrored_stops_and_defaults_the_end() {
+        assert_eq!(
+            parse("<$1>$2</$1>"),
+            Snippet {
+                content: String::from("<></>"),
+                stops: vec![vec![1, 4], vec![2]],
+                end: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_skips_escaped_and_unnumbered_dollar_signs() {
+        let snippet = parse("\\$1 costs $$1");
+
+        assert_eq!(snippet.content, "$1 costs $");
+        assert_eq!(snippet.stops, vec![vec![10]]);
+    }
+
+    #[test]
+    fn indent_prefixes_subsequent_lines() {
+        assert_eq!(indent("if $1 {\n    $0\n}", "  "), "if $1 {\n      $0\n  }");
+    }
+}
@@ -0,0 +1,65 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+/// Compares strings in a numeric-aware ("natural") order, where runs
+/// of digits are compared by their numeric value (e.g. "item2" sorts
+/// before "item10"), and everything else is compared character-wise.
+pub fn natural_cmp(left: &str, right: &str) -> Ordering {
+    let mut left = left.chars().peekable();
+    let mut right = right.chars().peekable();
+
+    loop {
+        let (l, r) = match (left.peek(), right.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(&l), Some(&r)) => (l, r),
+        };
+
+        if l.is_ascii_digit() && r.is_ascii_digit() {
+            let left_number = take_digits(&mut left);
+            let right_number = take_digits(&mut right);
+
+            // Without leading zeros, longer numbers are larger ones.
+            let ordering = left_number.trim_start_matches('0').len()
+                .cmp(&right_number.trim_start_matches('0').len())
+                .then_with(|| left_number.trim_start_matches('0').cmp(right_number.trim_start_matches('0')))
+                .then_with(|| left_number.len().cmp(&right_number.len()));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        } else {
+            if l != r {
+                return l.cmp(&r);
+            }
+            left.next();
+            right.next();
+        }
+    }
+}
+
+fn take_digits<I: Iterator<Item = char>>(characters: &mut Peekable<I>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = characters.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        characters.next();
+    }
+
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::natural_cmp;
+
+    #[test]
+    fn natural_cmp_compares_numbers_by_value() {
+        let mut lines = vec!["item10", "item2", "item1", "item02", "alpha"];
+        lines.sort_by(|a, b| natural_cmp(a, b));
+
+        assert_eq!(lines, vec!["alpha", "item1", "item2", "item02", "item10"]);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::errors::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use syntect::parsing::ScopeStack;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The maximum number of edits separating a suggestion from a misspelled word.
+const SUGGESTION_DISTANCE: usize = 2;
+
+/// Scopes within text documents (e.g. Markdown) that aren't prose.
+const NON_PROSE_SCOPES: [&str; 4] = ["markup.raw", "markup.underline.link", "meta.tag", "source."];
+
+/// A set of correctly-spelled words, compared case-insensitively.
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    pub fn load(path: &Path) -> Result<Dictionary> {
+        let content = fs::read_to_string(path)
+            .chain_err(|| format!("Couldn't read the dictionary at {}", path.display()))?;
+
+        Ok(Dictionary::parse(&content))
+    }
+
+    /// Parses a word list with one word per line. Hunspell dictionaries, which
+    /// start with a word count and follow words with /-prefixed flags, are
+    /// also supported (their flags are ignored).
+    pub fn parse(content: &str) -> Dictionary {
+        let words = content
+            .lines()
+            .filter_map(|line| {
+                let word = line.split('/').next().unwrap_or("").trim();
+                if word.is_empty() || word.chars().all(|c| c.is_ascii_digit()) {
+                    None
+                } else {
+                    Some(word.to_lowercase())
+                }
+            })
+            .collect();
+
+        Dictionary { words }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+
+    /// Dictionary words within a couple of edits (including transpositions)
+    /// of the word, closest first, matching its capitalization.
+    pub fn suggestions(&self, word: &str, limit: usize) -> Vec<String> {
+        let lowercase = word.to_lowercase();
+        let length = lowercase.chars().count();
+        let mut suggestions: Vec<(usize, &String)> = self.words
+            .iter()
+            .filter(|candidate| {
+                let candidate_length = candidate.chars().count();
+                candidate_length + SUGGESTION_DISTANCE >= length &&
+                    candidate_length <= length + SUGGESTION_DISTANCE
+            })
+            .filter_map(|candidate| {
+                let distance = edit_distance(&lowercase, candidate);
+                if distance <= SUGGESTION_DISTANCE {
+                    Some((distance, candidate))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        suggestions.sort();
+
+        suggestions
+            .into_iter()
+            .take(limit)
+            .map(|(_, suggestion)| match_case(word, suggestion))
+            .collect()
+    }
+}
+
+/// The byte ranges of misspelled words in the text. Single letters, words
+/// containing digits or underscores, and those with capitals beyond their
+/// first letter (e.g. acronyms and camelCase identifiers) are skipped.
+pub fn misspellings(text: &str, dictionary: &Dictionary) -> Vec<(usize, usize)> {
+    words(text)
+        .into_iter()
+        .filter(|&(start, end)| {
+            let word = &text[start..end];
+            let mut letters = word.chars().filter(|&c| c != '\'');
+            let checkable = letters.next().map(char::is_alphabetic).unwrap_or(false) &&
+                word.chars().count() > 1 &&
+                letters.all(|c| c.is_alphabetic() && !c.is_uppercase());
+
+            checkable && !dictionary.contains(word)
+        })
+        .collect()
+}
+
+/// The word at (or immediately before) the offset in the line, along with
+/// the (grapheme) offset at which it starts.
+pub fn word_at(line: &str, offset: usize) -> Option<(usize, String)> {
+    words(line).into_iter().find_map(|(start, end)| {
+        let start_offset = line[..start].graphemes(true).count();
+        let end_offset = start_offset + line[start..end].graphemes(true).count();
+        if start_offset <= offset && offset <= end_offset {
+            Some((start_offset, line[start..end].to_string()))
+        } else {
+            None
+        }
+    })
+}
+
+/// Whether text with the specified scopes is prose: comments and strings in
+/// source code, and anything in text documents (e.g. plain text or Markdown)
+/// other than code, tags, and links.
+pub fn prose_scope(scopes: &ScopeStack) -> bool {
+    let names: Vec<String> = scopes.as_slice().iter().map(|scope| scope.build_string()).collect();
+    if names.iter().any(|name| name.starts_with("comment") || name.starts_with("string")) {
+        return true;
+    }
+
+    names.first().map(|root| root.starts_with("text.")).unwrap_or(false) &&
+        !names.iter().any(|name| NON_PROSE_SCOPES.iter().any(|scope| name.starts_with(scope)))
+}
+
+/// The byte ranges of runs of word characters (and apostrophes
+/// within them, as in contractions) in the text.
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (index, c) in text.char_indices().chain(Some((text.len(), ' '))) {
+        let word_character = c.is_alphanumeric() || c == '_' || c == '\'';
+        match (start, word_character) {
+            (None, true) => start = Some(index),
+            (Some(word_start), false) => {
+                let word = &text[word_start..index];
+                let trimmed_start = word_start + word.len() - word.trim_start_matches('\'').len();
+                let trimmed_end = word_start + word.trim_end_matches('\'').len();
+                if trimmed_start < trimmed_end {
+                    words.push((trimmed_start, trimmed_end));
+                }
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    words
+}
+
+/// The number of insertions, deletions, substitutions, and
+/// transpositions of adjacent characters separating the words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// Capitalizes the suggestion to match the word it's replacing.
+fn match_case(word: &str, suggestion: &str) -> String {
+    let mut characters = word.chars();
+    let capitalized = characters.next().map(char::is_uppercase).unwrap_or(false);
+    if capitalized && word.chars().count() > 1 && characters.all(char::is_uppercase) {
+        return suggestion.to_uppercase();
+    }
+
+    let mut suggestion_characters = suggestion.chars();
+    match suggestion_characters.next() {
+        Some(first) if capitalized => first.to_uppercase().chain(suggestion_characters).collect(),
+        _ => suggestion.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use syntect::parsing::ScopeStack;
+    use super::{Dictionary, misspellings, prose_scope, word_at};
+
+    fn dictionary() -> Dictionary {
+        Dictionary::parse("4\nthe\nthen\nquick/S\nbrown\nfox's\n")
+    }
+
+    #[test]
+    fn parse_reads_word_lists_and_hunspell_dictionaries() {
+        let dictionary = dictionary();
+
+        assert!(dictionary.contains("quick"));
+        assert!(dictionary.contains("The"));
+        assert!(!dictionary.contains("4"));
+        assert!(!dictionary.contains("quick/S"));
+    }
+
+    #[test]
+    fn misspellings_skips_identifiers_and_acronyms() {
+        let text = "The quikc brown fox's HTML fooBar foo_bar x1 a teh.";
+
+        assert_eq!(misspellings(text, &dictionary()), vec![(4, 9), (47, 50)]);
+    }
+
+    #[test]
+    fn suggestions_are_ordered_by_distance_and_match_capitalization() {
+        let dictionary = dictionary();
+
+        assert_eq!(dictionary.suggestions("teh", 5), vec!["the", "then"]);
+        assert_eq!(dictionary.suggestions("Quikc", 5), vec!["Quick"]);
+        assert_eq!(dictionary.suggestions("TEH", 1), vec!["THE"]);
+    }
+
+    #[test]
+    fn word_at_finds_the_word_under_or_before_the_offset() {
+        assert_eq!(word_at("the quikc fox", 6), Some((4, String::from("quikc"))));
+        assert_eq!(word_at("the quikc fox", 9), Some((4, String::from("quikc"))));
+        assert_eq!(word_at("the  fox", 4), None);
+    }
+
+    #[test]
+    fn prose_scope_covers_comments_strings_and_text_documents() {
+        let scope = |scopes: &str| prose_scope(&ScopeStack::from_str(scopes).unwrap());
+
+        assert!(scope("source.rust comment.line.double-slash.rust"));
+        assert!(scope("source.rust string.quoted.double.rust"));
+        assert!(!scope("source.rust meta.function.rust"));
+        assert!(scope("text.html.markdown meta.paragraph.markdown"));
+        assert!(!scope("text.html.markdown markup.raw.block.markdown"));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::errors::*;
+use crate::util;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const HEADER: &str = "amp swap";
+
+/// A recovery copy of a modified buffer's content, kept in case Amp exits
+/// without saving it, along with the process that wrote it.
+pub struct SwapFile {
+    pub pid: u32,
+    pub data: String,
+}
+
+impl SwapFile {
+    /// Whether the swap file belongs to another instance of Amp that's
+    /// still running, rather than one that's since crashed.
+    pub fn owned_by_other_instance(&self) -> bool {
+        self.pid != process::id() && process_running(self.pid)
+    }
+}
+
+/// Records the content as the file's swap file, owned by this process.
+pub fn write(file: &Path, data: &str) -> Result<()> {
+    fs::write(swap_path(file)?, format!("{} {}\n{}", HEADER, process::id(), data))
+        .chain_err(|| "Couldn't write swap file")
+}
+
+/// The file's swap file, if one exists and can be read.
+pub fn read(file: &Path) -> Option<SwapFile> {
+    let content = fs::read_to_string(swap_path(file).ok()?).ok()?;
+    let (header, data) = content.split_at(content.find('\n')?);
+    let pid = header.strip_prefix(HEADER)?.trim().parse().ok()?;
+
+    Some(SwapFile { pid, data: data[1..].to_string() })
+}
+
+/// Removes the file's swap file, if it has one.
+pub fn remove(file: &Path) {
+    if let Ok(path) = swap_path(file) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Swap files are named after the canonical path of the file they're for.
+fn swap_path(file: &Path) -> Result<PathBuf> {
+    let name = util::canonical_path(file)
+        .to_string_lossy()
+        .replace(|c: char| c == '/' || c == '\\' || c == ':', "%");
+
+    swap_location(&name)
+}
+
+#[cfg(not(test))]
+fn swap_location(name: &str) -> Result<PathBuf> {
+    crate::models::application::Preferences::swap_path(name)
+}
+
+// Keep tests from reading or writing the user's swap files.
+#[cfg(test)]
+fn swap_location(name: &str) -> Result<PathBuf> {
+    let directory = std::env::temp_dir().join("amp_swap");
+    fs::create_dir_all(&directory).chain_err(|| "Couldn't create swap directory")?;
+
+    Ok(directory.join(name))
+}
+
+fn process_running(pid: u32) -> bool {
+    if pid == 0 {
+        return false;
+    }
+
+    // Signalling with zero checks that the process exists, without affecting it.
+    // Processes we're not permitted to signal still exist.
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SwapFile;
+    use std::env;
+    use std::path::Path;
+    use std::process;
+
+    #[test]
+    fn write_and_read_round_trip_the_content_and_owner() {
+        let file = env::temp_dir().join("amp_swap_round_trip.txt");
+        super::write(&file, "amp\neditor\n").unwrap();
+
+        let swap = super::read(&file).unwrap();
+        assert_eq!(swap.pid, process::id());
+        assert_eq!(swap.data, "amp\neditor\n");
+        assert!(!swap.owned_by_other_instance());
+
+        super::remove(&file);
+        assert!(super::read(&file).is_none());
+        assert!(super::read(Path::new("/nonexistent/amp.txt")).is_none());
+    }
+
+    #[test]
+    fn owned_by_other_instance_checks_whether_the_process_is_running() {
+        // The init process is always running.
+        assert!(SwapFile { pid: 1, data: String::new() }.owned_by_other_instance());
+        assert!(!SwapFile { pid: 0, data: String::new() }.owned_by_other_instance());
+    }
+}
@@ -0,0 +1,82 @@
+use scribe::buffer::Position;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CURSOR_PLACEHOLDER: &str = "{{cursor}}";
+const DATE_PLACEHOLDER: &str = "{{date}}";
+const FILE_NAME_PLACEHOLDER: &str = "{{filename}}";
+
+/// Fills in a new file template's placeholders, returning the content along
+/// with the position of its cursor placeholder (the first, if several are
+/// present), which is removed from the output.
+pub fn render(template: &str, file_name: &str, date: &str) -> (String, Option<Position>) {
+    let content = template
+        .replace(FILE_NAME_PLACEHOLDER, file_name)
+        .replace(DATE_PLACEHOLDER, date);
+
+    let cursor = content.find(CURSOR_PLACEHOLDER).map(|index| {
+        let preceding = &content[..index];
+        let line = preceding.matches('\n').count();
+        let line_start = preceding.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+        Position{ line, offset: preceding[line_start..].chars().count() }
+    });
+
+    (content.replace(CURSOR_PLACEHOLDER, ""), cursor)
+}
+
+/// The current UTC date, formatted as YYYY-MM-DD.
+pub fn current_date() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_date(seconds / 86_400);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts days since the Unix epoch into a (year, month, day) triple,
+/// using Howard Hinnant's civil calendar algorithm.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use super::{civil_date, render};
+
+    #[test]
+    fn render_replaces_placeholders() {
+        let (content, cursor) = render("// {{filename}}, {{date}}\n", "amp.rs", "2020-01-02");
+
+        assert_eq!(content, "// amp.rs, 2020-01-02\n");
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn render_removes_the_cursor_placeholder_and_returns_its_position() {
+        let (content, cursor) = render("<body>\n  {{cursor}}\n</body>\n", "index.html", "");
+
+        assert_eq!(content, "<body>\n  \n</body>\n");
+        assert_eq!(cursor, Some(Position{ line: 1, offset: 2 }));
+    }
+
+    #[test]
+    fn civil_date_converts_days_since_the_epoch() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(18_628), (2021, 1, 1));
+    }
+}
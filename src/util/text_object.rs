@@ -0,0 +1,81 @@
+use crate::util::indent;
+
+/// Finds the lines surrounding the specified line that are indented at least
+/// as far as it, ignoring blank lines at either end. From a blank line, the
+/// surrounding run of blank lines is used instead. Ranges are described by
+/// their first and last lines.
+pub fn indentation_block(data: &str, line: usize, tab_width: usize) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = data.lines().collect();
+    if line >= lines.len() {
+        return None;
+    }
+    if is_blank(lines[line]) {
+        return Some(blank_run(&lines, line));
+    }
+
+    let level = indent::width(lines[line], tab_width);
+    let within = |index: &usize| is_blank(lines[*index]) || indent::width(lines[*index], tab_width) >= level;
+    let start = (0..line).rev().take_while(within).last().unwrap_or(line);
+    let end = (line + 1..lines.len()).take_while(within).last().unwrap_or(line);
+
+    // Leave out blank lines at either end of the block.
+    let start = (start..=line).find(|&index| !is_blank(lines[index])).unwrap_or(line);
+    let end = (line..=end).rev().find(|&index| !is_blank(lines[index])).unwrap_or(line);
+
+    Some((start, end))
+}
+
+/// Finds the run of non-blank lines containing the specified line. From
+/// a blank line, the surrounding run of blank lines is used instead.
+pub fn paragraph(data: &str, line: usize) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = data.lines().collect();
+    if line >= lines.len() {
+        return None;
+    }
+
+    Some(blank_run(&lines, line))
+}
+
+/// The run of lines around the specified one that are blank if it is, or non-blank if not.
+fn blank_run(lines: &[&str], line: usize) -> (usize, usize) {
+    let blank = is_blank(lines[line]);
+    let start = (0..line).rev().take_while(|&index| is_blank(lines[index]) == blank).last().unwrap_or(line);
+    let end = (line + 1..lines.len()).take_while(|&index| is_blank(lines[index]) == blank).last().unwrap_or(line);
+
+    (start, end)
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{indentation_block, paragraph};
+
+    #[test]
+    fn indentation_block_spans_lines_indented_at_least_as_far() {
+        let data = "def amp():\n    x = 1\n\n    if x:\n        y = 2\n\ndef edit():\n";
+
+        assert_eq!(indentation_block(data, 1, 4), Some((1, 4)));
+        assert_eq!(indentation_block(data, 4, 4), Some((4, 4)));
+        assert_eq!(indentation_block(data, 0, 4), Some((0, 6)));
+    }
+
+    #[test]
+    fn indentation_block_and_paragraph_select_blank_runs_from_blank_lines() {
+        let data = "amp\n\n  \neditor\n";
+
+        assert_eq!(indentation_block(data, 2, 4), Some((1, 2)));
+        assert_eq!(paragraph(data, 1), Some((1, 2)));
+        assert_eq!(paragraph(data, 5), None);
+    }
+
+    #[test]
+    fn paragraph_spans_non_blank_lines() {
+        let data = "amp\neditor\n\ntext\n";
+
+        assert_eq!(paragraph(data, 1), Some((0, 1)));
+        assert_eq!(paragraph(data, 3), Some((3, 3)));
+    }
+}
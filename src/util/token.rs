@@ -1,5 +1,5 @@
 use crate::util::movement_lexer;
-use scribe::buffer::{Buffer, Position};
+use scribe::buffer::{Buffer, Position, Range, ScopeStack, Token};
 use luthor::token::Category;
 
 #[derive(Clone, Copy, PartialEq)]
@@ -81,3 +81,293 @@ pub fn adjacent_token_position(buffer: &mut Buffer,
     None
 }
 
+/// Finds the identifier (a run of alphanumeric and underscore characters)
+/// surrounding the buffer's cursor, if there is one.
+pub fn identifier_at_cursor(buffer: &Buffer) -> Option<String> {
+    let range = identifier_range_at_cursor(buffer)?;
+    let data = buffer.data();
+    let line = data.lines().nth(range.start().line)?;
+
+    Some(line
+        .chars()
+        .skip(range.start().offset)
+        .take(range.end().offset - range.start().offset)
+        .collect())
+}
+
+/// Finds the range of the identifier surrounding the buffer's cursor.
+pub fn identifier_range_at_cursor(buffer: &Buffer) -> Option<Range> {
+    let data = buffer.data();
+    let line: Vec<char> = data.lines().nth(buffer.cursor.line)?.chars().collect();
+    let is_identifier_char = |c: &char| c.is_alphanumeric() || *c == '_';
+
+    // Bail early if the cursor isn't sitting on an identifier character.
+    if !line.get(buffer.cursor.offset).map(is_identifier_char).unwrap_or(false) {
+        return None;
+    }
+
+    // Walk backwards and forwards from the cursor to find the identifier's edges.
+    let start = line[..buffer.cursor.offset]
+        .iter()
+        .rposition(|c| !is_identifier_char(c))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let end = line[buffer.cursor.offset..]
+        .iter()
+        .position(|c| !is_identifier_char(c))
+        .map(|index| buffer.cursor.offset + index)
+        .unwrap_or_else(|| line.len());
+
+    Some(Range::new(
+        Position{ line: buffer.cursor.line, offset: start },
+        Position{ line: buffer.cursor.line, offset: end }
+    ))
+}
+
+/// Finds the whole-word occurrences of an identifier within
+/// an inclusive range of lines (e.g. those visible on-screen).
+pub fn identifier_ranges(data: &str, identifier: &str, lines: (usize, usize)) -> Vec<Range> {
+    let identifier: Vec<char> = identifier.chars().collect();
+    let is_identifier_char = |c: &char| c.is_alphanumeric() || *c == '_';
+    let mut ranges = Vec::new();
+    if identifier.is_empty() {
+        return ranges;
+    }
+
+    let line_range = data
+        .lines()
+        .enumerate()
+        .skip(lines.0)
+        .take_while(|(line_number, _)| *line_number <= lines.1);
+
+    for (line_number, line) in line_range {
+        let line: Vec<char> = line.chars().collect();
+        let mut offset = 0;
+        while offset + identifier.len() <= line.len() {
+            let end = offset + identifier.len();
+            let bounded = (offset == 0 || !is_identifier_char(&line[offset - 1])) &&
+                line.get(end).map(|c| !is_identifier_char(c)).unwrap_or(true);
+            if bounded && line[offset..end] == identifier[..] {
+                ranges.push(Range::new(
+                    Position{ line: line_number, offset },
+                    Position{ line: line_number, offset: end }
+                ));
+                offset = end;
+            } else {
+                offset += 1;
+            }
+        }
+    }
+
+    ranges
+}
+
+/// Finds the path-like text (a run of characters other than whitespace,
+/// quotes, and brackets) surrounding the buffer's cursor, if there is one.
+/// Trailing punctuation (e.g. a sentence's closing period) is dropped.
+pub fn path_at_cursor(buffer: &Buffer) -> Option<String> {
+    let data = buffer.data();
+    let line: Vec<char> = data.lines().nth(buffer.cursor.line)?.chars().collect();
+    let is_path_char = |c: &char| !c.is_whitespace() && !"\"'`()[]{}<>,;|".contains(*c);
+
+    if !line.get(buffer.cursor.offset).map(is_path_char).unwrap_or(false) {
+        return None;
+    }
+
+    let start = line[..buffer.cursor.offset]
+        .iter()
+        .rposition(|c| !is_path_char(c))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let end = line[buffer.cursor.offset..]
+        .iter()
+        .position(|c| !is_path_char(c))
+        .map(|index| buffer.cursor.offset + index)
+        .unwrap_or_else(|| line.len());
+    let path: String = line[start..end].iter().collect();
+    let path = path.trim_end_matches(|c| c == '.' || c == ':');
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.to_string())
+    }
+}
+
+/// Finds the range of the syntactic token (e.g. a string or numeric literal)
+/// at the specified position, spanning adjacent lexemes that share its most
+/// specific scope. Structural (meta) and punctuation scopes are ignored, so
+/// that a string's range includes its quotes.
+pub fn scoped_token_range<'a, T>(tokens: T, position: &Position) -> Option<Range>
+    where T: Iterator<Item=Token<'a>>
+{
+    let lexemes: Vec<(Position, Position, ScopeStack)> = tokens.filter_map(|token| {
+        if let Token::Lexeme(lexeme) = token {
+            let end = Position{
+                line: lexeme.position.line,
+                offset: lexeme.position.offset + lexeme.value.chars().count()
+            };
+            Some((lexeme.position, end, lexeme.scope))
+        } else {
+            None
+        }
+    }).collect();
+    let index = lexemes
+        .iter()
+        .position(|&(start, end, _)| start <= *position && *position < end)?;
+
+    // The first scope is the syntax's base scope (e.g. "source.rust").
+    let (depth, scope) = lexemes[index].2
+        .as_slice()
+        .iter()
+        .enumerate()
+        .skip(1)
+        .rev()
+        .find(|&(_, scope)| {
+            let name = scope.build_string();
+            !name.starts_with("meta.") && !name.starts_with("punctuation.")
+        })?;
+    let shares_scope = |stack: &ScopeStack| stack.as_slice().get(depth) == Some(scope);
+
+    let mut first = index;
+    while first > 0 && shares_scope(&lexemes[first - 1].2) {
+        first -= 1;
+    }
+    let mut last = index;
+    while last + 1 < lexemes.len() && shares_scope(&lexemes[last + 1].2) {
+        last += 1;
+    }
+
+    Some(Range::new(lexemes[first].0, lexemes[last].1))
+}
+
+/// Finds the ranges of lexemes that are part of strings or comments,
+/// where delimiters don't carry any structural meaning. Ranges are found
+/// in order as they're iterated, so content past those needed isn't lexed.
+pub fn string_and_comment_ranges<'a, T>(tokens: T) -> impl Iterator<Item=Range> + 'a
+    where T: Iterator<Item=Token<'a>> + 'a
+{
+    tokens.filter_map(|token| {
+        if let Token::Lexeme(lexeme) = token {
+            let literal = lexeme.scope.as_slice().iter().any(|scope| {
+                let name = scope.build_string();
+                name.starts_with("string.") || name.starts_with("comment.")
+            });
+            if literal {
+                let end = Position{
+                    line: lexeme.position.line,
+                    offset: lexeme.position.offset + lexeme.value.chars().count()
+                };
+                return Some(Range::new(lexeme.position, end));
+            }
+        }
+
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::Buffer;
+    use scribe::buffer::{Lexeme, Position, Range, ScopeStack, Token};
+    use std::str::FromStr;
+    use super::{identifier_at_cursor, identifier_ranges, path_at_cursor, scoped_token_range, string_and_comment_ranges};
+
+    fn lexeme<'a>(value: &'a str, offset: usize, scope: &str) -> Token<'a> {
+        Token::Lexeme(Lexeme{
+            value,
+            position: Position{ line: 0, offset },
+            scope: ScopeStack::from_str(scope).unwrap()
+        })
+    }
+
+    #[test]
+    fn scoped_token_range_spans_a_string_including_its_quotes() {
+        let tokens = vec![
+            lexeme("let amp = ", 0, "source.rust"),
+            lexeme("\"", 10, "source.rust string.quoted.double.rust punctuation.definition.string.begin.rust"),
+            lexeme("editor", 11, "source.rust string.quoted.double.rust"),
+            lexeme("\"", 17, "source.rust string.quoted.double.rust punctuation.definition.string.end.rust"),
+            lexeme(";", 18, "source.rust punctuation.terminator.rust"),
+        ];
+
+        assert_eq!(
+            scoped_token_range(tokens.into_iter(), &Position{ line: 0, offset: 13 }),
+            Some(Range::new(Position{ line: 0, offset: 10 }, Position{ line: 0, offset: 18 }))
+        );
+    }
+
+    #[test]
+    fn scoped_token_range_returns_none_without_a_specific_scope() {
+        let tokens = vec![
+            lexeme("amp", 0, "source.rust"),
+            lexeme(";", 3, "source.rust punctuation.terminator.rust"),
+        ];
+
+        assert_eq!(scoped_token_range(tokens.into_iter(), &Position{ line: 0, offset: 1 }), None);
+    }
+
+    #[test]
+    fn string_and_comment_ranges_includes_only_literal_lexemes() {
+        let tokens = vec![
+            lexeme("amp(", 0, "source.rust"),
+            lexeme("\")\"", 4, "source.rust string.quoted.double.rust"),
+            lexeme(") ", 7, "source.rust"),
+            lexeme("// (", 9, "source.rust comment.line.double-slash.rust"),
+        ];
+
+        assert_eq!(
+            string_and_comment_ranges(tokens.into_iter()).collect::<Vec<Range>>(),
+            vec![
+                Range::new(Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 7 }),
+                Range::new(Position{ line: 0, offset: 9 }, Position{ line: 0, offset: 13 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn identifier_at_cursor_returns_surrounding_identifier() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor_name(value)");
+        buffer.cursor.move_to(Position{ line: 0, offset: 8 });
+
+        assert_eq!(identifier_at_cursor(&buffer), Some(String::from("editor_name")));
+    }
+
+    #[test]
+    fn identifier_at_cursor_returns_none_when_not_on_an_identifier() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+
+        assert_eq!(identifier_at_cursor(&buffer), None);
+    }
+
+    #[test]
+    fn identifier_ranges_finds_whole_words_within_the_line_range() {
+        let data = "amp\namp amped amp_2 (amp)\namp";
+
+        assert_eq!(
+            identifier_ranges(data, "amp", (1, 1)),
+            vec![
+                Range::new(Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 3 }),
+                Range::new(Position{ line: 1, offset: 17 }, Position{ line: 1, offset: 20 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn path_at_cursor_finds_paths_and_drops_trailing_punctuation() {
+        let mut buffer = Buffer::new();
+        buffer.insert("see (src/main.rs:42:7). or \"lib.rs\".");
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 8 });
+        assert_eq!(path_at_cursor(&buffer), Some(String::from("src/main.rs:42:7")));
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 30 });
+        assert_eq!(path_at_cursor(&buffer), Some(String::from("lib.rs")));
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        assert_eq!(path_at_cursor(&buffer), None);
+    }
+}
@@ -0,0 +1,151 @@
+use scribe::Buffer;
+use crate::util::diff::{self, RowKind};
+
+/// Tracks bookmarked lines for a buffer. Buffer changes are recorded as they
+/// happen, and bookmarks are moved to follow their lines the next time the
+/// buffer is provided via `update`, by aligning its lines with a snapshot
+/// taken at the previous update. This keeps bookmarks in place across any
+/// number of changes, wherever they occur.
+#[derive(Default)]
+pub struct Bookmarks {
+    lines: Vec<usize>,
+    snapshot: Option<Vec<String>>,
+    changed: bool,
+}
+
+impl Bookmarks {
+    pub fn new() -> Bookmarks {
+        Bookmarks::default()
+    }
+
+    /// Adds a bookmark for the specified line, or removes it if present.
+    pub fn toggle(&mut self, line: usize) {
+        match self.lines.binary_search(&line) {
+            Ok(index) => { self.lines.remove(index); },
+            Err(index) => self.lines.insert(index, line),
+        }
+    }
+
+    /// The bookmarked lines, in ascending order.
+    pub fn lines(&self) -> &[usize] {
+        &self.lines
+    }
+
+    /// Records that the buffer has changed since the last update.
+    pub fn record_change(&mut self) {
+        self.changed = true;
+    }
+
+    /// Moves bookmarks to follow their lines through any changes recorded
+    /// since the last update. Bookmarks on lines that were removed are
+    /// discarded. A snapshot is only kept while there are bookmarks.
+    pub fn update(&mut self, buffer: &Buffer) {
+        let changed = self.changed;
+        self.changed = false;
+
+        if self.lines.is_empty() {
+            self.snapshot = None;
+            return;
+        }
+        if !changed && self.snapshot.is_some() {
+            return;
+        }
+
+        // Keep the trailing empty line, so that indices match buffer lines.
+        let current: Vec<String> = buffer.data().split('\n').map(String::from).collect();
+        if let (true, Some(previous)) = (changed, self.snapshot.take()) {
+            let mut lines: Vec<usize> = diff::rows(&previous, &current)
+                .into_iter()
+                .filter(|row| row.kind == RowKind::Unchanged || row.kind == RowKind::Changed)
+                .filter_map(|row| match (row.left, row.right) {
+                    (Some(left), Some(right)) if self.lines.binary_search(&left).is_ok() => Some(right),
+                    _ => None,
+                })
+                .collect();
+            lines.sort();
+            lines.dedup();
+            self.lines = lines;
+        }
+        self.snapshot = Some(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::Buffer;
+    use scribe::buffer::{Position, Range};
+    use super::Bookmarks;
+
+    fn buffer(content: &str) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer
+    }
+
+    #[test]
+    fn toggle_adds_and_removes_bookmarks_in_order() {
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.toggle(5);
+        bookmarks.toggle(2);
+        bookmarks.toggle(8);
+        bookmarks.toggle(5);
+
+        assert_eq!(bookmarks.lines(), &[2, 8]);
+    }
+
+    #[test]
+    fn update_shifts_bookmarks_after_inserted_lines() {
+        let mut buffer = buffer("a\nb\nc\nd\ne\nf\n");
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.toggle(2);
+        bookmarks.toggle(5);
+        bookmarks.update(&buffer);
+
+        buffer.cursor.move_to(Position{ line: 3, offset: 1 });
+        buffer.insert("\nx\ny");
+        bookmarks.record_change();
+        bookmarks.update(&buffer);
+
+        assert_eq!(bookmarks.lines(), &[2, 7]);
+    }
+
+    #[test]
+    fn update_discards_bookmarks_on_removed_lines() {
+        let mut buffer = buffer("a\nb\nc\nd\ne\nf\n");
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.toggle(2);
+        bookmarks.toggle(3);
+        bookmarks.toggle(5);
+        bookmarks.update(&buffer);
+
+        buffer.delete_range(Range::new(
+            Position{ line: 3, offset: 0 },
+            Position{ line: 4, offset: 0 }
+        ));
+        bookmarks.record_change();
+        bookmarks.update(&buffer);
+
+        assert_eq!(bookmarks.lines(), &[2, 4]);
+    }
+
+    #[test]
+    fn update_applies_several_changes_made_between_updates() {
+        let mut buffer = buffer("a\nb\nc\nd\ne\nf\n");
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.toggle(2);
+        bookmarks.update(&buffer);
+
+        // Insert a line above the bookmark, and remove two below it.
+        buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+        buffer.insert("x\n");
+        bookmarks.record_change();
+        buffer.delete_range(Range::new(
+            Position{ line: 4, offset: 0 },
+            Position{ line: 6, offset: 0 }
+        ));
+        bookmarks.record_change();
+        bookmarks.update(&buffer);
+
+        assert_eq!(bookmarks.lines(), &[3]);
+    }
+}
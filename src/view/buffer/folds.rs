@@ -0,0 +1,191 @@
+use crate::util::fold::Block;
+use scribe::buffer::Position;
+
+/// Tracks folded line ranges for a buffer. Each fold is described by its
+/// first and last (inclusive) lines; the first stays visible, standing in
+/// for the rest. Buffer changes are recorded as they happen, and folds are
+/// adjusted the next time the buffer's line count is provided via `update`.
+pub struct Folds {
+    ranges: Vec<(usize, usize)>,
+    line_count: usize,
+    change_position: Option<Position>,
+}
+
+impl Folds {
+    pub fn new(line_count: usize) -> Folds {
+        Folds {
+            ranges: Vec::new(),
+            line_count,
+            change_position: None,
+        }
+    }
+
+    /// Folds the specified lines, replacing any overlapping folds.
+    pub fn add(&mut self, start: usize, end: usize) {
+        self.ranges.retain(|&(s, e)| e < start || s > end);
+        let index = self.ranges.iter().position(|&(s, _)| s > start).unwrap_or(self.ranges.len());
+        self.ranges.insert(index, (start, end));
+    }
+
+    /// Removes the fold containing the specified line,
+    /// returning false if there isn't one.
+    pub fn remove(&mut self, line: usize) -> bool {
+        let count = self.ranges.len();
+        self.ranges.retain(|&(start, end)| line < start || line > end);
+
+        self.ranges.len() != count
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Replaces the folds with the blocks at the specified depth, leaving
+    /// shallower blocks open; deeper ones are hidden within those folded.
+    /// A depth of 1 folds the outermost blocks, collapsing everything.
+    pub fn fold_to_depth(&mut self, blocks: &[Block], depth: usize) {
+        self.ranges = blocks
+            .iter()
+            .filter(|block| block.depth == depth)
+            .map(|block| (block.start, block.end))
+            .collect();
+    }
+
+    /// The depth of the shallowest folded block or, if none of the
+    /// blocks are folded, one more than the depth of the deepest block.
+    pub fn folded_depth(&self, blocks: &[Block]) -> usize {
+        blocks
+            .iter()
+            .filter(|block| self.ranges.contains(&(block.start, block.end)))
+            .map(|block| block.depth)
+            .min()
+            .unwrap_or_else(|| blocks.iter().map(|block| block.depth).max().unwrap_or(0) + 1)
+    }
+
+    /// The folded ranges, in ascending order.
+    pub fn ranges(&self) -> &[(usize, usize)] {
+        &self.ranges
+    }
+
+    /// Records the earliest position changed since the last update.
+    pub fn record_change(&mut self, position: Position) {
+        if self.change_position.map(|p| position < p).unwrap_or(true) {
+            self.change_position = Some(position);
+        }
+    }
+
+    /// Unfolds ranges whose content has changed, and shifts those following
+    /// a recorded change by the difference between the specified and
+    /// previous line counts.
+    pub fn update(&mut self, line_count: usize) {
+        if let Some(position) = self.change_position.take() {
+            let removed = self.line_count.saturating_sub(line_count);
+
+            // Lines inserted at the start of a fold's first line don't alter its content.
+            let inserted_above = |start: usize| {
+                position.offset == 0 && removed == 0 && start == position.line
+            };
+            self.ranges.retain(|&(start, end)| {
+                end < position.line || start > position.line + removed || inserted_above(start)
+            });
+
+            // A change at the start of a line moves the line itself.
+            let first_line = if position.offset == 0 {
+                position.line
+            } else {
+                position.line + 1
+            };
+            for range in self.ranges.iter_mut().filter(|r| r.0 >= first_line) {
+                if line_count > self.line_count {
+                    range.0 += line_count - self.line_count;
+                    range.1 += line_count - self.line_count;
+                } else {
+                    range.0 -= removed;
+                    range.1 -= removed;
+                }
+            }
+        }
+
+        self.line_count = line_count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::fold::Block;
+    use scribe::buffer::Position;
+    use super::Folds;
+
+    #[test]
+    fn add_replaces_overlapping_folds() {
+        let mut folds = Folds::new(20);
+        folds.add(10, 12);
+        folds.add(2, 4);
+        folds.add(3, 6);
+
+        assert_eq!(folds.ranges(), &[(3, 6), (10, 12)]);
+    }
+
+    #[test]
+    fn remove_only_removes_the_fold_containing_the_line() {
+        let mut folds = Folds::new(20);
+        folds.add(2, 4);
+        folds.add(10, 12);
+
+        assert!(!folds.remove(6));
+        assert!(folds.remove(4));
+        assert_eq!(folds.ranges(), &[(10, 12)]);
+    }
+
+    #[test]
+    fn fold_to_depth_folds_blocks_at_that_depth() {
+        let blocks = [
+            Block { start: 0, end: 8, depth: 1 },
+            Block { start: 1, end: 3, depth: 2 },
+            Block { start: 5, end: 7, depth: 2 },
+            Block { start: 6, end: 7, depth: 3 },
+        ];
+        let mut folds = Folds::new(20);
+        assert_eq!(folds.folded_depth(&blocks), 4);
+
+        folds.fold_to_depth(&blocks, 2);
+        assert_eq!(folds.ranges(), &[(1, 3), (5, 7)]);
+        assert_eq!(folds.folded_depth(&blocks), 2);
+
+        folds.fold_to_depth(&blocks, 1);
+        assert_eq!(folds.ranges(), &[(0, 8)]);
+        assert_eq!(folds.folded_depth(&blocks), 1);
+    }
+
+    #[test]
+    fn update_shifts_folds_following_the_change() {
+        let mut folds = Folds::new(20);
+        folds.add(2, 4);
+        folds.add(10, 12);
+        folds.record_change(Position{ line: 6, offset: 3 });
+        folds.update(22);
+
+        assert_eq!(folds.ranges(), &[(2, 4), (12, 14)]);
+    }
+
+    #[test]
+    fn update_unfolds_changed_folds() {
+        let mut folds = Folds::new(20);
+        folds.add(2, 4);
+        folds.add(10, 12);
+        folds.record_change(Position{ line: 3, offset: 0 });
+        folds.update(19);
+
+        assert_eq!(folds.ranges(), &[(9, 11)]);
+    }
+
+    #[test]
+    fn update_shifts_folds_when_lines_are_inserted_above_them() {
+        let mut folds = Folds::new(20);
+        folds.add(2, 4);
+        folds.record_change(Position{ line: 2, offset: 0 });
+        folds.update(21);
+
+        assert_eq!(folds.ranges(), &[(3, 5)]);
+    }
+}
@@ -6,16 +6,25 @@ pub const PADDING_WIDTH: usize = 2;
 pub struct LineNumbers {
     current_number: usize,
     buffer_line_count_width: usize,
+    relative_to: Option<usize>,
 }
 
 impl LineNumbers {
     pub fn new(buffer: &Buffer, offset: Option<usize>) -> LineNumbers {
         LineNumbers{
             current_number: offset.unwrap_or(0),
-            buffer_line_count_width: buffer.line_count().to_string().len()
+            buffer_line_count_width: buffer.line_count().to_string().len(),
+            relative_to: None,
         }
     }
 
+    /// Numbers lines by their distance from the specified (zero-based)
+    /// line, which keeps its absolute number. Relative numbers never exceed
+    /// the line count, so this doesn't affect the width.
+    pub fn relative_to(&mut self, line: usize) {
+        self.relative_to = Some(line);
+    }
+
     pub fn width(&self) -> usize {
         self.buffer_line_count_width + PADDING_WIDTH
     }
@@ -26,10 +35,17 @@ impl Iterator for LineNumbers {
 
     fn next(&mut self) -> Option<String> {
         self.current_number += 1;
+        let number = match self.relative_to {
+            Some(line) if line + 1 != self.current_number => {
+                (self.current_number as isize - (line + 1) as isize).abs() as usize
+            }
+            _ => self.current_number,
+        };
+
         Some(
             format!(
                 " {:>width$} ",
-                self.current_number,
+                number,
                 width = self.buffer_line_count_width
             )
         )
@@ -108,4 +124,20 @@ mod tests {
         let mut line_numbers = LineNumbers::new(&buffer, None);
         assert_eq!(line_numbers.next().unwrap(), "   1 ");
     }
+
+    #[test]
+    fn relative_line_numbers_show_distance_from_the_cursor_line() {
+        let mut buffer = Buffer::new();
+        for _ in 0..101 {
+            buffer.insert("\n");
+        }
+        let mut line_numbers = LineNumbers::new(&buffer, Some(8));
+        line_numbers.relative_to(9);
+
+        assert_eq!(line_numbers.next().unwrap(), "   1 ");
+        assert_eq!(line_numbers.next().unwrap(), "  10 ");
+        assert_eq!(line_numbers.next().unwrap(), "   1 ");
+        assert_eq!(line_numbers.next().unwrap(), "   2 ");
+        assert_eq!(line_numbers.width(), 5);
+    }
 }
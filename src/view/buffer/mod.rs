@@ -1,13 +1,21 @@
+mod bookmarks;
+mod folds;
 mod renderer;
 mod render_cache;
 mod render_state;
 mod lexeme_mapper;
 mod line_numbers;
 mod scrollable_region;
+mod text_stats;
+mod undo_depth;
 
+pub use self::bookmarks::Bookmarks;
+pub use self::folds::Folds;
 pub use self::renderer::BufferRenderer;
 pub use self::render_cache::RenderCache;
 pub use self::render_state::RenderState;
 pub use self::lexeme_mapper::{LexemeMapper, MappedLexeme};
 pub use self::line_numbers::LineNumbers;
 pub use self::scrollable_region::ScrollableRegion;
+pub use self::text_stats::TextStats;
+pub use self::undo_depth::UndoDepth;
@@ -3,7 +3,7 @@ use scribe::buffer::{Buffer, Position, Range};
 use scribe::util::LineIterator;
 use crate::view::buffer::{LexemeMapper, MappedLexeme, RenderState};
 use crate::view::buffer::line_numbers::*;
-use crate::view::{Colors, RENDER_CACHE_FREQUENCY, RGBColor, Style};
+use crate::view::{Colors, Region, RENDER_CACHE_FREQUENCY, RGBColor, Style};
 use crate::view::color::to_rgb_color;
 use crate::view::terminal::{Cell, Terminal, TerminalBuffer};
 use std::borrow::Cow;
@@ -16,25 +16,43 @@ use syntect::highlighting::Style as ThemeStyle;
 use syntect::parsing::ScopeStack;
 use unicode_segmentation::UnicodeSegmentation;
 use crate::errors::*;
+use crate::util;
+use crate::util::indent;
+use crate::util::git::LineChange;
+use crate::util::lsp::{Diagnostic, Severity};
+use crate::util::spell::{self, Dictionary};
 
 /// A one-time-use type that encapsulates all of the
 /// details involved in rendering a buffer to the screen.
 pub struct BufferRenderer<'a, 'p> {
+    bookmarks: &'a [usize],
     buffer: &'a Buffer,
     buffer_position: Position,
     cursor_position: Option<Position>,
+    folds: &'a [(usize, usize)],
     gutter_width: usize,
     highlights: Option<&'a [Range]>,
     stylist: Highlighter<'a>,
     current_style: ThemeStyle,
+    dictionary: Option<&'a Dictionary>,
+    line_changes: &'a [(usize, LineChange)],
+    diagnostics: &'a [Diagnostic],
     line_numbers: LineNumbers,
+    misspellings: Vec<(usize, usize)>,
     preferences: &'a Preferences,
+    region: Region,
     render_cache: &'a Rc<RefCell<HashMap<usize, RenderState>>>,
     screen_position: Position,
     scroll_offset: usize,
+    secondary_cursors: &'a [Position],
+    show_control_chars: bool,
+    show_whitespace: bool,
     terminal: &'a Terminal,
     terminal_buffer: &'a mut TerminalBuffer<'p>,
     theme: &'a Theme,
+    trailing_whitespace: usize,
+    word_highlights: &'a [Range],
+    wrap_indent: usize,
 }
 
 impl<'a, 'p> BufferRenderer<'a, 'p> {
@@ -52,24 +70,106 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         let current_style = stylist.get_default();
 
         BufferRenderer{
+            bookmarks: &[],
             buffer,
             cursor_position: None,
+            folds: &[],
             gutter_width,
             highlights,
             stylist,
             current_style,
+            dictionary: None,
+            line_changes: &[],
+            diagnostics: &[],
             line_numbers,
+            misspellings: Vec::new(),
             buffer_position: Position{ line: 0, offset: 0 },
             preferences,
+            region: Region{
+                line: 0,
+                offset: 0,
+                width: terminal.width(),
+                height: terminal.height().checked_sub(1).unwrap_or(0),
+            },
             render_cache,
             screen_position: Position{ line: 0, offset: 0 },
             scroll_offset,
+            secondary_cursors: &[],
+            show_control_chars: preferences.show_control_chars(),
+            show_whitespace: false,
             terminal,
             terminal_buffer,
             theme,
+            trailing_whitespace: 0,
+            word_highlights: &[],
+            wrap_indent: 0,
         }
     }
 
+    /// Confines rendering to a region of the screen (e.g. a split pane),
+    /// rather than the full terminal, less its status line.
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    /// Lines to be flagged with a bookmark glyph in the line number gutter.
+    pub fn set_bookmarks(&mut self, bookmarks: &'a [usize]) {
+        self.bookmarks = bookmarks;
+    }
+
+    /// Folded line ranges, whose lines following the first are hidden.
+    pub fn set_folds(&mut self, folds: &'a [(usize, usize)]) {
+        self.folds = folds;
+    }
+
+    /// Displays line numbers relative to the cursor line.
+    pub fn set_relative_line_numbers(&mut self) {
+        self.line_numbers.relative_to(self.buffer.cursor.line);
+    }
+
+    /// Draws tabs, trailing spaces, and line endings using visible symbols.
+    pub fn set_show_whitespace(&mut self) {
+        self.show_whitespace = true;
+    }
+
+    /// Lines to be flagged with a git change sign in the line number gutter.
+    pub fn set_line_changes(&mut self, line_changes: &'a [(usize, LineChange)]) {
+        self.line_changes = line_changes;
+    }
+
+    /// Underlines misspelled words in prose (i.e. comments, strings, and text documents).
+    pub fn set_dictionary(&mut self, dictionary: &'a Dictionary) {
+        self.dictionary = Some(dictionary);
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: &'a [Diagnostic]) {
+        self.diagnostics = diagnostics;
+    }
+
+    /// Additional cursor positions to be drawn as inverted cells.
+    pub fn set_secondary_cursors(&mut self, secondary_cursors: &'a [Position]) {
+        self.secondary_cursors = secondary_cursors;
+    }
+
+    /// Occurrences of the word under the cursor, drawn with a subtler
+    /// background than explicit highlights (e.g. search matches).
+    pub fn set_word_highlights(&mut self, word_highlights: &'a [Range]) {
+        self.word_highlights = word_highlights;
+    }
+
+    fn on_secondary_cursor(&self) -> bool {
+        self.secondary_cursors.contains(&self.buffer_position)
+    }
+
+    fn misspelled(&self) -> bool {
+        let offset = self.buffer_position.offset;
+        self.misspellings.iter().any(|&(start, end)| start <= offset && offset < end)
+    }
+
+    fn folded(&self, line: usize) -> bool {
+        self.folds.iter().any(|&(start, end)| start < line && line <= end)
+    }
+
     fn on_cursor_line(&self) -> bool {
         self.buffer_position.line == self.buffer.cursor.line
     }
@@ -78,7 +178,17 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         let on_cursor_line = self.on_cursor_line();
         let guide_offset = self.length_guide_offset();
 
-        for offset in self.screen_position.offset..self.terminal.width() {
+        let on_secondary_cursor = self.on_secondary_cursor();
+
+        for offset in self.screen_position.offset..self.region.width {
+            if on_secondary_cursor && offset == self.screen_position.offset {
+                self.print(Position{ line: self.screen_position.line, offset },
+                           Style::Inverted,
+                           Colors::Default,
+                           " ");
+                continue;
+            }
+
             let colors = if on_cursor_line || guide_offset.map(|go| go == offset).unwrap_or(false) {
                 Colors::Focused
             } else {
@@ -97,8 +207,15 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     }
 
     fn advance_to_next_line(&mut self) {
-        if self.inside_visible_content() {
+        // Folded lines don't take up any rows, so the row
+        // holding a fold's first line is finished as they begin.
+        if self.inside_visible_content() && !self.folded(self.buffer_position.line) {
             self.set_cursor();
+            if self.folded(self.buffer_position.line + 1) {
+                self.print_fold_marker();
+            } else if self.show_whitespace {
+                self.print_line_ending_marker();
+            }
             self.print_rest_of_line();
 
             // It's important to only increase this once we've entered the
@@ -119,43 +236,47 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     // at which point we can set it relative to the screen,
     // which will compensate for scrolling, tab expansion, etc.
     fn set_cursor(&mut self) {
-        if self.inside_visible_content() && *self.buffer.cursor == self.buffer_position {
+        if self.inside_visible_content() && *self.buffer.cursor == self.buffer_position &&
+            !self.folded(self.buffer_position.line) {
             self.cursor_position = Some(self.screen_position);
         }
     }
 
     fn current_char_style(&self, token_color: RGBColor) -> (Style, Colors) {
-        let (style, colors) = match self.highlights {
-            Some(highlight_ranges) => {
-                for range in highlight_ranges {
-                    if range.includes(&self.buffer_position) {
-                        // We're inside of one of the highlighted areas.
-                        // Return early with highlight colors.
-                        if range.includes(&self.buffer.cursor) {
-                            return (Style::Bold, Colors::SelectMode)
-                        } else {
-                            return (Style::Inverted, Colors::Default)
-                        }
-                    }
-                }
+        if self.on_secondary_cursor() {
+            return (Style::Inverted, Colors::Default)
+        }
 
-                // We aren't inside one of the highlighted areas.
-                // Fall back to other styling considerations.
-                if self.on_cursor_line() {
-                    (Style::Default, Colors::CustomFocusedForeground(token_color))
-                } else {
-                    (Style::Default, Colors::CustomForeground(token_color))
+        if let Some(highlight_ranges) = self.highlights {
+            for range in highlight_ranges {
+                if range.includes(&self.buffer_position) {
+                    // We're inside of one of the highlighted areas.
+                    // Return early with highlight colors.
+                    if range.includes(&self.buffer.cursor) {
+                        return (Style::Bold, Colors::SelectMode)
+                    } else {
+                        return (Style::Inverted, Colors::Default)
+                    }
                 }
             }
-            None => {
-                if self.on_cursor_line() {
-                    (Style::Default, Colors::CustomFocusedForeground(token_color))
-                } else {
-                    (Style::Default, Colors::CustomForeground(token_color))
-                }
-            },
+        }
+
+        if self.word_highlights.iter().any(|range| range.includes(&self.buffer_position)) {
+            return (Style::Default, Colors::WordHighlight)
+        }
+
+        // We aren't inside one of the highlighted areas.
+        // Fall back to other styling considerations.
+        let (style, colors) = if self.on_cursor_line() {
+            (Style::Default, Colors::CustomFocusedForeground(token_color))
+        } else {
+            (Style::Default, Colors::CustomForeground(token_color))
         };
 
+        if style == Style::Default && self.misspelled() {
+            return (Style::Underlined, colors)
+        }
+
         (style, colors)
     }
 
@@ -170,9 +291,9 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             let token_color = to_rgb_color(self.current_style.foreground);
             let (style, color) = self.current_char_style(token_color);
 
-            if self.preferences.line_wrapping() && self.screen_position.offset == self.terminal.width() {
+            if self.preferences.line_wrapping(self.buffer.path.as_ref()) && self.screen_position.offset == self.region.width {
                 self.screen_position.line += 1;
-                self.screen_position.offset = self.gutter_width;
+                self.screen_position.offset = self.gutter_width + self.wrap_indent;
                 self.print(self.screen_position, style, color, character.to_string());
                 self.screen_position.offset += 1;
                 self.buffer_position.offset += 1;
@@ -184,16 +305,35 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                 let mut screen_tab_stop = buffer_tab_stop + self.gutter_width;
 
                 // Now that we know where we'd like to go, prevent it from being off-screen.
-                if screen_tab_stop > self.terminal.width() {
-                    screen_tab_stop = self.terminal.width();
+                if screen_tab_stop > self.region.width {
+                    screen_tab_stop = self.region.width;
                 }
 
-                // Print the sequence of spaces and move the offset accordingly.
-                for _ in self.screen_position.offset..screen_tab_stop {
-                    self.print(self.screen_position, style, color, " ");
+                // Print the sequence of spaces (led by an arrow, if whitespace is
+                // being shown) and move the offset accordingly.
+                let tab_start = self.screen_position.offset;
+                for offset in tab_start..screen_tab_stop {
+                    if self.show_whitespace && offset == tab_start {
+                        self.print(self.screen_position, style, self.whitespace_colors(color), "→");
+                    } else {
+                        self.print(self.screen_position, style, color, " ");
+                    }
                     self.screen_position.offset += 1;
                 }
                 self.buffer_position.offset += 1;
+            } else if let Some(symbol) = self.control_character_symbol(character) {
+                // Print a visible stand-in, without running off-screen.
+                for symbol_character in symbol.chars() {
+                    if self.screen_position.offset < self.region.width {
+                        self.print(self.screen_position, Style::Bold, Colors::Warning, symbol_character.to_string());
+                        self.screen_position.offset += 1;
+                    }
+                }
+                self.buffer_position.offset += 1;
+            } else if self.trailing_space(character) {
+                self.print(self.screen_position, style, self.whitespace_colors(color), "·");
+                self.screen_position.offset += 1;
+                self.buffer_position.offset += 1;
             } else {
                 self.print(self.screen_position, style, color, character.to_string());
                 self.screen_position.offset += 1;
@@ -204,12 +344,32 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         }
     }
 
+    // Provides a visible representation of single-character
+    // control graphemes, if the user has asked to see them.
+    fn control_character_symbol(&self, grapheme: &str) -> Option<String> {
+        if !self.show_control_chars {
+            return None;
+        }
+
+        let mut characters = grapheme.chars();
+        match (characters.next(), characters.next()) {
+            (Some(character), None) => util::control_character_symbol(character),
+            _ => None,
+        }
+    }
+
+    // Whether the grapheme is a space following the line's
+    // content, if the user has asked to see whitespace.
+    fn trailing_space(&self, grapheme: &str) -> bool {
+        self.show_whitespace && grapheme == " " && self.buffer_position.offset >= self.trailing_whitespace
+    }
+
     fn before_visible_content(&mut self) -> bool {
         self.buffer_position.line < self.scroll_offset
     }
 
     fn after_visible_content(&self) -> bool {
-        self.screen_position.line >= self.terminal.height().checked_sub(1).unwrap_or(0)
+        self.screen_position.line >= self.region.height
     }
 
     fn inside_visible_content(&mut self) -> bool {
@@ -239,6 +399,14 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                 }
 
                 let events = state.parse.parse_line(line);
+
+                // Spell checking is limited to prose, which requires tracking the
+                // scopes of each lexeme, applying the parser's operations as we go.
+                let mut scopes = state.highlight.path.clone();
+                let mut event_index = 0;
+                let mut lexeme_start = 0;
+                self.misspellings.clear();
+
                 let styled_lexemes = HighlightIterator::new(
                     &mut state.highlight,
                     &events,
@@ -246,9 +414,25 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                     &highlighter
                 );
 
+                let folded = self.folded(line_no);
+                self.wrap_indent = self.line_wrap_indent(line);
+                self.trailing_whitespace = line.trim_end().graphemes(true).count();
                 for (style, lexeme) in styled_lexemes {
-                    // Move along until we've hit visible content.
-                    if self.before_visible_content() {
+                    let prose = if self.dictionary.is_some() {
+                        while event_index < events.len() && events[event_index].0 <= lexeme_start {
+                            scopes.apply(&events[event_index].1);
+                            event_index += 1;
+                        }
+                        lexeme_start += lexeme.len();
+
+                        spell::prose_scope(&scopes)
+                    } else {
+                        false
+                    };
+
+                    // Move along until we've hit visible content,
+                    // skipping over any lines that are folded away.
+                    if self.before_visible_content() || folded {
                         continue;
                     }
 
@@ -274,6 +458,19 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
 
                         }
                     } else {
+                        if let (Some(dictionary), true) = (self.dictionary, prose) {
+                            let offset = self.buffer_position.offset;
+                            self.misspellings = spell::misspellings(lexeme, dictionary)
+                                .into_iter()
+                                .map(|(start, end)| (
+                                    offset + lexeme[..start].graphemes(true).count(),
+                                    offset + lexeme[..end].graphemes(true).count()
+                                ))
+                                .collect();
+                        } else {
+                            self.misspellings.clear();
+                        }
+
                         self.current_style = style;
                         self.print_lexeme(lexeme);
                     }
@@ -287,18 +484,31 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
 
         self.set_cursor();
 
-        // One last call to this for the last line.
-        self.print_rest_of_line();
+        // One last call to this for the last line, unless it's
+        // folded, in which case its fold's row is already finished.
+        if !self.folded(self.buffer_position.line) {
+            self.print_rest_of_line();
+        }
 
         // Return the cursor location. If it occurred somewhere in the buffer, it
         // will be shown at the right location. If not, it will be None and will
         // be hidden.
-        Ok(self.cursor_position)
+        let region = self.region;
+        Ok(self.cursor_position.map(|position| Position{
+            line: position.line + region.line,
+            offset: position.offset + region.offset,
+        }))
     }
 
     fn print_line_number(&mut self) {
         if !self.inside_visible_content() { return };
 
+        // Folded lines aren't printed, but are still counted.
+        if self.folded(self.buffer_position.line) {
+            self.line_numbers.next();
+            return;
+        }
+
         let line_number = self.line_numbers.next().unwrap();
 
         // Cursor line number is emboldened.
@@ -308,12 +518,54 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             Style::Default
         };
 
-        self.print(
-            Position{ line: self.screen_position.line, offset: 0 },
-            weight,
-            Colors::Focused,
-            line_number
-        );
+        // Bookmarks and diagnostics are flagged in the line number's leading
+        // padding column, so the number itself is printed after it.
+        let severity = self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.line == self.buffer_position.line)
+            .map(|diagnostic| diagnostic.severity)
+            .min();
+        let sign = if let Some(severity) = severity {
+            // Diagnostics take precedence, using their most severe sign.
+            let color = match severity {
+                Severity::Error => RGBColor(220, 0, 0),
+                Severity::Warning => RGBColor(220, 180, 0),
+                Severity::Information => RGBColor(0, 120, 220),
+                Severity::Hint => RGBColor(150, 150, 150),
+            };
+            let colors = if self.on_cursor_line() {
+                Colors::CustomFocusedForeground(color)
+            } else {
+                Colors::CustomForeground(color)
+            };
+            Some((severity.sign(), colors))
+        } else if self.bookmarks.binary_search(&self.buffer_position.line).is_ok() {
+            Some(("•", Colors::Focused))
+        } else {
+            None
+        };
+
+        if let Some((sign, colors)) = sign {
+            self.print(
+                Position{ line: self.screen_position.line, offset: 0 },
+                Style::Bold,
+                colors,
+                sign
+            );
+            self.print(
+                Position{ line: self.screen_position.line, offset: 1 },
+                weight,
+                Colors::Focused,
+                line_number[1..].to_string()
+            );
+        } else {
+            self.print(
+                Position{ line: self.screen_position.line, offset: 0 },
+                weight,
+                Colors::Focused,
+                line_number
+            );
+        }
 
         // Leave a one-column gap between line numbers and buffer content.
         let gap_color = if self.on_cursor_line() {
@@ -328,9 +580,91 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             " "
         );
 
+        // Flag lines that differ from the git index in the gap column.
+        let line = self.buffer_position.line;
+        if let Some(&(_, change)) = self.line_changes.iter().find(|&&(l, _)| l == line) {
+            let (sign, color) = match change {
+                LineChange::Added => ("+", RGBColor(0, 200, 0)),
+                LineChange::Modified => ("~", RGBColor(220, 180, 0)),
+                LineChange::Removed => ("_", RGBColor(220, 0, 0)),
+            };
+            let colors = if self.on_cursor_line() {
+                Colors::CustomFocusedForeground(color)
+            } else {
+                Colors::CustomForeground(color)
+            };
+            self.print(
+                Position{ line: self.screen_position.line, offset: self.line_numbers.width() },
+                weight,
+                colors,
+                sign
+            );
+        }
+
         self.screen_position.offset = self.line_numbers.width() + 1;
     }
 
+    /// Notes the number of lines hidden by the fold
+    /// starting on the current line, following its content.
+    fn print_fold_marker(&mut self) {
+        let line = self.buffer_position.line;
+        let hidden_count = self.folds
+            .iter()
+            .find(|&&(start, _)| start == line)
+            .map(|&(start, end)| end - start)
+            .unwrap_or(0);
+
+        for character in format!(" ⋯ {} lines", hidden_count).chars() {
+            if self.screen_position.offset < self.region.width {
+                self.print(self.screen_position, Style::Bold, Colors::Focused, character.to_string());
+                self.screen_position.offset += 1;
+            }
+        }
+    }
+
+    /// Marks the end of a line (after the cursor's been placed, since
+    /// the marker occupies the cell the cursor sits on at the line's end).
+    fn print_line_ending_marker(&mut self) {
+        if self.screen_position.offset >= self.region.width {
+            return;
+        }
+
+        let (style, colors) = if self.on_secondary_cursor() {
+            (Style::Inverted, Colors::Default)
+        } else if self.on_cursor_line() {
+            (Style::Default, Colors::CustomFocusedForeground(self.whitespace_color()))
+        } else {
+            (Style::Default, Colors::CustomForeground(self.whitespace_color()))
+        };
+        self.print(self.screen_position, style, colors, "¬");
+        self.screen_position.offset += 1;
+    }
+
+    /// Swaps the foreground of regular (i.e. not highlighted) text for the
+    /// theme's dimmer whitespace color, keeping the cursor line's background.
+    fn whitespace_colors(&self, colors: Colors) -> Colors {
+        match colors {
+            Colors::CustomForeground(_) => Colors::CustomForeground(self.whitespace_color()),
+            Colors::CustomFocusedForeground(_) => Colors::CustomFocusedForeground(self.whitespace_color()),
+            colors => colors,
+        }
+    }
+
+    fn whitespace_color(&self) -> RGBColor {
+        self.theme.settings.guide.map(to_rgb_color).unwrap_or(RGBColor(100, 100, 100))
+    }
+
+    /// The indentation of the line's continuation rows, if it's wrapped.
+    fn line_wrap_indent(&self, line: &str) -> usize {
+        if !self.preferences.wrap_indent() {
+            return 0;
+        }
+        let tab_width = self.preferences.tab_width(self.buffer.path.as_ref());
+        let content_width = self.region.width.checked_sub(self.gutter_width).unwrap_or(0);
+
+        indent::wrap_width(line, tab_width, content_width)
+    }
+
     fn next_tab_stop(&self, offset: usize) -> usize {
         (offset / self.preferences.tab_width(self.buffer.path.as_ref()) + 1) * self.preferences.tab_width(self.buffer.path.as_ref())
     }
@@ -370,7 +704,7 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         where C: Into<Cow<'p, str>>
     {
         self.terminal_buffer.set_cell(
-            position,
+            Position{ line: position.line + self.region.line, offset: position.offset + self.region.offset },
             Cell{ content: content.into(), style, colors }
         );
     }
@@ -387,7 +721,7 @@ fn has_trailing_newline(line: &str) -> bool {
 mod tests {
     use crate::models::application::Preferences;
     use scribe::{Buffer, Workspace};
-    use scribe::buffer::Position;
+    use scribe::buffer::{Position, Range};
     use scribe::util::LineIterator;
     use std::cell::RefCell;
     use std::collections::HashMap;
@@ -395,6 +729,9 @@ mod tests {
     use std::rc::Rc;
     use super::{BufferRenderer, LexemeMapper, MappedLexeme};
     use syntect::highlighting::ThemeSet;
+    use crate::util::lsp::{Diagnostic, Severity};
+    use crate::util::spell::Dictionary;
+    use crate::view::{Colors, Region, Style};
     use crate::view::terminal::*;
     use crate::yaml::yaml::YamlLoader;
 
@@ -465,6 +802,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_confines_content_to_the_region() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("ab");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let render_cache = Rc::new(RefCell::new(HashMap::new()));
+
+        let cursor_position = {
+            let mut renderer = BufferRenderer::new(
+                workspace.current_buffer().unwrap(),
+                None,
+                0,
+                &**terminal,
+                &theme_set.themes["base16-ocean.dark"],
+                &preferences,
+                &render_cache,
+                &mut terminal_buffer
+            );
+            renderer.set_region(Region{ line: 1, offset: 4, width: 6, height: 8 });
+            renderer.render(lines, None).unwrap()
+        };
+
+        let expected_content = "          \n     1  ab";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+        assert_eq!(cursor_position, Some(Position{ line: 1, offset: 8 }));
+    }
+
     #[test]
     fn unaligned_tabs_expand_to_correct_number_of_spaces() {
         // Set up a workspace and buffer; the workspace will
@@ -537,6 +912,154 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_skips_folded_lines_and_keeps_their_numbers() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("a\nb\nc\nd\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let render_cache = Rc::new(RefCell::new(HashMap::new()));
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &render_cache,
+            &mut terminal_buffer
+        );
+        renderer.set_folds(&[(0, 2)]);
+        renderer.render(lines, None).unwrap();
+
+        let expected_content = " 1  a ⋯ 2 \n 4  d     \n 5        ";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
+    #[test]
+    fn render_flags_lines_with_diagnostics_in_the_gutter() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let render_cache = Rc::new(RefCell::new(HashMap::new()));
+        let diagnostics = vec![
+            Diagnostic{ line: 1, severity: Severity::Hint, message: String::from("hint") },
+            Diagnostic{ line: 1, severity: Severity::Warning, message: String::from("warning") },
+        ];
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &render_cache,
+            &mut terminal_buffer
+        );
+        renderer.set_diagnostics(&diagnostics);
+        renderer.render(lines, None).unwrap();
+
+        let expected_content = " 1  amp   \nW2  editor";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
+    #[test]
+    fn render_indents_continuation_rows_when_enabled() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("  abcdefghij");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("wrap_indent: true").unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        let expected_content = " 1    abcd\n      efgh\n      ij  ";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
+    #[test]
+    fn render_shows_control_characters_when_enabled() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("a\u{1b}b");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("show_control_chars: true").unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        let expected_content = " 1  a^[b";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
     // Used to test lexeme mapper usage.
     struct TestMapper {}
     impl LexemeMapper for TestMapper {
@@ -761,4 +1284,135 @@ mod tests {
             &terminal_buffer.content()[0..expected_content.len()],
             expected_content);
     }
+
+    #[test]
+    fn misspelled_prose_is_underlined() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("teh cat");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let render_cache = Rc::new(RefCell::new(HashMap::new()));
+        let dictionary = Dictionary::parse("the\ncat");
+
+        {
+            let mut renderer = BufferRenderer::new(
+                workspace.current_buffer().unwrap(),
+                None,
+                0,
+                &**terminal,
+                &theme_set.themes["base16-ocean.dark"],
+                &preferences,
+                &render_cache,
+                &mut terminal_buffer
+            );
+            renderer.set_dictionary(&dictionary);
+            renderer.render(lines, None).unwrap();
+        }
+
+        // Buffer content starts after the three-column line number and gap.
+        let styles: Vec<Style> = terminal_buffer
+            .iter()
+            .filter(|&(position, _)| position.line == 0 && position.offset >= 4 && position.offset < 10)
+            .map(|(_, cell)| cell.style)
+            .collect();
+        assert_eq!(styles, vec![
+            Style::Underlined,
+            Style::Underlined,
+            Style::Underlined,
+            Style::Default,
+            Style::Default,
+            Style::Default
+        ]);
+    }
+
+    #[test]
+    fn word_highlights_use_word_highlight_colors() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp amp");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let render_cache = Rc::new(RefCell::new(HashMap::new()));
+        let word_highlights = vec![
+            Range::new(Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 7 })
+        ];
+
+        {
+            let mut renderer = BufferRenderer::new(
+                workspace.current_buffer().unwrap(),
+                None,
+                0,
+                &**terminal,
+                &theme_set.themes["base16-ocean.dark"],
+                &preferences,
+                &render_cache,
+                &mut terminal_buffer
+            );
+            renderer.set_word_highlights(&word_highlights);
+            renderer.render(lines, None).unwrap();
+        }
+
+        // Buffer content starts after the three-column line number and gap.
+        let highlighted: Vec<bool> = terminal_buffer
+            .iter()
+            .filter(|&(position, _)| position.line == 0 && position.offset >= 4 && position.offset < 10)
+            .map(|(_, cell)| cell.colors == Colors::WordHighlight)
+            .collect();
+        assert_eq!(highlighted, vec![false, false, false, false, true, true]);
+    }
+
+    #[test]
+    fn whitespace_is_shown_without_moving_the_cursor() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\ta \nb");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let terminal = build_terminal().unwrap();
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let mut render = |show_whitespace| {
+            let render_cache = Rc::new(RefCell::new(HashMap::new()));
+            let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+            let cursor_position = {
+                let mut renderer = BufferRenderer::new(
+                    workspace.current_buffer().unwrap(),
+                    None,
+                    0,
+                    &**terminal,
+                    &theme_set.themes["base16-ocean.dark"],
+                    &preferences,
+                    &render_cache,
+                    &mut terminal_buffer
+                );
+                if show_whitespace {
+                    renderer.set_show_whitespace();
+                }
+                renderer.render(LineIterator::new(&data), None).unwrap()
+            };
+
+            (terminal_buffer.content(), cursor_position)
+        };
+
+        let (plain_content, plain_cursor) = render(false);
+        let (content, cursor) = render(true);
+        assert!(!plain_content.contains("→"));
+        assert_eq!(content.lines().nth(0).unwrap(), " 1  → a·¬ ");
+        assert_eq!(cursor, plain_cursor);
+    }
 }
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use scribe::buffer::Buffer;
 use unicode_segmentation::UnicodeSegmentation;
+use crate::util::indent;
 use crate::view::buffer::LineNumbers;
 use crate::view::terminal::Terminal;
 
@@ -10,6 +11,8 @@ use crate::view::terminal::Terminal;
 pub struct ScrollableRegion {
     terminal: Arc<Box<Terminal + Sync + Send + 'static>>,
     line_offset: usize,
+    size: Option<(usize, usize)>,
+    wrap_indent: Option<usize>,
 }
 
 impl ScrollableRegion {
@@ -17,6 +20,8 @@ impl ScrollableRegion {
         ScrollableRegion {
             terminal,
             line_offset: 0,
+            size: None,
+            wrap_indent: None,
         }
     }
 
@@ -31,7 +36,7 @@ impl ScrollableRegion {
             // Calculate and apply the absolute line
             // offset based on the cursor location.
             let starting_line = (buffer.cursor.line).checked_sub(
-                self.preceding_line_count(&buffer, self.height())
+                self.preceding_line_count(&buffer, buffer.cursor.line, self.height())
             ).unwrap_or(0);
 
             if starting_line > self.line_offset {
@@ -42,19 +47,43 @@ impl ScrollableRegion {
 
     /// Moves the line offset such that the specified line is centered vertically.
     pub fn scroll_to_center(&mut self, buffer: &Buffer) {
-        let limit = (self.height() as f32 / 2.0).ceil() as usize;
+        self.scroll_to_fraction(buffer, 0.5);
+    }
+
+    /// Moves the line offset such that the cursor line sits at the specified
+    /// fraction of the region's height (0.0 being the top, and 0.5 the center).
+    pub fn scroll_to_fraction(&mut self, buffer: &Buffer, fraction: f32) {
+        let limit = (self.height() as f32 * fraction.max(0.0).min(1.0)).ceil() as usize;
 
         self.line_offset = buffer.cursor.line.checked_sub(
-            self.preceding_line_count(&buffer, limit)
+            self.preceding_line_count(&buffer, buffer.cursor.line, limit.max(1))
         ).unwrap_or(0);
     }
 
+    /// Accounts for wrapped lines' continuation rows being indented to match
+    /// their leading whitespace, measured using the specified tab width.
+    /// Passing None lays continuation rows out without indentation.
+    pub fn set_wrap_indent(&mut self, tab_width: Option<usize>) {
+        self.wrap_indent = tab_width;
+    }
+
     /// The number of lines the region has scrolled over.
     /// A value of zero represents an unscrolled region.
     pub fn line_offset(&self) -> usize {
         self.line_offset
     }
 
+    pub fn set_line_offset(&mut self, line_offset: usize) {
+        self.line_offset = line_offset;
+    }
+
+    /// Confines the region to the specified width and height, rather than
+    /// the full terminal (e.g. a split pane), which affects how lines
+    /// are wrapped and how many fit on-screen.
+    pub fn set_size(&mut self, size: Option<(usize, usize)>) {
+        self.size = size;
+    }
+
     pub fn scroll_up(&mut self, amount: usize) {
         self.line_offset = match self.line_offset.checked_sub(amount) {
             Some(amount) => amount,
@@ -66,23 +95,60 @@ impl ScrollableRegion {
         self.line_offset += amount;
     }
 
-    /// Scrollable regions occupy one line short of the full
-    /// terminal height, which is reserved for the status line.
+    /// Scrolls down by the specified amount, stopping once the
+    /// buffer's last line has reached the bottom of the region.
+    pub fn scroll_down_within_buffer(&mut self, buffer: &Buffer, amount: usize) {
+        let last_line = buffer.line_count().checked_sub(1).unwrap_or(0);
+        let max_offset = last_line.checked_sub(
+            self.preceding_line_count(&buffer, last_line, self.height())
+        ).unwrap_or(0);
+
+        self.line_offset = self.line_offset.max(
+            (self.line_offset + amount).min(max_offset)
+        );
+    }
+
+    /// The number of buffer lines that fit entirely within
+    /// the region, taking line wrapping into consideration.
+    pub fn visible_line_count(&self, buffer: &Buffer) -> usize {
+        let gutter_width = LineNumbers::new(&buffer, None).width() + 1;
+        let data = buffer.data();
+        let mut lines = data.lines().skip(self.line_offset);
+        let mut consumed_height = 0;
+        let mut visible_line_count = 0;
+
+        for _ in self.line_offset..buffer.line_count() {
+            // The lines() iterator doesn't yield a final line for
+            // trailing newlines, but Amp considers there to be one.
+            let line = lines.next().unwrap_or("");
+            consumed_height += self.wrapped_line_count(line, gutter_width);
+
+            if consumed_height > self.height() {
+                break;
+            }
+            visible_line_count += 1;
+        }
+
+        visible_line_count
+    }
+
+    /// Scrollable regions occupy one line short of the full terminal
+    /// height, which is reserved for the status line, unless sized otherwise.
     fn height(&self) -> usize {
-        self.terminal.height() - 1
+        self.size.map(|(_, height)| height.max(1)).unwrap_or_else(|| self.terminal.height() - 1)
     }
 
-    /// Assuming that the buffer cursor is at the bottom of the screen,
+    /// Assuming that the specified line is at the bottom of the screen,
     /// counts the number of preceding lines that can be fit above it
     /// on-screen, taking line wrapping into consideration.
-    fn preceding_line_count(&self, buffer: &Buffer, limit: usize) -> usize {
+    fn preceding_line_count(&self, buffer: &Buffer, line: usize, limit: usize) -> usize {
         let mut preceding_line_count = 0;
 
         // The buffer renderer adds a single-column margin
         // to the right-hand side of the line number columns.
         let gutter_width = LineNumbers::new(&buffer, None).width() + 1;
 
-        let end = buffer.cursor.line + 1;
+        let end = line + 1;
         let start = end.checked_sub(limit).unwrap_or(0);
         let line_count = end - start;
 
@@ -91,13 +157,7 @@ impl ScrollableRegion {
             .lines()
             .skip(start)
             .take(line_count)
-            .map(|line| {
-                let grapheme_count = line.graphemes(true).count().max(1) as f32;
-                let buffer_content_width = (self.terminal.width() - gutter_width) as f32;
-                let wrapped_line_count = grapheme_count / buffer_content_width;
-
-                wrapped_line_count.ceil() as usize
-            })
+            .map(|line| self.wrapped_line_count(line, gutter_width))
             .collect();
 
         // Figure out how many lines we can fit
@@ -122,6 +182,25 @@ impl ScrollableRegion {
 
         preceding_line_count
     }
+
+    /// The number of screen lines the line occupies when wrapped.
+    fn wrapped_line_count(&self, line: &str, gutter_width: usize) -> usize {
+        let grapheme_count = line.graphemes(true).count().max(1);
+        let width = self.size.map(|(width, _)| width).unwrap_or_else(|| self.terminal.width());
+        let buffer_content_width = width.checked_sub(gutter_width).unwrap_or(0).max(1);
+        if grapheme_count <= buffer_content_width {
+            return 1;
+        }
+
+        // Continuation rows are narrowed by their indentation.
+        let indent = self.wrap_indent
+            .map(|tab_width| indent::wrap_width(line, tab_width, buffer_content_width))
+            .unwrap_or(0);
+        let continuation_width = buffer_content_width - indent;
+        let continuation_count = grapheme_count - buffer_content_width;
+
+        1 + (continuation_count + continuation_width - 1) / continuation_width
+    }
 }
 
 #[cfg(test)]
@@ -286,6 +365,23 @@ mod tests {
         assert_eq!(region.line_offset(), 1);
     }
 
+    #[test]
+    fn scroll_to_fraction_places_the_cursor_line_at_the_specified_fraction() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        for _ in 0..20 {
+            buffer.insert("\n");
+        }
+        buffer.cursor.move_to(Position{ line: 20, offset: 0 });
+
+        region.scroll_to_fraction(&buffer, 0.0);
+        assert_eq!(region.line_offset(), 20);
+
+        region.scroll_to_fraction(&buffer, 0.5);
+        assert_eq!(region.line_offset(), 16);
+    }
+
     #[test]
     fn scroll_down_increases_line_offset_by_amount() {
         let terminal = build_terminal().unwrap();
@@ -310,4 +406,71 @@ mod tests {
         region.scroll_up(5);
         assert_eq!(region.line_offset(), 0);
     }
+
+    #[test]
+    fn scroll_down_within_buffer_stops_with_the_last_line_at_the_bottom() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        for _ in 0..20 {
+            buffer.insert("\n");
+        }
+        region.scroll_down_within_buffer(&buffer, 4);
+        assert_eq!(region.line_offset(), 4);
+
+        region.scroll_down_within_buffer(&buffer, 20);
+        assert_eq!(region.line_offset(), 12);
+    }
+
+    #[test]
+    fn scroll_down_within_buffer_does_not_scroll_up_from_beyond_the_limit() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        for _ in 0..20 {
+            buffer.insert("\n");
+        }
+        region.scroll_down(15);
+        region.scroll_down_within_buffer(&buffer, 4);
+        assert_eq!(region.line_offset(), 15);
+    }
+
+    #[test]
+    fn visible_line_count_considers_wrapped_lines_and_the_end_of_the_buffer() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        // Insert non-wrapped lines at the top.
+        for _ in 0..10 {
+            buffer.insert("\n");
+        }
+        // Insert wrapped lines below.
+        buffer.cursor.move_to(Position{ line: 10, offset: 0 });
+        for _ in 0..4 {
+            // Less than ten spaces to confirm that line numbers
+            // are considered, which eat into terminal space.
+            buffer.insert("       \n");
+        }
+        assert_eq!(region.visible_line_count(&buffer), 9);
+
+        region.scroll_down(8);
+        assert_eq!(region.visible_line_count(&buffer), 5);
+
+        region.scroll_down(4);
+        assert_eq!(region.visible_line_count(&buffer), 3);
+    }
+
+    #[test]
+    fn visible_line_count_considers_indented_continuation_rows() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        // Each line spans two rows, or three once
+        // its continuation rows are indented.
+        buffer.insert(&"  abcdefghij\n".repeat(5));
+        assert_eq!(region.visible_line_count(&buffer), 4);
+
+        region.set_wrap_indent(Some(4));
+        assert_eq!(region.visible_line_count(&buffer), 3);
+    }
 }
@@ -0,0 +1,31 @@
+/// Line, word, and character counts for a piece of text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextStats {
+    pub lines: usize,
+    pub words: usize,
+    pub characters: usize,
+}
+
+impl TextStats {
+    pub fn new(content: &str) -> TextStats {
+        TextStats {
+            lines: content.lines().count(),
+            words: content.split_whitespace().count(),
+            characters: content.chars().count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextStats;
+
+    #[test]
+    fn new_counts_lines_words_and_characters() {
+        assert_eq!(
+            TextStats::new("amp is a\n  text editor\n"),
+            TextStats { lines: 2, words: 5, characters: 23 }
+        );
+        assert_eq!(TextStats::new(""), TextStats::default());
+    }
+}
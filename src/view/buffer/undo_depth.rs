@@ -0,0 +1,175 @@
+/// Tracks the depth of a buffer's undo and redo stacks, which scribe doesn't
+/// expose. Changes are recorded as they happen; those made while an operation
+/// group is open count as a single step once the group is closed.
+#[derive(Default)]
+pub struct UndoDepth {
+    undo: usize,
+    redo: usize,
+    changes: usize,
+    grouped: bool,
+    held: bool,
+    pending: bool,
+    replaying: bool,
+}
+
+impl UndoDepth {
+    pub fn new() -> UndoDepth {
+        UndoDepth::default()
+    }
+
+    /// The number of steps that can be undone.
+    pub fn undo_count(&self) -> usize {
+        self.undo
+    }
+
+    /// The number of steps that can be redone.
+    pub fn redo_count(&self) -> usize {
+        self.redo
+    }
+
+    /// The number of steps made by editing (rather than undoing or redoing).
+    pub fn change_count(&self) -> usize {
+        self.changes
+    }
+
+    /// Records a change to the buffer. Changes made while
+    /// undoing or redoing (replaying history) are ignored.
+    pub fn record_change(&mut self) {
+        if self.replaying {
+            return;
+        }
+
+        if self.grouped {
+            self.pending = true;
+        } else {
+            self.push();
+        }
+    }
+
+    pub fn start_group(&mut self) {
+        self.grouped = true;
+    }
+
+    /// Closes the open group, returning whether it was closed;
+    /// a group that's being held stays open.
+    pub fn end_group(&mut self) -> bool {
+        if self.held {
+            return false;
+        }
+
+        self.grouped = false;
+        if self.pending {
+            self.push();
+        }
+
+        true
+    }
+
+    /// Keeps the open group from being closed until `release_group` is called.
+    pub fn hold_group(&mut self) {
+        self.held = true;
+    }
+
+    pub fn release_group(&mut self) {
+        self.held = false;
+    }
+
+    /// Flags changes recorded until the next call to `end_replay` as
+    /// the result of undoing or redoing, rather than new edits.
+    pub fn start_replay(&mut self) {
+        self.replaying = true;
+    }
+
+    pub fn end_replay(&mut self) {
+        self.replaying = false;
+    }
+
+    pub fn undo(&mut self) {
+        // Undoing closes an open group, which becomes the step that's undone.
+        self.held = false;
+        self.end_group();
+
+        if self.undo > 0 {
+            self.undo -= 1;
+            self.redo += 1;
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if self.redo > 0 {
+            self.redo -= 1;
+            self.undo += 1;
+        }
+    }
+
+    // New edits discard anything that could previously be redone.
+    fn push(&mut self) {
+        self.undo += 1;
+        self.redo = 0;
+        self.changes += 1;
+        self.pending = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UndoDepth;
+
+    #[test]
+    fn grouped_changes_count_as_a_single_step() {
+        let mut depth = UndoDepth::new();
+        depth.record_change();
+        depth.start_group();
+        depth.record_change();
+        depth.record_change();
+        assert_eq!(depth.undo_count(), 1);
+
+        depth.end_group();
+        assert_eq!(depth.undo_count(), 2);
+    }
+
+    #[test]
+    fn held_groups_arent_closed_until_released() {
+        let mut depth = UndoDepth::new();
+        depth.start_group();
+        depth.hold_group();
+        depth.record_change();
+        assert!(!depth.end_group());
+        depth.record_change();
+        assert_eq!(depth.undo_count(), 0);
+
+        depth.release_group();
+        assert!(depth.end_group());
+        assert_eq!(depth.undo_count(), 1);
+    }
+
+    #[test]
+    fn undo_and_redo_move_steps_between_stacks() {
+        let mut depth = UndoDepth::new();
+        depth.record_change();
+        depth.record_change();
+
+        depth.start_replay();
+        depth.record_change();
+        depth.end_replay();
+        depth.undo();
+        assert_eq!((depth.undo_count(), depth.redo_count()), (1, 1));
+
+        depth.redo();
+        depth.redo();
+        assert_eq!((depth.undo_count(), depth.redo_count()), (2, 0));
+        assert_eq!(depth.change_count(), 2);
+    }
+
+    #[test]
+    fn new_changes_discard_redo_steps() {
+        let mut depth = UndoDepth::new();
+        depth.record_change();
+        depth.undo();
+        depth.undo();
+        assert_eq!((depth.undo_count(), depth.redo_count()), (0, 1));
+
+        depth.record_change();
+        assert_eq!((depth.undo_count(), depth.redo_count()), (1, 0));
+    }
+}
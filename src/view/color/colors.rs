@@ -13,6 +13,7 @@ pub enum Colors {
     PathMode,      // white/pink
     SearchMode,    // white/purple
     SelectMode,    // white/blue
+    WordHighlight, // default/selection background
     CustomForeground(RGBColor),
     CustomFocusedForeground(RGBColor),
     Custom(RGBColor, RGBColor),
@@ -26,6 +26,12 @@ impl ColorMap for Theme {
             map(to_rgb_color).
             unwrap_or(RGBColor(55, 55, 55));
 
+        let selection_bg = self.
+            settings.
+            selection.
+            map(to_rgb_color).
+            unwrap_or(RGBColor(80, 80, 80));
+
         match colors {
             Colors::Default => Colors::Custom(fg, bg),
             Colors::Focused => Colors::Custom(fg, alt_bg),
@@ -35,6 +41,7 @@ impl ColorMap for Theme {
             Colors::PathMode => Colors::Custom(RGBColor(255, 255, 255), RGBColor(255, 20, 147)),
             Colors::SearchMode => Colors::Custom(RGBColor(255, 255, 255), RGBColor(120, 0, 120)),
             Colors::SelectMode => Colors::Custom(RGBColor(255, 255, 255), RGBColor(0, 120, 160)),
+            Colors::WordHighlight => Colors::Custom(fg, selection_bg),
             Colors::CustomForeground(custom_fg) => Colors::Custom(custom_fg, bg),
             Colors::CustomFocusedForeground(custom_fg) => Colors::Custom(custom_fg, alt_bg),
             Colors::Custom(custom_fg, custom_bg) => Colors::Custom(custom_fg, custom_bg),
@@ -4,43 +4,77 @@ mod buffer;
 mod data;
 mod event_listener;
 mod presenter;
+mod pane_tree;
 mod style;
 mod theme_loader;
 
 // Published API
 pub use self::data::StatusLineData;
-pub use self::buffer::{LexemeMapper, MappedLexeme};
+pub use self::buffer::{LexemeMapper, MappedLexeme, TextStats};
 pub use self::style::Style;
 pub use self::color::{Colors, RGBColor};
 pub use self::presenter::Presenter;
+pub use self::pane_tree::{Direction, Layout, Orientation, Pane, Region};
 pub use self::terminal::*;
 
 use crate::errors::*;
 use crate::input::Key;
 use crate::models::application::{Event, Preferences};
-use self::buffer::{RenderCache, RenderState};
+use crate::util::conflict;
+use crate::util::git::LineChange;
+use crate::util::encoding::SourceEncoding;
+use crate::util::fold::Block;
+use crate::util::indent;
+use crate::util::line_ending::{self, LineEndings};
+use crate::util::lsp::Diagnostic;
+use crate::util::spell::Dictionary;
+use self::buffer::{Bookmarks, Folds, LineNumbers, RenderCache, RenderState, UndoDepth};
 use self::buffer::ScrollableRegion;
 use self::event_listener::EventListener;
-use scribe::buffer::Buffer;
+use self::pane_tree::{PaneTree, Snapshot};
+use scribe::buffer::{Buffer, Position, Range};
 use std::cmp;
-use std::collections::HashMap;
+use std::fs;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::ops::Drop;
 use std::sync::mpsc::{self, Sender, SyncSender};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use self::theme_loader::ThemeLoader;
 use syntect::highlighting::ThemeSet;
+use unicode_segmentation::UnicodeSegmentation;
 
 const RENDER_CACHE_FREQUENCY: usize = 100;
 
+// Checking the repository for uncommitted changes scans the working
+// tree, so the result is reused for this long (or until a save).
+const GIT_DIRTY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct View {
     terminal: Arc<Box<Terminal + Sync + Send + 'static>>,
     scrollable_regions: HashMap<usize, ScrollableRegion>,
     render_caches: HashMap<usize, Rc<RefCell<HashMap<usize, RenderState>>>>,
+    bookmarks: HashMap<usize, Rc<RefCell<Bookmarks>>>,
+    folds: HashMap<usize, Rc<RefCell<Folds>>>,
+    encodings: HashMap<usize, SourceEncoding>,
+    line_changes: HashMap<usize, Vec<(usize, LineChange)>>,
+    diagnostics: HashMap<usize, Vec<Diagnostic>>,
+    line_endings: HashMap<usize, LineEndings>,
+    read_only: HashSet<usize>,
+    undo_depths: HashMap<usize, Rc<RefCell<UndoDepth>>>,
+    text_stats: HashMap<usize, Rc<RefCell<Option<TextStats>>>>,
+    conflict_markers: HashMap<usize, Rc<RefCell<Option<Vec<Range>>>>>,
+    revisions: HashMap<usize, Rc<RefCell<usize>>>,
+    git_dirty: Option<(bool, Instant)>,
+    dictionary: Option<Dictionary>,
+    word_highlights: Option<bool>,
+    panes: Option<PaneTree>,
     pub theme_set: ThemeSet,
     preferences: Rc<RefCell<Preferences>>,
     pub last_key: Option<Key>,
+    pub mode_str: Option<&'static str>,
     event_channel: Sender<Event>,
     event_listener_killswitch: SyncSender<()>
 }
@@ -48,18 +82,44 @@ pub struct View {
 impl View {
     pub fn new(preferences: Rc<RefCell<Preferences>>, event_channel: Sender<Event>) -> Result<View> {
         let terminal = build_terminal().chain_err(|| "Failed to initialize terminal")?;
+
+        View::with_terminal(preferences, event_channel, terminal)
+    }
+
+    /// Builds a view drawing to (and listening for input from) the specified
+    /// terminal, rather than the one for the current environment.
+    pub fn with_terminal(preferences: Rc<RefCell<Preferences>>, event_channel: Sender<Event>,
+                         terminal: Arc<Box<Terminal + Sync + Send + 'static>>) -> Result<View> {
         let theme_path = preferences.borrow().theme_path()?;
         let theme_set = ThemeLoader::new(theme_path).load()?;
 
+        terminal.set_mouse_reporting(preferences.borrow().mouse());
+
         let (killswitch_tx, killswitch_rx) = mpsc::sync_channel(0);
         EventListener::start(terminal.clone(), event_channel.clone(), killswitch_rx);
 
         Ok(View {
             terminal,
             last_key: None,
+            mode_str: None,
             preferences,
             scrollable_regions: HashMap::new(),
             render_caches: HashMap::new(),
+            bookmarks: HashMap::new(),
+            folds: HashMap::new(),
+            encodings: HashMap::new(),
+            line_changes: HashMap::new(),
+            diagnostics: HashMap::new(),
+            line_endings: HashMap::new(),
+            read_only: HashSet::new(),
+            undo_depths: HashMap::new(),
+            text_stats: HashMap::new(),
+            conflict_markers: HashMap::new(),
+            revisions: HashMap::new(),
+            git_dirty: None,
+            dictionary: None,
+            word_highlights: None,
+            panes: None,
             theme_set,
             event_channel,
             event_listener_killswitch: killswitch_tx
@@ -86,6 +146,12 @@ impl View {
         Ok(())
     }
 
+    pub fn scroll_to_fraction(&mut self, buffer: &Buffer, fraction: f32) -> Result<()> {
+        self.get_region(buffer)?.scroll_to_fraction(&buffer, fraction);
+
+        Ok(())
+    }
+
     pub fn scroll_up(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
         self.get_region(buffer)?.scroll_up(amount);
 
@@ -116,11 +182,716 @@ impl View {
         Ok(())
     }
 
+    /// Scrolls down without moving the buffer's last line above the bottom of the screen.
+    pub fn scroll_down_within_buffer(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
+        self.get_region(buffer)?.scroll_down_within_buffer(&buffer, amount);
+
+        Ok(())
+    }
+
+    /// The first and last buffer lines visible on-screen.
+    pub fn visible_lines(&mut self, buffer: &Buffer) -> Result<(usize, usize)> {
+        let region = self.get_region(buffer)?;
+        let last_line = buffer.line_count().checked_sub(1).unwrap_or(0);
+        let first_visible_line = region.line_offset().min(last_line);
+        let visible_line_count = region.visible_line_count(&buffer).max(1);
+
+        Ok((
+            first_visible_line,
+            (first_visible_line + visible_line_count - 1).min(last_line)
+        ))
+    }
+
+    /// Half of the height available to buffer content (excluding the status line).
+    pub fn half_page_height(&self) -> usize {
+        (self.pane_region().height / 2).max(1)
+    }
+
+    /// Switches to the named theme (in memory only). Cached render
+    /// states hold the previous theme's styles, so they're discarded.
+    pub fn set_theme(&mut self, theme: &str) {
+        self.preferences.borrow_mut().set_theme(theme);
+        self.clear_render_caches();
+    }
+
+    /// Discards every buffer's cached render states, which
+    /// depend on the theme (e.g. after it's been changed).
+    pub fn clear_render_caches(&self) {
+        for render_cache in self.render_caches.values() {
+            render_cache.borrow_mut().clear();
+        }
+        if let Some(ref panes) = self.panes {
+            for snapshot in panes.snapshots() {
+                snapshot.render_cache.borrow_mut().clear();
+            }
+        }
+    }
+
+    /// Discards the buffer's cached render states, which
+    /// depend on its syntax (e.g. after it's been changed).
+    pub fn clear_render_cache(&self, buffer: &Buffer) -> Result<()> {
+        self.get_render_cache(buffer)?.borrow_mut().clear();
+
+        Ok(())
+    }
+
+    /// The number of columns available to a buffer's content, once its
+    /// line number gutter is accounted for; wrapped lines break at this width.
+    pub fn wrapped_line_width(&self, buffer: &Buffer) -> usize {
+        // The buffer renderer adds a single-column margin
+        // to the right-hand side of the line number columns.
+        let gutter_width = LineNumbers::new(&buffer, None).width() + 1;
+
+        self.pane_region().width.checked_sub(gutter_width).unwrap_or(0).max(1)
+    }
+
+    /// The indentation of the line's continuation rows when it's wrapped,
+    /// which is zero unless the wrap_indent preference is enabled.
+    pub fn wrap_indent_width(&self, buffer: &Buffer, line: &str) -> usize {
+        wrap_indent_tab_width(&self.preferences.borrow(), buffer)
+            .map(|tab_width| indent::wrap_width(line, tab_width, self.wrapped_line_width(buffer)))
+            .unwrap_or(0)
+    }
+
+    /// Maps a screen position to the buffer position rendered there, taking
+    /// scrolling, line wrapping, and tabs into account. The line number gutter,
+    /// the status line, and any space below the buffer's content don't map
+    /// to a buffer position. Positions beyond the end of a line map to it.
+    pub fn buffer_position(&mut self, buffer: &Buffer, screen_position: &Position) -> Result<Option<Position>> {
+        let gutter_width = LineNumbers::new(&buffer, None).width() + 1;
+        let region = self.pane_region();
+        if screen_position.line < region.line ||
+            screen_position.line >= region.line + region.height ||
+            screen_position.offset < region.offset + gutter_width ||
+            screen_position.offset >= region.offset + region.width {
+            return Ok(None);
+        }
+
+        let (line_wrapping, tab_width) = {
+            let preferences = self.preferences.borrow();
+            (preferences.line_wrapping(buffer.path.as_ref()), preferences.tab_width(buffer.path.as_ref()))
+        };
+        let content_width = self.wrapped_line_width(buffer);
+        let line_offset = self.get_region(buffer)?.line_offset();
+        let column = screen_position.offset - region.offset - gutter_width;
+        let screen_line = screen_position.line - region.line;
+
+        let mut row = 0;
+        for (line, data) in buffer.data().split('\n').enumerate().skip(line_offset) {
+            let indent = self.wrap_indent_width(buffer, data);
+            let layout = layout_line(data, content_width, line_wrapping, tab_width, indent);
+            let line_rows = layout.last().map(|&(last_row, _)| last_row + 1).unwrap_or(1);
+
+            if screen_line < row + line_rows {
+                let target_row = screen_line - row;
+                let offset = layout
+                    .iter()
+                    .position(|&(grapheme_row, end)| grapheme_row == target_row && column < end)
+                    .or_else(|| {
+                        layout.iter().rposition(|&(grapheme_row, _)| grapheme_row <= target_row).map(|index| index + 1)
+                    })
+                    .unwrap_or(0);
+
+                return Ok(Some(Position { line, offset }));
+            }
+            row += line_rows;
+        }
+
+        Ok(None)
+    }
+
+    /// Turns terminal mouse reporting on or off, based on the mouse preference.
+    pub fn update_mouse_reporting(&self) {
+        self.terminal.set_mouse_reporting(self.preferences.borrow().mouse());
+    }
+
     /// Cleans up buffer-related view data. This method
     /// should be called whenever a buffer is closed.
     pub fn forget_buffer(&mut self, buffer: &Buffer) -> Result<()> {
         self.scrollable_regions.remove(&buffer_key(buffer)?);
         self.render_caches.remove(&buffer_key(buffer)?);
+        self.bookmarks.remove(&buffer_key(buffer)?);
+        self.folds.remove(&buffer_key(buffer)?);
+        self.line_changes.remove(&buffer_key(buffer)?);
+        self.diagnostics.remove(&buffer_key(buffer)?);
+        self.line_endings.remove(&buffer_key(buffer)?);
+        self.read_only.remove(&buffer_key(buffer)?);
+        self.encodings.remove(&buffer_key(buffer)?);
+        self.undo_depths.remove(&buffer_key(buffer)?);
+        self.text_stats.remove(&buffer_key(buffer)?);
+        self.conflict_markers.remove(&buffer_key(buffer)?);
+        self.revisions.remove(&buffer_key(buffer)?);
+
+        // Panes can't outlive their buffers.
+        if let Some(ref mut panes) = self.panes {
+            panes.close_buffer_panes(buffer_key(buffer)?);
+        }
+        self.collapse_panes();
+
+        Ok(())
+    }
+
+    ///
+    /// Bookmark delegation methods.
+    ///
+
+    /// Adds a bookmark for the buffer's cursor line, or removes it if present.
+    pub fn toggle_bookmark(&mut self, buffer: &Buffer) -> Result<()> {
+        let bookmarks = self.get_bookmarks(buffer)?;
+        let mut bookmarks = bookmarks.borrow_mut();
+        bookmarks.update(buffer);
+        bookmarks.toggle(buffer.cursor.line);
+
+        // Snapshot the buffer if this is its first bookmark.
+        bookmarks.update(buffer);
+
+        Ok(())
+    }
+
+    /// Bookmarks the specified lines (e.g. those from a previous session),
+    /// leaving existing bookmarks in place. Lines beyond the end of the
+    /// buffer are ignored.
+    pub fn restore_bookmarks(&mut self, buffer: &Buffer, lines: &[usize]) -> Result<()> {
+        let bookmarks = self.get_bookmarks(buffer)?;
+        let mut bookmarks = bookmarks.borrow_mut();
+        bookmarks.update(buffer);
+        for &line in lines {
+            if line < buffer.line_count() && !bookmarks.lines().contains(&line) {
+                bookmarks.toggle(line);
+            }
+        }
+        bookmarks.update(buffer);
+
+        Ok(())
+    }
+
+    /// Returns the buffer's bookmarked lines, in ascending order.
+    pub fn bookmarks(&self, buffer: &Buffer) -> Result<Vec<usize>> {
+        let bookmarks = self.get_bookmarks(buffer)?;
+        let mut bookmarks = bookmarks.borrow_mut();
+        bookmarks.update(buffer);
+
+        Ok(bookmarks.lines().to_vec())
+    }
+
+    ///
+    /// Fold delegation methods.
+    ///
+
+    /// Folds the specified lines, replacing any overlapping folds.
+    pub fn fold(&mut self, buffer: &Buffer, start: usize, end: usize) -> Result<()> {
+        let folds = self.get_folds(buffer)?;
+        let mut folds = folds.borrow_mut();
+        folds.update(buffer.line_count());
+        folds.add(start, end);
+
+        Ok(())
+    }
+
+    /// Removes the fold containing the specified line,
+    /// returning false if there isn't one.
+    pub fn unfold(&mut self, buffer: &Buffer, line: usize) -> Result<bool> {
+        let folds = self.get_folds(buffer)?;
+        let mut folds = folds.borrow_mut();
+        folds.update(buffer.line_count());
+
+        Ok(folds.remove(line))
+    }
+
+    pub fn unfold_all(&mut self, buffer: &Buffer) -> Result<()> {
+        self.get_folds(buffer)?.borrow_mut().clear();
+
+        Ok(())
+    }
+
+    /// Replaces the buffer's folds with the blocks at the specified depth.
+    pub fn fold_to_depth(&mut self, buffer: &Buffer, blocks: &[Block], depth: usize) -> Result<()> {
+        let folds = self.get_folds(buffer)?;
+        let mut folds = folds.borrow_mut();
+        folds.update(buffer.line_count());
+        folds.fold_to_depth(blocks, depth);
+
+        Ok(())
+    }
+
+    /// Returns the depth of the buffer's shallowest folded block.
+    pub fn folded_depth(&self, buffer: &Buffer, blocks: &[Block]) -> Result<usize> {
+        let folds = self.get_folds(buffer)?;
+        let mut folds = folds.borrow_mut();
+        folds.update(buffer.line_count());
+
+        Ok(folds.folded_depth(blocks))
+    }
+
+    /// Returns the buffer's folded line ranges, in ascending order.
+    pub fn folds(&self, buffer: &Buffer) -> Result<Vec<(usize, usize)>> {
+        let folds = self.get_folds(buffer)?;
+        let mut folds = folds.borrow_mut();
+        folds.update(buffer.line_count());
+
+        Ok(folds.ranges().to_vec())
+    }
+
+    /// Whether the specified line is hidden by a fold (i.e. follows its first line).
+    pub fn is_folded(&self, buffer: &Buffer, line: usize) -> bool {
+        self.folds(buffer)
+            .map(|folds| folds.iter().any(|&(start, end)| start < line && line <= end))
+            .unwrap_or(false)
+    }
+
+    ///
+    /// Undo history delegation methods.
+    ///
+
+    /// Returns the number of steps that can be undone and redone, respectively.
+    pub fn undo_depth(&self, buffer: &Buffer) -> Result<(usize, usize)> {
+        let depth = self.get_undo_depth(buffer)?.borrow();
+
+        Ok((depth.undo_count(), depth.redo_count()))
+    }
+
+    /// Line, word, and character counts for the buffer's content. These are
+    /// cached, and only recomputed once the buffer has been modified.
+    pub fn text_stats(&self, buffer: &Buffer) -> TextStats {
+        let cache = buffer_key(buffer).ok().and_then(|key| self.text_stats.get(&key));
+
+        match cache {
+            Some(cache) => *cache.borrow_mut().get_or_insert_with(|| TextStats::new(&buffer.data())),
+            None => TextStats::new(&buffer.data()),
+        }
+    }
+
+    /// Ranges covering the marker lines of the buffer's merge conflicts. Like
+    /// text stats, these are cached, and only rebuilt once the buffer changes.
+    pub fn conflict_markers(&self, buffer: &Buffer) -> Vec<Range> {
+        let cache = buffer_key(buffer).ok().and_then(|key| self.conflict_markers.get(&key));
+
+        match cache {
+            Some(cache) => cache
+                .borrow_mut()
+                .get_or_insert_with(|| conflict::marker_ranges(&buffer.data()))
+                .clone(),
+            None => conflict::marker_ranges(&buffer.data()),
+        }
+    }
+
+    /// Whether the repository has uncommitted changes, as of the last check. Returns
+    /// None if it hasn't been checked recently, or a buffer has been saved since.
+    pub fn git_dirty(&self) -> Option<bool> {
+        self.git_dirty
+            .filter(|&(_, checked_at)| checked_at.elapsed() < GIT_DIRTY_CHECK_INTERVAL)
+            .map(|(dirty, _)| dirty)
+    }
+
+    pub fn set_git_dirty(&mut self, dirty: bool) {
+        self.git_dirty = Some((dirty, Instant::now()));
+    }
+
+    pub fn clear_git_dirty(&mut self) {
+        self.git_dirty = None;
+    }
+
+    /// Returns the number of steps made by editing the buffer (rather than
+    /// undoing or redoing), or zero if the buffer isn't being tracked.
+    pub fn change_count(&self, buffer: &Buffer) -> usize {
+        buffer_key(buffer)
+            .ok()
+            .and_then(|key| self.undo_depths.get(&key))
+            .map(|depth| depth.borrow().change_count())
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of times the buffer has been modified (including
+    /// undos, redos, and reloads), or zero if the buffer isn't being tracked.
+    /// Anything derived from the buffer's content can compare this against
+    /// the revision it was built from to tell when it needs to be rebuilt.
+    pub fn revision(&self, buffer: &Buffer) -> usize {
+        buffer_key(buffer)
+            .ok()
+            .and_then(|key| self.revisions.get(&key))
+            .map(|revision| *revision.borrow())
+            .unwrap_or(0)
+    }
+
+    /// Undoes the buffer's last change, keeping its tracked undo depth in sync.
+    pub fn undo(&mut self, buffer: &mut Buffer) -> Result<()> {
+        let depth = self.undo_depths.get(&buffer_key(buffer)?).cloned();
+        if let Some(ref depth) = depth {
+            depth.borrow_mut().start_replay();
+        }
+        buffer.undo();
+        if let Some(depth) = depth {
+            let mut depth = depth.borrow_mut();
+            depth.end_replay();
+            depth.undo();
+        }
+
+        Ok(())
+    }
+
+    /// Redoes the buffer's last undone change, keeping its tracked undo depth in sync.
+    pub fn redo(&mut self, buffer: &mut Buffer) -> Result<()> {
+        let depth = self.undo_depths.get(&buffer_key(buffer)?).cloned();
+        if let Some(ref depth) = depth {
+            depth.borrow_mut().start_replay();
+        }
+        buffer.redo();
+        if let Some(depth) = depth {
+            let mut depth = depth.borrow_mut();
+            depth.end_replay();
+            depth.redo();
+        }
+
+        Ok(())
+    }
+
+    /// Signals that the buffer's subsequent changes make up a single undo step,
+    /// grouping them on the buffer itself. Buffers that haven't been initialized
+    /// by the view aren't tracked.
+    pub fn start_undo_group(&mut self, buffer: &mut Buffer) -> Result<()> {
+        buffer.start_operation_group();
+        if let Some(depth) = self.undo_depths.get(&buffer_key(buffer)?) {
+            depth.borrow_mut().start_group();
+        }
+
+        Ok(())
+    }
+
+    /// Closes the buffer's undo group, unless it's being held open.
+    pub fn end_undo_group(&mut self, buffer: &mut Buffer) -> Result<()> {
+        let closed = match self.undo_depths.get(&buffer_key(buffer)?) {
+            Some(depth) => depth.borrow_mut().end_group(),
+            None => true,
+        };
+        if closed {
+            buffer.end_operation_group();
+        }
+
+        Ok(())
+    }
+
+    /// Starts an undo group that's held open (i.e. ending it has no effect)
+    /// until it's released, so that several commands make up a single step.
+    pub fn hold_undo_group(&mut self, buffer: &mut Buffer) -> Result<()> {
+        self.start_undo_group(buffer)?;
+        if let Some(depth) = self.undo_depths.get(&buffer_key(buffer)?) {
+            depth.borrow_mut().hold_group();
+        }
+
+        Ok(())
+    }
+
+    /// Stops holding the buffer's undo group open, without ending it.
+    pub fn release_undo_group(&mut self, buffer: &Buffer) -> Result<()> {
+        if let Some(depth) = self.undo_depths.get(&buffer_key(buffer)?) {
+            depth.borrow_mut().release_group();
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Git line change delegation methods.
+    ///
+
+    /// Returns the buffer's git line changes, if they've been computed.
+    pub fn line_changes(&self, buffer: &Buffer) -> Result<Option<&[(usize, LineChange)]>> {
+        Ok(self.line_changes.get(&buffer_key(buffer)?).map(|changes| changes.as_slice()))
+    }
+
+    pub fn set_line_changes(&mut self, buffer: &Buffer, changes: Vec<(usize, LineChange)>) -> Result<()> {
+        self.line_changes.insert(buffer_key(buffer)?, changes);
+
+        Ok(())
+    }
+
+    /// Discards the buffer's git line changes, so that they're recomputed.
+    pub fn clear_line_changes(&mut self, buffer: &Buffer) -> Result<()> {
+        self.line_changes.remove(&buffer_key(buffer)?);
+
+        Ok(())
+    }
+
+    ///
+    /// Diagnostic delegation methods.
+    ///
+
+    /// Returns the diagnostics last published for the buffer by its language
+    /// server, ordered by line, or an empty slice if there aren't any.
+    pub fn diagnostics(&self, buffer: &Buffer) -> &[Diagnostic] {
+        buffer_key(buffer)
+            .ok()
+            .and_then(|key| self.diagnostics.get(&key))
+            .map(|diagnostics| diagnostics.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn set_diagnostics(&mut self, buffer: &Buffer, diagnostics: Vec<Diagnostic>) -> Result<()> {
+        self.diagnostics.insert(buffer_key(buffer)?, diagnostics);
+
+        Ok(())
+    }
+
+    ///
+    /// Spell checking methods.
+    ///
+
+    /// The dictionary used to underline misspelled words, if spell checking is enabled.
+    pub fn dictionary(&self) -> Option<&Dictionary> {
+        self.dictionary.as_ref()
+    }
+
+    /// Enables spell checking with the dictionary, or disables it if there isn't one.
+    pub fn set_dictionary(&mut self, dictionary: Option<Dictionary>) {
+        self.dictionary = dictionary;
+    }
+
+    ///
+    /// Word highlight methods.
+    ///
+
+    /// Whether occurrences of the word under the cursor are highlighted,
+    /// deferring to the highlight_word_occurrences preference until toggled.
+    pub fn word_highlights(&self) -> bool {
+        self.word_highlights.unwrap_or_else(|| self.preferences.borrow().highlight_word_occurrences())
+    }
+
+    pub fn toggle_word_highlights(&mut self) {
+        self.word_highlights = Some(!self.word_highlights());
+    }
+
+    ///
+    /// Split pane methods.
+    ///
+
+    /// Splits the focused pane in two, both halves displaying the buffer.
+    /// The new pane is placed to the right of (or below) the focused one,
+    /// which keeps focus, starting out with the same cursor and scroll offset.
+    pub fn split_pane(&mut self, buffer: &Buffer, orientation: Orientation) -> Result<()> {
+        let pane = self.pane(buffer)?;
+        match self.panes {
+            Some(ref mut panes) => panes.split(orientation, pane),
+            None => self.panes = Some(PaneTree::new(pane, orientation)),
+        }
+
+        Ok(())
+    }
+
+    /// Moves focus to the nearest pane in the direction, remembering the
+    /// buffer, cursor, and scroll offset of the pane losing focus. The newly
+    /// focused pane is returned, so that its buffer can be selected and its
+    /// cursor restored.
+    pub fn focus_pane(&mut self, buffer: &Buffer, direction: Direction) -> Result<Pane> {
+        let pane = self.pane(buffer)?;
+        let area = self.screen_area();
+        let panes = self.panes.as_mut().ok_or("The view isn't split")?;
+        let focused = panes.focus(direction, area, pane).ok_or("There's no pane in that direction")?;
+
+        Ok(focused)
+    }
+
+    /// Like focus_pane, but moves focus to the pane following the focused one.
+    pub fn focus_next_pane(&mut self, buffer: &Buffer) -> Result<Pane> {
+        let pane = self.pane(buffer)?;
+        let panes = self.panes.as_mut().ok_or("The view isn't split")?;
+        let focused = panes.focus_next(pane).ok_or("Couldn't find the next pane")?;
+
+        Ok(focused)
+    }
+
+    /// Closes the focused pane, returning the pane taking focus. Once a
+    /// single pane remains, the view is no longer split, and that pane
+    /// takes up the entire screen.
+    pub fn close_pane(&mut self) -> Result<Pane> {
+        let focused = self.panes
+            .as_mut()
+            .ok_or("The view isn't split")?
+            .close_focused()
+            .ok_or("Can't close the only pane")?;
+        self.collapse_panes();
+
+        Ok(focused)
+    }
+
+    /// Resizes panes so that those sharing a row (or column) are equally sized.
+    pub fn balance_panes(&mut self) -> Result<()> {
+        self.panes.as_mut().ok_or("The view isn't split")?.balance();
+
+        Ok(())
+    }
+
+    /// Scrolls the buffer to the pane's remembered line offset.
+    pub fn restore_pane(&mut self, buffer: &Buffer, pane: &Pane) -> Result<()> {
+        self.get_region(buffer)?.set_line_offset(pane.line_offset);
+
+        Ok(())
+    }
+
+    /// The focused pane's (one-based) position amongst all of them, and their count, if split.
+    pub fn pane_position(&self) -> Option<(usize, usize)> {
+        self.panes.as_ref().map(|panes| {
+            let (index, count) = panes.focused_index();
+
+            (index + 1, count)
+        })
+    }
+
+    /// The panes other than the focused one, each displaying a copy of its buffer.
+    pub fn unfocused_panes(&self) -> Vec<(usize, Pane)> {
+        self.panes.as_ref().map(|panes| panes.unfocused_panes()).unwrap_or_default()
+    }
+
+    /// The regions occupied by panes and the dividers between them, if split.
+    pub fn pane_layout(&self) -> Option<Layout> {
+        self.panes.as_ref().map(|panes| panes.layout(self.screen_area()))
+    }
+
+    /// The copy of an unfocused pane's buffer to be rendered alongside the current one.
+    pub fn pane_snapshot(&self, id: usize) -> Option<&Snapshot> {
+        self.panes.as_ref().and_then(|panes| panes.snapshot(id))
+    }
+
+    /// Brings the copy of an unfocused pane's buffer, which is rendered
+    /// alongside the current one, up to date. The buffer's only copied again
+    /// if it's changed since the last copy was taken, keeping the render states
+    /// cached for the lines above the change. Without a buffer (i.e. it's been
+    /// closed), the pane is closed.
+    pub fn update_pane_snapshot(&mut self, id: usize, buffer: Option<&Buffer>, cursor: Position) {
+        let buffer = match buffer {
+            Some(buffer) => buffer,
+            None => {
+                if let Some(ref mut panes) = self.panes {
+                    panes.close_pane(id);
+                }
+                self.collapse_panes();
+                return;
+            }
+        };
+        let source = (buffer.id, self.revision(buffer));
+        let panes = match self.panes {
+            Some(ref mut panes) => panes,
+            None => return,
+        };
+
+        let syntax_name = |buffer: &Buffer| buffer.syntax_definition.as_ref().map(|syntax| syntax.name.clone());
+        if let Some(snapshot) = panes.snapshot_mut(id) {
+            if snapshot.source == source && syntax_name(&snapshot.buffer) == syntax_name(buffer) {
+                snapshot.buffer.cursor.move_to(cursor);
+                return;
+            }
+        }
+
+        let data = buffer.data();
+        let mut copy = Buffer::new();
+        copy.insert(data.as_str());
+        copy.path = buffer.path.clone();
+        copy.syntax_definition = buffer.syntax_definition.clone();
+        copy.cursor.move_to(cursor);
+
+        // Later copies of the same buffer only re-render from the first changed line.
+        match panes.snapshot_mut(id) {
+            Some(ref mut snapshot) if snapshot.source.0 == source.0 &&
+                syntax_name(&snapshot.buffer) == syntax_name(buffer) => {
+                let changed_line = first_changed_line(&snapshot.buffer.data(), &data);
+                snapshot.render_cache.borrow_mut().invalidate_from(changed_line);
+                snapshot.buffer = copy;
+                snapshot.source = source;
+            }
+            _ => panes.set_snapshot(id, Snapshot::new(copy, source)),
+        }
+    }
+
+    /// The region occupied by the focused pane, which is the entire screen
+    /// (less the status line) when the view isn't split. It's derived from
+    /// the terminal's current size, so that panes follow resizes.
+    pub fn pane_region(&self) -> Region {
+        let area = self.screen_area();
+
+        self.panes.as_ref().map(|panes| panes.focused_region(area)).unwrap_or(area)
+    }
+
+    fn screen_area(&self) -> Region {
+        Region {
+            line: 0,
+            offset: 0,
+            width: self.terminal.width(),
+            height: self.terminal.height().checked_sub(1).unwrap_or(0),
+        }
+    }
+
+    /// Falls back to a single, full-screen view once only one pane remains.
+    fn collapse_panes(&mut self) {
+        if self.panes.as_ref().map(|panes| panes.single()).unwrap_or(false) {
+            self.panes = None;
+        }
+    }
+
+    fn pane(&mut self, buffer: &Buffer) -> Result<Pane> {
+        Ok(Pane {
+            buffer_id: buffer_key(buffer)?,
+            cursor: *buffer.cursor,
+            line_offset: self.get_region(buffer)?.line_offset(),
+        })
+    }
+
+    ///
+    /// Read-only delegation methods.
+    ///
+
+    /// Whether the buffer's commands::buffer edits are refused.
+    pub fn read_only(&self, buffer: &Buffer) -> bool {
+        buffer_key(buffer)
+            .map(|key| self.read_only.contains(&key))
+            .unwrap_or(false)
+    }
+
+    pub fn set_read_only(&mut self, buffer: &Buffer, read_only: bool) -> Result<()> {
+        let key = buffer_key(buffer)?;
+        if read_only {
+            self.read_only.insert(key);
+        } else {
+            self.read_only.remove(&key);
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Line ending delegation methods.
+    ///
+
+    /// Returns the line endings detected when the buffer was loaded
+    /// (or last converted), if it's been initialized.
+    pub fn line_endings(&self, buffer: &Buffer) -> Option<LineEndings> {
+        buffer_key(buffer)
+            .ok()
+            .and_then(|key| self.line_endings.get(&key))
+            .cloned()
+    }
+
+    pub fn set_line_endings(&mut self, buffer: &Buffer, endings: LineEndings) -> Result<()> {
+        self.line_endings.insert(buffer_key(buffer)?, endings);
+
+        Ok(())
+    }
+
+    ///
+    /// Encoding delegation methods.
+    ///
+
+    /// Returns the encoding of the buffer's file, unless it's UTF-8.
+    pub fn encoding(&self, buffer: &Buffer) -> Option<SourceEncoding> {
+        buffer_key(buffer)
+            .ok()
+            .and_then(|key| self.encodings.get(&key))
+            .cloned()
+    }
+
+    pub fn set_encoding(&mut self, buffer: &Buffer, encoding: Option<SourceEncoding>) -> Result<()> {
+        match encoding {
+            Some(encoding) => self.encodings.insert(buffer_key(buffer)?, encoding),
+            None => self.encodings.remove(&buffer_key(buffer)?),
+        };
 
         Ok(())
     }
@@ -128,12 +899,21 @@ impl View {
     // Tries to fetch a scrollable region for the specified buffer,
     // inserting (and returning a reference to) a new one if not.
     fn get_region(&mut self, buffer: &Buffer) -> Result<&mut ScrollableRegion> {
-        Ok(self.scrollable_regions
+        let wrap_indent = wrap_indent_tab_width(&self.preferences.borrow(), buffer);
+        let size = self.panes.as_ref().map(|_| {
+            let region = self.pane_region();
+
+            (region.width, region.height)
+        });
+        let region = self.scrollable_regions
             .entry(buffer_key(buffer)?)
             .or_insert(
                 ScrollableRegion::new(self.terminal.clone())
-            )
-        )
+            );
+        region.set_wrap_indent(wrap_indent);
+        region.set_size(size);
+
+        Ok(region)
     }
 
     fn get_render_cache(&self, buffer: &Buffer) -> Result<&Rc<RefCell<HashMap<usize, RenderState>>>> {
@@ -144,6 +924,30 @@ impl View {
         Ok(cache)
     }
 
+    fn get_bookmarks(&self, buffer: &Buffer) -> Result<&Rc<RefCell<Bookmarks>>> {
+        let bookmarks = self.bookmarks
+            .get(&buffer_key(buffer)?)
+            .ok_or("Buffer not properly initialized (bookmarks not present).")?;
+
+        Ok(bookmarks)
+    }
+
+    fn get_folds(&self, buffer: &Buffer) -> Result<&Rc<RefCell<Folds>>> {
+        let folds = self.folds
+            .get(&buffer_key(buffer)?)
+            .ok_or("Buffer not properly initialized (folds not present).")?;
+
+        Ok(folds)
+    }
+
+    fn get_undo_depth(&self, buffer: &Buffer) -> Result<&Rc<RefCell<UndoDepth>>> {
+        let depth = self.undo_depths
+            .get(&buffer_key(buffer)?)
+            .ok_or("Buffer not properly initialized (undo depth not present).")?;
+
+        Ok(depth)
+    }
+
     pub fn suspend(&mut self) {
         let _ = self.event_listener_killswitch.send(());
         self.terminal.suspend();
@@ -165,10 +969,73 @@ impl View {
             render_cache.clone()
         );
 
-        // Wire up the buffer's change callback to invalidate the render cache.
+        // Track bookmarks, so that they can be adjusted as the buffer changes.
+        let bookmarks = Rc::new(RefCell::new(Bookmarks::new()));
+        self.bookmarks.insert(
+            buffer_key(buffer)?,
+            bookmarks.clone()
+        );
+
+        // Track folds, so that they can be adjusted as the buffer changes.
+        let folds = Rc::new(RefCell::new(Folds::new(buffer.line_count())));
+        self.folds.insert(
+            buffer_key(buffer)?,
+            folds.clone()
+        );
+
+        // Track the depth of the buffer's undo history, for display purposes.
+        let undo_depth = Rc::new(RefCell::new(UndoDepth::new()));
+        self.undo_depths.insert(
+            buffer_key(buffer)?,
+            undo_depth.clone()
+        );
+
+        // Cache the buffer's text stats until it changes, since they span its entire content.
+        let text_stats = Rc::new(RefCell::new(None));
+        self.text_stats.insert(
+            buffer_key(buffer)?,
+            text_stats.clone()
+        );
+
+        // Likewise for the ranges of its merge conflict markers, which are highlighted.
+        let conflict_markers = Rc::new(RefCell::new(None));
+        self.conflict_markers.insert(
+            buffer_key(buffer)?,
+            conflict_markers.clone()
+        );
+
+        // Count changes, so that content derived from the buffer can be rebuilt.
+        let revision = Rc::new(RefCell::new(0));
+        self.revisions.insert(
+            buffer_key(buffer)?,
+            revision.clone()
+        );
+
+        // Remember the buffer's line endings, so that they can be preserved.
+        self.line_endings.insert(
+            buffer_key(buffer)?,
+            line_ending::detect(&buffer.data())
+        );
+
+        // Files that can't be written to are opened read-only.
+        let writable = buffer.path
+            .as_ref()
+            .and_then(|path| fs::metadata(path).ok())
+            .map(|metadata| !metadata.permissions().readonly())
+            .unwrap_or(true);
+        self.set_read_only(buffer, !writable)?;
+
+        // Wire up the buffer's change callback to invalidate the render cache, text stats, and conflict markers,
+        // and record the change against the buffer's bookmarks, folds, undo depth, and revision.
         buffer.change_callback = Some(
             Box::new(move |change_position| {
                 render_cache.borrow_mut().invalidate_from(change_position.line);
+                bookmarks.borrow_mut().record_change();
+                folds.borrow_mut().record_change(change_position);
+                undo_depth.borrow_mut().record_change();
+                text_stats.borrow_mut().take();
+                conflict_markers.borrow_mut().take();
+                *revision.borrow_mut() += 1;
             })
         );
 
@@ -186,11 +1053,56 @@ fn buffer_key(buffer: &Buffer) -> Result<usize> {
     buffer.id.ok_or_else(|| Error::from("Buffer ID doesn't exist"))
 }
 
+/// The first line at which the content differs from its previous version.
+fn first_changed_line(previous: &str, current: &str) -> usize {
+    previous.bytes()
+        .zip(current.bytes())
+        .take_while(|(previous, current)| previous == current)
+        .filter(|&(byte, _)| byte == b'\n')
+        .count()
+}
+
+/// The tab width used to measure the indentation of wrapped lines'
+/// continuation rows, if the wrap_indent preference is enabled.
+fn wrap_indent_tab_width(preferences: &Preferences, buffer: &Buffer) -> Option<usize> {
+    if preferences.wrap_indent() {
+        Some(preferences.tab_width(buffer.path.as_ref()))
+    } else {
+        None
+    }
+}
+
+/// The screen row and (exclusive) end column of each of the line's
+/// graphemes, relative to the start of the line's content, laid out
+/// the way the buffer renderer prints them. Continuation rows start
+/// at the specified indentation.
+fn layout_line(line: &str, width: usize, wrapping: bool, tab_width: usize, indent: usize) -> Vec<(usize, usize)> {
+    let tab_width = tab_width.max(1);
+    let mut row = 0;
+    let mut column = 0;
+
+    line.graphemes(true)
+        .map(|grapheme| {
+            if wrapping && column == width {
+                row += 1;
+                column = indent + 1;
+            } else if grapheme == "\t" {
+                column = ((column / tab_width + 1) * tab_width).min(width);
+            } else {
+                column += 1;
+            }
+
+            (row, column)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use scribe::{Buffer, Workspace};
-    use super::View;
+    use super::{Orientation, View};
     use crate::models::application::Preferences;
+    use crate::util::line_ending::{LineEnding, LineEndings};
     use scribe::buffer::Position;
     use std::cell::RefCell;
     use std::path::{Path, PathBuf};
@@ -199,6 +1111,41 @@ mod tests {
     use syntect::highlighting::{Highlighter, ThemeSet};
     use crate::view::buffer::RenderState;
 
+    #[test]
+    fn buffer_position_accounts_for_gutter_wrapping_and_tabs() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp\nabcdefghij\n\tb");
+        let mut position = |line, offset| view.buffer_position(&buffer, &Position{ line, offset }).unwrap();
+
+        // The test terminal is 10x10, and the gutter 4 columns wide.
+        assert_eq!(position(0, 4), Some(Position{ line: 0, offset: 0 }));
+        assert_eq!(position(0, 9), Some(Position{ line: 0, offset: 3 }));
+        assert_eq!(position(1, 5), Some(Position{ line: 1, offset: 1 }));
+        assert_eq!(position(2, 4), Some(Position{ line: 1, offset: 6 }));
+        assert_eq!(position(2, 9), Some(Position{ line: 1, offset: 10 }));
+        assert_eq!(position(3, 5), Some(Position{ line: 2, offset: 0 }));
+        assert_eq!(position(3, 6), Some(Position{ line: 2, offset: 1 }));
+    }
+
+    #[test]
+    fn buffer_position_ignores_gutter_status_line_and_empty_space() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp");
+        let mut position = |line, offset| view.buffer_position(&buffer, &Position{ line, offset }).unwrap();
+
+        assert_eq!(position(0, 2), None);
+        assert_eq!(position(1, 4), None);
+        assert_eq!(position(9, 4), None);
+    }
+
     #[test]
     fn scroll_down_prevents_scrolling_completely_beyond_buffer() {
         let preferences = Rc::new(RefCell::new(Preferences::new(None)));
@@ -252,6 +1199,38 @@ mod tests {
         assert!(view.render_caches.get(&buffer.id.unwrap()).is_some());
     }
 
+    #[test]
+    fn revision_counts_buffer_changes() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+
+        assert_eq!(view.revision(&buffer), 0);
+        view.initialize_buffer(&mut buffer).unwrap();
+        buffer.insert("amp");
+        buffer.insert("editor");
+        assert_eq!(view.revision(&buffer), 2);
+    }
+
+    #[test]
+    fn initialize_buffer_detects_line_endings() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp\r\neditor\r\n");
+
+        assert!(view.line_endings(&buffer).is_none());
+        view.initialize_buffer(&mut buffer).unwrap();
+        assert_eq!(
+            view.line_endings(&buffer),
+            Some(LineEndings { ending: LineEnding::Crlf, mixed: false })
+        );
+    }
+
     #[test]
     fn initialize_buffer_sets_change_callback_to_clear_render_cache() {
         let preferences = Rc::new(RefCell::new(Preferences::new(None)));
@@ -307,5 +1286,97 @@ mod tests {
             vec![&0]
         );
     }
-}
 
+    #[test]
+    fn bookmarks_follow_their_lines_as_the_buffer_changes() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp\neditor\n");
+        view.initialize_buffer(&mut buffer).unwrap();
+
+        // Bookmark the second line, and then insert a line above it.
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        view.toggle_bookmark(&buffer).unwrap();
+        buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+        buffer.insert("\n");
+
+        assert_eq!(view.bookmarks(&buffer).unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn undo_depth_tracks_grouped_changes_undo_and_redo() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        view.initialize_buffer(&mut buffer).unwrap();
+
+        buffer.insert("amp");
+        view.start_undo_group(&mut buffer).unwrap();
+        buffer.insert("a");
+        buffer.insert("b");
+        view.end_undo_group(&mut buffer).unwrap();
+        assert_eq!(view.undo_depth(&buffer).unwrap(), (2, 0));
+
+        view.undo(&mut buffer).unwrap();
+        assert_eq!(buffer.data(), "amp");
+        assert_eq!(view.undo_depth(&buffer).unwrap(), (1, 1));
+
+        view.redo(&mut buffer).unwrap();
+        assert_eq!(view.undo_depth(&buffer).unwrap(), (2, 0));
+    }
+
+    #[test]
+    fn conflict_markers_are_rebuilt_once_the_buffer_changes() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp\n");
+        view.initialize_buffer(&mut buffer).unwrap();
+        assert!(view.conflict_markers(&buffer).is_empty());
+
+        buffer.insert("<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n");
+        assert_eq!(
+            view.conflict_markers(&buffer).iter().map(|range| range.start().line).collect::<Vec<usize>>(),
+            vec![0, 2, 4]
+        );
+    }
+
+    #[test]
+    fn pane_snapshots_are_reused_until_their_buffers_change() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp");
+        view.initialize_buffer(&mut buffer).unwrap();
+        view.split_pane(&buffer, Orientation::Vertical).unwrap();
+        let (id, pane) = view.unfocused_panes().pop().unwrap();
+
+        view.update_pane_snapshot(id, Some(&buffer), pane.cursor);
+        let render_cache = view.pane_snapshot(id).unwrap().render_cache.clone();
+        view.update_pane_snapshot(id, Some(&buffer), pane.cursor);
+        assert!(Rc::ptr_eq(&render_cache, &view.pane_snapshot(id).unwrap().render_cache));
+
+        // Changes are copied, keeping the render states above them.
+        buffer.insert("editor ");
+        view.update_pane_snapshot(id, Some(&buffer), pane.cursor);
+        let snapshot = view.pane_snapshot(id).unwrap();
+        assert!(Rc::ptr_eq(&render_cache, &snapshot.render_cache));
+        assert_eq!(snapshot.buffer.data(), "editor amp");
+    }
+
+    #[test]
+    fn first_changed_line_counts_the_lines_before_the_first_difference() {
+        assert_eq!(super::first_changed_line("amp\neditor\n", "amp\neditor\n"), 2);
+        assert_eq!(super::first_changed_line("amp\neditor\n", "amp\nedit\n"), 1);
+        assert_eq!(super::first_changed_line("amp\neditor\n", "vim\neditor\n"), 0);
+    }
+}
@@ -0,0 +1,473 @@
+use crate::view::buffer::RenderState;
+use scribe::buffer::{Buffer, Position};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem;
+use std::rc::Rc;
+
+/// How a split arranges its halves: vertical splits place them side by side,
+/// separated by a divider column, and horizontal splits stack them,
+/// separated by a divider row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// A rectangular area of the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub line: usize,
+    pub offset: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// A buffer displayed in a pane, along with the cursor
+/// and scroll offset it had when the pane was last focused.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pane {
+    pub buffer_id: usize,
+    pub cursor: Position,
+    pub line_offset: usize,
+}
+
+/// The screen regions occupied by panes (in order) and the dividers between them.
+#[derive(Debug, PartialEq)]
+pub struct Layout {
+    pub focused: usize,
+    pub panes: Vec<(usize, Region)>,
+    pub dividers: Vec<(Orientation, Region)>,
+}
+
+enum Node {
+    Pane(usize),
+    Split {
+        orientation: Orientation,
+        ratio: f32,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    fn layout(&self, region: Region, layout: &mut Layout) {
+        match *self {
+            Node::Pane(id) => layout.panes.push((id, region)),
+            Node::Split { orientation, ratio, ref first, ref second } => {
+                let (first_region, divider, second_region) = divide(region, orientation, ratio);
+                first.layout(first_region, layout);
+                layout.dividers.push((orientation, divider));
+                second.layout(second_region, layout);
+            }
+        }
+    }
+
+    /// Replaces the pane with a split of it and the new pane.
+    fn split(&mut self, id: usize, orientation: Orientation, new_id: usize) -> bool {
+        let found = match *self {
+            Node::Pane(pane_id) => pane_id == id,
+            Node::Split { ref mut first, ref mut second, .. } => {
+                return first.split(id, orientation, new_id) || second.split(id, orientation, new_id);
+            }
+        };
+
+        if found {
+            *self = Node::Split {
+                orientation,
+                ratio: 0.5,
+                first: Box::new(Node::Pane(id)),
+                second: Box::new(Node::Pane(new_id)),
+            };
+        }
+
+        found
+    }
+
+    /// Removes the pane, replacing its parent split with the pane's sibling,
+    /// whose first pane is returned. The root pane can't be removed.
+    fn remove(&mut self, id: usize) -> Option<usize> {
+        let sibling = match *self {
+            Node::Pane(_) => return None,
+            Node::Split { ref mut first, ref mut second, .. } => {
+                if first.is_pane(id) {
+                    mem::replace(second, Box::new(Node::Pane(id)))
+                } else if second.is_pane(id) {
+                    mem::replace(first, Box::new(Node::Pane(id)))
+                } else {
+                    return first.remove(id).or_else(|| second.remove(id));
+                }
+            }
+        };
+        let sibling_pane = sibling.first_pane();
+        *self = *sibling;
+
+        Some(sibling_pane)
+    }
+
+    fn is_pane(&self, id: usize) -> bool {
+        match *self {
+            Node::Pane(pane_id) => pane_id == id,
+            _ => false,
+        }
+    }
+
+    fn first_pane(&self) -> usize {
+        match *self {
+            Node::Pane(id) => id,
+            Node::Split { ref first, .. } => first.first_pane(),
+        }
+    }
+
+    /// The number of panes laid out in a row (or column) with the orientation.
+    fn count(&self, orientation: Orientation) -> usize {
+        match *self {
+            Node::Split { orientation: split_orientation, ref first, ref second, .. }
+                if split_orientation == orientation => {
+                first.count(orientation) + second.count(orientation)
+            }
+            _ => 1,
+        }
+    }
+
+    /// Sizes splits so that panes laid out in a row (or column) are equal.
+    fn balance(&mut self) {
+        if let Node::Split { orientation, ref mut ratio, ref mut first, ref mut second } = *self {
+            first.balance();
+            second.balance();
+
+            let first_count = first.count(orientation) as f32;
+            *ratio = first_count / (first_count + second.count(orientation) as f32);
+        }
+    }
+}
+
+/// A copy of an unfocused pane's buffer, along with the id and revision of
+/// the buffer it was taken from. It's only taken again once that changes,
+/// and its render cache is kept between frames, up to the changed line.
+pub struct Snapshot {
+    pub buffer: Buffer,
+    pub source: (Option<usize>, usize),
+    pub render_cache: Rc<RefCell<HashMap<usize, RenderState>>>,
+}
+
+impl Snapshot {
+    pub fn new(buffer: Buffer, source: (Option<usize>, usize)) -> Snapshot {
+        Snapshot { buffer, source, render_cache: Rc::new(RefCell::new(HashMap::new())) }
+    }
+}
+
+/// A tree of splits, whose leaves are panes, one of which is focused. The
+/// focused pane displays the workspace's current buffer, using its own
+/// cursor and scroll offset, so its stored state is only brought up to date
+/// when it loses focus. Other panes are rendered from copies of their
+/// buffers, refreshed before each frame is presented.
+pub struct PaneTree {
+    root: Node,
+    panes: HashMap<usize, Pane>,
+    snapshots: HashMap<usize, Snapshot>,
+    focused: usize,
+    next_id: usize,
+}
+
+impl PaneTree {
+    /// Splits a full-screen view of the pane in two, both halves starting
+    /// out the same. The first (left or top) pane is focused.
+    pub fn new(pane: Pane, orientation: Orientation) -> PaneTree {
+        let mut tree = PaneTree {
+            root: Node::Pane(0),
+            panes: HashMap::new(),
+            snapshots: HashMap::new(),
+            focused: 0,
+            next_id: 1,
+        };
+        tree.panes.insert(0, pane.clone());
+        tree.split(orientation, pane);
+
+        tree
+    }
+
+    /// Splits the focused pane, placing the new pane to its right (or below it).
+    pub fn split(&mut self, orientation: Orientation, pane: Pane) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.root.split(self.focused, orientation, id);
+        self.panes.insert(id, pane);
+    }
+
+    pub fn layout(&self, area: Region) -> Layout {
+        let mut layout = Layout { focused: self.focused, panes: Vec::new(), dividers: Vec::new() };
+        self.root.layout(area, &mut layout);
+
+        layout
+    }
+
+    pub fn focused_region(&self, area: Region) -> Region {
+        self.layout(area)
+            .panes
+            .into_iter()
+            .find(|&(id, _)| id == self.focused)
+            .map(|(_, region)| region)
+            .unwrap_or(area)
+    }
+
+    /// The focused pane's (zero-based) position amongst
+    /// all of the panes, in layout order, and their count.
+    pub fn focused_index(&self) -> (usize, usize) {
+        let ids = self.ids();
+        let index = ids.iter().position(|&id| id == self.focused).unwrap_or(0);
+
+        (index, ids.len())
+    }
+
+    pub fn unfocused_panes(&self) -> Vec<(usize, Pane)> {
+        self.ids()
+            .into_iter()
+            .filter(|&id| id != self.focused)
+            .filter_map(|id| self.panes.get(&id).map(|pane| (id, pane.clone())))
+            .collect()
+    }
+
+    pub fn snapshot(&self, id: usize) -> Option<&Snapshot> {
+        self.snapshots.get(&id)
+    }
+
+    pub fn snapshot_mut(&mut self, id: usize) -> Option<&mut Snapshot> {
+        self.snapshots.get_mut(&id)
+    }
+
+    pub fn set_snapshot(&mut self, id: usize, snapshot: Snapshot) {
+        self.snapshots.insert(id, snapshot);
+    }
+
+    pub fn snapshots(&self) -> impl Iterator<Item = &Snapshot> {
+        self.snapshots.values()
+    }
+
+    /// Moves focus to the nearest pane in the direction, storing the focused
+    /// pane's current state. Returns the newly focused pane, if there's one
+    /// in that direction.
+    pub fn focus(&mut self, direction: Direction, area: Region, current: Pane) -> Option<Pane> {
+        let layout = self.layout(area);
+        let focused_region = self.focused_region(area);
+        let (_, target) = layout.panes
+            .iter()
+            .filter(|&&(id, _)| id != self.focused)
+            .filter_map(|&(id, region)| distance(focused_region, region, direction).map(|d| (d, id)))
+            .min()?;
+
+        self.focus_pane(target, current)
+    }
+
+    /// Moves focus to the pane following the focused one
+    /// (in layout order), wrapping around to the first.
+    pub fn focus_next(&mut self, current: Pane) -> Option<Pane> {
+        let ids = self.ids();
+        let index = ids.iter().position(|&id| id == self.focused)?;
+        let target = ids[(index + 1) % ids.len()];
+
+        self.focus_pane(target, current)
+    }
+
+    /// Closes the focused pane, moving focus to the (first pane of the)
+    /// sibling taking its place. Returns the newly focused pane, or
+    /// None if the focused pane is the only one, leaving it open.
+    pub fn close_focused(&mut self) -> Option<Pane> {
+        let target = self.close(self.focused)?;
+        self.focused = target;
+        self.snapshots.remove(&target);
+
+        self.panes.get(&target).cloned()
+    }
+
+    /// Closes the panes (other than the focused one) showing the buffer.
+    pub fn close_buffer_panes(&mut self, buffer_id: usize) {
+        let ids: Vec<usize> = self.unfocused_panes()
+            .into_iter()
+            .filter(|(_, pane)| pane.buffer_id == buffer_id)
+            .map(|(id, _)| id)
+            .collect();
+        for id in ids {
+            self.close(id);
+        }
+    }
+
+    /// Closes an unfocused pane, whose buffer can no longer be displayed.
+    pub fn close_pane(&mut self, id: usize) {
+        if id != self.focused {
+            self.close(id);
+        }
+    }
+
+    pub fn balance(&mut self) {
+        self.root.balance();
+    }
+
+    /// Whether a single pane remains, at which point the view needn't be split.
+    pub fn single(&self) -> bool {
+        match self.root {
+            Node::Pane(_) => true,
+            _ => false,
+        }
+    }
+
+    fn focus_pane(&mut self, id: usize, current: Pane) -> Option<Pane> {
+        self.panes.insert(self.focused, current);
+        self.focused = id;
+        self.snapshots.remove(&id);
+
+        self.panes.get(&id).cloned()
+    }
+
+    fn close(&mut self, id: usize) -> Option<usize> {
+        let sibling = self.root.remove(id)?;
+        self.panes.remove(&id);
+        self.snapshots.remove(&id);
+
+        Some(sibling)
+    }
+
+    fn ids(&self) -> Vec<usize> {
+        let area = Region { line: 0, offset: 0, width: 0, height: 0 };
+
+        self.layout(area).panes.into_iter().map(|(id, _)| id).collect()
+    }
+}
+
+/// Divides the region in two using the split's ratio, leaving a
+/// single column (or row) between the halves for a divider.
+fn divide(region: Region, orientation: Orientation, ratio: f32) -> (Region, Region, Region) {
+    match orientation {
+        Orientation::Vertical => {
+            let available = region.width.saturating_sub(1);
+            let first_width = ((available as f32 * ratio).round() as usize).min(available);
+
+            (
+                Region { width: first_width, ..region },
+                Region { offset: region.offset + first_width, width: region.width.min(1), ..region },
+                Region { offset: region.offset + first_width + 1, width: available - first_width, ..region },
+            )
+        }
+        Orientation::Horizontal => {
+            let available = region.height.saturating_sub(1);
+            let first_height = ((available as f32 * ratio).round() as usize).min(available);
+
+            (
+                Region { height: first_height, ..region },
+                Region { line: region.line + first_height, height: region.height.min(1), ..region },
+                Region { line: region.line + first_height + 1, height: available - first_height, ..region },
+            )
+        }
+    }
+}
+
+/// How far the region lies in the direction from the focused region, if it
+/// does: the gap between them, followed by how far their starting edges are
+/// offset, so that the nearest and most closely aligned pane is preferred.
+fn distance(focused: Region, region: Region, direction: Direction) -> Option<(usize, usize)> {
+    let shares_rows = region.line < focused.line + focused.height &&
+        focused.line < region.line + region.height;
+    let shares_columns = region.offset < focused.offset + focused.width &&
+        focused.offset < region.offset + region.width;
+
+    match direction {
+        Direction::Left if shares_rows && region.offset + region.width <= focused.offset => {
+            Some((focused.offset - region.offset - region.width, difference(region.line, focused.line)))
+        }
+        Direction::Right if shares_rows && region.offset >= focused.offset + focused.width => {
+            Some((region.offset - focused.offset - focused.width, difference(region.line, focused.line)))
+        }
+        Direction::Up if shares_columns && region.line + region.height <= focused.line => {
+            Some((focused.line - region.line - region.height, difference(region.offset, focused.offset)))
+        }
+        Direction::Down if shares_columns && region.line >= focused.line + focused.height => {
+            Some((region.line - focused.line - focused.height, difference(region.offset, focused.offset)))
+        }
+        _ => None,
+    }
+}
+
+fn difference(a: usize, b: usize) -> usize {
+    if a > b { a - b } else { b - a }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use super::{Direction, Orientation, Pane, PaneTree, Region};
+
+    fn pane(buffer_id: usize) -> Pane {
+        Pane { buffer_id, cursor: Position { line: 0, offset: 0 }, line_offset: 0 }
+    }
+
+    fn area() -> Region {
+        Region { line: 0, offset: 0, width: 81, height: 21 }
+    }
+
+    #[test]
+    fn layout_divides_regions_and_leaves_room_for_dividers() {
+        let mut tree = PaneTree::new(pane(0), Orientation::Vertical);
+        tree.focus_next(pane(0));
+        tree.split(Orientation::Horizontal, pane(1));
+        let layout = tree.layout(area());
+
+        assert_eq!(layout.panes, vec![
+            (0, Region { line: 0, offset: 0, width: 40, height: 21 }),
+            (1, Region { line: 0, offset: 41, width: 40, height: 10 }),
+            (2, Region { line: 11, offset: 41, width: 40, height: 10 }),
+        ]);
+        assert_eq!(layout.dividers, vec![
+            (Orientation::Vertical, Region { line: 0, offset: 40, width: 1, height: 21 }),
+            (Orientation::Horizontal, Region { line: 10, offset: 41, width: 40, height: 1 }),
+        ]);
+    }
+
+    #[test]
+    fn balance_sizes_panes_in_a_row_equally() {
+        let mut tree = PaneTree::new(pane(0), Orientation::Vertical);
+        tree.split(Orientation::Vertical, pane(0));
+        tree.balance();
+        let widths: Vec<usize> = tree.layout(area()).panes.iter().map(|(_, region)| region.width).collect();
+
+        // Two of the 81 columns are used by dividers.
+        assert_eq!(widths, vec![26, 26, 27]);
+    }
+
+    #[test]
+    fn focus_moves_to_the_nearest_pane_in_the_direction() {
+        let mut tree = PaneTree::new(pane(0), Orientation::Vertical);
+        tree.focus_next(pane(0));
+        tree.split(Orientation::Horizontal, pane(1));
+        tree.focus(Direction::Down, area(), pane(2));
+
+        assert_eq!(tree.focused_index(), (2, 3));
+        assert_eq!(tree.focus(Direction::Right, area(), pane(1)), None);
+        assert_eq!(tree.focus(Direction::Left, area(), pane(1)), Some(pane(0)));
+        assert_eq!(tree.focused_index(), (0, 3));
+
+        // The left pane borders both of the stacked panes,
+        // and prefers the one aligned with its top edge.
+        assert_eq!(tree.focus(Direction::Right, area(), pane(0)), Some(pane(2)));
+        assert_eq!(tree.focused_index(), (1, 3));
+    }
+
+    #[test]
+    fn close_focused_moves_focus_to_the_sibling_and_keeps_the_last_pane() {
+        let mut tree = PaneTree::new(pane(0), Orientation::Horizontal);
+        tree.split(Orientation::Vertical, pane(1));
+
+        assert_eq!(tree.close_focused(), Some(pane(1)));
+        assert_eq!(tree.focused_index(), (0, 2));
+        assert_eq!(tree.close_focused(), Some(pane(0)));
+        assert!(tree.single());
+        assert_eq!(tree.close_focused(), None);
+    }
+}
@@ -5,39 +5,40 @@ use crate::view::color::{ColorMap, Colors};
 use crate::view::StatusLineData;
 use crate::view::style::Style;
 use crate::view::terminal::{Cell, TerminalBuffer};
-use crate::view::View;
+use crate::view::{Orientation, Region, View};
 use pad::PadStr;
 use scribe::buffer::{Buffer, Position, Range};
 use scribe::util::LineIterator;
 use std::borrow::Cow;
+use std::path::PathBuf;
 use syntect::highlighting::Theme;
 
 pub struct Presenter<'p> {
     cursor_position: Option<Position>,
+    relative_line_numbers: bool,
+    secondary_cursors: Vec<Position>,
+    show_whitespace: bool,
     terminal_buffer: TerminalBuffer<'p>,
     theme: Theme,
+    word_highlights: Vec<Range>,
     pub view: &'p mut View,
 }
 
 impl<'p> Presenter<'p> {
     pub fn new(view: &mut View) -> Result<Presenter> {
-        let theme = {
-            let preferences = view.preferences.borrow();
-            let theme_name = preferences.theme();
-            let theme = view.theme_set.themes
-                .get(theme_name)
-                .or_else(|| view.theme_set.themes.get(application::THEME_DEFAULT))
-                .ok_or_else(|| format!("Couldn't find \"{}\" theme", theme_name))?;
-            theme.clone()
-        };
+        let theme = find_theme(view, None)?;
 
         Ok(Presenter{
             cursor_position: None,
+            relative_line_numbers: false,
+            secondary_cursors: Vec::new(),
+            show_whitespace: false,
             terminal_buffer: TerminalBuffer::new(
                 view.terminal.width(),
                 view.terminal.height(),
             ),
             theme,
+            word_highlights: Vec::new(),
             view
         })
     }
@@ -54,6 +55,11 @@ impl<'p> Presenter<'p> {
         self.terminal_buffer.clear()
     }
 
+    /// The screen position of the cursor, once a buffer has been printed.
+    pub fn cursor_position(&self) -> Option<Position> {
+        self.cursor_position
+    }
+
     pub fn set_cursor(&mut self, position: Option<Position>) {
         self.cursor_position = position;
     }
@@ -71,27 +77,149 @@ impl<'p> Presenter<'p> {
         self.view.terminal.present();
     }
 
+    /// Displays line numbers for subsequently printed buffers relative to their cursor lines.
+    pub fn set_relative_line_numbers(&mut self, relative: bool) {
+        self.relative_line_numbers = relative;
+    }
+
+    /// Draws tabs, trailing spaces, and line endings in subsequently printed buffers.
+    pub fn set_show_whitespace(&mut self, show: bool) {
+        self.show_whitespace = show;
+    }
+
+    /// Displays additional (multiple cursor) carets in subsequently printed buffers.
+    pub fn set_secondary_cursors(&mut self, positions: &[Position]) {
+        self.secondary_cursors = positions.to_vec();
+    }
+
+    /// Highlights occurrences of the word under the cursor in subsequently printed buffers.
+    pub fn set_word_highlights(&mut self, ranges: &[Range]) {
+        self.word_highlights = ranges.to_vec();
+    }
+
     pub fn print_buffer(&mut self, buffer: &Buffer, buffer_data: &'p str, highlights: Option<&[Range]>, lexeme_mapper: Option<&'p mut LexemeMapper>) -> Result<()> {
+        // The focused buffer's filetype may call for a different theme.
+        self.theme = find_theme(self.view, buffer.path.as_ref())?;
+
+        self.print_focused_pane(buffer, buffer_data, highlights, lexeme_mapper)?;
+        self.print_unfocused_panes()
+    }
+
+    fn print_focused_pane(&mut self, buffer: &Buffer, buffer_data: &'p str, highlights: Option<&[Range]>, lexeme_mapper: Option<&'p mut LexemeMapper>) -> Result<()> {
         let scroll_offset = self.view.get_region(buffer)?.line_offset();
         let lines = LineIterator::new(buffer_data);
+        let bookmarks = self.view.bookmarks(buffer)?;
+        let folds = self.view.folds(buffer)?;
+        let line_changes = self.view.line_changes(buffer)?.unwrap_or(&[]);
+        let diagnostics = self.view.diagnostics(buffer);
+        let region = self.view.pane_region();
+        let preferences = self.view.preferences.borrow();
 
-        self.cursor_position = BufferRenderer::new(
+        let mut renderer = BufferRenderer::new(
             buffer,
             highlights,
             scroll_offset,
             &**self.view.terminal,
             &self.theme,
-            &self.view.preferences.borrow(),
+            &preferences,
             self.view.get_render_cache(buffer)?,
             &mut self.terminal_buffer
-        ).render(lines, lexeme_mapper)?;
+        );
+        renderer.set_region(region);
+        renderer.set_bookmarks(&bookmarks);
+        renderer.set_folds(&folds);
+        renderer.set_line_changes(line_changes);
+        renderer.set_diagnostics(diagnostics);
+        if let Some(dictionary) = self.view.dictionary.as_ref() {
+            renderer.set_dictionary(dictionary);
+        }
+        renderer.set_secondary_cursors(&self.secondary_cursors);
+        renderer.set_word_highlights(&self.word_highlights);
+        if self.relative_line_numbers {
+            renderer.set_relative_line_numbers();
+        }
+        if self.show_whitespace {
+            renderer.set_show_whitespace();
+        }
+        self.cursor_position = renderer.render(lines, lexeme_mapper)?;
+
+        Ok(())
+    }
+
+    /// Draws the unfocused panes of a split view from their buffers'
+    /// snapshots, along with the dividers between panes. Snapshots don't
+    /// live as long as the presenter's cells, so they're rendered
+    /// separately and their cells are copied into place. Each snapshot
+    /// keeps its own render cache, so that panes scrolled far into large
+    /// files aren't re-highlighted from the top on every frame.
+    fn print_unfocused_panes(&mut self) -> Result<()> {
+        let layout = match self.view.pane_layout() {
+            Some(layout) => layout,
+            None => return Ok(()),
+        };
+
+        for (id, pane) in self.view.unfocused_panes() {
+            let region = match layout.panes.iter().find(|&&(pane_id, _)| pane_id == id) {
+                Some(&(_, region)) => region,
+                None => continue,
+            };
+            let snapshot = match self.view.pane_snapshot(id) {
+                Some(snapshot) => snapshot,
+                None => continue,
+            };
+            let data = snapshot.buffer.data();
+            let preferences = self.view.preferences.borrow();
+            let mut pane_buffer = TerminalBuffer::new(region.width, region.height);
+            let mut renderer = BufferRenderer::new(
+                &snapshot.buffer,
+                None,
+                pane.line_offset,
+                &**self.view.terminal,
+                &self.theme,
+                &preferences,
+                &snapshot.render_cache,
+                &mut pane_buffer
+            );
+            renderer.set_region(Region{ line: 0, offset: 0, ..region });
+            renderer.render(LineIterator::new(&data), None)?;
+
+            for (position, cell) in pane_buffer.iter() {
+                self.terminal_buffer.set_cell(
+                    Position{ line: position.line + region.line, offset: position.offset + region.offset },
+                    Cell{ content: Cow::Owned(cell.content.to_string()), style: cell.style, colors: cell.colors }
+                );
+            }
+        }
+
+        for (orientation, region) in layout.dividers {
+            let divider = match orientation {
+                Orientation::Vertical => "│",
+                Orientation::Horizontal => "─",
+            };
+            for line in region.line..region.line + region.height {
+                for offset in region.offset..region.offset + region.width {
+                    self.print(&Position{ line, offset }, Style::Default, Colors::Focused, divider);
+                }
+            }
+        }
 
         Ok(())
     }
 
     pub fn print_status_line(&mut self, entries: &[StatusLineData]) {
+        self.print_status_line_with_mode(entries, Some(0));
+    }
+
+    /// Prints the status line, applying any user-defined colors for the
+    /// current mode to the mode entry at the specified index, if present.
+    pub fn print_status_line_with_mode(&mut self, entries: &[StatusLineData], mode_index: Option<usize>) {
         let line = self.view.terminal.height() - 1;
 
+        // The mode entry uses user-defined colors for the current mode, if any.
+        let mode_colors = self.view.mode_str.and_then(|mode|
+            self.view.preferences.borrow().status_line_mode_colors(mode)
+        );
+
         entries.iter().enumerate().fold(0, |offset, (index, element)| {
             let content = match entries.len() {
                 1 => {
@@ -101,7 +229,7 @@ impl<'p> Presenter<'p> {
                 2 => {
                     if index == entries.len() - 1 {
                         // Expand the last element to fill the remaining width.
-                        element.content.pad_to_width(self.view.terminal.width() - offset)
+                        element.content.pad_to_width(self.view.terminal.width().saturating_sub(offset))
                     } else {
                         element.content.clone()
                     }
@@ -109,7 +237,9 @@ impl<'p> Presenter<'p> {
                 _ => {
                     if index == entries.len() - 2 {
                         // Before-last element extends to fill unused space.
-                        element.content.pad_to_width(self.view.terminal.width() - offset - entries[index+1].content.len())
+                        element.content.pad_to_width(
+                            self.view.terminal.width().saturating_sub(offset + entries[index+1].content.len())
+                        )
                     } else {
                         element.content.clone()
                     }
@@ -119,10 +249,14 @@ impl<'p> Presenter<'p> {
             // Update the tracked offset.
             let updated_offset = offset + content.len();
 
+            let colors = match mode_colors {
+                Some(colors) if Some(index) == mode_index => colors,
+                _ => element.colors,
+            };
             self.print(
                 &Position{ line, offset },
                 element.style,
-                element.colors,
+                colors,
                 content
             );
 
@@ -140,6 +274,19 @@ impl<'p> Presenter<'p> {
     }
 }
 
+/// The theme for the path (or the global one, without a path),
+/// falling back to the default if it can't be found.
+fn find_theme(view: &View, path: Option<&PathBuf>) -> Result<Theme> {
+    let preferences = view.preferences.borrow();
+    let theme_name = preferences.theme_for(path);
+    let theme = view.theme_set.themes
+        .get(theme_name)
+        .or_else(|| view.theme_set.themes.get(application::THEME_DEFAULT))
+        .ok_or_else(|| format!("Couldn't find \"{}\" theme", theme_name))?;
+
+    Ok(theme.clone())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::models::application::Preferences;
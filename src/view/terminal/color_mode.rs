@@ -0,0 +1,177 @@
+use crate::view::{Colors, RGBColor};
+use std::env;
+
+// The xterm 256-color palette's RGB cube levels.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// The xterm defaults for the 16 standard ANSI colors.
+const ANSI_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0),
+    (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+    (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+/// The range of colors a terminal can display. Theme colors are
+/// sent as-is to true-color terminals, and approximated otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMode {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorMode {
+    /// Determines the terminal's color support using the environment.
+    pub fn detect() -> ColorMode {
+        let variable = |name| env::var(name).ok();
+
+        ColorMode::from_environment(variable("COLORMODE"), variable("COLORTERM"), variable("TERM"))
+    }
+
+    /// Terminals advertise 24-bit color support through COLORTERM (which
+    /// can be overridden using COLORMODE), and 256-color support via TERM.
+    fn from_environment(color_mode: Option<String>, color_term: Option<String>, term: Option<String>) -> ColorMode {
+        let advertised_mode = color_mode.or(color_term).unwrap_or_default();
+        match advertised_mode.to_lowercase().as_str() {
+            "truecolor" | "24bit" => return ColorMode::TrueColor,
+            "256" | "256color" => return ColorMode::Ansi256,
+            "16" | "16color" => return ColorMode::Ansi16,
+            _ => (),
+        }
+
+        if term.map(|term| term.contains("256color")).unwrap_or(false) {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+
+    /// The escape sequence used to switch to the specified colors, if any.
+    pub fn escape_sequence(self, colors: Colors) -> Option<String> {
+        match colors {
+            Colors::Default => Some(String::from("\x1b[39m\x1b[49m")),
+            Colors::Custom(fg, bg) => Some(format!("{}{}", self.foreground(fg), self.background(bg))),
+            Colors::CustomForeground(fg) => Some(format!("{}\x1b[49m", self.foreground(fg))),
+            _ => None,
+        }
+    }
+
+    fn foreground(self, color: RGBColor) -> String {
+        let RGBColor(r, g, b) = color;
+        match self {
+            ColorMode::TrueColor => format!("\x1b[38;2;{};{};{}m", r, g, b),
+            ColorMode::Ansi256 => format!("\x1b[38;5;{}m", ansi_256(color)),
+            ColorMode::Ansi16 => {
+                let index = ansi_16(color);
+                format!("\x1b[{}m", if index < 8 { 30 + index } else { 82 + index })
+            }
+        }
+    }
+
+    fn background(self, color: RGBColor) -> String {
+        let RGBColor(r, g, b) = color;
+        match self {
+            ColorMode::TrueColor => format!("\x1b[48;2;{};{};{}m", r, g, b),
+            ColorMode::Ansi256 => format!("\x1b[48;5;{}m", ansi_256(color)),
+            ColorMode::Ansi16 => {
+                let index = ansi_16(color);
+                format!("\x1b[{}m", if index < 8 { 40 + index } else { 92 + index })
+            }
+        }
+    }
+}
+
+/// Finds the closest color in the xterm 256-color palette,
+/// using either its RGB cube or its grayscale ramp.
+fn ansi_256(color: RGBColor) -> u8 {
+    let RGBColor(r, g, b) = color;
+    let cube_index = |value: u8| -> usize {
+        match value {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => ((value - 35) / 40) as usize,
+        }
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube_color = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+    let average = (r as usize + g as usize + b as usize) / 3;
+    let gray_index = if average > 238 { 23 } else { average.saturating_sub(3) / 10 };
+    let gray_level = (8 + gray_index * 10) as u8;
+
+    if distance((r, g, b), (gray_level, gray_level, gray_level)) < distance((r, g, b), cube_color) {
+        232 + gray_index as u8
+    } else {
+        16 + (36 * ri + 6 * gi + bi) as u8
+    }
+}
+
+/// Finds the closest of the 16 standard ANSI colors.
+fn ansi_16(color: RGBColor) -> u8 {
+    let RGBColor(r, g, b) = color;
+
+    (0..ANSI_COLORS.len())
+        .min_by_key(|&index| distance((r, g, b), ANSI_COLORS[index]))
+        .unwrap_or(0) as u8
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let component = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2) as u32;
+
+    component(a.0, b.0) + component(a.1, b.1) + component(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorMode, ansi_16, ansi_256};
+    use crate::view::{Colors, RGBColor};
+
+    fn mode(color_mode: Option<&str>, color_term: Option<&str>, term: Option<&str>) -> ColorMode {
+        ColorMode::from_environment(
+            color_mode.map(String::from),
+            color_term.map(String::from),
+            term.map(String::from)
+        )
+    }
+
+    #[test]
+    fn from_environment_detects_advertised_color_support() {
+        assert_eq!(mode(None, Some("truecolor"), Some("xterm")), ColorMode::TrueColor);
+        assert_eq!(mode(None, Some("24bit"), None), ColorMode::TrueColor);
+        assert_eq!(mode(None, None, Some("xterm-256color")), ColorMode::Ansi256);
+        assert_eq!(mode(None, None, Some("xterm")), ColorMode::Ansi16);
+        assert_eq!(mode(Some("256"), Some("truecolor"), None), ColorMode::Ansi256);
+    }
+
+    #[test]
+    fn escape_sequence_uses_rgb_values_directly_in_true_color_mode() {
+        let colors = Colors::Custom(RGBColor(253, 246, 227), RGBColor(0, 43, 54));
+
+        assert_eq!(
+            ColorMode::TrueColor.escape_sequence(colors),
+            Some(String::from("\x1b[38;2;253;246;227m\x1b[48;2;0;43;54m"))
+        );
+    }
+
+    #[test]
+    fn escape_sequence_approximates_colors_for_limited_terminals() {
+        let colors = Colors::CustomForeground(RGBColor(255, 0, 0));
+
+        assert_eq!(ColorMode::Ansi256.escape_sequence(colors), Some(String::from("\x1b[38;5;196m\x1b[49m")));
+        assert_eq!(ColorMode::Ansi16.escape_sequence(colors), Some(String::from("\x1b[91m\x1b[49m")));
+    }
+
+    #[test]
+    fn ansi_256_prefers_the_grayscale_ramp_for_grays() {
+        assert_eq!(ansi_256(RGBColor(128, 128, 128)), 244);
+        assert_eq!(ansi_256(RGBColor(0, 0, 0)), 16);
+        assert_eq!(ansi_256(RGBColor(0, 135, 255)), 33);
+    }
+
+    #[test]
+    fn ansi_16_finds_the_closest_standard_color() {
+        assert_eq!(ansi_16(RGBColor(0, 0, 200)), 4);
+        assert_eq!(ansi_16(RGBColor(250, 250, 250)), 15);
+    }
+}
@@ -1,6 +1,7 @@
 mod buffer;
 mod buffer_iterator;
 mod cell;
+mod color_mode;
 mod termion_terminal;
 
 #[cfg(any(test, feature = "bench"))]
@@ -15,6 +16,7 @@ use std::sync::Arc;
 pub use self::buffer::TerminalBuffer;
 pub use self::buffer_iterator::TerminalBufferIterator;
 pub use self::cell::Cell;
+pub use self::color_mode::ColorMode;
 pub use self::termion_terminal::TermionTerminal;
 
 #[cfg(any(test, feature = "bench"))]
@@ -29,6 +31,7 @@ pub trait Terminal {
     fn set_cursor(&self, _: Option<Position>);
     fn print<'a>(&self, _: &Position, _: Style, _: Colors, _: &str);
     fn suspend(&self);
+    fn set_mouse_reporting(&self, _: bool);
 }
 
 #[cfg(not(any(test, feature = "bench")))]
@@ -4,13 +4,12 @@ extern crate termion;
 use crate::errors::*;
 use mio::{Events, Poll, PollOpt, Ready, Token};
 use mio::unix::EventedFd;
-use super::Terminal;
+use super::{ColorMode, Terminal};
 use std::io::Stdout;
 use std::os::unix::io::AsRawFd;
 use scribe::buffer::{Distance, Position};
-use self::termion::color::{Bg, Fg};
-use self::termion::{color, cursor};
-use self::termion::input::{Keys, TermRead};
+use self::termion::cursor;
+use self::termion::input::{Events as TermionEvents, TermRead};
 use self::termion::raw::{IntoRawMode, RawTerminal};
 use self::termion::style;
 use std::io::{BufWriter, Stdin, stdin, stdout, Write};
@@ -22,21 +21,29 @@ use crate::view::{Colors, Style};
 use unicode_segmentation::UnicodeSegmentation;
 use signal_hook::iterator::Signals;
 
-use self::termion::event::Key as TermionKey;
-use crate::input::Key;
+use self::termion::event::{Event as TermionEvent, Key as TermionKey};
+use self::termion::event::{MouseButton, MouseEvent as TermionMouseEvent};
+use crate::input::{Key, MouseEvent};
 use crate::models::application::Event;
 
 const STDIN_INPUT: Token = Token(0);
 const RESIZE: Token = Token(1);
 
+// Enables (and disables) reporting of button presses, drags, and releases,
+// using the SGR and urxvt extended coordinate formats where supported.
+const ENABLE_MOUSE_REPORTING: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h";
+const DISABLE_MOUSE_REPORTING: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
 pub struct TermionTerminal {
     event_listener: Poll,
     signals: Signals,
-    input: Mutex<Option<Keys<Stdin>>>,
+    input: Mutex<Option<TermionEvents<Stdin>>>,
     output: Mutex<Option<BufWriter<RawTerminal<Stdout>>>>,
     current_style: Mutex<Option<Style>>,
     current_colors: Mutex<Option<Colors>>,
     current_position: Mutex<Option<Position>>,
+    color_mode: ColorMode,
+    mouse_reporting: Mutex<bool>,
 }
 
 impl TermionTerminal {
@@ -47,11 +54,13 @@ impl TermionTerminal {
         Ok(TermionTerminal {
             event_listener,
             signals,
-            input: Mutex::new(Some(stdin().keys())),
+            input: Mutex::new(Some(stdin().events())),
             output: Mutex::new(Some(create_output_instance())),
             current_style: Mutex::new(None),
             current_colors: Mutex::new(None),
             current_position: Mutex::new(None),
+            color_mode: ColorMode::detect(),
+            mouse_reporting: Mutex::new(false),
         })
     }
 
@@ -73,13 +82,10 @@ impl TermionTerminal {
 
                             // Resetting styles unfortunately clears active colors, too.
                             if let Ok(color_guard) = self.current_colors.lock() {
-                                if let Some(ref current_colors) = *color_guard {
-                                    match *current_colors {
-                                        Colors::Default => { let _ = write!(output, "{}{}", Fg(color::Reset), Bg(color::Reset)); }
-                                        Colors::Custom(fg, bg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(bg)); }
-                                        Colors::CustomForeground(fg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(color::Reset)); }
-                                        _ => (),
-                                    };
+                                if let Some(current_colors) = *color_guard {
+                                    if let Some(sequence) = self.color_mode.escape_sequence(current_colors) {
+                                        let _ = write!(output, "{}", sequence);
+                                    }
                                 }
                             }
                         }
@@ -98,12 +104,9 @@ impl TermionTerminal {
                 // Check if colors have changed.
                 if let Ok(mut color_guard) = self.current_colors.lock() {
                     if Some(&colors) != color_guard.as_ref() {
-                        match colors {
-                            Colors::Default => { let _ = write!(output, "{}{}", Fg(color::Reset), Bg(color::Reset)); }
-                            Colors::Custom(fg, bg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(bg)); }
-                            Colors::CustomForeground(fg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(color::Reset)); }
-                            _ => (),
-                        };
+                        if let Some(sequence) = self.color_mode.escape_sequence(colors) {
+                            let _ = write!(output, "{}", sequence);
+                        }
                     }
 
                     color_guard.replace(colors);
@@ -112,12 +115,23 @@ impl TermionTerminal {
         }
     }
 
+    fn write_mouse_reporting(&self, enabled: bool) {
+        if let Ok(mut guard) = self.output.lock() {
+            if let Some(ref mut output) = *guard {
+                let sequence = if enabled { ENABLE_MOUSE_REPORTING } else { DISABLE_MOUSE_REPORTING };
+                let _ = write!(output, "{}", sequence);
+                let _ = output.flush();
+            }
+        }
+    }
+
     fn restore_cursor(&self) {
         if let Ok(mut guard) = self.output.lock() {
             if let Some(ref mut output) = *guard {
                 let _ = write!(
                     output,
-                    "{}{}{}",
+                    "{}{}{}{}",
+                    DISABLE_MOUSE_REPORTING,
                     termion::cursor::Show,
                     style::Reset,
                     termion::clear::All,
@@ -139,7 +153,11 @@ impl Terminal for TermionTerminal {
                     let mut guard = self.input.lock().ok()?;
                     let input_handle = guard.as_mut()?;
                     let input_data = input_handle.next()?;
-                    let key = input_data.ok()?;
+                    let key = match input_data.ok()? {
+                        TermionEvent::Key(key) => key,
+                        TermionEvent::Mouse(event) => return map_mouse_event(event).map(Event::Mouse),
+                        _ => return None,
+                    };
 
                     match key {
                         TermionKey::Backspace => Some(Event::Key(Key::Backspace)),
@@ -284,7 +302,21 @@ impl Terminal for TermionTerminal {
             guard.replace(create_output_instance());
         }
         if let Ok(mut guard) = self.input.lock() {
-            guard.replace(stdin().keys());
+            guard.replace(stdin().events());
+        }
+        if let Ok(enabled) = self.mouse_reporting.lock() {
+            if *enabled {
+                self.write_mouse_reporting(true);
+            }
+        }
+    }
+
+    fn set_mouse_reporting(&self, enabled: bool) {
+        if let Ok(mut current) = self.mouse_reporting.lock() {
+            if *current != enabled {
+                self.write_mouse_reporting(enabled);
+                *current = enabled;
+            }
         }
     }
 }
@@ -334,11 +366,29 @@ fn create_output_instance() -> BufWriter<RawTerminal<Stdout>> {
     BufWriter::with_capacity(1_048_576, stdout().into_raw_mode().unwrap())
 }
 
+// Termion reports one-based coordinates.
+fn map_mouse_event(event: TermionMouseEvent) -> Option<MouseEvent> {
+    let position = |x: u16, y: u16| Position {
+        line: y.saturating_sub(1) as usize,
+        offset: x.saturating_sub(1) as usize,
+    };
+
+    match event {
+        TermionMouseEvent::Press(MouseButton::Left, x, y) => Some(MouseEvent::Press(position(x, y))),
+        TermionMouseEvent::Press(MouseButton::WheelUp, x, y) => Some(MouseEvent::ScrollUp(position(x, y))),
+        TermionMouseEvent::Press(MouseButton::WheelDown, x, y) => Some(MouseEvent::ScrollDown(position(x, y))),
+        TermionMouseEvent::Press(..) => None,
+        TermionMouseEvent::Hold(x, y) => Some(MouseEvent::Drag(position(x, y))),
+        TermionMouseEvent::Release(x, y) => Some(MouseEvent::Release(position(x, y))),
+    }
+}
+
 fn map_style(style: Style) -> Option<Box<Display>> {
     match style {
         Style::Default => None,
         Style::Bold => Some(Box::new(style::Bold)),
         Style::Inverted => Some(Box::new(style::Invert)),
         Style::Italic => Some(Box::new(style::Italic)),
+        Style::Underlined => Some(Box::new(style::Underline)),
     }
 }
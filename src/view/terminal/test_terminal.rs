@@ -25,6 +25,15 @@ impl TestTerminal {
         }
     }
 
+    // Builds a terminal that never returns a key, leaving the
+    // application to be driven by other events (e.g. a replay).
+    pub fn without_input() -> TestTerminal {
+        TestTerminal {
+            key_sent: Mutex::new(true),
+            ..TestTerminal::new()
+        }
+    }
+
     // Returns a String representation of the printed data.
     pub fn content(&self) -> String {
         let mut data = String::new();
@@ -87,6 +96,7 @@ impl Terminal for TestTerminal {
         *cursor = position;
     }
     fn suspend(&self) { }
+    fn set_mouse_reporting(&self, _: bool) { }
     fn print(&self, position: &Position, _: Style, colors: Colors, content: &str) {
         // Ignore lines beyond visible height.
         if position.line >= self.height() { return; }